@@ -1,4 +1,5 @@
 use std::env;
+use wim_parser::fmt::ToTable;
 use wim_parser::WimParser;
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -29,8 +30,8 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         if parser.is_compressed() { "是" } else { "否" }
     );
 
-    if let Some(compression_type) = parser.get_compression_type() {
-        println!("压缩类型: {compression_type}");
+    if parser.is_compressed() {
+        println!("压缩类型: {}", parser.compression());
     }
 
     // 显示文件头信息
@@ -39,6 +40,14 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         println!("{header}");
     }
 
+    // 以表格形式显示镜像列表
+    println!("\n=== 镜像列表 ===");
+    println!("{}", parser.get_images().table());
+
+    // 以表格形式显示版本分组
+    println!("\n=== 版本分组 ===");
+    println!("{}", parser.edition_summary().table());
+
     // 显示所有镜像信息
     println!("\n=== 镜像详情 ===");
     for (i, image) in parser.get_images().iter().enumerate() {
@@ -0,0 +1,38 @@
+use std::env;
+use wim_parser::WimParser;
+
+/// 带注释的 WIM 文件头十六进制转储
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let args: Vec<String> = env::args().collect();
+    if args.len() != 2 {
+        eprintln!("用法: {} <wim_file_path>", args[0]);
+        std::process::exit(1);
+    }
+
+    let mut parser = WimParser::new(&args[1])?;
+    println!("{:<8} {:<6} {:<48} 字段", "偏移", "长度", "原始字节");
+    for field in parser.header_layout()? {
+        let hex: Vec<String> = field.raw.iter().map(|b| format!("{b:02X}")).collect();
+        println!(
+            "0x{:04X}   {:<6} {:<48} {}",
+            field.offset,
+            field.length,
+            hex.join(" "),
+            field.name
+        );
+    }
+
+    println!("\n=== 资源布局 ===");
+    for (kind, entry) in parser.resources() {
+        let range = entry.byte_range();
+        println!(
+            "{:<14} 0x{:010X}..0x{:010X} 标志: {}",
+            kind.to_string(),
+            range.start,
+            range.end,
+            entry.resource_flags()
+        );
+    }
+
+    Ok(())
+}
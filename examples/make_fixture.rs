@@ -0,0 +1,62 @@
+use std::env;
+use wim_parser::{VirtualWim, WimTimestamp};
+
+/// 只有 PE 文件头的可执行文件
+fn pe(machine: u16) -> Vec<u8> {
+    let mut image = vec![0u8; 0x100];
+    image[0..2].copy_from_slice(b"MZ");
+    image[0x3C..0x40].copy_from_slice(&0x80u32.to_le_bytes());
+    image[0x80..0x84].copy_from_slice(b"PE\0\0");
+    image[0x84..0x86].copy_from_slice(&machine.to_le_bytes());
+    image
+}
+
+fn windows_xml(edition: &str) -> String {
+    format!(
+        "<WINDOWS><ARCH>9</ARCH><PRODUCTNAME>Microsoft® Windows® Operating System</PRODUCTNAME>\
+         <EDITIONID>{edition}</EDITIONID><INSTALLATIONTYPE>Client</INSTALLATIONTYPE>\
+         <PRODUCTTYPE>WinNT</PRODUCTTYPE>\
+         <LANGUAGES><LANGUAGE>en-US</LANGUAGE><DEFAULT>en-US</DEFAULT></LANGUAGES>\
+         <VERSION><MAJOR>10</MAJOR><MINOR>0</MINOR><BUILD>19045</BUILD><SPBUILD>2965</SPBUILD></VERSION>\
+         <SYSTEMROOT>WINDOWS</SYSTEMROOT></WINDOWS>"
+    )
+}
+
+/// 重新生成 `fixtures` 特性内嵌的迷你 WIM（data/mini.wim）
+fn main() -> anyhow::Result<()> {
+    let output = env::args()
+        .nth(1)
+        .unwrap_or_else(|| "data/mini.wim".to_string());
+    // 固定为 2023-01-01 00:00:00 UTC，文档示例中的时间不随生成时间变化
+    let time = WimTimestamp::from_unix(1_672_531_200, 0).unwrap();
+
+    let mut wim = VirtualWim::new();
+    for (name, edition) in [
+        ("Windows 10 Home", "Core"),
+        ("Windows 10 Pro", "Professional"),
+    ] {
+        let image = wim.add_image(name);
+        image
+            .set_time(time)
+            .set_description(name)
+            .extra_xml(&windows_xml(edition));
+        image
+            .add_file("/Windows/System32/ntoskrnl.exe", pe(0x8664))?
+            .add_file("/Windows/System32/kernel32.dll", pe(0x8664))?
+            .add_file("/Windows/SysWOW64/kernel32.dll", pe(0x014C))?
+            .add_file(
+                "/Windows/win.ini",
+                "; for 16-bit app support\r\n[fonts]\r\n",
+            )?
+            .add_dir("/Users/Public")?
+            .add_file("/setup.exe", pe(0x014C))?
+            .add_file("/readme.txt", format!("{name}\r\n"))?;
+        if edition == "Professional" {
+            image.add_file("/Windows/System32/gpedit.msc", "<MMC_ConsoleFile/>")?;
+        }
+    }
+
+    let header = wim.save(&output)?;
+    println!("已写入 {output}: {} 个镜像", header.image_count);
+    Ok(())
+}
@@ -0,0 +1,624 @@
+//! 镜像应用（释放）的预演：在不写入任何文件的情况下报告释放操作的影响
+
+use anyhow::Result;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::SystemTime;
+
+use crate::fmt::{format_bytes, Table, ToTable};
+use crate::glob::PathPattern;
+use crate::log::{debug, info};
+use crate::metadata::{
+    DirEntry, FILE_ATTRIBUTE_HIDDEN, FILE_ATTRIBUTE_SYSTEM, IO_REPARSE_TAG_MOUNT_POINT,
+    IO_REPARSE_TAG_SYMLINK,
+};
+use crate::progress::Progress;
+use crate::{WimParser, WimTimestamp};
+
+/// 目标路径已存在时的处理策略
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ConflictPolicy {
+    /// 报错并中止（默认）
+    #[default]
+    Error,
+    /// 保留已有内容，跳过该项
+    Skip,
+    /// 删除已有内容后写入
+    Overwrite,
+    /// 镜像中的版本较新时覆盖，否则跳过
+    OverwriteIfNewer,
+}
+
+/// 针对单个冲突确定的处理动作
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConflictAction {
+    /// 跳过
+    Skip,
+    /// 覆盖
+    Overwrite,
+    /// 报错
+    Fail,
+}
+
+/// 逐项冲突回调：返回 `Some` 时覆盖按策略确定的动作
+pub type ConflictResolver = dyn Fn(&ApplyConflict) -> Option<ConflictAction> + Send + Sync;
+
+/// 镜像释放选项
+#[derive(Clone, Default)]
+pub struct ApplyOptions {
+    conflict_policy: ConflictPolicy,
+    resolver: Option<Arc<ConflictResolver>>,
+    skip_attributes: u32,
+    min_file_size: Option<u64>,
+    max_file_size: Option<u64>,
+    include_extensions: Vec<String>,
+    exclude_extensions: Vec<String>,
+    include_patterns: Vec<PathPattern>,
+    no_rp_fix: bool,
+    max_throughput: Option<u64>,
+    progress: Option<Arc<dyn Progress>>,
+    /// 按冲突处理动作跳过的路径（由 [`WimParser::apply_image`] 设置，目录的内容一并跳过）
+    pub(crate) skip_paths: Vec<String>,
+}
+
+impl fmt::Debug for ApplyOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ApplyOptions")
+            .field("conflict_policy", &self.conflict_policy)
+            .field("resolver", &self.resolver.is_some())
+            .field("skip_attributes", &self.skip_attributes)
+            .field("min_file_size", &self.min_file_size)
+            .field("max_file_size", &self.max_file_size)
+            .field("include_extensions", &self.include_extensions)
+            .field("exclude_extensions", &self.exclude_extensions)
+            .field("include_patterns", &self.include_patterns)
+            .field("rp_fix", &!self.no_rp_fix)
+            .field("max_throughput", &self.max_throughput)
+            .field("progress", &self.progress.is_some())
+            .finish()
+    }
+}
+
+/// 规范化扩展名：去掉开头的 `.` 并转为小写
+fn normalize_extension(extension: &str) -> String {
+    extension.trim_start_matches('.').to_ascii_lowercase()
+}
+
+/// 路径最后一个组成部分的扩展名（小写，没有扩展名时为空字符串）
+fn extension_of(path: &str) -> String {
+    let name = path.rsplit('/').next().unwrap_or(path);
+    match name.rsplit_once('.') {
+        Some((stem, extension)) if !stem.is_empty() => extension.to_ascii_lowercase(),
+        _ => String::new(),
+    }
+}
+
+impl ApplyOptions {
+    /// 默认选项：遇到冲突报错
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 设置冲突策略
+    pub fn conflict_policy(mut self, policy: ConflictPolicy) -> Self {
+        self.conflict_policy = policy;
+        self
+    }
+
+    /// 设置逐项冲突回调，例如对特定路径强制覆盖
+    pub fn on_conflict<F>(mut self, resolver: F) -> Self
+    where
+        F: Fn(&ApplyConflict) -> Option<ConflictAction> + Send + Sync + 'static,
+    {
+        self.resolver = Some(Arc::new(resolver));
+        self
+    }
+
+    /// 跳过隐藏的文件和目录（目录被跳过时其内容一并跳过）
+    pub fn skip_hidden(self, skip: bool) -> Self {
+        self.skip_attribute_bits(FILE_ATTRIBUTE_HIDDEN, skip)
+    }
+
+    /// 跳过带系统属性的文件和目录（目录被跳过时其内容一并跳过）
+    pub fn skip_system(self, skip: bool) -> Self {
+        self.skip_attribute_bits(FILE_ATTRIBUTE_SYSTEM, skip)
+    }
+
+    /// 跳过具有任一指定属性位的文件和目录
+    pub fn skip_attributes(mut self, attributes: u32) -> Self {
+        self.skip_attributes |= attributes;
+        self
+    }
+
+    fn skip_attribute_bits(mut self, attributes: u32, skip: bool) -> Self {
+        if skip {
+            self.skip_attributes |= attributes;
+        } else {
+            self.skip_attributes &= !attributes;
+        }
+        self
+    }
+
+    /// 只释放不小于 `size` 字节的文件
+    pub fn min_file_size(mut self, size: u64) -> Self {
+        self.min_file_size = Some(size);
+        self
+    }
+
+    /// 只释放不大于 `size` 字节的文件
+    pub fn max_file_size(mut self, size: u64) -> Self {
+        self.max_file_size = Some(size);
+        self
+    }
+
+    /// 只释放指定扩展名的文件（不区分大小写，可带或不带 `.`；空字符串表示没有扩展名的文件）
+    pub fn include_extensions<I, S>(mut self, extensions: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.include_extensions.extend(
+            extensions
+                .into_iter()
+                .map(|e| normalize_extension(e.as_ref())),
+        );
+        self
+    }
+
+    /// 不释放指定扩展名的文件（优先于允许列表）
+    pub fn exclude_extensions<I, S>(mut self, extensions: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.exclude_extensions.extend(
+            extensions
+                .into_iter()
+                .map(|e| normalize_extension(e.as_ref())),
+        );
+        self
+    }
+
+    /// 只释放匹配任一通配符模式的文件，以及匹配模式的目录中的全部内容（不区分大小写）
+    ///
+    /// 设置后只创建包含所选内容的目录；模式的写法见 [`PathPattern`]。
+    pub fn include_patterns<I>(mut self, patterns: I) -> Self
+    where
+        I: IntoIterator<Item = PathPattern>,
+    {
+        self.include_patterns.extend(patterns);
+        self
+    }
+
+    /// 是否按文件头的 `RP_FIX` 标志把捕获时修正过的链接目标重新指向释放出的内容（默认开启）
+    pub fn rp_fix(mut self, enabled: bool) -> Self {
+        self.no_rp_fix = !enabled;
+        self
+    }
+
+    /// 是否修正链接目标
+    pub fn rp_fix_enabled(&self) -> bool {
+        !self.no_rp_fix
+    }
+
+    /// 限制读写速率（字节/秒，令牌桶）：从 WIM 读取和向目标写入各自不超过该速率，
+    /// 以免后台释放占满其他服务的 I/O
+    pub fn max_throughput(mut self, bytes_per_sec: u64) -> Self {
+        self.max_throughput = Some(bytes_per_sec);
+        self
+    }
+
+    /// 读写速率上限（字节/秒，`None` 表示不限速）
+    pub fn throughput_limit(&self) -> Option<u64> {
+        self.max_throughput
+    }
+
+    /// 设置进度回调：按写入的文件数据字节数回报平滑后的吞吐率和剩余时间，
+    /// 开始和结束时各回报一次，中间至多每 250 毫秒一次
+    pub fn progress<P: Progress + 'static>(mut self, progress: P) -> Self {
+        self.progress = Some(Arc::new(progress));
+        self
+    }
+
+    /// 进度回调
+    pub(crate) fn progress_callback(&self) -> Option<&dyn Progress> {
+        self.progress.as_deref()
+    }
+
+    /// 按属性、大小和扩展名过滤条件判断是否释放该目录项
+    ///
+    /// 大小和扩展名条件只作用于文件；`size` 为未命名数据流的大小。
+    pub fn selects(&self, path: &str, attributes: u32, is_dir: bool, size: u64) -> bool {
+        if attributes & self.skip_attributes != 0 {
+            return false;
+        }
+        if is_dir {
+            return true;
+        }
+        if self.min_file_size.is_some_and(|min| size < min)
+            || self.max_file_size.is_some_and(|max| size > max)
+        {
+            return false;
+        }
+
+        if !self.include_patterns.is_empty()
+            && !self
+                .include_patterns
+                .iter()
+                .any(|pattern| pattern.matches_self_or_parent(path))
+        {
+            return false;
+        }
+
+        let extension = extension_of(path);
+        if self.exclude_extensions.contains(&extension) {
+            return false;
+        }
+        self.include_extensions.is_empty() || self.include_extensions.contains(&extension)
+    }
+
+    /// 按过滤条件选出要释放的目录项（先序，父目录在前）
+    ///
+    /// `stream_sizes` 为数据流 SHA-1 到未压缩大小的映射，用于大小条件和被排除字节数的统计。
+    pub(crate) fn select<'a>(
+        &self,
+        root: &'a DirEntry,
+        stream_sizes: &HashMap<[u8; 20], u64>,
+    ) -> Selection<'a> {
+        let mut selection = Selection {
+            entries: Vec::new(),
+            filtered_count: 0,
+            filtered_bytes: 0,
+        };
+        // 被过滤条件排除的目录（其子孙一并排除）
+        let mut skipped_dirs: Vec<String> = Vec::new();
+
+        root.walk_with_path(&mut |path, entry| {
+            let unnamed_size = stream_sizes.get(&entry.hash).copied().unwrap_or(0);
+            let in_skipped_dir = skipped_dirs.iter().any(|dir| {
+                path.strip_prefix(dir.as_str())
+                    .is_some_and(|rest| rest.starts_with('/'))
+            });
+            if in_skipped_dir
+                || self.skip_paths.iter().any(|skip| skip == path)
+                || !self.selects(path, entry.attributes, entry.is_directory(), unnamed_size)
+            {
+                selection.filtered_count += 1;
+                if entry.is_directory() {
+                    if !in_skipped_dir {
+                        skipped_dirs.push(path.to_string());
+                    }
+                } else {
+                    selection.filtered_bytes += unnamed_size;
+                }
+                return;
+            }
+            selection.entries.push((path.to_string(), entry));
+        });
+
+        if !self.include_patterns.is_empty() {
+            self.prune_unmatched_dirs(&mut selection);
+        }
+        selection
+    }
+
+    /// 去掉既不匹配通配符模式、也不包含所选内容的目录
+    fn prune_unmatched_dirs(&self, selection: &mut Selection<'_>) {
+        let mut needed: HashSet<&str> = HashSet::new();
+        for (path, entry) in &selection.entries {
+            let matched = !entry.is_directory()
+                || self
+                    .include_patterns
+                    .iter()
+                    .any(|pattern| pattern.matches_self_or_parent(path));
+            if matched {
+                let mut ancestor = path.as_str();
+                needed.insert(ancestor);
+                while let Some((parent, _)) = ancestor.rsplit_once('/') {
+                    if !needed.insert(parent) {
+                        break;
+                    }
+                    ancestor = parent;
+                }
+            }
+        }
+        let needed: HashSet<String> = needed.into_iter().map(str::to_string).collect();
+        let before = selection.entries.len();
+        selection
+            .entries
+            .retain(|(path, _)| needed.contains(path.as_str()));
+        selection.filtered_count += (before - selection.entries.len()) as u32;
+    }
+
+    /// 当前冲突策略
+    pub fn policy(&self) -> ConflictPolicy {
+        self.conflict_policy
+    }
+
+    /// 确定单个冲突的处理动作：回调优先，其次按策略
+    pub fn resolve(&self, conflict: &ApplyConflict) -> ConflictAction {
+        if let Some(action) = self
+            .resolver
+            .as_ref()
+            .and_then(|resolver| resolver(conflict))
+        {
+            return action;
+        }
+
+        match self.conflict_policy {
+            ConflictPolicy::Error => ConflictAction::Fail,
+            ConflictPolicy::Skip => ConflictAction::Skip,
+            ConflictPolicy::Overwrite => ConflictAction::Overwrite,
+            ConflictPolicy::OverwriteIfNewer => {
+                let entry = conflict.entry_write_time.to_system_time();
+                match (entry, conflict.existing_modified) {
+                    (Some(entry), Some(existing)) if entry > existing => ConflictAction::Overwrite,
+                    _ => ConflictAction::Skip,
+                }
+            }
+        }
+    }
+}
+
+/// 按过滤条件选出的目录项
+pub(crate) struct Selection<'a> {
+    /// 要释放的目录项及其相对路径（`/` 分隔）
+    pub entries: Vec<(String, &'a DirEntry)>,
+    /// 被排除的文件和目录数量
+    pub filtered_count: u32,
+    /// 被排除文件的总字节数
+    pub filtered_bytes: u64,
+}
+
+/// 当前平台无法还原的特性
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UnsupportedFeature {
+    /// 除符号链接和目录联接之外的重解析点（例如 WOF、去重）
+    ReparsePoint(u32),
+    /// 命名数据流（仅 Windows 支持）
+    NamedStream(String),
+}
+
+impl fmt::Display for UnsupportedFeature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UnsupportedFeature::ReparsePoint(tag) => write!(f, "重解析点 (0x{tag:08X})"),
+            UnsupportedFeature::NamedStream(name) => write!(f, "命名数据流 ({name})"),
+        }
+    }
+}
+
+/// 包含不支持特性的目录项
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnsupportedEntry {
+    /// 镜像内的相对路径（`/` 分隔）
+    pub path: String,
+    /// 不支持的特性
+    pub feature: UnsupportedFeature,
+}
+
+/// 目标目录中已存在的冲突项
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ApplyConflict {
+    /// 镜像内的相对路径（`/` 分隔）
+    pub path: String,
+    /// 目标路径
+    pub target: PathBuf,
+    /// 已存在的是否为目录
+    pub existing_is_dir: bool,
+    /// 镜像中的是否为目录
+    pub entry_is_dir: bool,
+    /// 镜像中的最后写入时间
+    pub entry_write_time: WimTimestamp,
+    /// 已存在内容的修改时间
+    pub existing_modified: Option<SystemTime>,
+    /// 按 [`ApplyOptions`] 确定的处理动作
+    pub action: ConflictAction,
+}
+
+/// 镜像释放计划
+#[derive(Debug, Clone)]
+pub struct ApplyPlan {
+    /// 镜像索引
+    pub index: u32,
+    /// 目标目录
+    pub target: PathBuf,
+    /// 将创建的目录数量（不含根目录）
+    pub dir_count: u32,
+    /// 将写入的文件数量（含重解析点）
+    pub file_count: u32,
+    /// 将写入的总字节数（含命名数据流，硬链接按目录项重复计算）
+    pub total_bytes: u64,
+    /// 与目标目录中已有内容的冲突（目录与目录合并不算冲突）
+    pub conflicts: Vec<ApplyConflict>,
+    /// 当前平台无法还原的特性
+    pub unsupported: Vec<UnsupportedEntry>,
+    /// 带有安全描述符（ACL）但当前平台无法还原的目录项数量
+    pub acl_entries: u32,
+    /// 数据流不在偏移表中的文件（例如缺少分卷）
+    pub missing_streams: Vec<String>,
+    /// 被过滤条件排除的文件和目录数量（被排除目录中的内容也计入）
+    pub filtered_count: u32,
+    /// 被排除文件的总字节数
+    pub filtered_bytes: u64,
+}
+
+impl ApplyPlan {
+    /// 是否有按选项会导致释放失败的冲突
+    pub fn has_fatal_conflicts(&self) -> bool {
+        self.conflicts
+            .iter()
+            .any(|conflict| conflict.action == ConflictAction::Fail)
+    }
+
+    /// 释放是否可以无警告地完成
+    pub fn is_clean(&self) -> bool {
+        self.conflicts.is_empty()
+            && self.unsupported.is_empty()
+            && self.acl_entries == 0
+            && self.missing_streams.is_empty()
+    }
+}
+
+impl ToTable for ApplyPlan {
+    fn table(&self) -> Table {
+        let mut table = Table::new(["项目", "值"]);
+        table.push_row(["镜像".to_string(), self.index.to_string()]);
+        table.push_row(["目标".to_string(), self.target.display().to_string()]);
+        table.push_row(["目录数".to_string(), self.dir_count.to_string()]);
+        table.push_row(["文件数".to_string(), self.file_count.to_string()]);
+        table.push_row(["总大小".to_string(), format_bytes(self.total_bytes)]);
+        table.push_row(["冲突".to_string(), self.conflicts.len().to_string()]);
+        table.push_row([
+            "不支持的特性".to_string(),
+            self.unsupported.len().to_string(),
+        ]);
+        table.push_row(["无法还原的 ACL".to_string(), self.acl_entries.to_string()]);
+        table.push_row([
+            "缺失数据流".to_string(),
+            self.missing_streams.len().to_string(),
+        ]);
+        table.push_row(["已过滤".to_string(), self.filtered_count.to_string()]);
+        table
+    }
+}
+
+impl WimParser {
+    /// 预演将镜像释放到 `target`：统计文件数和字节数，检查冲突和不支持的特性，不写入任何内容
+    ///
+    /// 使用默认的 [`ApplyOptions`]，所有冲突的处理动作均为 [`ConflictAction::Fail`]。
+    ///
+    /// ```
+    /// # #[cfg(feature = "fixtures")] {
+    /// # let fixture = wim_parser::fixtures::MiniWim::create()?;
+    /// # let mut parser = wim_parser::WimParser::new(fixture.path())?;
+    /// let plan = parser.plan_apply(1, "/nonexistent/deploy")?;
+    /// assert_eq!(plan.file_count, 6);
+    /// assert!(plan.conflicts.is_empty());
+    /// # }
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    pub fn plan_apply<P: AsRef<Path>>(&mut self, index: u32, target: P) -> Result<ApplyPlan> {
+        self.plan_apply_with(index, target, &ApplyOptions::default())
+    }
+
+    /// 按指定选项预演镜像释放，每个冲突附带按选项确定的处理动作
+    pub fn plan_apply_with<P: AsRef<Path>>(
+        &mut self,
+        index: u32,
+        target: P,
+        options: &ApplyOptions,
+    ) -> Result<ApplyPlan> {
+        let target = target.as_ref();
+        let root = self.read_metadata_root(index)?;
+
+        // 数据流 SHA-1 -> 未压缩大小
+        let stream_sizes: HashMap<[u8; 20], u64> = self
+            .read_lookup_table()?
+            .iter()
+            .filter(|entry| !entry.is_metadata())
+            .map(|entry| (entry.hash, entry.resource.original_size))
+            .collect();
+
+        let mut plan = ApplyPlan {
+            index,
+            target: target.to_path_buf(),
+            dir_count: 0,
+            file_count: 0,
+            total_bytes: 0,
+            conflicts: Vec::new(),
+            unsupported: Vec::new(),
+            acl_entries: 0,
+            missing_streams: Vec::new(),
+            filtered_count: 0,
+            filtered_bytes: 0,
+        };
+        let target_exists = target.exists();
+        let selection = options.select(&root, &stream_sizes);
+        plan.filtered_count = selection.filtered_count;
+        plan.filtered_bytes = selection.filtered_bytes;
+
+        for (path, entry) in &selection.entries {
+            let path = path.as_str();
+            if entry.is_directory() {
+                plan.dir_count += 1;
+            } else {
+                plan.file_count += 1;
+            }
+
+            if !cfg!(windows) && entry.security_id >= 0 {
+                plan.acl_entries += 1;
+            }
+
+            if entry.is_reparse_point()
+                && !matches!(
+                    entry.reparse_tag,
+                    IO_REPARSE_TAG_SYMLINK | IO_REPARSE_TAG_MOUNT_POINT
+                )
+            {
+                plan.unsupported.push(UnsupportedEntry {
+                    path: path.to_string(),
+                    feature: UnsupportedFeature::ReparsePoint(entry.reparse_tag),
+                });
+            }
+
+            let streams = std::iter::once(&entry.hash).chain(entry.streams.iter().map(|s| &s.hash));
+            let mut missing = false;
+            for hash in streams.filter(|hash| **hash != [0u8; 20]) {
+                match stream_sizes.get(hash) {
+                    Some(size) => plan.total_bytes += size,
+                    None => missing = true,
+                }
+            }
+            if missing {
+                plan.missing_streams.push(path.to_string());
+            }
+
+            if !cfg!(windows) {
+                for stream in entry.streams.iter().filter(|s| !s.name.is_empty()) {
+                    plan.unsupported.push(UnsupportedEntry {
+                        path: path.to_string(),
+                        feature: UnsupportedFeature::NamedStream(stream.name.clone()),
+                    });
+                }
+            }
+
+            if target_exists {
+                let target_path = target.join(path);
+                if let Ok(existing) = std::fs::symlink_metadata(&target_path) {
+                    // 目录与已有目录合并，不算冲突
+                    if !(existing.is_dir() && entry.is_directory()) {
+                        let mut conflict = ApplyConflict {
+                            path: path.to_string(),
+                            target: target_path,
+                            existing_is_dir: existing.is_dir(),
+                            entry_is_dir: entry.is_directory(),
+                            entry_write_time: entry.last_write_time,
+                            existing_modified: existing.modified().ok(),
+                            action: ConflictAction::Fail,
+                        };
+                        conflict.action = options.resolve(&conflict);
+                        plan.conflicts.push(conflict);
+                    }
+                }
+            }
+        }
+
+        debug!(
+            "镜像 {} 释放预演: {} 个冲突, {} 个不支持的特性",
+            index,
+            plan.conflicts.len(),
+            plan.unsupported.len()
+        );
+        info!(
+            "镜像 {} 释放预演完成 - 目录: {}, 文件: {}, 总大小: {}",
+            index,
+            plan.dir_count,
+            plan.file_count,
+            format_bytes(plan.total_bytes)
+        );
+        Ok(plan)
+    }
+}
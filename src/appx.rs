@@ -0,0 +1,190 @@
+use anyhow::Result;
+
+use crate::fmt::{Table, ToTable};
+use crate::log::debug;
+use crate::WimParser;
+
+/// 商店应用安装目录
+const WINDOWS_APPS_DIR: &str = r"\Program Files\WindowsApps";
+/// 预配应用列表（为新用户安装的应用）
+const APPX_PROVISIONING_XML: &str = r"\ProgramData\Microsoft\Windows\AppxProvisioning.xml";
+
+/// 镜像中预装的商店应用包
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AppxPackage {
+    /// 包名称（例如 `Microsoft.WindowsCalculator`）
+    pub name: String,
+    /// 版本
+    pub version: String,
+    /// 处理器架构（`x64`、`neutral` 等）
+    pub architecture: String,
+    /// 资源 ID（例如 `split.scale-100`，主包为空，捆绑包为 `~`）
+    pub resource_id: String,
+    /// 发布者 ID
+    pub publisher_id: String,
+    /// 包完整名称（即安装目录名）
+    pub full_name: String,
+    /// 是否包含 `AppxManifest.xml`
+    pub has_manifest: bool,
+    /// 是否列在预配应用列表中（镜像中没有预配列表时为 `None`）
+    pub provisioned: Option<bool>,
+}
+
+impl AppxPackage {
+    /// 从包完整名称 `Name_Version_Arch_ResourceId_PublisherId` 解析
+    pub fn from_full_name(full_name: &str) -> Option<AppxPackage> {
+        let parts: Vec<&str> = full_name.split('_').collect();
+        let [name, version, architecture, resource_id, publisher_id] = parts[..] else {
+            return None;
+        };
+        if name.is_empty() || publisher_id.is_empty() || !is_package_version(version) {
+            return None;
+        }
+        Some(AppxPackage {
+            name: name.to_string(),
+            version: version.to_string(),
+            architecture: architecture.to_string(),
+            resource_id: resource_id.to_string(),
+            publisher_id: publisher_id.to_string(),
+            full_name: full_name.to_string(),
+            has_manifest: false,
+            provisioned: None,
+        })
+    }
+
+    /// 包系列名称 `Name_PublisherId`
+    pub fn family_name(&self) -> String {
+        format!("{}_{}", self.name, self.publisher_id)
+    }
+
+    /// 是否为捆绑包
+    pub fn is_bundle(&self) -> bool {
+        self.resource_id == "~"
+    }
+
+    /// 是否为资源包（语言、缩放比例等）
+    pub fn is_resource(&self) -> bool {
+        !self.resource_id.is_empty() && !self.is_bundle()
+    }
+}
+
+/// 四段数字版本号 (`a.b.c.d`)
+fn is_package_version(version: &str) -> bool {
+    let parts: Vec<&str> = version.split('.').collect();
+    parts.len() == 4
+        && parts
+            .iter()
+            .all(|part| !part.is_empty() && part.bytes().all(|b| b.is_ascii_digit()))
+}
+
+/// 按数值比较版本号的排序键
+fn version_key(version: &str) -> Vec<u32> {
+    version
+        .split('.')
+        .map(|part| part.parse().unwrap_or(0))
+        .collect()
+}
+
+/// 将 XML 文件内容解码为字符串（UTF-16 LE 带 BOM，或 UTF-8）
+fn decode_text(data: &[u8]) -> String {
+    match data {
+        [0xFF, 0xFE, rest @ ..] => {
+            let units: Vec<u16> = rest
+                .chunks_exact(2)
+                .map(|chunk| u16::from_le_bytes([chunk[0], chunk[1]]))
+                .collect();
+            String::from_utf16_lossy(&units)
+        }
+        _ => String::from_utf8_lossy(data).into_owned(),
+    }
+}
+
+impl ToTable for [AppxPackage] {
+    fn table(&self) -> Table {
+        let mut table = Table::new(["名称", "版本", "架构", "类型", "预配"]);
+        for package in self {
+            let kind = if package.is_bundle() {
+                "捆绑包"
+            } else if package.is_resource() {
+                "资源包"
+            } else {
+                "主包"
+            };
+            let provisioned = match package.provisioned {
+                Some(true) => "是",
+                Some(false) => "否",
+                None => "-",
+            };
+            table.push_row([
+                package.name.clone(),
+                package.version.clone(),
+                package.architecture.clone(),
+                kind.to_string(),
+                provisioned.to_string(),
+            ]);
+        }
+        table
+    }
+}
+
+impl WimParser {
+    /// 列出镜像中预装的商店应用（`\Program Files\WindowsApps` 下的包目录）
+    ///
+    /// 镜像中有预配应用列表 (`AppxProvisioning.xml`) 时，同时标记每个包是否会为新用户预配。
+    /// 结果按名称、版本排列，可用于精简镜像前后的对比。
+    pub fn list_provisioned_appx(&mut self, index: u32) -> Result<Vec<AppxPackage>> {
+        let root = self.read_metadata_root(index)?;
+
+        let mut packages: Vec<AppxPackage> = root
+            .find_path(WINDOWS_APPS_DIR)
+            .map(|apps| {
+                apps.children
+                    .iter()
+                    .filter(|entry| entry.is_directory())
+                    .filter_map(|entry| {
+                        let mut package = AppxPackage::from_full_name(&entry.name)?;
+                        package.has_manifest = entry
+                            .find_path("AppxManifest.xml")
+                            .is_some_and(|manifest| !manifest.is_directory());
+                        Some(package)
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let provisioning_hash = root
+            .find_path(APPX_PROVISIONING_XML)
+            .filter(|entry| !entry.is_directory())
+            .map(|entry| entry.hash);
+        let provisioning = match provisioning_hash {
+            Some(hash) => match self.read_stream(&hash) {
+                Ok(data) => Some(decode_text(&data).to_ascii_lowercase()),
+                Err(e) => {
+                    debug!("无法读取镜像 {} 的预配应用列表: {}", index, e);
+                    None
+                }
+            },
+            None => None,
+        };
+        if let Some(provisioning) = &provisioning {
+            for package in &mut packages {
+                let family = package.family_name().to_ascii_lowercase();
+                let full = package.full_name.to_ascii_lowercase();
+                package.provisioned =
+                    Some(provisioning.contains(&full) || provisioning.contains(&family));
+            }
+        }
+
+        packages.sort_by(|a, b| {
+            a.name
+                .to_ascii_lowercase()
+                .cmp(&b.name.to_ascii_lowercase())
+                .then_with(|| version_key(&a.version).cmp(&version_key(&b.version)))
+                .then_with(|| a.is_resource().cmp(&b.is_resource()))
+                .then_with(|| a.full_name.cmp(&b.full_name))
+        });
+
+        debug!("镜像 {} 中有 {} 个商店应用包", index, packages.len());
+        Ok(packages)
+    }
+}
@@ -0,0 +1,151 @@
+use alloc::vec::Vec;
+use core::fmt;
+
+/// 处理器架构（`<WINDOWS><ARCH>` 中的 `PROCESSOR_ARCHITECTURE_*` 数值）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Arch {
+    /// x86 (0)
+    X86,
+    /// ARM (5)
+    Arm,
+    /// Itanium (6)
+    Ia64,
+    /// x64 / AMD64 (9)
+    X64,
+    /// ARM64 (12)
+    Arm64,
+    /// 无法识别的架构，保留原始数值
+    Unknown(u32),
+}
+
+impl Arch {
+    /// 根据 ARCH 数值确定架构
+    pub fn from_raw(value: u32) -> Arch {
+        match value {
+            0 => Arch::X86,
+            5 => Arch::Arm,
+            6 => Arch::Ia64,
+            9 => Arch::X64,
+            12 => Arch::Arm64,
+            other => Arch::Unknown(other),
+        }
+    }
+
+    /// 根据架构名称确定架构（不区分大小写，`amd64` 视为 x64）
+    pub fn from_name(name: &str) -> Option<Arch> {
+        let arch = match name.to_ascii_lowercase().as_str() {
+            "x86" => Arch::X86,
+            "arm" => Arch::Arm,
+            "ia64" => Arch::Ia64,
+            "x64" | "amd64" => Arch::X64,
+            "arm64" => Arch::Arm64,
+            _ => return None,
+        };
+        Some(arch)
+    }
+
+    /// 原始 ARCH 数值
+    pub fn raw(&self) -> u32 {
+        match self {
+            Arch::X86 => 0,
+            Arch::Arm => 5,
+            Arch::Ia64 => 6,
+            Arch::X64 => 9,
+            Arch::Arm64 => 12,
+            Arch::Unknown(value) => *value,
+        }
+    }
+
+    /// 架构名称（未知架构时为 `None`）
+    pub fn name(&self) -> Option<&'static str> {
+        match self {
+            Arch::X86 => Some("x86"),
+            Arch::Arm => Some("ARM"),
+            Arch::Ia64 => Some("IA64"),
+            Arch::X64 => Some("x64"),
+            Arch::Arm64 => Some("ARM64"),
+            Arch::Unknown(_) => None,
+        }
+    }
+
+    /// 是否为已知架构
+    pub fn is_known(&self) -> bool {
+        !matches!(self, Arch::Unknown(_))
+    }
+}
+
+impl fmt::Display for Arch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.name() {
+            Some(name) => f.write_str(name),
+            None => write!(f, "未知架构 ({})", self.raw()),
+        }
+    }
+}
+
+/// 按镜像架构对 WIM 介质分类
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MediaKind {
+    /// 没有镜像记录架构，无法确定
+    Unknown,
+    /// 所有镜像为同一架构
+    SingleArch(Arch),
+    /// 包含多种架构的镜像（例如同时含 ARM64 和 x64 镜像的 Windows on ARM 混合介质），
+    /// 按首次出现的顺序排列
+    MultiArch(Vec<Arch>),
+}
+
+impl MediaKind {
+    /// 根据镜像架构集合分类（重复项会被去掉）
+    pub fn from_architectures(architectures: &[Arch]) -> MediaKind {
+        let mut distinct: Vec<Arch> = Vec::new();
+        for &arch in architectures {
+            if !distinct.contains(&arch) {
+                distinct.push(arch);
+            }
+        }
+        match distinct.as_slice() {
+            [] => MediaKind::Unknown,
+            [arch] => MediaKind::SingleArch(*arch),
+            _ => MediaKind::MultiArch(distinct),
+        }
+    }
+
+    /// 介质包含的所有架构
+    pub fn architectures(&self) -> &[Arch] {
+        match self {
+            MediaKind::Unknown => &[],
+            MediaKind::SingleArch(arch) => core::slice::from_ref(arch),
+            MediaKind::MultiArch(architectures) => architectures,
+        }
+    }
+
+    /// 是否包含指定架构
+    pub fn contains(&self, arch: Arch) -> bool {
+        self.architectures().contains(&arch)
+    }
+
+    /// 是否为多架构介质
+    pub fn is_multi_arch(&self) -> bool {
+        matches!(self, MediaKind::MultiArch(_))
+    }
+}
+
+impl fmt::Display for MediaKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MediaKind::Unknown => f.write_str("未知架构"),
+            MediaKind::SingleArch(arch) => write!(f, "{arch}"),
+            MediaKind::MultiArch(architectures) => {
+                f.write_str("多架构 (")?;
+                for (i, arch) in architectures.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(", ")?;
+                    }
+                    write!(f, "{arch}")?;
+                }
+                f.write_str(")")
+            }
+        }
+    }
+}
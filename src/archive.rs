@@ -0,0 +1,660 @@
+//! 归档输出目标：tar (GNU 格式) 和 zip（存储方式，按需使用 zip64）
+
+use anyhow::{Context, Result};
+use std::io::{self, Write};
+
+use crate::apply::ApplyOptions;
+use crate::target::{ApplyReport, ApplyTarget, EntryMetadata};
+use crate::timestamp::civil_from_days;
+use crate::{WimParser, WimTimestamp};
+
+/// tar 块大小
+const TAR_BLOCK_SIZE: usize = 512;
+/// ustar 头中文件名和链接名字段的长度
+const TAR_NAME_LEN: usize = 100;
+/// 12 字节八进制字段可表示的最大值（超过时使用 GNU base-256 编码）
+const TAR_OCTAL_MAX: u64 = 0o77777777777;
+/// GNU 长文件名条目的名称
+const TAR_LONG_LINK_NAME: &[u8] = b"././@LongLink";
+
+/// zip 本地文件头签名
+const ZIP_LOCAL_HEADER_SIG: u32 = 0x0403_4B50;
+/// zip 数据描述符签名
+const ZIP_DATA_DESCRIPTOR_SIG: u32 = 0x0807_4B50;
+/// zip 中央目录记录签名
+const ZIP_CENTRAL_HEADER_SIG: u32 = 0x0201_4B50;
+/// zip64 中央目录结束记录签名
+const ZIP64_END_SIG: u32 = 0x0606_4B50;
+/// zip64 中央目录结束记录定位器签名
+const ZIP64_LOCATOR_SIG: u32 = 0x0706_4B50;
+/// zip 中央目录结束记录签名
+const ZIP_END_SIG: u32 = 0x0605_4B50;
+/// zip64 扩展字段标识
+const ZIP64_EXTRA_ID: u16 = 0x0001;
+/// NTFS 扩展字段标识（三个 FILETIME，精度 100 纳秒）
+const ZIP_NTFS_EXTRA_ID: u16 = 0x000A;
+/// 扩展时间戳字段标识 ("UT"，Unix 秒)
+const ZIP_UNIX_TIME_EXTRA_ID: u16 = 0x5455;
+/// 通用标志：大小和 CRC 写在数据描述符中 (bit 3) | 文件名为 UTF-8 (bit 11)
+const ZIP_FLAGS: u16 = 0x0008 | 0x0800;
+/// 解压所需版本：2.0 (目录和存储方式)，4.5 (zip64)
+const ZIP_VERSION: u16 = 20;
+const ZIP64_VERSION: u16 = 45;
+/// 创建系统：MS-DOS (外部属性为 DOS 属性) 或 Unix (外部属性高 16 位为 mode)
+const ZIP_HOST_DOS: u16 = 0;
+const ZIP_HOST_UNIX: u16 = 3;
+/// 32 位字段的溢出标记
+const ZIP_U32_MAX: u64 = 0xFFFF_FFFF;
+
+/// CRC-32 (IEEE) 查找表
+const CRC32_TABLE: [u32; 256] = {
+    let mut table = [0u32; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+};
+
+/// 更新 CRC-32（`crc` 为未取反的中间值，初始为 `!0`）
+fn crc32_update(crc: u32, data: &[u8]) -> u32 {
+    data.iter().fold(crc, |crc, &byte| {
+        CRC32_TABLE[((crc ^ u32::from(byte)) & 0xFF) as usize] ^ (crc >> 8)
+    })
+}
+
+/// 正在写入内容的归档条目
+#[derive(Debug, Clone, Copy)]
+struct PendingFile {
+    /// 声明的大小
+    size: u64,
+    /// 已写入的字节数
+    written: u64,
+    /// CRC-32 中间值（仅 zip 使用）
+    crc: u32,
+}
+
+impl PendingFile {
+    fn new(size: u64) -> Self {
+        Self {
+            size,
+            written: 0,
+            crc: !0,
+        }
+    }
+
+    fn check_size(&self) -> Result<()> {
+        if self.written != self.size {
+            return Err(anyhow::anyhow!(
+                "写入的字节数 {} 与声明的大小 {} 不符",
+                self.written,
+                self.size
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// 写入归档条目内容，同时统计字节数
+struct EntryWriter<'a, W: Write> {
+    writer: &'a mut W,
+    pending: &'a mut PendingFile,
+    offset: &'a mut u64,
+}
+
+impl<W: Write> Write for EntryWriter<'_, W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let len = self.writer.write(buf)?;
+        self.pending.crc = crc32_update(self.pending.crc, &buf[..len]);
+        self.pending.written += len as u64;
+        *self.offset += len as u64;
+        Ok(len)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+/// 输出为 tar 归档（GNU 格式，支持长路径和超过 8 GiB 的文件）
+///
+/// 只读属性映射为 mode `0444`，其余文件为 `0644`，目录为 `0755`。
+#[derive(Debug)]
+pub struct TarTarget<W: Write> {
+    writer: W,
+    pending: Option<PendingFile>,
+    offset: u64,
+}
+
+impl<W: Write> TarTarget<W> {
+    /// 写入到 `writer`
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer,
+            pending: None,
+            offset: 0,
+        }
+    }
+
+    /// 取回底层写入器（应在释放完成即 [`ApplyTarget::finish`] 之后调用）
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+
+    /// 结束上一个文件：检查大小并补齐到块边界
+    fn finish_pending(&mut self) -> Result<()> {
+        if let Some(pending) = self.pending.take() {
+            pending.check_size()?;
+            let padding =
+                (TAR_BLOCK_SIZE - (pending.size % TAR_BLOCK_SIZE as u64) as usize) % TAR_BLOCK_SIZE;
+            self.write_all(&vec![0u8; padding])?;
+        }
+        Ok(())
+    }
+
+    fn write_all(&mut self, data: &[u8]) -> Result<()> {
+        self.writer.write_all(data).context("写入 tar 归档失败")?;
+        self.offset += data.len() as u64;
+        Ok(())
+    }
+
+    /// 写入条目头（路径或链接目标过长时先写 GNU 长名称条目）
+    fn write_header(
+        &mut self,
+        path: &str,
+        typeflag: u8,
+        size: u64,
+        mode: u32,
+        link: &str,
+        metadata: &EntryMetadata,
+    ) -> Result<()> {
+        self.finish_pending()?;
+        if link.len() >= TAR_NAME_LEN {
+            self.write_long_name(b'K', link)?;
+        }
+        if path.len() >= TAR_NAME_LEN {
+            self.write_long_name(b'L', path)?;
+        }
+        let mtime = metadata.last_write_time.unix_seconds().max(0) as u64;
+        let header = tar_header(
+            path.as_bytes(),
+            typeflag,
+            size,
+            mode,
+            mtime,
+            link.as_bytes(),
+        );
+        self.write_all(&header)
+    }
+
+    /// 写入 GNU 长名称条目 (`L` 文件名 / `K` 链接目标)
+    fn write_long_name(&mut self, typeflag: u8, name: &str) -> Result<()> {
+        let mut data = name.as_bytes().to_vec();
+        data.push(0);
+        let header = tar_header(TAR_LONG_LINK_NAME, typeflag, data.len() as u64, 0, 0, b"");
+        self.write_all(&header)?;
+        data.resize(data.len().div_ceil(TAR_BLOCK_SIZE) * TAR_BLOCK_SIZE, 0);
+        self.write_all(&data)
+    }
+}
+
+/// 写入 tar 数值字段：八进制，超出范围时使用 GNU base-256 编码
+fn tar_number(field: &mut [u8], value: u64) {
+    let digits = field.len() - 1;
+    if digits >= 11 && value > TAR_OCTAL_MAX {
+        field.fill(0);
+        field[0] = 0x80;
+        let bytes = value.to_be_bytes();
+        let len = field.len();
+        field[len - 8..].copy_from_slice(&bytes);
+        return;
+    }
+    let text = format!("{:0width$o}", value, width = digits);
+    field[..digits].copy_from_slice(&text.as_bytes()[text.len() - digits..]);
+    field[digits] = 0;
+}
+
+/// 生成 512 字节的 GNU tar 头（名称超长时截断，完整名称由前置的长名称条目提供）
+fn tar_header(
+    name: &[u8],
+    typeflag: u8,
+    size: u64,
+    mode: u32,
+    mtime: u64,
+    link: &[u8],
+) -> [u8; TAR_BLOCK_SIZE] {
+    let mut header = [0u8; TAR_BLOCK_SIZE];
+    let name_len = name.len().min(TAR_NAME_LEN);
+    header[..name_len].copy_from_slice(&name[..name_len]);
+    tar_number(&mut header[100..108], u64::from(mode));
+    tar_number(&mut header[108..116], 0);
+    tar_number(&mut header[116..124], 0);
+    tar_number(&mut header[124..136], size);
+    tar_number(&mut header[136..148], mtime);
+    header[156] = typeflag;
+    let link_len = link.len().min(TAR_NAME_LEN);
+    header[157..157 + link_len].copy_from_slice(&link[..link_len]);
+    header[257..265].copy_from_slice(b"ustar  \0");
+
+    header[148..156].fill(b' ');
+    let checksum: u32 = header.iter().map(|&b| u32::from(b)).sum();
+    let text = format!("{checksum:06o}\0 ");
+    header[148..156].copy_from_slice(text.as_bytes());
+    header
+}
+
+impl<W: Write> ApplyTarget for TarTarget<W> {
+    fn create_dir(&mut self, path: &str, metadata: &EntryMetadata) -> Result<()> {
+        self.write_header(&format!("{path}/"), b'5', 0, 0o755, "", metadata)
+    }
+
+    fn create_file(
+        &mut self,
+        path: &str,
+        size: u64,
+        metadata: &EntryMetadata,
+    ) -> Result<Box<dyn Write + '_>> {
+        let mode = if metadata.is_readonly() { 0o444 } else { 0o644 };
+        self.write_header(path, b'0', size, mode, "", metadata)?;
+        let pending = self.pending.insert(PendingFile::new(size));
+        Ok(Box::new(EntryWriter {
+            writer: &mut self.writer,
+            pending,
+            offset: &mut self.offset,
+        }))
+    }
+
+    fn set_metadata(&mut self, _path: &str, _metadata: &EntryMetadata) -> Result<()> {
+        // 时间和权限已写在条目头中
+        Ok(())
+    }
+
+    fn symlink(&mut self, path: &str, target: &str, metadata: &EntryMetadata) -> Result<()> {
+        self.write_header(path, b'2', 0, 0o777, target, metadata)
+    }
+
+    fn hard_link(&mut self, path: &str, existing: &str, metadata: &EntryMetadata) -> Result<bool> {
+        let mode = if metadata.is_readonly() { 0o444 } else { 0o644 };
+        self.write_header(path, b'1', 0, mode, existing, metadata)?;
+        Ok(true)
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.finish_pending()?;
+        self.write_all(&[0u8; TAR_BLOCK_SIZE * 2])?;
+        self.writer.flush().context("写入 tar 归档失败")
+    }
+}
+
+/// zip 中央目录中的条目
+#[derive(Debug, Clone)]
+struct ZipEntry {
+    name: String,
+    crc: u32,
+    size: u64,
+    offset: u64,
+    dos_time: u16,
+    dos_date: u16,
+    host: u16,
+    external_attributes: u32,
+    /// 本地头使用了 zip64（数据描述符中的大小为 8 字节）
+    zip64: bool,
+    /// 时间戳（写入扩展字段）
+    metadata: EntryMetadata,
+}
+
+impl ZipEntry {
+    /// 中央目录记录
+    fn central_header(&self) -> Vec<u8> {
+        let mut zip64 = Vec::new();
+        if self.size >= ZIP_U32_MAX {
+            zip64.extend(self.size.to_le_bytes());
+            zip64.extend(self.size.to_le_bytes());
+        }
+        if self.offset >= ZIP_U32_MAX {
+            zip64.extend(self.offset.to_le_bytes());
+        }
+        let version = if self.zip64 || !zip64.is_empty() {
+            ZIP64_VERSION
+        } else {
+            ZIP_VERSION
+        };
+        let size32 = self.size.min(ZIP_U32_MAX) as u32;
+
+        let mut extra = Vec::new();
+        if !zip64.is_empty() {
+            push_extra_field(&mut extra, ZIP64_EXTRA_ID, &zip64);
+        }
+        extra.extend(timestamp_extra(&self.metadata, false));
+
+        let mut record = Vec::with_capacity(46 + self.name.len() + extra.len() + 4);
+        record.extend(ZIP_CENTRAL_HEADER_SIG.to_le_bytes());
+        record.extend(((self.host << 8) | version).to_le_bytes());
+        record.extend(version.to_le_bytes());
+        record.extend(ZIP_FLAGS.to_le_bytes());
+        record.extend(0u16.to_le_bytes()); // 存储方式
+        record.extend(self.dos_time.to_le_bytes());
+        record.extend(self.dos_date.to_le_bytes());
+        record.extend(self.crc.to_le_bytes());
+        record.extend(size32.to_le_bytes());
+        record.extend(size32.to_le_bytes());
+        record.extend((self.name.len() as u16).to_le_bytes());
+        record.extend((extra.len() as u16).to_le_bytes());
+        record.extend(0u16.to_le_bytes()); // 注释长度
+        record.extend(0u16.to_le_bytes()); // 起始分卷
+        record.extend(0u16.to_le_bytes()); // 内部属性
+        record.extend(self.external_attributes.to_le_bytes());
+        record.extend((self.offset.min(ZIP_U32_MAX) as u32).to_le_bytes());
+        record.extend(self.name.as_bytes());
+        record.extend(extra);
+        record
+    }
+}
+
+/// 追加一个扩展字段（标识 + 长度 + 数据）
+fn push_extra_field(extra: &mut Vec<u8>, id: u16, data: &[u8]) {
+    extra.extend(id.to_le_bytes());
+    extra.extend((data.len() as u16).to_le_bytes());
+    extra.extend(data);
+}
+
+/// 时间戳扩展字段：NTFS 字段保留完整的 FILETIME（Windows 资源管理器和 7-Zip 使用），
+/// "UT" 字段提供 Unix 秒（Info-ZIP 使用）；中央目录中的 "UT" 字段只含修改时间
+fn timestamp_extra(metadata: &EntryMetadata, local: bool) -> Vec<u8> {
+    let times = [
+        metadata.last_write_time,
+        metadata.last_access_time,
+        metadata.creation_time,
+    ];
+    let mut extra = Vec::new();
+    if times.iter().all(WimTimestamp::is_zero) {
+        return extra;
+    }
+
+    let mut ntfs = Vec::with_capacity(32);
+    ntfs.extend(0u32.to_le_bytes()); // 保留
+    ntfs.extend(1u16.to_le_bytes()); // 属性标记 1：三个时间
+    ntfs.extend(24u16.to_le_bytes());
+    for time in times {
+        ntfs.extend(time.filetime().to_le_bytes());
+    }
+    push_extra_field(&mut extra, ZIP_NTFS_EXTRA_ID, &ntfs);
+
+    let unix: Vec<Option<i32>> = times
+        .iter()
+        .map(|time| (!time.is_zero()).then(|| i32::try_from(time.unix_seconds()).ok())?)
+        .collect();
+    if unix[0].is_some() {
+        let flags = unix
+            .iter()
+            .enumerate()
+            .filter(|(_, time)| time.is_some())
+            .fold(0u8, |flags, (bit, _)| flags | (1 << bit));
+        let mut field = vec![flags];
+        let values = if local { &unix[..] } else { &unix[..1] };
+        for time in values.iter().flatten() {
+            field.extend(time.to_le_bytes());
+        }
+        push_extra_field(&mut extra, ZIP_UNIX_TIME_EXTRA_ID, &field);
+    }
+    extra
+}
+
+/// 转换为 MS-DOS 日期和时间（早于 1980 年时取 1980-01-01）
+fn dos_date_time(time: WimTimestamp) -> (u16, u16) {
+    let secs = time.unix_seconds();
+    let (year, month, day) = civil_from_days(secs.div_euclid(86_400));
+    if year < 1980 {
+        return (0, (1 << 5) | 1);
+    }
+    let year = year.min(2107) as u16;
+    let seconds = secs.rem_euclid(86_400) as u16;
+    let dos_time = ((seconds / 3600) << 11) | ((seconds % 3600 / 60) << 5) | (seconds % 60 / 2);
+    let dos_date = ((year - 1980) << 9) | ((month as u16) << 5) | day as u16;
+    (dos_time, dos_date)
+}
+
+/// 输出为 zip 归档（存储方式，不压缩）
+///
+/// 文件名使用 UTF-8，普通条目保留 DOS 属性；创建、访问和最后写入时间写入 NTFS 和 "UT"
+/// 扩展字段，DOS 时间取最后写入时间；
+/// 超过 4 GiB 的文件、偏移或超过 65535 个条目时自动使用 zip64 扩展。
+#[derive(Debug)]
+pub struct ZipTarget<W: Write> {
+    writer: W,
+    entries: Vec<ZipEntry>,
+    pending: Option<PendingFile>,
+    offset: u64,
+}
+
+impl<W: Write> ZipTarget<W> {
+    /// 写入到 `writer`
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer,
+            entries: Vec::new(),
+            pending: None,
+            offset: 0,
+        }
+    }
+
+    /// 取回底层写入器（应在释放完成即 [`ApplyTarget::finish`] 之后调用）
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+
+    fn write_all(&mut self, data: &[u8]) -> Result<()> {
+        self.writer.write_all(data).context("写入 zip 归档失败")?;
+        self.offset += data.len() as u64;
+        Ok(())
+    }
+
+    /// 结束上一个文件：写入数据描述符并记录 CRC 和大小
+    fn finish_pending(&mut self) -> Result<()> {
+        let Some(pending) = self.pending.take() else {
+            return Ok(());
+        };
+        pending.check_size()?;
+        let crc = !pending.crc;
+        let entry = self
+            .entries
+            .last_mut()
+            .expect("正在写入的 zip 条目必然已记录");
+        entry.crc = crc;
+        let zip64 = entry.zip64;
+
+        let mut descriptor = Vec::with_capacity(24);
+        descriptor.extend(ZIP_DATA_DESCRIPTOR_SIG.to_le_bytes());
+        descriptor.extend(crc.to_le_bytes());
+        if zip64 {
+            descriptor.extend(pending.size.to_le_bytes());
+            descriptor.extend(pending.size.to_le_bytes());
+        } else {
+            descriptor.extend((pending.size as u32).to_le_bytes());
+            descriptor.extend((pending.size as u32).to_le_bytes());
+        }
+        self.write_all(&descriptor)
+    }
+
+    /// 写入本地文件头并记录条目
+    fn add_entry(
+        &mut self,
+        name: String,
+        size: u64,
+        host: u16,
+        external_attributes: u32,
+        metadata: &EntryMetadata,
+    ) -> Result<()> {
+        self.finish_pending()?;
+        let (dos_time, dos_date) = dos_date_time(metadata.last_write_time);
+        let zip64 = size >= ZIP_U32_MAX;
+        let version = if zip64 { ZIP64_VERSION } else { ZIP_VERSION };
+
+        let mut header = Vec::with_capacity(30 + name.len() + 20);
+        header.extend(ZIP_LOCAL_HEADER_SIG.to_le_bytes());
+        header.extend(version.to_le_bytes());
+        header.extend(ZIP_FLAGS.to_le_bytes());
+        header.extend(0u16.to_le_bytes()); // 存储方式
+        header.extend(dos_time.to_le_bytes());
+        header.extend(dos_date.to_le_bytes());
+        header.extend(0u32.to_le_bytes()); // CRC 写在数据描述符中
+        let size_field: u32 = if zip64 { u32::MAX } else { 0 };
+        header.extend(size_field.to_le_bytes());
+        header.extend(size_field.to_le_bytes());
+
+        let mut extra = Vec::new();
+        if zip64 {
+            // 大小写在数据描述符中，这里置零
+            push_extra_field(&mut extra, ZIP64_EXTRA_ID, &[0u8; 16]);
+        }
+        extra.extend(timestamp_extra(metadata, true));
+        header.extend((name.len() as u16).to_le_bytes());
+        header.extend((extra.len() as u16).to_le_bytes());
+        header.extend(name.as_bytes());
+        header.extend(extra);
+
+        self.entries.push(ZipEntry {
+            name,
+            crc: 0,
+            size,
+            offset: self.offset,
+            dos_time,
+            dos_date,
+            host,
+            external_attributes,
+            zip64,
+            metadata: *metadata,
+        });
+        self.write_all(&header)
+    }
+}
+
+impl<W: Write> ApplyTarget for ZipTarget<W> {
+    fn create_dir(&mut self, path: &str, metadata: &EntryMetadata) -> Result<()> {
+        self.add_entry(
+            format!("{path}/"),
+            0,
+            ZIP_HOST_DOS,
+            metadata.attributes & 0xFF,
+            metadata,
+        )?;
+        self.pending = Some(PendingFile::new(0));
+        self.finish_pending()
+    }
+
+    fn create_file(
+        &mut self,
+        path: &str,
+        size: u64,
+        metadata: &EntryMetadata,
+    ) -> Result<Box<dyn Write + '_>> {
+        self.add_entry(
+            path.to_string(),
+            size,
+            ZIP_HOST_DOS,
+            metadata.attributes & 0xFF,
+            metadata,
+        )?;
+        let pending = self.pending.insert(PendingFile::new(size));
+        Ok(Box::new(EntryWriter {
+            writer: &mut self.writer,
+            pending,
+            offset: &mut self.offset,
+        }))
+    }
+
+    fn set_metadata(&mut self, _path: &str, _metadata: &EntryMetadata) -> Result<()> {
+        // 时间和属性已写在条目头中
+        Ok(())
+    }
+
+    fn symlink(&mut self, path: &str, target: &str, metadata: &EntryMetadata) -> Result<()> {
+        // Unix 符号链接：mode 0120777，内容为链接目标
+        self.add_entry(
+            path.to_string(),
+            target.len() as u64,
+            ZIP_HOST_UNIX,
+            0o120777 << 16,
+            metadata,
+        )?;
+        let pending = self.pending.insert(PendingFile::new(target.len() as u64));
+        EntryWriter {
+            writer: &mut self.writer,
+            pending,
+            offset: &mut self.offset,
+        }
+        .write_all(target.as_bytes())
+        .context("写入 zip 归档失败")?;
+        self.finish_pending()
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.finish_pending()?;
+
+        let directory_offset = self.offset;
+        let records: Vec<u8> = self
+            .entries
+            .iter()
+            .flat_map(|entry| entry.central_header())
+            .collect();
+        self.write_all(&records)?;
+        let directory_size = records.len() as u64;
+        let count = self.entries.len() as u64;
+
+        let needs_zip64 =
+            count >= 0xFFFF || directory_offset >= ZIP_U32_MAX || directory_size >= ZIP_U32_MAX;
+        let mut end = Vec::with_capacity(98);
+        if needs_zip64 {
+            let zip64_end_offset = self.offset;
+            end.extend(ZIP64_END_SIG.to_le_bytes());
+            end.extend(44u64.to_le_bytes());
+            end.extend(ZIP64_VERSION.to_le_bytes());
+            end.extend(ZIP64_VERSION.to_le_bytes());
+            end.extend(0u32.to_le_bytes());
+            end.extend(0u32.to_le_bytes());
+            end.extend(count.to_le_bytes());
+            end.extend(count.to_le_bytes());
+            end.extend(directory_size.to_le_bytes());
+            end.extend(directory_offset.to_le_bytes());
+
+            end.extend(ZIP64_LOCATOR_SIG.to_le_bytes());
+            end.extend(0u32.to_le_bytes());
+            end.extend(zip64_end_offset.to_le_bytes());
+            end.extend(1u32.to_le_bytes());
+        }
+        let count16 = count.min(0xFFFF) as u16;
+        end.extend(ZIP_END_SIG.to_le_bytes());
+        end.extend(0u16.to_le_bytes());
+        end.extend(0u16.to_le_bytes());
+        end.extend(count16.to_le_bytes());
+        end.extend(count16.to_le_bytes());
+        end.extend((directory_size.min(ZIP_U32_MAX) as u32).to_le_bytes());
+        end.extend((directory_offset.min(ZIP_U32_MAX) as u32).to_le_bytes());
+        end.extend(0u16.to_le_bytes());
+        self.write_all(&end)?;
+        self.writer.flush().context("写入 zip 归档失败")
+    }
+}
+
+impl WimParser {
+    /// 将镜像导出为 zip 归档，无需专门工具即可在 Windows 资源管理器中打开
+    ///
+    /// 文件以存储方式写入并保留创建、访问和最后写入时间，大文件和大归档自动使用 zip64。
+    /// 输出按顺序写入，`writer` 为文件时建议包装为 [`BufWriter`](std::io::BufWriter)。
+    pub fn export_image_as_zip<W: Write>(&mut self, index: u32, writer: W) -> Result<ApplyReport> {
+        let mut target = ZipTarget::new(writer);
+        self.apply_to(index, &mut target, &ApplyOptions::default())
+            .with_context(|| format!("导出镜像 {index} 为 zip 失败"))
+    }
+}
@@ -0,0 +1,247 @@
+use anyhow::Result;
+
+use crate::log::debug;
+use crate::pe::{coff_machine, PeMachine};
+use crate::winpe::{STARTNET_CMD, WINPESHL_INI};
+use crate::{Arch, WimParser};
+
+/// UEFI 启动加载器
+const WINLOAD_EFI: &str = r"\Windows\System32\boot\winload.efi";
+
+/// wimboot / iPXE 网络启动所需的文件（相对镜像根目录）
+const BOOT_FILES: [(&str, &str); 5] = [
+    (r"\Windows\Boot\PXE\bootmgr.exe", "bootmgr (BIOS)"),
+    (r"\Windows\Boot\EFI\bootmgfw.efi", "bootmgfw.efi (UEFI)"),
+    (r"\Windows\Boot\DVD\PCAT\BCD", "BCD (BIOS)"),
+    (r"\Windows\Boot\DVD\EFI\BCD", "BCD (UEFI)"),
+    (r"\Windows\Boot\DVD\PCAT\boot.sdi", "boot.sdi"),
+];
+
+/// 网络启动所需的单个文件
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BootFile {
+    /// 镜像内的路径
+    pub path: &'static str,
+    /// 用途说明
+    pub role: &'static str,
+    /// 是否存在于启动镜像中
+    pub present: bool,
+}
+
+/// wimboot / iPXE 脚本生成所需的启动信息
+#[derive(Debug, Clone)]
+pub struct WimbootInfo {
+    /// 文件头中的可引导镜像索引（0 表示未设置）
+    pub bootable_index: u32,
+    /// 实际检查的启动镜像索引
+    pub boot_image: Option<u32>,
+    /// 文件头是否引用了引导元数据资源
+    pub has_boot_metadata: bool,
+    /// 启动所需文件及其是否存在
+    pub required_files: Vec<BootFile>,
+}
+
+impl WimbootInfo {
+    /// 是否具备通过 wimboot 启动的条件（设置了可引导镜像且包含 bootmgr）
+    pub fn is_bootable(&self) -> bool {
+        self.bootable_index != 0
+            && self
+                .required_files
+                .iter()
+                .any(|file| file.present && file.role.starts_with("bootmgr"))
+    }
+
+    /// 缺失的文件
+    pub fn missing_files(&self) -> impl Iterator<Item = &BootFile> {
+        self.required_files.iter().filter(|file| !file.present)
+    }
+}
+
+/// boot.wim 校验发现的问题
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BootIssue {
+    /// 文件头未设置可引导镜像索引（DISM/Windows 安装程序需要该索引）
+    NoBootableIndex,
+    /// 文件中没有可检查的启动镜像
+    NoBootImage,
+    /// 缺少启动必需的文件
+    MissingFile(&'static str),
+    /// 既没有 winpeshl.ini 也没有 startnet.cmd，Windows PE 启动后没有可执行的 shell
+    MissingShellLauncher,
+    /// 镜像 XML 中的架构与启动加载器的 PE 机器类型不一致
+    ArchMismatch {
+        /// XML 中记录的架构
+        image: Arch,
+        /// winload.efi 的架构
+        loader: Arch,
+    },
+}
+
+impl std::fmt::Display for BootIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BootIssue::NoBootableIndex => f.write_str("未设置可引导镜像索引"),
+            BootIssue::NoBootImage => f.write_str("没有启动镜像"),
+            BootIssue::MissingFile(path) => write!(f, "缺少文件 {path}"),
+            BootIssue::MissingShellLauncher => f.write_str("缺少 winpeshl.ini 和 startnet.cmd"),
+            BootIssue::ArchMismatch { image, loader } => {
+                write!(f, "镜像架构 {image} 与 winload.efi 架构 {loader} 不一致")
+            }
+        }
+    }
+}
+
+/// boot.wim 启动关键文件校验结果
+#[derive(Debug, Clone)]
+pub struct BootValidation {
+    /// 检查的启动镜像索引
+    pub boot_image: Option<u32>,
+    /// 是否包含 winload.efi
+    pub has_winload_efi: bool,
+    /// 是否包含 winpeshl.ini
+    pub has_winpeshl_ini: bool,
+    /// 是否包含 startnet.cmd
+    pub has_startnet_cmd: bool,
+    /// 镜像 XML 中记录的架构
+    pub image_arch: Option<Arch>,
+    /// 从 winload.efi 的 PE 文件头读取的架构（无法读取时为 `None`）
+    pub loader_arch: Option<Arch>,
+    /// 发现的问题
+    pub issues: Vec<BootIssue>,
+}
+
+impl BootValidation {
+    /// 是否没有发现问题
+    pub fn is_valid(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+/// 从 PE 文件头读取机器类型对应的架构
+fn pe_machine_arch(image: &[u8]) -> Option<Arch> {
+    PeMachine::from_coff(coff_machine(image)?, false).arch()
+}
+
+impl WimParser {
+    /// 校验 boot.wim 的启动关键文件，便于在写入 U 盘之前发现损坏的启动镜像
+    ///
+    /// 检查可引导镜像（未设置时检查最后一个镜像）中是否包含 winload.efi、
+    /// winpeshl.ini 或 startnet.cmd，并比对镜像 XML 中的架构与 winload.efi 的机器类型。
+    pub fn validate_boot_wim(&mut self) -> Result<BootValidation> {
+        if self.images.is_empty() {
+            self.parse_full()?;
+        }
+
+        let header = self.read_header()?;
+        let bootable_index = header.bootable_image_index;
+        let image_count = header.image_count;
+        let boot_image = match bootable_index {
+            0 if image_count == 0 => None,
+            0 => Some(image_count),
+            index => Some(index),
+        };
+
+        let mut validation = BootValidation {
+            boot_image,
+            has_winload_efi: false,
+            has_winpeshl_ini: false,
+            has_startnet_cmd: false,
+            image_arch: None,
+            loader_arch: None,
+            issues: Vec::new(),
+        };
+        if bootable_index == 0 {
+            validation.issues.push(BootIssue::NoBootableIndex);
+        }
+        let Some(index) = boot_image else {
+            validation.issues.push(BootIssue::NoBootImage);
+            return Ok(validation);
+        };
+
+        let root = self.read_metadata_root(index)?;
+        let file_hash = |path: &str| {
+            root.find_path(path)
+                .filter(|entry| !entry.is_directory())
+                .map(|entry| entry.hash)
+        };
+        let winload_hash = file_hash(WINLOAD_EFI);
+        validation.has_winload_efi = winload_hash.is_some();
+        validation.has_winpeshl_ini = file_hash(WINPESHL_INI).is_some();
+        validation.has_startnet_cmd = file_hash(STARTNET_CMD).is_some();
+        validation.image_arch = self.get_image(index).and_then(|image| image.arch());
+
+        if let Some(hash) = winload_hash {
+            match self.read_stream(&hash) {
+                Ok(data) => validation.loader_arch = pe_machine_arch(&data),
+                Err(err) => debug!("无法读取 winload.efi: {}", err),
+            }
+        } else {
+            validation.issues.push(BootIssue::MissingFile(WINLOAD_EFI));
+        }
+
+        if !validation.has_winpeshl_ini && !validation.has_startnet_cmd {
+            validation.issues.push(BootIssue::MissingShellLauncher);
+        }
+
+        if let (Some(image), Some(loader)) = (validation.image_arch, validation.loader_arch) {
+            if image != loader {
+                validation
+                    .issues
+                    .push(BootIssue::ArchMismatch { image, loader });
+            }
+        }
+
+        debug!(
+            "boot.wim 校验 - 镜像: {:?}, 问题: {}",
+            boot_image,
+            validation.issues.len()
+        );
+        Ok(validation)
+    }
+
+    /// 收集 wimboot / iPXE 网络启动所需的信息
+    ///
+    /// 在可引导镜像中查找 bootmgr、BCD 和 boot.sdi；未设置可引导镜像时检查最后一个镜像
+    /// （boot.wim 中的安装程序镜像）。
+    pub fn wimboot_info(&mut self) -> Result<WimbootInfo> {
+        let header = self.read_header()?;
+        let bootable_index = header.bootable_image_index;
+        let has_boot_metadata = !header.boot_metadata_resource.is_absent();
+        let image_count = header.image_count;
+
+        let boot_image = match bootable_index {
+            0 if image_count == 0 => None,
+            0 => Some(image_count),
+            index => Some(index),
+        };
+
+        let root = match boot_image {
+            Some(index) => Some(self.read_metadata_root(index)?),
+            None => None,
+        };
+
+        let required_files = BOOT_FILES
+            .iter()
+            .map(|&(path, role)| BootFile {
+                path,
+                role,
+                present: root
+                    .as_ref()
+                    .and_then(|root| root.find_path(path))
+                    .is_some_and(|entry| !entry.is_directory()),
+            })
+            .collect();
+
+        debug!(
+            "启动信息 - 可引导镜像: {}, 检查镜像: {:?}, 引导元数据: {}",
+            bootable_index, boot_image, has_boot_metadata
+        );
+
+        Ok(WimbootInfo {
+            bootable_index,
+            boot_image,
+            has_boot_metadata,
+            required_files,
+        })
+    }
+}
@@ -0,0 +1,265 @@
+//! 离线可查询的 Windows 内部版本数据库：内部版本号 → 发行版本、可用版本和支持周期
+//!
+//! 内置表来自仓库中的 `data/builds.txt`，编译时嵌入。离线环境可以复制该文件、追加新的行，
+//! 再用 [`BuildDatabase::load`] 加载并 [`merge`](BuildDatabase::merge) 到内置表上，不必等待新版本发布。
+
+use anyhow::{Context, Result};
+use std::fmt;
+use std::path::Path;
+use std::sync::OnceLock;
+
+use crate::ImageInfo;
+
+/// 内置数据文件
+const BUILTIN_DATA: &str = include_str!("../data/builds.txt");
+
+/// 日期（公历，无时区）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct BuildDate {
+    /// 年
+    pub year: u16,
+    /// 月 (1-12)
+    pub month: u8,
+    /// 日 (1-31)
+    pub day: u8,
+}
+
+impl BuildDate {
+    /// 解析 `YYYY-MM-DD`
+    pub fn parse(text: &str) -> Option<Self> {
+        let mut parts = text.trim().splitn(3, '-');
+        let year = parts.next()?.parse().ok()?;
+        let month = parts.next()?.parse().ok()?;
+        let day = parts.next()?.parse().ok()?;
+        ((1..=12).contains(&month) && (1..=31).contains(&day)).then_some(Self { year, month, day })
+    }
+}
+
+impl fmt::Display for BuildDate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+/// 数据库中的一个发行版
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BuildInfo {
+    /// 内部版本号，例如 `22631`
+    pub build: u32,
+    /// 适用的最小修订号 (SPBUILD)，同一内部版本按修订号区分发行版时使用，否则为 0
+    pub sp_build_from: u32,
+    /// 产品名称，例如 `Windows 11`、`Windows Server 2022`
+    pub product: String,
+    /// 发行版本，例如 `23H2`
+    pub release: String,
+    /// 发布日期
+    pub released: Option<BuildDate>,
+    /// 支持结束日期（Home/Pro 等版本）
+    pub end_of_support: Option<BuildDate>,
+    /// Enterprise/Education 系列的支持结束日期（`None` 表示与 [`end_of_support`](Self::end_of_support) 相同）
+    pub enterprise_end_of_support: Option<BuildDate>,
+    /// 可用的版本 (EDITIONID)
+    pub editions: Vec<String>,
+}
+
+impl BuildInfo {
+    /// 显示名称，例如 `Windows 11 23H2`
+    pub fn display_name(&self) -> String {
+        format!("{} {}", self.product, self.release)
+    }
+
+    /// 是否提供该版本 (EDITIONID，不区分大小写)
+    pub fn has_edition(&self, edition_id: &str) -> bool {
+        self.editions
+            .iter()
+            .any(|edition| edition.eq_ignore_ascii_case(edition_id))
+    }
+
+    /// 指定版本的支持结束日期：Enterprise/Education 系列优先使用企业版日期
+    pub fn end_of_support_for(&self, edition_id: &str) -> Option<BuildDate> {
+        let enterprise = ["Enterprise", "Education", "IoTEnterprise"]
+            .iter()
+            .any(|prefix| edition_id.starts_with(prefix))
+            && !edition_id.starts_with("EnterpriseS")
+            && !edition_id.starts_with("IoTEnterpriseS");
+        if enterprise {
+            self.enterprise_end_of_support.or(self.end_of_support)
+        } else {
+            self.end_of_support
+        }
+    }
+
+    /// 是否为服务器发行版
+    pub fn is_server(&self) -> bool {
+        self.product.contains("Server")
+    }
+
+    fn parse_line(line: &str) -> Option<Self> {
+        let fields: Vec<&str> = line.split('|').map(str::trim).collect();
+        let [build, sp_build_from, product, release, released, end_of_support, enterprise, editions] =
+            fields.as_slice()
+        else {
+            return None;
+        };
+        let date = |text: &str| match text {
+            "" | "-" => Some(None),
+            text => BuildDate::parse(text).map(Some),
+        };
+        if product.is_empty() || release.is_empty() {
+            return None;
+        }
+        Some(Self {
+            build: build.parse().ok()?,
+            sp_build_from: sp_build_from.parse().ok()?,
+            product: product.to_string(),
+            release: release.to_string(),
+            released: date(released)?,
+            end_of_support: date(end_of_support)?,
+            enterprise_end_of_support: date(enterprise)?,
+            editions: editions
+                .split(',')
+                .map(str::trim)
+                .filter(|edition| !edition.is_empty() && *edition != "-")
+                .map(str::to_string)
+                .collect(),
+        })
+    }
+}
+
+/// 内部版本数据库
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BuildDatabase {
+    /// 按内部版本号和修订号排序；同一键的多行保持数据文件中的顺序
+    entries: Vec<BuildInfo>,
+}
+
+impl BuildDatabase {
+    /// 空数据库
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 编译时嵌入的内置数据库
+    pub fn builtin() -> Self {
+        Self::parse(BUILTIN_DATA).expect("内置内部版本数据无效")
+    }
+
+    /// 解析数据文件文本
+    ///
+    /// 每行一个发行版，以 `|` 分隔 8 个字段：内部版本号、起始修订号、产品、发行版本、
+    /// 发布日期、支持结束日期、企业版支持结束日期、逗号分隔的 EDITIONID。
+    /// 日期为 `YYYY-MM-DD`，未知时写 `-`。空行和以 `#` 开头的行被忽略。
+    pub fn parse(text: &str) -> Result<Self> {
+        let mut database = Self::new();
+        let text = text.strip_prefix('\u{feff}').unwrap_or(text);
+        for (number, line) in text.lines().enumerate() {
+            let line = line.trim_end_matches('\r');
+            if line.trim().is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let entry = BuildInfo::parse_line(line).ok_or_else(|| {
+                anyhow::anyhow!("第 {} 行不是有效的内部版本记录: {}", number + 1, line)
+            })?;
+            database.entries.push(entry);
+        }
+        database.sort();
+        Ok(database)
+    }
+
+    /// 从数据文件读取
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("无法读取内部版本数据文件: {}", path.display()))?;
+        Self::parse(&text).with_context(|| format!("无法解析内部版本数据文件: {}", path.display()))
+    }
+
+    /// 合并另一个数据库：内部版本号、起始修订号和产品都相同的记录被替换，其余追加
+    pub fn merge(&mut self, other: BuildDatabase) {
+        for entry in other.entries {
+            match self.entries.iter_mut().find(|existing| {
+                existing.build == entry.build
+                    && existing.sp_build_from == entry.sp_build_from
+                    && existing.product == entry.product
+            }) {
+                Some(existing) => *existing = entry,
+                None => self.entries.push(entry),
+            }
+        }
+        self.sort();
+    }
+
+    /// 所有记录
+    pub fn entries(&self) -> &[BuildInfo] {
+        &self.entries
+    }
+
+    /// 按内部版本号和修订号查找（修订号为 `None` 时按 0 处理）
+    ///
+    /// 同一内部版本有多个产品（客户端、LTSC、服务器）时返回数据文件中的第一行，
+    /// 需要区分时使用 [`lookup_edition`](Self::lookup_edition)。
+    pub fn lookup(&self, build: u32, sp_build: Option<u32>) -> Option<&BuildInfo> {
+        self.best_match(build, sp_build, |_| true)
+    }
+
+    /// 按内部版本号、修订号和 EDITIONID 查找提供该版本的发行版
+    pub fn lookup_edition(
+        &self,
+        build: u32,
+        sp_build: Option<u32>,
+        edition_id: &str,
+    ) -> Option<&BuildInfo> {
+        self.best_match(build, sp_build, |entry| entry.has_edition(edition_id))
+    }
+
+    /// 按镜像的 `BUILD`、`SPBUILD` 和 `EDITIONID` 查找
+    ///
+    /// 没有记录提供镜像的 EDITIONID 时，按安装类型（`Server` 或客户端）选择同一内部版本的记录。
+    pub fn lookup_image(&self, image: &ImageInfo) -> Option<&BuildInfo> {
+        let build = image.build?;
+        if let Some(entry) = image
+            .edition_id
+            .as_deref()
+            .and_then(|edition| self.lookup_edition(build, image.sp_build, edition))
+        {
+            return Some(entry);
+        }
+        let server = [&image.installation_type, &image.product_type]
+            .into_iter()
+            .flatten()
+            .any(|value| value.contains("Server"));
+        self.best_match(build, image.sp_build, |entry| entry.is_server() == server)
+            .or_else(|| self.lookup(build, image.sp_build))
+    }
+
+    /// 满足条件且起始修订号不超过 `sp_build` 的记录中，起始修订号最大的第一行
+    fn best_match<F: Fn(&BuildInfo) -> bool>(
+        &self,
+        build: u32,
+        sp_build: Option<u32>,
+        filter: F,
+    ) -> Option<&BuildInfo> {
+        let sp_build = sp_build.unwrap_or(0);
+        self.entries
+            .iter()
+            .filter(|entry| entry.build == build && entry.sp_build_from <= sp_build)
+            .filter(|entry| filter(entry))
+            .fold(None, |best: Option<&BuildInfo>, entry| match best {
+                Some(best) if best.sp_build_from >= entry.sp_build_from => Some(best),
+                _ => Some(entry),
+            })
+    }
+
+    fn sort(&mut self) {
+        self.entries
+            .sort_by_key(|entry| (entry.build, entry.sp_build_from));
+    }
+}
+
+/// 在内置数据库中按内部版本号查找，例如 `lookup_build(22631)` 返回 Windows 11 23H2
+pub fn lookup_build(build: u32) -> Option<&'static BuildInfo> {
+    static BUILTIN: OnceLock<BuildDatabase> = OnceLock::new();
+    BUILTIN
+        .get_or_init(BuildDatabase::builtin)
+        .lookup(build, None)
+}
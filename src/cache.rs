@@ -0,0 +1,218 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::SystemTime;
+
+use crate::lookup_table::LookupTableEntry;
+use crate::{ImageInfo, ResourceLimits, Warning};
+
+/// 缓存键：WIM GUID + 分卷序号 + 文件大小 + 文件修改时间
+///
+/// 拆分 WIM (.swm) 的各个分卷共用同一个 GUID，修改时间通常也相同，需要按分卷序号和文件大小区分。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CacheKey {
+    /// WIM 文件头中的 GUID
+    pub guid: [u8; 16],
+    /// 文件头中的分卷序号（从 1 开始）
+    pub part_number: u16,
+    /// 文件大小
+    pub file_size: u64,
+    /// 文件修改时间
+    pub mtime: SystemTime,
+}
+
+/// 镜像信息和解析 XML 时产生的警告
+type CachedImages = (Arc<Vec<ImageInfo>>, Arc<[Warning]>);
+
+/// 缓存的解析结果
+#[derive(Debug, Clone, Default)]
+struct CachedWim {
+    /// 镜像信息和解析 XML 时产生的警告（命中时重放，严格模式据此报错）
+    images: Option<CachedImages>,
+    lookup_table: Option<Arc<Vec<LookupTableEntry>>>,
+}
+
+impl CachedWim {
+    /// 估算占用的内存字节数（结构体大小加字符串内容）
+    fn approx_bytes(&self) -> u64 {
+        let images = self.images.as_ref().map_or(0, |(images, _)| {
+            images
+                .iter()
+                .map(|image| {
+                    std::mem::size_of::<ImageInfo>() + image.name.len() + image.description.len()
+                })
+                .sum()
+        });
+        let lookup_table = self.lookup_table.as_deref().map_or(0, |table| {
+            table.len() * std::mem::size_of::<LookupTableEntry>()
+        });
+        (images + lookup_table) as u64
+    }
+}
+
+/// 缓存命中统计
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct CacheStats {
+    /// 命中次数
+    pub hits: u64,
+    /// 未命中次数
+    pub misses: u64,
+    /// 当前条目数
+    pub entries: usize,
+    /// 缓存内容估算占用的字节数
+    pub bytes: u64,
+}
+
+#[derive(Debug, Default)]
+struct CacheInner {
+    entries: HashMap<CacheKey, CachedWim>,
+    /// 插入顺序，用于超出容量时淘汰最早的条目
+    order: VecDeque<CacheKey>,
+}
+
+/// 可在多个 [`WimParser`](crate::WimParser) 之间共享的并发元数据缓存
+///
+/// 以 [`CacheKey`]（GUID、分卷序号、文件大小和修改时间）为键缓存镜像信息、解析警告和偏移表，批量索引大量文件时，
+/// 内容相同的副本只需解析一次。通过 `Arc<WimCatalogCache>` 在线程间共享。
+#[derive(Debug, Default)]
+pub struct WimCatalogCache {
+    inner: RwLock<CacheInner>,
+    max_entries: Option<usize>,
+    max_bytes: Option<u64>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl WimCatalogCache {
+    /// 创建不限容量的缓存
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 创建最多保存 `max_entries` 个 WIM 的缓存，超出时淘汰最早加入的条目
+    pub fn with_max_entries(max_entries: usize) -> Self {
+        Self {
+            max_entries: Some(max_entries),
+            ..Self::default()
+        }
+    }
+
+    /// 创建受 [`ResourceLimits::max_memory`] 约束的缓存：缓存内容的估算大小超出时淘汰最早加入的条目
+    ///
+    /// 单个条目本身超出限制时不会被缓存。
+    pub fn with_limits(limits: &ResourceLimits) -> Self {
+        Self {
+            max_bytes: limits.max_memory,
+            ..Self::default()
+        }
+    }
+
+    /// 命中统计
+    pub fn stats(&self) -> CacheStats {
+        let inner = self.read();
+        CacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            entries: inner.entries.len(),
+            bytes: inner.entries.values().map(CachedWim::approx_bytes).sum(),
+        }
+    }
+
+    /// 当前缓存的 WIM 数量
+    pub fn len(&self) -> usize {
+        self.read().entries.len()
+    }
+
+    /// 缓存是否为空
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// 是否包含指定键
+    pub fn contains(&self, key: &CacheKey) -> bool {
+        self.read().entries.contains_key(key)
+    }
+
+    /// 清空缓存
+    pub fn clear(&self) {
+        let mut inner = self.write();
+        inner.entries.clear();
+        inner.order.clear();
+    }
+
+    pub(crate) fn get_images(&self, key: &CacheKey) -> Option<CachedImages> {
+        let images = self
+            .read()
+            .entries
+            .get(key)
+            .and_then(|entry| entry.images.clone());
+        self.record(images.is_some());
+        images
+    }
+
+    pub(crate) fn get_lookup_table(&self, key: &CacheKey) -> Option<Arc<Vec<LookupTableEntry>>> {
+        let table = self
+            .read()
+            .entries
+            .get(key)
+            .and_then(|entry| entry.lookup_table.clone());
+        self.record(table.is_some());
+        table
+    }
+
+    pub(crate) fn put_images(
+        &self,
+        key: CacheKey,
+        images: Arc<Vec<ImageInfo>>,
+        warnings: Arc<[Warning]>,
+    ) {
+        self.update(key, |entry| entry.images = Some((images, warnings)));
+    }
+
+    pub(crate) fn put_lookup_table(&self, key: CacheKey, table: Arc<Vec<LookupTableEntry>>) {
+        self.update(key, |entry| entry.lookup_table = Some(table));
+    }
+
+    fn update<F: FnOnce(&mut CachedWim)>(&self, key: CacheKey, f: F) {
+        let mut inner = self.write();
+        if !inner.entries.contains_key(&key) {
+            if let Some(max) = self.max_entries {
+                while inner.entries.len() >= max.max(1) {
+                    match inner.order.pop_front() {
+                        Some(oldest) => {
+                            inner.entries.remove(&oldest);
+                        }
+                        None => break,
+                    }
+                }
+            }
+            inner.order.push_back(key);
+        }
+        f(inner.entries.entry(key).or_default());
+
+        if let Some(max) = self.max_bytes {
+            let mut total: u64 = inner.entries.values().map(CachedWim::approx_bytes).sum();
+            while total > max {
+                let Some(oldest) = inner.order.pop_front() else {
+                    break;
+                };
+                if let Some(evicted) = inner.entries.remove(&oldest) {
+                    total -= evicted.approx_bytes();
+                }
+            }
+        }
+    }
+
+    fn record(&self, hit: bool) {
+        let counter = if hit { &self.hits } else { &self.misses };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn read(&self) -> std::sync::RwLockReadGuard<'_, CacheInner> {
+        self.inner.read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn write(&self) -> std::sync::RwLockWriteGuard<'_, CacheInner> {
+        self.inner.write().unwrap_or_else(|e| e.into_inner())
+    }
+}
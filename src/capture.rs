@@ -0,0 +1,312 @@
+//! 从本地目录捕获镜像，支持以上一次捕获的 WIM 为基准的增量捕获
+//!
+//! 上一次捕获的 WIM 本身就是清单：元数据中记录了每个文件的最后写入时间和数据流 SHA-1，
+//! 偏移表中记录了数据流大小。大小和最后写入时间都未变化的文件直接沿用记录的 SHA-1，
+//! 只有变化的文件需要重新计算摘要，重复捕获同一黄金镜像时可省去大部分计算。
+
+use anyhow::{Context, Result};
+use sha1::{Digest as _, Sha1};
+use std::collections::HashMap;
+use std::fs::{self, Metadata};
+use std::path::Path;
+use std::time::SystemTime;
+
+use crate::lock::LockPolicy;
+use crate::log::{debug, info};
+use crate::metadata::{self, DirEntry, FILE_ATTRIBUTE_DIRECTORY};
+use crate::writer::WimWriter;
+use crate::xml_tree::escape_xml;
+use crate::{WimParser, WimTimestamp, WriteSettings};
+
+/// 普通文件属性 (FILE_ATTRIBUTE_NORMAL)
+const FILE_ATTRIBUTE_NORMAL: u32 = 0x0000_0080;
+
+/// 捕获结果
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CaptureReport {
+    /// 镜像名称
+    pub image_name: String,
+    /// 目录数（不含根目录）
+    pub dir_count: u32,
+    /// 文件数
+    pub file_count: u32,
+    /// 文件总字节数
+    pub total_bytes: u64,
+    /// 重新计算摘要的文件数（新增或大小、最后写入时间变化的文件）
+    pub hashed_files: u32,
+    /// 重新计算摘要的字节数
+    pub hashed_bytes: u64,
+    /// 沿用基准 WIM 中摘要的文件数
+    pub reused_files: u32,
+    /// 沿用摘要的字节数
+    pub reused_bytes: u64,
+    /// 跳过的符号链接等特殊文件数
+    pub skipped: u32,
+}
+
+/// 基准 WIM 中记录的文件状态
+#[derive(Debug, Clone, Copy)]
+struct ManifestEntry {
+    size: u64,
+    last_write_time: WimTimestamp,
+    hash: [u8; 20],
+}
+
+/// 捕获过程中的状态
+struct Capture<'a> {
+    writer: WimWriter,
+    manifest: &'a HashMap<String, ManifestEntry>,
+    report: CaptureReport,
+}
+
+/// 文件时间转换为 WIM 时间戳（无法获取时使用 `fallback`）
+fn timestamp(time: std::io::Result<SystemTime>, fallback: WimTimestamp) -> WimTimestamp {
+    time.ok()
+        .and_then(WimTimestamp::from_system_time)
+        .unwrap_or(fallback)
+}
+
+/// 由文件系统信息创建目录项
+fn new_entry(name: &str, attributes: u32, info: &Metadata) -> DirEntry {
+    let last_write_time = timestamp(info.modified(), WimTimestamp::default());
+    DirEntry {
+        attributes,
+        security_id: -1,
+        creation_time: timestamp(info.created(), last_write_time),
+        last_access_time: timestamp(info.accessed(), last_write_time),
+        last_write_time,
+        hash: [0u8; 20],
+        reparse_tag: 0,
+        rp_flags: 0,
+        hard_link_group_id: 0,
+        name: name.to_string(),
+        name_utf16: None,
+        short_name: String::new(),
+        streams: Vec::new(),
+        children: Vec::new(),
+    }
+}
+
+/// `<HIGHPART>` / `<LOWPART>` 形式的时间节点
+fn xml_time(tag: &str, time: WimTimestamp) -> String {
+    format!(
+        "<{tag}><HIGHPART>0x{:08X}</HIGHPART><LOWPART>0x{:08X}</LOWPART></{tag}>",
+        time.high_part(),
+        time.low_part()
+    )
+}
+
+impl Capture<'_> {
+    /// 捕获目录的内容（按名称排序），`rel` 为目录在镜像中的路径（小写，`/` 分隔）
+    fn capture_dir(&mut self, dir: &Path, rel: &str, entry: &mut DirEntry) -> Result<()> {
+        let mut children: Vec<_> = fs::read_dir(dir)
+            .with_context(|| format!("无法读取目录: {}", dir.display()))?
+            .collect::<std::io::Result<_>>()
+            .with_context(|| format!("无法读取目录: {}", dir.display()))?;
+        children.sort_by_key(|child| child.file_name());
+
+        for child in children {
+            let path = child.path();
+            let name = child.file_name().to_string_lossy().into_owned();
+            let child_rel = if rel.is_empty() {
+                name.to_lowercase()
+            } else {
+                format!("{rel}/{}", name.to_lowercase())
+            };
+            let info = fs::symlink_metadata(&path)
+                .with_context(|| format!("无法读取文件信息: {}", path.display()))?;
+
+            if info.is_dir() {
+                let mut sub = new_entry(&name, FILE_ATTRIBUTE_DIRECTORY, &info);
+                self.capture_dir(&path, &child_rel, &mut sub)?;
+                self.report.dir_count += 1;
+                entry.children.push(sub);
+            } else if info.is_file() {
+                let mut file = new_entry(&name, FILE_ATTRIBUTE_NORMAL, &info);
+                file.hash = self.capture_file(&path, &child_rel, &file, info.len())?;
+                self.report.file_count += 1;
+                self.report.total_bytes += info.len();
+                entry.children.push(file);
+            } else {
+                debug!("跳过特殊文件: {}", path.display());
+                self.report.skipped += 1;
+            }
+        }
+        Ok(())
+    }
+
+    /// 写入文件的数据流，返回其 SHA-1
+    fn capture_file(
+        &mut self,
+        path: &Path,
+        rel: &str,
+        entry: &DirEntry,
+        size: u64,
+    ) -> Result<[u8; 20]> {
+        if size == 0 {
+            return Ok([0u8; 20]);
+        }
+
+        let unchanged = self
+            .manifest
+            .get(rel)
+            .filter(|old| old.size == size && old.last_write_time == entry.last_write_time);
+        if let Some(old) = unchanged {
+            // 内容相同的数据流已写入时不必再读取文件
+            if self.writer.add_stream_ref(&old.hash) {
+                self.report.reused_files += 1;
+                self.report.reused_bytes += size;
+                return Ok(old.hash);
+            }
+            let data =
+                fs::read(path).with_context(|| format!("无法读取文件: {}", path.display()))?;
+            if data.len() as u64 == size {
+                self.writer.add_stream(old.hash, &data)?;
+                self.report.reused_files += 1;
+                self.report.reused_bytes += size;
+                return Ok(old.hash);
+            }
+            debug!("文件在捕获期间发生变化，重新计算摘要: {}", path.display());
+            return self.hash_and_add(&data);
+        }
+
+        let data = fs::read(path).with_context(|| format!("无法读取文件: {}", path.display()))?;
+        self.hash_and_add(&data)
+    }
+
+    fn hash_and_add(&mut self, data: &[u8]) -> Result<[u8; 20]> {
+        let hash: [u8; 20] = Sha1::digest(data).into();
+        self.writer.add_stream(hash, data)?;
+        self.report.hashed_files += 1;
+        self.report.hashed_bytes += data.len() as u64;
+        Ok(hash)
+    }
+}
+
+/// 读取基准 WIM 最后一个镜像的文件状态，返回镜像名称和清单
+fn load_manifest(base_wim: &Path) -> Result<(String, HashMap<String, ManifestEntry>)> {
+    let mut base = WimParser::new(base_wim)?;
+    base.parse_full()?;
+    let index = base.images.len() as u32;
+    let name = base
+        .get_image(index)
+        .map(|image| image.name.clone())
+        .ok_or_else(|| anyhow::anyhow!("基准 WIM 中没有镜像: {}", base_wim.display()))?;
+    let sizes: HashMap<[u8; 20], u64> = base
+        .read_lookup_table()?
+        .iter()
+        .filter(|entry| !entry.is_metadata())
+        .map(|entry| (entry.hash, entry.resource.original_size))
+        .collect();
+    let root = base.read_metadata_root(index)?;
+
+    let mut manifest = HashMap::new();
+    root.walk_with_path(&mut |path, entry| {
+        if entry.is_directory() || entry.is_reparse_point() {
+            return;
+        }
+        let size = match entry.hash {
+            hash if hash == [0u8; 20] => Some(0),
+            hash => sizes.get(&hash).copied(),
+        };
+        if let Some(size) = size {
+            manifest.insert(
+                path.to_lowercase(),
+                ManifestEntry {
+                    size,
+                    last_write_time: entry.last_write_time,
+                    hash: entry.hash,
+                },
+            );
+        }
+    });
+    debug!(
+        "基准 WIM {} 镜像 {}: {} 个文件",
+        base_wim.display(),
+        index,
+        manifest.len()
+    );
+    Ok((name, manifest))
+}
+
+fn capture_with(
+    dir: &Path,
+    out: &Path,
+    name: String,
+    manifest: &HashMap<String, ManifestEntry>,
+) -> Result<CaptureReport> {
+    let info = fs::metadata(dir).with_context(|| format!("无法读取捕获目录: {}", dir.display()))?;
+    if !info.is_dir() {
+        return Err(anyhow::anyhow!("捕获源不是目录: {}", dir.display()));
+    }
+
+    let mut root = new_entry("", FILE_ATTRIBUTE_DIRECTORY, &info);
+    let mut capture = Capture {
+        writer: WimWriter::create(out, WriteSettings::default(), LockPolicy::default())?,
+        manifest,
+        report: CaptureReport {
+            image_name: name,
+            ..CaptureReport::default()
+        },
+    };
+    capture.capture_dir(dir, "", &mut root)?;
+
+    let encoded = metadata::encode_metadata_resource(&root);
+    capture
+        .writer
+        .add_metadata(Sha1::digest(&encoded).into(), &encoded)?;
+
+    let report = capture.report;
+    let now = WimTimestamp::from_system_time(SystemTime::now()).unwrap_or_default();
+    let xml = format!(
+        "<WIM><TOTALBYTES>{bytes}</TOTALBYTES><IMAGE INDEX=\"1\"><DIRCOUNT>{}</DIRCOUNT>\
+         <FILECOUNT>{}</FILECOUNT><TOTALBYTES>{bytes}</TOTALBYTES>\
+         <HARDLINKBYTES>0</HARDLINKBYTES>{}{}<NAME>{name}</NAME>\
+         <DISPLAYNAME>{name}</DISPLAYNAME></IMAGE></WIM>",
+        report.dir_count,
+        report.file_count,
+        xml_time("CREATIONTIME", now),
+        xml_time("LASTMODIFICATIONTIME", now),
+        bytes = report.total_bytes,
+        name = escape_xml(&report.image_name),
+    );
+    capture.writer.finish(&xml)?;
+
+    info!(
+        "捕获 {} 到 {}: {} 个文件，重新计算摘要 {} 个，沿用 {} 个",
+        dir.display(),
+        out.display(),
+        report.file_count,
+        report.hashed_files,
+        report.reused_files
+    );
+    Ok(report)
+}
+
+/// 将目录完整捕获为单镜像 WIM（镜像名称为目录名），计算每个文件的摘要
+///
+/// 只捕获普通文件和目录（未压缩），符号链接等特殊文件被跳过；文件时间保留到元数据中，
+/// 供之后的 [`capture_incremental`] 比较。
+pub fn capture(dir: impl AsRef<Path>, out: impl AsRef<Path>) -> Result<CaptureReport> {
+    let dir = dir.as_ref();
+    let name = dir
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    capture_with(dir, out.as_ref(), name, &HashMap::new())
+}
+
+/// 以上一次捕获的 `base_wim` 为基准，增量捕获目录为单镜像 WIM
+///
+/// 大小和最后写入时间与基准 WIM 最后一个镜像中记录的相同的文件沿用记录的 SHA-1，不重新计算；
+/// 其余文件重新计算摘要。镜像名称沿用基准镜像。`out` 可以与 `base_wim` 相同，
+/// 基准在写入前已读取完毕。
+pub fn capture_incremental(
+    dir: impl AsRef<Path>,
+    base_wim: impl AsRef<Path>,
+    out: impl AsRef<Path>,
+) -> Result<CaptureReport> {
+    let (name, manifest) = load_manifest(base_wim.as_ref())
+        .with_context(|| format!("无法读取基准 WIM: {}", base_wim.as_ref().display()))?;
+    capture_with(dir.as_ref(), out.as_ref(), name, &manifest)
+}
@@ -0,0 +1,162 @@
+//! 签名扫描（数据恢复）：在原始磁盘镜像或任意数据块中查找 `MSWIM` 签名，
+//! 校验候选文件头，找出嵌入或残留的 WIM（例如被删除的恢复分区中的 Winre.wim）
+
+use anyhow::{Context, Result};
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+
+use crate::format::{self, WIM_HEADER_DISK_SIZE, WIM_HEADER_MIN_SIZE, WIM_SIGNATURE};
+use crate::log::debug;
+use crate::{WimHeader, WimParser};
+
+/// 每次扫描读取的字节数
+const SCAN_BLOCK: usize = 1024 * 1024;
+
+/// 扫描找到的 WIM
+#[derive(Debug, Clone)]
+pub struct CarvedWim {
+    /// 文件头在数据中的偏移
+    pub offset: u64,
+    /// 文件头
+    pub header: WimHeader,
+    /// 文件头引用的资源的最远结束位置（相对文件头），即 WIM 的最小长度
+    pub extent: u64,
+    /// 数据是否包含到 [`extent`](Self::extent) 为止的全部字节（恢复的数据可能被截断）
+    pub complete: bool,
+}
+
+impl CarvedWim {
+    /// 以找到的偏移打开扫描所用的文件（`path` 必须是扫描时读取的同一文件或磁盘设备）
+    pub fn open<P: AsRef<Path>>(&self, path: P) -> Result<WimParser> {
+        WimParser::new_at(path, self.offset)
+    }
+}
+
+/// 签名扫描器，逐个返回通过校验的 WIM（由 [`carve`] 创建）
+#[derive(Debug)]
+pub struct Carver<R> {
+    reader: R,
+    len: u64,
+    next: u64,
+    block: Vec<u8>,
+    done: bool,
+}
+
+/// 扫描 `reader` 中的所有 WIM 文件头
+///
+/// 候选签名处的文件头大小须为 208 字节且引用了 XML 数据资源，以排除偶然出现的签名字节。
+/// 找到的 WIM 之间不互相排除：扫描从每个签名之后继续，嵌套的 WIM 也会被找到。
+pub fn carve<R: Read + Seek>(mut reader: R) -> Result<Carver<R>> {
+    let len = reader.seek(SeekFrom::End(0)).context("无法确定数据长度")?;
+    Ok(Carver {
+        reader,
+        len,
+        next: 0,
+        block: vec![0u8; SCAN_BLOCK + WIM_SIGNATURE.len() - 1],
+        done: false,
+    })
+}
+
+/// 读取直到填满缓冲区或到达末尾，返回读取的字节数
+fn read_full<R: Read>(reader: &mut R, buffer: &mut [u8]) -> std::io::Result<usize> {
+    let mut filled = 0;
+    while filled < buffer.len() {
+        match reader.read(&mut buffer[filled..]) {
+            Ok(0) => break,
+            Ok(read) => filled += read,
+            Err(err) if err.kind() == std::io::ErrorKind::Interrupted => {}
+            Err(err) => return Err(err),
+        }
+    }
+    Ok(filled)
+}
+
+impl<R: Read + Seek> Carver<R> {
+    /// 数据总长度
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    /// 数据是否为空
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// 校验 `offset` 处的候选文件头
+    fn candidate(&mut self, offset: u64) -> Result<Option<CarvedWim>> {
+        let mut buffer = [0u8; WIM_HEADER_MIN_SIZE];
+        self.reader.seek(SeekFrom::Start(offset))?;
+        if read_full(&mut self.reader, &mut buffer)? < buffer.len() {
+            return Ok(None);
+        }
+        let Ok(header) = format::parse_header(&buffer) else {
+            return Ok(None);
+        };
+        if header.header_size as usize != WIM_HEADER_DISK_SIZE
+            || header.xml_data_resource.is_absent()
+        {
+            return Ok(None);
+        }
+        let extent = header
+            .resources()
+            .filter(|(_, resource)| !resource.is_absent())
+            .map(|(_, resource)| resource.byte_range().end)
+            .max()
+            .unwrap_or(WIM_HEADER_DISK_SIZE as u64);
+        let complete = offset.saturating_add(extent) <= self.len;
+        debug!(
+            "在偏移 {} 处找到 WIM 文件头，长度至少 {} 字节{}",
+            offset,
+            extent,
+            if complete { "" } else { "（已截断）" }
+        );
+        Ok(Some(CarvedWim {
+            offset,
+            header,
+            extent,
+            complete,
+        }))
+    }
+
+    fn scan(&mut self) -> Result<Option<CarvedWim>> {
+        while !self.done {
+            let block_start = self.next;
+            self.reader.seek(SeekFrom::Start(block_start))?;
+            let len = read_full(&mut self.reader, &mut self.block)?;
+            if len < self.block.len() {
+                self.done = true;
+            }
+            let candidates: Vec<u64> = self.block[..len]
+                .windows(WIM_SIGNATURE.len())
+                .enumerate()
+                .filter(|(_, window)| *window == WIM_SIGNATURE)
+                .map(|(pos, _)| block_start + pos as u64)
+                .collect();
+            // 块末尾不足一个签名长度的字节留到下一块重新扫描
+            self.next = block_start + len.min(SCAN_BLOCK) as u64;
+
+            for offset in candidates {
+                if let Some(carved) = self.candidate(offset)? {
+                    self.next = offset + 1;
+                    self.done = false;
+                    return Ok(Some(carved));
+                }
+            }
+        }
+        Ok(None)
+    }
+}
+
+impl<R: Read + Seek> Iterator for Carver<R> {
+    type Item = Result<CarvedWim>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.scan() {
+            Ok(carved) => carved.map(Ok),
+            Err(err) => {
+                self.done = true;
+                Some(Err(err))
+            }
+        }
+    }
+}
@@ -0,0 +1,331 @@
+//! 多个 WIM 文件的目录：汇总文件标识、镜像、数据流摘要和（可选的）文件列表，
+//! 启用 `sqlite` 功能时可导出为可查询的 SQLite 数据库
+
+use anyhow::{Context, Result};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+#[cfg(feature = "sqlite")]
+use crate::fmt::{format_guid, format_hash};
+use crate::log::info;
+#[cfg(feature = "sqlite")]
+use crate::ResourceFlags;
+use crate::{Compression, ImageInfo, WimParser};
+
+/// 数据流（偏移表中的一项）
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CatalogStream {
+    /// 内容的 SHA-1
+    pub hash: [u8; 20],
+    /// 存储大小（压缩后）
+    pub size: u64,
+    /// 原始大小
+    pub original_size: u64,
+    /// 引用计数
+    pub ref_count: u32,
+    /// 所在分卷号
+    pub part_number: u16,
+    /// 资源标志（`ResourceFlags`）
+    pub flags: u8,
+}
+
+/// 镜像中的文件或目录
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CatalogFile {
+    /// 镜像索引
+    pub image_index: u32,
+    /// 相对镜像根目录的路径（`/` 分隔）
+    pub path: String,
+    /// 未命名数据流的大小（目录为 0）
+    pub size: u64,
+    /// 未命名数据流的 SHA-1（目录和空文件为 `None`）
+    pub hash: Option<[u8; 20]>,
+    /// 文件属性
+    pub attributes: u32,
+}
+
+/// 目录中的一个 WIM 文件
+#[derive(Debug, Clone)]
+pub struct CatalogWim {
+    /// 文件路径
+    pub path: PathBuf,
+    /// 文件大小
+    pub file_size: u64,
+    /// 文件头中的 GUID
+    pub guid: [u8; 16],
+    /// 段号
+    pub segment_number: u16,
+    /// 段总数
+    pub total_segments: u16,
+    /// 压缩格式
+    pub compression: Compression,
+    /// 镜像信息
+    pub images: Vec<ImageInfo>,
+    /// 偏移表中的所有数据流
+    pub streams: Vec<CatalogStream>,
+    /// 所有镜像的文件列表（仅在 [`Catalog::with_files`] 启用时收集）
+    pub files: Vec<CatalogFile>,
+}
+
+/// WIM 文件目录
+#[derive(Debug, Clone, Default)]
+pub struct Catalog {
+    include_files: bool,
+    wims: Vec<CatalogWim>,
+}
+
+impl Catalog {
+    /// 创建空目录（默认不收集文件列表）
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 是否遍历镜像元数据收集文件列表（需要读取并解压每个镜像的元数据资源）
+    pub fn with_files(mut self, include_files: bool) -> Self {
+        self.include_files = include_files;
+        self
+    }
+
+    /// 已加入的 WIM 文件
+    pub fn wims(&self) -> &[CatalogWim] {
+        &self.wims
+    }
+
+    /// 打开并加入一个 WIM 文件
+    pub fn add<P: AsRef<Path>>(&mut self, path: P) -> Result<&CatalogWim> {
+        let mut parser = WimParser::new(path)?;
+        self.add_parser(&mut parser)
+    }
+
+    /// 加入一个已打开的 WIM 文件
+    pub fn add_parser(&mut self, parser: &mut WimParser) -> Result<&CatalogWim> {
+        parser.parse_full()?;
+        let header = parser.read_header()?.clone();
+        let file_size = parser
+            .file
+            .get_ref()
+            .metadata()
+            .context("无法读取 WIM 文件大小")?
+            .len()
+            .saturating_sub(parser.base_offset());
+
+        let lookup_table = parser.read_lookup_table()?.to_vec();
+        let sizes: HashMap<[u8; 20], u64> = lookup_table
+            .iter()
+            .map(|entry| (entry.hash, entry.resource.original_size))
+            .collect();
+        let streams = lookup_table
+            .iter()
+            .map(|entry| CatalogStream {
+                hash: entry.hash,
+                size: entry.resource.size,
+                original_size: entry.resource.original_size,
+                ref_count: entry.ref_count,
+                part_number: entry.part_number,
+                flags: entry.resource.flags,
+            })
+            .collect();
+
+        let images = parser.get_images().to_vec();
+        let mut files = Vec::new();
+        if self.include_files {
+            for image in &images {
+                let root = parser.read_metadata_root(image.index)?;
+                root.walk_with_path(&mut |path, entry| {
+                    let hash =
+                        (!entry.is_directory() && entry.hash != [0u8; 20]).then_some(entry.hash);
+                    files.push(CatalogFile {
+                        image_index: image.index,
+                        path: path.to_string(),
+                        size: hash.and_then(|hash| sizes.get(&hash).copied()).unwrap_or(0),
+                        hash,
+                        attributes: entry.attributes,
+                    });
+                });
+            }
+        }
+
+        let wim = CatalogWim {
+            path: parser.path.clone().unwrap_or_default(),
+            file_size,
+            guid: header.guid,
+            segment_number: header.segment_number,
+            total_segments: header.total_segments,
+            compression: parser.compression(),
+            images,
+            streams,
+            files,
+        };
+        info!(
+            "已加入目录: {} ({} 个镜像, {} 个数据流, {} 个文件)",
+            wim.path.display(),
+            wim.images.len(),
+            wim.streams.len(),
+            wim.files.len()
+        );
+        self.wims.push(wim);
+        Ok(self.wims.last().expect("刚刚加入"))
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl Catalog {
+    /// 导出为 SQLite 数据库（已存在的文件会被覆盖）
+    ///
+    /// 表结构：
+    /// - `wims(id, path, guid, file_size, segment_number, total_segments, compression, image_count)`
+    /// - `images(wim_id, image_index, name, english_name, description, edition_id, architecture,
+    ///   version, build, sp_build, languages, default_language, dir_count, file_count, total_bytes)`
+    /// - `streams(wim_id, hash, size, original_size, ref_count, part_number, flags, is_metadata)`
+    /// - `files(wim_id, image_index, path, size, hash, attributes)`（仅在收集文件列表时有数据）
+    ///
+    /// GUID 以 `{XXXXXXXX-...}` 形式、摘要以小写十六进制文本存储，`languages` 以逗号分隔。
+    pub fn export_sqlite<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let path = path.as_ref();
+        if path.exists() {
+            std::fs::remove_file(path)
+                .with_context(|| format!("无法删除已有的数据库文件: {}", path.display()))?;
+        }
+        let mut connection = rusqlite::Connection::open(path)
+            .with_context(|| format!("无法创建数据库文件: {}", path.display()))?;
+        let transaction = connection.transaction()?;
+        transaction.execute_batch(SQLITE_SCHEMA)?;
+        {
+            let mut insert_wim = transaction.prepare(
+                "INSERT INTO wims (path, guid, file_size, segment_number, total_segments, \
+                 compression, image_count) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            )?;
+            let mut insert_image = transaction.prepare(
+                "INSERT INTO images (wim_id, image_index, name, english_name, description, \
+                 edition_id, architecture, version, build, sp_build, languages, \
+                 default_language, dir_count, file_count, total_bytes) \
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15)",
+            )?;
+            let mut insert_stream = transaction.prepare(
+                "INSERT INTO streams (wim_id, hash, size, original_size, ref_count, \
+                 part_number, flags, is_metadata) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            )?;
+            let mut insert_file = transaction.prepare(
+                "INSERT INTO files (wim_id, image_index, path, size, hash, attributes) \
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            )?;
+
+            for wim in &self.wims {
+                insert_wim.execute(rusqlite::params![
+                    wim.path.to_string_lossy(),
+                    format_guid(&wim.guid),
+                    wim.file_size,
+                    wim.segment_number,
+                    wim.total_segments,
+                    wim.compression.name(),
+                    wim.images.len(),
+                ])?;
+                let wim_id = transaction.last_insert_rowid();
+
+                for image in &wim.images {
+                    insert_image.execute(rusqlite::params![
+                        wim_id,
+                        image.index,
+                        image.name,
+                        image.english_name,
+                        image.description,
+                        image.edition_id,
+                        image.architecture,
+                        image.version,
+                        image.build,
+                        image.sp_build,
+                        image.languages.join(","),
+                        image.default_language,
+                        image.dir_count,
+                        image.file_count,
+                        image.total_bytes,
+                    ])?;
+                }
+                for stream in &wim.streams {
+                    insert_stream.execute(rusqlite::params![
+                        wim_id,
+                        format_hash(&stream.hash),
+                        stream.size,
+                        stream.original_size,
+                        stream.ref_count,
+                        stream.part_number,
+                        stream.flags,
+                        stream.flags & ResourceFlags::METADATA != 0,
+                    ])?;
+                }
+                for file in &wim.files {
+                    insert_file.execute(rusqlite::params![
+                        wim_id,
+                        file.image_index,
+                        file.path,
+                        file.size,
+                        file.hash.as_ref().map(|hash| format_hash(hash)),
+                        file.attributes,
+                    ])?;
+                }
+            }
+        }
+        transaction.commit()?;
+        info!(
+            "已导出 SQLite 目录: {} ({} 个 WIM 文件)",
+            path.display(),
+            self.wims.len()
+        );
+        Ok(())
+    }
+}
+
+/// SQLite 目录的表结构
+#[cfg(feature = "sqlite")]
+const SQLITE_SCHEMA: &str = "
+CREATE TABLE wims (
+    id INTEGER PRIMARY KEY,
+    path TEXT NOT NULL,
+    guid TEXT NOT NULL,
+    file_size INTEGER NOT NULL,
+    segment_number INTEGER NOT NULL,
+    total_segments INTEGER NOT NULL,
+    compression TEXT NOT NULL,
+    image_count INTEGER NOT NULL
+);
+CREATE TABLE images (
+    wim_id INTEGER NOT NULL REFERENCES wims(id),
+    image_index INTEGER NOT NULL,
+    name TEXT NOT NULL,
+    english_name TEXT,
+    description TEXT NOT NULL,
+    edition_id TEXT,
+    architecture TEXT,
+    version TEXT,
+    build INTEGER,
+    sp_build INTEGER,
+    languages TEXT NOT NULL,
+    default_language TEXT,
+    dir_count INTEGER NOT NULL,
+    file_count INTEGER NOT NULL,
+    total_bytes INTEGER NOT NULL,
+    PRIMARY KEY (wim_id, image_index)
+);
+CREATE TABLE streams (
+    wim_id INTEGER NOT NULL REFERENCES wims(id),
+    hash TEXT NOT NULL,
+    size INTEGER NOT NULL,
+    original_size INTEGER NOT NULL,
+    ref_count INTEGER NOT NULL,
+    part_number INTEGER NOT NULL,
+    flags INTEGER NOT NULL,
+    is_metadata INTEGER NOT NULL
+);
+CREATE TABLE files (
+    wim_id INTEGER NOT NULL REFERENCES wims(id),
+    image_index INTEGER NOT NULL,
+    path TEXT NOT NULL,
+    size INTEGER NOT NULL,
+    hash TEXT,
+    attributes INTEGER NOT NULL
+);
+CREATE INDEX wims_guid ON wims(guid);
+CREATE INDEX streams_hash ON streams(hash);
+CREATE INDEX files_hash ON files(hash);
+CREATE INDEX files_path ON files(path);
+";
@@ -0,0 +1,75 @@
+//! 由镜像 XML 推断的安装渠道（客户端、服务器、评估版、IoT）
+
+use alloc::string::String;
+use core::fmt;
+
+use crate::ImageInfo;
+
+/// 镜像的安装渠道，供许可证合规工具对镜像分类
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum ImageChannel {
+    /// 客户端版本（Home、Pro、Enterprise 等）
+    Client,
+    /// 服务器版本（Standard、Datacenter 等）
+    Server,
+    /// 评估版（`EDITIONID` 以 `Eval` 结尾，例如 `ServerStandardEval`）
+    Evaluation,
+    /// IoT 版本（`EDITIONID` 以 `IoT` 开头，例如 `IoTEnterpriseS`）
+    IoT,
+}
+
+impl ImageChannel {
+    /// 渠道名称
+    pub fn name(&self) -> &'static str {
+        match self {
+            ImageChannel::Client => "Client",
+            ImageChannel::Server => "Server",
+            ImageChannel::Evaluation => "Evaluation",
+            ImageChannel::IoT => "IoT",
+        }
+    }
+
+    /// 根据镜像 XML 中的 `FLAGS`、`EDITIONID`、`INSTALLATIONTYPE` 和 `PRODUCTTYPE` 推断渠道
+    ///
+    /// 按评估版、IoT、服务器、客户端的顺序判断；Windows PE 镜像和缺少上述字段的镜像返回 `None`。
+    pub fn detect(image: &ImageInfo) -> Option<ImageChannel> {
+        if image.is_windows_pe() {
+            return None;
+        }
+        let lower = |value: &Option<String>| value.as_deref().map(str::to_ascii_lowercase);
+        let edition = lower(&image.edition_id).or_else(|| lower(&image.flags));
+        let installation = lower(&image.installation_type);
+        let product = lower(&image.product_type);
+
+        if edition
+            .as_deref()
+            .is_some_and(|edition| edition.ends_with("eval") || edition.contains("evaluation"))
+        {
+            return Some(ImageChannel::Evaluation);
+        }
+        if edition
+            .as_deref()
+            .is_some_and(|edition| edition.starts_with("iot"))
+        {
+            return Some(ImageChannel::IoT);
+        }
+        let is_server = installation
+            .as_deref()
+            .is_some_and(|installation| installation.starts_with("server"))
+            || product.as_deref() == Some("servernt")
+            || edition
+                .as_deref()
+                .is_some_and(|edition| edition.starts_with("server"));
+        if is_server {
+            return Some(ImageChannel::Server);
+        }
+        (installation.is_some() || product.is_some() || edition.is_some())
+            .then_some(ImageChannel::Client)
+    }
+}
+
+impl fmt::Display for ImageChannel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
@@ -0,0 +1,119 @@
+use core::fmt;
+
+use crate::{FileFlags, FileResourceEntry, WimHeader};
+
+/// 未在文件头中指定分块大小时使用的默认值 (32 KiB)
+pub const DEFAULT_CHUNK_SIZE: u32 = 32 * 1024;
+
+/// 压缩格式及分块大小
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Compression {
+    /// 未压缩
+    None,
+    /// XPRESS (Huffman) 压缩
+    Xpress {
+        /// 分块大小（字节）
+        chunk: u32,
+    },
+    /// LZX 压缩
+    Lzx {
+        /// 分块大小（字节）
+        chunk: u32,
+    },
+    /// LZMS 压缩（ESD 文件）
+    Lzms {
+        /// 分块大小（字节）
+        chunk: u32,
+    },
+    /// 设置了压缩标志但无法识别具体格式，保留原始标志值
+    Unknown(u32),
+}
+
+impl Compression {
+    /// 根据文件头标志和分块大小字段确定压缩格式
+    pub fn from_header(header: &WimHeader) -> Compression {
+        let flags = header.file_flags;
+        if flags & FileFlags::COMPRESSION == 0 {
+            return Compression::None;
+        }
+
+        let chunk = match header.compressed_size {
+            0 => DEFAULT_CHUNK_SIZE,
+            size => size,
+        };
+
+        if flags & FileFlags::COMPRESS_LZMS != 0 {
+            Compression::Lzms { chunk }
+        } else if flags & FileFlags::COMPRESS_LZX != 0 {
+            Compression::Lzx { chunk }
+        } else if flags & (FileFlags::COMPRESS_XPRESS | FileFlags::COMPRESS_XPRESS_2) != 0 {
+            Compression::Xpress { chunk }
+        } else {
+            Compression::Unknown(flags)
+        }
+    }
+
+    /// 根据固实资源头中的压缩格式编号确定压缩格式
+    /// (0: 无, 1: XPRESS, 2: LZX, 3: LZMS)
+    pub fn from_solid_format(format: u32, chunk: u32) -> Compression {
+        match format {
+            0 => Compression::None,
+            1 => Compression::Xpress { chunk },
+            2 => Compression::Lzx { chunk },
+            3 => Compression::Lzms { chunk },
+            other => Compression::Unknown(other),
+        }
+    }
+
+    /// 对单个资源的压缩格式：资源未设置压缩标志时为 [`Compression::None`]，
+    /// 否则沿用文件头的格式（固实资源需读取其资源头，见 `WimParser::resource_compression`）
+    pub fn for_resource(header: &WimHeader, resource: &FileResourceEntry) -> Compression {
+        if !resource.is_compressed() {
+            Compression::None
+        } else {
+            Compression::from_header(header)
+        }
+    }
+
+    /// 是否压缩
+    pub fn is_compressed(&self) -> bool {
+        !matches!(self, Compression::None)
+    }
+
+    /// 分块大小（未压缩或未知格式时为 `None`）
+    pub fn chunk_size(&self) -> Option<u32> {
+        match self {
+            Compression::Xpress { chunk }
+            | Compression::Lzx { chunk }
+            | Compression::Lzms { chunk } => Some(*chunk),
+            Compression::None | Compression::Unknown(_) => None,
+        }
+    }
+
+    /// 压缩格式名称
+    pub fn name(&self) -> &'static str {
+        match self {
+            Compression::None => "None",
+            Compression::Xpress { .. } => "XPRESS",
+            Compression::Lzx { .. } => "LZX",
+            Compression::Lzms { .. } => "LZMS",
+            Compression::Unknown(_) => "Unknown",
+        }
+    }
+}
+
+impl fmt::Display for Compression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.chunk_size() {
+            Some(chunk) => write!(f, "{} ({} KiB 分块)", self.name(), chunk / 1024),
+            None => f.write_str(self.name()),
+        }
+    }
+}
+
+impl WimHeader {
+    /// 文件头声明的压缩格式
+    pub fn compression(&self) -> Compression {
+        Compression::from_header(self)
+    }
+}
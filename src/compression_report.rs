@@ -0,0 +1,243 @@
+use anyhow::Result;
+use std::collections::{HashMap, HashSet};
+
+use crate::fmt::{format_bytes, Align, Table, ToTable};
+use crate::log::debug;
+use crate::{Compression, WimParser};
+
+/// 存储字节数与原始字节数
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct CompressionRatio {
+    /// 文件中实际占用的字节数（偏移表中的 `size`）
+    pub stored_bytes: u64,
+    /// 解压后的字节数（偏移表中的 `original_size`）
+    pub logical_bytes: u64,
+}
+
+impl CompressionRatio {
+    fn add(&mut self, stored: u64, logical: u64) {
+        self.stored_bytes += stored;
+        self.logical_bytes += logical;
+    }
+
+    /// 压缩率（存储字节数 / 原始字节数），没有数据时为 `None`
+    pub fn ratio(&self) -> Option<f64> {
+        (self.logical_bytes != 0).then(|| self.stored_bytes as f64 / self.logical_bytes as f64)
+    }
+
+    /// 压缩节省的字节数
+    pub fn saved_bytes(&self) -> u64 {
+        self.logical_bytes.saturating_sub(self.stored_bytes)
+    }
+
+    /// 压缩率的百分比表示，没有数据时为 `-`
+    fn percent(&self) -> String {
+        self.ratio()
+            .map(|ratio| format!("{:.1}%", ratio * 100.0))
+            .unwrap_or_else(|| "-".to_string())
+    }
+}
+
+/// 某一文件类型（扩展名）的压缩情况
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExtensionRatio {
+    /// 小写扩展名（没有扩展名时为空字符串）
+    pub extension: String,
+    /// 数据流数量
+    pub streams: usize,
+    /// 存储与原始字节数
+    pub ratio: CompressionRatio,
+}
+
+/// 单个镜像引用的数据流的压缩情况
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImageRatio {
+    /// 镜像索引
+    pub index: u32,
+    /// 镜像引用的不同数据流数量
+    pub streams: usize,
+    /// 存储与原始字节数（共享的数据流在每个镜像中都计入）
+    pub ratio: CompressionRatio,
+}
+
+/// 数据流压缩率报告
+#[derive(Debug, Clone)]
+pub struct CompressionReport {
+    /// 文件头声明的压缩格式
+    pub compression: Compression,
+    /// 数据流数量（不含固实资源中的数据流）
+    pub stream_count: usize,
+    /// 所有数据流的合计
+    pub total: CompressionRatio,
+    /// 镜像元数据资源的合计
+    pub metadata: CompressionRatio,
+    /// 按文件类型统计，压缩率从好到差排列
+    pub by_extension: Vec<ExtensionRatio>,
+    /// 按镜像统计
+    pub images: Vec<ImageRatio>,
+    /// 固实资源中的数据流数量（无法单独计算存储大小，不计入统计）
+    pub solid_streams: usize,
+}
+
+impl CompressionReport {
+    /// 压缩效果最好的 `n` 种文件类型
+    pub fn best_types(&self, n: usize) -> &[ExtensionRatio] {
+        &self.by_extension[..n.min(self.by_extension.len())]
+    }
+
+    /// 压缩效果最差的 `n` 种文件类型（最差的在前）
+    pub fn worst_types(&self, n: usize) -> Vec<&ExtensionRatio> {
+        self.by_extension.iter().rev().take(n).collect()
+    }
+}
+
+impl ToTable for CompressionReport {
+    fn table(&self) -> Table {
+        let mut table = Table::new(["项目", "数据流", "存储", "原始", "压缩率"])
+            .align(1, Align::Right)
+            .align(2, Align::Right)
+            .align(3, Align::Right)
+            .align(4, Align::Right);
+
+        let mut push = |label: String, streams: String, ratio: &CompressionRatio| {
+            table.push_row([
+                label,
+                streams,
+                format_bytes(ratio.stored_bytes),
+                format_bytes(ratio.logical_bytes),
+                ratio.percent(),
+            ]);
+        };
+
+        push(
+            "总计".to_string(),
+            self.stream_count.to_string(),
+            &self.total,
+        );
+        push("元数据".to_string(), "-".to_string(), &self.metadata);
+        for image in &self.images {
+            push(
+                format!("镜像 {}", image.index),
+                image.streams.to_string(),
+                &image.ratio,
+            );
+        }
+        for extension in &self.by_extension {
+            let label = if extension.extension.is_empty() {
+                "(无扩展名)".to_string()
+            } else {
+                format!(".{}", extension.extension)
+            };
+            push(label, extension.streams.to_string(), &extension.ratio);
+        }
+        table
+    }
+}
+
+/// 文件名的小写扩展名（没有扩展名时为空字符串）
+fn extension_of(name: &str) -> String {
+    match name.rsplit_once('.') {
+        Some((stem, extension)) if !stem.is_empty() => extension.to_ascii_lowercase(),
+        _ => String::new(),
+    }
+}
+
+impl WimParser {
+    /// 根据偏移表中的存储大小和原始大小生成压缩率报告
+    ///
+    /// 文件类型按首次引用该数据流的文件名确定；固实资源 (ESD) 中的数据流没有单独的存储大小，
+    /// 只计入 `solid_streams`。
+    pub fn compression_report(&mut self) -> Result<CompressionReport> {
+        let image_count = self.read_header()?.image_count;
+        let compression = self.compression();
+
+        // 数据流 SHA-1 -> (存储大小, 原始大小)
+        let mut streams: HashMap<[u8; 20], (u64, u64)> = HashMap::new();
+        let mut metadata = CompressionRatio::default();
+        let mut solid_streams = 0;
+        for entry in self.read_lookup_table()? {
+            let resource = &entry.resource;
+            if entry.is_metadata() {
+                metadata.add(resource.size, resource.original_size);
+            } else if resource.is_solid() {
+                // 固实资源本身不是数据流
+                if !resource.is_solid_resource() {
+                    solid_streams += 1;
+                }
+            } else {
+                streams.insert(entry.hash, (resource.size, resource.original_size));
+            }
+        }
+
+        let mut extensions: HashMap<String, ExtensionRatio> = HashMap::new();
+        let mut classified: HashSet<[u8; 20]> = HashSet::new();
+        let mut images = Vec::new();
+        for index in 1..=image_count {
+            let root = self.read_metadata_root(index)?;
+            let mut image = ImageRatio {
+                index,
+                streams: 0,
+                ratio: CompressionRatio::default(),
+            };
+            let mut seen: HashSet<[u8; 20]> = HashSet::new();
+
+            root.walk(&mut |entry| {
+                let hashes =
+                    std::iter::once(&entry.hash).chain(entry.streams.iter().map(|s| &s.hash));
+                for hash in hashes {
+                    let Some(&(stored, logical)) = streams.get(hash) else {
+                        continue;
+                    };
+                    if seen.insert(*hash) {
+                        image.streams += 1;
+                        image.ratio.add(stored, logical);
+                    }
+                    if classified.insert(*hash) {
+                        let extension = extension_of(&entry.name);
+                        let group =
+                            extensions
+                                .entry(extension.clone())
+                                .or_insert_with(|| ExtensionRatio {
+                                    extension,
+                                    streams: 0,
+                                    ratio: CompressionRatio::default(),
+                                });
+                        group.streams += 1;
+                        group.ratio.add(stored, logical);
+                    }
+                }
+            });
+            images.push(image);
+        }
+
+        let mut total = CompressionRatio::default();
+        for &(stored, logical) in streams.values() {
+            total.add(stored, logical);
+        }
+
+        let mut by_extension: Vec<ExtensionRatio> = extensions.into_values().collect();
+        by_extension.sort_by(|a, b| {
+            let ratio = |e: &ExtensionRatio| e.ratio.ratio().unwrap_or(f64::INFINITY);
+            ratio(a)
+                .total_cmp(&ratio(b))
+                .then_with(|| a.extension.cmp(&b.extension))
+        });
+
+        debug!(
+            "压缩率报告 - 数据流: {}, 文件类型: {}, 固实数据流: {}",
+            streams.len(),
+            by_extension.len(),
+            solid_streams
+        );
+
+        Ok(CompressionReport {
+            compression,
+            stream_count: streams.len(),
+            total,
+            metadata,
+            by_extension,
+            images,
+            solid_streams,
+        })
+    }
+}
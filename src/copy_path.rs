@@ -0,0 +1,402 @@
+//! 镜像间复制文件：把一个镜像中的文件或目录树克隆到另一个镜像，引用已有的数据流
+//!
+//! 复制只修改目标镜像的元数据资源（目录项、安全描述符和硬链接组），数据流按 SHA-1 共享，
+//! 因此向多个版本注入相同文件时不需要解包再重新捕获。从其他 WIM 复制时，目标文件中
+//! 没有的数据流一并复制：压缩格式相同时原样复制压缩分块，否则以未压缩形式存放。
+
+use anyhow::{Context, Result};
+use sha1::{Digest, Sha1};
+use std::collections::{HashMap, HashSet};
+
+use crate::log::{debug, info};
+use crate::metadata::{self, DirEntry};
+use crate::names::NameMatching;
+use crate::transaction::{AddedStream, ImageEdit, RewrittenImage, Transaction, TransactionPlan};
+use crate::WimParser;
+
+/// 从镜像中取出的待复制目录树
+#[derive(Debug, Clone)]
+pub(crate) struct SubtreeCopy {
+    /// 要复制的目录项（含子孙）
+    entry: DirEntry,
+    /// 从根目录到 `entry` 之间的各级目录（不含子目录项），目标中缺少时按此创建
+    ancestors: Vec<DirEntry>,
+    /// 源镜像的安全描述符
+    descriptors: Vec<Vec<u8>>,
+}
+
+/// 从其他 WIM 读取的复制内容
+#[derive(Debug, Clone)]
+pub(crate) struct ForeignCopy {
+    copy: SubtreeCopy,
+    /// 目标文件中没有的数据流
+    streams: Vec<AddedStream>,
+}
+
+impl SubtreeCopy {
+    /// 从目录树中取出 `path` 对应的目录项
+    fn extract(root: &DirEntry, descriptors: Vec<Vec<u8>>, path: &str) -> Result<Self> {
+        let matching = NameMatching::default();
+        let mut ancestors = Vec::new();
+        let mut current = root;
+        let mut parts = path.split(['\\', '/']).filter(|part| !part.is_empty());
+        let Some(mut part) = parts.next() else {
+            return Err(anyhow::anyhow!("不能复制根目录"));
+        };
+        loop {
+            let child = matching
+                .find(&current.children, part, |child| &child.name)
+                .ok_or_else(|| anyhow::anyhow!("源镜像中找不到路径: {}", path))?;
+            match parts.next() {
+                Some(next) => {
+                    if !child.is_directory() {
+                        return Err(anyhow::anyhow!("源镜像中 {} 不是目录", child.name));
+                    }
+                    ancestors.push(DirEntry {
+                        children: Vec::new(),
+                        ..child.clone()
+                    });
+                    current = child;
+                    part = next;
+                }
+                None => {
+                    return Ok(Self {
+                        entry: child.clone(),
+                        ancestors,
+                        descriptors,
+                    })
+                }
+            }
+        }
+    }
+
+    /// 读取镜像的目录树并取出 `path`
+    fn load(parser: &mut WimParser, index: u32, path: &str) -> Result<Self> {
+        let (root, descriptors) = load_tree(parser, index)?;
+        Self::extract(&root, descriptors, path)
+            .with_context(|| format!("无法从镜像 {index} 复制 {path}"))
+    }
+
+    /// 复制的目录树引用的所有数据流
+    fn stream_hashes(&self) -> Vec<[u8; 20]> {
+        let mut seen = HashSet::new();
+        let mut hashes = Vec::new();
+        self.entry.walk(&mut |entry| {
+            let entry_hashes =
+                std::iter::once(&entry.hash).chain(entry.streams.iter().map(|s| &s.hash));
+            for hash in entry_hashes.filter(|hash| **hash != [0u8; 20]) {
+                if seen.insert(*hash) {
+                    hashes.push(*hash);
+                }
+            }
+        });
+        hashes
+    }
+
+    /// 将目录树接入目标镜像：缺少的上级目录按源镜像创建，同名目录合并，同名文件替换
+    ///
+    /// 安全描述符追加到 `descriptors`（内容相同的复用），硬链接组 ID 改为目标镜像中未使用的值。
+    fn graft(&self, root: &mut DirEntry, descriptors: &mut Vec<Vec<u8>>) -> Result<()> {
+        let mut next_link = 1;
+        root.walk(&mut |entry| {
+            if !entry.is_reparse_point() {
+                next_link = next_link.max(entry.hard_link_group_id + 1);
+            }
+        });
+        let mut remap = Remap {
+            source: &self.descriptors,
+            target: descriptors,
+            security: HashMap::new(),
+            links: HashMap::new(),
+            next_link,
+        };
+
+        let matching = NameMatching::default();
+        let mut dir = root;
+        for ancestor in &self.ancestors {
+            let position = match matching.position(&dir.children, &ancestor.name, |c| &c.name) {
+                Some(position) if dir.children[position].is_directory() => position,
+                Some(position) => {
+                    return Err(anyhow::anyhow!(
+                        "目标镜像中 {} 不是目录",
+                        dir.children[position].name
+                    ))
+                }
+                None => {
+                    let mut created = ancestor.clone();
+                    remap.apply(&mut created)?;
+                    dir.children.push(created);
+                    dir.children.len() - 1
+                }
+            };
+            dir = &mut dir.children[position];
+        }
+
+        let mut entry = self.entry.clone();
+        remap.apply_tree(&mut entry)?;
+        merge_child(dir, entry, &matching);
+        Ok(())
+    }
+}
+
+/// 复制时的安全描述符和硬链接组 ID 映射
+struct Remap<'a> {
+    source: &'a [Vec<u8>],
+    target: &'a mut Vec<Vec<u8>>,
+    security: HashMap<i32, i32>,
+    links: HashMap<u64, u64>,
+    next_link: u64,
+}
+
+impl Remap<'_> {
+    fn apply(&mut self, entry: &mut DirEntry) -> Result<()> {
+        if entry.security_id >= 0 {
+            entry.security_id = match self.security.get(&entry.security_id) {
+                Some(&id) => id,
+                None => {
+                    let descriptor =
+                        self.source.get(entry.security_id as usize).ok_or_else(|| {
+                            anyhow::anyhow!(
+                                "{} 的安全描述符索引 {} 超出范围",
+                                entry.name,
+                                entry.security_id
+                            )
+                        })?;
+                    let id = match self.target.iter().position(|d| d == descriptor) {
+                        Some(position) => position,
+                        None => {
+                            self.target.push(descriptor.clone());
+                            self.target.len() - 1
+                        }
+                    } as i32;
+                    self.security.insert(entry.security_id, id);
+                    id
+                }
+            };
+        }
+        if !entry.is_reparse_point() && entry.hard_link_group_id != 0 {
+            entry.hard_link_group_id =
+                *self
+                    .links
+                    .entry(entry.hard_link_group_id)
+                    .or_insert_with(|| {
+                        self.next_link += 1;
+                        self.next_link - 1
+                    });
+        }
+        Ok(())
+    }
+
+    fn apply_tree(&mut self, entry: &mut DirEntry) -> Result<()> {
+        self.apply(entry)?;
+        for child in &mut entry.children {
+            self.apply_tree(child)?;
+        }
+        Ok(())
+    }
+}
+
+/// 将 `entry` 放入目录 `dir`：与同名目录合并，替换同名文件
+fn merge_child(dir: &mut DirEntry, entry: DirEntry, matching: &NameMatching) {
+    match matching.position(&dir.children, &entry.name, |c| &c.name) {
+        Some(position) if dir.children[position].is_directory() && entry.is_directory() => {
+            let existing = &mut dir.children[position];
+            for child in entry.children {
+                merge_child(existing, child, matching);
+            }
+        }
+        Some(position) => dir.children[position] = entry,
+        None => dir.children.push(entry),
+    }
+}
+
+/// 读取镜像的目录树和安全描述符
+fn load_tree(parser: &mut WimParser, index: u32) -> Result<(DirEntry, Vec<Vec<u8>>)> {
+    let data = parser.read_metadata_bytes(index)?;
+    let descriptors = metadata::parse_security_descriptors(&data)?
+        .into_iter()
+        .map(<[u8]>::to_vec)
+        .collect();
+    let root = metadata::parse_metadata_resource(&data, &parser.options().resource_limits())?;
+    Ok((root, descriptors))
+}
+
+impl WimParser {
+    /// 将镜像 `source` 中的文件或目录复制到镜像 `target` 的相同路径并提交
+    ///
+    /// 相当于 `transaction().copy_path(source, path, target).commit()`，见 [`Transaction::copy_path`]。
+    pub fn copy_path(&mut self, source: u32, path: &str, target: u32) -> Result<TransactionPlan> {
+        self.transaction().copy_path(source, path, target).commit()
+    }
+}
+
+impl Transaction<'_> {
+    /// 将镜像 `source` 中的文件或目录（如 `/Windows/Boot`）复制到镜像 `target` 的相同路径
+    ///
+    /// 复制的目录项引用已有的数据流，不复制文件内容；目标中缺少的上级目录按源镜像创建，
+    /// 同名目录合并，同名文件被替换。提交时重建文件，目标镜像写入新的元数据资源，
+    /// XML 中的 DIRCOUNT/FILECOUNT/TOTALBYTES 随之更新。
+    ///
+    /// ```
+    /// # #[cfg(feature = "fixtures")] {
+    /// # use wim_parser::WimParser;
+    /// # let fixture = wim_parser::fixtures::MiniWim::create()?;
+    /// let mut parser = WimParser::new(fixture.path())?;
+    /// parser.parse_full()?;
+    /// let count = parser.get_image_count();
+    /// let mut transaction = parser.transaction();
+    /// for target in 1..count {
+    ///     transaction = transaction.copy_path(count, "/Windows/System32/gpedit.msc", target);
+    /// }
+    /// transaction.commit()?;
+    /// # assert!(parser.read_image_metadata(1)?.root.find("Windows/System32/gpedit.msc").is_some());
+    /// # }
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    pub fn copy_path(self, source: u32, path: impl Into<String>, target: u32) -> Self {
+        self.edit(ImageEdit::CopyPath {
+            source,
+            path: path.into(),
+            target,
+            from: None,
+        })
+    }
+
+    /// 从另一个 WIM 的镜像 `source` 复制文件或目录到本文件的镜像 `target`
+    ///
+    /// 立即读取要复制的目录树，以及目标文件中没有的数据流（两个文件的压缩格式相同时原样复制压缩分块，
+    /// 并先校验解压后的 SHA-1），提交时追加到重建的文件中。
+    pub fn copy_path_from(
+        mut self,
+        from: &mut WimParser,
+        source: u32,
+        path: &str,
+        target: u32,
+    ) -> Result<Self> {
+        let copy = SubtreeCopy::load(from, source, path)?;
+        let existing: HashSet<[u8; 20]> = self
+            .parser
+            .read_lookup_table()?
+            .iter()
+            .map(|entry| entry.hash)
+            .collect();
+        let target_compression = self.parser.read_header()?.compression();
+        let raw_copy = target_compression.is_compressed()
+            && from.read_header()?.compression() == target_compression;
+
+        let mut streams = Vec::new();
+        for hash in copy.stream_hashes() {
+            if existing.contains(&hash) {
+                continue;
+            }
+            let resource = from
+                .stream_resource(&hash)?
+                .ok_or_else(|| anyhow::anyhow!("源文件的偏移表中找不到数据流"))?;
+            let stream = if raw_copy && resource.is_compressed() && !resource.is_solid() {
+                AddedStream {
+                    hash,
+                    raw: from
+                        .read_raw_stream(&resource, &hash)
+                        .context("复制压缩数据流失败")?,
+                    original_size: resource.original_size,
+                    compressed: true,
+                }
+            } else {
+                AddedStream {
+                    hash,
+                    raw: from
+                        .read_stream_resource(&resource)
+                        .context("读取数据流失败")?,
+                    original_size: resource.original_size,
+                    compressed: false,
+                }
+            };
+            streams.push(stream);
+        }
+
+        info!(
+            "从 {} 的镜像 {} 读取 {}: 需要复制 {} 个数据流",
+            from.path
+                .as_deref()
+                .map_or_else(|| "(未命名)".into(), |p| p.display().to_string()),
+            source,
+            path,
+            streams.len()
+        );
+        self.foreign
+            .insert(self.edits.len(), ForeignCopy { copy, streams });
+        Ok(self.edit(ImageEdit::CopyPath {
+            source,
+            path: path.to_string(),
+            target,
+            from: Some(from.path.clone().unwrap_or_default()),
+        }))
+    }
+
+    /// 按顺序执行复制编辑，返回目录树被修改的镜像和需要追加的数据流
+    pub(crate) fn apply_copies(
+        &mut self,
+    ) -> Result<(HashMap<u32, RewrittenImage>, Vec<AddedStream>)> {
+        let mut trees: HashMap<u32, (DirEntry, Vec<Vec<u8>>)> = HashMap::new();
+        let mut added = Vec::new();
+        let mut added_hashes = HashSet::new();
+        let edits = self.edits.clone();
+        for (position, edit) in edits.iter().enumerate() {
+            let ImageEdit::CopyPath {
+                source,
+                path,
+                target,
+                from,
+            } = edit
+            else {
+                continue;
+            };
+            let local;
+            let copy = match from {
+                Some(from) => {
+                    let foreign = self.foreign.get(&position).ok_or_else(|| {
+                        anyhow::anyhow!(
+                            "没有读取 {} 中要复制的内容，请使用 copy_path_from 添加",
+                            from.display()
+                        )
+                    })?;
+                    for stream in &foreign.streams {
+                        if added_hashes.insert(stream.hash) {
+                            added.push(stream.clone());
+                        }
+                    }
+                    &foreign.copy
+                }
+                None => {
+                    // 源镜像已被之前的复制修改时从修改后的目录树复制
+                    local = match trees.get(source) {
+                        Some((root, descriptors)) => {
+                            SubtreeCopy::extract(root, descriptors.clone(), path)
+                                .with_context(|| format!("无法从镜像 {source} 复制 {path}"))?
+                        }
+                        None => SubtreeCopy::load(self.parser, *source, path)?,
+                    };
+                    &local
+                }
+            };
+
+            if !trees.contains_key(target) {
+                trees.insert(*target, load_tree(self.parser, *target)?);
+            }
+            let (root, descriptors) = trees.get_mut(target).expect("目标目录树已加载");
+            copy.graft(root, descriptors)
+                .with_context(|| format!("无法将 {path} 复制到镜像 {target}"))?;
+            debug!("复制 {} 到镜像 {}", path, target);
+        }
+
+        let rewritten = trees
+            .into_iter()
+            .map(|(index, (root, descriptors))| {
+                let data = metadata::encode_metadata_with_security(&root, &descriptors);
+                let hash = Sha1::digest(&data).into();
+                (index, RewrittenImage { root, hash, data })
+            })
+            .collect();
+        Ok((rewritten, added))
+    }
+}
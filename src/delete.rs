@@ -0,0 +1,199 @@
+use anyhow::Result;
+use std::collections::HashMap;
+
+use crate::fmt::{format_bytes, Align, Table, ToTable};
+use crate::log::{debug, info};
+use crate::metadata::DirEntry;
+use crate::WimParser;
+
+/// 删除镜像的选项
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DeleteOptions {
+    force: bool,
+}
+
+impl DeleteOptions {
+    /// 默认选项：发现仍被其他镜像引用的数据流会被丢弃时拒绝删除
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 强制删除：即使引用计数检查失败也返回删除计划（相当于 `--force`）
+    pub fn force(mut self, force: bool) -> Self {
+        self.force = force;
+        self
+    }
+
+    /// 是否强制删除
+    pub fn is_forced(&self) -> bool {
+        self.force
+    }
+}
+
+/// 按引用计数会被丢弃、但仍被其他镜像引用的数据流（偏移表引用计数偏小）
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SharedStreamConflict {
+    /// 数据流 SHA-1
+    pub hash: [u8; 20],
+    /// 偏移表中记录的引用计数
+    pub ref_count: u32,
+    /// 被删除镜像中的引用次数
+    pub deleted_refs: u32,
+    /// 仍引用该数据流的其他镜像
+    pub other_images: Vec<u32>,
+}
+
+/// 删除镜像的检查结果
+#[derive(Debug, Clone)]
+pub struct DeletePlan {
+    /// 要删除的镜像索引
+    pub index: u32,
+    /// 引用计数降为零、将被丢弃的数据流
+    pub freed_streams: Vec<[u8; 20]>,
+    /// 被丢弃数据流的未压缩总字节数
+    pub freed_bytes: u64,
+    /// 仅减少引用计数的数据流数量
+    pub shared_streams: usize,
+    /// 引用计数检查失败的数据流
+    pub conflicts: Vec<SharedStreamConflict>,
+    /// 是否以强制方式生成（存在冲突时仍返回）
+    pub forced: bool,
+}
+
+impl DeletePlan {
+    /// 删除是否不会影响其他镜像
+    pub fn is_safe(&self) -> bool {
+        self.conflicts.is_empty()
+    }
+}
+
+impl ToTable for DeletePlan {
+    fn table(&self) -> Table {
+        let mut table = Table::new(["项目", "值"]).align(1, Align::Right);
+        table.push_row(["镜像".to_string(), self.index.to_string()]);
+        table.push_row([
+            "丢弃的数据流".to_string(),
+            self.freed_streams.len().to_string(),
+        ]);
+        table.push_row(["释放的字节数".to_string(), format_bytes(self.freed_bytes)]);
+        table.push_row(["共享的数据流".to_string(), self.shared_streams.to_string()]);
+        table.push_row(["引用计数冲突".to_string(), self.conflicts.len().to_string()]);
+        table
+    }
+}
+
+/// 统计目录树中每个数据流的引用次数
+fn count_stream_refs(root: &DirEntry) -> HashMap<[u8; 20], u32> {
+    let mut refs = HashMap::new();
+    root.walk(&mut |entry| {
+        let hashes = std::iter::once(&entry.hash).chain(entry.streams.iter().map(|s| &s.hash));
+        for hash in hashes.filter(|hash| **hash != [0u8; 20]) {
+            *refs.entry(*hash).or_insert(0) += 1;
+        }
+    });
+    refs
+}
+
+impl WimParser {
+    /// 检查删除指定镜像是否安全，使用默认选项
+    pub fn plan_delete_image(&mut self, index: u32) -> Result<DeletePlan> {
+        self.plan_delete_image_with(index, &DeleteOptions::new())
+    }
+
+    /// 检查删除指定镜像是否安全（不会修改文件）
+    ///
+    /// 按偏移表引用计数减去该镜像中的引用次数，确定哪些数据流将被丢弃；
+    /// 若其中有数据流仍被其他镜像引用（引用计数偏小），删除会破坏其他镜像，
+    /// 此时除非设置了 [`DeleteOptions::force`]，否则返回错误。
+    pub fn plan_delete_image_with(
+        &mut self,
+        index: u32,
+        options: &DeleteOptions,
+    ) -> Result<DeletePlan> {
+        let image_count = self.read_header()?.image_count;
+        if index == 0 || index > image_count {
+            return Err(anyhow::anyhow!(
+                "镜像索引 {} 超出范围 (1-{})",
+                index,
+                image_count
+            ));
+        }
+
+        let deleted_refs = count_stream_refs(&self.read_metadata_root(index)?);
+        let mut other_refs = Vec::new();
+        for other in (1..=image_count).filter(|&other| other != index) {
+            other_refs.push((other, count_stream_refs(&self.read_metadata_root(other)?)));
+        }
+
+        // 数据流 SHA-1 -> (引用计数, 未压缩大小)
+        let lookup: HashMap<[u8; 20], (u32, u64)> = self
+            .read_lookup_table()?
+            .iter()
+            .filter(|entry| !entry.is_metadata())
+            .map(|entry| (entry.hash, (entry.ref_count, entry.resource.original_size)))
+            .collect();
+
+        let mut plan = DeletePlan {
+            index,
+            freed_streams: Vec::new(),
+            freed_bytes: 0,
+            shared_streams: 0,
+            conflicts: Vec::new(),
+            forced: options.is_forced(),
+        };
+
+        let mut hashes: Vec<_> = deleted_refs.into_iter().collect();
+        hashes.sort_unstable();
+        for (hash, refs) in hashes {
+            let Some(&(ref_count, size)) = lookup.get(&hash) else {
+                continue;
+            };
+            if ref_count > refs {
+                plan.shared_streams += 1;
+                continue;
+            }
+
+            let other_images: Vec<u32> = other_refs
+                .iter()
+                .filter(|(_, refs)| refs.contains_key(&hash))
+                .map(|(other, _)| *other)
+                .collect();
+            if other_images.is_empty() {
+                plan.freed_streams.push(hash);
+                plan.freed_bytes += size;
+            } else {
+                plan.conflicts.push(SharedStreamConflict {
+                    hash,
+                    ref_count,
+                    deleted_refs: refs,
+                    other_images,
+                });
+            }
+        }
+
+        debug!(
+            "删除镜像 {} - 丢弃 {} 个数据流, 共享 {} 个, 冲突 {} 个",
+            index,
+            plan.freed_streams.len(),
+            plan.shared_streams,
+            plan.conflicts.len()
+        );
+
+        if !plan.is_safe() {
+            if !options.is_forced() {
+                return Err(anyhow::anyhow!(
+                    "删除镜像 {} 会丢弃 {} 个仍被其他镜像引用的数据流（偏移表引用计数不正确），可使用强制选项忽略",
+                    index,
+                    plan.conflicts.len()
+                ));
+            }
+            info!(
+                "强制删除镜像 {}：忽略 {} 个引用计数冲突",
+                index,
+                plan.conflicts.len()
+            );
+        }
+
+        Ok(plan)
+    }
+}
@@ -0,0 +1,417 @@
+use crate::fmt::{format_bytes, Align, Table, ToTable};
+use crate::{ImageInfo, WimParser};
+
+/// 将 EDITIONID 映射为常见的版本名称（未知值原样返回）
+pub fn edition_display_name(edition_id: &str) -> String {
+    let name = match edition_id {
+        "Core" => "Home",
+        "CoreN" => "Home N",
+        "CoreSingleLanguage" => "Home Single Language",
+        "CoreCountrySpecific" => "Home China",
+        "Professional" => "Pro",
+        "ProfessionalN" => "Pro N",
+        "ProfessionalEducation" => "Pro Education",
+        "ProfessionalEducationN" => "Pro Education N",
+        "ProfessionalWorkstation" => "Pro for Workstations",
+        "ProfessionalWorkstationN" => "Pro N for Workstations",
+        "EducationN" => "Education N",
+        "EnterpriseN" => "Enterprise N",
+        "EnterpriseS" => "Enterprise LTSC",
+        "EnterpriseSN" => "Enterprise N LTSC",
+        "IoTEnterprise" => "IoT Enterprise",
+        "IoTEnterpriseS" => "IoT Enterprise LTSC",
+        "PPIPro" => "Team",
+        "CloudEdition" => "SE",
+        "CloudEditionN" => "SE N",
+        "ServerStandard" => "Server Standard",
+        "ServerDatacenter" => "Server Datacenter",
+        "ServerStandardCore" => "Server Standard (Core)",
+        "ServerDatacenterCore" => "Server Datacenter (Core)",
+        other => other,
+    };
+    name.to_string()
+}
+
+/// 常见的 Windows 客户端版本
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Edition {
+    /// 家庭版 (`Core`)
+    Home,
+    /// 家庭版 N (`CoreN`)
+    HomeN,
+    /// 家庭单语言版 (`CoreSingleLanguage`)
+    HomeSingleLanguage,
+    /// 专业版 (`Professional`)
+    Pro,
+    /// 专业版 N (`ProfessionalN`)
+    ProN,
+    /// 专业教育版 (`ProfessionalEducation`)
+    ProEducation,
+    /// 专业工作站版 (`ProfessionalWorkstation`)
+    ProWorkstations,
+    /// 教育版 (`Education`)
+    Education,
+    /// 教育版 N (`EducationN`)
+    EducationN,
+    /// 企业版 (`Enterprise`)
+    Enterprise,
+    /// 企业版 N (`EnterpriseN`)
+    EnterpriseN,
+    /// 企业版 LTSC (`EnterpriseS`)
+    EnterpriseLtsc,
+    /// IoT 企业版 (`IoTEnterprise`)
+    IoTEnterprise,
+    /// IoT 企业版 LTSC (`IoTEnterpriseS`)
+    IoTEnterpriseLtsc,
+    /// Surface Hub 使用的 Team 版 (`PPIPro`)
+    Team,
+    /// 面向教育场景的 SE 版 (`CloudEdition`)
+    Se,
+}
+
+impl Edition {
+    /// 对应的 EDITIONID
+    pub fn edition_id(&self) -> &'static str {
+        match self {
+            Edition::Home => "Core",
+            Edition::HomeN => "CoreN",
+            Edition::HomeSingleLanguage => "CoreSingleLanguage",
+            Edition::Pro => "Professional",
+            Edition::ProN => "ProfessionalN",
+            Edition::ProEducation => "ProfessionalEducation",
+            Edition::ProWorkstations => "ProfessionalWorkstation",
+            Edition::Education => "Education",
+            Edition::EducationN => "EducationN",
+            Edition::Enterprise => "Enterprise",
+            Edition::EnterpriseN => "EnterpriseN",
+            Edition::EnterpriseLtsc => "EnterpriseS",
+            Edition::IoTEnterprise => "IoTEnterprise",
+            Edition::IoTEnterpriseLtsc => "IoTEnterpriseS",
+            Edition::Team => "PPIPro",
+            Edition::Se => "CloudEdition",
+        }
+    }
+
+    /// 根据 EDITIONID 确定版本（不区分大小写）
+    pub fn from_edition_id(edition_id: &str) -> Option<Edition> {
+        const ALL: [Edition; 16] = [
+            Edition::Home,
+            Edition::HomeN,
+            Edition::HomeSingleLanguage,
+            Edition::Pro,
+            Edition::ProN,
+            Edition::ProEducation,
+            Edition::ProWorkstations,
+            Edition::Education,
+            Edition::EducationN,
+            Edition::Enterprise,
+            Edition::EnterpriseN,
+            Edition::EnterpriseLtsc,
+            Edition::IoTEnterprise,
+            Edition::IoTEnterpriseLtsc,
+            Edition::Team,
+            Edition::Se,
+        ];
+        ALL.into_iter()
+            .find(|edition| edition.edition_id().eq_ignore_ascii_case(edition_id))
+    }
+
+    /// 镜像是否属于该版本：优先比较 EDITIONID，没有时根据名称推断
+    pub fn matches(&self, image: &ImageInfo) -> bool {
+        match &image.edition_id {
+            Some(edition_id) => self.edition_id().eq_ignore_ascii_case(edition_id),
+            None => {
+                edition_from_name(&image.name)
+                    == Some(edition_display_name(self.edition_id()).as_str())
+            }
+        }
+    }
+}
+
+impl Edition {
+    /// 通过更换产品密钥可以直接升级到的版本（内置矩阵，依据 Windows 10/11 的版本升级路径）
+    ///
+    /// N 版本只能在 N 版本之间切换。
+    pub fn upgrade_targets(&self) -> &'static [Edition] {
+        use Edition::*;
+        match self {
+            Home => &[Pro, ProEducation, ProWorkstations, Education],
+            HomeSingleLanguage => &[Pro],
+            HomeN => &[ProN, EducationN],
+            Pro => &[ProEducation, ProWorkstations, Education, Enterprise],
+            ProN => &[EducationN, EnterpriseN],
+            ProWorkstations => &[ProEducation, Education, Enterprise],
+            ProEducation => &[Education],
+            Enterprise => &[Education],
+            EnterpriseN => &[EducationN],
+            EnterpriseLtsc => &[IoTEnterpriseLtsc],
+            Education | EducationN | IoTEnterprise | IoTEnterpriseLtsc | Team | Se => &[],
+        }
+    }
+
+    /// 支持的降级目标（例如订阅过期后企业版回到专业版），家庭版无法通过降级得到
+    pub fn downgrade_targets(&self) -> &'static [Edition] {
+        use Edition::*;
+        match self {
+            ProWorkstations => &[Pro],
+            ProEducation => &[Pro, ProWorkstations],
+            Enterprise => &[Pro, ProWorkstations, ProEducation],
+            EnterpriseN => &[ProN],
+            Education => &[Pro, ProWorkstations, ProEducation, Enterprise],
+            EducationN => &[ProN, EnterpriseN],
+            Home | HomeN | HomeSingleLanguage | Pro | ProN => &[],
+            EnterpriseLtsc | IoTEnterprise | IoTEnterpriseLtsc | Team | Se => &[],
+        }
+    }
+
+    /// 能否从当前版本切换到 `target`（升级或降级）
+    pub fn can_switch_to(&self, target: Edition) -> Option<SwitchKind> {
+        if self.upgrade_targets().contains(&target) {
+            Some(SwitchKind::Upgrade)
+        } else if self.downgrade_targets().contains(&target) {
+            Some(SwitchKind::Downgrade)
+        } else {
+            None
+        }
+    }
+}
+
+impl std::fmt::Display for Edition {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&edition_display_name(self.edition_id()))
+    }
+}
+
+/// 版本切换的方向
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SwitchKind {
+    /// 升级（更换产品密钥即可，不需要重新安装）
+    Upgrade,
+    /// 降级（保留应用和设置的版本回退）
+    Downgrade,
+}
+
+impl std::fmt::Display for SwitchKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            SwitchKind::Upgrade => "升级",
+            SwitchKind::Downgrade => "降级",
+        })
+    }
+}
+
+/// 一条版本切换路径
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UpgradePath {
+    /// 镜像中的版本
+    pub from: Edition,
+    /// 可以切换到的版本
+    pub to: Edition,
+    /// 升级或降级
+    pub kind: SwitchKind,
+    /// 目标版本是否也在此 WIM 文件中
+    pub target_in_media: bool,
+}
+
+impl ToTable for [UpgradePath] {
+    fn table(&self) -> Table {
+        let mut table = Table::new(["当前版本", "目标版本", "方向", "介质中包含目标"]);
+        for path in self {
+            table.push_row([
+                path.from.to_string(),
+                path.to.to_string(),
+                path.kind.to_string(),
+                if path.target_in_media { "是" } else { "否" }.to_string(),
+            ]);
+        }
+        table
+    }
+}
+
+impl ToTable for Vec<UpgradePath> {
+    fn table(&self) -> Table {
+        self.as_slice().table()
+    }
+}
+
+/// 没有 EDITIONID 时根据镜像名称推断版本
+///
+/// 更具体的名称先于通用名称判断（例如 `IoT Enterprise LTSC` 先于 `Enterprise`），
+/// `SE` 和 `Team` 按整词匹配，避免误匹配 `Enterprise` 等名称中的片段。
+fn edition_from_name(name: &str) -> Option<&'static str> {
+    let name_lower = name.to_lowercase();
+    let has_word = |word: &str| name_lower.split_whitespace().any(|part| part == word);
+    let ltsc = has_word("ltsc") || has_word("ltsb");
+    if name_lower.contains("iot enterprise") {
+        Some(if ltsc {
+            "IoT Enterprise LTSC"
+        } else {
+            "IoT Enterprise"
+        })
+    } else if has_word("team") {
+        Some("Team")
+    } else if has_word("se") {
+        Some("SE")
+    } else if name_lower.contains("pro") {
+        Some("Pro")
+    } else if name_lower.contains("home") {
+        Some("Home")
+    } else if name_lower.contains("enterprise") {
+        Some(if ltsc {
+            "Enterprise LTSC"
+        } else {
+            "Enterprise"
+        })
+    } else if name_lower.contains("education") {
+        Some("Education")
+    } else {
+        None
+    }
+}
+
+/// 镜像的版本名称：优先使用 EDITIONID，其次根据名称推断，最后使用镜像名称本身
+fn edition_of(image: &ImageInfo) -> String {
+    match (&image.edition_id, edition_from_name(&image.name)) {
+        (Some(edition_id), _) => edition_display_name(edition_id),
+        (None, Some(edition)) => edition.to_string(),
+        (None, None) => image.name.clone(),
+    }
+}
+
+/// 同一版本、同一架构的镜像分组（对应安装程序"选择要安装的版本"列表中的一行）
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EditionGroup {
+    /// 版本名称（例如 `Pro`、`Home`）
+    pub edition: String,
+    /// 原始 EDITIONID
+    pub edition_id: Option<String>,
+    /// 第一个镜像的显示名称
+    pub name: String,
+    /// 属于该分组的镜像索引
+    pub indexes: Vec<u32>,
+    /// 架构
+    pub architecture: Option<String>,
+    /// 内部版本号（分组内的最大值）
+    pub build: Option<u32>,
+    /// 分组内镜像的总字节数
+    pub total_bytes: u64,
+}
+
+impl ToTable for [EditionGroup] {
+    fn table(&self) -> Table {
+        let mut table = Table::new(["版本", "镜像", "架构", "内部版本", "大小"])
+            .align(3, Align::Right)
+            .align(4, Align::Right);
+
+        for group in self {
+            let indexes: Vec<String> = group.indexes.iter().map(u32::to_string).collect();
+            table.push_row([
+                group.edition.clone(),
+                indexes.join(", "),
+                group
+                    .architecture
+                    .clone()
+                    .unwrap_or_else(|| "-".to_string()),
+                group
+                    .build
+                    .map(|build| build.to_string())
+                    .unwrap_or_else(|| "-".to_string()),
+                format_bytes(group.total_bytes),
+            ]);
+        }
+        table
+    }
+}
+
+impl ToTable for Vec<EditionGroup> {
+    fn table(&self) -> Table {
+        self.as_slice().table()
+    }
+}
+
+impl WimParser {
+    /// 按版本和架构对镜像分组，按首次出现的镜像索引排序
+    ///
+    /// ```
+    /// # #[cfg(feature = "fixtures")] {
+    /// # let fixture = wim_parser::fixtures::MiniWim::create()?;
+    /// # let mut parser = wim_parser::WimParser::new(fixture.path())?;
+    /// parser.parse_full()?;
+    /// let groups = parser.edition_summary();
+    /// assert_eq!(groups.len(), 2);
+    /// assert_eq!(groups[1].indexes, [2]);
+    /// # }
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    pub fn edition_summary(&self) -> Vec<EditionGroup> {
+        let mut groups: Vec<EditionGroup> = Vec::new();
+
+        for image in &self.images {
+            let edition = edition_of(image);
+            let existing = groups
+                .iter_mut()
+                .find(|group| group.edition == edition && group.architecture == image.architecture);
+
+            match existing {
+                Some(group) => {
+                    group.indexes.push(image.index);
+                    group.build = group.build.max(image.build);
+                    group.total_bytes += image.total_bytes;
+                }
+                None => groups.push(EditionGroup {
+                    edition,
+                    edition_id: image.edition_id.clone(),
+                    name: image.name.clone(),
+                    indexes: vec![image.index],
+                    architecture: image.architecture.clone(),
+                    build: image.build,
+                    total_bytes: image.total_bytes,
+                }),
+            }
+        }
+
+        groups
+    }
+}
+
+impl WimParser {
+    /// 镜像中各版本（按首次出现的顺序、按 EDITIONID 识别）可以切换到的版本
+    ///
+    /// 基于内置矩阵（[`Edition::upgrade_targets`]、[`Edition::downgrade_targets`]），
+    /// 先列出升级再列出降级；无法识别的 EDITIONID 被忽略。
+    pub fn upgrade_paths(&self) -> Vec<UpgradePath> {
+        let mut editions: Vec<Edition> = Vec::new();
+        for edition in self
+            .images
+            .iter()
+            .filter_map(|image| image.edition_id.as_deref())
+            .filter_map(Edition::from_edition_id)
+        {
+            if !editions.contains(&edition) {
+                editions.push(edition);
+            }
+        }
+
+        let mut paths = Vec::new();
+        for &from in &editions {
+            let targets = from
+                .upgrade_targets()
+                .iter()
+                .map(|&to| (to, SwitchKind::Upgrade))
+                .chain(
+                    from.downgrade_targets()
+                        .iter()
+                        .map(|&to| (to, SwitchKind::Downgrade)),
+                );
+            for (to, kind) in targets {
+                paths.push(UpgradePath {
+                    from,
+                    to,
+                    kind,
+                    target_in_media: editions.contains(&to),
+                });
+            }
+        }
+        paths
+    }
+}
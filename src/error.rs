@@ -0,0 +1,373 @@
+use core::fmt;
+
+/// 格式解析错误（不依赖 `std`，可在 `no_std + alloc` 环境中使用）
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Error {
+    /// 数据长度不足
+    Truncated {
+        /// 需要的字节数
+        expected: usize,
+        /// 实际可用的字节数
+        actual: usize,
+    },
+    /// 无效的 WIM 文件签名
+    InvalidSignature,
+    /// 压缩数据损坏（无法按资源的压缩格式解压）
+    CorruptData(&'static str),
+    /// 无效的 XML 数据（BOM 或 UTF-16 编码错误）
+    InvalidXml(&'static str),
+    /// 超出 [`ResourceLimits`](crate::ResourceLimits) 设定的资源限制
+    LimitExceeded {
+        /// 超出的限制项（如 `max_memory`）
+        limit: &'static str,
+        /// 操作需要的数量
+        requested: u64,
+        /// 允许的最大数量
+        allowed: u64,
+    },
+    /// 尚不支持的格式特性（如压缩资源、跨分卷读取）
+    Unsupported(&'static str),
+    /// 不支持的数据流类型（如需要 DPX 应用的增量补丁），无法按原样释放
+    UnsupportedStreamType(&'static str),
+    /// 数据校验失败
+    VerificationFailed {
+        /// 校验失败的项目数量
+        failures: usize,
+    },
+    /// 写回前发现磁盘上的文件头已被其他进程修改
+    HeaderChanged {
+        /// 第一个不一致的字段
+        field: &'static str,
+    },
+    /// 文件正被其他进程编辑（已持有排他锁）
+    FileLocked,
+}
+
+/// 错误类别，对应错误码的高 16 位
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(u16)]
+pub enum ErrorCategory {
+    /// I/O 错误
+    Io = 0x0001,
+    /// 文件格式错误（签名、截断、结构损坏）
+    Format = 0x0002,
+    /// XML 数据错误
+    Xml = 0x0003,
+    /// 超出资源限制
+    Limit = 0x0004,
+    /// 不支持的特性
+    Unsupported = 0x0005,
+    /// 数据校验失败
+    Verification = 0x0006,
+    /// 与其他进程的并发修改冲突
+    Conflict = 0x0007,
+    /// 其他错误
+    Other = 0x00FF,
+}
+
+/// 稳定的数值错误码，供 C/Python 等 FFI 绑定使用
+///
+/// 错误码格式为 `(类别 << 16) | 细分码`，`0` 保留表示成功。已发布的值不会改变，
+/// 新增的错误只会分配新的值。
+///
+/// | 错误码        | 含义                      |
+/// |---------------|---------------------------|
+/// | `0x0001_0001` | 文件不存在                |
+/// | `0x0001_0002` | 权限不足                  |
+/// | `0x0001_0003` | 意外的文件结尾            |
+/// | `0x0001_00FF` | 其他 I/O 错误             |
+/// | `0x0002_0001` | 数据被截断                |
+/// | `0x0002_0002` | 无效的 WIM 文件签名       |
+/// | `0x0002_0003` | 压缩数据损坏              |
+/// | `0x0003_0001` | 无效的 XML 数据           |
+/// | `0x0004_0001` | 超出资源限制              |
+/// | `0x0005_0001` | 不支持的特性              |
+/// | `0x0005_0002` | 不支持的数据流类型        |
+/// | `0x0006_0001` | 数据校验失败              |
+/// | `0x0007_0001` | 文件头已被其他进程修改    |
+/// | `0x0007_0002` | 文件已被其他进程锁定      |
+/// | `0x00FF_0000` | 未分类错误                |
+pub mod codes {
+    /// 成功
+    pub const OK: u32 = 0;
+    /// 文件不存在
+    pub const IO_NOT_FOUND: u32 = 0x0001_0001;
+    /// 权限不足
+    pub const IO_PERMISSION_DENIED: u32 = 0x0001_0002;
+    /// 意外的文件结尾
+    pub const IO_UNEXPECTED_EOF: u32 = 0x0001_0003;
+    /// 其他 I/O 错误
+    pub const IO_OTHER: u32 = 0x0001_00FF;
+    /// 数据被截断
+    pub const FORMAT_TRUNCATED: u32 = 0x0002_0001;
+    /// 无效的 WIM 文件签名
+    pub const FORMAT_INVALID_SIGNATURE: u32 = 0x0002_0002;
+    /// 压缩数据损坏
+    pub const FORMAT_CORRUPT_DATA: u32 = 0x0002_0003;
+    /// 无效的 XML 数据
+    pub const XML_INVALID: u32 = 0x0003_0001;
+    /// 超出资源限制
+    pub const LIMIT_EXCEEDED: u32 = 0x0004_0001;
+    /// 不支持的特性
+    pub const UNSUPPORTED: u32 = 0x0005_0001;
+    /// 不支持的数据流类型
+    pub const UNSUPPORTED_STREAM_TYPE: u32 = 0x0005_0002;
+    /// 数据校验失败
+    pub const VERIFICATION_FAILED: u32 = 0x0006_0001;
+    /// 文件头已被其他进程修改
+    pub const CONFLICT_HEADER_CHANGED: u32 = 0x0007_0001;
+    /// 文件已被其他进程锁定
+    pub const CONFLICT_LOCKED: u32 = 0x0007_0002;
+    /// 未分类错误
+    pub const OTHER: u32 = 0x00FF_0000;
+}
+
+/// 命令行工具的进程退出码
+///
+/// 由错误类别决定（见 [`ErrorCategory::exit_code`]），供编排系统按失败原因分支处理。
+///
+/// | 退出码 | 含义                                 |
+/// |--------|--------------------------------------|
+/// | `0`    | 成功                                 |
+/// | `1`    | 未分类错误                           |
+/// | `2`    | 无效的 WIM 文件（签名、截断、XML）   |
+/// | `3`    | 数据校验失败                         |
+/// | `4`    | 不支持的特性                         |
+/// | `5`    | I/O 错误（文件不存在、权限不足等）   |
+/// | `6`    | 超出资源限制                         |
+/// | `7`    | 文件已被其他进程修改或锁定           |
+/// | `64`   | 命令行参数错误                       |
+pub mod exit_codes {
+    /// 成功
+    pub const OK: u8 = 0;
+    /// 未分类错误
+    pub const FAILURE: u8 = 1;
+    /// 无效的 WIM 文件
+    pub const INVALID_FILE: u8 = 2;
+    /// 数据校验失败
+    pub const VERIFICATION_FAILED: u8 = 3;
+    /// 不支持的特性
+    pub const UNSUPPORTED: u8 = 4;
+    /// I/O 错误
+    pub const IO: u8 = 5;
+    /// 超出资源限制
+    pub const LIMIT_EXCEEDED: u8 = 6;
+    /// 文件已被其他进程修改或锁定
+    pub const CONFLICT: u8 = 7;
+    /// 命令行参数错误
+    pub const USAGE: u8 = 64;
+}
+
+impl ErrorCategory {
+    /// 从错误码中提取类别
+    pub fn from_code(code: u32) -> Option<ErrorCategory> {
+        match code >> 16 {
+            0x0001 => Some(ErrorCategory::Io),
+            0x0002 => Some(ErrorCategory::Format),
+            0x0003 => Some(ErrorCategory::Xml),
+            0x0004 => Some(ErrorCategory::Limit),
+            0x0005 => Some(ErrorCategory::Unsupported),
+            0x0006 => Some(ErrorCategory::Verification),
+            0x0007 => Some(ErrorCategory::Conflict),
+            0x00FF => Some(ErrorCategory::Other),
+            _ => None,
+        }
+    }
+
+    /// 类别名称（用于 JSON 等机器可读输出）
+    pub fn name(&self) -> &'static str {
+        match self {
+            ErrorCategory::Io => "io",
+            ErrorCategory::Format => "format",
+            ErrorCategory::Xml => "xml",
+            ErrorCategory::Limit => "limit",
+            ErrorCategory::Unsupported => "unsupported",
+            ErrorCategory::Verification => "verification",
+            ErrorCategory::Conflict => "conflict",
+            ErrorCategory::Other => "other",
+        }
+    }
+
+    /// 对应的进程退出码（见 [`exit_codes`]）
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            ErrorCategory::Io => exit_codes::IO,
+            ErrorCategory::Format | ErrorCategory::Xml => exit_codes::INVALID_FILE,
+            ErrorCategory::Limit => exit_codes::LIMIT_EXCEEDED,
+            ErrorCategory::Unsupported => exit_codes::UNSUPPORTED,
+            ErrorCategory::Verification => exit_codes::VERIFICATION_FAILED,
+            ErrorCategory::Conflict => exit_codes::CONFLICT,
+            ErrorCategory::Other => exit_codes::FAILURE,
+        }
+    }
+}
+
+/// 错误码对应的进程退出码；`0` 映射为成功，无法识别的错误码映射为 [`exit_codes::FAILURE`]
+pub fn exit_code(code: u32) -> u8 {
+    if code == codes::OK {
+        return exit_codes::OK;
+    }
+    ErrorCategory::from_code(code).map_or(exit_codes::FAILURE, |category| category.exit_code())
+}
+
+impl Error {
+    /// 稳定的数值错误码（见 [`codes`]）
+    pub fn code(&self) -> u32 {
+        match self {
+            Error::Truncated { .. } => codes::FORMAT_TRUNCATED,
+            Error::InvalidSignature => codes::FORMAT_INVALID_SIGNATURE,
+            Error::CorruptData(_) => codes::FORMAT_CORRUPT_DATA,
+            Error::InvalidXml(_) => codes::XML_INVALID,
+            Error::LimitExceeded { .. } => codes::LIMIT_EXCEEDED,
+            Error::Unsupported(_) => codes::UNSUPPORTED,
+            Error::UnsupportedStreamType(_) => codes::UNSUPPORTED_STREAM_TYPE,
+            Error::VerificationFailed { .. } => codes::VERIFICATION_FAILED,
+            Error::HeaderChanged { .. } => codes::CONFLICT_HEADER_CHANGED,
+            Error::FileLocked => codes::CONFLICT_LOCKED,
+        }
+    }
+
+    /// 错误类别
+    pub fn category(&self) -> ErrorCategory {
+        ErrorCategory::from_code(self.code()).unwrap_or(ErrorCategory::Other)
+    }
+}
+
+/// I/O 错误对应的稳定错误码
+#[cfg(feature = "std")]
+pub fn io_error_code(err: &std::io::Error) -> u32 {
+    match err.kind() {
+        std::io::ErrorKind::NotFound => codes::IO_NOT_FOUND,
+        std::io::ErrorKind::PermissionDenied => codes::IO_PERMISSION_DENIED,
+        std::io::ErrorKind::UnexpectedEof => codes::IO_UNEXPECTED_EOF,
+        _ => err
+            .get_ref()
+            .and_then(|inner| inner.downcast_ref::<Error>())
+            .map(Error::code)
+            .unwrap_or(codes::IO_OTHER),
+    }
+}
+
+/// 任意 `anyhow` 错误对应的稳定错误码：沿错误链查找第一个可识别的错误
+#[cfg(feature = "parser")]
+pub fn error_code(err: &anyhow::Error) -> u32 {
+    err.chain()
+        .find_map(|cause| {
+            cause
+                .downcast_ref::<Error>()
+                .map(Error::code)
+                .or_else(|| cause.downcast_ref::<std::io::Error>().map(io_error_code))
+        })
+        .unwrap_or(codes::OTHER)
+}
+
+/// 机器可读的错误报告（命令行 `--error-format json` 的输出）
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ErrorReport {
+    /// 稳定错误码（见 [`codes`]）
+    pub code: u32,
+    /// 错误类别
+    pub category: ErrorCategory,
+    /// 进程退出码（见 [`exit_codes`]）
+    pub exit_code: u8,
+    /// 最外层错误信息
+    pub message: String,
+    /// 错误链中的其余原因（由外到内）
+    pub causes: Vec<String>,
+}
+
+#[cfg(feature = "std")]
+impl ErrorReport {
+    fn new(code: u32, message: String, causes: Vec<String>) -> Self {
+        Self {
+            code,
+            category: ErrorCategory::from_code(code).unwrap_or(ErrorCategory::Other),
+            exit_code: exit_code(code),
+            message,
+            causes,
+        }
+    }
+
+    /// 由类型化错误生成报告
+    pub fn from_error(err: &Error) -> Self {
+        Self::new(err.code(), err.to_string(), Vec::new())
+    }
+
+    /// 由 I/O 错误生成报告
+    pub fn from_io(err: &std::io::Error) -> Self {
+        Self::new(io_error_code(err), err.to_string(), Vec::new())
+    }
+
+    /// 由 `anyhow` 错误生成报告，错误码取自错误链（见 [`error_code`]）
+    #[cfg(feature = "parser")]
+    pub fn from_anyhow(err: &anyhow::Error) -> Self {
+        let causes = err.chain().skip(1).map(|cause| cause.to_string()).collect();
+        Self::new(error_code(err), err.to_string(), causes)
+    }
+
+    /// 单行 JSON 对象
+    pub fn to_json(&self) -> String {
+        let causes: Vec<String> = self.causes.iter().map(|cause| json_string(cause)).collect();
+        format!(
+            "{{\"code\":{},\"code_hex\":\"0x{:08X}\",\"category\":\"{}\",\"exit_code\":{},\"message\":{},\"causes\":[{}]}}",
+            self.code,
+            self.code,
+            self.category.name(),
+            self.exit_code,
+            json_string(&self.message),
+            causes.join(",")
+        )
+    }
+}
+
+/// JSON 字符串字面量（含引号与转义）
+#[cfg(feature = "std")]
+pub(crate) fn json_string(value: &str) -> String {
+    use core::fmt::Write;
+
+    let mut out = String::with_capacity(value.len() + 2);
+    out.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Truncated { expected, actual } => {
+                write!(f, "数据被截断: 需要 {expected} 字节, 实际 {actual} 字节")
+            }
+            Error::InvalidSignature => f.write_str("无效的 WIM 文件签名"),
+            Error::CorruptData(reason) => write!(f, "压缩数据损坏: {reason}"),
+            Error::InvalidXml(reason) => f.write_str(reason),
+            Error::LimitExceeded {
+                limit,
+                requested,
+                allowed,
+            } => write!(f, "超出资源限制 {limit}: 需要 {requested}, 允许 {allowed}"),
+            Error::Unsupported(feature) => write!(f, "不支持的特性: {feature}"),
+            Error::UnsupportedStreamType(kind) => write!(f, "不支持的数据流类型: {kind}"),
+            Error::VerificationFailed { failures } => write!(f, "数据校验失败: {failures} 项"),
+            Error::HeaderChanged { field } => write!(f, "文件头已被其他进程修改: {field}"),
+            Error::FileLocked => f.write_str("文件已被其他进程锁定"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
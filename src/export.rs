@@ -0,0 +1,196 @@
+use anyhow::{Context, Result};
+use std::path::Path;
+
+use crate::edition::Edition;
+use crate::log::{debug, info};
+use crate::writer::WimWriter;
+use crate::{format, Compression, FileFlags, FileResourceEntry, WimParser, WriteSettings};
+
+/// ESD 中按惯例存放安装介质镜像的索引（Windows Setup Media、Windows PE、Windows Setup）
+const SETUP_MEDIA_INDEXES: [u32; 3] = [1, 2, 3];
+
+/// 导出单个版本的结果
+#[derive(Debug, Clone)]
+pub struct ExportReport {
+    /// 导出的版本
+    pub edition: Edition,
+    /// 源文件中该版本的镜像索引
+    pub source_index: u32,
+    /// 源文件中的安装介质镜像索引（用于生成 boot.wim 和介质文件，不写入 install.wim）
+    pub setup_indexes: Vec<u32>,
+    /// 写入的数据流数量
+    pub stream_count: usize,
+    /// 写入的数据流总字节数（未压缩大小）
+    pub stream_bytes: u64,
+    /// 其中原样复制压缩分块（不解压再压缩）的数据流数量
+    pub raw_stream_count: usize,
+    /// 输出文件的压缩格式
+    pub compression: Compression,
+}
+
+impl WimParser {
+    /// 查找指定版本的镜像索引（ESD 中的安装介质镜像除外）
+    pub fn find_edition(&mut self, edition: Edition) -> Result<Option<u32>> {
+        if self.images.is_empty() {
+            self.parse_full()?;
+        }
+        let skip_setup = self.images.len() > SETUP_MEDIA_INDEXES.len();
+        Ok(self
+            .images
+            .iter()
+            .filter(|image| !(skip_setup && SETUP_MEDIA_INDEXES.contains(&image.index)))
+            .find(|image| edition.matches(image))
+            .map(|image| image.index))
+    }
+
+    /// 将指定版本导出为可由 DISM 安装的单镜像 install.wim
+    ///
+    /// 复制该镜像的元数据资源、引用的数据流和 XML 信息（镜像索引改为 1）。
+    /// 输出未压缩的 WIM；源文件中的压缩资源需要对应的解压支持。
+    pub fn export_edition(&mut self, edition: Edition, out: &Path) -> Result<ExportReport> {
+        self.export_edition_with(edition, out, WriteSettings::default())
+    }
+
+    /// 按写入设置（如 [`Preset::DismMax`](crate::Preset::DismMax)）导出指定版本
+    ///
+    /// 文件头的压缩格式、分块大小和完整性表按设置写入。设置的压缩格式和分块大小与源文件相同时
+    /// （如常见的 LZX→LZX），压缩的数据流原样复制分块表和分块，不解压再压缩；启用 `verify`
+    /// 特性时复制前校验解压后的 SHA-1 与偏移表记录一致。其余资源在压缩编码器实现之前以未压缩形式存放。
+    pub fn export_edition_with(
+        &mut self,
+        edition: Edition,
+        out: &Path,
+        settings: impl Into<WriteSettings>,
+    ) -> Result<ExportReport> {
+        let mut settings = settings.into();
+        // 元数据原样复制，链接目标是否已修正沿用源文件
+        settings.rp_fix = self.read_header()?.flags().contains(FileFlags::RP_FIX);
+        let source_index = self
+            .find_edition(edition)?
+            .ok_or_else(|| anyhow::anyhow!("源文件中找不到版本 {}", edition))?;
+        let image_count = self.images.len() as u32;
+        let setup_indexes = if image_count > SETUP_MEDIA_INDEXES.len() as u32 {
+            SETUP_MEDIA_INDEXES.to_vec()
+        } else {
+            Vec::new()
+        };
+
+        info!(
+            "导出版本 {} (镜像 {}) 到 {}",
+            edition,
+            source_index,
+            out.display()
+        );
+
+        let xml = format::decode_xml_utf16(&self.read_xml_buffer()?)?;
+        let image_xml = format::extract_image_xml(&xml, source_index)
+            .map(|node| format::set_image_index(node, 1))
+            .ok_or_else(|| anyhow::anyhow!("XML 数据中没有镜像 {}", source_index))?;
+
+        let metadata_entry = self
+            .read_lookup_table()?
+            .iter()
+            .filter(|entry| entry.is_metadata())
+            .nth(source_index as usize - 1)
+            .cloned()
+            .ok_or_else(|| anyhow::anyhow!("找不到镜像 {} 的元数据资源", source_index))?;
+        let metadata = self
+            .read_resource(&metadata_entry.resource)
+            .with_context(|| format!("读取镜像 {source_index} 的元数据资源失败"))?;
+        let root =
+            crate::metadata::parse_metadata_resource(&metadata, &self.options().resource_limits())?;
+
+        let mut hashes = Vec::new();
+        root.walk(&mut |entry| {
+            let entry_hashes =
+                std::iter::once(&entry.hash).chain(entry.streams.iter().map(|s| &s.hash));
+            hashes.extend(entry_hashes.filter(|hash| **hash != [0u8; 20]).copied());
+        });
+
+        // 压缩格式和分块大小相同时压缩分块可以原样复制
+        let raw_copy = settings.compression.is_compressed()
+            && self.read_header()?.compression() == settings.compression;
+        let mut writer = WimWriter::create(out, settings, self.options().file_lock_policy())?;
+        let mut stream_count = 0;
+        let mut stream_bytes = 0;
+        let mut raw_stream_count = 0;
+        for hash in hashes {
+            if writer.add_stream_ref(&hash) {
+                continue;
+            }
+            stream_count += 1;
+            let resource = self
+                .stream_resource(&hash)?
+                .ok_or_else(|| anyhow::anyhow!("偏移表中找不到数据流"))?;
+            if raw_copy && resource.is_compressed() && !resource.is_solid() {
+                let raw = self
+                    .read_raw_stream(&resource, &hash)
+                    .context("复制压缩数据流失败")?;
+                raw_stream_count += 1;
+                stream_bytes += resource.original_size;
+                writer.add_raw_stream(hash, &raw, resource.original_size)?;
+                continue;
+            }
+            let data = self
+                .read_stream_resource(&resource)
+                .context("读取数据流失败")?;
+            stream_bytes += data.len() as u64;
+            writer.add_stream(hash, &data)?;
+        }
+        writer.add_metadata(metadata_entry.hash, &metadata)?;
+
+        writer.finish(&format!("<WIM>{image_xml}</WIM>"))?;
+
+        debug!(
+            "导出完成 - 数据流: {} (原样复制 {}), 字节数: {}",
+            stream_count, raw_stream_count, stream_bytes
+        );
+
+        Ok(ExportReport {
+            edition,
+            source_index,
+            setup_indexes,
+            stream_count,
+            stream_bytes,
+            raw_stream_count,
+            compression: settings.compression,
+        })
+    }
+
+    /// 读取压缩数据流的原始分块表和分块；启用 `verify` 特性时先校验解压后的 SHA-1
+    #[cfg_attr(not(feature = "verify"), allow(unused_variables))]
+    pub(crate) fn read_raw_stream(
+        &mut self,
+        resource: &FileResourceEntry,
+        hash: &[u8; 20],
+    ) -> Result<Vec<u8>> {
+        #[cfg(feature = "verify")]
+        {
+            let mut reader = self.open_resource(resource)?;
+            let actual: [u8; 20] = crate::verify::hash_reader::<sha1::Sha1>(&mut reader)?.into();
+            if actual != *hash {
+                return Err(
+                    anyhow::Error::new(crate::Error::VerificationFailed { failures: 1 }).context(
+                        format!(
+                            "数据流 {} 的 SHA-1 与偏移表记录不一致 (实际: {})",
+                            crate::verify::Digest::Sha1(*hash),
+                            crate::verify::Digest::Sha1(actual)
+                        ),
+                    ),
+                );
+            }
+        }
+        self.read_raw_resource(resource)
+    }
+}
+
+/// 从多版本 ESD/WIM 中导出单个版本为 install.wim
+pub fn export_edition(
+    esd: impl AsRef<Path>,
+    edition: Edition,
+    out: impl AsRef<Path>,
+) -> Result<ExportReport> {
+    let mut parser = WimParser::new(esd.as_ref())?;
+    parser.parse_full()?;
+    parser.export_edition(edition, out.as_ref())
+}
@@ -0,0 +1,224 @@
+//! 镜像文件列表的惰性迭代器：按目录逐个解析目录项，不构建完整的目录树，
+//! 适合 `wimdir` 式的列表和 50 万以上目录项的大镜像中的搜索
+
+use anyhow::{Context, Result};
+use std::collections::{HashMap, HashSet};
+use std::vec;
+
+use crate::log::debug;
+use crate::metadata::{self, DirEntry};
+use crate::{ResourceLimits, WimParser, WimTimestamp};
+
+/// [`WimParser::list_files`] 返回的文件元数据（不含文件内容）
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileMetadata {
+    /// 文件属性 (FILE_ATTRIBUTE_*)
+    pub attributes: u32,
+    /// 安全描述符索引（对应 [`ImageMetadata::security_descriptors`](crate::ImageMetadata::security_descriptors)，`None` 表示无）
+    pub security_id: Option<u32>,
+    /// 未命名数据流的未压缩大小（目录、空文件和偏移表中找不到的数据流为 0）
+    pub size: u64,
+    /// 未命名数据流的 SHA-1（全零表示空文件或目录）
+    pub hash: [u8; 20],
+    /// 短文件名 (8.3)，没有时为空
+    pub short_name: String,
+    /// 创建时间
+    pub creation_time: WimTimestamp,
+    /// 最后访问时间
+    pub last_access_time: WimTimestamp,
+    /// 最后写入时间
+    pub last_write_time: WimTimestamp,
+    /// 重解析点标记（仅对重解析点有效）
+    pub reparse_tag: u32,
+    /// 硬链接组 ID（仅对非重解析点有效，0 表示不属于任何组）
+    pub hard_link_group_id: u64,
+    /// 命名数据流的数量
+    pub named_streams: usize,
+}
+
+impl FileMetadata {
+    fn new(entry: &DirEntry, sizes: &HashMap<[u8; 20], u64>) -> Self {
+        let is_reparse_point = entry.is_reparse_point();
+        Self {
+            attributes: entry.attributes,
+            security_id: u32::try_from(entry.security_id).ok(),
+            size: sizes.get(&entry.hash).copied().unwrap_or(0),
+            hash: entry.hash,
+            short_name: entry.short_name.clone(),
+            creation_time: entry.creation_time,
+            last_access_time: entry.last_access_time,
+            last_write_time: entry.last_write_time,
+            reparse_tag: if is_reparse_point {
+                entry.reparse_tag
+            } else {
+                0
+            },
+            hard_link_group_id: if is_reparse_point {
+                0
+            } else {
+                entry.hard_link_group_id
+            },
+            named_streams: entry
+                .streams
+                .iter()
+                .filter(|stream| !stream.name.is_empty())
+                .count(),
+        }
+    }
+
+    /// 是否为目录
+    pub fn is_directory(&self) -> bool {
+        self.attributes & metadata::FILE_ATTRIBUTE_DIRECTORY != 0
+    }
+
+    /// 是否为重解析点
+    pub fn is_reparse_point(&self) -> bool {
+        self.attributes & metadata::FILE_ATTRIBUTE_REPARSE_POINT != 0
+    }
+
+    /// 是否为隐藏文件
+    pub fn is_hidden(&self) -> bool {
+        self.attributes & metadata::FILE_ATTRIBUTE_HIDDEN != 0
+    }
+}
+
+/// 正在遍历的目录
+#[derive(Debug)]
+struct PendingDir {
+    /// 目录路径（`/` 分隔，根目录为空）
+    path: String,
+    /// 尚未返回的子目录项及其子目录项列表偏移
+    entries: vec::IntoIter<(DirEntry, u64)>,
+}
+
+/// 镜像中所有目录项的惰性迭代器（由 [`WimParser::list_files`] 创建）
+///
+/// 按深度优先顺序返回 `(路径, 元数据)`，目录先于其内容，同一目录中的目录项保持元数据资源中的顺序；
+/// 路径以 `/` 分隔、不含开头的 `/`，不包含根目录本身。
+/// 只保留解压后的元数据资源和当前路径上各目录的目录项列表，遇到损坏的元数据时返回一次错误后结束。
+#[derive(Debug)]
+pub struct FileList {
+    index: u32,
+    data: Vec<u8>,
+    limits: ResourceLimits,
+    /// 数据流 SHA-1 到未压缩大小的索引
+    sizes: HashMap<[u8; 20], u64>,
+    stack: Vec<PendingDir>,
+    /// 已访问过的子目录项列表偏移，防止循环引用
+    visited: HashSet<u64>,
+    /// 已解析的目录项数量（含根目录）
+    dentries: usize,
+}
+
+impl FileList {
+    /// 镜像索引
+    pub fn index(&self) -> u32 {
+        self.index
+    }
+
+    /// 已解析的目录项数量（含根目录）
+    pub fn loaded_entries(&self) -> usize {
+        self.dentries
+    }
+
+    /// 解析 `offset` 处的子目录项列表并压入遍历栈
+    fn push_dir(&mut self, path: String, offset: u64) -> Result<()> {
+        if offset == 0 {
+            return Ok(());
+        }
+        if self.stack.len() >= metadata::MAX_DIRECTORY_DEPTH {
+            return Err(anyhow::anyhow!("目录嵌套过深，元数据可能已损坏"));
+        }
+        if !self.visited.insert(offset) {
+            return Err(anyhow::anyhow!("检测到循环目录引用，偏移: {}", offset));
+        }
+        let entries = metadata::parse_dentry_list(&self.data, offset, &self.limits, self.dentries)
+            .with_context(|| format!("解析镜像 {} 的目录项失败", self.index))?;
+        self.dentries += entries.len();
+        self.stack.push(PendingDir {
+            path,
+            entries: entries.into_iter(),
+        });
+        Ok(())
+    }
+}
+
+impl Iterator for FileList {
+    type Item = Result<(String, FileMetadata)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let dir = self.stack.last_mut()?;
+            let Some((entry, subdir_offset)) = dir.entries.next() else {
+                self.stack.pop();
+                continue;
+            };
+            let path = if dir.path.is_empty() {
+                entry.name.clone()
+            } else {
+                format!("{}/{}", dir.path, entry.name)
+            };
+            let file = FileMetadata::new(&entry, &self.sizes);
+            if entry.is_directory() {
+                if let Err(err) = self.push_dir(path.clone(), subdir_offset) {
+                    self.stack.clear();
+                    return Some(Err(err));
+                }
+            }
+            return Some(Ok((path, file)));
+        }
+    }
+}
+
+impl WimParser {
+    /// 惰性列出镜像中的所有文件和目录，不释放文件内容
+    ///
+    /// 只读取并解压元数据资源，每个目录的目录项在迭代到它时才解析，
+    /// 内存占用约为元数据资源本身加上当前路径上各目录的目录项，不随镜像的文件总数增长。
+    /// 文件大小取自偏移表中对应数据流的未压缩大小。
+    ///
+    /// ```
+    /// # #[cfg(feature = "fixtures")] {
+    /// # let fixture = wim_parser::fixtures::MiniWim::create()?;
+    /// # let mut parser = wim_parser::WimParser::new(fixture.path())?;
+    /// for item in parser.list_files(1)? {
+    ///     let (path, file) = item?;
+    ///     if path.ends_with(".exe") {
+    ///         println!("{path} {} 字节", file.size);
+    ///     }
+    /// }
+    /// let dirs = parser
+    ///     .list_files(1)?
+    ///     .filter_map(Result::ok)
+    ///     .filter(|(_, file)| file.is_directory())
+    ///     .count();
+    /// assert_eq!(dirs, 5);
+    /// # }
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    pub fn list_files(&mut self, index: u32) -> Result<FileList> {
+        let data = self.read_metadata_bytes(index)?;
+        let limits = self.options().resource_limits();
+        let (_, subdir_offset) = metadata::parse_root_dentry(&data, &limits)
+            .with_context(|| format!("解析镜像 {index} 的元数据资源失败"))?;
+        let sizes = self
+            .read_lookup_table()?
+            .iter()
+            .filter(|entry| !entry.is_metadata())
+            .map(|entry| (entry.hash, entry.resource.original_size))
+            .collect();
+        debug!("开始列出镜像 {} 的文件", index);
+
+        let mut list = FileList {
+            index,
+            data,
+            limits,
+            sizes,
+            stack: Vec::new(),
+            visited: HashSet::new(),
+            dentries: 1,
+        };
+        list.push_dir(String::new(), subdir_offset)?;
+        Ok(list)
+    }
+}
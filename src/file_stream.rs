@@ -0,0 +1,92 @@
+//! 文件内容的流式读取：数据流的分块在读取到时才逐个解压，多 GB 的文件（WinSxS 负载、内嵌的 VHD）
+//! 可以直接通过管道传递，而不必整个读入内存
+
+use anyhow::Result;
+use std::io::{self, Cursor, Read};
+
+use crate::{ResourceReader, WimParser};
+
+/// 镜像中单个文件未命名数据流的顺序读取器（由 [`WimParser::open_file_stream`] 或
+/// [`LazyTree::open_file`](crate::LazyTree::open_file) 创建），内存占用约为一个分块
+pub struct FileStream<'a> {
+    /// 打开时为检查补丁签名已读出的开头部分
+    head: Cursor<Vec<u8>>,
+    /// 其余部分（空文件为 `None`）
+    reader: Option<ResourceReader<'a>>,
+    size: u64,
+    position: u64,
+}
+
+impl<'a> FileStream<'a> {
+    /// 空文件的读取器
+    pub(crate) fn empty() -> Self {
+        Self {
+            head: Cursor::new(Vec::new()),
+            reader: None,
+            size: 0,
+            position: 0,
+        }
+    }
+
+    /// `head` 为已从 `reader` 读出的开头部分
+    pub(crate) fn new(head: Vec<u8>, reader: ResourceReader<'a>) -> Self {
+        Self {
+            size: reader.size(),
+            head: Cursor::new(head),
+            reader: Some(reader),
+            position: 0,
+        }
+    }
+
+    /// 文件的未压缩大小
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    /// 已读出的字节数
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+}
+
+impl Read for FileStream<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let mut read = self.head.read(buf)?;
+        if read == 0 {
+            if let Some(reader) = &mut self.reader {
+                read = reader.read(buf)?;
+            }
+        }
+        self.position += read as u64;
+        Ok(read)
+    }
+}
+
+impl WimParser {
+    /// 打开镜像中单个文件的未命名数据流，返回按需解压的 [`FileStream`]（`impl Read`）
+    ///
+    /// 路径的查找规则与 [`extract_file`](Self::extract_file) 相同。打开时只解析路径上经过的目录、
+    /// 读取数据流的分块表和第一个分块；之后每次读取最多解压一个分块，
+    /// 适合把大文件直接传给 `io::copy`、哈希计算或解析器，而不必分配与文件等大的缓冲区。
+    /// 增量补丁数据流返回 [`Error::UnsupportedStreamType`](crate::Error::UnsupportedStreamType)。
+    ///
+    /// ```
+    /// # #[cfg(feature = "fixtures")] {
+    /// # let fixture = wim_parser::fixtures::MiniWim::create()?;
+    /// # let mut parser = wim_parser::WimParser::new(fixture.path())?;
+    /// use std::io::Read;
+    ///
+    /// let mut stream = parser.open_file_stream(1, "Windows/System32/ntoskrnl.exe")?;
+    /// let mut magic = [0u8; 2];
+    /// stream.read_exact(&mut magic)?;
+    /// assert_eq!(&magic, b"MZ");
+    /// let rest = std::io::copy(&mut stream, &mut std::io::sink())?;
+    /// assert_eq!(rest + 2, stream.size());
+    /// # }
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    pub fn open_file_stream(&mut self, index: u32, path: &str) -> Result<FileStream<'_>> {
+        let mut tree = self.open_lazy_tree(index)?;
+        tree.open_file(self, path)
+    }
+}
@@ -0,0 +1,66 @@
+//! 内嵌的迷你 WIM，供文档示例和下游测试使用（`fixtures` 特性）
+//!
+//! [`MINI_WIM`] 是由 `examples/make_fixture.rs` 生成的未压缩 WIM（约 8 KB），内容：
+//!
+//! | 索引 | 名称            | EDITIONID    | 架构 | 版本       |
+//! |------|-----------------|--------------|------|------------|
+//! | 1    | Windows 10 Home | Core         | x64  | 10.0.19045 |
+//! | 2    | Windows 10 Pro  | Professional | x64  | 10.0.19045 |
+//!
+//! 两个镜像都包含 `Windows/System32/ntoskrnl.exe`、`Windows/System32/kernel32.dll`（x64 PE 文件头）、
+//! `Windows/SysWOW64/kernel32.dll`、`setup.exe`（x86 PE 文件头）、`Windows/win.ini`、
+//! 内容为镜像名称的 `readme.txt` 和空目录 `Users/Public`；镜像 2 另有 `Windows/System32/gpedit.msc`。
+//! 所有目录项的时间为 2023-01-01 00:00:00 UTC。
+//!
+//! ```
+//! use wim_parser::fixtures::MiniWim;
+//! use wim_parser::WimParser;
+//!
+//! let fixture = MiniWim::create()?;
+//! let mut parser = WimParser::new(fixture.path())?;
+//! parser.parse_full()?;
+//! assert_eq!(parser.get_image_count(), 2);
+//! # Ok::<(), anyhow::Error>(())
+//! ```
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// 迷你 WIM 的全部字节
+pub const MINI_WIM: &[u8] = include_bytes!("../data/mini.wim");
+
+/// 写到临时目录中的 [`MINI_WIM`] 副本，释放时删除
+///
+/// [`WimParser`](crate::WimParser) 从文件读取，示例和测试可以直接打开 [`path`](Self::path)，
+/// 也可以修改副本而不影响其他示例。
+#[derive(Debug)]
+pub struct MiniWim {
+    path: PathBuf,
+}
+
+impl MiniWim {
+    /// 在系统临时目录中创建副本（文件名包含进程号和序号，可并发使用）
+    pub fn create() -> io::Result<Self> {
+        static NEXT: AtomicUsize = AtomicUsize::new(0);
+        let path = std::env::temp_dir().join(format!(
+            "wim-parser-mini-{}-{}.wim",
+            std::process::id(),
+            NEXT.fetch_add(1, Ordering::Relaxed)
+        ));
+        fs::write(&path, MINI_WIM)?;
+        Ok(Self { path })
+    }
+
+    /// 副本的路径
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for MiniWim {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
@@ -0,0 +1,305 @@
+//! 报告格式化：对齐的文本表格（可选 ANSI 颜色）
+//!
+//! 单行的 `Display` 输出在镜像较多（例如包含 11 个版本的 ISO）时难以阅读，
+//! 本模块提供按列对齐的表格，供命令行工具和库用户直接打印：
+//!
+//! ```ignore
+//! use wim_parser::fmt::ToTable;
+//!
+//! println!("{}", parser.get_images().table());
+//! ```
+//!
+//! 列宽按终端显示宽度计算，中日韩字符按 2 列处理。
+
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::ImageInfo;
+
+/// 表头使用的 ANSI 样式（粗体）
+const ANSI_BOLD: &str = "\x1b[1m";
+/// 分隔线使用的 ANSI 样式（暗色）
+const ANSI_DIM: &str = "\x1b[2m";
+/// 重置 ANSI 样式
+const ANSI_RESET: &str = "\x1b[0m";
+
+/// 列对齐方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Align {
+    /// 左对齐（默认）
+    #[default]
+    Left,
+    /// 右对齐，适合数值列
+    Right,
+}
+
+/// 按列对齐的文本表格
+#[derive(Debug, Clone, Default)]
+pub struct Table {
+    headers: Vec<String>,
+    aligns: Vec<Align>,
+    rows: Vec<Vec<String>>,
+    color: bool,
+}
+
+impl Table {
+    /// 使用指定表头创建表格
+    pub fn new<I, S>(headers: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let headers: Vec<String> = headers.into_iter().map(Into::into).collect();
+        Self {
+            aligns: alloc::vec![Align::Left; headers.len()],
+            headers,
+            rows: Vec::new(),
+            color: false,
+        }
+    }
+
+    /// 设置指定列的对齐方式（超出范围的列会被忽略）
+    pub fn align(mut self, column: usize, align: Align) -> Self {
+        if let Some(slot) = self.aligns.get_mut(column) {
+            *slot = align;
+        }
+        self
+    }
+
+    /// 是否输出 ANSI 颜色（表头粗体、分隔线暗色）
+    pub fn color(mut self, enabled: bool) -> Self {
+        self.color = enabled;
+        self
+    }
+
+    /// 追加一行；单元格不足时补空，多余的单元格被忽略
+    pub fn push_row<I, S>(&mut self, cells: I)
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let mut row: Vec<String> = cells
+            .into_iter()
+            .take(self.headers.len())
+            .map(Into::into)
+            .collect();
+        row.resize(self.headers.len(), String::new());
+        self.rows.push(row);
+    }
+
+    /// 表头
+    pub fn headers(&self) -> &[String] {
+        &self.headers
+    }
+
+    /// 所有数据行
+    pub fn rows(&self) -> &[Vec<String>] {
+        &self.rows
+    }
+
+    /// 是否没有数据行
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    /// 每列的显示宽度
+    fn column_widths(&self) -> Vec<usize> {
+        let mut widths: Vec<usize> = self.headers.iter().map(|h| display_width(h)).collect();
+        for row in &self.rows {
+            for (width, cell) in widths.iter_mut().zip(row) {
+                *width = (*width).max(display_width(cell));
+            }
+        }
+        widths
+    }
+
+    fn write_row(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+        cells: &[String],
+        widths: &[usize],
+    ) -> fmt::Result {
+        let last = cells.len().saturating_sub(1);
+        for (i, (cell, &width)) in cells.iter().zip(widths).enumerate() {
+            if i > 0 {
+                f.write_str("  ")?;
+            }
+            let padding = width - display_width(cell);
+            match self.aligns[i] {
+                Align::Left => {
+                    f.write_str(cell)?;
+                    // 行尾不输出多余空格
+                    if i != last {
+                        write_spaces(f, padding)?;
+                    }
+                }
+                Align::Right => {
+                    write_spaces(f, padding)?;
+                    f.write_str(cell)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Table {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let widths = self.column_widths();
+
+        if self.color {
+            f.write_str(ANSI_BOLD)?;
+        }
+        self.write_row(f, &self.headers, &widths)?;
+        if self.color {
+            f.write_str(ANSI_RESET)?;
+        }
+        f.write_str("\n")?;
+
+        if self.color {
+            f.write_str(ANSI_DIM)?;
+        }
+        let rule: Vec<String> = widths.iter().map(|&w| "-".repeat(w)).collect();
+        self.write_row(f, &rule, &widths)?;
+        if self.color {
+            f.write_str(ANSI_RESET)?;
+        }
+
+        for row in &self.rows {
+            f.write_str("\n")?;
+            self.write_row(f, row, &widths)?;
+        }
+        Ok(())
+    }
+}
+
+fn write_spaces(f: &mut fmt::Formatter<'_>, count: usize) -> fmt::Result {
+    for _ in 0..count {
+        f.write_str(" ")?;
+    }
+    Ok(())
+}
+
+/// 字符串在等宽终端中的显示宽度（中日韩及全角字符按 2 列计算）
+pub fn display_width(s: &str) -> usize {
+    s.chars().map(char_width).sum()
+}
+
+fn char_width(c: char) -> usize {
+    match c as u32 {
+        0x0000..=0x001F | 0x007F => 0,
+        0x1100..=0x115F
+        | 0x2E80..=0x303E
+        | 0x3041..=0x33FF
+        | 0x3400..=0x4DBF
+        | 0x4E00..=0x9FFF
+        | 0xA000..=0xA4CF
+        | 0xAC00..=0xD7A3
+        | 0xF900..=0xFAFF
+        | 0xFE30..=0xFE4F
+        | 0xFF00..=0xFF60
+        | 0xFFE0..=0xFFE6
+        | 0x20000..=0x3FFFD => 2,
+        _ => 1,
+    }
+}
+
+/// 将字节数格式化为易读的大小（例如 `4.50 GiB`）
+pub fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["KiB", "MiB", "GiB", "TiB", "PiB"];
+
+    if bytes < 1024 {
+        return alloc::format!("{bytes} B");
+    }
+
+    let mut value = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    alloc::format!("{value:.2} {}", UNITS[unit])
+}
+
+/// 紧凑的字节数表示，保留一位小数且不带空格（例如 `4.6GiB`、`512B`）
+pub fn format_bytes_compact(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["KiB", "MiB", "GiB", "TiB", "PiB"];
+
+    if bytes < 1024 {
+        return alloc::format!("{bytes}B");
+    }
+
+    let mut value = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    alloc::format!("{value:.1}{}", UNITS[unit])
+}
+
+/// 可以渲染为 [`Table`] 的报告
+pub trait ToTable {
+    /// 生成对齐的表格
+    fn table(&self) -> Table;
+}
+
+impl ToTable for [ImageInfo] {
+    fn table(&self) -> Table {
+        let mut table = Table::new(["索引", "名称", "版本", "架构", "目录数", "文件数", "总大小"])
+            .align(0, Align::Right)
+            .align(4, Align::Right)
+            .align(5, Align::Right)
+            .align(6, Align::Right);
+
+        for image in self {
+            table.push_row([
+                image.index.to_string(),
+                image.name.clone(),
+                image.version.clone().unwrap_or_else(|| "-".to_string()),
+                image
+                    .architecture
+                    .clone()
+                    .unwrap_or_else(|| "-".to_string()),
+                image.dir_count.to_string(),
+                image.file_count.to_string(),
+                format_bytes(image.total_bytes),
+            ]);
+        }
+        table
+    }
+}
+
+impl ToTable for Vec<ImageInfo> {
+    fn table(&self) -> Table {
+        self.as_slice().table()
+    }
+}
+
+/// 将 WIM 文件头中的 GUID 格式化为标准形式（例如 `{3F2504E0-4F89-11D3-9A0C-0305E82C3301}`）
+///
+/// 前三段按小端序存储，与 Windows 工具显示的值一致。
+pub fn format_guid(guid: &[u8; 16]) -> String {
+    alloc::format!(
+        "{{{:08X}-{:04X}-{:04X}-{:02X}{:02X}-{:02X}{:02X}{:02X}{:02X}{:02X}{:02X}}}",
+        u32::from_le_bytes([guid[0], guid[1], guid[2], guid[3]]),
+        u16::from_le_bytes([guid[4], guid[5]]),
+        u16::from_le_bytes([guid[6], guid[7]]),
+        guid[8],
+        guid[9],
+        guid[10],
+        guid[11],
+        guid[12],
+        guid[13],
+        guid[14],
+        guid[15]
+    )
+}
+
+/// 将 SHA-1 等摘要格式化为小写十六进制字符串
+pub fn format_hash(hash: &[u8]) -> String {
+    hash.iter()
+        .map(|byte| alloc::format!("{byte:02x}"))
+        .collect()
+}
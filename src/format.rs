@@ -0,0 +1,549 @@
+//! 纯解析逻辑：文件头、资源头以及从已解码字符串中提取镜像信息
+//!
+//! 本模块只依赖 `core` 和 `alloc`，在关闭 `std` 特性时同样可用，
+//! 适合 UEFI / 预启动部署代理等嵌入式环境复用。
+
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::ops::Range;
+
+use crate::version_rules;
+use crate::xml_tree;
+use crate::{Arch, Error, FileResourceEntry, ImageInfo, WimHeader, WimTimestamp, XmlElement};
+
+/// WIM 文件签名
+pub const WIM_SIGNATURE: [u8; 8] = *b"MSWIM\x00\x00\x00";
+
+/// 解析文件头所需的最少字节数
+pub const WIM_HEADER_MIN_SIZE: usize = 148;
+
+/// 磁盘上文件头的完整大小（含保留区域），完整性表从此处开始覆盖
+pub const WIM_HEADER_DISK_SIZE: usize = 208;
+
+/// 资源头 (_RESHDR_DISK_SHORT) 大小
+pub const RESOURCE_ENTRY_SIZE: usize = 24;
+
+fn ensure_len(buffer: &[u8], expected: usize) -> Result<(), Error> {
+    if buffer.len() < expected {
+        return Err(Error::Truncated {
+            expected,
+            actual: buffer.len(),
+        });
+    }
+    Ok(())
+}
+
+fn read_u16_le(buffer: &[u8], offset: usize) -> u16 {
+    u16::from_le_bytes([buffer[offset], buffer[offset + 1]])
+}
+
+fn read_u32_le(buffer: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(buffer[offset..offset + 4].try_into().unwrap())
+}
+
+fn read_u64_le(buffer: &[u8], offset: usize) -> u64 {
+    u64::from_le_bytes(buffer[offset..offset + 8].try_into().unwrap())
+}
+
+/// 解析 24 字节的资源头 (7 字节大小 + 1 字节标志 + 8 字节偏移 + 8 字节原始大小)
+pub fn parse_resource_entry(buffer: &[u8]) -> Result<FileResourceEntry, Error> {
+    ensure_len(buffer, RESOURCE_ENTRY_SIZE)?;
+
+    let mut size_array = [0u8; 8];
+    size_array[..7].copy_from_slice(&buffer[0..7]);
+
+    Ok(FileResourceEntry {
+        size: u64::from_le_bytes(size_array),
+        flags: buffer[7],
+        offset: read_u64_le(buffer, 8),
+        original_size: read_u64_le(buffer, 16),
+    })
+}
+
+/// 将资源头序列化为 24 字节的磁盘格式（`parse_resource_entry` 的逆操作）
+pub fn resource_entry_bytes(entry: &FileResourceEntry) -> [u8; RESOURCE_ENTRY_SIZE] {
+    let mut buffer = [0u8; RESOURCE_ENTRY_SIZE];
+    buffer[0..7].copy_from_slice(&entry.size.to_le_bytes()[..7]);
+    buffer[7] = entry.flags;
+    buffer[8..16].copy_from_slice(&entry.offset.to_le_bytes());
+    buffer[16..24].copy_from_slice(&entry.original_size.to_le_bytes());
+    buffer
+}
+
+/// 解析并校验 WIM 文件头 (WIMHEADER_V1_PACKED)
+pub fn parse_header(buffer: &[u8]) -> Result<WimHeader, Error> {
+    ensure_len(buffer, WIM_HEADER_MIN_SIZE)?;
+
+    let mut signature = [0u8; 8];
+    signature.copy_from_slice(&buffer[0..8]);
+    if signature != WIM_SIGNATURE {
+        return Err(Error::InvalidSignature);
+    }
+
+    Ok(WimHeader {
+        signature,
+        header_size: read_u32_le(buffer, 8),
+        format_version: read_u32_le(buffer, 12),
+        file_flags: read_u32_le(buffer, 16),
+        compressed_size: read_u32_le(buffer, 20),
+        guid: buffer[24..40].try_into().unwrap(),
+        segment_number: read_u16_le(buffer, 40),
+        total_segments: read_u16_le(buffer, 42),
+        image_count: read_u32_le(buffer, 44),
+        offset_table_resource: parse_resource_entry(&buffer[48..72])?,
+        xml_data_resource: parse_resource_entry(&buffer[72..96])?,
+        boot_metadata_resource: parse_resource_entry(&buffer[96..120])?,
+        bootable_image_index: read_u32_le(buffer, 120),
+        integrity_resource: parse_resource_entry(&buffer[124..148])?,
+    })
+}
+
+/// 将带 BOM 的 UTF-16 LE XML 资源解码为字符串
+pub fn decode_xml_utf16(xml_buffer: &[u8]) -> Result<String, Error> {
+    // XML 数据以 UTF-16 LE BOM 开始
+    if xml_buffer.len() < 2 {
+        return Err(Error::InvalidXml("XML 数据太短"));
+    }
+
+    // 检查 BOM (0xFEFF)
+    if xml_buffer[0] != 0xFF || xml_buffer[1] != 0xFE {
+        return Err(Error::InvalidXml("无效的 XML 数据 BOM"));
+    }
+
+    // 确保数据长度为偶数（UTF-16 每个字符 2 字节）
+    let xml_utf16_data = &xml_buffer[2..];
+    if !xml_utf16_data.len().is_multiple_of(2) {
+        return Err(Error::InvalidXml("XML UTF-16 数据长度不是偶数"));
+    }
+
+    let utf16_chars: Vec<u16> = xml_utf16_data
+        .chunks_exact(2)
+        .map(|chunk| u16::from_le_bytes([chunk[0], chunk[1]]))
+        .collect();
+
+    String::from_utf16(&utf16_chars).map_err(|_| Error::InvalidXml("无法将 XML 数据转换为 UTF-8"))
+}
+
+/// 将 XML 文本编码为带 BOM 的 UTF-16 LE（`decode_xml_utf16` 的逆操作）
+pub fn encode_xml_utf16(xml: &str) -> Vec<u8> {
+    let mut buffer = Vec::with_capacity(2 + xml.len() * 2);
+    buffer.extend_from_slice(&[0xFF, 0xFE]);
+    for unit in xml.encode_utf16() {
+        buffer.extend_from_slice(&unit.to_le_bytes());
+    }
+    buffer
+}
+
+/// 提取指定索引的 `<IMAGE>` 节点原文（包含起止标签）
+///
+/// 元素名可以带命名空间前缀，`INDEX` 属性可以出现在任意位置、使用单引号或双引号，节点也可以是自闭合标签。
+pub fn extract_image_xml(xml: &str, index: u32) -> Option<&str> {
+    scan_image_nodes(xml).into_iter().find_map(|node| {
+        let range = node.range?;
+        (image_index(node.start_tag) == Some(index)).then(|| &xml[range])
+    })
+}
+
+/// XML 文本中的一个 `<IMAGE>` 节点
+struct ImageNode<'a> {
+    /// 开始标签（含 `<` 和 `>`）
+    start_tag: &'a str,
+    /// 节点在 XML 文本中的范围（含起止标签），未闭合时为 `None`
+    range: Option<Range<usize>>,
+}
+
+/// 按文档顺序扫描所有 `<IMAGE>` 节点（元素名可带命名空间前缀），跳过注释和 CDATA
+///
+/// 在下一个 `<IMAGE>` 开始标签之前找不到结束标签的节点记为未闭合，扫描从它的开始标签之后继续。
+fn scan_image_nodes(xml: &str) -> Vec<ImageNode<'_>> {
+    let mut nodes = Vec::new();
+    let mut pos = 0;
+    while let Some(offset) = xml[pos..].find('<') {
+        let start = pos + offset;
+        let rest = &xml[start..];
+        let skip_to = |end: &str| {
+            rest.find(end)
+                .map_or(xml.len(), |len| start + len + end.len())
+        };
+        if rest.starts_with("<!--") {
+            pos = skip_to("-->");
+            continue;
+        }
+        if rest.starts_with("<![CDATA[") {
+            pos = skip_to("]]>");
+            continue;
+        }
+        let name = tag_name(&rest[1..]);
+        if xml_tree::local_name(name) != "IMAGE" {
+            pos = start + 1;
+            continue;
+        }
+        let Some(tag_len) = start_tag_len(rest) else {
+            break;
+        };
+        let start_tag = &rest[..tag_len];
+        let after = start + tag_len;
+        let range = if start_tag.ends_with("/>") {
+            Some(start..after)
+        } else {
+            closing_tag_end(xml, after, name).map(|end| start..end)
+        };
+        pos = range.as_ref().map_or(after, |range| range.end);
+        nodes.push(ImageNode { start_tag, range });
+    }
+    nodes
+}
+
+/// 标签名（到空白、`>` 或 `/` 为止）
+fn tag_name(text: &str) -> &str {
+    let end = text
+        .find(|c: char| c.is_whitespace() || matches!(c, '>' | '/'))
+        .unwrap_or(text.len());
+    &text[..end]
+}
+
+/// 开始标签的长度（到 `>` 为止，忽略属性值中的 `>`）
+fn start_tag_len(text: &str) -> Option<usize> {
+    let mut quote = None;
+    for (position, c) in text.char_indices() {
+        match (quote, c) {
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(open), _) if open == c => quote = None,
+            (None, '>') => return Some(position + 1),
+            _ => {}
+        }
+    }
+    None
+}
+
+/// `from` 之后 `</name>` 结束标签的结束位置；其间又出现同名开始标签时返回 `None`
+fn closing_tag_end(xml: &str, from: usize, name: &str) -> Option<usize> {
+    let mut pos = from;
+    while let Some(offset) = xml[pos..].find('<') {
+        let start = pos + offset;
+        let rest = &xml[start + 1..];
+        if let Some(close) = rest.strip_prefix('/') {
+            if tag_name(close) == name {
+                let end = close[name.len()..].find('>')?;
+                return Some(start + 2 + name.len() + end + 1);
+            }
+        } else if tag_name(rest) == name {
+            return None;
+        }
+        pos = start + 1;
+    }
+    None
+}
+
+/// 开始标签中属性值的范围（按本地名匹配属性名）
+fn tag_attribute(start_tag: &str, name: &str) -> Option<Range<usize>> {
+    let mut pos = 1 + tag_name(&start_tag[1..]).len();
+    loop {
+        let rest = &start_tag[pos..];
+        let trimmed = rest.trim_start();
+        pos += rest.len() - trimmed.len();
+        if trimmed.is_empty() || trimmed.starts_with(['/', '>']) {
+            return None;
+        }
+        let key_len = trimmed
+            .find(|c: char| c.is_whitespace() || matches!(c, '=' | '/' | '>'))
+            .unwrap_or(trimmed.len());
+        let key = &trimmed[..key_len];
+        pos += key_len;
+        let rest = &start_tag[pos..];
+        let Some(value) = rest.trim_start().strip_prefix('=') else {
+            // 没有值的属性
+            continue;
+        };
+        let value = value.trim_start();
+        pos += rest.len() - value.len();
+        let quote = value.chars().next().filter(|c| matches!(c, '"' | '\''))?;
+        let len = value[1..].find(quote)?;
+        let range = pos + 1..pos + 1 + len;
+        pos = range.end + 1;
+        if key == name || (!key.starts_with("xmlns") && xml_tree::local_name(key) == name) {
+            return Some(range);
+        }
+    }
+}
+
+/// 开始标签的 `INDEX` 属性
+fn image_index(start_tag: &str) -> Option<u32> {
+    let range = tag_attribute(start_tag, "INDEX")?;
+    start_tag[range].trim().parse().ok()
+}
+
+/// XML 中 `<IMAGE>` 开始标签的数量（含未闭合的节点）
+#[cfg(feature = "parser")]
+pub(crate) fn count_image_nodes(xml: &str) -> usize {
+    scan_image_nodes(xml).len()
+}
+
+/// 把 `<IMAGE>` 节点开始标签中的 `INDEX` 属性改为 `index`（没有时插入），保留其余属性和引号风格
+#[cfg(feature = "parser")]
+pub(crate) fn set_image_index(node: &str, index: u32) -> String {
+    let Some(tag_len) = start_tag_len(node) else {
+        return node.to_string();
+    };
+    match tag_attribute(&node[..tag_len], "INDEX") {
+        Some(range) => format!("{}{index}{}", &node[..range.start], &node[range.end..]),
+        None => {
+            let name_end = 1 + tag_name(&node[1..]).len();
+            format!(
+                "{} INDEX=\"{index}\"{}",
+                &node[..name_end],
+                &node[name_end..]
+            )
+        }
+    }
+}
+
+/// 从 XML 中提取第一个指定标签的文本值
+fn extract_tag_value(xml: &str, tag: &str) -> Option<String> {
+    let start_tag = format!("<{tag}>");
+    let end_tag = format!("</{tag}>");
+
+    if let Some(start) = xml.find(&start_tag) {
+        if let Some(end) = xml.find(&end_tag) {
+            let value_start = start + start_tag.len();
+            if value_start < end {
+                return Some(xml[value_start..end].trim().to_string());
+            }
+        }
+    }
+    None
+}
+
+/// 提取某个标签所有出现处的值
+fn extract_tag_values(xml: &str, tag: &str) -> Vec<String> {
+    let start_tag = format!("<{tag}>");
+    let end_tag = format!("</{tag}>");
+
+    let mut values = Vec::new();
+    let mut rest = xml;
+    while let Some(start) = rest.find(&start_tag) {
+        let value_start = start + start_tag.len();
+        let Some(len) = rest[value_start..].find(&end_tag) else {
+            break;
+        };
+        values.push(rest[value_start..value_start + len].trim().to_string());
+        rest = &rest[value_start + len + end_tag.len()..];
+    }
+    values
+}
+
+/// 解析 `<HIGHPART>` / `<LOWPART>` 形式的时间节点（十六进制，可带 `0x` 前缀）
+fn parse_xml_time(xml: &str, tag: &str) -> Option<WimTimestamp> {
+    let node = extract_tag_value(xml, tag)?;
+    let part = |name| parse_hex_u32(&extract_tag_value(&node, name)?);
+    Some(WimTimestamp::from_parts(
+        part("HIGHPART")?,
+        part("LOWPART")?,
+    ))
+}
+
+/// 解析十六进制数值（可带 `0x` 前缀）
+fn parse_hex_u32(value: &str) -> Option<u32> {
+    let value = value.trim();
+    let hex = value
+        .strip_prefix("0x")
+        .or_else(|| value.strip_prefix("0X"))
+        .unwrap_or(value);
+    u32::from_str_radix(hex, 16).ok()
+}
+
+/// 从已解码的 XML 文本中提取所有镜像信息
+pub fn parse_images_from_xml(xml_content: &str) -> Vec<ImageInfo> {
+    parse_images_from_xml_with(xml_content, true)
+}
+
+/// 从已解码的 XML 文本中提取所有镜像信息
+///
+/// `windows_metadata` 为 `false` 时只提取名称、描述和统计信息，跳过 `<WINDOWS>` 节
+/// 以及从名称推断版本和架构的步骤。
+pub fn parse_images_from_xml_with(xml_content: &str, windows_metadata: bool) -> Vec<ImageInfo> {
+    scan_image_nodes(xml_content)
+        .into_iter()
+        .filter_map(|node| node.range)
+        .map(|range| parse_single_image_xml_with(&xml_content[range], windows_metadata))
+        .collect()
+}
+
+/// 解析单个 `<IMAGE>` 节点的信息
+pub fn parse_single_image_xml(image_xml: &str) -> ImageInfo {
+    parse_single_image_xml_with(image_xml, true)
+}
+
+/// 镜像节点的字段来源：元素树（支持命名空间前缀和自闭合标签），XML 格式错误时退回字符串匹配
+enum Fields<'a> {
+    Tree(&'a XmlElement),
+    Text(&'a str),
+}
+
+impl Fields<'_> {
+    /// 第一个指定名称的后代元素的文本（为空时返回 `None`）
+    fn value(&self, tag: &str) -> Option<String> {
+        match self {
+            Fields::Tree(element) => element
+                .descendant(tag)
+                .filter(|child| !child.text.is_empty())
+                .map(|child| child.text.clone()),
+            Fields::Text(xml) => extract_tag_value(xml, tag),
+        }
+    }
+
+    /// 所有指定名称的后代元素的文本
+    fn values(&self, tag: &str) -> Vec<String> {
+        match self {
+            Fields::Tree(element) => element
+                .descendants_named(tag)
+                .into_iter()
+                .map(|child| child.text.clone())
+                .collect(),
+            Fields::Text(xml) => extract_tag_values(xml, tag),
+        }
+    }
+
+    /// `<HIGHPART>` / `<LOWPART>` 形式的时间节点
+    fn time(&self, tag: &str) -> Option<WimTimestamp> {
+        match self {
+            Fields::Tree(element) => {
+                let node = element.descendant(tag)?;
+                let part = |name| parse_hex_u32(&node.descendant(name)?.text);
+                Some(WimTimestamp::from_parts(
+                    part("HIGHPART")?,
+                    part("LOWPART")?,
+                ))
+            }
+            Fields::Text(xml) => parse_xml_time(xml, tag),
+        }
+    }
+}
+
+/// 解析单个 `<IMAGE>` 节点的信息，`windows_metadata` 控制是否提取版本和架构
+///
+/// 元素和属性可以带命名空间前缀（如 `<wim:IMAGE wim:INDEX="2">`），属性顺序任意，
+/// 空字段可以写成自闭合标签。
+pub fn parse_single_image_xml_with(image_xml: &str, windows_metadata: bool) -> ImageInfo {
+    let image_xml = image_xml.trim();
+    let xml = XmlElement::parse(image_xml);
+    let fields = match &xml {
+        Some(element) => Fields::Tree(element),
+        None => Fields::Text(image_xml),
+    };
+    let index = start_tag_len(image_xml)
+        .and_then(|len| image_index(&image_xml[..len]))
+        .unwrap_or(0);
+
+    // 提取各种信息
+    let name = fields
+        .value("DISPLAYNAME")
+        .unwrap_or_else(|| format!("Image {index}"));
+    let english_name = fields.value("NAME");
+    let description = fields
+        .value("DISPLAYDESCRIPTION")
+        .unwrap_or_else(|| "Unknown".to_string());
+    let dir_count = fields
+        .value("DIRCOUNT")
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(0);
+    let file_count = fields
+        .value("FILECOUNT")
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(0);
+    let total_bytes = fields
+        .value("TOTALBYTES")
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(0);
+    let flags = fields.value("FLAGS");
+
+    // 从名称中提取版本信息，架构信息优先使用XML中的ARCH标签
+    let (version, architecture) = if windows_metadata {
+        let (version, arch_from_name) = extract_version_and_arch(&name, &description);
+        (
+            version,
+            fields
+                .value("ARCH")
+                .and_then(|value| arch_name(&value))
+                .or(arch_from_name),
+        )
+    } else {
+        (None, None)
+    };
+    let windows_tag = |tag| {
+        if windows_metadata {
+            fields.value(tag)
+        } else {
+            None
+        }
+    };
+    let product_type = windows_tag("PRODUCTTYPE");
+    let installation_type = windows_tag("INSTALLATIONTYPE");
+    let edition_id = windows_tag("EDITIONID");
+    let build = windows_tag("BUILD").and_then(|s| s.parse().ok());
+    let sp_build = windows_tag("SPBUILD").and_then(|s| s.parse().ok());
+    let arch_raw = windows_tag("ARCH").and_then(|s| s.trim().parse().ok());
+    let languages = if windows_metadata {
+        fields.values("LANGUAGE")
+    } else {
+        Vec::new()
+    };
+    let default_language = windows_tag("DEFAULT");
+
+    ImageInfo {
+        index,
+        name,
+        english_name,
+        description,
+        dir_count,
+        file_count,
+        total_bytes,
+        creation_time: fields.time("CREATIONTIME"),
+        last_modification_time: fields.time("LASTMODIFICATIONTIME"),
+        version,
+        architecture,
+        arch_raw,
+        flags,
+        product_type,
+        installation_type,
+        edition_id,
+        build,
+        sp_build,
+        languages,
+        default_language,
+        xml,
+    }
+}
+
+/// 将 ARCH 数值映射为架构名称（无法识别的数值返回 `None`，原始值见 [`ImageInfo::arch_raw`]）
+pub fn arch_name(value: &str) -> Option<String> {
+    let raw = value.trim().parse().ok()?;
+    Arch::from_raw(raw).name().map(|name| name.to_string())
+}
+
+/// 从XML中的ARCH标签解析架构信息
+pub fn parse_arch_from_xml(image_xml: &str) -> Option<String> {
+    extract_tag_value(image_xml, "ARCH").and_then(|value| arch_name(&value))
+}
+
+/// 从镜像名称和描述中提取版本和架构信息
+pub fn extract_version_and_arch(name: &str, description: &str) -> (Option<String>, Option<String>) {
+    let combined_text = format!("{name} {description}").to_lowercase();
+
+    // 提取版本信息（内置规则，见 [`VersionRules`](crate::VersionRules)）
+    let version = version_rules::detect_builtin(&combined_text).map(str::to_string);
+
+    // 提取架构信息
+    let architecture = if combined_text.contains("x64") || combined_text.contains("amd64") {
+        Some("x64".to_string())
+    } else if combined_text.contains("x86") {
+        Some("x86".to_string())
+    } else if combined_text.contains("arm64") {
+        Some("ARM64".to_string())
+    } else {
+        None
+    };
+
+    (version, architecture)
+}
@@ -0,0 +1,211 @@
+//! 镜像内路径的通配符模式：`*`、`?`、`[...]` 和跨目录的 `**`，不区分大小写
+
+use anyhow::Result;
+use std::fmt;
+
+/// 路径组成部分中的一个匹配单元
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    /// 单个字符（已转为小写）
+    Literal(char),
+    /// `?`：任意单个字符
+    One,
+    /// `*`：任意个字符（不跨越 `/`）
+    Any,
+    /// `[...]` / `[!...]`：字符集合（范围已转为小写）
+    Class {
+        negated: bool,
+        ranges: Vec<(char, char)>,
+    },
+}
+
+/// 模式的一个路径组成部分
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    /// `**`：零个或多个目录层级
+    AnyDirs,
+    Tokens(Vec<Token>),
+}
+
+/// 镜像内路径的通配符模式
+///
+/// 路径以 `/` 或 `\` 分隔，开头的分隔符可省略；`*` 和 `?` 不跨越分隔符，单独成段的 `**`
+/// 匹配零个或多个目录层级，`[abc]`、`[a-z]`、`[!...]` 匹配字符集合。比较时不区分大小写。
+///
+/// ```
+/// # #[cfg(feature = "parser")] {
+/// use wim_parser::PathPattern;
+///
+/// let drivers = PathPattern::new("Windows/System32/drivers/**/*.sys")?;
+/// assert!(drivers.matches("/Windows/System32/drivers/NTFS.SYS"));
+/// assert!(drivers.matches(r"windows\system32\drivers\en-US\beep.sys"));
+/// assert!(!drivers.matches("Windows/System32/ntoskrnl.exe"));
+/// # }
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PathPattern {
+    pattern: String,
+    segments: Vec<Segment>,
+}
+
+/// 路径的组成部分（`\\` 或 `/` 分隔，忽略空段）
+fn components(path: &str) -> impl Iterator<Item = &str> {
+    path.split(['\\', '/']).filter(|part| !part.is_empty())
+}
+
+/// 转为小写，用于不区分大小写的比较（只取单个字符的小写形式）
+fn fold(c: char) -> char {
+    c.to_lowercase().next().unwrap_or(c)
+}
+
+impl PathPattern {
+    /// 解析模式，`[` 未闭合或模式为空时返回错误
+    pub fn new(pattern: &str) -> Result<Self> {
+        let segments = components(pattern)
+            .map(|part| parse_segment(part, pattern))
+            .collect::<Result<Vec<_>>>()?;
+        if segments.is_empty() {
+            return Err(anyhow::anyhow!("通配符模式为空: {:?}", pattern));
+        }
+        Ok(Self {
+            pattern: pattern.to_string(),
+            segments,
+        })
+    }
+
+    /// 原始模式
+    pub fn as_str(&self) -> &str {
+        &self.pattern
+    }
+
+    /// 路径是否匹配此模式
+    pub fn matches(&self, path: &str) -> bool {
+        let parts: Vec<Vec<char>> = components(path)
+            .map(|part| part.chars().map(fold).collect())
+            .collect();
+        match_segments(&self.segments, &parts)
+    }
+
+    /// 路径本身或它的某个上级目录是否匹配此模式
+    pub fn matches_self_or_parent(&self, path: &str) -> bool {
+        let parts: Vec<Vec<char>> = components(path)
+            .map(|part| part.chars().map(fold).collect())
+            .collect();
+        (1..=parts.len()).any(|len| match_segments(&self.segments, &parts[..len]))
+    }
+}
+
+impl fmt::Display for PathPattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.pattern)
+    }
+}
+
+fn parse_segment(part: &str, pattern: &str) -> Result<Segment> {
+    if part == "**" {
+        return Ok(Segment::AnyDirs);
+    }
+    let mut tokens = Vec::new();
+    let mut chars = part.chars().peekable();
+    while let Some(c) = chars.next() {
+        let token = match c {
+            '*' => {
+                // 段内连续的 `*` 等同于一个
+                while chars.peek() == Some(&'*') {
+                    chars.next();
+                }
+                Token::Any
+            }
+            '?' => Token::One,
+            '[' => {
+                let negated = chars.next_if(|&c| c == '!' || c == '^').is_some();
+                let mut ranges = Vec::new();
+                let mut closed = false;
+                let mut first = true;
+                while let Some(c) = chars.next() {
+                    if c == ']' && !first {
+                        closed = true;
+                        break;
+                    }
+                    first = false;
+                    let end = match chars.peek() {
+                        Some('-') => {
+                            chars.next();
+                            match chars.next_if(|&next| next != ']') {
+                                Some(end) => end,
+                                None => {
+                                    // 结尾的 `-` 按字面处理
+                                    ranges.push(('-', '-'));
+                                    c
+                                }
+                            }
+                        }
+                        _ => c,
+                    };
+                    ranges.push((fold(c), fold(end)));
+                }
+                if !closed {
+                    return Err(anyhow::anyhow!("通配符模式中的 [ 未闭合: {:?}", pattern));
+                }
+                Token::Class { negated, ranges }
+            }
+            c => Token::Literal(fold(c)),
+        };
+        tokens.push(token);
+    }
+    Ok(Segment::Tokens(tokens))
+}
+
+fn match_segments(segments: &[Segment], parts: &[Vec<char>]) -> bool {
+    match segments.split_first() {
+        None => parts.is_empty(),
+        Some((Segment::AnyDirs, rest)) => {
+            (0..=parts.len()).any(|skip| match_segments(rest, &parts[skip..]))
+        }
+        Some((Segment::Tokens(tokens), rest)) => match parts.split_first() {
+            Some((part, remaining)) => {
+                match_tokens(tokens, part) && match_segments(rest, remaining)
+            }
+            None => false,
+        },
+    }
+}
+
+/// 单个路径组成部分的匹配（`*` 回溯到最近一次的位置）
+fn match_tokens(tokens: &[Token], text: &[char]) -> bool {
+    let (mut t, mut c) = (0, 0);
+    let mut star: Option<(usize, usize)> = None;
+    while c < text.len() {
+        match tokens.get(t) {
+            Some(Token::Any) => {
+                star = Some((t, c));
+                t += 1;
+            }
+            Some(token) if token_matches(token, text[c]) => {
+                t += 1;
+                c += 1;
+            }
+            _ => match star {
+                Some((star_t, star_c)) => {
+                    t = star_t + 1;
+                    c = star_c + 1;
+                    star = Some((star_t, star_c + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    tokens[t..].iter().all(|token| *token == Token::Any)
+}
+
+fn token_matches(token: &Token, c: char) -> bool {
+    match token {
+        Token::Literal(literal) => *literal == c,
+        Token::One => true,
+        Token::Any => false,
+        Token::Class { negated, ranges } => {
+            ranges.iter().any(|&(start, end)| start <= c && c <= end) != *negated
+        }
+    }
+}
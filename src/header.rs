@@ -0,0 +1,307 @@
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::{format, FileFlags, FileResourceEntry, WimHeader};
+
+/// 文件头中已定义字段占用的字节数（之后为保留区域）
+pub const HEADER_FIELDS_SIZE: usize = 148;
+
+/// 文件头字段布局：(字段名, 偏移, 长度)
+const FIELD_LAYOUT: [(&str, usize, usize); 26] = [
+    ("signature", 0, 8),
+    ("header_size", 8, 4),
+    ("format_version", 12, 4),
+    ("file_flags", 16, 4),
+    ("compressed_size", 20, 4),
+    ("guid", 24, 16),
+    ("segment_number", 40, 2),
+    ("total_segments", 42, 2),
+    ("image_count", 44, 4),
+    ("offset_table_resource.size", 48, 7),
+    ("offset_table_resource.flags", 55, 1),
+    ("offset_table_resource.offset", 56, 8),
+    ("offset_table_resource.original_size", 64, 8),
+    ("xml_data_resource.size", 72, 7),
+    ("xml_data_resource.flags", 79, 1),
+    ("xml_data_resource.offset", 80, 8),
+    ("xml_data_resource.original_size", 88, 8),
+    ("boot_metadata_resource.size", 96, 7),
+    ("boot_metadata_resource.flags", 103, 1),
+    ("boot_metadata_resource.offset", 104, 8),
+    ("boot_metadata_resource.original_size", 112, 8),
+    ("bootable_image_index", 120, 4),
+    ("integrity_resource.size", 124, 7),
+    ("integrity_resource.flags", 131, 1),
+    ("integrity_resource.offset", 132, 8),
+    ("integrity_resource.original_size", 140, 8),
+];
+
+/// 文件头标志位集合（类型化的 [`FileFlags`] 视图）
+///
+/// 原始值始终保留，未定义的标志位可通过 [`unknown_bits`](Self::unknown_bits) 获得，
+/// 解析时作为 [`Warning::UnknownHeaderFlags`](crate::Warning::UnknownHeaderFlags) 报告。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct HeaderFlags(pub u32);
+
+impl HeaderFlags {
+    /// 已定义的标志位及其名称
+    const NAMED: [(u32, &'static str); 11] = [
+        (FileFlags::COMPRESSION, "COMPRESSION"),
+        (FileFlags::READONLY, "READONLY"),
+        (FileFlags::SPANNED, "SPANNED"),
+        (FileFlags::RESOURCE_ONLY, "RESOURCE_ONLY"),
+        (FileFlags::METADATA_ONLY, "METADATA_ONLY"),
+        (FileFlags::WRITE_IN_PROGRESS, "WRITE_IN_PROGRESS"),
+        (FileFlags::RP_FIX, "RP_FIX"),
+        (FileFlags::COMPRESS_XPRESS, "COMPRESS_XPRESS"),
+        (FileFlags::COMPRESS_LZX, "COMPRESS_LZX"),
+        (FileFlags::COMPRESS_LZMS, "COMPRESS_LZMS"),
+        (FileFlags::COMPRESS_XPRESS_2, "COMPRESS_XPRESS_2"),
+    ];
+
+    /// 可以原地修改的标志：只描述文件状态，不影响资源布局和压缩格式
+    pub const EDITABLE: u32 =
+        FileFlags::READONLY | FileFlags::WRITE_IN_PROGRESS | FileFlags::RP_FIX;
+
+    /// 按名称查找标志位（不区分大小写，可省略 `FLAG_` 前缀），例如 `READONLY`
+    pub fn from_name(name: &str) -> Option<u32> {
+        let name = name.trim();
+        let name = name
+            .get(..5)
+            .filter(|prefix| prefix.eq_ignore_ascii_case("FLAG_"))
+            .map_or(name, |_| &name[5..]);
+        Self::NAMED
+            .iter()
+            .find(|(_, known)| known.eq_ignore_ascii_case(name))
+            .map(|(bit, _)| *bit)
+    }
+
+    /// 原始标志值
+    pub fn bits(&self) -> u32 {
+        self.0
+    }
+
+    /// 是否包含指定标志位
+    pub fn contains(&self, flag: u32) -> bool {
+        self.0 & flag == flag
+    }
+
+    /// 已设置的已知标志名称
+    pub fn names(&self) -> Vec<&'static str> {
+        Self::NAMED
+            .iter()
+            .filter(|(bit, _)| self.contains(*bit))
+            .map(|(_, name)| *name)
+            .collect()
+    }
+
+    /// 未定义的已设置标志位
+    pub fn unknown_bits(&self) -> u32 {
+        let known = Self::NAMED.iter().fold(0, |acc, (bit, _)| acc | bit);
+        self.0 & !known
+    }
+}
+
+impl fmt::Display for HeaderFlags {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut names = self.names().join("|");
+        if self.unknown_bits() != 0 {
+            if !names.is_empty() {
+                names.push('|');
+            }
+            names.push_str(&alloc::format!("0x{:08X}", self.unknown_bits()));
+        }
+        if names.is_empty() {
+            names.push_str("NONE");
+        }
+        f.write_str(&names)
+    }
+}
+
+/// WIM 文件的种类（由文件头标志和镜像数量确定）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum WimKind {
+    /// 包含镜像的普通 WIM
+    Standard,
+    /// 只包含文件资源的数据 WIM（`RESOURCE_ONLY`），由其他 WIM 的元数据引用
+    ResourceOnly,
+    /// 只包含元数据的 WIM（`METADATA_ONLY`），文件资源位于其他 WIM
+    MetadataOnly,
+    /// 没有镜像也没有上述标志（例如服务操作使用的纯数据 WIM）
+    Empty,
+}
+
+impl WimKind {
+    /// 显示名称
+    pub fn name(&self) -> &'static str {
+        match self {
+            WimKind::Standard => "standard",
+            WimKind::ResourceOnly => "resource-only",
+            WimKind::MetadataOnly => "metadata-only",
+            WimKind::Empty => "empty",
+        }
+    }
+
+    /// 是否可能包含镜像；为 `false` 时缺少 XML 数据资源不视为错误，镜像列表为空
+    pub fn has_images(&self) -> bool {
+        matches!(self, WimKind::Standard | WimKind::MetadataOnly)
+    }
+}
+
+impl fmt::Display for WimKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl WimHeader {
+    /// 类型化的文件头标志
+    pub fn flags(&self) -> HeaderFlags {
+        HeaderFlags(self.file_flags)
+    }
+
+    /// 文件种类：`RESOURCE_ONLY` 和 `METADATA_ONLY` 标志优先，其次按镜像数量区分
+    pub fn kind(&self) -> WimKind {
+        let flags = self.flags();
+        if flags.contains(FileFlags::RESOURCE_ONLY) {
+            WimKind::ResourceOnly
+        } else if flags.contains(FileFlags::METADATA_ONLY) {
+            WimKind::MetadataOnly
+        } else if self.image_count == 0 {
+            WimKind::Empty
+        } else {
+            WimKind::Standard
+        }
+    }
+
+    /// 是否为多分卷 (`.swm`) 文件之一：`total_segments` 大于 1
+    pub fn is_split(&self) -> bool {
+        self.total_segments > 1
+    }
+
+    /// 文件头的 `SPANNED` 标志与分卷总数不一致（设置了标志但只有一个分卷，或有多个分卷但未设置标志）
+    ///
+    /// 第三方工具生成的文件中存在这种情况；读取时以 `total_segments` 为准。
+    pub fn spanned_flag_mismatch(&self) -> bool {
+        self.flags().contains(FileFlags::SPANNED) != self.is_split()
+    }
+}
+
+/// 文件头字段的位置和原始字节（用于带注释的十六进制转储）
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HeaderField {
+    /// 字段名称（资源头子字段形如 `xml_data_resource.offset`）
+    pub name: &'static str,
+    /// 相对文件头起始的偏移
+    pub offset: usize,
+    /// 字段长度（字节）
+    pub length: usize,
+    /// 字段的原始字节（little-endian）
+    pub raw: Vec<u8>,
+}
+
+impl HeaderField {
+    /// 按磁盘上的原始文件头字节划分字段，已定义字段之后的字节（通常为 60 字节）作为 `reserved` 字段
+    ///
+    /// 不足 [`HEADER_FIELDS_SIZE`] 字节时只返回完整包含在其中的字段。
+    pub fn layout(raw: &[u8]) -> Vec<HeaderField> {
+        let mut fields: Vec<HeaderField> = FIELD_LAYOUT
+            .iter()
+            .filter(|&&(_, offset, length)| offset + length <= raw.len())
+            .map(|&(name, offset, length)| HeaderField {
+                name,
+                offset,
+                length,
+                raw: raw[offset..offset + length].to_vec(),
+            })
+            .collect();
+        if raw.len() > HEADER_FIELDS_SIZE {
+            fields.push(HeaderField {
+                name: "reserved",
+                offset: HEADER_FIELDS_SIZE,
+                length: raw.len() - HEADER_FIELDS_SIZE,
+                raw: raw[HEADER_FIELDS_SIZE..].to_vec(),
+            });
+        }
+        fields
+    }
+}
+
+/// 将资源头写入 24 字节缓冲区
+fn write_resource_entry(buffer: &mut [u8], entry: &FileResourceEntry) {
+    buffer.copy_from_slice(&format::resource_entry_bytes(entry));
+}
+
+impl WimHeader {
+    /// 将已定义字段序列化为磁盘格式（不含保留区域）
+    pub fn to_bytes(&self) -> [u8; HEADER_FIELDS_SIZE] {
+        let mut buffer = [0u8; HEADER_FIELDS_SIZE];
+        buffer[0..8].copy_from_slice(&self.signature);
+        buffer[8..12].copy_from_slice(&self.header_size.to_le_bytes());
+        buffer[12..16].copy_from_slice(&self.format_version.to_le_bytes());
+        buffer[16..20].copy_from_slice(&self.file_flags.to_le_bytes());
+        buffer[20..24].copy_from_slice(&self.compressed_size.to_le_bytes());
+        buffer[24..40].copy_from_slice(&self.guid);
+        buffer[40..42].copy_from_slice(&self.segment_number.to_le_bytes());
+        buffer[42..44].copy_from_slice(&self.total_segments.to_le_bytes());
+        buffer[44..48].copy_from_slice(&self.image_count.to_le_bytes());
+        write_resource_entry(&mut buffer[48..72], &self.offset_table_resource);
+        write_resource_entry(&mut buffer[72..96], &self.xml_data_resource);
+        write_resource_entry(&mut buffer[96..120], &self.boot_metadata_resource);
+        buffer[120..124].copy_from_slice(&self.bootable_image_index.to_le_bytes());
+        write_resource_entry(&mut buffer[124..148], &self.integrity_resource);
+        buffer
+    }
+
+    /// 按已解析的取值重新序列化已定义字段，返回每个字段的名称、偏移、长度和字节（不含保留区域）
+    ///
+    /// 用于比较两个文件头；转储磁盘上的实际字节（含保留区域）见
+    /// [`WimParser::header_layout`](crate::WimParser::header_layout)。
+    pub fn field_layout(&self) -> Vec<HeaderField> {
+        HeaderField::layout(&self.to_bytes())
+    }
+}
+
+/// 两个文件头之间取值不同的字段
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HeaderFieldChange {
+    /// 字段名称
+    pub name: &'static str,
+    /// 相对文件头起始的偏移
+    pub offset: usize,
+    /// 原值的字节
+    pub before: Vec<u8>,
+    /// 新值的字节
+    pub after: Vec<u8>,
+}
+
+impl fmt::Display for HeaderFieldChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} (0x{:02X}):", self.name, self.offset)?;
+        for byte in &self.before {
+            write!(f, " {byte:02X}")?;
+        }
+        f.write_str(" ->")?;
+        for byte in &self.after {
+            write!(f, " {byte:02X}")?;
+        }
+        Ok(())
+    }
+}
+
+impl WimHeader {
+    /// 逐字段比较文件头，返回 `other` 中取值不同的字段（按偏移排序）
+    pub fn diff(&self, other: &WimHeader) -> Vec<HeaderFieldChange> {
+        self.field_layout()
+            .into_iter()
+            .zip(other.field_layout())
+            .filter(|(before, after)| before.raw != after.raw)
+            .map(|(before, after)| HeaderFieldChange {
+                name: before.name,
+                offset: before.offset,
+                before: before.raw,
+                after: after.raw,
+            })
+            .collect()
+    }
+}
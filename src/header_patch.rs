@@ -0,0 +1,169 @@
+//! 文件头原地写回：写入前重新读取磁盘上的文件头并与预期的先前状态逐字段比较，
+//! 发现其他进程（例如正在进行的 DISM 操作）修改过文件时拒绝写入
+
+use anyhow::{Context, Result};
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use crate::format::{self, WIM_HEADER_MIN_SIZE};
+use crate::lock;
+use crate::log::{debug, info};
+use crate::{Error, FileFlags, HeaderFieldChange, HeaderFlags, WimHeader, WimParser};
+
+/// 从 WIM 数据起始偏移处读取并解析文件头（不经过解析器的缓冲区）
+pub(crate) fn read_disk_header(file: &mut File, base_offset: u64) -> Result<WimHeader> {
+    let mut buffer = [0u8; WIM_HEADER_MIN_SIZE];
+    file.seek(SeekFrom::Start(base_offset))?;
+    file.read_exact(&mut buffer)
+        .context("重新读取 WIM 文件头失败")?;
+    Ok(format::parse_header(&buffer)?)
+}
+
+impl WimParser {
+    /// 确认磁盘上的文件头与解析器读取时相同
+    ///
+    /// 首次调用前未读取文件头时先读取，此时总是一致。不一致时返回
+    /// [`Error::HeaderChanged`]，错误信息列出所有变化的字段。
+    pub fn check_header_unchanged(&mut self) -> Result<()> {
+        let path = self.header_patch_path()?;
+        let expected = self.read_header()?.clone();
+        let mut file =
+            File::open(&path).with_context(|| format!("无法打开 WIM 文件: {}", path.display()))?;
+        ensure_unchanged(
+            &expected,
+            &read_disk_header(&mut file, self.base_offset())?,
+            &path,
+        )
+    }
+
+    /// 原地修改文件头：写入前确认磁盘上的文件头未被其他进程修改，返回实际写入的字段变化
+    ///
+    /// `edit` 在先前读取的文件头副本上修改；没有字段变化时不写入文件。
+    /// 文件头不在完整性表覆盖范围内，写回后完整性表仍然有效。
+    pub fn patch_header(
+        &mut self,
+        edit: impl FnOnce(&mut WimHeader),
+    ) -> Result<Vec<HeaderFieldChange>> {
+        let path = self.header_patch_path()?;
+        let expected = self.read_header()?.clone();
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(&path)
+            .with_context(|| format!("无法以写入方式打开 WIM 文件: {}", path.display()))?;
+        lock::lock_exclusive(&file, &path, self.options().file_lock_policy())?;
+        ensure_unchanged(
+            &expected,
+            &read_disk_header(&mut file, self.base_offset())?,
+            &path,
+        )?;
+
+        let mut header = expected.clone();
+        edit(&mut header);
+        let changes = expected.diff(&header);
+        if changes.is_empty() {
+            debug!("文件头没有变化，跳过写回: {}", path.display());
+            return Ok(changes);
+        }
+
+        file.seek(SeekFrom::Start(self.base_offset()))?;
+        file.write_all(&header.to_bytes())
+            .context("写回 WIM 文件头失败")?;
+        file.sync_data().context("同步 WIM 文件头失败")?;
+        info!(
+            "已写回 WIM 文件头: {} ({} 个字段)",
+            path.display(),
+            changes.len()
+        );
+        self.header = Some(header);
+        Ok(changes)
+    }
+
+    /// 设置和清除文件头标志（wimtweak 式的标志编辑），返回修改前后的标志
+    ///
+    /// 只允许修改 [`HeaderFlags::EDITABLE`] 中的状态标志；压缩格式、分卷等描述文件布局的标志
+    /// 返回 [`Error::Unsupported`]。写回通过 [`patch_header`](Self::patch_header) 进行。
+    /// 清除 `WRITE_IN_PROGRESS` 表示确认上次写入已完成，此时按当前内容重新计算完整性表
+    /// （需要 `verify` 特性，见 [`refresh_integrity_table`](Self::refresh_integrity_table)）。
+    pub fn edit_header_flags(&mut self, set: u32, clear: u32) -> Result<FlagsEdit> {
+        if set & clear != 0 {
+            return Err(anyhow::anyhow!(
+                "不能同时设置和清除同一标志: {}",
+                HeaderFlags(set & clear)
+            ));
+        }
+        let fixed = (set | clear) & !HeaderFlags::EDITABLE;
+        if fixed != 0 {
+            return Err(
+                anyhow::Error::new(Error::Unsupported("修改文件布局标志")).context(format!(
+                    "只能修改 {} 标志，{} 描述文件布局，不能原地修改",
+                    HeaderFlags(HeaderFlags::EDITABLE),
+                    HeaderFlags(fixed)
+                )),
+            );
+        }
+
+        let before = self.read_header()?.flags();
+        let changes = self.patch_header(|header| {
+            header.file_flags = (header.file_flags | set) & !clear;
+        })?;
+        let after = self.read_header()?.flags();
+
+        let finished = before.contains(FileFlags::WRITE_IN_PROGRESS)
+            && !after.contains(FileFlags::WRITE_IN_PROGRESS);
+        #[cfg(feature = "verify")]
+        let integrity_refreshed = finished && self.refresh_integrity_table()?;
+        #[cfg(not(feature = "verify"))]
+        let integrity_refreshed = {
+            let _ = finished;
+            false
+        };
+
+        Ok(FlagsEdit {
+            before,
+            after,
+            changed: !changes.is_empty(),
+            integrity_refreshed,
+        })
+    }
+
+    pub(crate) fn header_patch_path(&self) -> Result<PathBuf> {
+        self.path
+            .clone()
+            .ok_or_else(|| anyhow::anyhow!("解析器没有关联的文件路径，无法写回文件头"))
+    }
+}
+
+/// 文件头标志编辑的结果（由 [`WimParser::edit_header_flags`] 返回）
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FlagsEdit {
+    /// 修改前的标志
+    pub before: HeaderFlags,
+    /// 修改后的标志
+    pub after: HeaderFlags,
+    /// 文件头是否被写回（标志已是目标状态时不写入）
+    pub changed: bool,
+    /// 是否重新计算并写回了完整性表
+    pub integrity_refreshed: bool,
+}
+
+/// 比较预期与磁盘上的文件头，不一致时返回 [`Error::HeaderChanged`]
+pub(crate) fn ensure_unchanged(
+    expected: &WimHeader,
+    actual: &WimHeader,
+    path: &Path,
+) -> Result<()> {
+    let changes = expected.diff(actual);
+    let Some(first) = changes.first() else {
+        return Ok(());
+    };
+    let fields: Vec<String> = changes.iter().map(|change| change.to_string()).collect();
+    Err(
+        anyhow::Error::new(Error::HeaderChanged { field: first.name }).context(format!(
+            "{} 的文件头已被其他进程修改，拒绝写入: {}",
+            path.display(),
+            fields.join("; ")
+        )),
+    )
+}
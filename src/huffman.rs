@@ -0,0 +1,72 @@
+//! 规范哈夫曼码的解码表：码字按（码长, 符号）顺序分配，从比特流的高位开始读取
+
+/// 单级查找表：以接下来的 `max_len` 位（实际最长码字的长度）为下标，项为 `(符号 << 8) | 码长`，0 表示无效码字
+pub(crate) struct HuffmanTable {
+    entries: Vec<u32>,
+    max_len: u32,
+}
+
+impl HuffmanTable {
+    /// 由各符号的码长构建解码表（码长为 0 的符号不出现）
+    ///
+    /// 码长超过 `max_len` 或码字超额分配（Kraft 和大于 1）时返回 `None`；
+    /// 不完整的码是允许的，读到未分配的码字时 [`decode`](Self::decode) 返回 `None`。
+    /// 表的大小按实际出现的最长码字确定，码字较短的树不必分配 `2^max_len` 项。
+    pub(crate) fn new(lengths: &[u8], max_len: u32) -> Option<Self> {
+        let mut counts = vec![0u32; max_len as usize + 1];
+        for &len in lengths {
+            if u32::from(len) > max_len {
+                return None;
+            }
+            counts[len as usize] += 1;
+        }
+        counts[0] = 0;
+        let max_len = (1..=max_len)
+            .rev()
+            .find(|&len| counts[len as usize] > 0)
+            .unwrap_or(1);
+        counts.truncate(max_len as usize + 1);
+
+        let mut used = 0u64;
+        for (len, &count) in counts.iter().enumerate().skip(1) {
+            used += u64::from(count) << (max_len as usize - len);
+        }
+        if used > 1u64 << max_len {
+            return None;
+        }
+
+        let mut next_code = vec![0u32; max_len as usize + 1];
+        let mut code = 0u32;
+        for len in 1..=max_len as usize {
+            code = (code + counts[len - 1]) << 1;
+            next_code[len] = code;
+        }
+
+        let mut entries = vec![0u32; 1 << max_len];
+        for (symbol, &len) in lengths.iter().enumerate() {
+            if len == 0 {
+                continue;
+            }
+            let len = u32::from(len);
+            let code = next_code[len as usize];
+            next_code[len as usize] += 1;
+            let start = (code << (max_len - len)) as usize;
+            let end = ((code + 1) << (max_len - len)) as usize;
+            entries[start..end].fill(((symbol as u32) << 8) | len);
+        }
+        Some(Self { entries, max_len })
+    }
+
+    /// 最长码字的长度（[`decode`](Self::decode) 需要的位数）
+    pub(crate) fn max_len(&self) -> u32 {
+        self.max_len
+    }
+
+    /// 按接下来的 `max_len` 位（位于低位，先读的位在高位）解码，返回符号和实际码长
+    pub(crate) fn decode(&self, bits: u32) -> Option<(u16, u32)> {
+        match self.entries[bits as usize] {
+            0 => None,
+            entry => Some(((entry >> 8) as u16, entry & 0xFF)),
+        }
+    }
+}
@@ -0,0 +1,298 @@
+//! 镜像元数据资源的公开目录树：安全数据块和完整的目录项 (DIRENTRY) 树
+
+use anyhow::{Context, Result};
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+use crate::log::debug;
+use crate::metadata::{self, DirEntry, StreamEntry};
+use crate::names::{self, NameMatching};
+use crate::{SecurityDescriptor, WimParser, WimTimestamp};
+
+/// 目录项的命名数据流（未命名数据流记录在 [`WimDirEntry::hash`] 中）
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WimStream {
+    /// 数据流名称（空字符串表示以数据流条目记录的未命名数据流）
+    pub name: String,
+    /// 数据流内容的 SHA-1（全零表示空数据流）
+    pub hash: [u8; 20],
+}
+
+impl From<StreamEntry> for WimStream {
+    fn from(stream: StreamEntry) -> Self {
+        Self {
+            name: stream.name,
+            hash: stream.hash,
+        }
+    }
+}
+
+/// 镜像目录树中的一个目录项
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WimDirEntry {
+    /// 长文件名（根目录为空；不成对的代理项转义为 `%uXXXX`，见 [`decode_name`](crate::decode_name)）
+    pub name: String,
+    /// 短文件名 (8.3)，没有时为空
+    pub short_name: String,
+    /// 文件属性 (FILE_ATTRIBUTE_*)
+    pub attributes: u32,
+    /// 安全描述符索引（对应 [`ImageMetadata::security_descriptors`]，`None` 表示无）
+    pub security_id: Option<u32>,
+    /// 创建时间
+    pub creation_time: WimTimestamp,
+    /// 最后访问时间
+    pub last_access_time: WimTimestamp,
+    /// 最后写入时间
+    pub last_write_time: WimTimestamp,
+    /// 未命名数据流的 SHA-1（全零表示空文件或目录）
+    pub hash: [u8; 20],
+    /// 重解析点标记（仅对重解析点有效）
+    pub reparse_tag: u32,
+    /// 硬链接组 ID（仅对非重解析点有效，0 表示不属于任何组）
+    pub hard_link_group_id: u64,
+    /// 附加数据流
+    pub streams: Vec<WimStream>,
+    /// 子目录项
+    pub children: Vec<WimDirEntry>,
+    /// 原始 UTF-16 名称（仅当其不是有效的 UTF-16 时保留）
+    name_utf16: Option<Vec<u16>>,
+}
+
+impl From<DirEntry> for WimDirEntry {
+    fn from(entry: DirEntry) -> Self {
+        let is_reparse_point = entry.is_reparse_point();
+        Self {
+            name: entry.name,
+            short_name: entry.short_name,
+            attributes: entry.attributes,
+            security_id: u32::try_from(entry.security_id).ok(),
+            creation_time: entry.creation_time,
+            last_access_time: entry.last_access_time,
+            last_write_time: entry.last_write_time,
+            hash: entry.hash,
+            reparse_tag: if is_reparse_point {
+                entry.reparse_tag
+            } else {
+                0
+            },
+            hard_link_group_id: if is_reparse_point {
+                0
+            } else {
+                entry.hard_link_group_id
+            },
+            streams: entry.streams.into_iter().map(WimStream::from).collect(),
+            children: entry.children.into_iter().map(WimDirEntry::from).collect(),
+            name_utf16: entry.name_utf16,
+        }
+    }
+}
+
+impl WimDirEntry {
+    /// 是否为目录
+    pub fn is_directory(&self) -> bool {
+        self.attributes & metadata::FILE_ATTRIBUTE_DIRECTORY != 0
+    }
+
+    /// 是否为重解析点（符号链接、目录联接等）
+    pub fn is_reparse_point(&self) -> bool {
+        self.attributes & metadata::FILE_ATTRIBUTE_REPARSE_POINT != 0
+    }
+
+    /// 原始 UTF-16 名称
+    pub fn raw_name(&self) -> Cow<'_, [u16]> {
+        names::raw_units(&self.name, self.name_utf16.as_deref())
+    }
+
+    /// 按路径查找子孙目录项（`\\` 或 `/` 分隔，按默认的 [`NameMatching`] 比较，空路径返回自身）
+    pub fn find(&self, path: &str) -> Option<&WimDirEntry> {
+        let matching = NameMatching::default();
+        path.split(['\\', '/'])
+            .filter(|part| !part.is_empty())
+            .try_fold(self, |dir, part| {
+                matching.find(&dir.children, part, |child| &child.name)
+            })
+    }
+
+    /// 遍历所有子孙目录项（先序，不含自身），同时提供以 `/` 分隔的相对路径
+    pub fn walk<'a, F: FnMut(&str, &'a WimDirEntry)>(&'a self, f: &mut F) {
+        let mut path = String::new();
+        self.walk_children(&mut path, f);
+    }
+
+    fn walk_children<'a, F: FnMut(&str, &'a WimDirEntry)>(&'a self, path: &mut String, f: &mut F) {
+        for child in &self.children {
+            let parent_len = path.len();
+            if !path.is_empty() {
+                path.push('/');
+            }
+            path.push_str(&child.name);
+            f(path, child);
+            child.walk_children(path, f);
+            path.truncate(parent_len);
+        }
+    }
+}
+
+/// 同一硬链接组中的文件（由 [`ImageMetadata::hard_link_groups`] 创建）
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HardLinkGroup {
+    /// 硬链接组 ID
+    pub id: u64,
+    /// 共享的未命名数据流的 SHA-1
+    pub hash: [u8; 20],
+    /// 组内文件以 `/` 分隔的路径（先序遍历顺序，至少两个）
+    pub paths: Vec<String>,
+}
+
+/// 解析后的镜像元数据资源（由 [`WimParser::read_image_metadata`] 创建）
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImageMetadata {
+    /// 镜像索引
+    pub index: u32,
+    /// 安全数据块中的安全描述符（自相关格式），按 [`WimDirEntry::security_id`] 索引
+    pub security_descriptors: Vec<Vec<u8>>,
+    /// 根目录项
+    pub root: WimDirEntry,
+}
+
+impl ImageMetadata {
+    /// 目录项的安全描述符
+    pub fn security_descriptor(&self, entry: &WimDirEntry) -> Option<&[u8]> {
+        let id = usize::try_from(entry.security_id?).ok()?;
+        self.security_descriptors.get(id).map(Vec::as_slice)
+    }
+
+    /// 解析目录项的安全描述符（所有者、组、DACL 和 SACL），没有安全描述符时返回 `None`
+    ///
+    /// [`SecurityDescriptor::to_sddl`] 可以转为 SDDL 字符串。
+    ///
+    /// ```
+    /// # #[cfg(feature = "fixtures")] {
+    /// # let fixture = wim_parser::fixtures::MiniWim::create()?;
+    /// # let mut parser = wim_parser::WimParser::new(fixture.path())?;
+    /// let metadata = parser.read_image_metadata(1)?;
+    /// let mut entries = Vec::new();
+    /// metadata.root.walk(&mut |path, entry| entries.push((path.to_string(), entry)));
+    /// for (path, entry) in entries {
+    ///     if let Some(descriptor) = metadata.parsed_security_descriptor(entry)? {
+    ///         println!("{path}: {descriptor}");
+    ///     }
+    /// }
+    /// # }
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    pub fn parsed_security_descriptor(
+        &self,
+        entry: &WimDirEntry,
+    ) -> Result<Option<SecurityDescriptor>> {
+        self.security_descriptor(entry)
+            .map(|data| {
+                SecurityDescriptor::parse(data).with_context(|| {
+                    format!(
+                        "解析镜像 {} 的安全描述符 {} 失败",
+                        self.index,
+                        entry.security_id.unwrap_or_default()
+                    )
+                })
+            })
+            .transpose()
+    }
+
+    /// 硬链接组：硬链接组 ID 和未命名数据流都相同的多个文件，按组 ID 排序
+    ///
+    /// 组 ID 相同但内容不同的目录项视为损坏的镜像中互不相关的文件，分别成组。
+    /// 释放时每组只写入一次内容，其余成员经 [`ApplyTarget::hard_link`](crate::ApplyTarget::hard_link) 创建。
+    ///
+    /// ```
+    /// # #[cfg(feature = "fixtures")] {
+    /// # let fixture = wim_parser::fixtures::MiniWim::create()?;
+    /// # let mut parser = wim_parser::WimParser::new(fixture.path())?;
+    /// let metadata = parser.read_image_metadata(1)?;
+    /// for group in metadata.hard_link_groups() {
+    ///     println!("硬链接组 {}: {}", group.id, group.paths.join(", "));
+    /// }
+    /// # }
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    pub fn hard_link_groups(&self) -> Vec<HardLinkGroup> {
+        let mut groups: Vec<HardLinkGroup> = Vec::new();
+        let mut positions: HashMap<(u64, [u8; 20]), usize> = HashMap::new();
+        self.root.walk(&mut |path, entry| {
+            if entry.hard_link_group_id == 0 || entry.is_directory() {
+                return;
+            }
+            let position = *positions
+                .entry((entry.hard_link_group_id, entry.hash))
+                .or_insert_with(|| {
+                    groups.push(HardLinkGroup {
+                        id: entry.hard_link_group_id,
+                        hash: entry.hash,
+                        paths: Vec::new(),
+                    });
+                    groups.len() - 1
+                });
+            groups[position].paths.push(path.to_string());
+        });
+        groups.retain(|group| group.paths.len() > 1);
+        groups.sort_by_key(|group| group.id);
+        groups
+    }
+
+    /// 目录数和文件数（不含根目录）
+    pub fn counts(&self) -> (usize, usize) {
+        let (mut dirs, mut files) = (0, 0);
+        self.root.walk(&mut |_, entry| {
+            if entry.is_directory() {
+                dirs += 1;
+            } else {
+                files += 1;
+            }
+        });
+        (dirs, files)
+    }
+}
+
+impl WimParser {
+    /// 读取并解析镜像的元数据资源：安全数据块和完整的目录树
+    ///
+    /// 元数据资源按偏移表中带 `METADATA` 标志的条目顺序对应镜像索引（从 1 开始）。
+    /// 整棵目录树一次性解析到内存；只需访问少数目录时可使用 [`open_lazy_tree`](Self::open_lazy_tree)。
+    ///
+    /// ```
+    /// # #[cfg(feature = "fixtures")] {
+    /// # let fixture = wim_parser::fixtures::MiniWim::create()?;
+    /// # let mut parser = wim_parser::WimParser::new(fixture.path())?;
+    /// let metadata = parser.read_image_metadata(2)?;
+    /// let gpedit = metadata.root.find("Windows/System32/gpedit.msc").unwrap();
+    /// assert!(!gpedit.is_directory());
+    /// let (dirs, files) = metadata.counts();
+    /// println!("{dirs} 个目录, {files} 个文件");
+    /// # }
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    pub fn read_image_metadata(&mut self, index: u32) -> Result<ImageMetadata> {
+        let data = self.read_metadata_bytes(index)?;
+        let security_descriptors = metadata::parse_security_descriptors(&data)
+            .with_context(|| format!("解析镜像 {index} 的安全数据块失败"))?
+            .into_iter()
+            .map(<[u8]>::to_vec)
+            .collect::<Vec<_>>();
+        let root = metadata::parse_metadata_resource(&data, &self.options().resource_limits())
+            .with_context(|| format!("解析镜像 {index} 的元数据资源失败"))?;
+
+        let metadata = ImageMetadata {
+            index,
+            security_descriptors,
+            root: WimDirEntry::from(root),
+        };
+        let (dirs, files) = metadata.counts();
+        debug!(
+            "镜像 {} 元数据: {} 个安全描述符, {} 个目录, {} 个文件",
+            index,
+            metadata.security_descriptors.len(),
+            dirs,
+            files
+        );
+        Ok(metadata)
+    }
+}
@@ -0,0 +1,198 @@
+use anyhow::{Context, Result};
+use sha1::{Digest as _, Sha1};
+use std::fs::OpenOptions;
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::ops::Range;
+
+use crate::format::WIM_HEADER_DISK_SIZE;
+use crate::header_patch::{ensure_unchanged, read_disk_header};
+use crate::lock;
+use crate::log::{debug, info};
+use crate::{Error, WimHeader, WimParser};
+
+/// 完整性表头大小：表大小 (4 字节) + 条目数 (4 字节) + 分块大小 (4 字节)
+const INTEGRITY_TABLE_HEADER_SIZE: usize = 12;
+/// 重新计算完整性表时每次读取的字节数
+const REFRESH_BUFFER_SIZE: usize = 1 << 20;
+
+/// 完整性表：文件头之后到偏移表结尾区域按固定分块计算的 SHA-1
+#[derive(Debug, Clone)]
+pub(crate) struct IntegrityTable {
+    /// 分块大小（通常为 10 MiB）
+    pub chunk_size: u32,
+    /// 每个分块的 SHA-1
+    pub hashes: Vec<[u8; 20]>,
+}
+
+impl IntegrityTable {
+    /// 第 `index` 个分块在文件中的字节范围（最后一个分块可能较短）
+    pub fn chunk_range(&self, region: &Range<u64>, index: usize) -> Range<u64> {
+        let start = region.start + index as u64 * u64::from(self.chunk_size);
+        start..(start + u64::from(self.chunk_size)).min(region.end)
+    }
+}
+
+/// 完整性表覆盖的文件区域：文件头之后到偏移表结尾
+pub(crate) fn integrity_region(header: &WimHeader) -> Range<u64> {
+    let end = header.offset_table_resource.byte_range().end;
+    WIM_HEADER_DISK_SIZE as u64..end.max(WIM_HEADER_DISK_SIZE as u64)
+}
+
+/// 解析完整性表资源
+pub(crate) fn parse_integrity_table(data: &[u8]) -> Result<IntegrityTable> {
+    if data.len() < INTEGRITY_TABLE_HEADER_SIZE {
+        return Err(anyhow::anyhow!("完整性表太短: {} 字节", data.len()));
+    }
+
+    let num_entries = u32::from_le_bytes(data[4..8].try_into().unwrap()) as usize;
+    let chunk_size = u32::from_le_bytes(data[8..12].try_into().unwrap());
+    if chunk_size == 0 {
+        return Err(anyhow::anyhow!("完整性表的分块大小为 0"));
+    }
+
+    let hashes_end = num_entries
+        .checked_mul(20)
+        .and_then(|len| len.checked_add(INTEGRITY_TABLE_HEADER_SIZE))
+        .filter(|&end| end <= data.len())
+        .ok_or_else(|| anyhow::anyhow!("完整性表被截断: 声明 {} 个条目", num_entries))?;
+
+    let hashes = data[INTEGRITY_TABLE_HEADER_SIZE..hashes_end]
+        .chunks_exact(20)
+        .map(|hash| hash.try_into().unwrap())
+        .collect();
+
+    Ok(IntegrityTable { chunk_size, hashes })
+}
+
+impl WimParser {
+    /// 读取完整性表（文件中没有完整性表时返回 `None`）
+    pub(crate) fn read_integrity_table(&mut self) -> Result<Option<IntegrityTable>> {
+        let resource = self.read_header()?.integrity_resource.clone();
+        if resource.is_absent() {
+            return Ok(None);
+        }
+
+        let data = self.read_resource(&resource).context("读取完整性表失败")?;
+        parse_integrity_table(&data).map(Some)
+    }
+}
+
+impl WimParser {
+    /// 按当前文件内容重新计算完整性表并原地写回（分块大小不变），返回完整性表是否有变化
+    ///
+    /// 重新计算会让完整性表接受文件当前的内容（包括已损坏的数据），只应在确认内容正确时使用，
+    /// 例如写入中断后清除 `WRITE_IN_PROGRESS`。没有完整性表时不做任何事。
+    pub fn refresh_integrity_table(&mut self) -> Result<bool> {
+        let Some(table) = self.read_integrity_table()? else {
+            return Ok(false);
+        };
+        let header = self.read_header()?.clone();
+        let resource = header.integrity_resource.clone();
+        if resource.is_compressed() {
+            return Err(
+                anyhow::Error::new(Error::Unsupported("压缩的完整性表")).context(format!(
+                    "完整性表已压缩，无法原地更新 (偏移: {})",
+                    resource.offset
+                )),
+            );
+        }
+
+        let region = integrity_region(&header);
+        let mut builder = IntegrityBuilder::new(table.chunk_size);
+        let mut buffer = vec![0u8; REFRESH_BUFFER_SIZE];
+        self.seek_to(region.start)?;
+        let mut remaining = region.end - region.start;
+        while remaining > 0 {
+            let len = remaining.min(REFRESH_BUFFER_SIZE as u64) as usize;
+            self.file
+                .read_exact(&mut buffer[..len])
+                .context("读取完整性区域失败")?;
+            builder.update(&buffer[..len]);
+            remaining -= len as u64;
+        }
+        let refreshed = builder.finish();
+        let current = self.read_resource(&resource).context("读取完整性表失败")?;
+        if refreshed == current {
+            debug!("完整性表与当前内容一致，无需更新");
+            return Ok(false);
+        }
+        if refreshed.len() as u64 != resource.size {
+            return Err(anyhow::anyhow!(
+                "完整性表大小变化 ({} -> {} 字节)，无法原地更新",
+                resource.size,
+                refreshed.len()
+            ));
+        }
+
+        let path = self.header_patch_path()?;
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(&path)
+            .with_context(|| format!("无法以写入方式打开 WIM 文件: {}", path.display()))?;
+        lock::lock_exclusive(&file, &path, self.options().file_lock_policy())?;
+        ensure_unchanged(
+            &header,
+            &read_disk_header(&mut file, self.base_offset())?,
+            &path,
+        )?;
+        file.seek(SeekFrom::Start(self.base_offset() + resource.offset))?;
+        file.write_all(&refreshed).context("写回完整性表失败")?;
+        file.sync_data().context("同步完整性表失败")?;
+        info!(
+            "已重新计算完整性表: {} ({} 个分块)",
+            path.display(),
+            (refreshed.len() - INTEGRITY_TABLE_HEADER_SIZE) / 20
+        );
+        Ok(true)
+    }
+}
+
+/// 边写入边计算完整性表：按固定分块累计 SHA-1
+pub(crate) struct IntegrityBuilder {
+    chunk_size: u32,
+    hasher: Sha1,
+    filled: u32,
+    hashes: Vec<[u8; 20]>,
+}
+
+impl IntegrityBuilder {
+    pub fn new(chunk_size: u32) -> Self {
+        Self {
+            chunk_size: chunk_size.max(1),
+            hasher: Sha1::new(),
+            filled: 0,
+            hashes: Vec::new(),
+        }
+    }
+
+    /// 追加完整性区域中的下一段数据
+    pub fn update(&mut self, mut data: &[u8]) {
+        while !data.is_empty() {
+            let len = data.len().min((self.chunk_size - self.filled) as usize);
+            self.hasher.update(&data[..len]);
+            self.filled += len as u32;
+            data = &data[len..];
+            if self.filled == self.chunk_size {
+                self.hashes.push(self.hasher.finalize_reset().into());
+                self.filled = 0;
+            }
+        }
+    }
+
+    /// 编码完整性表资源：表大小、条目数、分块大小和各分块的 SHA-1
+    pub fn finish(mut self) -> Vec<u8> {
+        if self.filled > 0 {
+            self.hashes.push(self.hasher.finalize().into());
+        }
+        let size = INTEGRITY_TABLE_HEADER_SIZE + self.hashes.len() * 20;
+        let mut table = Vec::with_capacity(size);
+        table.extend_from_slice(&(size as u32).to_le_bytes());
+        table.extend_from_slice(&(self.hashes.len() as u32).to_le_bytes());
+        table.extend_from_slice(&self.chunk_size.to_le_bytes());
+        for hash in &self.hashes {
+            table.extend_from_slice(hash);
+        }
+        table
+    }
+}
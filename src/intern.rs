@@ -0,0 +1,208 @@
+//! 共享字符串：镜像 XML 中反复出现的取值（版本标识、语言、产品类型等）在所有镜像间共用一份内存
+//!
+//! 多镜像 ESD 中几十个镜像的 `<EDITIONID>`、`<LANGUAGE>` 等字段大多相同，为成千上万个文件建立目录时，
+//! 这些字段可以通过全局字符串池去重，只保存一份 `Arc<str>`。池的大小有上限，
+//! 已满时不再加入新字符串，每隔一定次数的未命中清理一次已不再被引用的字符串。关闭 `std` 特性时不去重。
+
+use alloc::string::String;
+use alloc::sync::Arc;
+use core::borrow::Borrow;
+use core::fmt;
+use core::ops::Deref;
+
+/// 字符串池中最多保留的字符串数量
+#[cfg(feature = "std")]
+const POOL_CAPACITY: usize = 4096;
+
+/// 字符串池已满时，每隔多少次未命中清理一次已不再被引用的字符串
+#[cfg(feature = "std")]
+const SWEEP_INTERVAL: usize = POOL_CAPACITY / 4;
+
+/// 不可变的共享字符串（`Arc<str>`），克隆只增加引用计数
+///
+/// 解引用为 `&str`，可以直接与 `&str`、`String` 比较。经由全局字符串池创建，
+/// 相同取值指向同一份内存（[`SharedStr::ptr_eq`]）；长期保存大量 [`ImageInfo`](crate::ImageInfo)
+/// 字段（例如为成千上万个文件建立的目录）时可以代替 `String`。
+///
+/// ```
+/// use wim_parser::SharedStr;
+///
+/// let languages = ["zh-CN", "en-US", "zh-CN"].map(SharedStr::from);
+/// # #[cfg(feature = "std")]
+/// assert!(SharedStr::ptr_eq(&languages[0], &languages[2]));
+/// assert_eq!(languages[1], "en-US");
+/// ```
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SharedStr(Arc<str>);
+
+impl SharedStr {
+    /// 从全局字符串池取得与 `value` 相同的共享字符串，没有时加入字符串池
+    pub fn interned(value: &str) -> Self {
+        #[cfg(feature = "std")]
+        {
+            Self(pool::intern(value))
+        }
+        #[cfg(not(feature = "std"))]
+        {
+            Self(Arc::from(value))
+        }
+    }
+
+    /// 字符串内容
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// 两个共享字符串是否指向同一份内存
+    pub fn ptr_eq(a: &Self, b: &Self) -> bool {
+        Arc::ptr_eq(&a.0, &b.0)
+    }
+
+    /// 全局字符串池中当前的字符串数量（关闭 `std` 特性时为 0）
+    pub fn pool_size() -> usize {
+        #[cfg(feature = "std")]
+        {
+            pool::size()
+        }
+        #[cfg(not(feature = "std"))]
+        {
+            0
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+mod pool {
+    use super::{POOL_CAPACITY, SWEEP_INTERVAL};
+    use alloc::sync::Arc;
+    use std::collections::HashSet;
+    use std::sync::{Mutex, MutexGuard, OnceLock};
+
+    #[derive(Default)]
+    struct Pool {
+        strings: HashSet<Arc<str>>,
+        /// 字符串池已满后未命中的次数，达到 [`SWEEP_INTERVAL`] 时清理一次
+        misses_since_sweep: usize,
+    }
+
+    fn lock() -> MutexGuard<'static, Pool> {
+        static POOL: OnceLock<Mutex<Pool>> = OnceLock::new();
+        POOL.get_or_init(Mutex::default)
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    pub(super) fn intern(value: &str) -> Arc<str> {
+        let mut pool = lock();
+        if let Some(existing) = pool.strings.get(value) {
+            return Arc::clone(existing);
+        }
+        let string: Arc<str> = Arc::from(value);
+        if pool.strings.len() >= POOL_CAPACITY {
+            pool.misses_since_sweep += 1;
+            if pool.misses_since_sweep < SWEEP_INTERVAL {
+                // 已满时不加入新字符串，清理的开销分摊到多次未命中上
+                return string;
+            }
+            pool.misses_since_sweep = 0;
+            // 只剩字符串池本身引用的字符串已无人使用
+            pool.strings.retain(|string| Arc::strong_count(string) > 1);
+            if pool.strings.len() >= POOL_CAPACITY {
+                return string;
+            }
+        }
+        pool.strings.insert(Arc::clone(&string));
+        string
+    }
+
+    pub(super) fn size() -> usize {
+        lock().strings.len()
+    }
+}
+
+impl Deref for SharedStr {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl AsRef<str> for SharedStr {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Borrow<str> for SharedStr {
+    fn borrow(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Debug for SharedStr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&*self.0, f)
+    }
+}
+
+impl fmt::Display for SharedStr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&*self.0, f)
+    }
+}
+
+impl From<&str> for SharedStr {
+    fn from(value: &str) -> Self {
+        Self::interned(value)
+    }
+}
+
+impl From<String> for SharedStr {
+    fn from(value: String) -> Self {
+        Self::interned(&value)
+    }
+}
+
+impl From<SharedStr> for String {
+    fn from(value: SharedStr) -> Self {
+        String::from(&*value.0)
+    }
+}
+
+impl PartialEq<str> for SharedStr {
+    fn eq(&self, other: &str) -> bool {
+        &*self.0 == other
+    }
+}
+
+impl PartialEq<&str> for SharedStr {
+    fn eq(&self, other: &&str) -> bool {
+        &*self.0 == *other
+    }
+}
+
+impl PartialEq<String> for SharedStr {
+    fn eq(&self, other: &String) -> bool {
+        *self.0 == **other
+    }
+}
+
+impl PartialEq<SharedStr> for str {
+    fn eq(&self, other: &SharedStr) -> bool {
+        self == &*other.0
+    }
+}
+
+impl PartialEq<SharedStr> for &str {
+    fn eq(&self, other: &SharedStr) -> bool {
+        *self == &*other.0
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl rusqlite::ToSql for SharedStr {
+    fn to_sql(&self) -> rusqlite::Result<rusqlite::types::ToSqlOutput<'_>> {
+        self.as_str().to_sql()
+    }
+}
@@ -0,0 +1,268 @@
+//! 介质库盘点：递归扫描目录中的 WIM/ESD/SWM 文件和 ISO 中的 WIM，汇总为一份报告
+//!
+//! ISO 不需要挂载：Windows 安装介质中的 `install.wim`、`boot.wim` 以未压缩的连续数据存放，
+//! 通过 [`carve`](crate::carve) 签名扫描定位后按偏移直接读取。
+
+use anyhow::{Context, Result};
+use std::fs::{self, File};
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+
+use crate::error::{error_code, json_string};
+use crate::fmt::{format_bytes, Align, Table, ToTable};
+use crate::log::{debug, info};
+use crate::{carve, ImageInfo, WimParser};
+
+/// 按扩展名识别的 WIM 文件（不区分大小写）
+const WIM_EXTENSIONS: [&str; 3] = ["wim", "esd", "swm"];
+/// 扫描其中嵌入的 WIM 的光盘镜像扩展名
+const ISO_EXTENSION: &str = "iso";
+
+/// 盘点中找到的一个 WIM
+#[derive(Debug, Clone)]
+pub struct InventoryEntry {
+    /// 文件路径
+    pub path: PathBuf,
+    /// WIM 在 ISO 中的偏移（独立的 WIM 文件为 `None`）
+    pub offset: Option<u64>,
+    /// 镜像列表（解析失败时为空）
+    pub images: Vec<ImageInfo>,
+    /// 解析失败时的错误码和错误信息
+    pub error: Option<(u32, String)>,
+}
+
+impl InventoryEntry {
+    fn parsed(path: &Path, offset: Option<u64>, result: Result<Vec<ImageInfo>>) -> Self {
+        let (images, error) = match result {
+            Ok(images) => (images, None),
+            Err(err) => {
+                debug!("解析 {} 失败: {:#}", path.display(), err);
+                (Vec::new(), Some((error_code(&err), format!("{err:#}"))))
+            }
+        };
+        Self {
+            path: path.to_path_buf(),
+            offset,
+            images,
+            error,
+        }
+    }
+}
+
+/// 介质库盘点报告（由 [`inventory`] 创建）
+#[derive(Debug, Clone, Default)]
+pub struct MediaInventory {
+    /// 扫描的根目录
+    pub root: PathBuf,
+    /// 检查的文件数（WIM/ESD/SWM 和 ISO）
+    pub files_scanned: usize,
+    /// 找到的 WIM，按路径和偏移排序
+    pub entries: Vec<InventoryEntry>,
+}
+
+impl MediaInventory {
+    /// 镜像总数
+    pub fn image_count(&self) -> usize {
+        self.entries.iter().map(|entry| entry.images.len()).sum()
+    }
+
+    /// 解析失败的条目
+    pub fn failures(&self) -> impl Iterator<Item = &InventoryEntry> {
+        self.entries.iter().filter(|entry| entry.error.is_some())
+    }
+
+    /// 单个 JSON 对象
+    pub fn to_json(&self) -> String {
+        let entries: Vec<String> = self.entries.iter().map(entry_json).collect();
+        format!(
+            "{{\"root\":{},\"files_scanned\":{},\"wim_count\":{},\"image_count\":{},\"failures\":{},\"wims\":[{}]}}",
+            json_string(&self.root.to_string_lossy()),
+            self.files_scanned,
+            self.entries.len(),
+            self.image_count(),
+            self.failures().count(),
+            entries.join(",")
+        )
+    }
+}
+
+fn json_opt_string(value: Option<&str>) -> String {
+    value.map_or_else(|| "null".to_string(), json_string)
+}
+
+fn json_opt_u32(value: Option<u32>) -> String {
+    value.map_or_else(|| "null".to_string(), |value| value.to_string())
+}
+
+fn image_json(image: &ImageInfo) -> String {
+    let languages: Vec<String> = image.languages.iter().map(|l| json_string(l)).collect();
+    format!(
+        "{{\"index\":{},\"name\":{},\"description\":{},\"version\":{},\"architecture\":{},\"edition_id\":{},\"build\":{},\"sp_build\":{},\"languages\":[{}],\"default_language\":{},\"dir_count\":{},\"file_count\":{},\"total_bytes\":{}}}",
+        image.index,
+        json_string(&image.name),
+        json_string(&image.description),
+        json_opt_string(image.version.as_deref()),
+        json_opt_string(image.architecture.as_deref()),
+        json_opt_string(image.edition_id.as_deref()),
+        json_opt_u32(image.build),
+        json_opt_u32(image.sp_build),
+        languages.join(","),
+        json_opt_string(image.default_language.as_deref()),
+        image.dir_count,
+        image.file_count,
+        image.total_bytes
+    )
+}
+
+fn entry_json(entry: &InventoryEntry) -> String {
+    let images: Vec<String> = entry.images.iter().map(image_json).collect();
+    let error = entry.error.as_ref().map_or_else(
+        || "null".to_string(),
+        |(code, message)| format!("{{\"code\":{},\"message\":{}}}", code, json_string(message)),
+    );
+    format!(
+        "{{\"path\":{},\"offset\":{},\"images\":[{}],\"error\":{}}}",
+        json_string(&entry.path.to_string_lossy()),
+        entry
+            .offset
+            .map_or_else(|| "null".to_string(), |offset| offset.to_string()),
+        images.join(","),
+        error
+    )
+}
+
+impl ToTable for MediaInventory {
+    fn table(&self) -> Table {
+        let mut table = Table::new(["文件", "索引", "名称", "版本", "架构", "总大小"])
+            .align(1, Align::Right)
+            .align(5, Align::Right);
+        for entry in &self.entries {
+            let file = match entry.offset {
+                Some(offset) => format!("{}@{}", entry.path.display(), offset),
+                None => entry.path.display().to_string(),
+            };
+            if let Some((_, message)) = &entry.error {
+                table.push_row([
+                    file,
+                    "-".into(),
+                    format!("错误: {message}"),
+                    "-".into(),
+                    "-".into(),
+                    "-".into(),
+                ]);
+                continue;
+            }
+            for image in &entry.images {
+                table.push_row([
+                    file.clone(),
+                    image.index.to_string(),
+                    image.name.clone(),
+                    image.version.clone().unwrap_or_else(|| "-".to_string()),
+                    image
+                        .architecture
+                        .clone()
+                        .unwrap_or_else(|| "-".to_string()),
+                    format_bytes(image.total_bytes),
+                ]);
+            }
+        }
+        table
+    }
+}
+
+fn has_extension(path: &Path, extensions: &[&str]) -> bool {
+    path.extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| {
+            extensions
+                .iter()
+                .any(|known| extension.eq_ignore_ascii_case(known))
+        })
+}
+
+/// 解析一个 WIM 的镜像列表
+fn list_images(mut parser: WimParser) -> Result<Vec<ImageInfo>> {
+    parser.parse_full()?;
+    Ok(parser.get_images().to_vec())
+}
+
+/// 扫描 ISO 中的 WIM；位于已找到的 WIM 范围内的签名（嵌套的 WIM）不重复报告
+fn scan_iso(path: &Path, entries: &mut Vec<InventoryEntry>) -> Result<()> {
+    let file = File::open(path).with_context(|| format!("无法打开文件: {}", path.display()))?;
+    let mut covered_until = 0;
+    for carved in carve(BufReader::new(file))? {
+        let carved = carved?;
+        if carved.offset < covered_until {
+            continue;
+        }
+        covered_until = carved.offset.saturating_add(carved.extent);
+        let result = carved.open(path).and_then(list_images);
+        entries.push(InventoryEntry::parsed(path, Some(carved.offset), result));
+    }
+    Ok(())
+}
+
+/// 递归收集目录中的文件（不跟随符号链接）
+fn collect_files(dir: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
+    for child in fs::read_dir(dir).with_context(|| format!("无法读取目录: {}", dir.display()))?
+    {
+        let child = child.with_context(|| format!("无法读取目录: {}", dir.display()))?;
+        let file_type = child.file_type()?;
+        if file_type.is_dir() {
+            collect_files(&child.path(), files)?;
+        } else if file_type.is_file() {
+            files.push(child.path());
+        }
+    }
+    Ok(())
+}
+
+/// 盘点 `root`：可以是单个 WIM/ESD/SWM/ISO 文件，也可以是递归扫描的目录
+///
+/// 单个文件解析失败不会中止盘点，错误记录在对应的 [`InventoryEntry::error`] 中；
+/// 目录无法读取时返回错误。
+pub fn inventory(root: impl AsRef<Path>) -> Result<MediaInventory> {
+    let root = root.as_ref();
+    let mut files = Vec::new();
+    if fs::metadata(root)
+        .with_context(|| format!("无法访问: {}", root.display()))?
+        .is_dir()
+    {
+        collect_files(root, &mut files)?;
+        files.retain(|path| {
+            has_extension(path, &WIM_EXTENSIONS) || has_extension(path, &[ISO_EXTENSION])
+        });
+    } else {
+        files.push(root.to_path_buf());
+    }
+    files.sort();
+
+    let mut report = MediaInventory {
+        root: root.to_path_buf(),
+        files_scanned: files.len(),
+        entries: Vec::new(),
+    };
+    for path in &files {
+        if has_extension(path, &[ISO_EXTENSION]) {
+            if let Err(err) = scan_iso(path, &mut report.entries) {
+                report
+                    .entries
+                    .push(InventoryEntry::parsed(path, None, Err(err)));
+            }
+        } else {
+            let result = WimParser::new(path).and_then(list_images);
+            report
+                .entries
+                .push(InventoryEntry::parsed(path, None, result));
+        }
+    }
+
+    info!(
+        "盘点 {}: {} 个文件, {} 个 WIM, {} 个镜像",
+        root.display(),
+        report.files_scanned,
+        report.entries.len(),
+        report.image_count()
+    );
+    Ok(report)
+}
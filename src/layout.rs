@@ -0,0 +1,184 @@
+use anyhow::Result;
+use std::collections::HashMap;
+
+use crate::fmt::{format_bytes, format_hash, Align, Table, ToTable};
+use crate::log::{debug, info};
+use crate::{FileResourceEntry, WimParser};
+
+/// 引用某个数据流的目录项
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StreamUse {
+    /// 镜像内的相对路径（`/` 分隔）
+    pub path: String,
+    /// 命名数据流的名称（未命名数据流为 `None`）
+    pub stream_name: Option<String>,
+    /// 数据流是重解析数据而不是文件内容
+    pub is_reparse_data: bool,
+}
+
+/// 按顺序写入计划中的一个数据流
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlannedStream {
+    /// 数据流 SHA-1（用于 [`WimParser::read_stream`]）
+    pub hash: [u8; 20],
+    /// 未压缩大小
+    pub size: u64,
+    /// 在 WIM 中占用的大小
+    pub stored_size: u64,
+    /// 所在分卷号
+    pub part_number: u16,
+    /// 在所在分卷中的偏移
+    pub offset: u64,
+    /// 是否为压缩资源
+    pub compressed: bool,
+    /// 引用该数据流的目录项（硬链接和重复内容共享同一数据流）
+    pub uses: Vec<StreamUse>,
+}
+
+/// 镜像的数据流布局计划
+///
+/// 数据流按所在分卷和偏移排列，依次读取即为顺序读；外部 NTFS 写入工具可按此顺序连续分配簇，
+/// 每个数据流只需读取一次，再写入所有引用它的文件。
+#[derive(Debug, Clone)]
+pub struct StreamLayout {
+    /// 镜像索引
+    pub index: u32,
+    /// 数据流（去重，按分卷号、偏移排列）
+    pub streams: Vec<PlannedStream>,
+    /// 内容为空的文件（没有数据流，只需创建）
+    pub empty_files: Vec<String>,
+    /// 数据流不在偏移表中的目录项
+    pub missing: Vec<StreamUse>,
+}
+
+impl StreamLayout {
+    /// 所有数据流的未压缩总大小（每个数据流只计一次）
+    pub fn total_bytes(&self) -> u64 {
+        self.streams.iter().map(|stream| stream.size).sum()
+    }
+
+    /// 写入所有引用后的总大小（硬链接和重复内容按引用次数计算）
+    pub fn total_written_bytes(&self) -> u64 {
+        self.streams
+            .iter()
+            .map(|stream| stream.size * stream.uses.len() as u64)
+            .sum()
+    }
+}
+
+impl ToTable for StreamLayout {
+    fn table(&self) -> Table {
+        let mut table = Table::new(["分卷", "偏移", "大小", "SHA-1", "路径"])
+            .align(0, Align::Right)
+            .align(1, Align::Right)
+            .align(2, Align::Right);
+        for stream in &self.streams {
+            let path = match stream.uses.as_slice() {
+                [] => "-".to_string(),
+                [only] => only.path.clone(),
+                [first, rest @ ..] => format!("{} (+{})", first.path, rest.len()),
+            };
+            table.push_row([
+                stream.part_number.to_string(),
+                stream.offset.to_string(),
+                format_bytes(stream.size),
+                format_hash(&stream.hash),
+                path,
+            ]);
+        }
+        table
+    }
+}
+
+impl WimParser {
+    /// 生成镜像的数据流布局计划：文件及其大小和 SHA-1，按 WIM 中的存放顺序排列
+    ///
+    /// 供直接写入原始分区的外部 NTFS 格式化工具使用：按计划顺序调用
+    /// [`read_stream`](Self::read_stream) 读取内容即可顺序地读取源文件、连续地写入目标。
+    pub fn plan_stream_layout(&mut self, index: u32) -> Result<StreamLayout> {
+        let root = self.read_metadata_root(index)?;
+
+        let resources: HashMap<[u8; 20], (u16, FileResourceEntry)> = self
+            .read_lookup_table()?
+            .iter()
+            .filter(|entry| !entry.is_metadata())
+            .map(|entry| (entry.hash, (entry.part_number, entry.resource.clone())))
+            .collect();
+
+        let mut layout = StreamLayout {
+            index,
+            streams: Vec::new(),
+            empty_files: Vec::new(),
+            missing: Vec::new(),
+        };
+        let mut positions: HashMap<[u8; 20], usize> = HashMap::new();
+
+        root.walk_with_path(&mut |path, entry| {
+            let unnamed = std::iter::once((None, &entry.hash));
+            let named = entry
+                .streams
+                .iter()
+                .filter(|stream| !stream.name.is_empty())
+                .map(|stream| (Some(stream.name.clone()), &stream.hash));
+            // 未命名数据流也可能以空名称的附加条目存储
+            let unnamed_extra = entry
+                .streams
+                .iter()
+                .filter(|stream| stream.name.is_empty() && stream.hash != [0u8; 20])
+                .map(|stream| (None, &stream.hash));
+
+            let mut has_content = false;
+            for (stream_name, hash) in unnamed.chain(unnamed_extra).chain(named) {
+                if *hash == [0u8; 20] {
+                    continue;
+                }
+                has_content |= stream_name.is_none();
+                let stream_use = StreamUse {
+                    path: path.to_string(),
+                    is_reparse_data: stream_name.is_none() && entry.is_reparse_point(),
+                    stream_name,
+                };
+                if let Some(&position) = positions.get(hash) {
+                    layout.streams[position].uses.push(stream_use);
+                    continue;
+                }
+                match resources.get(hash) {
+                    Some((part_number, resource)) => {
+                        positions.insert(*hash, layout.streams.len());
+                        layout.streams.push(PlannedStream {
+                            hash: *hash,
+                            size: resource.original_size,
+                            stored_size: resource.size,
+                            part_number: *part_number,
+                            offset: resource.offset,
+                            compressed: resource.is_compressed(),
+                            uses: vec![stream_use],
+                        });
+                    }
+                    None => layout.missing.push(stream_use),
+                }
+            }
+            if !has_content && !entry.is_directory() && !entry.is_reparse_point() {
+                layout.empty_files.push(path.to_string());
+            }
+        });
+
+        layout
+            .streams
+            .sort_by_key(|stream| (stream.part_number, stream.offset));
+
+        debug!(
+            "镜像 {} 数据流布局: {} 个空文件, {} 个缺失数据流",
+            index,
+            layout.empty_files.len(),
+            layout.missing.len()
+        );
+        info!(
+            "镜像 {} 数据流布局完成 - 数据流: {}, 总大小: {}",
+            index,
+            layout.streams.len(),
+            format_bytes(layout.total_bytes())
+        );
+        Ok(layout)
+    }
+}
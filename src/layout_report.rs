@@ -0,0 +1,570 @@
+//! 整个文件的字节范围注释：文件头、偏移表、XML、完整性表、元数据和每个数据流资源
+//!
+//! [`LayoutReport`] 可输出为 JSON，或生成 010 Editor 模板 (`.bt`) 和 Kaitai Struct 描述 (`.ksy`)，
+//! 在十六进制编辑器中直接按区域着色、跳转，用于调试格式问题或制作教学材料。
+
+use anyhow::Result;
+use std::collections::HashMap;
+use std::fmt::Write as _;
+
+use crate::error::json_string;
+use crate::fmt::{format_bytes, format_hash, Align, Table, ToTable};
+use crate::log::{debug, info};
+use crate::{HeaderField, ResourceKind, WimParser, HEADER_FIELDS_SIZE};
+
+/// 区域的种类
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RegionKind {
+    /// 文件头（含保留区域）
+    Header,
+    /// 偏移表（查找表）
+    OffsetTable,
+    /// XML 数据
+    XmlData,
+    /// 不在偏移表中的引导元数据资源
+    BootMetadata,
+    /// 完整性表
+    Integrity,
+    /// 镜像元数据资源（镜像索引）
+    Metadata(u32),
+    /// 数据流资源
+    Stream,
+    /// 固实资源（其中的数据流记录在 `owners` 中）
+    SolidResource,
+    /// 没有任何结构引用的字节
+    Unreferenced,
+}
+
+impl RegionKind {
+    /// 种类名称（用于 JSON 和模板中的标识符）
+    pub fn name(&self) -> &'static str {
+        match self {
+            RegionKind::Header => "header",
+            RegionKind::OffsetTable => "offset_table",
+            RegionKind::XmlData => "xml_data",
+            RegionKind::BootMetadata => "boot_metadata",
+            RegionKind::Integrity => "integrity",
+            RegionKind::Metadata(_) => "metadata",
+            RegionKind::Stream => "stream",
+            RegionKind::SolidResource => "solid_resource",
+            RegionKind::Unreferenced => "unreferenced",
+        }
+    }
+
+    /// 010 Editor 中的背景色（0xBBGGRR）
+    fn color(&self) -> u32 {
+        match self {
+            RegionKind::Header => 0xC0C0FF,
+            RegionKind::OffsetTable => 0xFFD0A0,
+            RegionKind::XmlData => 0xC0FFC0,
+            RegionKind::BootMetadata | RegionKind::Metadata(_) => 0xA0E0FF,
+            RegionKind::Integrity => 0xFFC0FF,
+            RegionKind::Stream => 0xF0F0F0,
+            RegionKind::SolidResource => 0xE0E0C0,
+            RegionKind::Unreferenced => 0x8080FF,
+        }
+    }
+}
+
+/// 引用区域中数据的目录项
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RegionOwner {
+    /// 镜像索引
+    pub image: u32,
+    /// 镜像内的相对路径（`/` 分隔，命名数据流为 `路径:名称`）
+    pub path: String,
+}
+
+/// 文件中的一段字节范围
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LayoutRegion {
+    /// 相对 WIM 数据起始处的偏移
+    pub offset: u64,
+    /// 长度（字节）
+    pub length: u64,
+    pub kind: RegionKind,
+    /// 说明
+    pub label: String,
+    /// 偏移表中记录的 SHA-1（文件头、XML 等没有）
+    pub hash: Option<[u8; 20]>,
+    /// 是否为压缩资源
+    pub compressed: bool,
+    /// 未压缩大小
+    pub original_size: u64,
+    /// 引用该区域的目录项（固实资源为其中所有数据流的引用）
+    pub owners: Vec<RegionOwner>,
+    /// 文件头字段（只有 [`RegionKind::Header`] 区域有）
+    pub fields: Vec<HeaderField>,
+}
+
+impl LayoutRegion {
+    fn new(offset: u64, length: u64, kind: RegionKind, label: impl Into<String>) -> Self {
+        Self {
+            offset,
+            length,
+            kind,
+            label: label.into(),
+            hash: None,
+            compressed: false,
+            original_size: length,
+            owners: Vec::new(),
+            fields: Vec::new(),
+        }
+    }
+
+    /// 结束偏移（不含）
+    pub fn end(&self) -> u64 {
+        self.offset.saturating_add(self.length)
+    }
+}
+
+/// 整个文件的字节范围注释，区域按偏移排列并覆盖 WIM 数据的每个字节
+#[derive(Debug, Clone)]
+pub struct LayoutReport {
+    /// WIM 数据在文件中的起始偏移（见 [`WimParser::base_offset`]）
+    pub base_offset: u64,
+    /// WIM 数据的长度
+    pub data_size: u64,
+    /// 当前分卷号
+    pub segment_number: u16,
+    pub regions: Vec<LayoutRegion>,
+    /// 位于其他分卷、不在本文件中的资源数量
+    pub external_resources: usize,
+}
+
+impl LayoutReport {
+    /// 没有任何结构引用的字节数
+    pub fn unreferenced_bytes(&self) -> u64 {
+        self.regions
+            .iter()
+            .filter(|region| region.kind == RegionKind::Unreferenced)
+            .map(|region| region.length)
+            .sum()
+    }
+
+    /// 包含指定偏移（相对 WIM 数据起始处）的区域
+    pub fn region_at(&self, offset: u64) -> Option<&LayoutRegion> {
+        self.regions
+            .iter()
+            .find(|region| region.offset <= offset && offset < region.end())
+    }
+
+    /// 单个 JSON 对象，偏移相对 WIM 数据起始处
+    pub fn to_json(&self) -> String {
+        let regions: Vec<String> = self.regions.iter().map(region_json).collect();
+        format!(
+            "{{\"base_offset\":{},\"data_size\":{},\"segment_number\":{},\"external_resources\":{},\"unreferenced_bytes\":{},\"regions\":[{}]}}",
+            self.base_offset,
+            self.data_size,
+            self.segment_number,
+            self.external_resources,
+            self.unreferenced_bytes(),
+            regions.join(",")
+        )
+    }
+
+    /// 010 Editor 二进制模板 (`.bt`)，偏移为文件中的绝对偏移
+    pub fn to_010_template(&self) -> String {
+        let mut out = String::from(
+            "// 010 Editor 模板：由 wim-parser 根据实际文件生成的区域注释\n\
+             LittleEndian();\n\n\
+             typedef struct {\n    ubyte size[7];\n    ubyte flags <format=hex>;\n    uint64 offset;\n    uint64 original_size;\n} RESHDR;\n\n\
+             typedef struct {\n    RESHDR resource;\n    uint16 part_number;\n    uint32 ref_count;\n    ubyte hash[20];\n} LOOKUP_ENTRY;\n\n",
+        );
+        let header_length = self
+            .regions
+            .iter()
+            .find(|region| region.kind == RegionKind::Header)
+            .map_or(0, |region| region.length);
+        let _ = write!(
+            out,
+            "typedef struct {{\n    char signature[8];\n    uint32 header_size;\n    uint32 format_version <format=hex>;\n    uint32 file_flags <format=hex>;\n    uint32 compressed_size;\n    ubyte guid[16];\n    uint16 segment_number;\n    uint16 total_segments;\n    uint32 image_count;\n    RESHDR offset_table_resource;\n    RESHDR xml_data_resource;\n    RESHDR boot_metadata_resource;\n    uint32 bootable_image_index;\n    RESHDR integrity_resource;\n    ubyte reserved[{}];\n}} WIM_HEADER;\n",
+            header_length.saturating_sub(HEADER_FIELDS_SIZE as u64)
+        );
+        for (position, region) in self.regions.iter().enumerate() {
+            let declaration = match region.kind {
+                RegionKind::Header => "WIM_HEADER".to_string(),
+                RegionKind::OffsetTable => "LOOKUP_ENTRY".to_string(),
+                _ => "ubyte".to_string(),
+            };
+            let count = match region.kind {
+                RegionKind::Header => String::new(),
+                RegionKind::OffsetTable => format!("[{}]", region.length / 50),
+                _ => format!("[{}]", region.length),
+            };
+            let _ = write!(
+                out,
+                "\nFSeek(0x{:X});\n{} {}{} <bgcolor=0x{:06X}, comment={}>;\n",
+                self.base_offset + region.offset,
+                declaration,
+                region_id(position, region),
+                count,
+                region.kind.color(),
+                json_string(&region.label)
+            );
+        }
+        out
+    }
+
+    /// Kaitai Struct 描述 (`.ksy`)，每个区域是一个带 `pos` 的实例，偏移为文件中的绝对偏移
+    pub fn to_kaitai(&self) -> String {
+        let mut out = String::from(
+            "# Kaitai Struct 描述：由 wim-parser 根据实际文件生成的区域注释\n\
+             meta:\n  id: wim_layout\n  endian: le\ninstances:\n",
+        );
+        for (position, region) in self.regions.iter().enumerate() {
+            let _ = writeln!(
+                out,
+                "  {}:\n    pos: 0x{:X}",
+                region_id(position, region),
+                self.base_offset + region.offset
+            );
+            match region.kind {
+                RegionKind::Header => {
+                    let _ = writeln!(out, "    size: {}\n    type: wim_header", region.length);
+                }
+                RegionKind::OffsetTable => {
+                    let _ = writeln!(
+                        out,
+                        "    type: lookup_entry\n    repeat: expr\n    repeat-expr: {}",
+                        region.length / 50
+                    );
+                }
+                _ => {
+                    let _ = writeln!(out, "    size: {}", region.length);
+                }
+            }
+            let _ = writeln!(out, "    doc: {}", json_string(&region.label));
+        }
+        out.push_str(
+            "types:\n\
+             \x20 reshdr:\n    seq:\n\
+             \x20     - id: size\n        size: 7\n        doc: 压缩后大小（7 字节）\n\
+             \x20     - id: flags\n        type: u1\n\
+             \x20     - id: offset\n        type: u8\n\
+             \x20     - id: original_size\n        type: u8\n\
+             \x20 lookup_entry:\n    seq:\n\
+             \x20     - id: resource\n        type: reshdr\n\
+             \x20     - id: part_number\n        type: u2\n\
+             \x20     - id: ref_count\n        type: u4\n\
+             \x20     - id: hash\n        size: 20\n\
+             \x20 wim_header:\n    seq:\n\
+             \x20     - id: signature\n        size: 8\n\
+             \x20     - id: header_size\n        type: u4\n\
+             \x20     - id: format_version\n        type: u4\n\
+             \x20     - id: file_flags\n        type: u4\n\
+             \x20     - id: compressed_size\n        type: u4\n\
+             \x20     - id: guid\n        size: 16\n\
+             \x20     - id: segment_number\n        type: u2\n\
+             \x20     - id: total_segments\n        type: u2\n\
+             \x20     - id: image_count\n        type: u4\n\
+             \x20     - id: offset_table_resource\n        type: reshdr\n\
+             \x20     - id: xml_data_resource\n        type: reshdr\n\
+             \x20     - id: boot_metadata_resource\n        type: reshdr\n\
+             \x20     - id: bootable_image_index\n        type: u4\n\
+             \x20     - id: integrity_resource\n        type: reshdr\n",
+        );
+        out
+    }
+}
+
+/// 区域在模板中的标识符（文件头等唯一的区域用种类名称，其余加序号）
+fn region_id(position: usize, region: &LayoutRegion) -> String {
+    match region.kind {
+        RegionKind::Header
+        | RegionKind::OffsetTable
+        | RegionKind::XmlData
+        | RegionKind::BootMetadata
+        | RegionKind::Integrity => region.kind.name().to_string(),
+        RegionKind::Metadata(index) => format!("metadata_{index}"),
+        kind => format!("{}_{}", kind.name(), position),
+    }
+}
+
+fn region_json(region: &LayoutRegion) -> String {
+    let owners: Vec<String> = region
+        .owners
+        .iter()
+        .map(|owner| {
+            format!(
+                "{{\"image\":{},\"path\":{}}}",
+                owner.image,
+                json_string(&owner.path)
+            )
+        })
+        .collect();
+    let fields: Vec<String> = region
+        .fields
+        .iter()
+        .map(|field| {
+            format!(
+                "{{\"name\":\"{}\",\"offset\":{},\"length\":{},\"raw\":\"{}\"}}",
+                field.name,
+                field.offset,
+                field.length,
+                format_hash(&field.raw)
+            )
+        })
+        .collect();
+    let image = match region.kind {
+        RegionKind::Metadata(index) => index.to_string(),
+        _ => "null".to_string(),
+    };
+    format!(
+        "{{\"offset\":{},\"length\":{},\"kind\":\"{}\",\"image\":{},\"label\":{},\"hash\":{},\"compressed\":{},\"original_size\":{},\"owners\":[{}],\"fields\":[{}]}}",
+        region.offset,
+        region.length,
+        region.kind.name(),
+        image,
+        json_string(&region.label),
+        region
+            .hash
+            .map_or_else(|| "null".to_string(), |hash| format!("\"{}\"", format_hash(&hash))),
+        region.compressed,
+        region.original_size,
+        owners.join(","),
+        fields.join(",")
+    )
+}
+
+impl ToTable for LayoutReport {
+    fn table(&self) -> Table {
+        let mut table = Table::new(["偏移", "长度", "种类", "说明"])
+            .align(0, Align::Right)
+            .align(1, Align::Right);
+        for region in &self.regions {
+            table.push_row([
+                format!("0x{:X}", region.offset),
+                format_bytes(region.length),
+                region.kind.name().to_string(),
+                region.label.clone(),
+            ]);
+        }
+        table
+    }
+}
+
+/// 数据流区域的说明：第一个引用者及其余引用者的数量
+fn stream_label(owners: &[RegionOwner]) -> String {
+    match owners {
+        [] => "未被任何镜像引用的数据流".to_string(),
+        [only] => format!("镜像 {}: {}", only.image, only.path),
+        [first, rest @ ..] => format!("镜像 {}: {} (+{})", first.image, first.path, rest.len()),
+    }
+}
+
+impl WimParser {
+    /// 生成整个文件的字节范围注释
+    ///
+    /// 区域包括文件头、偏移表、XML 数据、完整性表、每个镜像的元数据资源和每个数据流资源
+    /// （附带引用它的镜像和路径），区域之间没有任何结构引用的字节标记为
+    /// [`RegionKind::Unreferenced`]。固实资源中的数据流没有独立的字节范围，其引用者记录在
+    /// 所在的固实资源上。位于其他分卷的资源只计入 `external_resources`。
+    pub fn layout_report(&mut self) -> Result<LayoutReport> {
+        let header = self.read_header()?.clone();
+        let entries = self.read_lookup_table()?.to_vec();
+
+        let mut regions = Vec::new();
+        let mut header_region = LayoutRegion::new(
+            0,
+            u64::from(header.header_size).max(HEADER_FIELDS_SIZE as u64),
+            RegionKind::Header,
+            "文件头",
+        );
+        header_region.fields = self.header_layout()?;
+        regions.push(header_region);
+
+        // 数据流 SHA-1 -> 引用者
+        let mut owners: HashMap<[u8; 20], Vec<RegionOwner>> = HashMap::new();
+        let mut image = 0;
+        let mut external_resources = 0;
+        for entry in &entries {
+            let location = self.resolve_resource(&entry.resource)?;
+            let local = location.segment == header.segment_number;
+            if entry.is_metadata() {
+                image += 1;
+                if !local {
+                    continue;
+                }
+                let root = self.read_metadata_root(image)?;
+                root.walk_with_path(&mut |path, dentry| {
+                    let streams = std::iter::once((&dentry.hash, "")).chain(
+                        dentry
+                            .streams
+                            .iter()
+                            .map(|stream| (&stream.hash, stream.name.as_str())),
+                    );
+                    for (hash, name) in streams {
+                        if *hash == [0u8; 20] {
+                            continue;
+                        }
+                        let path = if name.is_empty() {
+                            path.to_string()
+                        } else {
+                            format!("{path}:{name}")
+                        };
+                        let uses = owners.entry(*hash).or_default();
+                        // 未命名数据流也可能以空名称的附加条目重复记录
+                        if !uses
+                            .iter()
+                            .any(|owner| owner.image == image && owner.path == path)
+                        {
+                            uses.push(RegionOwner { image, path });
+                        }
+                    }
+                });
+            }
+            if !local {
+                external_resources += 1;
+            }
+        }
+
+        for (kind, resource) in header.resources() {
+            if resource.is_absent() {
+                continue;
+            }
+            let (kind, label) = match kind {
+                ResourceKind::OffsetTable => (
+                    RegionKind::OffsetTable,
+                    format!("偏移表 ({} 个条目)", resource.size / 50),
+                ),
+                ResourceKind::XmlData => (RegionKind::XmlData, "XML 数据 (UTF-16 LE)".to_string()),
+                // 引导元数据通常就是某个镜像的元数据资源
+                ResourceKind::BootMetadata
+                    if entries
+                        .iter()
+                        .any(|entry| entry.resource.offset == resource.offset) =>
+                {
+                    continue;
+                }
+                ResourceKind::BootMetadata => (RegionKind::BootMetadata, "引导元数据".to_string()),
+                ResourceKind::Integrity => (RegionKind::Integrity, "完整性表".to_string()),
+            };
+            let mut region = LayoutRegion::new(resource.offset, resource.size, kind, label);
+            region.compressed = resource.is_compressed();
+            region.original_size = resource.original_size;
+            regions.push(region);
+        }
+
+        // 固实资源的偏移 -> 区域序号
+        let mut solid_regions: HashMap<u64, usize> = HashMap::new();
+        let mut image = 0;
+        let mut solid_streams = Vec::new();
+        for entry in &entries {
+            let resource = &entry.resource;
+            if entry.is_metadata() {
+                image += 1;
+            }
+            if self.resolve_resource(resource)?.segment != header.segment_number {
+                continue;
+            }
+            if resource.is_solid() && !resource.is_solid_resource() {
+                solid_streams.push(entry);
+                continue;
+            }
+            let (kind, label, length, original_size) = if entry.is_metadata() {
+                let label = if header.bootable_image_index == image {
+                    format!("镜像 {image} 元数据（引导镜像）")
+                } else {
+                    format!("镜像 {image} 元数据")
+                };
+                let kind = RegionKind::Metadata(image);
+                (kind, label, resource.size, resource.original_size)
+            } else if resource.is_solid_resource() {
+                let original_size = self.read_solid_header(resource)?.original_size;
+                solid_regions.insert(resource.offset, regions.len());
+                let kind = RegionKind::SolidResource;
+                (kind, String::new(), resource.size, original_size)
+            } else {
+                let label = stream_label(owners.get(&entry.hash).map_or(&[], Vec::as_slice));
+                (
+                    RegionKind::Stream,
+                    label,
+                    resource.size,
+                    resource.original_size,
+                )
+            };
+            let mut region = LayoutRegion::new(resource.offset, length, kind, label);
+            region.hash = Some(entry.hash);
+            region.compressed = resource.is_compressed();
+            region.original_size = original_size;
+            if kind == RegionKind::Stream {
+                region.owners = owners.remove(&entry.hash).unwrap_or_default();
+            }
+            regions.push(region);
+        }
+
+        let mut contained: HashMap<usize, usize> = HashMap::new();
+        for entry in solid_streams {
+            let solid = self.locate_solid_stream(&entry.resource)?;
+            let Some(&position) = solid_regions.get(&solid.resource.offset) else {
+                continue;
+            };
+            *contained.entry(position).or_default() += 1;
+            if let Some(uses) = owners.remove(&entry.hash) {
+                regions[position].owners.extend(uses);
+            }
+        }
+        for (&position, &count) in &contained {
+            regions[position].label = format!("固实资源 ({count} 个数据流)");
+        }
+        for &position in solid_regions.values() {
+            if regions[position].label.is_empty() {
+                regions[position].label = "固实资源 (0 个数据流)".to_string();
+            }
+        }
+
+        regions.sort_by_key(|region| (region.offset, region.end()));
+
+        // 嵌入其他文件中的 WIM 以最后一个区域结束，独立文件以文件末尾结束
+        let regions_end = regions.iter().map(LayoutRegion::end).max().unwrap_or(0);
+        let data_size = if self.base_offset() == 0 {
+            self.file.get_ref().metadata()?.len().max(regions_end)
+        } else {
+            regions_end
+        };
+
+        let mut covered = 0;
+        let mut gaps = Vec::new();
+        for region in &regions {
+            if region.offset > covered {
+                gaps.push(LayoutRegion::new(
+                    covered,
+                    region.offset - covered,
+                    RegionKind::Unreferenced,
+                    "未引用的区域",
+                ));
+            }
+            covered = covered.max(region.end());
+        }
+        if data_size > covered {
+            gaps.push(LayoutRegion::new(
+                covered,
+                data_size - covered,
+                RegionKind::Unreferenced,
+                "未引用的区域",
+            ));
+        }
+        regions.extend(gaps);
+        regions.sort_by_key(|region| (region.offset, region.end()));
+
+        let report = LayoutReport {
+            base_offset: self.base_offset(),
+            data_size,
+            segment_number: header.segment_number,
+            regions,
+            external_resources,
+        };
+        debug!(
+            "布局注释 - 其他分卷中的资源: {}, 未引用字节: {}",
+            report.external_resources,
+            report.unreferenced_bytes()
+        );
+        info!(
+            "布局注释完成 - 区域: {}, 数据大小: {}",
+            report.regions.len(),
+            format_bytes(report.data_size)
+        );
+        Ok(report)
+    }
+}
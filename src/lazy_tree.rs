@@ -0,0 +1,375 @@
+//! 按需加载的镜像目录树：只保留解压后的元数据资源和每个目录的子目录项列表偏移索引，
+//! 目录的内容在第一次访问时才解析，适合 40 万以上文件的大镜像中只查看少数目录的场景
+
+use anyhow::{Context, Result};
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::io::{self, Read, Write};
+
+use crate::log::debug;
+use crate::metadata::{self, DirEntry};
+use crate::names;
+use crate::patch_stream;
+use crate::{
+    Error, FileStream, NameMatching, ReparseInfo, ResourceLimits, WimParser, WimTimestamp,
+};
+
+/// 按需加载的目录项
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LazyEntry {
+    /// 长文件名（根目录为空；不成对的代理项转义为 `%uXXXX`，见 [`decode_name`](crate::decode_name)）
+    pub name: String,
+    /// 文件属性
+    pub attributes: u32,
+    /// 未命名数据流的 SHA-1（全零表示空文件）
+    pub hash: [u8; 20],
+    /// 创建时间
+    pub creation_time: WimTimestamp,
+    /// 最后写入时间
+    pub last_write_time: WimTimestamp,
+    /// 重解析点标记（仅对重解析点有效）
+    pub reparse_tag: u32,
+    /// 原始 UTF-16 名称（仅当其不是有效的 UTF-16 时保留）
+    name_utf16: Option<Vec<u16>>,
+    /// 子目录项列表在元数据资源中的偏移（0 表示无）
+    subdir_offset: u64,
+}
+
+impl LazyEntry {
+    fn new(entry: DirEntry, subdir_offset: u64) -> Self {
+        Self {
+            name: entry.name,
+            attributes: entry.attributes,
+            hash: entry.hash,
+            creation_time: entry.creation_time,
+            last_write_time: entry.last_write_time,
+            reparse_tag: entry.reparse_tag,
+            name_utf16: entry.name_utf16,
+            subdir_offset,
+        }
+    }
+
+    /// 原始 UTF-16 名称
+    pub fn raw_name(&self) -> Cow<'_, [u16]> {
+        names::raw_units(&self.name, self.name_utf16.as_deref())
+    }
+
+    /// 名称的有损 UTF-8 形式（不成对的代理项显示为 U+FFFD）
+    pub fn lossy_name(&self) -> Cow<'_, str> {
+        match &self.name_utf16 {
+            Some(units) => Cow::Owned(names::lossy_name(units)),
+            None => Cow::Borrowed(&self.name),
+        }
+    }
+
+    /// 原始名称是否为有效的 UTF-16（无效时 [`name`](Self::name) 为转义形式）
+    pub fn has_valid_name(&self) -> bool {
+        self.name_utf16.is_none()
+    }
+
+    /// 是否为目录
+    pub fn is_directory(&self) -> bool {
+        self.attributes & metadata::FILE_ATTRIBUTE_DIRECTORY != 0
+    }
+
+    /// 是否为重解析点
+    pub fn is_reparse_point(&self) -> bool {
+        self.attributes & metadata::FILE_ATTRIBUTE_REPARSE_POINT != 0
+    }
+}
+
+/// 按需加载的镜像目录树（由 [`WimParser::open_lazy_tree`] 创建）
+#[derive(Debug, Clone)]
+pub struct LazyTree {
+    index: u32,
+    data: Vec<u8>,
+    limits: ResourceLimits,
+    matching: NameMatching,
+    root: LazyEntry,
+    /// 目录路径（按访问时的写法，`/` 分隔，根目录为空）到子目录项列表偏移的索引
+    dir_offsets: HashMap<String, u64>,
+    /// 已解析的子目录项列表，按列表偏移索引
+    loaded: HashMap<u64, Vec<LazyEntry>>,
+    /// 已解析的目录项数量（含根目录）
+    dentries: usize,
+}
+
+/// 路径的组成部分（`\\` 或 `/` 分隔，忽略空段）
+fn components(path: &str) -> impl Iterator<Item = &str> {
+    path.split(['\\', '/']).filter(|part| !part.is_empty())
+}
+
+impl LazyTree {
+    /// 镜像索引
+    pub fn index(&self) -> u32 {
+        self.index
+    }
+
+    /// 根目录项
+    pub fn root(&self) -> &LazyEntry {
+        &self.root
+    }
+
+    /// 已解析的目录数量
+    pub fn loaded_dirs(&self) -> usize {
+        self.loaded.len()
+    }
+
+    /// 已解析的目录项数量（含根目录）
+    pub fn loaded_entries(&self) -> usize {
+        self.dentries
+    }
+
+    /// 列出目录的内容（`\\` 或 `/` 分隔，按 [`ParseOptions::name_matching`](crate::ParseOptions::name_matching)
+    /// 的规则比较名称，空路径为根目录）
+    ///
+    /// 只解析路径上经过的目录，已解析的目录会被缓存。
+    pub fn list_dir(&mut self, path: &str) -> Result<&[LazyEntry]> {
+        let offset = self.dir_offset(path)?;
+        self.load(offset)?;
+        Ok(self
+            .loaded
+            .get(&offset)
+            .map(Vec::as_slice)
+            .unwrap_or_default())
+    }
+
+    /// 按路径查找目录项（空路径返回根目录），不存在时返回 `None`
+    pub fn find(&mut self, path: &str) -> Result<Option<LazyEntry>> {
+        let path = path.trim_end_matches(['\\', '/']);
+        let (parent, name) = match path.rfind(['\\', '/']) {
+            Some(split) => (&path[..split], &path[split + 1..]),
+            None => ("", path),
+        };
+        if components(path).next().is_none() {
+            return Ok(Some(self.root.clone()));
+        }
+        let offset = match self.try_dir_offset(parent)? {
+            Some(offset) => offset,
+            None => return Ok(None),
+        };
+        self.load(offset)?;
+        Ok(self
+            .matching
+            .find(&self.loaded[&offset], name, |entry| &entry.name)
+            .cloned())
+    }
+
+    /// 打开文件的未命名数据流，返回按需解压的 [`FileStream`]
+    ///
+    /// 只解析路径上经过的目录；`parser` 必须是创建此目录树的同一 WIM 文件。
+    pub fn open_file<'p>(
+        &mut self,
+        parser: &'p mut WimParser,
+        path: &str,
+    ) -> Result<FileStream<'p>> {
+        let entry = self
+            .find(path)?
+            .ok_or_else(|| anyhow::anyhow!("镜像 {} 中找不到 {}", self.index, path))?;
+        if entry.is_directory() {
+            return Err(anyhow::anyhow!("{} 是目录，不能作为文件读取", path));
+        }
+        if entry.is_reparse_point() {
+            // 未命名数据流是重解析数据，按文件内容写出会把链接变成普通文件
+            let info = self.reparse_info(parser, path)?;
+            return Err(
+                anyhow::Error::new(Error::UnsupportedStreamType("重解析点")).context(format!(
+                    "{} 是{}，不能作为文件读取",
+                    path,
+                    info.map_or_else(|| "重解析点".to_string(), |info| info.to_string())
+                )),
+            );
+        }
+        if entry.hash == [0u8; 20] {
+            return Ok(FileStream::empty());
+        }
+        let resource = parser
+            .stream_resource(&entry.hash)?
+            .ok_or_else(|| anyhow::anyhow!("偏移表中找不到 {} 的数据流", path))?;
+        let mut reader = parser
+            .open_resource(&resource)
+            .with_context(|| format!("读取 {path} 失败"))?;
+
+        // 先检查开头的补丁签名，其余部分在读取时才逐块解压
+        let mut head = Vec::with_capacity(patch_stream::PATCH_SIGNATURE_LEN);
+        (&mut reader)
+            .take(patch_stream::PATCH_SIGNATURE_LEN as u64)
+            .read_to_end(&mut head)
+            .with_context(|| format!("读取 {path} 失败"))?;
+        patch_stream::ensure_plain_stream(&head, path)?;
+        Ok(FileStream::new(head, reader))
+    }
+
+    /// 读取并解析重解析点的数据（符号链接、目录联接的目标等），不是重解析点时返回 `None`
+    ///
+    /// 只解析路径上经过的目录；`parser` 必须是创建此目录树的同一 WIM 文件。
+    pub fn reparse_info(
+        &mut self,
+        parser: &mut WimParser,
+        path: &str,
+    ) -> Result<Option<ReparseInfo>> {
+        let entry = self
+            .find(path)?
+            .ok_or_else(|| anyhow::anyhow!("镜像 {} 中找不到 {}", self.index, path))?;
+        if !entry.is_reparse_point() {
+            return Ok(None);
+        }
+        let data = if entry.hash == [0u8; 20] {
+            Vec::new()
+        } else {
+            let resource = parser
+                .stream_resource(&entry.hash)?
+                .ok_or_else(|| anyhow::anyhow!("偏移表中找不到 {} 的重解析数据", path))?;
+            parser
+                .read_stream_resource(&resource)
+                .with_context(|| format!("读取 {path} 的重解析数据失败"))?
+        };
+        let info = ReparseInfo::parse(entry.reparse_tag, &data)
+            .map_err(anyhow::Error::new)
+            .with_context(|| format!("解析 {path} 的重解析数据失败"))?;
+        Ok(Some(info))
+    }
+
+    /// 将文件的未命名数据流写入 `writer`，返回写入的字节数
+    ///
+    /// 只解析路径上经过的目录；`parser` 必须是创建此目录树的同一 WIM 文件。
+    pub fn extract_file<W: Write>(
+        &mut self,
+        parser: &mut WimParser,
+        path: &str,
+        mut writer: W,
+    ) -> Result<u64> {
+        let mut stream = self.open_file(parser, path)?;
+        io::copy(&mut stream, &mut writer).with_context(|| format!("释放 {path} 失败"))
+    }
+
+    /// 目录的子目录项列表偏移，路径不存在或不是目录时返回错误
+    fn dir_offset(&mut self, path: &str) -> Result<u64> {
+        self.try_dir_offset(path)?
+            .ok_or_else(|| anyhow::anyhow!("镜像 {} 中找不到目录 {}", self.index, path))
+    }
+
+    /// 目录的子目录项列表偏移，路径不存在或不是目录时返回 `None`
+    fn try_dir_offset(&mut self, path: &str) -> Result<Option<u64>> {
+        let key = components(path).collect::<Vec<_>>().join("/");
+        if let Some(&offset) = self.dir_offsets.get(&key) {
+            return Ok(Some(offset));
+        }
+
+        let mut offset = self.root.subdir_offset;
+        let mut current = String::new();
+        for part in components(path) {
+            self.load(offset)?;
+            let Some(child) = self
+                .matching
+                .find(&self.loaded[&offset], part, |entry| &entry.name)
+            else {
+                return Ok(None);
+            };
+            if !child.is_directory() {
+                return Ok(None);
+            }
+            offset = child.subdir_offset;
+            if !current.is_empty() {
+                current.push('/');
+            }
+            current.push_str(part);
+            self.dir_offsets.insert(current.clone(), offset);
+        }
+        Ok(Some(offset))
+    }
+
+    /// 解析指定偏移处的子目录项列表（已解析时直接返回）
+    fn load(&mut self, offset: u64) -> Result<()> {
+        if self.loaded.contains_key(&offset) {
+            return Ok(());
+        }
+        let entries = if offset == 0 {
+            Vec::new()
+        } else {
+            metadata::parse_dentry_list(&self.data, offset, &self.limits, self.dentries)
+                .with_context(|| format!("解析镜像 {} 的目录项失败", self.index))?
+                .into_iter()
+                .map(|(entry, subdir_offset)| LazyEntry::new(entry, subdir_offset))
+                .collect()
+        };
+        self.dentries += entries.len();
+        debug!(
+            "按需加载镜像 {} 的目录 (偏移 {}): {} 个目录项",
+            self.index,
+            offset,
+            entries.len()
+        );
+        self.loaded.insert(offset, entries);
+        Ok(())
+    }
+}
+
+impl WimParser {
+    /// 打开镜像的按需加载目录树
+    ///
+    /// 只读取并解压元数据资源、解析根目录项；目录内容在
+    /// [`LazyTree::list_dir`]、[`LazyTree::find`] 或 [`LazyTree::extract_file`] 访问时才解析。
+    /// 与完整解析相比，内存占用约为元数据资源本身加上已访问目录的目录项。
+    ///
+    /// ```
+    /// # #[cfg(feature = "fixtures")] {
+    /// # let fixture = wim_parser::fixtures::MiniWim::create()?;
+    /// # let mut parser = wim_parser::WimParser::new(fixture.path())?;
+    /// let mut tree = parser.open_lazy_tree(1)?;
+    /// let names: Vec<&str> = tree.list_dir("Windows/System32")?.iter().map(|entry| entry.name.as_str()).collect();
+    /// assert!(names.contains(&"ntoskrnl.exe"));
+    /// assert_eq!(tree.loaded_dirs(), 3);
+    /// # }
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    pub fn open_lazy_tree(&mut self, index: u32) -> Result<LazyTree> {
+        let data = self.read_metadata_bytes(index)?;
+        let limits = self.options().resource_limits();
+        let matching = self.options().name_matching_rules();
+        let (root, subdir_offset) = metadata::parse_root_dentry(&data, &limits)
+            .with_context(|| format!("解析镜像 {index} 的元数据资源失败"))?;
+        Ok(LazyTree {
+            index,
+            data,
+            limits,
+            matching,
+            root: LazyEntry::new(root, subdir_offset),
+            dir_offsets: HashMap::from([(String::new(), subdir_offset)]),
+            loaded: HashMap::new(),
+            dentries: 1,
+        })
+    }
+
+    /// 从镜像中释放单个文件的未命名数据流到 `writer`，返回写入的字节数
+    ///
+    /// 路径按 [`NameMatching`] 规则查找（默认不区分大小写），`/` 和 `\` 均可作为分隔符。
+    /// 只解析路径上经过的目录，数据流逐块解压后写入，
+    /// 适合只需要 setup.exe、版本 DLL 等少数文件而不必释放整个镜像的场景。
+    /// 多次释放同一镜像中的文件时，[`open_lazy_tree`](Self::open_lazy_tree) 可以复用已解析的目录。
+    ///
+    /// ```
+    /// # #[cfg(feature = "fixtures")] {
+    /// # let fixture = wim_parser::fixtures::MiniWim::create()?;
+    /// # let mut parser = wim_parser::WimParser::new(fixture.path())?;
+    /// let mut setup = Vec::new();
+    /// let size = parser.extract_file(1, "/setup.exe", &mut setup)?;
+    /// assert_eq!(size, setup.len() as u64);
+    /// assert!(setup.starts_with(b"MZ"));
+    /// # }
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    pub fn extract_file<W: Write>(&mut self, index: u32, path: &str, writer: W) -> Result<u64> {
+        self.open_lazy_tree(index)?.extract_file(self, path, writer)
+    }
+
+    /// 读取并解析镜像中重解析点的数据，不是重解析点时返回 `None`
+    ///
+    /// 路径的查找规则与 [`extract_file`](Self::extract_file) 相同。符号链接和目录联接解析出目标，
+    /// 其他重解析点保留原始数据（见 [`ReparseInfo`]）；[`extract_file`](Self::extract_file) 和
+    /// [`open_file_stream`](Self::open_file_stream) 对重解析点返回错误，而不是把重解析数据当作文件内容写出。
+    pub fn reparse_info(&mut self, index: u32, path: &str) -> Result<Option<ReparseInfo>> {
+        let mut tree = self.open_lazy_tree(index)?;
+        tree.reparse_info(self, path)
+    }
+}
@@ -0,0 +1,255 @@
+use anyhow::Result;
+use std::fmt;
+
+use crate::fmt::{Table, ToTable};
+use crate::log::debug;
+use crate::WimParser;
+
+/// SOFTWARE 注册表配置单元，其中记录产品密钥信息
+const SOFTWARE_HIVE: &str = r"\Windows\System32\config\SOFTWARE";
+/// 软件保护平台的 SKU 许可证令牌目录
+const SKU_TOKENS_DIR: &str = r"\Windows\System32\spp\tokens\skus";
+
+/// 产品密钥信息的注册表值名称 (`Microsoft\Windows NT\CurrentVersion\DigitalProductId4`)
+const DIGITAL_PRODUCT_ID4: &[u8] = b"DigitalProductId4";
+/// DigitalProductId4 中通道字符串 (UTF-16) 的偏移
+const CHANNEL_OFFSET: usize = 0x3F8;
+/// 通道字符串的最大字节数（64 个字符）
+const CHANNEL_MAX_BYTES: usize = 128;
+/// 配置单元中 hbin 数据区相对文件开头的偏移
+const HIVE_BINS_OFFSET: usize = 0x1000;
+
+/// 许可证通道
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum LicenseChannel {
+    /// 零售
+    Retail,
+    /// OEM 预装
+    Oem,
+    /// 批量许可 (GVLK / MAK)
+    Volume,
+    /// 评估版
+    Evaluation,
+}
+
+impl LicenseChannel {
+    /// 从通道字符串（例如 `Retail`、`OEM:DM`、`Volume:GVLK`、`Retail:TB:Eval`）识别通道
+    pub fn from_channel_str(channel: &str) -> Option<LicenseChannel> {
+        let lower = channel.to_ascii_lowercase();
+        if lower.contains("eval") {
+            Some(LicenseChannel::Evaluation)
+        } else if lower.starts_with("volume") {
+            Some(LicenseChannel::Volume)
+        } else if lower.starts_with("oem") {
+            Some(LicenseChannel::Oem)
+        } else if lower.starts_with("retail") {
+            Some(LicenseChannel::Retail)
+        } else {
+            None
+        }
+    }
+
+    /// 从许可证令牌文件名（例如 `Professional-OEM-DM-ul-oob-rtm.xrm-ms`）识别通道
+    fn from_token_name(name: &str) -> Option<LicenseChannel> {
+        let lower = name.to_ascii_lowercase();
+        let parts: Vec<&str> = lower.split(['-', '_', '.']).collect();
+        let has = |part: &str| parts.contains(&part);
+        if has("eval") || has("evaluation") {
+            Some(LicenseChannel::Evaluation)
+        } else if has("volume") {
+            Some(LicenseChannel::Volume)
+        } else if has("oem") {
+            Some(LicenseChannel::Oem)
+        } else if has("retail") {
+            Some(LicenseChannel::Retail)
+        } else {
+            None
+        }
+    }
+
+    /// 通道名称
+    pub fn name(&self) -> &'static str {
+        match self {
+            LicenseChannel::Retail => "Retail",
+            LicenseChannel::Oem => "OEM",
+            LicenseChannel::Volume => "Volume",
+            LicenseChannel::Evaluation => "Eval",
+        }
+    }
+}
+
+impl fmt::Display for LicenseChannel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// 许可证通道的判断依据
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChannelSource {
+    /// SOFTWARE 配置单元中的 `DigitalProductId4`（镜像中实际安装的密钥）
+    DigitalProductId,
+    /// 版本 ID 以 `Eval` 结尾（例如 `ServerStandardEval`）
+    EditionId,
+    /// SKU 许可证令牌中只有一种通道
+    SkuTokens,
+}
+
+/// 镜像的许可证信息
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LicenseInfo {
+    /// 镜像索引
+    pub index: u32,
+    /// 判断出的许可证通道
+    pub channel: Option<LicenseChannel>,
+    /// `DigitalProductId4` 中的原始通道字符串
+    pub raw_channel: Option<String>,
+    /// 判断依据
+    pub source: Option<ChannelSource>,
+    /// 镜像中 SKU 许可证令牌包含的通道（去重、排序）
+    pub sku_channels: Vec<LicenseChannel>,
+}
+
+impl ToTable for [LicenseInfo] {
+    fn table(&self) -> Table {
+        let mut table = Table::new(["索引", "通道", "原始值", "令牌通道"]);
+        for info in self {
+            let tokens: Vec<&str> = info.sku_channels.iter().map(|c| c.name()).collect();
+            table.push_row([
+                info.index.to_string(),
+                info.channel
+                    .map_or_else(|| "-".to_string(), |channel| channel.to_string()),
+                info.raw_channel.clone().unwrap_or_else(|| "-".to_string()),
+                if tokens.is_empty() {
+                    "-".to_string()
+                } else {
+                    tokens.join(", ")
+                },
+            ]);
+        }
+        table
+    }
+}
+
+/// 在注册表配置单元中查找指定名称的值数据
+///
+/// vk 单元布局：签名 `vk` (2) + 名称长度 (2) + 数据大小 (4) + 数据偏移/内联数据 (4) +
+/// 数据类型 (4) + 标志 (2) + 保留 (2) + 名称。非内联数据位于 hbin 数据区中的数据单元
+/// （4 字节单元大小之后）。
+fn find_value_data<'a>(hive: &'a [u8], name: &[u8]) -> Option<&'a [u8]> {
+    const NAME_OFFSET: usize = 0x14;
+
+    hive.windows(name.len())
+        .enumerate()
+        .filter(|(_, window)| window.eq_ignore_ascii_case(name))
+        .filter_map(|(pos, _)| pos.checked_sub(NAME_OFFSET))
+        .find_map(|cell| {
+            let field = |offset: usize| -> Option<u32> {
+                Some(u32::from_le_bytes(
+                    hive.get(cell + offset..cell + offset + 4)?
+                        .try_into()
+                        .ok()?,
+                ))
+            };
+            let name_len = u16::from_le_bytes(hive.get(cell + 2..cell + 4)?.try_into().ok()?);
+            if hive.get(cell..cell + 2)? != b"vk" || usize::from(name_len) != name.len() {
+                return None;
+            }
+            let size = field(4)?;
+            if size & 0x8000_0000 != 0 {
+                let len = (size & 0x7FFF_FFFF).min(4) as usize;
+                return hive.get(cell + 8..cell + 8 + len);
+            }
+            let start = HIVE_BINS_OFFSET + field(8)? as usize + 4;
+            hive.get(start..start + size as usize)
+        })
+}
+
+/// 从 DigitalProductId4 数据中读取通道字符串
+fn channel_from_product_id(data: &[u8]) -> Option<String> {
+    let bytes = data.get(CHANNEL_OFFSET..)?;
+    let units: Vec<u16> = bytes[..bytes.len().min(CHANNEL_MAX_BYTES)]
+        .chunks_exact(2)
+        .map(|chunk| u16::from_le_bytes([chunk[0], chunk[1]]))
+        .take_while(|&unit| unit != 0)
+        .collect();
+    let channel = String::from_utf16(&units).ok()?;
+    (!channel.is_empty()).then_some(channel)
+}
+
+impl WimParser {
+    /// 深度探测镜像的许可证通道（零售 / OEM / 批量 / 评估）
+    ///
+    /// 需要读取镜像中的 SOFTWARE 注册表配置单元和 SKU 许可证令牌目录，开销较大，
+    /// 只在调用时执行。依次使用 `DigitalProductId4` 中的通道字符串、以 `Eval` 结尾的
+    /// 版本 ID、以及只包含一种通道的 SKU 令牌进行判断。
+    pub fn license_info(&mut self, index: u32) -> Result<LicenseInfo> {
+        if self.images.is_empty() {
+            self.parse_full()?;
+        }
+        let edition_id = self
+            .get_image(index)
+            .ok_or_else(|| anyhow::anyhow!("XML 数据中没有镜像 {}", index))?
+            .edition_id
+            .clone();
+
+        let root = self.read_metadata_root(index)?;
+
+        let mut sku_channels = Vec::new();
+        if let Some(skus) = root.find_path(SKU_TOKENS_DIR) {
+            skus.walk(&mut |entry| {
+                if let Some(channel) = (!entry.is_directory())
+                    .then(|| LicenseChannel::from_token_name(&entry.name))
+                    .flatten()
+                {
+                    sku_channels.push(channel);
+                }
+            });
+        }
+        sku_channels.sort();
+        sku_channels.dedup();
+
+        let hive_hash = root
+            .find_path(SOFTWARE_HIVE)
+            .filter(|entry| !entry.is_directory())
+            .map(|entry| entry.hash);
+        let raw_channel = match hive_hash {
+            Some(hash) => match self.read_stream(&hash) {
+                Ok(hive) => {
+                    find_value_data(&hive, DIGITAL_PRODUCT_ID4).and_then(channel_from_product_id)
+                }
+                Err(e) => {
+                    debug!("无法读取镜像 {} 的 SOFTWARE 配置单元: {}", index, e);
+                    None
+                }
+            },
+            None => None,
+        };
+
+        let from_product_id = raw_channel
+            .as_deref()
+            .and_then(LicenseChannel::from_channel_str)
+            .map(|channel| (channel, ChannelSource::DigitalProductId));
+        let from_edition = edition_id
+            .filter(|id| id.to_ascii_lowercase().ends_with("eval"))
+            .map(|_| (LicenseChannel::Evaluation, ChannelSource::EditionId));
+        let from_tokens = match sku_channels.as_slice() {
+            [channel] => Some((*channel, ChannelSource::SkuTokens)),
+            _ => None,
+        };
+        let detected = from_product_id.or(from_edition).or(from_tokens);
+
+        debug!(
+            "镜像 {} 许可证通道: {:?}, 原始值: {:?}, 令牌通道: {:?}",
+            index, detected, raw_channel, sku_channels
+        );
+
+        Ok(LicenseInfo {
+            index,
+            channel: detected.map(|(channel, _)| channel),
+            raw_channel,
+            source: detected.map(|(_, source)| source),
+            sku_channels,
+        })
+    }
+}
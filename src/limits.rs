@@ -0,0 +1,104 @@
+//! 每次操作的资源限制，用于在内存受限的服务（如 256 MB 的容器）中可预期地使用本库
+
+use crate::Error;
+
+/// 资源限制：在解析、缓存和提取过程中检查，超出时返回 [`Error::LimitExceeded`]
+///
+/// 所有限制默认关闭（`None`）。通过 [`ParseOptions::limits`](crate::ParseOptions::limits)
+/// 应用到解析器，通过 [`WimCatalogCache::with_limits`](crate::WimCatalogCache::with_limits)
+/// 应用到缓存。
+///
+/// ```
+/// # use wim_parser::{ParseOptions, ResourceLimits};
+/// let limits = ResourceLimits {
+///     max_memory: Some(64 * 1024 * 1024),
+///     max_xml_bytes: Some(4 * 1024 * 1024),
+///     ..ResourceLimits::default()
+/// };
+/// let options = ParseOptions::new().limits(limits);
+/// assert_eq!(options.resource_limits().max_memory, Some(64 * 1024 * 1024));
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ResourceLimits {
+    /// 单次操作最多分配的缓冲区字节数（读取资源、校验缓冲区、缓存内容）
+    pub max_memory: Option<u64>,
+    /// 同时持有的读取块数量上限（并行校验时即工作线程数上限）
+    pub max_open_chunks: Option<usize>,
+    /// XML 数据资源的最大字节数
+    pub max_xml_bytes: Option<u64>,
+    /// 单个镜像元数据中目录项数量的上限
+    pub max_dentries: Option<usize>,
+}
+
+impl ResourceLimits {
+    /// 不设任何限制（默认）
+    pub fn unlimited() -> Self {
+        Self::default()
+    }
+
+    /// 按总内存预算推导各项限制
+    ///
+    /// XML 数据最多占预算的 1/8，读取块以 1 MiB 计并最多占预算的 1/4，
+    /// 目录项按每项约 256 字节估算并最多占预算的 1/2。
+    pub fn with_memory_budget(bytes: u64) -> Self {
+        const CHUNK_SIZE: u64 = 1024 * 1024;
+        const DENTRY_COST: u64 = 256;
+        Self {
+            max_memory: Some(bytes),
+            max_open_chunks: Some((bytes / 4 / CHUNK_SIZE).max(1) as usize),
+            max_xml_bytes: Some(bytes / 8),
+            max_dentries: Some((bytes / 2 / DENTRY_COST) as usize),
+        }
+    }
+
+    /// 是否设置了任何限制
+    pub fn is_limited(&self) -> bool {
+        *self != Self::default()
+    }
+
+    /// 检查一次分配的字节数
+    pub fn check_memory(&self, bytes: u64) -> Result<(), Error> {
+        check("max_memory", bytes, self.max_memory)
+    }
+
+    /// 检查 XML 数据的字节数
+    pub fn check_xml_bytes(&self, bytes: u64) -> Result<(), Error> {
+        check("max_xml_bytes", bytes, self.max_xml_bytes)
+    }
+
+    /// 检查目录项数量
+    pub fn check_dentries(&self, count: usize) -> Result<(), Error> {
+        check(
+            "max_dentries",
+            count as u64,
+            self.max_dentries.map(|max| max as u64),
+        )
+    }
+
+    /// 在限制内可同时持有的读取块数量和每块大小（不超过 `chunk_size`）
+    #[cfg_attr(not(feature = "verify"), allow(dead_code))]
+    pub(crate) fn chunk_budget(&self, chunks: usize, chunk_size: usize) -> (usize, usize) {
+        let mut chunks = chunks.max(1);
+        if let Some(max) = self.max_open_chunks {
+            chunks = chunks.min(max.max(1));
+        }
+        let mut chunk_size = chunk_size.max(1);
+        if let Some(max) = self.max_memory {
+            let max = usize::try_from(max).unwrap_or(usize::MAX).max(1);
+            chunks = chunks.min((max / chunk_size).max(1));
+            chunk_size = chunk_size.min(max / chunks).max(1);
+        }
+        (chunks, chunk_size)
+    }
+}
+
+fn check(limit: &'static str, requested: u64, allowed: Option<u64>) -> Result<(), Error> {
+    match allowed {
+        Some(allowed) if requested > allowed => Err(Error::LimitExceeded {
+            limit,
+            requested,
+            allowed,
+        }),
+        _ => Ok(()),
+    }
+}
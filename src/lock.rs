@@ -0,0 +1,70 @@
+//! 编辑和写入操作的咨询式文件锁（Unix 上为 `flock`，Windows 上为 `LockFileEx`）
+//!
+//! 锁与打开的文件句柄绑定，句柄关闭时自动释放。只有同样加锁的进程（本库或命令行工具）
+//! 会互相排斥，不加锁的程序仍可直接写入文件。
+
+use anyhow::{Context, Result};
+use std::fs::{File, OpenOptions, TryLockError};
+use std::io;
+use std::path::Path;
+
+use crate::log::debug;
+use crate::Error;
+
+/// 编辑或写入 WIM 文件时获取排他锁的策略
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum LockPolicy {
+    /// 文件已被其他进程锁定时立即返回 [`Error::FileLocked`]（默认）
+    #[default]
+    FailFast,
+    /// 等待其他进程释放锁
+    Wait,
+    /// 不加锁
+    Disabled,
+}
+
+/// 按策略对已打开的文件加排他锁；文件系统不支持加锁时跳过
+pub(crate) fn lock_exclusive(file: &File, path: &Path, policy: LockPolicy) -> Result<()> {
+    let result = match policy {
+        LockPolicy::Disabled => return Ok(()),
+        LockPolicy::Wait => file.lock().map_err(TryLockError::Error),
+        LockPolicy::FailFast => file.try_lock(),
+    };
+    match result {
+        Ok(()) => {
+            debug!("已锁定文件: {}", path.display());
+            Ok(())
+        }
+        Err(TryLockError::WouldBlock) => Err(anyhow::Error::new(Error::FileLocked)
+            .context(format!("{} 正被其他进程编辑", path.display()))),
+        Err(TryLockError::Error(err)) if err.kind() == io::ErrorKind::Unsupported => {
+            debug!("文件系统不支持文件锁，跳过加锁: {}", path.display());
+            Ok(())
+        }
+        Err(TryLockError::Error(err)) => {
+            Err(err).with_context(|| format!("无法锁定文件: {}", path.display()))
+        }
+    }
+}
+
+/// 打开已有文件并加排他锁，返回持有锁的句柄
+pub(crate) fn lock_existing(path: &Path, policy: LockPolicy) -> Result<File> {
+    let file =
+        File::open(path).with_context(|| format!("无法打开 WIM 文件: {}", path.display()))?;
+    lock_exclusive(&file, path, policy)?;
+    Ok(file)
+}
+
+/// 创建（或清空）输出文件并加排他锁；先加锁再截断，不会破坏其他进程正在写入的文件
+pub(crate) fn create_locked(path: &Path, policy: LockPolicy) -> Result<File> {
+    let file = OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(path)
+        .with_context(|| format!("无法创建输出文件: {}", path.display()))?;
+    lock_exclusive(&file, path, policy)?;
+    file.set_len(0)
+        .with_context(|| format!("无法清空输出文件: {}", path.display()))?;
+    Ok(file)
+}
@@ -0,0 +1,25 @@
+//! 日志宏：启用 `logging` 特性时转发到 `tracing`，否则编译为空操作
+
+#[cfg(feature = "logging")]
+pub(crate) use tracing::{debug, info};
+
+#[cfg(not(feature = "logging"))]
+macro_rules! debug {
+    ($($arg:tt)*) => {
+        if false {
+            let _ = ::core::format_args!($($arg)*);
+        }
+    };
+}
+
+#[cfg(not(feature = "logging"))]
+macro_rules! info {
+    ($($arg:tt)*) => {
+        if false {
+            let _ = ::core::format_args!($($arg)*);
+        }
+    };
+}
+
+#[cfg(not(feature = "logging"))]
+pub(crate) use {debug, info};
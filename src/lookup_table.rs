@@ -0,0 +1,58 @@
+use crate::{format, FileResourceEntry};
+
+/// 偏移表（查找表）条目大小：24 字节资源头 + 2 字节分卷号 + 4 字节引用计数 + 20 字节 SHA-1
+pub(crate) const LOOKUP_TABLE_ENTRY_SIZE: usize = 50;
+
+/// 偏移表条目结构体 (_RESHDR_DISK + PartNumber + RefCount + Hash)
+/// 总大小：50 字节
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LookupTableEntry {
+    /// 资源位置信息
+    pub resource: FileResourceEntry,
+    /// 所在分卷号
+    pub part_number: u16,
+    /// 引用计数
+    pub ref_count: u32,
+    /// 资源内容的 SHA-1
+    pub hash: [u8; 20],
+}
+
+impl LookupTableEntry {
+    /// 是否为镜像元数据资源
+    pub fn is_metadata(&self) -> bool {
+        self.resource.is_metadata()
+    }
+
+    /// 序列化为 50 字节的磁盘格式
+    pub fn to_bytes(&self) -> [u8; LOOKUP_TABLE_ENTRY_SIZE] {
+        let mut buffer = [0u8; LOOKUP_TABLE_ENTRY_SIZE];
+        buffer[0..24].copy_from_slice(&format::resource_entry_bytes(&self.resource));
+        buffer[24..26].copy_from_slice(&self.part_number.to_le_bytes());
+        buffer[26..30].copy_from_slice(&self.ref_count.to_le_bytes());
+        buffer[30..50].copy_from_slice(&self.hash);
+        buffer
+    }
+}
+
+/// 解析偏移表数据（多余的尾部字节会被忽略）
+///
+/// 不存在的条目（FREE 标志或全零条目，见 [`ResourceState::Absent`](crate::ResourceState::Absent)）
+/// 不引用任何数据，解析时直接跳过。
+pub(crate) fn parse_lookup_table(data: &[u8]) -> Vec<LookupTableEntry> {
+    let mut entries = Vec::with_capacity(data.len() / LOOKUP_TABLE_ENTRY_SIZE);
+
+    for chunk in data.chunks_exact(LOOKUP_TABLE_ENTRY_SIZE) {
+        let resource = format::parse_resource_entry(&chunk[0..24]).unwrap();
+        if resource.is_absent() {
+            continue;
+        }
+        entries.push(LookupTableEntry {
+            resource,
+            part_number: u16::from_le_bytes([chunk[24], chunk[25]]),
+            ref_count: u32::from_le_bytes(chunk[26..30].try_into().unwrap()),
+            hash: chunk[30..50].try_into().unwrap(),
+        });
+    }
+
+    entries
+}
@@ -0,0 +1,534 @@
+//! LZMS 分块解压，用于 ESD 和 `/compress:recovery` 创建的 WIM
+//!
+//! 压缩数据由两个方向相反的 16 位小端字序列组成：区间解码器从开头向后读取，按自适应概率
+//! （最近 64 次判定中 0 的个数）解码条目类型；哈夫曼码字和附加位从末尾向前读取，从高位开始。
+//! 哈夫曼码不随数据传输，而是按已解码符号的频率定期重建，重建算法须与压缩端逐位一致。
+//! 条目分为字面量、LZ 匹配和差分 (delta) 匹配；最近使用的偏移延迟一个条目才进入队列。
+//! 解压后撤销 x86 相对地址转换。
+
+use crate::Error;
+
+/// 概率的精度（位数），概率以 1/64 为单位
+const PROBABILITY_BITS: u32 = 6;
+const PROBABILITY_DENOMINATOR: u32 = 1 << PROBABILITY_BITS;
+/// 初始状态：最近 64 次判定中有 48 次为 0
+const INITIAL_PROBABILITY: u32 = 48;
+const INITIAL_RECENT_BITS: u64 = 0x0000_0000_5555_5555;
+
+/// 各类判定的状态数（即概率表大小）
+const NUM_MAIN_PROBS: usize = 16;
+const NUM_MATCH_PROBS: usize = 32;
+const NUM_LZ_PROBS: usize = 64;
+const NUM_LZ_REP_PROBS: usize = 64;
+const NUM_DELTA_PROBS: usize = 64;
+const NUM_DELTA_REP_PROBS: usize = 64;
+/// 最近偏移队列的长度（多一项用于延迟更新）
+const NUM_REPS: usize = 3;
+
+const NUM_LITERAL_SYMS: usize = 256;
+const NUM_LENGTH_SYMS: usize = 54;
+const NUM_DELTA_POWER_SYMS: usize = 8;
+const MAX_CODEWORD_LEN: usize = 15;
+
+/// 各哈夫曼码每解码多少个符号重建一次
+const LITERAL_CODE_REBUILD_FREQ: u32 = 1024;
+const LZ_OFFSET_CODE_REBUILD_FREQ: u32 = 1024;
+const LENGTH_CODE_REBUILD_FREQ: u32 = 512;
+const DELTA_OFFSET_CODE_REBUILD_FREQ: u32 = 1024;
+const DELTA_POWER_CODE_REBUILD_FREQ: u32 = 512;
+
+/// 偏移槽基址之差的游程（差值依次为 1, 2, 4, ...）及最后一个槽的上界
+const OFFSET_SLOT_RUNS: [u32; 21] = [
+    9, 0, 9, 7, 10, 15, 15, 20, 20, 30, 33, 40, 42, 45, 60, 73, 80, 85, 95, 105, 6,
+];
+const OFFSET_SLOT_LIMIT: u32 = 0x7FFF_FFFF;
+/// 长度槽基址之差的游程及最后一个槽的上界
+const LENGTH_SLOT_RUNS: [u32; 17] = [27, 4, 6, 4, 5, 2, 1, 1, 1, 1, 1, 0, 0, 0, 0, 0, 1];
+const LENGTH_SLOT_LIMIT: u32 = 0x4001_08AB;
+
+/// x86 转换的有效范围：距上一条可能的 x86 指令不超过此字节数
+const X86_MAX_TRANSLATION_OFFSET: i32 = 1023;
+/// 两次引用同一地址（低 16 位）的间隔不超过此字节数时，认为处于 x86 代码中
+const X86_ID_WINDOW_SIZE: i32 = 65535;
+
+/// 由游程展开槽的基址（末尾附加上界）和附加位数
+fn slot_table(runs: &[u32], limit: u32) -> (Vec<u32>, Vec<u32>) {
+    let mut bases = Vec::new();
+    let mut extra = Vec::new();
+    let mut base = 0u32;
+    for (order, &run) in runs.iter().enumerate() {
+        for _ in 0..run {
+            if !bases.is_empty() {
+                extra.push(order as u32);
+            }
+            base += 1 << order;
+            bases.push(base);
+        }
+    }
+    extra.push((limit - base).ilog2());
+    bases.push(limit);
+    (bases, extra)
+}
+
+/// 自适应概率：最近 64 次判定的结果及其中 0 的个数
+#[derive(Clone, Copy)]
+struct Probability {
+    zeros: u32,
+    recent: u64,
+}
+
+impl Probability {
+    const INITIAL: Self = Self {
+        zeros: INITIAL_PROBABILITY,
+        recent: INITIAL_RECENT_BITS,
+    };
+
+    /// 下一位为 0 的概率（不取 0% 和 100%）
+    fn get(&self) -> u32 {
+        self.zeros.clamp(1, PROBABILITY_DENOMINATOR - 1)
+    }
+
+    fn update(&mut self, bit: u32) {
+        let oldest = (self.recent >> 63) as u32;
+        self.zeros = (self.zeros + oldest).wrapping_sub(bit);
+        self.recent = (self.recent << 1) | u64::from(bit);
+    }
+}
+
+/// 按状态选择概率的一类判定：状态为最近几次判定结果组成的位串
+struct Decision<const N: usize> {
+    state: usize,
+    probs: [Probability; N],
+}
+
+impl<const N: usize> Decision<N> {
+    fn new() -> Self {
+        Self {
+            state: 0,
+            probs: [Probability::INITIAL; N],
+        }
+    }
+
+    fn decode(&mut self, range: &mut RangeDecoder) -> bool {
+        let bit = range.decode(&mut self.probs[self.state]);
+        self.state = ((self.state << 1) | bit as usize) & (N - 1);
+        bit == 1
+    }
+}
+
+/// 区间解码器：从数据开头按 16 位字读取
+struct RangeDecoder<'a> {
+    data: &'a [u8],
+    pos: usize,
+    range: u32,
+    code: u32,
+}
+
+impl<'a> RangeDecoder<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        let word = |i: usize| u32::from(u16::from_le_bytes([data[i], data[i + 1]]));
+        Self {
+            data,
+            pos: 4,
+            range: 0xFFFF_FFFF,
+            code: (word(0) << 16) | word(2),
+        }
+    }
+
+    fn decode(&mut self, prob: &mut Probability) -> u32 {
+        if self.range & 0xFFFF_0000 == 0 {
+            self.range <<= 16;
+            self.code <<= 16;
+            if let Some(bytes) = self.data.get(self.pos..self.pos + 2) {
+                self.code |= u32::from(u16::from_le_bytes([bytes[0], bytes[1]]));
+                self.pos += 2;
+            }
+        }
+        let bound = (self.range >> PROBABILITY_BITS) * prob.get();
+        let bit = if self.code < bound {
+            self.range = bound;
+            0
+        } else {
+            self.range -= bound;
+            self.code -= bound;
+            1
+        };
+        prob.update(bit);
+        bit
+    }
+}
+
+/// 反向比特流：从数据末尾向前按 16 位字读取，`bits` 的低 `count` 位为尚未消耗的位
+struct BitReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+    bits: u64,
+    count: u32,
+}
+
+impl<'a> BitReader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self {
+            data,
+            pos: data.len(),
+            bits: 0,
+            count: 0,
+        }
+    }
+
+    /// 保证至少有 `n` 位可读（读完后按 0 补齐）
+    fn ensure(&mut self, n: u32) {
+        while self.count < n {
+            let word = if self.pos >= 2 {
+                self.pos -= 2;
+                u64::from(u16::from_le_bytes([
+                    self.data[self.pos],
+                    self.data[self.pos + 1],
+                ]))
+            } else {
+                0
+            };
+            self.bits = (self.bits << 16) | word;
+            self.count += 16;
+        }
+    }
+
+    /// 查看接下来的 `n` 位（`n` ≤ 32）
+    fn peek(&mut self, n: u32) -> u32 {
+        self.ensure(n);
+        ((self.bits >> (self.count - n)) & ((1u64 << n) - 1)) as u32
+    }
+
+    /// 读取 `n` 位（`n` ≤ 32）
+    fn read(&mut self, n: u32) -> u32 {
+        let value = self.peek(n);
+        self.count -= n;
+        value
+    }
+}
+
+/// 按符号频率定期重建的哈夫曼码
+struct AdaptiveCode {
+    freqs: Vec<u32>,
+    rebuild_freq: u32,
+    until_rebuild: u32,
+    /// 各码长的码字数
+    counts: [u32; MAX_CODEWORD_LEN + 1],
+    /// 按（码长, 符号）排序的符号
+    symbols: Vec<u16>,
+}
+
+impl AdaptiveCode {
+    fn new(num_syms: usize, rebuild_freq: u32) -> Self {
+        Self {
+            freqs: vec![1; num_syms],
+            rebuild_freq,
+            // 第一次解码前按初始频率构建
+            until_rebuild: 1,
+            counts: [0; MAX_CODEWORD_LEN + 1],
+            symbols: Vec::with_capacity(num_syms),
+        }
+    }
+
+    /// 按当前频率重建码，之后频率减半（保持至少为 1）
+    fn rebuild(&mut self) {
+        let lens = code_lengths(&self.freqs, MAX_CODEWORD_LEN);
+        self.counts = [0; MAX_CODEWORD_LEN + 1];
+        for &len in &lens {
+            self.counts[usize::from(len)] += 1;
+        }
+        self.counts[0] = 0;
+        self.symbols.clear();
+        for len in 1..=MAX_CODEWORD_LEN as u8 {
+            self.symbols
+                .extend((0..lens.len() as u16).filter(|&symbol| lens[usize::from(symbol)] == len));
+        }
+        for freq in &mut self.freqs {
+            *freq = (*freq >> 1) + 1;
+        }
+        self.until_rebuild = self.rebuild_freq;
+    }
+
+    /// 逐位按规范码解码一个符号
+    fn decode(&mut self, bits: &mut BitReader) -> Result<usize, Error> {
+        self.until_rebuild -= 1;
+        if self.until_rebuild == 0 {
+            self.rebuild();
+        }
+
+        let word = bits.peek(MAX_CODEWORD_LEN as u32);
+        let (mut first, mut index) = (0u32, 0usize);
+        for len in 1..=MAX_CODEWORD_LEN {
+            let code = word >> (MAX_CODEWORD_LEN - len);
+            let count = self.counts[len];
+            if code.wrapping_sub(first) < count {
+                bits.count -= len as u32;
+                let symbol = usize::from(self.symbols[index + (code - first) as usize]);
+                self.freqs[symbol] += 1;
+                return Ok(symbol);
+            }
+            index += count as usize;
+            first = (first + count) << 1;
+        }
+        Err(Error::CorruptData("LZMS 哈夫曼码字无效"))
+    }
+}
+
+/// 由频率计算码长（限制最长 `max_len`）
+///
+/// 符号按（频率, 符号）排序后构建哈夫曼树，频率相同时优先合并叶子；超过最长码长的节点
+/// 改挂在较短的可用层上。压缩端使用同一算法，结果必须完全相同。
+fn code_lengths(freqs: &[u32], max_len: usize) -> Vec<u8> {
+    let n = freqs.len();
+    let mut lens = vec![0u8; n];
+    if n < 2 {
+        lens.fill(1);
+        return lens;
+    }
+
+    let mut order: Vec<usize> = (0..n).collect();
+    order.sort_unstable_by_key(|&symbol| (freqs[symbol], symbol));
+
+    // 只保存内部节点：频率和父节点下标，最后一个为根
+    let mut node_freqs = vec![0u32; n - 1];
+    let mut parents = vec![0usize; n - 1];
+    let (mut leaf, mut node) = (0, 0);
+    for new in 0..n - 1 {
+        let mut freq = 0;
+        for _ in 0..2 {
+            if leaf < n && (node == new || freqs[order[leaf]] <= node_freqs[node]) {
+                freq += freqs[order[leaf]];
+                leaf += 1;
+            } else {
+                freq += node_freqs[node];
+                parents[node] = new;
+                node += 1;
+            }
+        }
+        node_freqs[new] = freq;
+    }
+
+    // 从根向下计算深度：每个内部节点把一个码字换成下一层的两个码字
+    let mut counts = vec![0u32; max_len + 1];
+    counts[1] = 2;
+    let mut depths = vec![0usize; n - 1];
+    for node in (0..n - 2).rev() {
+        let depth = depths[parents[node]] + 1;
+        depths[node] = depth;
+        let mut len = depth;
+        if len >= max_len {
+            len = max_len - 1;
+            while counts[len] == 0 {
+                len -= 1;
+            }
+        }
+        counts[len] -= 1;
+        counts[len + 1] += 2;
+    }
+
+    // 频率最低的符号分配最长的码字
+    let mut sorted = order.into_iter();
+    for len in (1..=max_len).rev() {
+        for symbol in sorted.by_ref().take(counts[len] as usize) {
+            lens[symbol] = len as u8;
+        }
+    }
+    lens
+}
+
+/// 不超过 `value` 的最大基址所在的槽
+fn slot_for(bases: &[u32], value: u32) -> usize {
+    bases.partition_point(|&base| base <= value) - 1
+}
+
+/// 解压一个 LZMS 分块，`size` 为分块的未压缩大小
+pub(crate) fn decompress(input: &[u8], size: usize) -> Result<Vec<u8>, Error> {
+    if input.len() < 4 || !input.len().is_multiple_of(2) {
+        return Err(Error::CorruptData("LZMS 数据长度无效"));
+    }
+    let (offset_bases, offset_extra) = slot_table(&OFFSET_SLOT_RUNS, OFFSET_SLOT_LIMIT);
+    let (length_bases, length_extra) = slot_table(&LENGTH_SLOT_RUNS, LENGTH_SLOT_LIMIT);
+    let num_offset_slots = if size < 2 {
+        0
+    } else {
+        1 + slot_for(&offset_bases[..offset_bases.len() - 1], (size - 1) as u32)
+    };
+
+    let mut range = RangeDecoder::new(input);
+    let mut bits = BitReader::new(input);
+    let mut main = Decision::<NUM_MAIN_PROBS>::new();
+    let mut matches = Decision::<NUM_MATCH_PROBS>::new();
+    let mut lz = Decision::<NUM_LZ_PROBS>::new();
+    let mut lz_reps = [
+        Decision::<NUM_LZ_REP_PROBS>::new(),
+        Decision::<NUM_LZ_REP_PROBS>::new(),
+    ];
+    let mut delta = Decision::<NUM_DELTA_PROBS>::new();
+    let mut delta_reps = [
+        Decision::<NUM_DELTA_REP_PROBS>::new(),
+        Decision::<NUM_DELTA_REP_PROBS>::new(),
+    ];
+    let mut literals = AdaptiveCode::new(NUM_LITERAL_SYMS, LITERAL_CODE_REBUILD_FREQ);
+    let mut lz_offsets = AdaptiveCode::new(num_offset_slots, LZ_OFFSET_CODE_REBUILD_FREQ);
+    let mut lengths = AdaptiveCode::new(NUM_LENGTH_SYMS, LENGTH_CODE_REBUILD_FREQ);
+    let mut delta_offsets = AdaptiveCode::new(num_offset_slots, DELTA_OFFSET_CODE_REBUILD_FREQ);
+    let mut delta_powers = AdaptiveCode::new(NUM_DELTA_POWER_SYMS, DELTA_POWER_CODE_REBUILD_FREQ);
+
+    let read_offset = |code: &mut AdaptiveCode, bits: &mut BitReader| -> Result<u32, Error> {
+        let slot = code.decode(bits)?;
+        Ok(offset_bases[slot] + bits.read(offset_extra[slot]))
+    };
+    let mut read_length = |bits: &mut BitReader| -> Result<usize, Error> {
+        let slot = lengths.decode(bits)?;
+        Ok((length_bases[slot] + bits.read(length_extra[slot])) as usize)
+    };
+
+    // 新的偏移立即放入队首；上一个条目是同类匹配时，最近偏移 i 实际位于 i + 1（延迟更新）
+    let mut recent_lz = [1u32, 2, 3, 4];
+    let mut recent_delta = [1u64, 2, 3, 4];
+    // 上一个条目：0 为字面量，1 为 LZ 匹配，2 为差分匹配
+    let mut previous = 0usize;
+    let mut out = Vec::with_capacity(size);
+
+    while out.len() < size {
+        if !main.decode(&mut range) {
+            out.push(literals.decode(&mut bits)? as u8);
+            previous = 0;
+        } else if !matches.decode(&mut range) {
+            let offset = if !lz.decode(&mut range) {
+                let offset = read_offset(&mut lz_offsets, &mut bits)?;
+                recent_lz.copy_within(0..NUM_REPS, 1);
+                offset
+            } else {
+                let rep = rep_index(&mut lz_reps, &mut range);
+                let skip = previous & 1;
+                let offset = recent_lz[rep + skip];
+                recent_lz[rep + skip] = recent_lz[rep];
+                recent_lz.copy_within(0..rep, 1);
+                offset
+            };
+            recent_lz[0] = offset;
+            previous = 1;
+
+            let length = read_length(&mut bits)?;
+            let offset = offset as usize;
+            if offset > out.len() {
+                return Err(Error::CorruptData("LZMS 匹配偏移超出已解压的数据"));
+            }
+            if length > size - out.len() {
+                return Err(Error::CorruptData("LZMS 匹配超出分块"));
+            }
+            let start = out.len() - offset;
+            for i in 0..length {
+                let byte = out[start + i];
+                out.push(byte);
+            }
+        } else {
+            let pair = if !delta.decode(&mut range) {
+                let power = delta_powers.decode(&mut bits)? as u64;
+                let raw_offset = read_offset(&mut delta_offsets, &mut bits)?;
+                recent_delta.copy_within(0..NUM_REPS, 1);
+                (power << 32) | u64::from(raw_offset)
+            } else {
+                let rep = rep_index(&mut delta_reps, &mut range);
+                let skip = previous >> 1;
+                let pair = recent_delta[rep + skip];
+                recent_delta[rep + skip] = recent_delta[rep];
+                recent_delta.copy_within(0..rep, 1);
+                pair
+            };
+            recent_delta[0] = pair;
+            previous = 2;
+
+            let length = read_length(&mut bits)?;
+            // 差分匹配：按 span 的间隔取差值，offset 为 raw_offset 以 span 为单位
+            let power = (pair >> 32) as u32;
+            let span = 1usize << power;
+            let offset = ((pair as u32) as usize) << power;
+            if offset + span > out.len() {
+                return Err(Error::CorruptData("LZMS 匹配偏移超出已解压的数据"));
+            }
+            if length > size - out.len() {
+                return Err(Error::CorruptData("LZMS 匹配超出分块"));
+            }
+            for _ in 0..length {
+                let pos = out.len();
+                let byte = out[pos - offset]
+                    .wrapping_add(out[pos - span])
+                    .wrapping_sub(out[pos - offset - span]);
+                out.push(byte);
+            }
+        }
+    }
+
+    undo_x86_translation(&mut out);
+    Ok(out)
+}
+
+/// 依次解码重复匹配使用的最近偏移序号 (0..3)
+fn rep_index<const N: usize>(decisions: &mut [Decision<N>; 2], range: &mut RangeDecoder) -> usize {
+    if !decisions[0].decode(range) {
+        0
+    } else if !decisions[1].decode(range) {
+        1
+    } else {
+        2
+    }
+}
+
+/// 撤销 x86 相对地址转换
+///
+/// 压缩端在可能的相对寻址指令（CALL、JMP 之外的 RIP 相对加载等）后把 32 位相对地址加上指令位置。
+/// 只有最近出现过"可能的 x86 指令"（两次引用同一目标地址）时才转换，CALL 的有效范围减半。
+/// 压缩端扫描时把倒数第 16 个字节临时替换为 0xE8 作为哨兵，其后的字节不处理，解压端必须照做。
+fn undo_x86_translation(data: &mut [u8]) {
+    if data.len() <= 17 {
+        return;
+    }
+    let mut last_target_usages = vec![-X86_ID_WINDOW_SIZE - 1; 65536];
+    let mut last_x86_pos = -X86_MAX_TRANSLATION_OFFSET - 1;
+    let tail = data.len() - 16;
+    let saved = data[tail];
+    data[tail] = 0xE8;
+
+    let mut p = 0;
+    while p < tail {
+        let (opcode_len, max_offset) = match data[p] {
+            0x48 if (data[p + 1] == 0x8B && matches!(data[p + 2], 0x05 | 0x0D))
+                || (data[p + 1] == 0x8D && data[p + 2] & 7 == 5) =>
+            {
+                (3, X86_MAX_TRANSLATION_OFFSET)
+            }
+            0x4C if data[p + 1] == 0x8D && data[p + 2] & 7 == 5 => (3, X86_MAX_TRANSLATION_OFFSET),
+            0xE8 => (1, X86_MAX_TRANSLATION_OFFSET / 2),
+            0xE9 => {
+                p += 5;
+                continue;
+            }
+            0xF0 if data[p + 1] == 0x83 && data[p + 2] == 0x05 => (3, X86_MAX_TRANSLATION_OFFSET),
+            0xFF if data[p + 1] == 0x15 => (2, X86_MAX_TRANSLATION_OFFSET),
+            _ => {
+                p += 1;
+                continue;
+            }
+        };
+
+        let pos = p as i32;
+        p += opcode_len;
+        let operand = &mut data[p..p + 4];
+        if pos - last_x86_pos <= max_offset {
+            let value = u32::from_le_bytes([operand[0], operand[1], operand[2], operand[3]]);
+            operand.copy_from_slice(&value.wrapping_sub(pos as u32).to_le_bytes());
+        }
+        let target =
+            usize::from((pos as u16).wrapping_add(u16::from_le_bytes([operand[0], operand[1]])));
+
+        let end = pos + opcode_len as i32 + 3;
+        if end - last_target_usages[target] <= X86_ID_WINDOW_SIZE {
+            last_x86_pos = end;
+        }
+        last_target_usages[target] = end;
+        p += 4;
+    }
+    data[tail] = saved;
+}
@@ -0,0 +1,320 @@
+//! LZX 分块解压，用于 `/compress:max` 创建的 WIM
+//!
+//! WIM 使用 LZX 的一个变体：每个分块独立压缩（窗口为分块大小，默认 32 KiB，最近偏移每个分块重置），
+//! 默认大小 (32 KiB) 的块在块头中只用 1 位表示；解压后撤销 E8 (x86 CALL) 地址转换，
+//! 转换时假定的文件大小固定为 12000000。比特流以 16 位小端字为单位、从高位开始读取。
+
+use crate::huffman::HuffmanTable;
+use crate::Error;
+
+/// 字面量符号数
+const NUM_CHARS: usize = 256;
+/// 长度树的符号数
+const NUM_LEN_SYMBOLS: usize = 249;
+/// 预树（编码其他树的码长）的符号数
+const NUM_PRETREE_SYMBOLS: usize = 20;
+/// 对齐偏移树的符号数
+const NUM_ALIGNED_SYMBOLS: usize = 8;
+/// 对齐偏移占用的低位数
+const NUM_ALIGNED_BITS: u32 = 3;
+/// 主树和长度树码字的最大长度
+const MAX_CODEWORD_LEN: u32 = 16;
+/// 匹配头中长度部分的最大值，达到时从长度树读取剩余长度
+const NUM_PRIMARY_LENS: usize = 7;
+/// 最短匹配长度
+const MIN_MATCH_LEN: usize = 2;
+/// 偏移槽基址与实际偏移之差
+const OFFSET_ADJUSTMENT: usize = 2;
+/// 块头中只用 1 位表示的默认块大小
+const DEFAULT_BLOCK_SIZE: usize = 32768;
+/// 支持的最大窗口 (2 MiB)
+const MAX_WINDOW_ORDER: u32 = 21;
+/// E8 转换假定的文件大小
+const E8_FILE_SIZE: i32 = 12_000_000;
+
+const BLOCK_VERBATIM: u32 = 1;
+const BLOCK_ALIGNED: u32 = 2;
+const BLOCK_UNCOMPRESSED: u32 = 3;
+
+/// 比特流读取器：`bits` 的低 `count` 位为已读入、尚未消耗的位，按需补充 16 位字
+struct BitReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+    bits: u64,
+    count: u32,
+}
+
+impl<'a> BitReader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self {
+            data,
+            pos: 0,
+            bits: 0,
+            count: 0,
+        }
+    }
+
+    /// 保证至少有 `n` 位可读（超出输入时按 0 补齐，由解压长度决定何时结束）
+    fn ensure(&mut self, n: u32) {
+        while self.count < n {
+            let word = match self.data.get(self.pos..self.pos + 2) {
+                Some(bytes) => u64::from(u16::from_le_bytes([bytes[0], bytes[1]])),
+                None => 0,
+            };
+            self.pos += 2;
+            self.bits = (self.bits << 16) | word;
+            self.count += 16;
+        }
+    }
+
+    /// 查看接下来的 `n` 位（`n` ≤ 32）
+    fn peek(&mut self, n: u32) -> u32 {
+        self.ensure(n);
+        ((self.bits >> (self.count - n)) & ((1u64 << n) - 1)) as u32
+    }
+
+    /// 读取 `n` 位（`n` ≤ 32）
+    fn read(&mut self, n: u32) -> u32 {
+        let value = self.peek(n);
+        self.count -= n;
+        value
+    }
+
+    /// 按哈夫曼表解码一个符号
+    fn decode(&mut self, table: &HuffmanTable) -> Result<usize, Error> {
+        let (symbol, len) = table
+            .decode(self.peek(table.max_len()))
+            .ok_or(Error::CorruptData("LZX 哈夫曼码字无效"))?;
+        self.count -= len;
+        Ok(usize::from(symbol))
+    }
+
+    /// 对齐到 16 位字边界（已对齐时丢弃下一个字），之后按字节读取
+    fn align(&mut self) {
+        let whole_words = (self.count / 16) as usize;
+        self.pos -= whole_words * 2;
+        if self.count.is_multiple_of(16) {
+            self.pos += 2;
+        }
+        self.bits = 0;
+        self.count = 0;
+    }
+
+    /// 对齐后直接读取字节
+    fn read_bytes(&mut self, len: usize) -> Result<&'a [u8], Error> {
+        let bytes = self
+            .data
+            .get(self.pos..self.pos + len)
+            .ok_or(Error::Truncated {
+                expected: self.pos + len,
+                actual: self.data.len(),
+            })?;
+        self.pos += len;
+        Ok(bytes)
+    }
+}
+
+/// 偏移槽的附加位数
+fn extra_bits(slot: usize) -> u32 {
+    if slot < 4 {
+        0
+    } else {
+        ((slot as u32 - 2) / 2).min(17)
+    }
+}
+
+/// 各偏移槽的基址（含 [`OFFSET_ADJUSTMENT`]），最后一项为上界
+fn slot_bases(num_slots: usize) -> Vec<usize> {
+    let mut bases = Vec::with_capacity(num_slots + 1);
+    let mut base = 0;
+    for slot in 0..=num_slots {
+        bases.push(base);
+        base += 1usize << extra_bits(slot);
+    }
+    bases
+}
+
+/// 窗口所需的偏移槽数：最大匹配偏移为窗口大小减 3
+fn num_offset_slots(window_order: u32) -> usize {
+    let max_offset = (1usize << window_order) - MIN_MATCH_LEN - 1;
+    let mut slots = 30;
+    while slot_bases(slots)[slots] <= max_offset {
+        slots += 1;
+    }
+    slots
+}
+
+/// 读取预树，再用它解码一组相对上一块的差值编码的码长（原地更新 `lens`）
+fn read_code_lens(bits: &mut BitReader, lens: &mut [u8]) -> Result<(), Error> {
+    let pre_lens: Vec<u8> = (0..NUM_PRETREE_SYMBOLS)
+        .map(|_| bits.read(4) as u8)
+        .collect();
+    let pretree = HuffmanTable::new(&pre_lens, 15).ok_or(Error::CorruptData("LZX 预树码长无效"))?;
+    let delta = |previous: u8, presym: usize| ((17 + usize::from(previous) - presym) % 17) as u8;
+
+    let mut i = 0;
+    while i < lens.len() {
+        let presym = bits.decode(&pretree)?;
+        let (run, len) = match presym {
+            0..=16 => (1, delta(lens[i], presym)),
+            17 => (4 + bits.read(4) as usize, 0),
+            18 => (20 + bits.read(5) as usize, 0),
+            _ => {
+                let run = 4 + bits.read(1) as usize;
+                let presym = bits.decode(&pretree)?;
+                if presym > 16 {
+                    return Err(Error::CorruptData("LZX 码长游程无效"));
+                }
+                (run, delta(lens[i], presym))
+            }
+        };
+        let end = (i + run).min(lens.len());
+        lens[i..end].fill(len);
+        i = end;
+    }
+    Ok(())
+}
+
+/// 解压一个 LZX 分块，`size` 为分块的未压缩大小，`chunk_size` 为文件头中的分块大小（决定窗口）
+pub(crate) fn decompress(input: &[u8], size: usize, chunk_size: usize) -> Result<Vec<u8>, Error> {
+    let window_order = chunk_size
+        .max(size)
+        .max(DEFAULT_BLOCK_SIZE)
+        .next_power_of_two()
+        .trailing_zeros();
+    if window_order > MAX_WINDOW_ORDER {
+        return Err(Error::CorruptData("LZX 分块大小超过 2 MiB"));
+    }
+    let num_slots = num_offset_slots(window_order);
+    let bases = slot_bases(num_slots);
+    let num_main_symbols = NUM_CHARS + num_slots * 8;
+
+    let mut bits = BitReader::new(input);
+    let mut out = Vec::with_capacity(size);
+    let mut main_lens = vec![0u8; num_main_symbols];
+    let mut len_lens = vec![0u8; NUM_LEN_SYMBOLS];
+    let mut recent = [1usize; 3];
+
+    while out.len() < size {
+        let block_type = bits.read(3);
+        let block_size = if bits.read(1) == 1 {
+            DEFAULT_BLOCK_SIZE
+        } else {
+            let mut block_size = bits.read(16) as usize;
+            if window_order >= 16 {
+                block_size = (block_size << 8) | bits.read(8) as usize;
+            }
+            block_size
+        };
+        if block_size == 0 || block_size > size - out.len() {
+            return Err(Error::CorruptData("LZX 块大小超出分块"));
+        }
+        let block_end = out.len() + block_size;
+
+        if block_type == BLOCK_UNCOMPRESSED {
+            bits.align();
+            for offset in recent.iter_mut() {
+                let value = bits.read_bytes(4)?;
+                *offset = u32::from_le_bytes([value[0], value[1], value[2], value[3]]) as usize;
+            }
+            out.extend_from_slice(bits.read_bytes(block_size)?);
+            if !block_size.is_multiple_of(2) {
+                bits.pos += 1;
+            }
+            continue;
+        }
+        if block_type != BLOCK_VERBATIM && block_type != BLOCK_ALIGNED {
+            return Err(Error::CorruptData("LZX 块类型无效"));
+        }
+
+        let aligned = if block_type == BLOCK_ALIGNED {
+            let lens: Vec<u8> = (0..NUM_ALIGNED_SYMBOLS)
+                .map(|_| bits.read(3) as u8)
+                .collect();
+            Some(HuffmanTable::new(&lens, 7).ok_or(Error::CorruptData("LZX 对齐偏移树无效"))?)
+        } else {
+            None
+        };
+        read_code_lens(&mut bits, &mut main_lens[..NUM_CHARS])?;
+        read_code_lens(&mut bits, &mut main_lens[NUM_CHARS..])?;
+        let main = HuffmanTable::new(&main_lens, MAX_CODEWORD_LEN)
+            .ok_or(Error::CorruptData("LZX 主树码长无效"))?;
+        read_code_lens(&mut bits, &mut len_lens)?;
+        let lengths = HuffmanTable::new(&len_lens, MAX_CODEWORD_LEN)
+            .ok_or(Error::CorruptData("LZX 长度树码长无效"))?;
+
+        while out.len() < block_end {
+            let symbol = bits.decode(&main)?;
+            if symbol < NUM_CHARS {
+                out.push(symbol as u8);
+                continue;
+            }
+
+            let header = symbol - NUM_CHARS;
+            let slot = header >> 3;
+            let mut length = (header & 7) + MIN_MATCH_LEN;
+            if header & 7 == NUM_PRIMARY_LENS {
+                length += bits.decode(&lengths)?;
+            }
+
+            let offset = if slot < 3 {
+                recent.swap(0, slot);
+                recent[0]
+            } else {
+                let extra = extra_bits(slot);
+                let mut offset = bases[slot] - OFFSET_ADJUSTMENT;
+                match &aligned {
+                    Some(aligned) if extra >= NUM_ALIGNED_BITS => {
+                        offset +=
+                            (bits.read(extra - NUM_ALIGNED_BITS) as usize) << NUM_ALIGNED_BITS;
+                        offset += bits.decode(aligned)?;
+                    }
+                    _ => offset += bits.read(extra) as usize,
+                }
+                recent = [offset, recent[0], recent[1]];
+                offset
+            };
+
+            if offset == 0 || offset > out.len() {
+                return Err(Error::CorruptData("LZX 匹配偏移超出已解压的数据"));
+            }
+            if length > block_end - out.len() {
+                return Err(Error::CorruptData("LZX 匹配超出块大小"));
+            }
+            let start = out.len() - offset;
+            for i in 0..length {
+                let byte = out[start + i];
+                out.push(byte);
+            }
+        }
+    }
+
+    undo_e8_translation(&mut out);
+    Ok(out)
+}
+
+/// 将 E8 指令后的绝对地址还原为相对地址（最后 10 个字节不处理）
+fn undo_e8_translation(data: &mut [u8]) {
+    if data.len() <= 10 {
+        return;
+    }
+    let mut pos = 0;
+    while pos < data.len() - 10 {
+        if data[pos] != 0xE8 {
+            pos += 1;
+            continue;
+        }
+        let target = &mut data[pos + 1..pos + 5];
+        let absolute = i32::from_le_bytes([target[0], target[1], target[2], target[3]]);
+        let input_pos = pos as i32;
+        let relative = if absolute >= 0 {
+            (absolute < E8_FILE_SIZE).then(|| absolute - input_pos)
+        } else {
+            (absolute >= -input_pos).then(|| absolute + E8_FILE_SIZE)
+        };
+        if let Some(relative) = relative {
+            target.copy_from_slice(&relative.to_le_bytes());
+        }
+        pos += 5;
+    }
+}
@@ -0,0 +1,791 @@
+//! wim-parser 命令行工具
+//!
+//! `info --recursive <目录>` 递归盘点目录中的 WIM/ESD/SWM 文件和 ISO 中的 WIM，
+//! 单个文件解析失败记录在报告中，不影响退出码。`--output json` 时报告以单个 JSON 对象输出。
+//!
+//! `flags <文件> --set READONLY --clear WRITE_IN_PROGRESS` 原地修改文件头的状态标志，
+//! 不带选项时只显示当前标志；清除 `WRITE_IN_PROGRESS` 时同时重新计算完整性表。
+//!
+//! `completions bash|zsh|fish|powershell` 输出由命令和选项表生成的 shell 补全脚本。
+//!
+//! 默认选项可写在 `$XDG_CONFIG_HOME/wim-parser.toml`（默认 `~/.config/wim-parser.toml`）
+//! 或 `--config` 指定的文件中，例如 `threads = 4`、`output = "json"`、`error_format = "json"`、
+//! `cache_dir = "/var/tmp/wim"`（重写文件时的临时文件目录）；命令行选项优先。
+//! 配置文件有误时按用法错误退出。
+//!
+//! 退出码见 [`wim_parser::error::exit_codes`]；`--error-format json` 时错误以单行 JSON
+//! 输出到标准错误（见 [`ErrorReport::to_json`]）。
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use wim_parser::error::exit_codes;
+use wim_parser::fmt::{display_width, ToTable};
+use wim_parser::{
+    inventory, Error, ErrorReport, HeaderFlags, InventoryEntry, MediaInventory,
+    StreamVerifyOptions, TempPolicy, WimParser,
+};
+
+/// 命令名称和说明（帮助信息和补全脚本共用）
+const COMMANDS: [(&str, &str); 5] = [
+    ("info", "显示镜像列表"),
+    ("header", "显示文件头"),
+    ("verify", "校验所有数据流的 SHA-1"),
+    ("flags", "显示或修改文件头标志"),
+    ("completions", "输出 shell 补全脚本"),
+];
+
+/// 支持补全脚本的 shell
+const SHELLS: [&str; 4] = ["bash", "zsh", "fish", "powershell"];
+
+/// 输出格式选项的取值
+const FORMATS: &[&str] = &["text", "json"];
+
+/// 可修改的文件头标志
+const FLAG_NAMES: &[&str] = &["READONLY", "WRITE_IN_PROGRESS", "RP_FIX"];
+
+/// 命令行选项的定义（帮助信息和补全脚本共用）
+struct OptionSpec {
+    long: &'static str,
+    short: Option<char>,
+    /// 取值的占位符（不带取值的开关为 `None`）
+    value: Option<&'static str>,
+    /// 取值的候选项（为空且 `value` 为 `<文件>` 时补全文件名）
+    choices: &'static [&'static str],
+    /// 适用的命令（为空表示全局选项）
+    command: Option<&'static str>,
+    help: &'static str,
+}
+
+const OPTIONS: [OptionSpec; 7] = [
+    OptionSpec {
+        long: "error-format",
+        short: None,
+        value: Some("text|json"),
+        choices: FORMATS,
+        command: None,
+        help: "错误信息格式",
+    },
+    OptionSpec {
+        long: "config",
+        short: None,
+        value: Some("<文件>"),
+        choices: &[],
+        command: None,
+        help: "配置文件（默认 ~/.config/wim-parser.toml）",
+    },
+    OptionSpec {
+        long: "recursive",
+        short: Some('r'),
+        value: None,
+        choices: &[],
+        command: Some("info"),
+        help: "递归扫描目录中的 WIM/ESD/SWM 文件和 ISO",
+    },
+    OptionSpec {
+        long: "output",
+        short: None,
+        value: Some("text|json"),
+        choices: FORMATS,
+        command: Some("info"),
+        help: "输出格式",
+    },
+    OptionSpec {
+        long: "threads",
+        short: None,
+        value: Some("<数量>"),
+        choices: &[],
+        command: Some("verify"),
+        help: "校验线程数（0 表示使用全部处理器）",
+    },
+    OptionSpec {
+        long: "set",
+        short: None,
+        value: Some("<标志>"),
+        choices: FLAG_NAMES,
+        command: Some("flags"),
+        help: "设置标志（READONLY、WRITE_IN_PROGRESS、RP_FIX），可重复或以逗号分隔",
+    },
+    OptionSpec {
+        long: "clear",
+        short: None,
+        value: Some("<标志>"),
+        choices: FLAG_NAMES,
+        command: Some("flags"),
+        help: "清除标志",
+    },
+];
+
+/// 帮助信息
+fn usage() -> String {
+    let mut text = String::from(
+        "用法: wim-parser [--error-format text|json] [--config <文件>] <命令> [选项] <wim_file_path>\n\
+         \x20     wim-parser completions bash|zsh|fish|powershell\n\n命令:\n",
+    );
+    for (name, help) in COMMANDS {
+        text.push_str(&format!("  {name:<12} {help}\n"));
+    }
+    text.push_str("\n选项:");
+    for option in &OPTIONS {
+        let mut left = match option.short {
+            Some(short) => format!("-{short}, --{}", option.long),
+            None => format!("--{}", option.long),
+        };
+        if let Some(value) = option.value {
+            left.push(' ');
+            left.push_str(value);
+        }
+        // 按显示宽度对齐（占位符中的中文占两列）
+        let padding = " ".repeat(25usize.saturating_sub(display_width(&left)));
+        let scope = option.command.map(|command| format!("({command}) "));
+        text.push_str(&format!(
+            "\n  {left}{padding}{}{}",
+            scope.unwrap_or_default(),
+            option.help
+        ));
+    }
+    text.push_str(
+        "\n\n配置文件 (TOML) 可设置默认选项: threads、output、error_format、cache_dir（临时文件目录），\
+         命令行选项优先",
+    );
+    text
+}
+
+/// 输出格式（错误信息和报告）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum OutputFormat {
+    Text,
+    Json,
+}
+
+/// 解析后的命令行参数（未给出的选项由配置文件补充）
+struct Args {
+    error_format: Option<OutputFormat>,
+    /// 报告输出格式
+    output: Option<OutputFormat>,
+    /// 校验线程数
+    threads: Option<usize>,
+    /// 显式指定的配置文件
+    config: Option<PathBuf>,
+    /// 临时文件目录（仅来自配置文件）
+    cache_dir: Option<PathBuf>,
+    recursive: bool,
+    /// 要设置的文件头标志
+    set_flags: u32,
+    /// 要清除的文件头标志
+    clear_flags: u32,
+    command: String,
+    /// WIM 文件路径（`completions` 命令为 shell 名称）
+    path: String,
+}
+
+impl Args {
+    fn error_format(&self) -> OutputFormat {
+        self.error_format.unwrap_or(OutputFormat::Text)
+    }
+
+    fn output(&self) -> OutputFormat {
+        self.output.unwrap_or(OutputFormat::Text)
+    }
+
+    /// 用配置文件中的默认值补充命令行未给出的选项（只用于适用的命令）
+    fn apply_config(&mut self, config: Config) {
+        self.error_format = self.error_format.or(config.error_format);
+        if self.command == "info" {
+            self.output = self.output.or(config.output);
+        }
+        if self.command == "verify" {
+            self.threads = self.threads.or(config.threads);
+        }
+        self.cache_dir = config.cache_dir;
+    }
+}
+
+/// 解析输出格式选项的取值（`text` / `json`）
+fn parse_format(option: &str, value: Option<&str>) -> Result<OutputFormat, String> {
+    match value {
+        Some("text") => Ok(OutputFormat::Text),
+        Some("json") => Ok(OutputFormat::Json),
+        other => Err(format!("{option} 的取值无效: {}", other.unwrap_or(""))),
+    }
+}
+
+/// 解析线程数
+fn parse_threads(option: &str, value: Option<&str>) -> Result<usize, String> {
+    value
+        .and_then(|value| value.parse().ok())
+        .ok_or_else(|| format!("{option} 的取值无效: {}", value.unwrap_or("")))
+}
+
+/// 解析以逗号分隔的文件头标志名称
+fn parse_flags(option: &str, value: Option<&str>) -> Result<u32, String> {
+    let value = value.ok_or_else(|| format!("{option} 需要标志名称"))?;
+    value.split(',').try_fold(0, |bits, name| {
+        HeaderFlags::from_name(name)
+            .map(|bit| bits | bit)
+            .ok_or_else(|| format!("未知的文件头标志: {name}"))
+    })
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Args, String> {
+    let mut error_format = None;
+    let mut output = None;
+    let mut threads = None;
+    let mut config = None;
+    let mut recursive = false;
+    let mut set_flags = 0;
+    let mut clear_flags = 0;
+    let mut positional = Vec::new();
+    while let Some(arg) = args.next() {
+        let (option, value) = match arg.split_once('=') {
+            Some((option, value)) if option.starts_with("--") => {
+                (option.to_string(), Some(value.to_string()))
+            }
+            _ => (arg.clone(), None),
+        };
+        match option.as_str() {
+            "--error-format" => {
+                let value = value.or_else(|| args.next());
+                error_format = Some(parse_format("--error-format", value.as_deref())?);
+            }
+            "--output" => {
+                let value = value.or_else(|| args.next());
+                output = Some(parse_format("--output", value.as_deref())?);
+            }
+            "--threads" => {
+                let value = value.or_else(|| args.next());
+                threads = Some(parse_threads("--threads", value.as_deref())?);
+            }
+            "--config" => {
+                let value = value
+                    .or_else(|| args.next())
+                    .ok_or("--config 需要文件路径")?;
+                config = Some(PathBuf::from(value));
+            }
+            "--set" => {
+                let value = value.or_else(|| args.next());
+                set_flags |= parse_flags("--set", value.as_deref())?;
+            }
+            "--clear" => {
+                let value = value.or_else(|| args.next());
+                clear_flags |= parse_flags("--clear", value.as_deref())?;
+            }
+            "-r" | "--recursive" if value.is_none() => recursive = true,
+            _ if arg.starts_with('-') => return Err(format!("未知选项: {arg}")),
+            _ => positional.push(arg),
+        }
+    }
+
+    let [command, path]: [String; 2] = positional
+        .try_into()
+        .map_err(|_| "需要一个命令和一个 WIM 文件路径".to_string())?;
+    if command != "info" && (recursive || output == Some(OutputFormat::Json)) {
+        return Err(format!("{command} 命令不支持 --recursive 和 --output"));
+    }
+    if command != "verify" && threads.is_some() {
+        return Err(format!("{command} 命令不支持 --threads"));
+    }
+    if command != "flags" && (set_flags | clear_flags) != 0 {
+        return Err(format!("{command} 命令不支持 --set 和 --clear"));
+    }
+    Ok(Args {
+        error_format,
+        output,
+        threads,
+        config,
+        cache_dir: None,
+        recursive,
+        set_flags,
+        clear_flags,
+        command,
+        path,
+    })
+}
+
+/// 配置文件中的默认选项
+#[derive(Debug, Default)]
+struct Config {
+    threads: Option<usize>,
+    output: Option<OutputFormat>,
+    error_format: Option<OutputFormat>,
+    cache_dir: Option<PathBuf>,
+}
+
+/// 默认配置文件路径：`$XDG_CONFIG_HOME/wim-parser.toml`，其次 `~/.config/wim-parser.toml`
+fn default_config_path() -> Option<PathBuf> {
+    let dir = env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| Path::new(&home).join(".config")))?;
+    Some(dir.join("wim-parser.toml"))
+}
+
+/// 读取配置文件；未显式指定且默认路径不存在时使用默认值
+fn load_config(explicit: Option<&Path>) -> Result<Config, String> {
+    let path = match explicit {
+        Some(path) => path.to_path_buf(),
+        None => match default_config_path() {
+            Some(path) if path.exists() => path,
+            _ => return Ok(Config::default()),
+        },
+    };
+    let text = fs::read_to_string(&path)
+        .map_err(|err| format!("读取配置文件 {} 失败: {err}", path.display()))?;
+    parse_config(&text).map_err(|message| format!("配置文件 {}: {message}", path.display()))
+}
+
+/// 解析配置文件（TOML 的 `键 = 值` 子集：字符串、整数和 `#` 注释）
+fn parse_config(text: &str) -> Result<Config, String> {
+    let mut config = Config::default();
+    for (number, line) in text.lines().enumerate() {
+        let error = |message: String| format!("第 {} 行: {message}", number + 1);
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let (key, value) = line
+            .split_once('=')
+            .ok_or_else(|| error(format!("应为 `键 = 值`: {line}")))?;
+        let (key, value) = (key.trim(), parse_toml_value(value.trim()).map_err(error)?);
+        match (key, value) {
+            ("threads", TomlValue::Integer(threads)) => {
+                config.threads = Some(
+                    usize::try_from(threads)
+                        .map_err(|_| error(format!("threads 的取值无效: {threads}")))?,
+                );
+            }
+            ("output", TomlValue::String(format)) => {
+                config.output = Some(parse_format("output", Some(&format)).map_err(error)?);
+            }
+            ("error_format", TomlValue::String(format)) => {
+                config.error_format =
+                    Some(parse_format("error_format", Some(&format)).map_err(error)?);
+            }
+            ("cache_dir", TomlValue::String(dir)) => config.cache_dir = Some(PathBuf::from(dir)),
+            ("threads" | "output" | "error_format" | "cache_dir", _) => {
+                return Err(error(format!("{key} 的类型不正确")));
+            }
+            _ => return Err(error(format!("未知的配置项: {key}"))),
+        }
+    }
+    Ok(config)
+}
+
+/// 配置项的取值
+enum TomlValue {
+    String(String),
+    Integer(i64),
+}
+
+/// 解析单个取值：基本字符串（支持 `\"`、`\\`、`\n`、`\t` 转义）、字面量字符串或整数，其后可跟注释
+fn parse_toml_value(value: &str) -> Result<TomlValue, String> {
+    let rest;
+    let parsed = if let Some(literal) = value.strip_prefix('\'') {
+        let end = literal.find('\'').ok_or("字符串缺少结尾的 '")?;
+        rest = &literal[end + 1..];
+        TomlValue::String(literal[..end].to_string())
+    } else if let Some(basic) = value.strip_prefix('"') {
+        let mut string = String::new();
+        let mut chars = basic.char_indices();
+        let end = loop {
+            match chars.next().ok_or("字符串缺少结尾的 \"")? {
+                (end, '"') => break end,
+                (_, '\\') => string.push(match chars.next().map(|(_, c)| c) {
+                    Some('"') => '"',
+                    Some('\\') => '\\',
+                    Some('n') => '\n',
+                    Some('t') => '\t',
+                    other => return Err(format!("不支持的转义: \\{}", other.unwrap_or(' '))),
+                }),
+                (_, c) => string.push(c),
+            }
+        };
+        rest = &basic[end + 1..];
+        TomlValue::String(string)
+    } else {
+        let (number, comment) = value.split_once('#').unwrap_or((value, ""));
+        rest = if comment.is_empty() { "" } else { "#" };
+        let number = number.trim();
+        TomlValue::Integer(
+            number
+                .replace('_', "")
+                .parse()
+                .map_err(|_| format!("无法识别的取值: {number}"))?,
+        )
+    };
+    let rest = rest.trim_start();
+    if !rest.is_empty() && !rest.starts_with('#') {
+        return Err(format!("取值之后有多余的内容: {rest}"));
+    }
+    Ok(parsed)
+}
+
+/// 命令适用的选项（全局选项和该命令的选项）
+fn options_for(command: Option<&str>) -> impl Iterator<Item = &'static OptionSpec> + '_ {
+    OPTIONS
+        .iter()
+        .filter(move |option| option.command.is_none() || option.command == command)
+}
+
+/// 选项的全部写法（`--long` 和 `-s`）
+fn option_words(option: &OptionSpec) -> Vec<String> {
+    let mut words = vec![format!("--{}", option.long)];
+    words.extend(option.short.map(|short| format!("-{short}")));
+    words
+}
+
+/// 生成 shell 补全脚本
+fn completions(shell: &str) -> Option<String> {
+    let commands: Vec<&str> = COMMANDS.iter().map(|&(name, _)| name).collect();
+    let wim_commands = commands[..commands.len() - 1].join(" ");
+    let script = match shell {
+        "bash" => {
+            let mut values = String::new();
+            for option in OPTIONS.iter().filter(|option| option.value.is_some()) {
+                let reply = match option.choices {
+                    [] if option.value == Some("<文件>") => {
+                        "COMPREPLY=($(compgen -f -- \"$cur\"))".to_string()
+                    }
+                    [] => "COMPREPLY=()".to_string(),
+                    choices => format!(
+                        "COMPREPLY=($(compgen -W \"{}\" -- \"$cur\"))",
+                        choices.join(" ")
+                    ),
+                };
+                values.push_str(&format!("        --{}) {reply}; return ;;\n", option.long));
+            }
+            let mut command_options = String::new();
+            for command in &commands {
+                let words: Vec<String> =
+                    options_for(Some(command)).flat_map(option_words).collect();
+                command_options.push_str(&format!(
+                    "        {command}) options=\"{}\" ;;\n",
+                    words.join(" ")
+                ));
+            }
+            let global: Vec<String> = options_for(None).flat_map(option_words).collect();
+            format!(
+                r#"# wim-parser 的 bash 补全脚本
+_wim_parser() {{
+    local cur="${{COMP_WORDS[COMP_CWORD]}}" prev="${{COMP_WORDS[COMP_CWORD-1]}}"
+    local command="" options word i
+    case "$prev" in
+{values}    esac
+    for ((i = 1; i < COMP_CWORD; i++)); do
+        word="${{COMP_WORDS[i]}}"
+        case " {commands} " in
+            *" $word "*) command="$word"; break ;;
+        esac
+    done
+    case "$command" in
+{command_options}        *) options="{global}" ;;
+    esac
+    if [[ "$cur" == -* ]]; then
+        COMPREPLY=($(compgen -W "$options" -- "$cur"))
+    elif [[ -z "$command" ]]; then
+        COMPREPLY=($(compgen -W "{commands}" -- "$cur"))
+    elif [[ "$command" == completions ]]; then
+        COMPREPLY=($(compgen -W "{shells}" -- "$cur"))
+    else
+        COMPREPLY=($(compgen -f -- "$cur"))
+    fi
+}}
+complete -o filenames -F _wim_parser wim-parser
+"#,
+                commands = commands.join(" "),
+                global = global.join(" "),
+                shells = SHELLS.join(" "),
+            )
+        }
+        "zsh" => {
+            let mut specs = String::new();
+            for option in &OPTIONS {
+                let action = match (option.value, option.choices) {
+                    (None, _) => String::new(),
+                    (Some("<文件>"), _) => ":file:_files".to_string(),
+                    (Some(_), []) => ":value:".to_string(),
+                    (Some(_), choices) => format!(":value:({})", choices.join(" ")),
+                };
+                let repeat = if option.choices == FLAG_NAMES {
+                    "*"
+                } else {
+                    ""
+                };
+                let equals = if option.value.is_some() { "=" } else { "" };
+                for word in option_words(option) {
+                    let equals = if word.starts_with("--") { equals } else { "" };
+                    specs.push_str(&format!(
+                        "        '{repeat}{word}{equals}[{}]{action}' \\\n",
+                        option.help
+                    ));
+                }
+            }
+            let described: Vec<String> = COMMANDS
+                .iter()
+                .map(|(name, help)| format!("'{name}:{help}'"))
+                .collect();
+            format!(
+                r#"#compdef wim-parser
+# wim-parser 的 zsh 补全脚本
+_wim_parser() {{
+    local -a commands
+    local context state state_descr line
+    typeset -A opt_args
+    commands=({described})
+    _arguments -s \
+{specs}        '1: :->command' \
+        '2: :->target'
+    case $state in
+        command) _describe -t commands 'command' commands ;;
+        target)
+            if [[ ${{line[1]}} == completions ]]; then
+                _values 'shell' {shells}
+            else
+                _files
+            fi
+            ;;
+    esac
+}}
+if [[ "$funcstack[1]" == _wim_parser ]]; then
+    _wim_parser "$@"
+else
+    compdef _wim_parser wim-parser
+fi
+"#,
+                described = described.join(" "),
+                shells = SHELLS.join(" "),
+            )
+        }
+        "fish" => {
+            let mut lines = vec![
+                "# wim-parser 的 fish 补全脚本".to_string(),
+                "complete -c wim-parser -f".to_string(),
+            ];
+            for (name, help) in COMMANDS {
+                lines.push(format!(
+                    "complete -c wim-parser -n __fish_use_subcommand -a {name} -d '{help}'"
+                ));
+            }
+            lines.push(format!(
+                "complete -c wim-parser -n '__fish_seen_subcommand_from completions' -a '{}'",
+                SHELLS.join(" ")
+            ));
+            lines.push(format!(
+                "complete -c wim-parser -n '__fish_seen_subcommand_from {wim_commands}' -F"
+            ));
+            for option in &OPTIONS {
+                let mut line = String::from("complete -c wim-parser");
+                if let Some(command) = option.command {
+                    line.push_str(&format!(" -n '__fish_seen_subcommand_from {command}'"));
+                }
+                if let Some(short) = option.short {
+                    line.push_str(&format!(" -s {short}"));
+                }
+                line.push_str(&format!(" -l {}", option.long));
+                match (option.value, option.choices) {
+                    (None, _) => {}
+                    (Some("<文件>"), _) => line.push_str(" -r -F"),
+                    (Some(_), []) => line.push_str(" -x"),
+                    (Some(_), choices) => {
+                        line.push_str(&format!(" -x -a '{}'", choices.join(" ")));
+                    }
+                }
+                line.push_str(&format!(" -d '{}'", option.help));
+                lines.push(line);
+            }
+            lines.join("\n") + "\n"
+        }
+        "powershell" => {
+            let command_table: Vec<String> = COMMANDS
+                .iter()
+                .map(|(name, help)| format!("'{name}' = '{help}'"))
+                .collect();
+            let mut values = String::new();
+            for option in OPTIONS.iter().filter(|option| !option.choices.is_empty()) {
+                let choices: Vec<String> = option
+                    .choices
+                    .iter()
+                    .map(|choice| format!("'{choice}'"))
+                    .collect();
+                values.push_str(&format!(
+                    "        '--{}' {{ @({}) }}\n",
+                    option.long,
+                    choices.join(", ")
+                ));
+            }
+            let mut command_options = String::new();
+            for command in &commands {
+                let words: Vec<String> = options_for(Some(command))
+                    .flat_map(option_words)
+                    .map(|word| format!("'{word}'"))
+                    .collect();
+                command_options
+                    .push_str(&format!("        '{command}' = @({})\n", words.join(", ")));
+            }
+            let global: Vec<String> = options_for(None)
+                .flat_map(option_words)
+                .map(|word| format!("'{word}'"))
+                .collect();
+            let shells: Vec<String> = SHELLS.iter().map(|shell| format!("'{shell}'")).collect();
+            format!(
+                r#"# wim-parser 的 PowerShell 补全脚本
+Register-ArgumentCompleter -Native -CommandName 'wim-parser' -ScriptBlock {{
+    param($wordToComplete, $commandAst, $cursorPosition)
+    $commands = [ordered]@{{ {command_table} }}
+    $commandOptions = @{{
+{command_options}    }}
+    $words = @($commandAst.CommandElements | Select-Object -Skip 1 | ForEach-Object {{ $_.ToString() }})
+    if ($wordToComplete) {{ $words = @($words | Select-Object -SkipLast 1) }}
+    $previous = if ($words.Count -gt 0) {{ $words[-1] }} else {{ '' }}
+    $command = $words | Where-Object {{ $commands.Contains($_) }} | Select-Object -First 1
+    $candidates = switch ($previous) {{
+{values}        default {{
+            if ($wordToComplete -like '-*') {{
+                if ($command) {{ $commandOptions[$command] }} else {{ @({global}) }}
+            }} elseif (-not $command) {{
+                $commands.Keys
+            }} elseif ($command -eq 'completions') {{
+                @({shells})
+            }}
+        }}
+    }}
+    $candidates | Where-Object {{ $_ -like "$wordToComplete*" }} | ForEach-Object {{
+        $tooltip = if ($commands.Contains($_)) {{ $commands[$_] }} else {{ $_ }}
+        [System.Management.Automation.CompletionResult]::new($_, $_, 'ParameterValue', $tooltip)
+    }}
+}}
+"#,
+                command_table = command_table.join("; "),
+                global = global.join(", "),
+                shells = shells.join(", "),
+            )
+        }
+        _ => return None,
+    };
+    Some(script)
+}
+
+/// 输出盘点报告
+fn print_inventory(report: &MediaInventory, output: OutputFormat) {
+    match output {
+        OutputFormat::Text => println!("{}", report.table()),
+        OutputFormat::Json => println!("{}", report.to_json()),
+    }
+}
+
+fn run(args: &Args) -> anyhow::Result<()> {
+    if args.recursive {
+        print_inventory(&inventory(&args.path)?, args.output());
+        return Ok(());
+    }
+
+    let mut parser = WimParser::new(&args.path)?;
+    if let Some(dir) = &args.cache_dir {
+        parser.set_temp_policy(TempPolicy::new().dir(dir));
+    }
+    match args.command.as_str() {
+        "info" => {
+            parser.parse_full()?;
+            match args.output() {
+                OutputFormat::Text => println!("{}", parser.get_images().table()),
+                OutputFormat::Json => {
+                    let report = MediaInventory {
+                        root: Path::new(&args.path).to_path_buf(),
+                        files_scanned: 1,
+                        entries: vec![InventoryEntry {
+                            path: Path::new(&args.path).to_path_buf(),
+                            offset: None,
+                            images: parser.get_images().to_vec(),
+                            error: None,
+                        }],
+                    };
+                    print_inventory(&report, args.output());
+                }
+            }
+        }
+        "header" => println!("{}", parser.read_header()?),
+        "verify" => {
+            let verification = match args.threads {
+                Some(threads) => {
+                    parser.verify_all_streams_with(&StreamVerifyOptions::new().threads(threads))?
+                }
+                None => parser.verify_all_streams()?,
+            };
+            println!("{}", verification.table());
+            let failures = verification.failures().count();
+            if failures > 0 {
+                return Err(Error::VerificationFailed { failures }.into());
+            }
+        }
+        "flags" if (args.set_flags | args.clear_flags) == 0 => {
+            println!("{}", parser.read_header()?.flags());
+        }
+        "flags" => {
+            let edit = parser.edit_header_flags(args.set_flags, args.clear_flags)?;
+            if edit.changed {
+                println!("标志: {} -> {}", edit.before, edit.after);
+            } else {
+                println!("标志未变化: {}", edit.after);
+            }
+            if edit.integrity_refreshed {
+                println!("已重新计算完整性表");
+            }
+        }
+        _ => unreachable!("命令已在解析参数时检查"),
+    }
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    let mut args = match parse_args(env::args().skip(1)) {
+        Ok(args) if args.command == "completions" => {
+            return match completions(&args.path) {
+                Some(script) => {
+                    print!("{script}");
+                    ExitCode::from(exit_codes::OK)
+                }
+                None => {
+                    eprintln!("不支持的 shell: {}\n\n{}", args.path, usage());
+                    ExitCode::from(exit_codes::USAGE)
+                }
+            };
+        }
+        Ok(args)
+            if matches!(
+                args.command.as_str(),
+                "info" | "header" | "verify" | "flags"
+            ) =>
+        {
+            args
+        }
+        Ok(args) => {
+            eprintln!("未知命令: {}\n\n{}", args.command, usage());
+            return ExitCode::from(exit_codes::USAGE);
+        }
+        Err(message) => {
+            eprintln!("{message}\n\n{}", usage());
+            return ExitCode::from(exit_codes::USAGE);
+        }
+    };
+    match load_config(args.config.as_deref()) {
+        Ok(config) => args.apply_config(config),
+        Err(message) => {
+            eprintln!("{message}");
+            return ExitCode::from(exit_codes::USAGE);
+        }
+    }
+
+    match run(&args) {
+        Ok(()) => ExitCode::from(exit_codes::OK),
+        Err(err) => {
+            let report = ErrorReport::from_anyhow(&err);
+            match args.error_format() {
+                OutputFormat::Text => eprintln!("错误: {err:#}"),
+                OutputFormat::Json => eprintln!("{}", report.to_json()),
+            }
+            ExitCode::from(report.exit_code)
+        }
+    }
+}
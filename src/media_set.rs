@@ -0,0 +1,237 @@
+//! boot.wim 与 install.wim 配对检查：在制作启动 U 盘之前发现混用的安装介质
+
+use anyhow::{Context, Result};
+use std::path::Path;
+
+use crate::log::debug;
+use crate::winpe::SETUP_EXE;
+use crate::{Arch, WimParser};
+
+/// 安装介质配对检查发现的问题
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MediaSetIssue {
+    /// boot.wim 中没有可检查的安装程序镜像
+    NoSetupImage,
+    /// boot.wim 的安装程序镜像中没有 setup.exe
+    MissingSetup {
+        /// 安装程序镜像索引
+        index: u32,
+    },
+    /// install.wim 中存在 boot.wim 无法安装的架构
+    ArchMismatch {
+        /// boot.wim 的架构
+        boot: Vec<Arch>,
+        /// install.wim 中 boot.wim 不支持的架构
+        install: Vec<Arch>,
+    },
+    /// 安装程序镜像与 install.wim 镜像的内部版本号不一致
+    BuildMismatch {
+        /// 安装程序镜像的内部版本号
+        boot: u32,
+        /// install.wim 镜像的内部版本号
+        install: u32,
+    },
+    /// 安装程序镜像与 install.wim 没有共同的语言
+    NoCommonLanguage {
+        /// 安装程序镜像的语言
+        boot: Vec<String>,
+        /// install.wim 的语言
+        install: Vec<String>,
+    },
+}
+
+/// 以逗号分隔显示列表
+fn join<T: std::fmt::Display>(items: &[T]) -> String {
+    items
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+impl std::fmt::Display for MediaSetIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MediaSetIssue::NoSetupImage => f.write_str("boot.wim 中没有安装程序镜像"),
+            MediaSetIssue::MissingSetup { index } => {
+                write!(f, "boot.wim 镜像 {index} 中缺少 setup.exe")
+            }
+            MediaSetIssue::ArchMismatch { boot, install } => write!(
+                f,
+                "install.wim 架构 {} 与 boot.wim 架构 {} 不一致",
+                join(install),
+                join(boot)
+            ),
+            MediaSetIssue::BuildMismatch { boot, install } => {
+                write!(
+                    f,
+                    "install.wim 内部版本 {install} 与 boot.wim 内部版本 {boot} 不一致"
+                )
+            }
+            MediaSetIssue::NoCommonLanguage { boot, install } => write!(
+                f,
+                "install.wim 语言 {} 与 boot.wim 语言 {} 没有交集",
+                join(install),
+                join(boot)
+            ),
+        }
+    }
+}
+
+/// boot.wim 与 install.wim 配对检查结果
+#[derive(Debug, Clone)]
+pub struct MediaSetValidation {
+    /// 检查的 boot.wim 安装程序镜像索引（可引导镜像，未设置时为最后一个镜像）
+    pub setup_image: Option<u32>,
+    /// 安装程序镜像中是否包含 setup.exe
+    pub has_setup: bool,
+    /// boot.wim 所有镜像的架构
+    pub boot_architectures: Vec<Arch>,
+    /// install.wim 所有镜像的架构
+    pub install_architectures: Vec<Arch>,
+    /// 安装程序镜像的内部版本号
+    pub boot_build: Option<u32>,
+    /// install.wim 镜像的内部版本号（去重，按出现顺序）
+    pub install_builds: Vec<u32>,
+    /// 安装程序镜像的语言
+    pub boot_languages: Vec<String>,
+    /// install.wim 所有镜像的语言（去重，按出现顺序）
+    pub install_languages: Vec<String>,
+    /// 发现的问题
+    pub issues: Vec<MediaSetIssue>,
+}
+
+impl MediaSetValidation {
+    /// 是否没有发现问题
+    pub fn is_valid(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+/// 去重并保持出现顺序
+fn dedup<T: PartialEq>(items: impl IntoIterator<Item = T>) -> Vec<T> {
+    let mut unique = Vec::new();
+    for item in items {
+        if !unique.contains(&item) {
+            unique.push(item);
+        }
+    }
+    unique
+}
+
+impl WimParser {
+    /// 检查当前 boot.wim 与 `install` 是否属于同一套安装介质
+    ///
+    /// 比较两者的架构、安装程序镜像与 install.wim 镜像的内部版本号和语言，并检查安装程序镜像
+    /// （可引导镜像，未设置时为最后一个镜像）中是否包含 setup.exe。XML 中没有记录的字段不参与比较。
+    pub fn validate_media_set(&mut self, install: &mut WimParser) -> Result<MediaSetValidation> {
+        if self.images.is_empty() {
+            self.parse_full()?;
+        }
+        if install.images.is_empty() {
+            install.parse_full()?;
+        }
+
+        let header = self.read_header()?;
+        let setup_image = match header.bootable_image_index {
+            0 if header.image_count == 0 => None,
+            0 => Some(header.image_count),
+            index => Some(index),
+        };
+        let setup_info = setup_image.and_then(|index| self.get_image(index));
+        let boot_build = setup_info.and_then(|image| image.build);
+        let boot_languages = setup_info
+            .map(|image| image.languages.clone())
+            .unwrap_or_default();
+        let boot_architectures = self.architectures();
+        let install_architectures = install.architectures();
+        let install_builds = dedup(install.images.iter().filter_map(|image| image.build));
+        let install_languages = dedup(
+            install
+                .images
+                .iter()
+                .flat_map(|image| image.languages.iter().cloned()),
+        );
+
+        let mut issues = Vec::new();
+        let has_setup = match setup_image {
+            Some(index) => {
+                let root = self.read_metadata_root(index)?;
+                let has_setup = SETUP_EXE.iter().any(|path| {
+                    root.find_path(path)
+                        .is_some_and(|entry| !entry.is_directory())
+                });
+                if !has_setup {
+                    issues.push(MediaSetIssue::MissingSetup { index });
+                }
+                has_setup
+            }
+            None => {
+                issues.push(MediaSetIssue::NoSetupImage);
+                false
+            }
+        };
+
+        let unsupported: Vec<Arch> = install_architectures
+            .iter()
+            .copied()
+            .filter(|arch| !boot_architectures.contains(arch))
+            .collect();
+        if !boot_architectures.is_empty() && !unsupported.is_empty() {
+            issues.push(MediaSetIssue::ArchMismatch {
+                boot: boot_architectures.clone(),
+                install: unsupported,
+            });
+        }
+
+        if let Some(boot) = boot_build {
+            issues.extend(
+                install_builds
+                    .iter()
+                    .filter(|&&install| install != boot)
+                    .map(|&install| MediaSetIssue::BuildMismatch { boot, install }),
+            );
+        }
+
+        let overlaps = install_languages.iter().any(|install| {
+            boot_languages
+                .iter()
+                .any(|boot| boot.eq_ignore_ascii_case(install))
+        });
+        if !boot_languages.is_empty() && !install_languages.is_empty() && !overlaps {
+            issues.push(MediaSetIssue::NoCommonLanguage {
+                boot: boot_languages.clone(),
+                install: install_languages.clone(),
+            });
+        }
+
+        debug!(
+            "安装介质配对检查 - 安装程序镜像: {:?}, 问题: {}",
+            setup_image,
+            issues.len()
+        );
+        Ok(MediaSetValidation {
+            setup_image,
+            has_setup,
+            boot_architectures,
+            install_architectures,
+            boot_build,
+            install_builds,
+            boot_languages,
+            install_languages,
+            issues,
+        })
+    }
+}
+
+/// 检查 boot.wim 与 install.wim（或 install.esd）是否属于同一套安装介质
+pub fn validate_media_set(
+    boot_wim: impl AsRef<Path>,
+    install_wim: impl AsRef<Path>,
+) -> Result<MediaSetValidation> {
+    let mut boot = WimParser::new(boot_wim.as_ref())
+        .with_context(|| format!("无法打开 {}", boot_wim.as_ref().display()))?;
+    let mut install = WimParser::new(install_wim.as_ref())
+        .with_context(|| format!("无法打开 {}", install_wim.as_ref().display()))?;
+    boot.validate_media_set(&mut install)
+}
@@ -0,0 +1,528 @@
+use anyhow::{Context, Result};
+use std::borrow::Cow;
+use std::collections::HashSet;
+
+use crate::names::{self, NameMatching};
+use crate::{ResourceLimits, WimTimestamp};
+
+/// 只读属性 (FILE_ATTRIBUTE_READONLY)
+pub(crate) const FILE_ATTRIBUTE_READONLY: u32 = 0x0000_0001;
+
+/// 隐藏属性 (FILE_ATTRIBUTE_HIDDEN)
+pub(crate) const FILE_ATTRIBUTE_HIDDEN: u32 = 0x0000_0002;
+
+/// 系统属性 (FILE_ATTRIBUTE_SYSTEM)
+pub(crate) const FILE_ATTRIBUTE_SYSTEM: u32 = 0x0000_0004;
+
+/// 目录属性 (FILE_ATTRIBUTE_DIRECTORY)
+pub(crate) const FILE_ATTRIBUTE_DIRECTORY: u32 = 0x0000_0010;
+
+/// 重解析点属性 (FILE_ATTRIBUTE_REPARSE_POINT)
+pub(crate) const FILE_ATTRIBUTE_REPARSE_POINT: u32 = 0x0000_0400;
+
+pub(crate) use crate::reparse::{IO_REPARSE_TAG_MOUNT_POINT, IO_REPARSE_TAG_SYMLINK};
+
+/// 重解析点未按 RP_FIX 修正（链接目标在捕获根目录之外）(WIM_RP_FLAG_NOT_FIXED)
+pub(crate) const WIM_RP_FLAG_NOT_FIXED: u16 = 0x0001;
+
+/// 目录项固定部分大小 (到文件名之前)
+const DENTRY_FIXED_SIZE: usize = 0x66;
+
+/// 附加数据流条目固定部分大小 (到流名称之前)
+const STREAM_ENTRY_FIXED_SIZE: usize = 0x26;
+
+/// 目录嵌套深度上限，防止损坏的元数据导致无限递归
+pub(crate) const MAX_DIRECTORY_DEPTH: usize = 1024;
+
+/// 附加数据流条目 (_WIM_STREAM_ENTRY)
+#[derive(Debug, Clone)]
+#[allow(dead_code)]
+pub(crate) struct StreamEntry {
+    /// 流名称（空字符串表示未命名流）
+    pub name: String,
+    /// 流内容的 SHA-1（全零表示空流）
+    pub hash: [u8; 20],
+}
+
+/// 目录项 (_DIRENTRY)
+#[derive(Debug, Clone)]
+#[allow(dead_code)]
+pub(crate) struct DirEntry {
+    /// 文件属性
+    pub attributes: u32,
+    /// 安全描述符索引（-1 表示无）
+    pub security_id: i32,
+    /// 创建时间
+    pub creation_time: WimTimestamp,
+    /// 最后访问时间
+    pub last_access_time: WimTimestamp,
+    /// 最后写入时间
+    pub last_write_time: WimTimestamp,
+    /// 未命名数据流的 SHA-1（全零表示空文件）
+    pub hash: [u8; 20],
+    /// 重解析点标记（仅对重解析点有效）
+    pub reparse_tag: u32,
+    /// 重解析点标志，如 [`WIM_RP_FLAG_NOT_FIXED`]（仅对重解析点有效）
+    pub rp_flags: u16,
+    /// 硬链接组 ID（仅对非重解析点有效）
+    pub hard_link_group_id: u64,
+    /// 长文件名（不成对的代理项转义为 `%uXXXX`，见 [`names::decode_name`]）
+    pub name: String,
+    /// 原始 UTF-16 长文件名（仅当其不是有效的 UTF-16、`name` 为转义形式时保留）
+    pub name_utf16: Option<Vec<u16>>,
+    /// 短文件名 (8.3)
+    pub short_name: String,
+    /// 附加数据流
+    pub streams: Vec<StreamEntry>,
+    /// 子目录项
+    pub children: Vec<DirEntry>,
+}
+
+impl DirEntry {
+    /// 是否为目录
+    pub fn is_directory(&self) -> bool {
+        self.attributes & FILE_ATTRIBUTE_DIRECTORY != 0
+    }
+
+    /// 是否为重解析点（符号链接、目录联接等）
+    pub fn is_reparse_point(&self) -> bool {
+        self.attributes & FILE_ATTRIBUTE_REPARSE_POINT != 0
+    }
+
+    /// 原始 UTF-16 长文件名
+    pub fn raw_name(&self) -> Cow<'_, [u16]> {
+        names::raw_units(&self.name, self.name_utf16.as_deref())
+    }
+
+    /// 按路径查找子孙目录项（`\\` 或 `/` 分隔，按默认的 [`NameMatching`] 比较，空路径返回自身）
+    pub fn find_path(&self, path: &str) -> Option<&DirEntry> {
+        let matching = NameMatching::default();
+        path.split(['\\', '/'])
+            .filter(|part| !part.is_empty())
+            .try_fold(self, |dir, part| {
+                matching.find(&dir.children, part, |child| &child.name)
+            })
+    }
+
+    /// 遍历该目录项及其所有子孙（先序）
+    pub fn walk<F: FnMut(&DirEntry)>(&self, f: &mut F) {
+        f(self);
+        for child in &self.children {
+            child.walk(f);
+        }
+    }
+
+    /// 遍历所有子孙目录项（先序，不含自身），同时提供以 `/` 分隔的相对路径
+    pub fn walk_with_path<'a, F: FnMut(&str, &'a DirEntry)>(&'a self, f: &mut F) {
+        let mut path = String::new();
+        self.walk_children_with_path(&mut path, f);
+    }
+
+    fn walk_children_with_path<'a, F: FnMut(&str, &'a DirEntry)>(
+        &'a self,
+        path: &mut String,
+        f: &mut F,
+    ) {
+        for child in &self.children {
+            let parent_len = path.len();
+            if !path.is_empty() {
+                path.push('/');
+            }
+            path.push_str(&child.name);
+            f(path, child);
+            child.walk_children_with_path(path, f);
+            path.truncate(parent_len);
+        }
+    }
+}
+
+/// 8 字节对齐
+fn align8(value: u64) -> u64 {
+    (value + 7) & !7
+}
+
+/// 读取指定偏移处的 little-endian 数值，越界时返回错误
+fn read_bytes<const N: usize>(data: &[u8], offset: usize) -> Result<[u8; N]> {
+    data.get(offset..offset + N)
+        .map(|bytes| bytes.try_into().unwrap())
+        .ok_or_else(|| anyhow::anyhow!("元数据资源在偏移 {} 处被截断", offset))
+}
+
+fn read_u16(data: &[u8], offset: usize) -> Result<u16> {
+    Ok(u16::from_le_bytes(read_bytes(data, offset)?))
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Result<u32> {
+    Ok(u32::from_le_bytes(read_bytes(data, offset)?))
+}
+
+fn read_u64(data: &[u8], offset: usize) -> Result<u64> {
+    Ok(u64::from_le_bytes(read_bytes(data, offset)?))
+}
+
+/// 读取 UTF-16 LE 名称
+fn read_utf16_name(data: &[u8], offset: usize, nbytes: usize) -> Result<Vec<u16>> {
+    let bytes = data
+        .get(offset..offset + nbytes)
+        .ok_or_else(|| anyhow::anyhow!("元数据资源在偏移 {} 处名称被截断", offset))?;
+    let units: Vec<u16> = bytes
+        .chunks_exact(2)
+        .map(|c| u16::from_le_bytes([c[0], c[1]]))
+        .collect();
+    Ok(units)
+}
+
+/// 解析元数据资源开头的安全数据块，返回各安全描述符（按目录项的安全描述符索引排列）
+pub(crate) fn parse_security_descriptors(data: &[u8]) -> Result<Vec<&[u8]>> {
+    let total_length = read_u32(data, 0).context("读取安全数据块失败")? as usize;
+    if total_length <= 8 {
+        return Ok(Vec::new());
+    }
+    let num_entries = read_u32(data, 4)? as usize;
+    let mut offset = 8usize
+        .checked_add(num_entries.saturating_mul(8))
+        .filter(|&end| end <= total_length)
+        .ok_or_else(|| anyhow::anyhow!("安全数据块的描述符数量无效: {}", num_entries))?;
+
+    let mut descriptors = Vec::with_capacity(num_entries);
+    for i in 0..num_entries {
+        let size = read_u64(data, 8 + i * 8)? as usize;
+        let descriptor = offset
+            .checked_add(size)
+            .filter(|&end| end <= total_length)
+            .and_then(|end| data.get(offset..end))
+            .ok_or_else(|| anyhow::anyhow!("安全描述符 {} 超出安全数据块", i))?;
+        descriptors.push(descriptor);
+        offset += size;
+    }
+    Ok(descriptors)
+}
+
+/// 解析镜像元数据资源，返回根目录项；目录项数量超过 [`ResourceLimits::max_dentries`] 时返回错误
+pub(crate) fn parse_metadata_resource(data: &[u8], limits: &ResourceLimits) -> Result<DirEntry> {
+    // 安全数据块：总长度 (4 字节) + 条目数 (4 字节) + 各描述符大小 + 描述符数据
+    let security_total_length = read_u32(data, 0).context("读取安全数据块失败")?;
+
+    // 总长度为 0 时按 8 字节处理（仅包含头部）
+    let root_offset = align8(u64::from(security_total_length.max(8)));
+
+    let mut parser = DentryParser {
+        data,
+        visited: HashSet::new(),
+        limits,
+        dentries: 1,
+    };
+
+    let mut root = parser
+        .parse_dentry(root_offset)?
+        .ok_or_else(|| anyhow::anyhow!("元数据资源中没有根目录项"))?;
+    parser.load_children(&mut root, 0)?;
+
+    Ok(root.entry)
+}
+
+/// 解析根目录项（不加载子目录项），返回根目录项及其子目录项列表的偏移
+pub(crate) fn parse_root_dentry(data: &[u8], limits: &ResourceLimits) -> Result<(DirEntry, u64)> {
+    let security_total_length = read_u32(data, 0).context("读取安全数据块失败")?;
+    let root_offset = align8(u64::from(security_total_length.max(8)));
+    let parser = DentryParser {
+        data,
+        visited: HashSet::new(),
+        limits,
+        dentries: 1,
+    };
+    let root = parser
+        .parse_dentry(root_offset)?
+        .ok_or_else(|| anyhow::anyhow!("元数据资源中没有根目录项"))?;
+    Ok((root.entry, root.subdir_offset))
+}
+
+/// 解析一个目录的子目录项列表（不递归），返回各目录项及其子目录项列表的偏移
+///
+/// `dentries` 为此前已加载的目录项数量，加上本列表后超过
+/// [`ResourceLimits::max_dentries`] 时返回错误。
+pub(crate) fn parse_dentry_list(
+    data: &[u8],
+    offset: u64,
+    limits: &ResourceLimits,
+    dentries: usize,
+) -> Result<Vec<(DirEntry, u64)>> {
+    let mut parser = DentryParser {
+        data,
+        visited: HashSet::new(),
+        limits,
+        dentries,
+    };
+    let mut entries = Vec::new();
+    let mut offset = offset;
+    while let Some(child) = parser.parse_dentry(offset)? {
+        parser.dentries += 1;
+        limits.check_dentries(parser.dentries)?;
+        offset = child.next_offset;
+        entries.push((child.entry, child.subdir_offset));
+    }
+    Ok(entries)
+}
+
+/// 解析后的目录项及其在元数据资源中的链接信息
+struct ParsedDentry {
+    entry: DirEntry,
+    /// 子目录项列表的偏移（0 表示无）
+    subdir_offset: u64,
+    /// 下一个兄弟目录项的偏移
+    next_offset: u64,
+}
+
+struct DentryParser<'a> {
+    data: &'a [u8],
+    /// 已访问过的子目录偏移，防止循环引用
+    visited: HashSet<u64>,
+    limits: &'a ResourceLimits,
+    /// 已解析的目录项数量（含根目录）
+    dentries: usize,
+}
+
+impl DentryParser<'_> {
+    /// 解析指定偏移处的单个目录项；长度为 0 表示目录结束
+    fn parse_dentry(&self, offset: u64) -> Result<Option<ParsedDentry>> {
+        let data = self.data;
+        let base = usize::try_from(offset).context("目录项偏移超出范围")?;
+
+        let length = read_u64(data, base)?;
+        if length == 0 {
+            return Ok(None);
+        }
+        if length < DENTRY_FIXED_SIZE as u64 {
+            return Err(anyhow::anyhow!(
+                "偏移 {} 处目录项长度无效: {}",
+                offset,
+                length
+            ));
+        }
+
+        let attributes = read_u32(data, base + 0x08)?;
+        let security_id = read_u32(data, base + 0x0C)? as i32;
+        let subdir_offset = read_u64(data, base + 0x10)?;
+        let creation_time = WimTimestamp::from_filetime(read_u64(data, base + 0x28)?);
+        let last_access_time = WimTimestamp::from_filetime(read_u64(data, base + 0x30)?);
+        let last_write_time = WimTimestamp::from_filetime(read_u64(data, base + 0x38)?);
+        let hash: [u8; 20] = read_bytes(data, base + 0x40)?;
+        let reparse_tag = read_u32(data, base + 0x58)?;
+        let rp_flags = read_u16(data, base + 0x5E)?;
+        let hard_link_group_id = read_u64(data, base + 0x58)?;
+        let num_streams = read_u16(data, base + 0x60)?;
+        let short_name_nbytes = usize::from(read_u16(data, base + 0x62)?);
+        let name_nbytes = usize::from(read_u16(data, base + 0x64)?);
+
+        let name_units = read_utf16_name(data, base + DENTRY_FIXED_SIZE, name_nbytes)?;
+        let name = names::decode_name(&name_units);
+        let name_utf16 = names::invalid_units(&name_units);
+        let short_name = if short_name_nbytes > 0 {
+            // 名称为空时没有空终止符
+            let name_len = if name_nbytes == 0 { 0 } else { name_nbytes + 2 };
+            let short_name_offset = base + DENTRY_FIXED_SIZE + name_len;
+            names::decode_name(&read_utf16_name(
+                data,
+                short_name_offset,
+                short_name_nbytes,
+            )?)
+        } else {
+            String::new()
+        };
+
+        // 附加数据流紧跟在（8 字节对齐的）目录项之后
+        let mut next_offset = offset + align8(length);
+        let mut streams = Vec::with_capacity(usize::from(num_streams));
+        for _ in 0..num_streams {
+            let stream_base = usize::try_from(next_offset).context("数据流偏移超出范围")?;
+            let stream_length = read_u64(data, stream_base)?;
+            if stream_length < STREAM_ENTRY_FIXED_SIZE as u64 {
+                return Err(anyhow::anyhow!(
+                    "偏移 {} 处数据流条目长度无效: {}",
+                    next_offset,
+                    stream_length
+                ));
+            }
+            let stream_hash: [u8; 20] = read_bytes(data, stream_base + 0x10)?;
+            let stream_name_nbytes = usize::from(read_u16(data, stream_base + 0x24)?);
+            let stream_name = names::decode_name(&read_utf16_name(
+                data,
+                stream_base + STREAM_ENTRY_FIXED_SIZE,
+                stream_name_nbytes,
+            )?);
+
+            streams.push(StreamEntry {
+                name: stream_name,
+                hash: stream_hash,
+            });
+            next_offset += align8(stream_length);
+        }
+
+        Ok(Some(ParsedDentry {
+            entry: DirEntry {
+                attributes,
+                security_id,
+                creation_time,
+                last_access_time,
+                last_write_time,
+                hash,
+                reparse_tag,
+                rp_flags,
+                hard_link_group_id,
+                name,
+                name_utf16,
+                short_name,
+                streams,
+                children: Vec::new(),
+            },
+            subdir_offset,
+            next_offset,
+        }))
+    }
+
+    /// 递归加载目录的子目录项
+    fn load_children(&mut self, dir: &mut ParsedDentry, depth: usize) -> Result<()> {
+        if !dir.entry.is_directory() || dir.subdir_offset == 0 {
+            return Ok(());
+        }
+        if depth >= MAX_DIRECTORY_DEPTH {
+            return Err(anyhow::anyhow!("目录嵌套过深，元数据可能已损坏"));
+        }
+        if !self.visited.insert(dir.subdir_offset) {
+            return Err(anyhow::anyhow!(
+                "检测到循环目录引用，偏移: {}",
+                dir.subdir_offset
+            ));
+        }
+
+        let mut offset = dir.subdir_offset;
+        while let Some(mut child) = self.parse_dentry(offset)? {
+            self.dentries += 1;
+            self.limits.check_dentries(self.dentries)?;
+            self.load_children(&mut child, depth + 1)?;
+            offset = child.next_offset;
+            dir.entry.children.push(child.entry);
+        }
+
+        Ok(())
+    }
+}
+
+/// 将 UTF-8 名称编码为 UTF-16 LE 字节
+fn encode_utf16_name(name: &str) -> Vec<u8> {
+    name.encode_utf16().flat_map(u16::to_le_bytes).collect()
+}
+
+/// 编码单个目录项（含附加数据流条目），子目录项偏移稍后回填
+fn encode_dentry(entry: &DirEntry) -> Vec<u8> {
+    // 无效的 UTF-16 名称按原始码元写回
+    let name: Vec<u8> = entry
+        .raw_name()
+        .iter()
+        .flat_map(|unit| unit.to_le_bytes())
+        .collect();
+    let short_name = encode_utf16_name(&entry.short_name);
+    // 名称之后各有 2 字节的空终止符（名称为空时省略）
+    let name_len = if name.is_empty() { 0 } else { name.len() + 2 };
+    let short_name_len = if short_name.is_empty() {
+        0
+    } else {
+        short_name.len() + 2
+    };
+    let length = DENTRY_FIXED_SIZE + name_len + short_name_len;
+
+    let mut buffer = vec![0u8; align8(length as u64) as usize];
+    buffer[0x00..0x08].copy_from_slice(&(length as u64).to_le_bytes());
+    buffer[0x08..0x0C].copy_from_slice(&entry.attributes.to_le_bytes());
+    buffer[0x0C..0x10].copy_from_slice(&entry.security_id.to_le_bytes());
+    buffer[0x28..0x30].copy_from_slice(&entry.creation_time.filetime().to_le_bytes());
+    buffer[0x30..0x38].copy_from_slice(&entry.last_access_time.filetime().to_le_bytes());
+    buffer[0x38..0x40].copy_from_slice(&entry.last_write_time.filetime().to_le_bytes());
+    buffer[0x40..0x54].copy_from_slice(&entry.hash);
+    if entry.is_reparse_point() {
+        buffer[0x58..0x5C].copy_from_slice(&entry.reparse_tag.to_le_bytes());
+        buffer[0x5E..0x60].copy_from_slice(&entry.rp_flags.to_le_bytes());
+    } else {
+        buffer[0x58..0x60].copy_from_slice(&entry.hard_link_group_id.to_le_bytes());
+    }
+    buffer[0x60..0x62].copy_from_slice(&(entry.streams.len() as u16).to_le_bytes());
+    buffer[0x62..0x64].copy_from_slice(&(short_name.len() as u16).to_le_bytes());
+    buffer[0x64..0x66].copy_from_slice(&(name.len() as u16).to_le_bytes());
+    buffer[DENTRY_FIXED_SIZE..DENTRY_FIXED_SIZE + name.len()].copy_from_slice(&name);
+    let short_name_offset = DENTRY_FIXED_SIZE + name_len;
+    buffer[short_name_offset..short_name_offset + short_name.len()].copy_from_slice(&short_name);
+
+    for stream in &entry.streams {
+        let name = encode_utf16_name(&stream.name);
+        let name_len = if name.is_empty() { 0 } else { name.len() + 2 };
+        let length = STREAM_ENTRY_FIXED_SIZE + name_len;
+        let start = buffer.len();
+        buffer.resize(start + align8(length as u64) as usize, 0);
+        let stream_buffer = &mut buffer[start..];
+        stream_buffer[0x00..0x08].copy_from_slice(&(length as u64).to_le_bytes());
+        stream_buffer[0x10..0x24].copy_from_slice(&stream.hash);
+        stream_buffer[0x24..0x26].copy_from_slice(&(name.len() as u16).to_le_bytes());
+        stream_buffer[STREAM_ENTRY_FIXED_SIZE..STREAM_ENTRY_FIXED_SIZE + name.len()]
+            .copy_from_slice(&name);
+    }
+    buffer
+}
+
+/// 写入目录的子目录项列表（以 8 字节 0 结束），递归写入子目录，返回列表偏移
+fn encode_children(buffer: &mut Vec<u8>, dir: &DirEntry) -> u64 {
+    let list_offset = buffer.len() as u64;
+    let mut positions = Vec::with_capacity(dir.children.len());
+    for child in &dir.children {
+        positions.push(buffer.len());
+        buffer.extend_from_slice(&encode_dentry(child));
+    }
+    buffer.extend_from_slice(&[0u8; 8]);
+
+    for (child, position) in dir.children.iter().zip(positions) {
+        if child.is_directory() {
+            let offset = encode_children(buffer, child);
+            buffer[position + 0x10..position + 0x18].copy_from_slice(&offset.to_le_bytes());
+        }
+    }
+    list_offset
+}
+
+/// 将目录树编码为未压缩的镜像元数据资源（`parse_metadata_resource` 的逆操作）
+///
+/// 写入空的安全数据块，目录项的安全描述符索引应为 -1。
+#[cfg_attr(not(feature = "verify"), allow(dead_code))]
+pub(crate) fn encode_metadata_resource(root: &DirEntry) -> Vec<u8> {
+    encode_metadata_with_security::<&[u8]>(root, &[])
+}
+
+/// 将安全描述符和目录树编码为未压缩的镜像元数据资源，目录项的安全描述符索引对应 `descriptors`
+#[cfg_attr(not(feature = "verify"), allow(dead_code))]
+pub(crate) fn encode_metadata_with_security<D: AsRef<[u8]>>(
+    root: &DirEntry,
+    descriptors: &[D],
+) -> Vec<u8> {
+    // 安全数据块：总长度、描述符数量、各描述符大小和描述符数据，按 8 字节对齐
+    let total_length = 8
+        + descriptors.len() * 8
+        + descriptors
+            .iter()
+            .map(|descriptor| descriptor.as_ref().len())
+            .sum::<usize>();
+    let mut buffer = Vec::new();
+    buffer.extend_from_slice(&(total_length as u32).to_le_bytes());
+    buffer.extend_from_slice(&(descriptors.len() as u32).to_le_bytes());
+    for descriptor in descriptors {
+        buffer.extend_from_slice(&(descriptor.as_ref().len() as u64).to_le_bytes());
+    }
+    for descriptor in descriptors {
+        buffer.extend_from_slice(descriptor.as_ref());
+    }
+    buffer.resize(align8(total_length as u64) as usize, 0);
+
+    let root_position = buffer.len();
+    buffer.extend_from_slice(&encode_dentry(root));
+    buffer.extend_from_slice(&[0u8; 8]);
+    let offset = encode_children(&mut buffer, root);
+    buffer[root_position + 0x10..root_position + 0x18].copy_from_slice(&offset.to_le_bytes());
+    buffer
+}
@@ -0,0 +1,195 @@
+//! 镜像元数据摘要：对目录树和镜像 XML 的规范化形式计算 SHA-256，用于证明归档的镜像未被修改
+//!
+//! 摘要只取决于镜像内容，与 WIM 容器的布局无关：资源偏移、压缩方式、偏移表顺序、
+//! 镜像索引和安全描述符的存储顺序都不参与计算，因此导出或重建容器后摘要保持不变。
+
+use anyhow::{Context, Result};
+use sha2::{Digest as _, Sha256};
+use std::collections::HashMap;
+
+use crate::log::debug;
+use crate::metadata::{self, DirEntry};
+use crate::verify::Digest;
+use crate::{WimParser, XmlElement};
+
+/// 规范化形式的版本标记，格式变化时递增
+const CANONICAL_VERSION: &[u8] = b"wim-parser/metadata-digest/v1\0";
+
+/// 写入规范化数据的哈希器（所有变长字段都带长度前缀）
+struct CanonicalHasher(Sha256);
+
+impl CanonicalHasher {
+    fn bytes(&mut self, bytes: &[u8]) {
+        self.0.update((bytes.len() as u64).to_le_bytes());
+        self.0.update(bytes);
+    }
+
+    fn str(&mut self, text: &str) {
+        self.bytes(text.as_bytes());
+    }
+
+    fn u32(&mut self, value: u32) {
+        self.0.update(value.to_le_bytes());
+    }
+
+    fn u64(&mut self, value: u64) {
+        self.0.update(value.to_le_bytes());
+    }
+
+    /// XML 元素：属性按名称排序，子元素按名称稳定排序（同名元素保持原有顺序）
+    fn xml(&mut self, element: &XmlElement, skip_attribute: Option<&str>) {
+        self.str(&element.name);
+        let mut attributes: Vec<_> = element
+            .attributes
+            .iter()
+            .filter(|(name, _)| Some(name.as_str()) != skip_attribute)
+            .collect();
+        attributes.sort();
+        self.u64(attributes.len() as u64);
+        for (name, value) in attributes {
+            self.str(name);
+            self.str(value);
+        }
+        self.str(&element.text);
+
+        let mut children: Vec<_> = element.children.iter().collect();
+        children.sort_by(|a, b| a.name.cmp(&b.name));
+        self.u64(children.len() as u64);
+        for child in children {
+            self.xml(child, None);
+        }
+    }
+}
+
+/// 按规范顺序写入目录树的状态
+struct TreeDigest<'a> {
+    hasher: CanonicalHasher,
+    descriptors: Vec<&'a [u8]>,
+    /// 硬链接组 ID 到组内第一个路径的映射（组 ID 本身由容器决定，不参与计算）
+    link_groups: HashMap<u64, String>,
+    entries: u64,
+}
+
+impl TreeDigest<'_> {
+    fn entry(&mut self, path: &str, entry: &DirEntry) -> Result<()> {
+        let hasher = &mut self.hasher;
+        hasher.str(path);
+        hasher.u32(entry.attributes);
+        hasher.u64(entry.creation_time.filetime());
+        hasher.u64(entry.last_access_time.filetime());
+        hasher.u64(entry.last_write_time.filetime());
+        hasher.u32(entry.reparse_tag);
+        hasher.0.update(entry.rp_flags.to_le_bytes());
+        hasher.str(&entry.short_name);
+
+        // 安全描述符按内容计算，与其在安全数据块中的位置无关
+        match usize::try_from(entry.security_id) {
+            Ok(id) => {
+                let descriptor = self.descriptors.get(id).ok_or_else(|| {
+                    anyhow::anyhow!("{} 的安全描述符索引无效: {}", path, entry.security_id)
+                })?;
+                hasher.0.update([1]);
+                hasher.bytes(descriptor);
+            }
+            Err(_) => hasher.0.update([0]),
+        }
+
+        // 未命名数据流可能记录在目录项本身或名称为空的数据流条目中
+        let unnamed = match entry.hash {
+            hash if hash != [0u8; 20] => hash,
+            _ => entry
+                .streams
+                .iter()
+                .find(|stream| stream.name.is_empty())
+                .map_or([0u8; 20], |stream| stream.hash),
+        };
+        hasher.0.update(unnamed);
+        let mut streams: Vec<_> = entry
+            .streams
+            .iter()
+            .filter(|stream| !stream.name.is_empty())
+            .collect();
+        streams.sort_by(|a, b| a.name.cmp(&b.name));
+        hasher.u64(streams.len() as u64);
+        for stream in streams {
+            hasher.str(&stream.name);
+            hasher.0.update(stream.hash);
+        }
+
+        let link = match entry.hard_link_group_id {
+            0 => "",
+            _ if entry.is_reparse_point() => "",
+            group => self
+                .link_groups
+                .entry(group)
+                .or_insert_with(|| path.to_string()),
+        };
+        hasher.str(link);
+        self.entries += 1;
+
+        // 子目录项按名称（不区分大小写）排序，与目录项列表中的存储顺序无关
+        let mut children: Vec<_> = entry.children.iter().collect();
+        children.sort_by(|a, b| {
+            a.name
+                .to_uppercase()
+                .cmp(&b.name.to_uppercase())
+                .then_with(|| a.name.cmp(&b.name))
+        });
+        self.hasher.u64(children.len() as u64);
+        for child in children {
+            let child_path = if path.is_empty() {
+                child.name.clone()
+            } else {
+                format!("{path}/{}", child.name)
+            };
+            self.entry(&child_path, child)?;
+        }
+        Ok(())
+    }
+}
+
+impl WimParser {
+    /// 计算镜像内容的规范化摘要（SHA-256）
+    ///
+    /// 摘要覆盖目录树中每个目录项的路径、属性、时间、短文件名、安全描述符、重解析点信息、
+    /// 各数据流的 SHA-1 和硬链接关系，以及镜像的 `<IMAGE>` XML（不含 `INDEX` 属性）。
+    /// 子目录项、属性、命名数据流和 XML 子元素按名称排序后计算，重建 WIM 容器
+    /// （导出、重新压缩、调整镜像顺序）不改变摘要；任何内容或元数据的修改都会改变摘要。
+    pub fn metadata_digest(&mut self, index: u32) -> Result<Digest> {
+        if self.images.is_empty() {
+            self.parse_full()?;
+        }
+        let xml = self
+            .get_image(index)
+            .ok_or_else(|| anyhow::anyhow!("找不到镜像 {}", index))?
+            .xml
+            .clone();
+
+        let data = self.read_metadata_bytes(index)?;
+        let root = metadata::parse_metadata_resource(&data, &self.options().resource_limits())
+            .with_context(|| format!("解析镜像 {index} 的元数据资源失败"))?;
+        let descriptors = metadata::parse_security_descriptors(&data)
+            .with_context(|| format!("解析镜像 {index} 的安全数据块失败"))?;
+
+        let mut hasher = CanonicalHasher(Sha256::new());
+        hasher.0.update(CANONICAL_VERSION);
+        match &xml {
+            Some(xml) => {
+                hasher.0.update([1]);
+                hasher.xml(xml, Some("INDEX"));
+            }
+            None => hasher.0.update([0]),
+        }
+
+        let mut tree = TreeDigest {
+            hasher,
+            descriptors,
+            link_groups: HashMap::new(),
+            entries: 0,
+        };
+        tree.entry("", &root)?;
+        debug!("镜像 {} 元数据摘要: {} 个目录项", index, tree.entries);
+
+        Ok(Digest::Sha256(tree.hasher.0.finalize().into()))
+    }
+}
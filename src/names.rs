@@ -0,0 +1,186 @@
+//! 目录项名称：原始 UTF-16 名称的解码和名称比较规则
+//!
+//! WIM 中的文件名是任意的 16 位码元序列，可能含有不成对的代理项（NTFS 不校验名称），
+//! 也可能以点或空格结尾（Win32 API 会去掉它们）。解码时不成对的代理项转义为 `%uXXXX`，
+//! 使名称保持唯一且可以按路径访问；[`NameMatching`] 控制查找路径时名称的比较方式。
+
+use alloc::borrow::Cow;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::Write as _;
+
+/// 将原始 UTF-16 名称解码为 UTF-8
+///
+/// 有效的 UTF-16 名称原样转换；不成对的代理项写成 `%uXXXX`（如 `%uD800`），
+/// 不同的无效名称因此不会像 [`lossy_name`] 那样全部变成 U+FFFD 而无法区分。
+pub fn decode_name(units: &[u16]) -> String {
+    let mut name = String::with_capacity(units.len());
+    for unit in char::decode_utf16(units.iter().copied()) {
+        match unit {
+            Ok(c) => name.push(c),
+            Err(err) => {
+                let _ = write!(name, "%u{:04X}", err.unpaired_surrogate());
+            }
+        }
+    }
+    name
+}
+
+/// 原始 UTF-16 名称的有损 UTF-8 形式（不成对的代理项替换为 U+FFFD），用于显示
+pub fn lossy_name(units: &[u16]) -> String {
+    String::from_utf16_lossy(units)
+}
+
+/// 原始名称不是有效的 UTF-16 时保留码元，供 [`decode_name`] 的结果无法还原时使用
+#[cfg_attr(not(feature = "parser"), allow(dead_code))]
+pub(crate) fn invalid_units(units: &[u16]) -> Option<Vec<u16>> {
+    char::decode_utf16(units.iter().copied())
+        .any(|unit| unit.is_err())
+        .then(|| units.to_vec())
+}
+
+/// 原始 UTF-16 名称：保留的码元，或由有效的 UTF-8 名称重新编码
+#[cfg_attr(not(feature = "parser"), allow(dead_code))]
+pub(crate) fn raw_units<'a>(name: &str, units: Option<&'a [u16]>) -> Cow<'a, [u16]> {
+    match units {
+        Some(units) => Cow::Borrowed(units),
+        None => Cow::Owned(name.encode_utf16().collect()),
+    }
+}
+
+/// 查找路径时目录项名称的比较规则（见 [`ParseOptions::name_matching`](crate::ParseOptions::name_matching)）
+///
+/// 默认与 Windows 一致：不区分大小写（逐字符的 Unicode 简单大写映射，与 NTFS 的大写表相同），
+/// 不忽略结尾的点和空格，不做 Unicode 规范化。名称完全相同的目录项总是优先匹配，
+/// 因此放宽规则后，仅在规则下相同的多个目录项仍然可以分别按原名访问。
+#[derive(Debug, Clone, Copy)]
+pub struct NameMatching {
+    case_insensitive: bool,
+    ignore_trailing_dots_spaces: bool,
+    normalizer: Option<fn(&str) -> String>,
+}
+
+impl Default for NameMatching {
+    fn default() -> Self {
+        Self {
+            case_insensitive: true,
+            ignore_trailing_dots_spaces: false,
+            normalizer: None,
+        }
+    }
+}
+
+impl PartialEq for NameMatching {
+    fn eq(&self, other: &Self) -> bool {
+        self.case_insensitive == other.case_insensitive
+            && self.ignore_trailing_dots_spaces == other.ignore_trailing_dots_spaces
+            && match (self.normalizer, other.normalizer) {
+                (Some(a), Some(b)) => core::ptr::fn_addr_eq(a, b),
+                (a, b) => a.is_none() && b.is_none(),
+            }
+    }
+}
+
+impl Eq for NameMatching {}
+
+impl NameMatching {
+    /// 逐码元精确比较（区分大小写，无任何规范化）
+    pub fn exact() -> Self {
+        Self {
+            case_insensitive: false,
+            ignore_trailing_dots_spaces: false,
+            normalizer: None,
+        }
+    }
+
+    /// Win32 路径规则：不区分大小写，并忽略名称结尾的点和空格（`"readme.txt. "` 与 `"README.TXT"` 相同）
+    pub fn win32() -> Self {
+        Self {
+            ignore_trailing_dots_spaces: true,
+            ..Self::default()
+        }
+    }
+
+    /// 是否不区分大小写
+    pub fn case_insensitive(mut self, enabled: bool) -> Self {
+        self.case_insensitive = enabled;
+        self
+    }
+
+    /// 是否忽略名称结尾的点和空格
+    pub fn ignore_trailing_dots_spaces(mut self, enabled: bool) -> Self {
+        self.ignore_trailing_dots_spaces = enabled;
+        self
+    }
+
+    /// 比较前对名称做的 Unicode 规范化（如 NFC），在其他规则之前应用
+    ///
+    /// 本库不内置 Unicode 数据表，需要时可传入 `unicode-normalization` 等库提供的函数，
+    /// 使组合形式不同（`"e\u{301}"` 与 `"é"`）的名称视为相同。
+    pub fn normalizer(mut self, normalizer: fn(&str) -> String) -> Self {
+        self.normalizer = Some(normalizer);
+        self
+    }
+
+    /// 名称在此规则下的比较键：键相同的名称视为同一名称
+    pub fn key(&self, name: &str) -> String {
+        let mut name = match self.normalizer {
+            Some(normalize) => Cow::Owned(normalize(name)),
+            None => Cow::Borrowed(name),
+        };
+        if self.ignore_trailing_dots_spaces {
+            let trimmed = name.trim_end_matches(['.', ' ']);
+            // 全部由点和空格组成的名称（如 `..`）保持不变
+            if !trimmed.is_empty() && trimmed.len() != name.len() {
+                name = Cow::Owned(String::from(trimmed));
+            }
+        }
+        if !self.case_insensitive {
+            return name.into_owned();
+        }
+        name.chars().map(simple_uppercase).collect()
+    }
+
+    /// 两个名称在此规则下是否相同
+    pub fn matches(&self, a: &str, b: &str) -> bool {
+        a == b || self.key(a) == self.key(b)
+    }
+
+    /// 在 `candidates` 中查找名称为 `name` 的项：完全相同的名称优先，其次是规则下相同的名称
+    #[cfg_attr(not(feature = "parser"), allow(dead_code))]
+    pub(crate) fn find<'a, T>(
+        &self,
+        candidates: &'a [T],
+        name: &str,
+        name_of: impl Fn(&T) -> &str,
+    ) -> Option<&'a T> {
+        self.position(candidates, name, name_of)
+            .map(|index| &candidates[index])
+    }
+
+    /// 同 [`find`](Self::find)，返回匹配项的下标
+    #[cfg_attr(not(feature = "parser"), allow(dead_code))]
+    pub(crate) fn position<T>(
+        &self,
+        candidates: &[T],
+        name: &str,
+        name_of: impl Fn(&T) -> &str,
+    ) -> Option<usize> {
+        if let Some(exact) = candidates.iter().position(|item| name_of(item) == name) {
+            return Some(exact);
+        }
+        let key = self.key(name);
+        candidates
+            .iter()
+            .position(|item| self.key(name_of(item)) == key)
+    }
+}
+
+/// 逐字符的简单大写映射：映射为多个字符的（如 `ß`）保持不变，与 NTFS 的大写表一致
+fn simple_uppercase(c: char) -> char {
+    let mut upper = c.to_uppercase();
+    match (upper.next(), upper.next()) {
+        (Some(single), None) => single,
+        _ => c,
+    }
+}
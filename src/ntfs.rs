@@ -0,0 +1,427 @@
+//! 通过 libntfs-3g 直接释放到 NTFS 分区（Linux 上部署 Windows，类似 wimlib 的 NTFS-3G 模式）
+//!
+//! 卷以读写方式由 libntfs-3g 在用户态打开，不需要挂载：文件内容、命名数据流、属性、时间、
+//! 安全描述符和任意标记的重解析点都按原样写入 NTFS，释放出的分区可以直接启动。
+//! 需要系统安装 libntfs-3g（链接 `-lntfs-3g`），分区必须未被挂载。
+
+use anyhow::{Context, Result};
+use std::ffi::{c_char, c_int, c_ulong, c_void, CString};
+use std::io::{self, Write};
+use std::path::Path;
+use std::ptr;
+
+use crate::log::{debug, info};
+use crate::{ApplyOptions, ApplyReport, ApplyTarget, EntryMetadata, WimParser};
+
+/// 数据属性类型 (AT_DATA)
+const AT_DATA: u32 = 0x80;
+/// 创建目录时的文件类型 (S_IFDIR)
+const S_IFDIR: u32 = 0o040000;
+/// 创建普通文件时的文件类型 (S_IFREG)
+const S_IFREG: u32 = 0o100000;
+
+/// libntfs-3g 的不透明句柄
+#[repr(C)]
+struct NtfsVolume {
+    _private: [u8; 0],
+}
+
+#[repr(C)]
+struct NtfsInode {
+    _private: [u8; 0],
+}
+
+#[repr(C)]
+struct NtfsAttr {
+    _private: [u8; 0],
+}
+
+/// `struct SECURITY_CONTEXT`：`ntfs_set_ntfs_acl` 只使用其中的卷指针，其余字段保持为零
+#[repr(C)]
+struct SecurityContext {
+    vol: *mut NtfsVolume,
+    reserved: [u64; 8],
+}
+
+#[link(name = "ntfs-3g")]
+extern "C" {
+    static AT_UNNAMED: [u16; 1];
+
+    fn ntfs_mount(name: *const c_char, flags: c_ulong) -> *mut NtfsVolume;
+    fn ntfs_umount(vol: *mut NtfsVolume, force: c_int) -> c_int;
+    fn ntfs_pathname_to_inode(
+        vol: *mut NtfsVolume,
+        parent: *mut NtfsInode,
+        pathname: *const c_char,
+    ) -> *mut NtfsInode;
+    fn ntfs_create(
+        dir_ni: *mut NtfsInode,
+        securid: u32,
+        name: *const u16,
+        name_len: u8,
+        mode: u32,
+    ) -> *mut NtfsInode;
+    fn ntfs_inode_close(ni: *mut NtfsInode) -> c_int;
+    fn ntfs_attr_add(
+        ni: *mut NtfsInode,
+        attr_type: u32,
+        name: *mut u16,
+        name_len: u8,
+        val: *const u8,
+        size: i64,
+    ) -> c_int;
+    fn ntfs_attr_open(
+        ni: *mut NtfsInode,
+        attr_type: u32,
+        name: *mut u16,
+        name_len: u32,
+    ) -> *mut NtfsAttr;
+    fn ntfs_attr_pwrite(na: *mut NtfsAttr, pos: i64, count: i64, buf: *const c_void) -> i64;
+    fn ntfs_attr_close(na: *mut NtfsAttr);
+    fn ntfs_set_ntfs_attrib(
+        ni: *mut NtfsInode,
+        value: *const c_char,
+        size: usize,
+        flags: c_int,
+    ) -> c_int;
+    fn ntfs_inode_set_times(
+        ni: *mut NtfsInode,
+        value: *const c_char,
+        size: usize,
+        flags: c_int,
+    ) -> c_int;
+    fn ntfs_set_ntfs_acl(
+        scx: *mut SecurityContext,
+        ni: *mut NtfsInode,
+        value: *const c_char,
+        size: usize,
+        flags: c_int,
+    ) -> c_int;
+    fn ntfs_set_ntfs_reparse_data(
+        ni: *mut NtfsInode,
+        value: *const c_char,
+        size: usize,
+        flags: c_int,
+    ) -> c_int;
+}
+
+/// 已打开的 inode，离开作用域时关闭
+struct Inode(*mut NtfsInode);
+
+impl Drop for Inode {
+    fn drop(&mut self) {
+        // SAFETY: 指针来自 ntfs_pathname_to_inode / ntfs_create，且只关闭一次
+        unsafe {
+            ntfs_inode_close(self.0);
+        }
+    }
+}
+
+/// 写入数据属性的 [`Write`]，离开作用域时关闭属性和 inode
+struct AttrWriter {
+    attr: *mut NtfsAttr,
+    position: i64,
+    _inode: Inode,
+}
+
+impl Write for AttrWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        // SAFETY: attr 在 AttrWriter 存活期间保持打开，buf 在调用期间有效
+        let written = unsafe {
+            ntfs_attr_pwrite(
+                self.attr,
+                self.position,
+                buf.len() as i64,
+                buf.as_ptr().cast(),
+            )
+        };
+        if written < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        self.position += written;
+        Ok(written as usize)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Drop for AttrWriter {
+    fn drop(&mut self) {
+        // SAFETY: 属性先于其所属 inode 关闭（字段 _inode 随后析构）
+        unsafe { ntfs_attr_close(self.attr) }
+    }
+}
+
+/// 名称转换为 NTFS 使用的 UTF-16 LE，超过 255 个字符时返回错误
+fn ntfs_name(name: &str) -> Result<Vec<u16>> {
+    let units: Vec<u16> = name.encode_utf16().map(u16::to_le).collect();
+    if units.len() > 255 {
+        return Err(anyhow::anyhow!("名称超过 255 个字符: {}", name));
+    }
+    Ok(units)
+}
+
+/// libntfs-3g 返回非零值时转换为带上下文的错误
+fn check(result: c_int, action: &str, path: &str) -> Result<()> {
+    if result != 0 {
+        return Err(io::Error::last_os_error()).with_context(|| format!("{action}失败: /{path}"));
+    }
+    Ok(())
+}
+
+/// 释放到 NTFS 分区（或 NTFS 格式的镜像文件）
+///
+/// 由 [`NtfsTarget::open`] 以读写方式打开卷，[`ApplyTarget::finish`] 或析构时关闭卷并写回。
+/// 目标卷应为新格式化的空卷：已存在的同名文件会导致创建失败。
+#[derive(Debug)]
+pub struct NtfsTarget {
+    vol: *mut NtfsVolume,
+    device: String,
+}
+
+impl NtfsTarget {
+    /// 打开块设备或镜像文件上的 NTFS 卷（例如 `/dev/sdb2`），卷不能处于挂载状态
+    pub fn open<P: AsRef<Path>>(device: P) -> Result<Self> {
+        let device = device.as_ref().to_string_lossy().into_owned();
+        let name = CString::new(device.as_str()).context("设备路径中包含 NUL 字符")?;
+        // SAFETY: name 是有效的 C 字符串；flags 为 0 表示读写打开
+        let vol = unsafe { ntfs_mount(name.as_ptr(), 0) };
+        if vol.is_null() {
+            return Err(io::Error::last_os_error())
+                .with_context(|| format!("无法打开 NTFS 卷: {device}"));
+        }
+        debug!("已打开 NTFS 卷 {}", device);
+        Ok(Self { vol, device })
+    }
+
+    /// 设备路径
+    pub fn device(&self) -> &str {
+        &self.device
+    }
+
+    /// 按镜像内路径打开 inode（空路径为根目录）
+    fn open_inode(&self, path: &str) -> Result<Inode> {
+        let name = CString::new(format!("/{path}")).context("路径中包含 NUL 字符")?;
+        // SAFETY: vol 在 NtfsTarget 存活期间保持打开
+        let ni = unsafe { ntfs_pathname_to_inode(self.vol, ptr::null_mut(), name.as_ptr()) };
+        if ni.is_null() {
+            return Err(io::Error::last_os_error())
+                .with_context(|| format!("在 NTFS 卷上找不到 /{path}"));
+        }
+        Ok(Inode(ni))
+    }
+
+    /// 在父目录中创建文件或目录
+    fn create(&mut self, path: &str, mode: u32) -> Result<Inode> {
+        let (parent, name) = path.rsplit_once('/').unwrap_or(("", path));
+        let parent = self.open_inode(parent)?;
+        let name = ntfs_name(name)?;
+        // SAFETY: parent 为打开的目录 inode，name 在调用期间有效且长度不超过 255
+        let ni = unsafe { ntfs_create(parent.0, 0, name.as_ptr(), name.len() as u8, mode) };
+        if ni.is_null() {
+            return Err(io::Error::last_os_error())
+                .with_context(|| format!("无法在 NTFS 卷上创建 /{path}"));
+        }
+        Ok(Inode(ni))
+    }
+
+    /// 打开 inode 的数据属性用于写入
+    fn data_writer(inode: Inode, name: &mut [u16], path: &str) -> Result<AttrWriter> {
+        // SAFETY: inode 已打开；未命名数据流使用库导出的 AT_UNNAMED
+        let attr = unsafe {
+            let name_ptr = if name.is_empty() {
+                AT_UNNAMED.as_ptr().cast_mut()
+            } else {
+                name.as_mut_ptr()
+            };
+            ntfs_attr_open(inode.0, AT_DATA, name_ptr, name.len() as u32)
+        };
+        if attr.is_null() {
+            return Err(io::Error::last_os_error())
+                .with_context(|| format!("无法打开 /{path} 的数据属性"));
+        }
+        Ok(AttrWriter {
+            attr,
+            position: 0,
+            _inode: inode,
+        })
+    }
+
+    /// 关闭卷，写回所有修改
+    fn unmount(&mut self) -> Result<()> {
+        if self.vol.is_null() {
+            return Ok(());
+        }
+        let vol = std::mem::replace(&mut self.vol, ptr::null_mut());
+        // SAFETY: vol 是打开的卷，关闭后不再使用
+        if unsafe { ntfs_umount(vol, 0) } != 0 {
+            return Err(io::Error::last_os_error())
+                .with_context(|| format!("关闭 NTFS 卷失败: {}", self.device));
+        }
+        Ok(())
+    }
+}
+
+impl Drop for NtfsTarget {
+    fn drop(&mut self) {
+        let _ = self.unmount();
+    }
+}
+
+impl ApplyTarget for NtfsTarget {
+    fn create_dir(&mut self, path: &str, _metadata: &EntryMetadata) -> Result<()> {
+        self.create(path, S_IFDIR).map(drop)
+    }
+
+    fn create_file(
+        &mut self,
+        path: &str,
+        _size: u64,
+        _metadata: &EntryMetadata,
+    ) -> Result<Box<dyn Write + '_>> {
+        let inode = self.create(path, S_IFREG)?;
+        Ok(Box::new(Self::data_writer(inode, &mut [], path)?))
+    }
+
+    fn set_metadata(&mut self, path: &str, metadata: &EntryMetadata) -> Result<()> {
+        let inode = self.open_inode(path)?;
+        let attributes = metadata.attributes.to_le_bytes();
+        // 时间顺序与 ntfs_inode_set_times 一致：创建、最后写入、最后访问
+        let times: Vec<u8> = [
+            metadata.creation_time,
+            metadata.last_write_time,
+            metadata.last_access_time,
+        ]
+        .iter()
+        .flat_map(|time| time.filetime().to_le_bytes())
+        .collect();
+        // SAFETY: inode 已打开，缓冲区在调用期间有效
+        unsafe {
+            check(
+                ntfs_set_ntfs_attrib(inode.0, attributes.as_ptr().cast(), attributes.len(), 0),
+                "设置属性",
+                path,
+            )?;
+            check(
+                ntfs_inode_set_times(inode.0, times.as_ptr().cast(), times.len(), 0),
+                "设置时间",
+                path,
+            )
+        }
+    }
+
+    fn symlink(&mut self, path: &str, target: &str, _metadata: &EntryMetadata) -> Result<()> {
+        // 重解析点总是由 reparse_point 按原始数据还原
+        Err(anyhow::anyhow!(
+            "NTFS 目标不通过符号链接还原重解析点: /{path} -> {target}"
+        ))
+    }
+
+    fn reparse_point(
+        &mut self,
+        path: &str,
+        tag: u32,
+        data: &[u8],
+        metadata: &EntryMetadata,
+    ) -> Result<bool> {
+        let mode = if metadata.is_directory() {
+            S_IFDIR
+        } else {
+            S_IFREG
+        };
+        let inode = self.create(path, mode)?;
+        let length = u16::try_from(data.len())
+            .map_err(|_| anyhow::anyhow!("/{} 的重解析数据过长: {} 字节", path, data.len()))?;
+        // REPARSE_DATA_BUFFER：标记、数据长度、保留字段，之后是重解析数据
+        let mut buffer = Vec::with_capacity(8 + data.len());
+        buffer.extend_from_slice(&tag.to_le_bytes());
+        buffer.extend_from_slice(&length.to_le_bytes());
+        buffer.extend_from_slice(&0u16.to_le_bytes());
+        buffer.extend_from_slice(data);
+        // SAFETY: inode 已打开，缓冲区在调用期间有效
+        check(
+            unsafe { ntfs_set_ntfs_reparse_data(inode.0, buffer.as_ptr().cast(), buffer.len(), 0) },
+            "设置重解析数据",
+            path,
+        )?;
+        Ok(true)
+    }
+
+    fn set_security_descriptor(&mut self, path: &str, descriptor: &[u8]) -> Result<bool> {
+        let inode = self.open_inode(path)?;
+        let mut context = SecurityContext {
+            vol: self.vol,
+            reserved: [0; 8],
+        };
+        // SAFETY: inode 已打开，context 只需要有效的卷指针，描述符在调用期间有效
+        check(
+            unsafe {
+                ntfs_set_ntfs_acl(
+                    &mut context,
+                    inode.0,
+                    descriptor.as_ptr().cast(),
+                    descriptor.len(),
+                    0,
+                )
+            },
+            "设置安全描述符",
+            path,
+        )?;
+        Ok(true)
+    }
+
+    fn create_named_stream(
+        &mut self,
+        path: &str,
+        name: &str,
+        _size: u64,
+    ) -> Result<Option<Box<dyn Write + '_>>> {
+        let inode = self.open_inode(path)?;
+        let mut name = ntfs_name(name)?;
+        // SAFETY: inode 已打开，name 在调用期间有效；创建空的命名数据属性后再写入内容
+        check(
+            unsafe {
+                ntfs_attr_add(
+                    inode.0,
+                    AT_DATA,
+                    name.as_mut_ptr(),
+                    name.len() as u8,
+                    ptr::null(),
+                    0,
+                )
+            },
+            "创建命名数据流",
+            path,
+        )?;
+        Ok(Some(Box::new(Self::data_writer(inode, &mut name, path)?)))
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.unmount()
+    }
+}
+
+impl WimParser {
+    /// 将镜像直接释放到 NTFS 分区（例如 `/dev/sdb2`），包括 ACL、属性、命名数据流和重解析点
+    ///
+    /// 分区应为新格式化的空 NTFS 卷且未被挂载；释放完成后关闭卷。
+    pub fn apply_to_ntfs<P: AsRef<Path>>(
+        &mut self,
+        index: u32,
+        device: P,
+        options: &ApplyOptions,
+    ) -> Result<ApplyReport> {
+        let mut target = NtfsTarget::open(device)?;
+        let report = self.apply_to(index, &mut target, options)?;
+        info!(
+            "镜像 {} 已释放到 NTFS 卷 {}: {} 个安全描述符, {} 个重解析点",
+            index,
+            target.device(),
+            report.security_count,
+            report.reparse_count
+        );
+        Ok(report)
+    }
+}
@@ -0,0 +1,116 @@
+use crate::limits::ResourceLimits;
+use crate::lock::LockPolicy;
+use crate::names::NameMatching;
+
+/// 解析选项：控制 [`WimParser::parse_full`](crate::WimParser::parse_full) 解析的深度
+///
+/// 批量探测大量文件时，可以关闭 XML 镜像列表或 Windows 元数据（版本、架构推断）的解析，
+/// 之后再通过 [`WimParser::load_windows_metadata`](crate::WimParser::load_windows_metadata)
+/// 按需补全。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParseOptions {
+    parse_images: bool,
+    parse_windows_metadata: bool,
+    strict: bool,
+    limits: ResourceLimits,
+    lock_policy: LockPolicy,
+    name_matching: NameMatching,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        Self {
+            parse_images: true,
+            parse_windows_metadata: true,
+            strict: false,
+            limits: ResourceLimits::unlimited(),
+            lock_policy: LockPolicy::FailFast,
+            name_matching: NameMatching::default(),
+        }
+    }
+}
+
+impl ParseOptions {
+    /// 完整解析（默认）
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 只读取文件头，不读取 XML 数据
+    pub fn header_only() -> Self {
+        Self {
+            parse_images: false,
+            parse_windows_metadata: false,
+            strict: false,
+            limits: ResourceLimits::unlimited(),
+            lock_policy: LockPolicy::FailFast,
+            name_matching: NameMatching::default(),
+        }
+    }
+
+    /// 是否读取 XML 数据中的镜像列表
+    pub fn parse_images(mut self, enabled: bool) -> Self {
+        self.parse_images = enabled;
+        self
+    }
+
+    /// 是否提取 Windows 元数据（`<WINDOWS>` 节中的架构、产品类型和安装类型，以及从名称推断的版本和架构）
+    pub fn parse_windows_metadata(mut self, enabled: bool) -> Self {
+        self.parse_windows_metadata = enabled;
+        self
+    }
+
+    /// 严格模式：解析过程中的警告（见 [`Warning`](crate::Warning)）作为错误返回
+    pub fn strict(mut self, enabled: bool) -> Self {
+        self.strict = enabled;
+        self
+    }
+
+    /// 资源限制（见 [`ResourceLimits`]），在读取资源、XML 数据和镜像元数据时检查
+    pub fn limits(mut self, limits: ResourceLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// 编辑和写入文件（提交事务、写回文件头、导出）时的加锁策略（见 [`LockPolicy`]）
+    pub fn lock_policy(mut self, policy: LockPolicy) -> Self {
+        self.lock_policy = policy;
+        self
+    }
+
+    /// 按路径查找目录项（如 [`LazyTree::find`](crate::LazyTree::find)）时名称的比较规则（见 [`NameMatching`]）
+    pub fn name_matching(mut self, matching: NameMatching) -> Self {
+        self.name_matching = matching;
+        self
+    }
+
+    /// 是否读取镜像列表
+    pub fn images_enabled(&self) -> bool {
+        self.parse_images
+    }
+
+    /// 是否提取 Windows 元数据
+    pub fn windows_metadata_enabled(&self) -> bool {
+        self.parse_images && self.parse_windows_metadata
+    }
+
+    /// 是否为严格模式
+    pub fn is_strict(&self) -> bool {
+        self.strict
+    }
+
+    /// 资源限制
+    pub fn resource_limits(&self) -> ResourceLimits {
+        self.limits
+    }
+
+    /// 加锁策略
+    pub fn file_lock_policy(&self) -> LockPolicy {
+        self.lock_policy
+    }
+
+    /// 名称比较规则
+    pub fn name_matching_rules(&self) -> NameMatching {
+        self.name_matching
+    }
+}
@@ -0,0 +1,159 @@
+use anyhow::Result;
+
+use crate::fmt::{Table, ToTable};
+use crate::log::debug;
+use crate::WimParser;
+
+/// 已安装组件和更新的清单目录
+const SERVICING_PACKAGES_DIR: &str = r"\Windows\servicing\Packages";
+
+/// 镜像中已安装的服务包（功能、可选组件或更新）
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ServicingPackage {
+    /// 包名称（例如 `Package_for_KB5005565`）
+    pub name: String,
+    /// 公钥令牌
+    pub public_key_token: String,
+    /// 处理器架构（`amd64`、`wow64` 等）
+    pub architecture: String,
+    /// 语言（语言无关的包为空）
+    pub language: String,
+    /// 版本
+    pub version: String,
+    /// 知识库编号（例如 `KB5005565`）
+    pub kb: Option<String>,
+    /// 发布类型（`.mum` 中的 `releaseType`，例如 `Security Update`、`Feature Pack`）
+    pub release_type: Option<String>,
+    /// `.mum` 文件名
+    pub file_name: String,
+}
+
+impl ServicingPackage {
+    /// 从 `.mum` 文件名 `Name~PublicKeyToken~Arch~Language~Version.mum` 解析
+    pub fn from_file_name(file_name: &str) -> Option<ServicingPackage> {
+        let split = file_name.len().checked_sub(4)?;
+        let (stem, extension) = (file_name.get(..split)?, file_name.get(split..)?);
+        if !extension.eq_ignore_ascii_case(".mum") {
+            return None;
+        }
+        let parts: Vec<&str> = stem.split('~').collect();
+        let [name, public_key_token, architecture, language, version] = parts[..] else {
+            return None;
+        };
+        if name.is_empty() || version.is_empty() {
+            return None;
+        }
+        Some(ServicingPackage {
+            name: name.to_string(),
+            public_key_token: public_key_token.to_string(),
+            architecture: architecture.to_string(),
+            language: language.to_string(),
+            version: version.to_string(),
+            kb: kb_number(name),
+            release_type: None,
+            file_name: file_name.to_string(),
+        })
+    }
+
+    /// 是否为更新（有知识库编号或发布类型为更新）
+    pub fn is_update(&self) -> bool {
+        self.kb.is_some()
+            || self
+                .release_type
+                .as_deref()
+                .is_some_and(|kind| kind.to_ascii_lowercase().contains("update"))
+    }
+
+    /// 补充 `.mum` 内容中的知识库编号和发布类型
+    fn apply_manifest(&mut self, manifest: &str) {
+        let Some(start) = manifest.find("<package ") else {
+            return;
+        };
+        let element = &manifest[start..];
+        let element = &element[..element.find('>').unwrap_or(element.len())];
+        if let Some(kb) = attribute(element, "identifier").and_then(kb_number) {
+            self.kb = Some(kb);
+        }
+        if let Some(kind) = attribute(element, "releaseType") {
+            self.release_type = Some(kind.to_string());
+        }
+    }
+}
+
+/// 提取 XML 元素中的属性值
+fn attribute<'a>(element: &'a str, name: &str) -> Option<&'a str> {
+    let pattern = format!("{name}=\"");
+    let start = element.find(&pattern)? + pattern.len();
+    let len = element[start..].find('"')?;
+    Some(&element[start..start + len])
+}
+
+/// 提取文本中的知识库编号（`KB` 后跟数字）
+fn kb_number(text: &str) -> Option<String> {
+    let upper = text.to_ascii_uppercase();
+    upper.match_indices("KB").find_map(|(pos, _)| {
+        let digits: String = upper[pos + 2..]
+            .chars()
+            .take_while(|c| c.is_ascii_digit())
+            .collect();
+        (!digits.is_empty()).then(|| format!("KB{digits}"))
+    })
+}
+
+impl ToTable for [ServicingPackage] {
+    fn table(&self) -> Table {
+        let mut table = Table::new(["名称", "版本", "架构", "知识库", "类型"]);
+        for package in self {
+            table.push_row([
+                package.name.clone(),
+                package.version.clone(),
+                package.architecture.clone(),
+                package.kb.clone().unwrap_or_else(|| "-".to_string()),
+                package
+                    .release_type
+                    .clone()
+                    .unwrap_or_else(|| "-".to_string()),
+            ]);
+        }
+        table
+    }
+}
+
+impl WimParser {
+    /// 列出镜像中已安装的服务包（`\Windows\servicing\Packages\*.mum`）
+    ///
+    /// 名称、架构和版本来自文件名；知识库编号和发布类型优先取自 `.mum` 内容，
+    /// 内容无法读取（例如位于压缩资源中）时只使用文件名中的信息。
+    pub fn list_packages(&mut self, index: u32) -> Result<Vec<ServicingPackage>> {
+        let root = self.read_metadata_root(index)?;
+
+        let mut entries = Vec::new();
+        if let Some(dir) = root.find_path(SERVICING_PACKAGES_DIR) {
+            for entry in dir.children.iter().filter(|entry| !entry.is_directory()) {
+                if let Some(package) = ServicingPackage::from_file_name(&entry.name) {
+                    entries.push((package, entry.hash));
+                }
+            }
+        }
+
+        let mut packages = Vec::with_capacity(entries.len());
+        for (mut package, hash) in entries {
+            if hash != [0u8; 20] {
+                match self.read_stream(&hash) {
+                    Ok(data) => package.apply_manifest(&String::from_utf8_lossy(&data)),
+                    Err(e) => debug!("无法读取 {}: {}", package.file_name, e),
+                }
+            }
+            packages.push(package);
+        }
+        packages.sort_by(|a, b| {
+            a.name
+                .to_ascii_lowercase()
+                .cmp(&b.name.to_ascii_lowercase())
+                .then_with(|| a.file_name.cmp(&b.file_name))
+        });
+
+        debug!("镜像 {} 中有 {} 个服务包", index, packages.len());
+        Ok(packages)
+    }
+}
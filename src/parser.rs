@@ -0,0 +1,1213 @@
+use crate::log::{debug, info};
+use anyhow::{Context, Result};
+use std::fs::File;
+use std::io::{BufReader, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+// 性能优化导入
+use encoding_rs::UTF_16LE;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+
+use crate::cache::{CacheKey, WimCatalogCache};
+use crate::lookup_table::{self, LookupTableEntry};
+use crate::metadata;
+use crate::options::ParseOptions;
+use crate::resource_reader::CachedChunk;
+use crate::segment::SegmentInfo;
+use crate::solid::SolidIndex;
+use crate::temp::TempPolicy;
+use crate::{
+    format, Arch, Compression, Error, FileFlags, FileResourceEntry, HeaderField, ImageInfo,
+    MediaKind, VersionRules, Warning, WimHeader, WimKind, WindowsInfo, XmlElement,
+};
+
+/// 字符串池用于减少内存分配
+#[derive(Debug)]
+struct StringPool {
+    pool: Vec<String>,
+    index: usize,
+}
+
+#[allow(dead_code)]
+impl StringPool {
+    fn new() -> Self {
+        Self {
+            pool: Vec::with_capacity(32), // 预分配32个字符串
+            index: 0,
+        }
+    }
+
+    fn get_string(&mut self) -> &mut String {
+        if self.index >= self.pool.len() {
+            self.pool.push(String::with_capacity(256)); // 预分配容量
+        }
+        let string = &mut self.pool[self.index];
+        string.clear();
+        self.index += 1;
+        string
+    }
+
+    fn reset(&mut self) {
+        self.index = 0;
+        // 保留字符串对象，只重置索引
+    }
+}
+
+/// WIM 文件解析器
+#[allow(dead_code)]
+pub struct WimParser {
+    pub(crate) file: BufReader<File>,
+    pub(crate) path: Option<PathBuf>,
+    pub(crate) header: Option<WimHeader>,
+    pub(crate) images: Vec<ImageInfo>,
+    pub(crate) lookup_table: Option<Arc<Vec<LookupTableEntry>>>,
+    string_pool: StringPool,
+    cache: Option<Arc<WimCatalogCache>>,
+    version_rules: Option<Arc<VersionRules>>,
+    /// WIM 数据在文件中的起始偏移（嵌入在其他文件中时非零），资源偏移均相对于此
+    base_offset: u64,
+    options: ParseOptions,
+    windows_metadata_loaded: bool,
+    warnings: Vec<Warning>,
+    pub(crate) segments: Vec<SegmentInfo>,
+    /// 固实资源中各数据流的位置（首次读取固实资源时建立）
+    pub(crate) solid_index: Option<Arc<SolidIndex>>,
+    /// 最近解压的固实资源分块
+    pub(crate) solid_chunk: Option<CachedChunk>,
+    /// 重写文件时的临时文件策略
+    pub(crate) temp_policy: TempPolicy,
+}
+
+#[allow(dead_code)]
+impl WimParser {
+    /// 创建新的 WIM 解析器
+    ///
+    /// ```
+    /// # #[cfg(feature = "fixtures")] {
+    /// # let fixture = wim_parser::fixtures::MiniWim::create()?;
+    /// use wim_parser::WimParser;
+    ///
+    /// let mut parser = WimParser::new(fixture.path())?;
+    /// assert_eq!(parser.read_header()?.image_count, 2);
+    /// # }
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    pub fn new<P: AsRef<Path>>(wim_path: P) -> Result<Self> {
+        let file = File::open(wim_path.as_ref())
+            .with_context(|| format!("无法打开 WIM 文件: {}", wim_path.as_ref().display()))?;
+
+        let buffered_file = BufReader::with_capacity(64 * 1024, file); // 64KB缓冲区
+
+        debug!("创建 WIM 解析器: {}", wim_path.as_ref().display());
+
+        Ok(Self {
+            file: buffered_file,
+            path: Some(wim_path.as_ref().to_path_buf()),
+            header: None,
+            images: Vec::with_capacity(8), // 预分配镜像容量
+            lookup_table: None,
+            string_pool: StringPool::new(),
+            cache: None,
+            version_rules: None,
+            base_offset: 0,
+            options: ParseOptions::default(),
+            windows_metadata_loaded: false,
+            warnings: Vec::new(),
+            segments: Vec::new(),
+            solid_index: None,
+            solid_chunk: None,
+            temp_policy: TempPolicy::default(),
+        })
+    }
+
+    /// 创建读取嵌入在其他文件中的 WIM 的解析器
+    ///
+    /// `offset` 为 WIM 文件头在文件中的位置；文件头和偏移表中的所有资源偏移都相对于该位置。
+    /// 适用于在固件更新包等容器文件中间嵌入 WIM 的情况，偏移未知时可先用
+    /// [`find_embedded`](Self::find_embedded) 查找。
+    pub fn new_at<P: AsRef<Path>>(wim_path: P, offset: u64) -> Result<Self> {
+        let mut parser = Self::new(wim_path)?;
+        parser.base_offset = offset;
+        debug!("WIM 数据起始偏移: {}", offset);
+        Ok(parser)
+    }
+
+    /// 在文件中查找第一个嵌入的 WIM，返回其文件头的偏移（没有找到时返回 `None`）
+    ///
+    /// 扫描 `MSWIM` 签名，并要求签名处的文件头大小为 208 字节且引用了 XML 数据资源，
+    /// 以排除偶然出现的签名字节。查找所有 WIM 见 [`carve`](crate::carve)。
+    pub fn find_embedded<P: AsRef<Path>>(path: P) -> Result<Option<u64>> {
+        let path = path.as_ref();
+        let file = File::open(path).with_context(|| format!("无法打开文件: {}", path.display()))?;
+        let carved = crate::carve(BufReader::new(file))?.next().transpose()?;
+        Ok(carved.map(|carved| carved.offset))
+    }
+
+    /// WIM 数据在文件中的起始偏移（由 [`new_at`](Self::new_at) 指定，普通 WIM 文件为 0）
+    pub fn base_offset(&self) -> u64 {
+        self.base_offset
+    }
+
+    /// 定位到相对 WIM 数据起始处的偏移
+    pub(crate) fn seek_to(&mut self, offset: u64) -> Result<()> {
+        self.file.seek(SeekFrom::Start(self.base_offset + offset))?;
+        Ok(())
+    }
+
+    /// 确认 WIM 不是嵌入在其他文件中（原地写入的操作只支持独立的 WIM 文件）
+    pub(crate) fn ensure_standalone(&self, action: &str) -> Result<()> {
+        if self.base_offset != 0 {
+            return Err(
+                anyhow::Error::new(Error::Unsupported("修改嵌入的 WIM")).context(format!(
+                    "WIM 嵌入在文件偏移 {} 处，无法{}",
+                    self.base_offset, action
+                )),
+            );
+        }
+        Ok(())
+    }
+
+    /// 创建使用共享元数据缓存的 WIM 解析器
+    pub fn with_cache<P: AsRef<Path>>(wim_path: P, cache: Arc<WimCatalogCache>) -> Result<Self> {
+        let mut parser = Self::new(wim_path)?;
+        parser.cache = Some(cache);
+        Ok(parser)
+    }
+
+    /// 创建使用指定解析选项的 WIM 解析器
+    pub fn with_options<P: AsRef<Path>>(wim_path: P, options: ParseOptions) -> Result<Self> {
+        let mut parser = Self::new(wim_path)?;
+        parser.options = options;
+        Ok(parser)
+    }
+
+    /// 当前解析选项
+    pub fn options(&self) -> ParseOptions {
+        self.options
+    }
+
+    /// 设置解析选项（对之后的解析生效）
+    pub fn set_options(&mut self, options: ParseOptions) {
+        self.options = options;
+    }
+
+    /// 设置重写文件（提交事务）时的临时文件策略（见 [`TempPolicy`]）
+    pub fn set_temp_policy(&mut self, policy: TempPolicy) {
+        self.temp_policy = policy;
+    }
+
+    /// 当前临时文件策略
+    pub fn temp_policy(&self) -> &TempPolicy {
+        &self.temp_policy
+    }
+
+    /// 设置共享元数据缓存
+    pub fn set_cache(&mut self, cache: Arc<WimCatalogCache>) {
+        self.cache = Some(cache);
+    }
+
+    /// 设置自定义版本推断规则（可在多个解析器间共享）
+    ///
+    /// 对已加载 Windows 元数据的镜像立即生效，之后的解析也使用这些规则。
+    pub fn set_version_rules(&mut self, rules: Arc<VersionRules>) {
+        self.version_rules = Some(rules);
+        if self.windows_metadata_loaded {
+            self.apply_version_rules();
+        }
+    }
+
+    /// 用自定义版本规则（如果设置了）重新推断所有镜像的版本
+    fn apply_version_rules(&mut self) {
+        if let Some(rules) = &self.version_rules {
+            for image in &mut self.images {
+                image.apply_version_rules(rules);
+            }
+        }
+    }
+
+    /// 当前文件的缓存键（需要已读取文件头）
+    pub fn cache_key(&self) -> Option<CacheKey> {
+        let header = self.header.as_ref()?;
+        let metadata = self.file.get_ref().metadata().ok()?;
+        Some(CacheKey {
+            guid: header.guid,
+            part_number: header.segment_number,
+            file_size: metadata.len(),
+            mtime: metadata.modified().ok()?,
+        })
+    }
+
+    /// 创建用于测试的 WIM 解析器（不需要实际文件）
+    #[doc(hidden)]
+    #[allow(dead_code)]
+    pub fn new_for_test(file: File) -> Self {
+        Self {
+            file: BufReader::new(file),
+            path: None,
+            header: None,
+            images: Vec::with_capacity(8),
+            lookup_table: None,
+            string_pool: StringPool::new(),
+            cache: None,
+            version_rules: None,
+            base_offset: 0,
+            options: ParseOptions::default(),
+            windows_metadata_loaded: false,
+            warnings: Vec::new(),
+            segments: Vec::new(),
+            solid_index: None,
+            solid_chunk: None,
+            temp_policy: TempPolicy::default(),
+        }
+    }
+
+    /// 重新打开文件并清除已解析的内容（文件被替换后调用）
+    pub(crate) fn reopen(&mut self) -> Result<()> {
+        let path = self
+            .path
+            .clone()
+            .ok_or_else(|| anyhow::anyhow!("解析器没有关联的文件路径"))?;
+        let file =
+            File::open(&path).with_context(|| format!("无法打开 WIM 文件: {}", path.display()))?;
+        self.file = BufReader::with_capacity(64 * 1024, file);
+        self.header = None;
+        self.images.clear();
+        self.lookup_table = None;
+        self.solid_index = None;
+        self.solid_chunk = None;
+        self.windows_metadata_loaded = false;
+        self.warnings.clear();
+        Ok(())
+    }
+
+    /// 读取并解析 WIM 文件头
+    ///
+    /// ```
+    /// # #[cfg(feature = "fixtures")] {
+    /// # let fixture = wim_parser::fixtures::MiniWim::create()?;
+    /// # let mut parser = wim_parser::WimParser::new(fixture.path())?;
+    /// use wim_parser::FileFlags;
+    ///
+    /// let header = parser.read_header()?;
+    /// assert_eq!(header.image_count, 2);
+    /// assert!(!header.flags().contains(FileFlags::COMPRESSION));
+    /// # }
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    pub fn read_header(&mut self) -> Result<&WimHeader> {
+        if let Some(ref header) = self.header {
+            return Ok(header);
+        }
+
+        debug!("开始读取 WIM 文件头");
+
+        // 跳转到 WIM 数据开始
+        self.seek_to(0)?;
+
+        // 读取 204 字节的文件头
+        let mut header_buffer = vec![0u8; 204];
+        self.file
+            .read_exact(&mut header_buffer)
+            .context("读取 WIM 文件头失败")?;
+
+        let header = format::parse_header(&header_buffer)?;
+
+        let flags = header.flags();
+        if flags.unknown_bits() != 0 {
+            self.warn(Warning::UnknownHeaderFlags {
+                bits: flags.unknown_bits(),
+            })?;
+        }
+        if flags.contains(FileFlags::WRITE_IN_PROGRESS) {
+            self.warn(Warning::WriteInProgress)?;
+        }
+        if header.spanned_flag_mismatch() {
+            self.warn(Warning::SpannedFlagMismatch {
+                total_segments: header.total_segments,
+                flagged: flags.contains(FileFlags::SPANNED),
+            })?;
+        }
+
+        for (kind, resource) in header.resources() {
+            let bits = resource.resource_flags().unknown_bits();
+            if bits != 0 {
+                self.warn(Warning::UnknownResourceFlags { kind, bits })?;
+            }
+        }
+
+        debug!(
+            "解析 WIM 头部完成 - 镜像数: {}, 文件标志: 0x{:08X}",
+            header.image_count, header.file_flags
+        );
+
+        info!(
+            "成功读取 WIM 文件头 - 版本: {}, 镜像数: {}",
+            header.format_version, header.image_count
+        );
+
+        self.header = Some(header);
+        Ok(self.header.as_ref().unwrap())
+    }
+
+    /// 磁盘上文件头的字段布局：按原始字节（最多 208 字节）划分，含 `reserved` 保留区域
+    ///
+    /// 与 [`WimHeader::field_layout`] 不同，报告的是文件中实际的字节，可以看出保留区域中的非零字节。
+    pub fn header_layout(&mut self) -> Result<Vec<HeaderField>> {
+        self.read_header()?;
+        self.seek_to(0)?;
+        let mut raw = Vec::with_capacity(format::WIM_HEADER_DISK_SIZE);
+        (&mut self.file)
+            .take(format::WIM_HEADER_DISK_SIZE as u64)
+            .read_to_end(&mut raw)
+            .context("读取 WIM 文件头失败")?;
+        Ok(HeaderField::layout(&raw))
+    }
+
+    /// 文件种类（普通、仅资源、仅元数据或没有镜像），见 [`WimKind`]
+    ///
+    /// 仅资源和没有镜像的 WIM 可以正常解析，镜像列表为空。
+    ///
+    /// ```
+    /// # #[cfg(feature = "fixtures")] {
+    /// # let fixture = wim_parser::fixtures::MiniWim::create()?;
+    /// # let mut parser = wim_parser::WimParser::new(fixture.path())?;
+    /// assert_eq!(parser.wim_kind()?, wim_parser::WimKind::Standard);
+    /// # }
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    pub fn wim_kind(&mut self) -> Result<WimKind> {
+        Ok(self.read_header()?.kind())
+    }
+
+    /// 解析过程中收集的警告（按发现顺序，不重复）
+    pub fn warnings(&self) -> &[Warning] {
+        &self.warnings
+    }
+
+    /// 记录警告；严格模式下作为错误返回
+    fn warn(&mut self, warning: Warning) -> Result<()> {
+        if self.options.is_strict() {
+            return Err(anyhow::anyhow!("严格模式: {}", warning));
+        }
+        debug!("解析警告: {}", warning);
+        if !self.warnings.contains(&warning) {
+            self.warnings.push(warning);
+        }
+        Ok(())
+    }
+
+    /// 检查单分卷文件中设置了 `SPANNED` 标志的资源条目（文件头和偏移表）
+    fn check_spanned_resources(&mut self) -> Result<()> {
+        let Some(header) = self.header.as_ref().filter(|header| !header.is_split()) else {
+            return Ok(());
+        };
+        let count = header
+            .resources()
+            .map(|(_, resource)| resource)
+            .chain(
+                self.lookup_table
+                    .iter()
+                    .flat_map(|entries| entries.iter().map(|entry| &entry.resource)),
+            )
+            .filter(|resource| resource.is_spanned())
+            .count();
+        if count > 0 {
+            self.warn(Warning::SpannedResources { count })?;
+        }
+        Ok(())
+    }
+
+    /// 检查 XML 解析结果中的容错恢复和不一致之处
+    fn check_xml_warnings(&mut self, xml_content: &str) -> Result<()> {
+        let mut warnings = Vec::new();
+
+        let image_nodes = format::count_image_nodes(xml_content);
+        if image_nodes > self.images.len() {
+            warnings.push(Warning::UnclosedImage {
+                count: image_nodes - self.images.len(),
+            });
+        }
+
+        for (position, image) in self.images.iter().enumerate() {
+            if image.index == 0 {
+                warnings.push(Warning::InvalidImageIndex {
+                    position: position + 1,
+                });
+            }
+            if let Some(value) = image.arch_raw {
+                if !Arch::from_raw(value).is_known() {
+                    warnings.push(Warning::UnknownArch {
+                        index: image.index,
+                        value,
+                    });
+                }
+            }
+        }
+
+        // 只有从文件读取 XML 时才有文件头可比对
+        if let Some(header) = &self.header {
+            if header.image_count as usize != self.images.len() {
+                warnings.push(Warning::ImageCountMismatch {
+                    header: header.image_count,
+                    xml: self.images.len(),
+                });
+            }
+        }
+
+        for warning in warnings {
+            self.warn(warning)?;
+        }
+        Ok(())
+    }
+
+    /// 读取并解析 XML 数据
+    pub fn read_xml_data(&mut self) -> Result<()> {
+        let xml_buffer = self.read_xml_buffer()?;
+
+        // 解析 XML 数据
+        self.parse_xml_data(&xml_buffer)?;
+        self.windows_metadata_loaded = self.options.windows_metadata_enabled();
+        if self.windows_metadata_loaded {
+            self.apply_version_rules();
+        }
+
+        info!("成功解析 {} 个镜像的信息", self.images.len());
+        Ok(())
+    }
+
+    /// 读取 XML 数据资源的原始字节
+    pub(crate) fn read_xml_buffer(&mut self) -> Result<Vec<u8>> {
+        // 确保文件头已读取
+        if self.header.is_none() {
+            self.read_header()?;
+        }
+
+        let header = self.header.as_ref().unwrap();
+        let resource = header.xml_data_resource.clone();
+
+        // 检查 XML 数据资源是否存在；没有镜像的数据 WIM 可以不带 XML，视为空的镜像列表
+        if resource.is_absent() {
+            let kind = header.kind();
+            if !kind.has_images() || header.image_count == 0 {
+                debug!("{} WIM 没有 XML 数据资源，镜像列表为空", kind);
+                return Ok(format::encode_xml_utf16("<WIM></WIM>"));
+            }
+            return Err(anyhow::anyhow!("WIM 文件中没有 XML 数据资源"));
+        }
+
+        debug!(
+            "开始读取 XML 数据，偏移: {}, 大小: {}, 原始大小: {}, 压缩: {}",
+            resource.offset,
+            resource.size,
+            resource.original_size,
+            resource.is_compressed()
+        );
+
+        let limits = self.options.resource_limits();
+        limits.check_xml_bytes(resource.size.max(resource.original_size))?;
+
+        // 压缩的 XML 资源先解压，再进行 BOM 检查
+        let xml_buffer = self.read_resource(&resource).context("读取 XML 数据失败")?;
+
+        // 解压（或读取）后的长度必须与资源头记录的原始大小一致
+        if resource.original_size != 0 && xml_buffer.len() as u64 != resource.original_size {
+            return Err(anyhow::anyhow!(
+                "XML 数据长度 ({}) 与资源原始大小 ({}) 不一致",
+                xml_buffer.len(),
+                resource.original_size
+            ));
+        }
+
+        Ok(xml_buffer)
+    }
+
+    /// 按需补全 Windows 元数据（版本和架构）
+    ///
+    /// 用于以 [`ParseOptions::parse_windows_metadata`] 关闭的方式解析之后。已加载时直接返回；
+    /// 对已有镜像只补全版本和架构字段，不会覆盖其他已修改的字段。
+    pub fn load_windows_metadata(&mut self) -> Result<()> {
+        if self.windows_metadata_loaded {
+            return Ok(());
+        }
+
+        let xml_buffer = self.read_xml_buffer()?;
+        let xml_string = format::decode_xml_utf16(&xml_buffer)?;
+        let full_images = format::parse_images_from_xml(&xml_string);
+
+        if self.images.is_empty() {
+            self.images = full_images;
+        } else {
+            for image in &mut self.images {
+                if let Some(full) = full_images.iter().find(|full| full.index == image.index) {
+                    image.copy_windows_metadata_from(full);
+                }
+            }
+        }
+
+        self.check_xml_warnings(&xml_string)?;
+        self.windows_metadata_loaded = true;
+        self.apply_version_rules();
+        debug!("已补全 {} 个镜像的 Windows 元数据", self.images.len());
+        Ok(())
+    }
+
+    /// 镜像的 Windows 元数据（版本和架构）是否已加载
+    pub fn has_windows_metadata(&self) -> bool {
+        self.windows_metadata_loaded
+    }
+
+    /// 读取资源的完整数据（压缩资源经 [`open_resource`](Self::open_resource) 逐块读取）
+    pub(crate) fn read_resource(&mut self, resource: &FileResourceEntry) -> Result<Vec<u8>> {
+        if resource.is_absent() {
+            return Err(anyhow::anyhow!(
+                "资源不存在 (标志: {}, 偏移: {})",
+                resource.resource_flags(),
+                resource.offset
+            ));
+        }
+
+        if resource.is_compressed() || resource.is_solid() {
+            self.options
+                .resource_limits()
+                .check_memory(resource.original_size)?;
+            let mut buffer = Vec::with_capacity(resource.original_size as usize);
+            self.open_resource(resource)?
+                .read_to_end(&mut buffer)
+                .with_context(|| {
+                    format!(
+                        "读取压缩资源失败 (偏移: {}, 大小: {})",
+                        resource.offset, resource.size
+                    )
+                })?;
+            return Ok(buffer);
+        }
+
+        self.options.resource_limits().check_memory(resource.size)?;
+        self.seek_to(resource.offset)?;
+
+        let mut buffer = vec![0u8; resource.size as usize];
+        self.file
+            .read_exact(&mut buffer)
+            .with_context(|| format!("读取资源数据失败，偏移: {}", resource.offset))?;
+
+        Ok(buffer)
+    }
+
+    /// 读取并解析偏移表（查找表）
+    pub(crate) fn read_lookup_table(&mut self) -> Result<&[LookupTableEntry]> {
+        let loaded = self.lookup_table.is_some();
+        if self.lookup_table.is_none() {
+            self.read_header()?;
+            if let Some((cache, key)) = self.cache.as_ref().zip(self.cache_key()) {
+                self.lookup_table = cache.get_lookup_table(&key);
+            }
+        }
+
+        if self.lookup_table.is_none() {
+            let resource = self.read_header()?.offset_table_resource.clone();
+
+            debug!(
+                "开始读取偏移表，偏移: {}, 大小: {}",
+                resource.offset, resource.size
+            );
+
+            let data = self.read_resource(&resource).context("读取偏移表失败")?;
+            let entries = lookup_table::parse_lookup_table(&data);
+
+            debug!("偏移表共 {} 个条目", entries.len());
+            let entries = Arc::new(entries);
+            if let Some((cache, key)) = self.cache.as_ref().zip(self.cache_key()) {
+                cache.put_lookup_table(key, entries.clone());
+            }
+            self.lookup_table = Some(entries);
+        }
+        if !loaded {
+            self.check_spanned_resources()?;
+        }
+
+        Ok(self
+            .lookup_table
+            .as_deref()
+            .map(Vec::as_slice)
+            .unwrap_or_default())
+    }
+
+    /// 偏移表中的所有条目（按磁盘顺序，不含不存在的条目），可传给
+    /// [`open_resource`](Self::open_resource) 或 `verify_resource`
+    ///
+    /// ```
+    /// # #[cfg(feature = "fixtures")] {
+    /// # let fixture = wim_parser::fixtures::MiniWim::create()?;
+    /// # let mut parser = wim_parser::WimParser::new(fixture.path())?;
+    /// let entries = parser.lookup_table_entries()?;
+    /// // 每个镜像一个元数据资源，其余为去重后的文件内容
+    /// assert_eq!(entries.iter().filter(|entry| entry.is_metadata()).count(), 2);
+    /// # }
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    pub fn lookup_table_entries(&mut self) -> Result<Vec<LookupTableEntry>> {
+        Ok(self.read_lookup_table()?.to_vec())
+    }
+
+    /// 按 SHA-1 读取数据流内容（压缩资源逐块解压，支持 XPRESS、LZX、LZMS 和固实资源）
+    ///
+    /// ```
+    /// # #[cfg(feature = "fixtures")] {
+    /// # let fixture = wim_parser::fixtures::MiniWim::create()?;
+    /// # let mut parser = wim_parser::WimParser::new(fixture.path())?;
+    /// let metadata = parser.read_image_metadata(1)?;
+    /// let readme = metadata.root.find("readme.txt").unwrap();
+    /// assert_eq!(parser.read_stream(&readme.hash)?, b"Windows 10 Home\r\n");
+    /// # }
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    pub fn read_stream(&mut self, hash: &[u8; 20]) -> Result<Vec<u8>> {
+        let resource = self
+            .read_lookup_table()?
+            .iter()
+            .find(|entry| !entry.is_metadata() && entry.hash == *hash)
+            .map(|entry| entry.resource.clone())
+            .ok_or_else(|| anyhow::anyhow!("偏移表中找不到数据流"))?;
+
+        self.read_stream_resource(&resource)
+    }
+
+    /// 读取偏移表条目指向的数据流资源（资源须位于当前分卷）
+    pub(crate) fn read_stream_resource(&mut self, resource: &FileResourceEntry) -> Result<Vec<u8>> {
+        let location = self.resolve_resource(resource)?;
+        self.ensure_local_segment(&location)?;
+        self.read_resource(resource)
+    }
+
+    /// 查找指定镜像的元数据资源条目
+    pub(crate) fn metadata_resource(&mut self, index: u32) -> Result<FileResourceEntry> {
+        // 元数据资源在偏移表中的出现顺序即镜像顺序（索引从 1 开始）
+        self.read_lookup_table()?
+            .iter()
+            .filter(|entry| entry.is_metadata())
+            .nth((index as usize).wrapping_sub(1))
+            .map(|entry| entry.resource.clone())
+            .ok_or_else(|| anyhow::anyhow!("找不到镜像 {} 的元数据资源", index))
+    }
+
+    /// 读取指定镜像的元数据资源（已解压）
+    pub(crate) fn read_metadata_bytes(&mut self, index: u32) -> Result<Vec<u8>> {
+        let resource = self.metadata_resource(index)?;
+        let location = self.resolve_resource(&resource)?;
+        self.ensure_local_segment(&location)?;
+
+        self.read_resource(&resource)
+            .with_context(|| format!("读取镜像 {index} 的元数据资源失败"))
+    }
+
+    /// 读取并解析指定镜像的元数据资源，返回根目录项
+    pub(crate) fn read_metadata_root(&mut self, index: u32) -> Result<metadata::DirEntry> {
+        let data = self.read_metadata_bytes(index)?;
+
+        metadata::parse_metadata_resource(&data, &self.options.resource_limits())
+            .with_context(|| format!("解析镜像 {index} 的元数据资源失败"))
+    }
+
+    /// 解析 XML 数据
+    fn parse_xml_data(&mut self, xml_buffer: &[u8]) -> Result<()> {
+        // 将 UTF-16 LE 转换为 UTF-8
+        let xml_string = format::decode_xml_utf16(xml_buffer)?;
+
+        debug!("XML 数据长度: {} 字符", xml_string.len());
+
+        // 解析 XML 镜像信息
+        self.parse_xml_images(&xml_string)?;
+        self.check_xml_warnings(&xml_string)?;
+
+        Ok(())
+    }
+
+    /// 优化的XML解析函数 - 使用proper XML parser和高效UTF-16解码
+    fn parse_xml_data_optimized(&mut self, xml_buffer: &[u8]) -> Result<()> {
+        // 检查基本格式
+        if xml_buffer.len() < 2 {
+            return Err(anyhow::anyhow!("XML 数据太短"));
+        }
+
+        // 检查 BOM (0xFEFF)
+        if xml_buffer[0] != 0xFF || xml_buffer[1] != 0xFE {
+            return Err(anyhow::anyhow!("无效的 XML 数据 BOM"));
+        }
+
+        // 使用encoding_rs进行高效UTF-16解码
+        let (xml_string, _, had_errors) = UTF_16LE.decode(&xml_buffer[2..]);
+        if had_errors {
+            return Err(anyhow::anyhow!("UTF-16解码过程中发现错误"));
+        }
+
+        debug!("XML 数据长度: {} 字符", xml_string.len());
+
+        // 使用quick-xml进行解析
+        self.parse_xml_images_optimized(&xml_string)?;
+
+        Ok(())
+    }
+
+    /// 优化的XML镜像解析函数 - 使用quick-xml
+    fn parse_xml_images_optimized(&mut self, xml_content: &str) -> Result<()> {
+        self.images.clear();
+
+        let mut reader = Reader::from_str(xml_content);
+        reader.config_mut().trim_text(true);
+
+        let mut current_image: Option<ImageInfo> = None;
+        let mut image_start = 0;
+        let mut current_tag = String::new();
+        let mut in_windows_section = false;
+        let windows_metadata = self.options.windows_metadata_enabled();
+
+        loop {
+            let event_start = reader.buffer_position() as usize;
+            match reader.read_event() {
+                Ok(Event::Start(ref e)) => {
+                    // 按本地名匹配，忽略 OEM 工具添加的命名空间前缀
+                    match e.local_name().as_ref() {
+                        b"IMAGE" => {
+                            image_start = event_start;
+                            current_image = Some(ImageInfo::new_with_index(image_index_attr(e)));
+                        }
+                        b"WINDOWS" => {
+                            in_windows_section = true;
+                        }
+                        tag => {
+                            current_tag = String::from_utf8_lossy(tag).into_owned();
+                        }
+                    }
+                }
+                // 自闭合的 <IMAGE/> 没有内容，其他自闭合标签表示空字段
+                Ok(Event::Empty(ref e)) if e.local_name().as_ref() == b"IMAGE" => {
+                    let image_end = reader.buffer_position() as usize;
+                    let mut image = ImageInfo::new_with_index(image_index_attr(e));
+                    image.xml = XmlElement::parse(&xml_content[event_start..image_end]);
+                    self.images.push(image);
+                }
+                Ok(Event::Text(e)) => {
+                    if let Some(ref mut image) = current_image {
+                        // 获取文本内容
+                        let text = std::str::from_utf8(&e)?;
+
+                        // 特殊处理WINDOWS节中的标签
+                        if in_windows_section
+                            && windows_metadata
+                            && matches!(
+                                current_tag.as_str(),
+                                "ARCH"
+                                    | "PRODUCTTYPE"
+                                    | "INSTALLATIONTYPE"
+                                    | "EDITIONID"
+                                    | "BUILD"
+                                    | "SPBUILD"
+                                    | "LANGUAGE"
+                                    | "DEFAULT"
+                            )
+                        {
+                            image.set_field(&current_tag, text);
+                        } else if !in_windows_section {
+                            // 其他标签在非WINDOWS节中处理
+                            image.set_field(&current_tag, text);
+                        }
+                    }
+                }
+                Ok(Event::End(ref e)) => {
+                    match e.local_name().as_ref() {
+                        b"IMAGE" => {
+                            if let Some(mut image) = current_image.take() {
+                                let image_end = reader.buffer_position() as usize;
+                                image.xml = XmlElement::parse(&xml_content[image_start..image_end]);
+                                // 推断版本和架构信息（如果尚未设置）
+                                if windows_metadata {
+                                    image.infer_version_and_arch();
+                                }
+                                self.images.push(image);
+                            }
+                        }
+                        b"WINDOWS" => {
+                            in_windows_section = false;
+                        }
+                        _ => {}
+                    }
+                }
+                Ok(Event::Eof) => break,
+                Err(e) => return Err(anyhow::anyhow!("XML解析错误: {}", e)),
+                _ => {}
+            }
+        }
+
+        info!("优化解析完成：成功解析 {} 个镜像的信息", self.images.len());
+        Ok(())
+    }
+
+    /// 解析 XML 中的镜像信息
+    fn parse_xml_images(&mut self, xml_content: &str) -> Result<()> {
+        // 简单的 XML 解析（基于字符串匹配）
+        // 在实际生产环境中，建议使用专门的 XML 解析库
+        self.images = format::parse_images_from_xml_with(
+            xml_content,
+            self.options.windows_metadata_enabled(),
+        );
+
+        for image_info in &self.images {
+            debug!(
+                "解析镜像信息: {} - {} - {} - {:#?}",
+                image_info.index, image_info.name, image_info.description, image_info.architecture
+            );
+        }
+
+        Ok(())
+    }
+
+    /// 解析单个镜像的 XML 信息
+    pub fn parse_single_image_xml(&self, image_xml: &str) -> Result<ImageInfo> {
+        Ok(format::parse_single_image_xml(image_xml))
+    }
+
+    /// 从XML中的ARCH标签解析架构信息
+    pub fn parse_arch_from_xml(&self, image_xml: &str) -> Option<String> {
+        format::parse_arch_from_xml(image_xml)
+    }
+
+    /// 获取所有镜像信息
+    ///
+    /// ```
+    /// # #[cfg(feature = "fixtures")] {
+    /// # let fixture = wim_parser::fixtures::MiniWim::create()?;
+    /// # let mut parser = wim_parser::WimParser::new(fixture.path())?;
+    /// parser.parse_full()?;
+    /// let names: Vec<&str> = parser.get_images().iter().map(|image| image.name.as_str()).collect();
+    /// assert_eq!(names, ["Windows 10 Home", "Windows 10 Pro"]);
+    /// # }
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    pub fn get_images(&self) -> &[ImageInfo] {
+        &self.images
+    }
+
+    /// 获取指定索引的镜像信息
+    ///
+    /// ```
+    /// # #[cfg(feature = "fixtures")] {
+    /// # let fixture = wim_parser::fixtures::MiniWim::create()?;
+    /// # let mut parser = wim_parser::WimParser::new(fixture.path())?;
+    /// parser.parse_full()?;
+    /// let pro = parser.get_image(2).unwrap();
+    /// assert_eq!(pro.edition_id.as_deref(), Some("Professional"));
+    /// assert_eq!(pro.build, Some(19045));
+    /// assert!(parser.get_image(3).is_none());
+    /// # }
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    #[allow(dead_code)]
+    pub fn get_image(&self, index: u32) -> Option<&ImageInfo> {
+        self.images.iter().find(|img| img.index == index)
+    }
+
+    /// 获取文件头信息
+    #[allow(dead_code)]
+    pub fn get_header(&self) -> Option<&WimHeader> {
+        self.header.as_ref()
+    }
+
+    /// 检查是否包含多个镜像
+    #[allow(dead_code)]
+    pub fn has_multiple_images(&self) -> bool {
+        self.header
+            .as_ref()
+            .map(|h| h.image_count > 1)
+            .unwrap_or(false)
+    }
+
+    /// 获取镜像数量
+    #[allow(dead_code)]
+    pub fn get_image_count(&self) -> u32 {
+        self.header.as_ref().map(|h| h.image_count).unwrap_or(0)
+    }
+
+    /// 检查是否为压缩文件
+    #[allow(dead_code)]
+    pub fn is_compressed(&self) -> bool {
+        self.header
+            .as_ref()
+            .map(|h| h.file_flags & FileFlags::COMPRESSION != 0)
+            .unwrap_or(false)
+    }
+
+    /// 获取压缩类型
+    #[deprecated(note = "请使用 compression()，它同时提供分块大小")]
+    pub fn get_compression_type(&self) -> Option<&'static str> {
+        match self.compression() {
+            Compression::None => None,
+            compression => Some(compression.name()),
+        }
+    }
+
+    /// 获取文件头声明的压缩格式（文件头未读取时为 [`Compression::None`]）
+    pub fn compression(&self) -> Compression {
+        self.header
+            .as_ref()
+            .map(WimHeader::compression)
+            .unwrap_or(Compression::None)
+    }
+
+    /// 获取单个资源实际使用的压缩格式
+    ///
+    /// 固实资源（ESD）在资源开头保存自己的压缩格式和分块大小，同一文件中不同资源可能使用不同格式。
+    pub fn resource_compression(&mut self, resource: &FileResourceEntry) -> Result<Compression> {
+        let header = self.read_header()?.clone();
+
+        if !resource.is_solid() {
+            return Ok(Compression::for_resource(&header, resource));
+        }
+
+        // 固实资源中的数据流使用所在固实资源头中的格式
+        let solid = if resource.is_solid_resource() {
+            resource.clone()
+        } else {
+            self.locate_solid_stream(resource)?.resource
+        };
+        Ok(self.read_solid_header(&solid)?.compression)
+    }
+
+    /// 完整解析 WIM 文件（头部 + XML 数据）
+    ///
+    /// 解析深度由 [`ParseOptions`] 控制，默认读取文件头和全部镜像信息。
+    ///
+    /// ```
+    /// # #[cfg(feature = "fixtures")] {
+    /// # let fixture = wim_parser::fixtures::MiniWim::create()?;
+    /// use wim_parser::WimParser;
+    ///
+    /// let mut parser = WimParser::new(fixture.path())?;
+    /// parser.parse_full()?;
+    /// for image in parser.get_images() {
+    ///     println!("{image}");
+    /// }
+    /// # }
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    pub fn parse_full(&mut self) -> Result<()> {
+        self.read_header()?;
+
+        if !self.options.images_enabled() {
+            debug!("解析选项已关闭镜像列表，只读取文件头");
+            return Ok(());
+        }
+
+        let cache = self.cache.clone().zip(self.cache_key());
+        if let Some((cache, key)) = &cache {
+            if let Some((images, warnings)) = cache.get_images(key) {
+                debug!("元数据缓存命中，跳过 XML 解析");
+                // 缓存中只保存完整解析的结果；解析时的警告重放给当前解析器
+                self.images = images.as_ref().clone();
+                for warning in warnings.iter() {
+                    self.warn(warning.clone())?;
+                }
+                self.windows_metadata_loaded = true;
+                self.apply_version_rules();
+                return Ok(());
+            }
+        }
+
+        let known_warnings = self.warnings.len();
+        self.read_xml_data()?;
+
+        if let Some((cache, key)) = cache {
+            if self.windows_metadata_loaded {
+                let warnings = self.warnings[known_warnings..].into();
+                cache.put_images(key, Arc::new(self.images.clone()), warnings);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[allow(dead_code)]
+impl WimParser {
+    /// 获取所有镜像的版本摘要
+    #[allow(dead_code)]
+    pub fn get_version_summary(&self) -> Vec<String> {
+        let mut summaries = Vec::new();
+
+        for image in &self.images {
+            let mut summary = format!("镜像 {}: {}", image.index, image.name);
+
+            if let Some(ref version) = image.version {
+                summary.push_str(&format!(" ({version})"));
+            }
+
+            if let Some(ref arch) = image.architecture {
+                summary.push_str(&format!(" [{arch}]"));
+            }
+
+            summaries.push(summary);
+        }
+
+        summaries
+    }
+
+    /// 获取主要版本信息（如果有多个镜像，返回最常见的版本）
+    pub fn get_primary_version(&self) -> Option<String> {
+        if self.images.is_empty() {
+            return None;
+        }
+
+        // 统计版本出现频率
+        let mut version_counts = std::collections::HashMap::new();
+        for image in &self.images {
+            if let Some(ref version) = image.version {
+                *version_counts.entry(version.clone()).or_insert(0) += 1;
+            }
+        }
+
+        // 找到最常见的版本
+        version_counts
+            .into_iter()
+            .max_by_key(|(_, count)| *count)
+            .map(|(version, _)| version)
+    }
+
+    /// 获取主要架构信息（如果有多个镜像，返回最常见的架构）
+    pub fn get_primary_architecture(&self) -> Option<String> {
+        if self.images.is_empty() {
+            return None;
+        }
+
+        // 统计架构出现频率
+        let mut arch_counts = std::collections::HashMap::new();
+        for image in &self.images {
+            if let Some(ref arch) = image.architecture {
+                *arch_counts.entry(arch.clone()).or_insert(0) += 1;
+            }
+        }
+
+        // 找到最常见的架构
+        arch_counts
+            .into_iter()
+            .max_by_key(|(_, count)| *count)
+            .map(|(arch, _)| arch)
+    }
+
+    /// 所有镜像的架构集合（去重，按首次出现的顺序；没有记录架构的镜像被忽略）
+    pub fn architectures(&self) -> Vec<Arch> {
+        let mut architectures = Vec::new();
+        for arch in self.images.iter().filter_map(ImageInfo::arch) {
+            if !architectures.contains(&arch) {
+                architectures.push(arch);
+            }
+        }
+        architectures
+    }
+
+    /// 按镜像架构对介质分类，同时含多种架构时为 [`MediaKind::MultiArch`]
+    ///
+    /// ```
+    /// # #[cfg(feature = "fixtures")] {
+    /// # let fixture = wim_parser::fixtures::MiniWim::create()?;
+    /// # let mut parser = wim_parser::WimParser::new(fixture.path())?;
+    /// use wim_parser::{Arch, MediaKind};
+    ///
+    /// parser.parse_full()?;
+    /// assert_eq!(parser.media_kind(), MediaKind::SingleArch(Arch::X64));
+    /// # }
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    pub fn media_kind(&self) -> MediaKind {
+        MediaKind::from_architectures(&self.architectures())
+    }
+
+    /// 检查是否包含指定版本的镜像
+    #[allow(dead_code)]
+    pub fn has_version(&self, version: &str) -> bool {
+        self.images.iter().any(|img| {
+            img.version
+                .as_ref()
+                .is_some_and(|v| v.to_lowercase().contains(&version.to_lowercase()))
+        })
+    }
+
+    /// 检查是否包含指定架构的镜像
+    #[allow(dead_code)]
+    pub fn has_architecture(&self, arch: &str) -> bool {
+        self.images.iter().any(|img| {
+            img.architecture
+                .as_ref()
+                .is_some_and(|a| a.to_lowercase().contains(&arch.to_lowercase()))
+        })
+    }
+
+    /// 获取Windows版本的详细信息
+    ///
+    /// ```
+    /// # #[cfg(feature = "fixtures")] {
+    /// # let fixture = wim_parser::fixtures::MiniWim::create()?;
+    /// # let mut parser = wim_parser::WimParser::new(fixture.path())?;
+    /// parser.parse_full()?;
+    /// let info = parser.get_windows_info().unwrap();
+    /// assert_eq!(info.image_count, 2);
+    /// println!("{info}");
+    /// # }
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    pub fn get_windows_info(&self) -> Option<WindowsInfo> {
+        let primary_version = self.get_primary_version()?;
+        let primary_arch = self.get_primary_architecture()?;
+
+        // 检查是否是Windows镜像
+        if !primary_version.to_lowercase().contains("windows") {
+            return None;
+        }
+
+        // 计算总的镜像版本（如Pro, Home, Enterprise等）
+        let mut editions: Vec<String> = Vec::new();
+        for group in self.edition_summary() {
+            if !editions.contains(&group.edition) {
+                editions.push(group.edition);
+            }
+        }
+
+        Some(WindowsInfo {
+            version: primary_version,
+            architecture: primary_arch,
+            editions,
+            image_count: self.images.len() as u32,
+            total_size: self.images.iter().map(|img| img.total_bytes).sum(),
+            images: self.images.clone(),
+            locale: None,
+        })
+    }
+}
+
+/// `<IMAGE>` 开始标签的 `INDEX` 属性（按本地名匹配，属性顺序任意，缺失或无效时为 0）
+fn image_index_attr(start: &BytesStart) -> u32 {
+    start
+        .attributes()
+        .flatten()
+        .find(|attr| attr.key.local_name().as_ref() == b"INDEX")
+        .and_then(|attr| std::str::from_utf8(&attr.value).ok()?.trim().parse().ok())
+        .unwrap_or(0)
+}
+
+// 基准测试和测试辅助函数
+#[cfg(any(test, feature = "benchmarking"))]
+impl WimParser {
+    /// 测试用：直接解析XML数据（当前实现）
+    pub fn parse_xml_data_for_bench(&mut self, xml_buffer: &[u8]) -> Result<()> {
+        self.parse_xml_data(xml_buffer)
+    }
+
+    /// 测试用：直接解析XML数据（优化实现）
+    pub fn parse_xml_data_optimized_for_bench(&mut self, xml_buffer: &[u8]) -> Result<()> {
+        self.parse_xml_data_optimized(xml_buffer)
+    }
+
+    /// 测试用：切换到优化解析模式
+    pub fn use_optimized_parsing(&mut self, xml_buffer: &[u8]) -> Result<()> {
+        self.parse_xml_data_optimized(xml_buffer)
+    }
+}
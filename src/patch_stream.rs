@@ -0,0 +1,172 @@
+//! 服务 WIM 中的增量补丁数据流（DPX 处理的 MSDelta / PA19 补丁和压缩清单）
+//!
+//! Windows 更新和服务映像中的部分文件并不是文件本身，而是需要由 DPX（Delta Package Expander）
+//! 针对基准文件应用的补丁：WinSxS 中 `f\`、`r\` 目录下的正向/反向增量、快速更新包中的
+//! MSDelta 补丁，以及以内置字典压缩的组件清单。这些数据流按原样保存在 WIM 中，导出时原样保留；
+//! 释放时无法还原出原始文件，因此报告 [`Error::UnsupportedStreamType`] 而不是写出补丁字节。
+
+use alloc::string::String;
+use core::fmt;
+
+#[cfg(feature = "parser")]
+use crate::{Error, FileResourceEntry, WimParser};
+
+/// 判断数据流类型所需的最少字节数（CRC32 前缀加签名）
+#[cfg(feature = "parser")]
+pub(crate) const PATCH_SIGNATURE_LEN: usize = 8;
+
+/// 增量补丁数据流的种类
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PatchStreamKind {
+    /// MSDelta 补丁（`PA30` / `PA31`，可能带 4 字节 CRC32 前缀）
+    MsDelta,
+    /// 旧版 MSPatch 补丁（`PA19`）
+    MsPatch,
+    /// WinSxS 压缩清单（`DCM\x01`，以内置字典为基准的 MSDelta）
+    CompressedManifest,
+}
+
+impl PatchStreamKind {
+    /// 种类名称
+    pub fn name(&self) -> &'static str {
+        match self {
+            PatchStreamKind::MsDelta => "MSDelta",
+            PatchStreamKind::MsPatch => "MSPatch",
+            PatchStreamKind::CompressedManifest => "DCM",
+        }
+    }
+
+    /// 由数据流开头的字节判断是否为增量补丁，普通文件返回 `None`
+    pub fn detect(data: &[u8]) -> Option<Self> {
+        let signature = |offset: usize| data.get(offset..offset + 4);
+        match signature(0)? {
+            b"DCM\x01" => return Some(PatchStreamKind::CompressedManifest),
+            b"PA30" | b"PA31" => return Some(PatchStreamKind::MsDelta),
+            b"PA19" => return Some(PatchStreamKind::MsPatch),
+            _ => {}
+        }
+        // WinSxS 增量文件在签名前有 4 字节的 CRC32
+        match signature(4)? {
+            b"PA30" | b"PA31" => Some(PatchStreamKind::MsDelta),
+            b"PA19" => Some(PatchStreamKind::MsPatch),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for PatchStreamKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// 镜像中的一个增量补丁数据流
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PatchStream {
+    /// 文件路径（`/` 分隔，不含开头的 `/`）
+    pub path: String,
+    /// 数据流名称（空字符串表示未命名数据流）
+    pub stream: String,
+    /// 数据流的 SHA-1
+    pub hash: [u8; 20],
+    /// 数据流大小（补丁本身的字节数）
+    pub size: u64,
+    /// 补丁种类
+    pub kind: PatchStreamKind,
+}
+
+/// 数据是增量补丁时返回 [`Error::UnsupportedStreamType`]，供释放文件前检查
+#[cfg(feature = "parser")]
+pub(crate) fn ensure_plain_stream(data: &[u8], path: &str) -> anyhow::Result<()> {
+    match PatchStreamKind::detect(data) {
+        Some(kind) => Err(
+            anyhow::Error::new(Error::UnsupportedStreamType(kind.name())).context(alloc::format!(
+                "{path} 是需要 DPX 应用的 {kind} 补丁数据流，无法直接释放"
+            )),
+        ),
+        None => Ok(()),
+    }
+}
+
+#[cfg(feature = "parser")]
+impl WimParser {
+    /// 读取数据流开头用于判断类型的字节（未压缩资源只读取开头部分）
+    fn read_stream_head(&mut self, resource: &FileResourceEntry) -> anyhow::Result<Vec<u8>> {
+        use std::io::Read;
+
+        if resource.is_compressed() || resource.is_absent() {
+            let mut data = self.read_stream_resource(resource)?;
+            data.truncate(PATCH_SIGNATURE_LEN);
+            return Ok(data);
+        }
+        let location = self.resolve_resource(resource)?;
+        self.ensure_local_segment(&location)?;
+        self.seek_to(resource.offset)?;
+        let mut head = Vec::with_capacity(PATCH_SIGNATURE_LEN);
+        (&mut self.file)
+            .take(resource.size.min(PATCH_SIGNATURE_LEN as u64))
+            .read_to_end(&mut head)?;
+        Ok(head)
+    }
+
+    /// 列出镜像中的增量补丁数据流（含命名数据流），按路径先序排列
+    ///
+    /// 每个不同的数据流只读取开头几个字节。
+    pub fn patch_streams(&mut self, index: u32) -> anyhow::Result<Vec<PatchStream>> {
+        use anyhow::Context;
+        use std::collections::HashMap;
+
+        let root = self.read_metadata_root(index)?;
+        let resources: HashMap<[u8; 20], FileResourceEntry> = self
+            .read_lookup_table()?
+            .iter()
+            .filter(|entry| !entry.is_metadata())
+            .map(|entry| (entry.hash, entry.resource.clone()))
+            .collect();
+
+        let mut candidates = Vec::new();
+        root.walk_with_path(&mut |path, entry| {
+            if entry.is_directory() || entry.is_reparse_point() {
+                return;
+            }
+            candidates.push((path.to_string(), String::new(), entry.hash));
+            for stream in entry.streams.iter().filter(|s| !s.name.is_empty()) {
+                candidates.push((path.to_string(), stream.name.clone(), stream.hash));
+            }
+        });
+
+        let mut kinds: HashMap<[u8; 20], Option<PatchStreamKind>> = HashMap::new();
+        let mut patches = Vec::new();
+        for (path, stream, hash) in candidates {
+            let Some(resource) = resources.get(&hash) else {
+                continue;
+            };
+            let kind = match kinds.get(&hash) {
+                Some(kind) => *kind,
+                None => {
+                    let head = self
+                        .read_stream_head(resource)
+                        .with_context(|| alloc::format!("读取 {path} 失败"))?;
+                    let kind = PatchStreamKind::detect(&head);
+                    kinds.insert(hash, kind);
+                    kind
+                }
+            };
+            if let Some(kind) = kind {
+                patches.push(PatchStream {
+                    path,
+                    stream,
+                    hash,
+                    size: resource.original_size,
+                    kind,
+                });
+            }
+        }
+        Ok(patches)
+    }
+
+    /// 镜像中是否含有增量补丁数据流
+    pub fn has_patch_streams(&mut self, index: u32) -> anyhow::Result<bool> {
+        Ok(!self.patch_streams(index)?.is_empty())
+    }
+}
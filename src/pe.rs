@@ -0,0 +1,352 @@
+//! 镜像中 PE 文件（可执行文件、DLL、驱动）的机器类型清单，区分 ARM64EC 和 ARM64X 混合二进制
+//!
+//! 机器类型取自 COFF 文件头。ARM64EC 二进制的机器类型为 AMD64，ARM64X 二进制为 ARM64，
+//! 二者与普通二进制的区别是加载配置目录 (IMAGE_LOAD_CONFIG_DIRECTORY64) 中的
+//! CHPE 元数据指针 (`CHPEMetadataPointer`) 不为零。
+
+use anyhow::{Context, Result};
+use std::collections::HashMap;
+use std::fmt;
+use std::io::Read;
+
+use crate::fmt::{Align, Table, ToTable};
+use crate::log::debug;
+use crate::{Arch, WimParser};
+
+/// 按扩展名识别的 PE 文件
+const PE_EXTENSIONS: [&str; 10] = [
+    "exe", "dll", "sys", "efi", "ocx", "cpl", "drv", "scr", "com", "mui",
+];
+/// 读取的文件开头字节数（通常足以包含全部文件头和节表）
+const HEAD_SIZE: u64 = 4096;
+
+const MACHINE_I386: u16 = 0x014C;
+const MACHINE_ARMNT: u16 = 0x01C4;
+const MACHINE_IA64: u16 = 0x0200;
+const MACHINE_AMD64: u16 = 0x8664;
+const MACHINE_ARM64: u16 = 0xAA64;
+/// 可选头魔数：PE32+
+const PE32_PLUS_MAGIC: u16 = 0x020B;
+/// 数据目录中加载配置目录的下标
+const LOAD_CONFIG_DIRECTORY: usize = 10;
+/// IMAGE_LOAD_CONFIG_DIRECTORY64 中 `CHPEMetadataPointer` 的偏移
+const CHPE_METADATA_OFFSET: u64 = 0xC8;
+
+/// PE 文件的机器类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum PeMachine {
+    /// x86 (`IMAGE_FILE_MACHINE_I386`)
+    X86,
+    /// x64 (`IMAGE_FILE_MACHINE_AMD64`)
+    X64,
+    /// ARM Thumb-2 (`IMAGE_FILE_MACHINE_ARMNT`)
+    Arm,
+    /// Itanium
+    Ia64,
+    /// 纯 ARM64
+    Arm64,
+    /// ARM64EC：机器类型为 AMD64、可与 x64 模拟代码互操作的 ARM64 代码
+    Arm64Ec,
+    /// ARM64X：机器类型为 ARM64、同时包含 ARM64 和 ARM64EC 代码的混合二进制
+    Arm64X,
+    /// 其他机器类型，保留原始数值
+    Unknown(u16),
+}
+
+impl PeMachine {
+    /// 显示名称
+    pub fn name(&self) -> &'static str {
+        match self {
+            PeMachine::X86 => "x86",
+            PeMachine::X64 => "x64",
+            PeMachine::Arm => "ARM",
+            PeMachine::Ia64 => "IA64",
+            PeMachine::Arm64 => "ARM64",
+            PeMachine::Arm64Ec => "ARM64EC",
+            PeMachine::Arm64X => "ARM64X",
+            PeMachine::Unknown(_) => "unknown",
+        }
+    }
+
+    /// 是否为 ARM64EC 或 ARM64X 混合二进制（可被 x64 模拟进程加载）
+    pub fn is_hybrid(&self) -> bool {
+        matches!(self, PeMachine::Arm64Ec | PeMachine::Arm64X)
+    }
+
+    /// 代码运行的处理器架构（ARM64EC 和 ARM64X 为 ARM64）
+    pub fn arch(&self) -> Option<Arch> {
+        match self {
+            PeMachine::X86 => Some(Arch::X86),
+            PeMachine::X64 => Some(Arch::X64),
+            PeMachine::Arm => Some(Arch::Arm),
+            PeMachine::Ia64 => Some(Arch::Ia64),
+            PeMachine::Arm64 | PeMachine::Arm64Ec | PeMachine::Arm64X => Some(Arch::Arm64),
+            PeMachine::Unknown(_) => None,
+        }
+    }
+
+    /// 由 COFF 文件头的机器类型和是否带有 CHPE 元数据确定
+    pub(crate) fn from_coff(machine: u16, chpe: bool) -> PeMachine {
+        match (machine, chpe) {
+            (MACHINE_I386, _) => PeMachine::X86,
+            (MACHINE_AMD64, false) => PeMachine::X64,
+            (MACHINE_AMD64, true) => PeMachine::Arm64Ec,
+            (MACHINE_ARMNT, _) => PeMachine::Arm,
+            (MACHINE_IA64, _) => PeMachine::Ia64,
+            (MACHINE_ARM64, false) => PeMachine::Arm64,
+            (MACHINE_ARM64, true) => PeMachine::Arm64X,
+            (other, _) => PeMachine::Unknown(other),
+        }
+    }
+}
+
+impl fmt::Display for PeMachine {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PeMachine::Unknown(machine) => write!(f, "0x{machine:04X}"),
+            machine => f.write_str(machine.name()),
+        }
+    }
+}
+
+/// 镜像中的一个 PE 文件
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PeBinary {
+    /// 以 `/` 分隔的相对路径
+    pub path: String,
+    /// 机器类型
+    pub machine: PeMachine,
+}
+
+/// 镜像的 PE 文件清单（由 [`WimParser::pe_inventory`] 创建）
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PeInventory {
+    /// 镜像索引
+    pub index: u32,
+    /// 镜像 XML 中记录的架构
+    pub image_arch: Option<Arch>,
+    /// 按路径排列的 PE 文件（扩展名匹配但不是有效 PE 文件的不计入）
+    pub binaries: Vec<PeBinary>,
+}
+
+impl PeInventory {
+    /// 指定机器类型的 PE 文件数量
+    pub fn count(&self, machine: PeMachine) -> usize {
+        self.binaries
+            .iter()
+            .filter(|binary| binary.machine == machine)
+            .count()
+    }
+
+    /// ARM64EC 和 ARM64X 混合二进制
+    pub fn hybrid(&self) -> impl Iterator<Item = &PeBinary> {
+        self.binaries
+            .iter()
+            .filter(|binary| binary.machine.is_hybrid())
+    }
+
+    /// 与镜像架构不同的 PE 文件（ARM64 镜像中的 x64/x86 文件需要模拟运行，混合二进制不计入）
+    pub fn foreign(&self) -> impl Iterator<Item = &PeBinary> {
+        let image_arch = self.image_arch;
+        self.binaries.iter().filter(move |binary| {
+            image_arch.is_some_and(|arch| binary.machine.arch().is_some_and(|bin| bin != arch))
+        })
+    }
+
+    /// 各机器类型的 PE 文件数量，按机器类型排列
+    pub fn machine_counts(&self) -> Vec<(PeMachine, usize)> {
+        let mut counts: HashMap<PeMachine, usize> = HashMap::new();
+        for binary in &self.binaries {
+            *counts.entry(binary.machine).or_insert(0) += 1;
+        }
+        let mut counts: Vec<_> = counts.into_iter().collect();
+        counts.sort();
+        counts
+    }
+}
+
+impl ToTable for PeInventory {
+    fn table(&self) -> Table {
+        let mut table = Table::new(["机器类型", "文件数"]).align(1, Align::Right);
+        for (machine, count) in self.machine_counts() {
+            table.push_row([machine.to_string(), count.to_string()]);
+        }
+        table
+    }
+}
+
+/// 从 PE 文件开头读取 COFF 文件头的机器类型
+pub(crate) fn coff_machine(image: &[u8]) -> Option<u16> {
+    let pe_offset = pe_header_offset(image)?;
+    Some(u16::from_le_bytes(
+        image.get(pe_offset + 4..pe_offset + 6)?.try_into().ok()?,
+    ))
+}
+
+/// PE 签名的位置（不是 PE 文件时为 `None`）
+fn pe_header_offset(image: &[u8]) -> Option<usize> {
+    if image.get(0..2)? != b"MZ" {
+        return None;
+    }
+    let pe_offset = u32::from_le_bytes(image.get(0x3C..0x40)?.try_into().ok()?) as usize;
+    (image.get(pe_offset..pe_offset.checked_add(4)?)? == b"PE\0\0").then_some(pe_offset)
+}
+
+/// PE32+ 文件中加载配置目录的文件偏移（没有加载配置目录或无法定位时为 `None`）
+fn load_config_offset(image: &[u8]) -> Option<u64> {
+    let u16_at = |offset: usize| -> Option<u16> {
+        Some(u16::from_le_bytes(
+            image.get(offset..offset + 2)?.try_into().ok()?,
+        ))
+    };
+    let u32_at = |offset: usize| -> Option<u32> {
+        Some(u32::from_le_bytes(
+            image.get(offset..offset + 4)?.try_into().ok()?,
+        ))
+    };
+
+    let pe_offset = pe_header_offset(image)?;
+    let section_count = usize::from(u16_at(pe_offset + 6)?);
+    let optional_size = usize::from(u16_at(pe_offset + 20)?);
+    let optional = pe_offset + 24;
+    if u16_at(optional)? != PE32_PLUS_MAGIC
+        || u32_at(optional + 108)? as usize <= LOAD_CONFIG_DIRECTORY
+    {
+        return None;
+    }
+    let directory = optional + 112 + LOAD_CONFIG_DIRECTORY * 8;
+    let rva = u32_at(directory)?;
+    if rva == 0 || u32_at(directory + 4)? == 0 {
+        return None;
+    }
+
+    let sections = optional + optional_size;
+    (0..section_count).find_map(|i| {
+        let section = sections + i * 40;
+        let virtual_size = u32_at(section + 8)?;
+        let virtual_address = u32_at(section + 12)?;
+        let raw_size = u32_at(section + 16)?;
+        let raw_pointer = u32_at(section + 20)?;
+        let size = virtual_size.max(raw_size);
+        (rva >= virtual_address && rva - virtual_address < size)
+            .then(|| u64::from(raw_pointer) + u64::from(rva - virtual_address))
+    })
+}
+
+impl WimParser {
+    /// 列出镜像中的 PE 文件及其机器类型，识别 ARM64EC 和 ARM64X 混合二进制
+    ///
+    /// 按扩展名（exe、dll、sys、efi 等）选取文件，只读取文件头、节表和加载配置目录所需的开头部分；
+    /// 同一数据流（硬链接或重复文件）只读取一次。Windows on ARM 部署校验可以据此确认
+    /// 哪些组件能被 x64 模拟进程加载（[`PeInventory::hybrid`]）。
+    ///
+    /// ```
+    /// # #[cfg(feature = "fixtures")] {
+    /// # let fixture = wim_parser::fixtures::MiniWim::create()?;
+    /// # let mut parser = wim_parser::WimParser::new(fixture.path())?;
+    /// use wim_parser::PeMachine;
+    ///
+    /// let inventory = parser.pe_inventory(1)?;
+    /// assert_eq!(inventory.count(PeMachine::X64), 2);
+    /// assert_eq!(inventory.count(PeMachine::X86), 2);
+    /// assert_eq!(inventory.hybrid().count(), 0);
+    /// # }
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    pub fn pe_inventory(&mut self, index: u32) -> Result<PeInventory> {
+        if self.images.is_empty() {
+            self.parse_full()?;
+        }
+        let image_arch = self.get_image(index).and_then(|image| image.arch());
+        let root = self.read_metadata_root(index)?;
+
+        let mut candidates = Vec::new();
+        root.walk_with_path(&mut |path, entry| {
+            let is_pe = path.rsplit_once('.').is_some_and(|(_, ext)| {
+                PE_EXTENSIONS
+                    .iter()
+                    .any(|known| ext.eq_ignore_ascii_case(known))
+            });
+            if is_pe
+                && !entry.is_directory()
+                && !entry.is_reparse_point()
+                && entry.hash != [0u8; 20]
+            {
+                candidates.push((path.to_string(), entry.hash));
+            }
+        });
+
+        let mut machines: HashMap<[u8; 20], Option<PeMachine>> = HashMap::new();
+        let mut binaries = Vec::new();
+        for (path, hash) in candidates {
+            let machine = match machines.get(&hash) {
+                Some(machine) => *machine,
+                None => {
+                    let machine = self
+                        .read_pe_machine(&hash)
+                        .with_context(|| format!("读取 {path} 的 PE 文件头失败"))?;
+                    machines.insert(hash, machine);
+                    machine
+                }
+            };
+            if let Some(machine) = machine {
+                binaries.push(PeBinary { path, machine });
+            }
+        }
+        binaries.sort_by(|a, b| a.path.cmp(&b.path));
+
+        debug!(
+            "镜像 {} 中有 {} 个 PE 文件，其中混合二进制 {} 个",
+            index,
+            binaries.len(),
+            binaries
+                .iter()
+                .filter(|binary| binary.machine.is_hybrid())
+                .count()
+        );
+        Ok(PeInventory {
+            index,
+            image_arch,
+            binaries,
+        })
+    }
+
+    /// 读取数据流开头的 PE 文件头确定机器类型（不是 PE 文件时为 `None`）
+    fn read_pe_machine(&mut self, hash: &[u8; 20]) -> Result<Option<PeMachine>> {
+        let resource = self
+            .stream_resource(hash)?
+            .ok_or_else(|| anyhow::anyhow!("偏移表中找不到数据流"))?;
+        let limits = self.options().resource_limits();
+        let mut reader = self.open_resource(&resource)?;
+        let size = reader.size();
+        let mut data = Vec::new();
+        (&mut reader).take(HEAD_SIZE).read_to_end(&mut data)?;
+
+        let Some(machine) = coff_machine(&data) else {
+            return Ok(None);
+        };
+        if machine != MACHINE_AMD64 && machine != MACHINE_ARM64 {
+            return Ok(Some(PeMachine::from_coff(machine, false)));
+        }
+        let Some(offset) = load_config_offset(&data) else {
+            return Ok(Some(PeMachine::from_coff(machine, false)));
+        };
+
+        // 加载配置目录不在开头部分时继续顺序读取到其所在位置
+        let end = offset.saturating_add(CHPE_METADATA_OFFSET + 8);
+        if end > size {
+            return Ok(Some(PeMachine::from_coff(machine, false)));
+        }
+        if end > data.len() as u64 {
+            limits.check_memory(end)?;
+            (&mut reader)
+                .take(end - data.len() as u64)
+                .read_to_end(&mut data)?;
+        }
+        let config = &data[offset as usize..];
+        let config_size = u32::from_le_bytes(config[0..4].try_into().unwrap());
+        let chpe = u64::from(config_size) >= CHPE_METADATA_OFFSET + 8
+            && config[CHPE_METADATA_OFFSET as usize..CHPE_METADATA_OFFSET as usize + 8] != [0u8; 8];
+        Ok(Some(PeMachine::from_coff(machine, chpe)))
+    }
+}
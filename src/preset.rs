@@ -0,0 +1,126 @@
+use core::fmt;
+
+use crate::{Compression, FileFlags};
+
+/// 完整性表分块大小 (10 MiB，与 DISM `/CheckIntegrity` 相同)
+pub const INTEGRITY_CHUNK_SIZE: u32 = 10 * 1024 * 1024;
+
+/// 与 DISM 默认行为一致的 WIM 创建预设
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Preset {
+    /// 不压缩 (`/Compress:none`)
+    DismNone,
+    /// LZX、32 KiB 分块 (`/Compress:max`，DISM 捕获镜像的默认值)
+    DismMax,
+    /// XPRESS、32 KiB 分块 (`/Compress:fast`)
+    DismFast,
+    /// ESD：LZMS 固实资源、64 MiB 固实分块，非固实资源使用 128 KiB 分块 (`/Compress:recovery`)
+    Esd,
+}
+
+impl Preset {
+    /// 所有预设
+    pub const ALL: [Preset; 4] = [
+        Preset::DismNone,
+        Preset::DismMax,
+        Preset::DismFast,
+        Preset::Esd,
+    ];
+
+    /// 预设对应的写入设置
+    pub fn settings(&self) -> WriteSettings {
+        let (compression, solid) = match self {
+            Preset::DismNone => (Compression::None, None),
+            Preset::DismMax => (Compression::Lzx { chunk: 32 * 1024 }, None),
+            Preset::DismFast => (Compression::Xpress { chunk: 32 * 1024 }, None),
+            Preset::Esd => (
+                Compression::Lzms { chunk: 128 * 1024 },
+                Some(Compression::Lzms {
+                    chunk: 64 * 1024 * 1024,
+                }),
+            ),
+        };
+        WriteSettings {
+            compression,
+            solid,
+            integrity: false,
+            rp_fix: true,
+        }
+    }
+
+    /// 对应的 DISM 参数
+    pub fn dism_argument(&self) -> &'static str {
+        match self {
+            Preset::DismNone => "/Compress:none",
+            Preset::DismMax => "/Compress:max",
+            Preset::DismFast => "/Compress:fast",
+            Preset::Esd => "/Compress:recovery",
+        }
+    }
+}
+
+impl fmt::Display for Preset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.dism_argument())
+    }
+}
+
+/// 写入 WIM 时使用的压缩格式、固实打包和完整性表设置
+///
+/// 一般通过 [`Preset::settings`] 获得，再按需调整（例如 `.integrity(true)` 对应
+/// DISM 的 `/CheckIntegrity`）。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct WriteSettings {
+    /// 文件头声明的压缩格式和非固实资源的分块大小
+    pub compression: Compression,
+    /// 固实资源的压缩格式和分块大小（`None` 表示不使用固实打包）
+    pub solid: Option<Compression>,
+    /// 是否写入完整性表
+    pub integrity: bool,
+    /// 是否修正绝对链接目标并设置文件头的 `RP_FIX` 标志（DISM 默认开启）
+    pub rp_fix: bool,
+}
+
+impl Default for WriteSettings {
+    /// 未压缩、无完整性表、修正链接目标
+    fn default() -> Self {
+        Preset::DismNone.settings()
+    }
+}
+
+impl From<Preset> for WriteSettings {
+    fn from(preset: Preset) -> Self {
+        preset.settings()
+    }
+}
+
+impl WriteSettings {
+    /// 是否写入完整性表 (DISM `/CheckIntegrity`)
+    pub fn integrity(mut self, enabled: bool) -> Self {
+        self.integrity = enabled;
+        self
+    }
+
+    /// 是否修正绝对链接目标 (RP_FIX，对应 DISM 的默认行为；wimlib 的 `--norpfix` 即关闭)
+    pub fn rp_fix(mut self, enabled: bool) -> Self {
+        self.rp_fix = enabled;
+        self
+    }
+
+    /// 文件头中的文件标志 (`FLAG_HEADER_COMPRESSION` 及压缩格式、`RP_FIX`)
+    pub fn file_flags(&self) -> u32 {
+        let compression = match self.compression {
+            Compression::None | Compression::Unknown(_) => 0,
+            Compression::Xpress { .. } => FileFlags::COMPRESSION | FileFlags::COMPRESS_XPRESS,
+            Compression::Lzx { .. } => FileFlags::COMPRESSION | FileFlags::COMPRESS_LZX,
+            Compression::Lzms { .. } => FileFlags::COMPRESSION | FileFlags::COMPRESS_LZMS,
+        };
+        let rp_fix = if self.rp_fix { FileFlags::RP_FIX } else { 0 };
+        compression | rp_fix
+    }
+
+    /// 文件头中的分块大小字段（未压缩时为 0）
+    pub fn chunk_size(&self) -> u32 {
+        self.compression.chunk_size().unwrap_or(0)
+    }
+}
@@ -0,0 +1,76 @@
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::Path;
+
+use crate::format::{self, WIM_HEADER_MIN_SIZE};
+use crate::{ImageInfo, WimHeader};
+
+/// [`probe_deep`] 允许读取的 XML 资源上限 (16 MiB)
+pub const PROBE_MAX_XML_BYTES: u64 = 16 * 1024 * 1024;
+
+/// 仅读取并校验文件头的轻量探测（不解析 XML，不依赖 anyhow 等外部库）
+pub fn probe_header<P: AsRef<Path>>(path: P) -> io::Result<WimHeader> {
+    probe_header_from(File::open(path)?)
+}
+
+/// 从任意读取器的当前位置读取并校验文件头
+pub fn probe_header_from<R: Read>(mut reader: R) -> io::Result<WimHeader> {
+    let mut buffer = [0u8; WIM_HEADER_MIN_SIZE];
+    reader.read_exact(&mut buffer)?;
+    format::parse_header(&buffer).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+/// [`probe_deep`] 的结果：文件头和 XML 中的镜像信息
+#[derive(Debug, Clone)]
+pub struct DeepProbe {
+    /// 文件头
+    pub header: WimHeader,
+    /// XML 中的镜像信息（没有 XML 资源时为空）
+    pub images: Vec<ImageInfo>,
+    /// 读取的 XML 资源字节数
+    pub xml_bytes: u64,
+}
+
+/// 仅读取文件头和 XML 的元数据探测，适合批量扫描慢速存储上的大量镜像
+///
+/// 至多两次读取：文件头和 XML 资源各一次，从不触及偏移表、元数据或文件数据资源。
+/// XML 超过 [`PROBE_MAX_XML_BYTES`] 或被压缩时返回 `InvalidData`。
+pub fn probe_deep<P: AsRef<Path>>(path: P) -> io::Result<DeepProbe> {
+    probe_deep_from(File::open(path)?)
+}
+
+/// 从任意可定位的读取器探测文件头和 XML（文件头须位于偏移 0）
+pub fn probe_deep_from<R: Read + Seek>(mut reader: R) -> io::Result<DeepProbe> {
+    let invalid = |message: String| io::Error::new(io::ErrorKind::InvalidData, message);
+
+    reader.seek(SeekFrom::Start(0))?;
+    let header = probe_header_from(&mut reader)?;
+    let resource = &header.xml_data_resource;
+    if resource.is_absent() {
+        return Ok(DeepProbe {
+            header,
+            images: Vec::new(),
+            xml_bytes: 0,
+        });
+    }
+    if resource.is_compressed() {
+        return Err(invalid("XML 资源已压缩，无法仅凭文件头探测".to_string()));
+    }
+    if resource.size > PROBE_MAX_XML_BYTES {
+        return Err(invalid(format!(
+            "XML 资源过大: {} 字节 (上限 {} 字节)",
+            resource.size, PROBE_MAX_XML_BYTES
+        )));
+    }
+
+    let mut buffer = vec![0u8; resource.size as usize];
+    reader.seek(SeekFrom::Start(resource.offset))?;
+    reader.read_exact(&mut buffer)?;
+    let xml = format::decode_xml_utf16(&buffer).map_err(|e| invalid(e.to_string()))?;
+
+    Ok(DeepProbe {
+        images: format::parse_images_from_xml(&xml),
+        xml_bytes: resource.size,
+        header,
+    })
+}
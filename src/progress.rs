@@ -0,0 +1,186 @@
+//! 进度回报：已处理字节数、平滑后的吞吐率和剩余时间估计
+//!
+//! 吞吐率按采样窗口计算后做指数加权移动平均 (EWMA)，短时间内的多次更新合并到同一窗口，
+//! 前端只需显示 [`ProgressUpdate`]，不必各自实现 ETA 计算和抖动处理。
+
+use std::time::{Duration, Instant};
+
+/// 一次进度回报
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ProgressUpdate {
+    /// 已处理的字节数
+    pub done: u64,
+    /// 总字节数
+    pub total: u64,
+    /// 开始以来经过的时间
+    pub elapsed: Duration,
+    /// 平滑后的吞吐率（字节/秒，尚无采样时为 0）
+    pub bytes_per_sec: f64,
+    /// 预计剩余时间（尚无采样或吞吐率为 0 时为 `None`，完成时为 0）
+    pub eta: Option<Duration>,
+}
+
+impl ProgressUpdate {
+    /// 完成比例 (0.0 - 1.0，总字节数为 0 时为 1.0)
+    pub fn fraction(&self) -> f64 {
+        if self.total == 0 {
+            1.0
+        } else {
+            (self.done as f64 / self.total as f64).min(1.0)
+        }
+    }
+
+    /// 是否已完成
+    pub fn is_complete(&self) -> bool {
+        self.done >= self.total
+    }
+}
+
+/// 进度回调（见 [`ApplyOptions::progress`](crate::ApplyOptions::progress)）
+///
+/// 闭包 `Fn(&ProgressUpdate)` 自动实现此 trait。
+pub trait Progress: Send + Sync {
+    /// 进度更新，至多每个采样窗口调用一次
+    fn update(&self, update: &ProgressUpdate);
+
+    /// 操作完成时调用一次（默认转发给 [`update`](Self::update)）
+    fn finish(&self, update: &ProgressUpdate) {
+        self.update(update);
+    }
+}
+
+impl<F: Fn(&ProgressUpdate) + Send + Sync> Progress for F {
+    fn update(&self, update: &ProgressUpdate) {
+        self(update)
+    }
+}
+
+/// 吞吐率和剩余时间估计器
+///
+/// 两次采样的间隔不少于 [`min_interval`](Self::min_interval)（默认 250 毫秒），窗口内的吞吐率以
+/// 系数 [`smoothing`](Self::smoothing)（默认 0.3）并入移动平均；越大越跟随最近的速度，越小越平稳。
+#[derive(Debug, Clone)]
+pub struct RateEstimator {
+    total: u64,
+    alpha: f64,
+    min_interval: Duration,
+    started: Instant,
+    /// 上次采样时的经过时间和字节数
+    sample_elapsed: Duration,
+    sample_done: u64,
+    /// 平滑后的吞吐率（尚无采样时为 `None`）
+    rate: Option<f64>,
+    done: u64,
+}
+
+impl RateEstimator {
+    /// 创建总量为 `total` 字节的估计器，从现在开始计时
+    pub fn new(total: u64) -> Self {
+        Self {
+            total,
+            alpha: 0.3,
+            min_interval: Duration::from_millis(250),
+            started: Instant::now(),
+            sample_elapsed: Duration::ZERO,
+            sample_done: 0,
+            rate: None,
+            done: 0,
+        }
+    }
+
+    /// 设置 EWMA 平滑系数（限制在 0.01 - 1.0，1.0 表示只看最近一个窗口）
+    pub fn smoothing(mut self, alpha: f64) -> Self {
+        self.alpha = alpha.clamp(0.01, 1.0);
+        self
+    }
+
+    /// 设置采样窗口的最短时长
+    pub fn min_interval(mut self, interval: Duration) -> Self {
+        self.min_interval = interval;
+        self
+    }
+
+    /// 总字节数
+    pub fn total(&self) -> u64 {
+        self.total
+    }
+
+    /// 记录已处理 `done` 字节（累计值），按当前时间计算
+    ///
+    /// 距上次采样不足一个窗口时只记录字节数并返回 `None`；否则更新吞吐率并返回进度。
+    pub fn record(&mut self, done: u64) -> Option<ProgressUpdate> {
+        let elapsed = self.started.elapsed();
+        self.record_at(done, elapsed)
+    }
+
+    /// 与 [`record`](Self::record) 相同，但使用调用方给出的经过时间（便于回放日志或测试）
+    pub fn record_at(&mut self, done: u64, elapsed: Duration) -> Option<ProgressUpdate> {
+        self.done = done;
+        let window = elapsed.saturating_sub(self.sample_elapsed);
+        if window < self.min_interval || window.is_zero() {
+            return None;
+        }
+        let sample = done.saturating_sub(self.sample_done) as f64 / window.as_secs_f64();
+        self.rate = Some(match self.rate {
+            Some(rate) => self.alpha * sample + (1.0 - self.alpha) * rate,
+            None => sample,
+        });
+        self.sample_elapsed = elapsed;
+        self.sample_done = done;
+        Some(self.snapshot_at(elapsed))
+    }
+
+    /// 当前进度（不产生新的采样）
+    pub fn snapshot(&self) -> ProgressUpdate {
+        self.snapshot_at(self.started.elapsed())
+    }
+
+    /// 指定经过时间下的当前进度（不产生新的采样）
+    pub fn snapshot_at(&self, elapsed: Duration) -> ProgressUpdate {
+        let remaining = self.total.saturating_sub(self.done);
+        let eta = if remaining == 0 {
+            Some(Duration::ZERO)
+        } else {
+            self.rate
+                .filter(|rate| *rate > 0.0)
+                .map(|rate| Duration::from_secs_f64(remaining as f64 / rate))
+        };
+        ProgressUpdate {
+            done: self.done,
+            total: self.total,
+            elapsed,
+            bytes_per_sec: self.rate.unwrap_or(0.0),
+            eta,
+        }
+    }
+}
+
+/// 把字节数累计到估计器，按采样窗口调用回调
+pub(crate) struct ProgressReporter<'a> {
+    progress: &'a dyn Progress,
+    estimator: RateEstimator,
+}
+
+impl<'a> ProgressReporter<'a> {
+    pub fn new(progress: &'a dyn Progress, total: u64) -> Self {
+        let estimator = RateEstimator::new(total);
+        progress.update(&estimator.snapshot());
+        Self {
+            progress,
+            estimator,
+        }
+    }
+
+    /// 又处理了 `bytes` 字节
+    pub fn advance(&mut self, bytes: u64) {
+        let done = self.estimator.done.saturating_add(bytes);
+        if let Some(update) = self.estimator.record(done) {
+            self.progress.update(&update);
+        }
+    }
+
+    /// 操作完成
+    pub fn finish(self) {
+        self.progress.finish(&self.estimator.snapshot());
+    }
+}
@@ -0,0 +1,208 @@
+//! 分块级修复计划：找出校验失败的字节范围，供下载器只从镜像站重新获取这些范围
+
+use anyhow::Result;
+use sha1::{Digest as _, Sha1};
+use std::io::Read;
+use std::ops::Range;
+
+use crate::fmt::{format_bytes, Align, Table, ToTable};
+use crate::integrity::{integrity_region, IntegrityTable};
+use crate::log::{debug, info};
+use crate::lookup_table::LookupTableEntry;
+use crate::verify::hash_reader;
+use crate::WimParser;
+
+/// 修复计划的校验依据
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RepairSource {
+    /// 完整性表（按固定分块校验文件头之后到偏移表结尾的区域）
+    IntegrityTable {
+        /// 分块大小
+        chunk_size: u32,
+    },
+    /// 偏移表中记录的 SHA-1（文件没有完整性表时，只能校验未压缩资源）
+    LookupTable,
+}
+
+/// 需要重新下载的字节范围
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RepairRange {
+    /// 文件中的字节范围 `[start, end)`
+    pub range: Range<u64>,
+    /// 与该范围重叠的资源的 SHA-1（来自偏移表）
+    pub resources: Vec<[u8; 20]>,
+}
+
+/// 修复计划
+#[derive(Debug, Clone)]
+pub struct RepairPlan {
+    /// 校验依据
+    pub source: RepairSource,
+    /// 校验失败的字节范围（按偏移排序，相邻范围已合并）
+    pub ranges: Vec<RepairRange>,
+    /// 无法校验的资源数量（无完整性表时的压缩资源）
+    pub unverified: usize,
+}
+
+impl RepairPlan {
+    /// 是否没有需要修复的范围
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    /// 需要重新下载的总字节数
+    pub fn total_bytes(&self) -> u64 {
+        self.ranges
+            .iter()
+            .map(|r| r.range.end - r.range.start)
+            .sum()
+    }
+}
+
+impl ToTable for RepairPlan {
+    fn table(&self) -> Table {
+        let mut table = Table::new(["起始偏移", "结束偏移", "大小", "受影响资源"])
+            .align(0, Align::Right)
+            .align(1, Align::Right)
+            .align(2, Align::Right)
+            .align(3, Align::Right);
+        for range in &self.ranges {
+            table.push_row([
+                range.range.start.to_string(),
+                range.range.end.to_string(),
+                format_bytes(range.range.end - range.range.start),
+                range.resources.len().to_string(),
+            ]);
+        }
+        table
+    }
+}
+
+/// 将范围追加到列表，与上一个范围相邻或重叠时合并
+fn push_merged(ranges: &mut Vec<Range<u64>>, range: Range<u64>) {
+    match ranges.last_mut() {
+        Some(last) if range.start <= last.end => last.end = last.end.max(range.end),
+        _ => ranges.push(range),
+    }
+}
+
+impl WimParser {
+    /// 生成修复计划：列出校验失败的字节范围
+    ///
+    /// 有完整性表时逐块校验文件头之后到偏移表结尾的区域（XML 数据不在覆盖范围内），
+    /// 并用偏移表标注每个范围影响的资源；没有完整性表时按偏移表中的 SHA-1
+    /// 校验未压缩资源，压缩资源计入 [`RepairPlan::unverified`]。
+    pub fn repair_plan(&mut self) -> Result<RepairPlan> {
+        let plan = match self.read_integrity_table()? {
+            Some(table) => self.repair_plan_from_integrity(&table)?,
+            None => self.repair_plan_from_lookup_table()?,
+        };
+
+        info!(
+            "修复计划: {} 个范围, 共 {} 字节",
+            plan.ranges.len(),
+            plan.total_bytes()
+        );
+        Ok(plan)
+    }
+
+    fn repair_plan_from_integrity(&mut self, table: &IntegrityTable) -> Result<RepairPlan> {
+        let region = integrity_region(self.read_header()?);
+        let mut failed: Vec<Range<u64>> = Vec::new();
+        let mut buffer = Vec::with_capacity(table.chunk_size as usize);
+
+        for (index, expected) in table.hashes.iter().enumerate() {
+            let range = table.chunk_range(&region, index);
+            if range.start >= range.end {
+                break;
+            }
+
+            buffer.clear();
+            self.seek_to(range.start)?;
+            (&mut self.file)
+                .take(range.end - range.start)
+                .read_to_end(&mut buffer)?;
+
+            let actual: [u8; 20] = Sha1::digest(&buffer).into();
+            if buffer.len() as u64 != range.end - range.start || &actual != expected {
+                debug!("完整性分块 {} 校验失败: {:?}", index, range);
+                push_merged(&mut failed, range);
+            }
+        }
+
+        // 偏移表本身可能已损坏，此时无法标注受影响的资源
+        let entries = self
+            .read_lookup_table()
+            .map(<[_]>::to_vec)
+            .unwrap_or_default();
+        let ranges = failed
+            .into_iter()
+            .map(|range| RepairRange {
+                resources: overlapping_resources(&entries, &range),
+                range,
+            })
+            .collect();
+
+        Ok(RepairPlan {
+            source: RepairSource::IntegrityTable {
+                chunk_size: table.chunk_size,
+            },
+            ranges,
+            unverified: 0,
+        })
+    }
+
+    fn repair_plan_from_lookup_table(&mut self) -> Result<RepairPlan> {
+        let mut entries = self.read_lookup_table()?.to_vec();
+        entries.sort_by_key(|entry| entry.resource.offset);
+
+        let mut failed: Vec<RepairRange> = Vec::new();
+        let mut unverified = 0;
+
+        for entry in &entries {
+            let flags = entry.resource.resource_flags();
+            if flags.is_compressed() || flags.is_solid() {
+                unverified += 1;
+                continue;
+            }
+
+            let range = entry.resource.byte_range();
+            self.seek_to(range.start)?;
+            let actual: [u8; 20] =
+                hash_reader::<Sha1>(&mut (&mut self.file).take(range.end - range.start))?.into();
+            if actual == entry.hash {
+                continue;
+            }
+
+            debug!("资源校验失败: {:?}", range);
+            match failed.last_mut() {
+                Some(last) if range.start <= last.range.end => {
+                    last.range.end = last.range.end.max(range.end);
+                    last.resources.push(entry.hash);
+                }
+                _ => failed.push(RepairRange {
+                    range,
+                    resources: vec![entry.hash],
+                }),
+            }
+        }
+
+        Ok(RepairPlan {
+            source: RepairSource::LookupTable,
+            ranges: failed,
+            unverified,
+        })
+    }
+}
+
+/// 与字节范围重叠的偏移表条目
+fn overlapping_resources(entries: &[LookupTableEntry], range: &Range<u64>) -> Vec<[u8; 20]> {
+    entries
+        .iter()
+        .filter(|entry| {
+            let resource = entry.resource.byte_range();
+            resource.start < range.end && range.start < resource.end
+        })
+        .map(|entry| entry.hash)
+        .collect()
+}
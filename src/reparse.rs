@@ -0,0 +1,203 @@
+//! 重解析点数据的解析：符号链接和目录联接的目标
+//!
+//! 设置了 `FILE_ATTRIBUTE_REPARSE_POINT` 的目录项，其未命名数据流保存的是重解析数据
+//! （不含 8 字节的重解析头：标记、数据长度和保留字段），而不是文件内容。
+//! 符号链接和目录联接解析为 [`ReparseInfo::Symlink`] / [`ReparseInfo::Junction`]，
+//! 其他标记（WOF 压缩、重复数据删除、云文件占位符等）保留原始数据。
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::Error;
+
+/// 符号链接重解析标记 (IO_REPARSE_TAG_SYMLINK)
+pub(crate) const IO_REPARSE_TAG_SYMLINK: u32 = 0xA000_000C;
+
+/// 目录联接重解析标记 (IO_REPARSE_TAG_MOUNT_POINT)
+pub(crate) const IO_REPARSE_TAG_MOUNT_POINT: u32 = 0xA000_0003;
+
+/// 符号链接重解析数据中的相对路径标志 (SYMLINK_FLAG_RELATIVE)
+const SYMLINK_FLAG_RELATIVE: u32 = 0x0000_0001;
+
+/// NT 路径前缀
+const NT_PATH_PREFIX: &str = r"\??\";
+
+/// 解析后的重解析数据
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReparseInfo {
+    /// 符号链接 (IO_REPARSE_TAG_SYMLINK)
+    Symlink {
+        /// 替代名称（绝对目标带 `\??\` 前缀）
+        substitute_name: String,
+        /// 显示名称
+        print_name: String,
+        /// 目标是否为相对链接所在目录的路径 (SYMLINK_FLAG_RELATIVE)
+        relative: bool,
+    },
+    /// 目录联接 (IO_REPARSE_TAG_MOUNT_POINT)
+    Junction {
+        /// 替代名称（带 `\??\` 前缀）
+        substitute_name: String,
+        /// 显示名称
+        print_name: String,
+    },
+    /// 其他重解析点，保留原始数据
+    Other {
+        /// 重解析标记
+        tag: u32,
+        /// 重解析数据（不含重解析头）
+        data: Vec<u8>,
+    },
+}
+
+fn read_u16(data: &[u8], offset: usize) -> Result<usize, Error> {
+    data.get(offset..offset + 2)
+        .map(|bytes| usize::from(u16::from_le_bytes([bytes[0], bytes[1]])))
+        .ok_or(Error::Truncated {
+            expected: offset + 2,
+            actual: data.len(),
+        })
+}
+
+/// 读取名称缓冲区中 `[offset, offset + len)` 处的 UTF-16 LE 名称
+fn read_name(
+    data: &[u8],
+    buffer_offset: usize,
+    offset: usize,
+    len: usize,
+) -> Result<String, Error> {
+    let start = buffer_offset + offset;
+    let bytes = data.get(start..start + len).ok_or(Error::Truncated {
+        expected: start + len,
+        actual: data.len(),
+    })?;
+    let units: Vec<u16> = bytes
+        .chunks_exact(2)
+        .map(|chunk| u16::from_le_bytes([chunk[0], chunk[1]]))
+        .collect();
+    String::from_utf16(&units)
+        .map_err(|_| Error::CorruptData("重解析数据中的名称不是有效的 UTF-16"))
+}
+
+impl ReparseInfo {
+    /// 解析重解析数据（不含 8 字节的重解析头）
+    ///
+    /// 符号链接和目录联接的名称偏移或长度超出数据范围时返回 [`Error::Truncated`]，
+    /// 名称不是有效的 UTF-16 时返回 [`Error::CorruptData`]；其他标记不检查数据内容。
+    ///
+    /// ```
+    /// use wim_parser::ReparseInfo;
+    ///
+    /// let name: Vec<u8> = "..\\Windows".encode_utf16().flat_map(u16::to_le_bytes).collect();
+    /// let mut data = Vec::new();
+    /// for field in [0u16, name.len() as u16, name.len() as u16, name.len() as u16] {
+    ///     data.extend(field.to_le_bytes());
+    /// }
+    /// data.extend(1u32.to_le_bytes());
+    /// data.extend(&name);
+    /// data.extend(&name);
+    ///
+    /// let info = ReparseInfo::parse(0xA000_000C, &data)?;
+    /// assert!(info.is_relative());
+    /// assert_eq!(info.target().as_deref(), Some("..\\Windows"));
+    /// # Ok::<(), wim_parser::Error>(())
+    /// ```
+    pub fn parse(tag: u32, data: &[u8]) -> Result<Self, Error> {
+        let (buffer_offset, relative) = match tag {
+            IO_REPARSE_TAG_SYMLINK => {
+                let flags = data.get(8..12).ok_or(Error::Truncated {
+                    expected: 12,
+                    actual: data.len(),
+                })?;
+                let flags = u32::from_le_bytes([flags[0], flags[1], flags[2], flags[3]]);
+                (12, flags & SYMLINK_FLAG_RELATIVE != 0)
+            }
+            IO_REPARSE_TAG_MOUNT_POINT => (8, false),
+            _ => {
+                return Ok(ReparseInfo::Other {
+                    tag,
+                    data: data.to_vec(),
+                })
+            }
+        };
+        let substitute_name =
+            read_name(data, buffer_offset, read_u16(data, 0)?, read_u16(data, 2)?)?;
+        let print_name = read_name(data, buffer_offset, read_u16(data, 4)?, read_u16(data, 6)?)?;
+        Ok(if tag == IO_REPARSE_TAG_SYMLINK {
+            ReparseInfo::Symlink {
+                substitute_name,
+                print_name,
+                relative,
+            }
+        } else {
+            ReparseInfo::Junction {
+                substitute_name,
+                print_name,
+            }
+        })
+    }
+
+    /// 重解析标记
+    pub fn tag(&self) -> u32 {
+        match self {
+            ReparseInfo::Symlink { .. } => IO_REPARSE_TAG_SYMLINK,
+            ReparseInfo::Junction { .. } => IO_REPARSE_TAG_MOUNT_POINT,
+            ReparseInfo::Other { tag, .. } => *tag,
+        }
+    }
+
+    /// 是否为符号链接或目录联接
+    pub fn is_link(&self) -> bool {
+        !matches!(self, ReparseInfo::Other { .. })
+    }
+
+    /// 是否为相对链接所在目录的符号链接
+    pub fn is_relative(&self) -> bool {
+        matches!(self, ReparseInfo::Symlink { relative: true, .. })
+    }
+
+    /// 链接目标（Windows 写法，`\` 分隔）
+    ///
+    /// 优先使用显示名称，为空时使用去掉 `\??\` 前缀的替代名称；其他重解析点或目标为空时返回 `None`。
+    pub fn target(&self) -> Option<String> {
+        let (substitute_name, print_name, relative) = match self {
+            ReparseInfo::Symlink {
+                substitute_name,
+                print_name,
+                relative,
+            } => (substitute_name, print_name, *relative),
+            ReparseInfo::Junction {
+                substitute_name,
+                print_name,
+            } => (substitute_name, print_name, false),
+            ReparseInfo::Other { .. } => return None,
+        };
+        let target = if !print_name.is_empty() {
+            print_name.as_str()
+        } else if relative {
+            substitute_name.as_str()
+        } else {
+            substitute_name
+                .strip_prefix(NT_PATH_PREFIX)
+                .unwrap_or(substitute_name)
+        };
+        (!target.is_empty()).then(|| String::from(target))
+    }
+}
+
+impl fmt::Display for ReparseInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let kind = match self {
+            ReparseInfo::Symlink { .. } => "符号链接",
+            ReparseInfo::Junction { .. } => "目录联接",
+            ReparseInfo::Other { tag, data } => {
+                return write!(f, "重解析点 0x{tag:08X} ({} 字节)", data.len());
+            }
+        };
+        match self.target() {
+            Some(target) => write!(f, "{kind} -> {target}"),
+            None => write!(f, "{kind} (目标为空)"),
+        }
+    }
+}
@@ -0,0 +1,310 @@
+use alloc::vec::Vec;
+use core::fmt;
+use core::ops::Range;
+
+#[cfg(feature = "parser")]
+use crate::{Error, WimParser};
+use crate::{FileResourceEntry, ResourceFlags, WimHeader};
+
+/// 固实资源条目的原始大小字段（固实资源的实际大小记录在资源头中）
+pub const SOLID_RESOURCE_MAGIC: u64 = 0x1_0000_0000;
+
+/// 文件头中引用的资源种类
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ResourceKind {
+    /// 偏移表（查找表）
+    OffsetTable,
+    /// XML 数据
+    XmlData,
+    /// 引导元数据
+    BootMetadata,
+    /// 完整性数据
+    Integrity,
+}
+
+impl ResourceKind {
+    /// 所有种类，按文件头中的字段顺序排列
+    pub const ALL: [ResourceKind; 4] = [
+        ResourceKind::OffsetTable,
+        ResourceKind::XmlData,
+        ResourceKind::BootMetadata,
+        ResourceKind::Integrity,
+    ];
+
+    /// 对应的文件头字段名称
+    pub fn name(&self) -> &'static str {
+        match self {
+            ResourceKind::OffsetTable => "offset_table",
+            ResourceKind::XmlData => "xml_data",
+            ResourceKind::BootMetadata => "boot_metadata",
+            ResourceKind::Integrity => "integrity",
+        }
+    }
+}
+
+impl fmt::Display for ResourceKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// 资源头标志位集合（类型化的 [`ResourceFlags`] 视图）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct ResHdrFlags(pub u8);
+
+impl ResHdrFlags {
+    /// 已定义的标志位及其名称
+    const NAMED: [(u8, &'static str); 5] = [
+        (ResourceFlags::FREE, "FREE"),
+        (ResourceFlags::METADATA, "METADATA"),
+        (ResourceFlags::COMPRESSED, "COMPRESSED"),
+        (ResourceFlags::SPANNED, "SPANNED"),
+        (ResourceFlags::SOLID, "SOLID"),
+    ];
+
+    /// 原始标志值
+    pub fn bits(&self) -> u8 {
+        self.0
+    }
+
+    /// 是否包含指定标志位
+    pub fn contains(&self, flag: u8) -> bool {
+        self.0 & flag == flag
+    }
+
+    /// 条目是否已释放（FREE）
+    pub fn is_free(&self) -> bool {
+        self.contains(ResourceFlags::FREE)
+    }
+
+    /// 是否为镜像元数据资源（METADATA）
+    pub fn is_metadata(&self) -> bool {
+        self.contains(ResourceFlags::METADATA)
+    }
+
+    /// 资源数据是否压缩（COMPRESSED），与文件头的压缩标志无关
+    pub fn is_compressed(&self) -> bool {
+        self.contains(ResourceFlags::COMPRESSED)
+    }
+
+    /// 资源是否跨分卷存储（SPANNED）
+    pub fn is_spanned(&self) -> bool {
+        self.contains(ResourceFlags::SPANNED)
+    }
+
+    /// 是否为固实资源（SOLID，ESD 中多个数据流共享的压缩块）
+    pub fn is_solid(&self) -> bool {
+        self.contains(ResourceFlags::SOLID)
+    }
+
+    /// 已设置的已知标志名称
+    pub fn names(&self) -> Vec<&'static str> {
+        Self::NAMED
+            .iter()
+            .filter(|(bit, _)| self.contains(*bit))
+            .map(|(_, name)| *name)
+            .collect()
+    }
+
+    /// 未定义的已设置标志位
+    pub fn unknown_bits(&self) -> u8 {
+        let known = Self::NAMED.iter().fold(0, |acc, (bit, _)| acc | bit);
+        self.0 & !known
+    }
+}
+
+impl fmt::Display for ResHdrFlags {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut names = self.names().join("|");
+        if self.unknown_bits() != 0 {
+            if !names.is_empty() {
+                names.push('|');
+            }
+            names.push_str(&alloc::format!("0x{:02X}", self.unknown_bits()));
+        }
+        if names.is_empty() {
+            names.push_str("NONE");
+        }
+        f.write_str(&names)
+    }
+}
+
+/// 资源的物理位置：所在分卷及分卷文件内的字节范围
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ResourceLocation {
+    /// 分卷号（从 1 开始，对应 `.swm` 分卷的 `segment_number`）
+    pub segment: u16,
+    /// 分卷文件内的偏移
+    pub offset: u64,
+    /// 资源在文件中占用的字节数（压缩后大小）
+    pub size: u64,
+}
+
+impl ResourceLocation {
+    /// 分卷文件内的字节范围 `[offset, offset + size)`
+    pub fn byte_range(&self) -> Range<u64> {
+        self.offset..self.offset.saturating_add(self.size)
+    }
+}
+
+/// 资源条目的状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ResourceState {
+    /// 条目指向文件中的数据
+    Present,
+    /// 条目不引用任何数据（设置了 FREE 标志或大小为零），不应按偏移读取
+    Absent,
+}
+
+impl FileResourceEntry {
+    /// 类型化的资源标志
+    pub fn resource_flags(&self) -> ResHdrFlags {
+        ResHdrFlags(self.flags)
+    }
+
+    /// 资源数据是否压缩
+    ///
+    /// 每个资源条目有自己的压缩标志：压缩的 WIM 中也可能有未压缩的资源（例如过小而不值得压缩的数据流），
+    /// 因此是否需要解压应以此判断，而不是文件头的压缩标志。
+    pub fn is_compressed(&self) -> bool {
+        self.resource_flags().is_compressed()
+    }
+
+    /// 是否为镜像元数据资源
+    pub fn is_metadata(&self) -> bool {
+        self.resource_flags().is_metadata()
+    }
+
+    /// 资源是否跨分卷存储
+    pub fn is_spanned(&self) -> bool {
+        self.resource_flags().is_spanned()
+    }
+
+    /// 是否为固实资源，或固实资源中的数据流
+    pub fn is_solid(&self) -> bool {
+        self.resource_flags().is_solid()
+    }
+
+    /// 是否为固实资源本身：原始大小字段为 [`SOLID_RESOURCE_MAGIC`]，实际大小记录在资源头中
+    pub fn is_solid_resource(&self) -> bool {
+        self.is_solid() && self.original_size == SOLID_RESOURCE_MAGIC
+    }
+
+    /// 资源状态：设置了 FREE 标志、或大小和原始大小均为零（全零条目）时视为不存在
+    pub fn state(&self) -> ResourceState {
+        if self.resource_flags().is_free() || (self.size == 0 && self.original_size == 0) {
+            ResourceState::Absent
+        } else {
+            ResourceState::Present
+        }
+    }
+
+    /// 资源是否不存在
+    pub fn is_absent(&self) -> bool {
+        self.state() == ResourceState::Absent
+    }
+
+    /// 资源在文件中占用的字节范围 `[offset, offset + size)`
+    pub fn byte_range(&self) -> Range<u64> {
+        self.offset..self.offset.saturating_add(self.size)
+    }
+}
+
+impl WimHeader {
+    /// 按文件头字段顺序遍历偏移表、XML、引导元数据和完整性数据资源
+    pub fn resources(&self) -> impl Iterator<Item = (ResourceKind, &FileResourceEntry)> {
+        ResourceKind::ALL
+            .into_iter()
+            .map(move |kind| (kind, self.resource(kind)))
+    }
+
+    /// 获取指定种类的资源条目，条目不存在时返回 `None`
+    pub fn present_resource(&self, kind: ResourceKind) -> Option<&FileResourceEntry> {
+        Some(self.resource(kind)).filter(|resource| !resource.is_absent())
+    }
+
+    /// 获取指定种类的资源条目
+    pub fn resource(&self, kind: ResourceKind) -> &FileResourceEntry {
+        match kind {
+            ResourceKind::OffsetTable => &self.offset_table_resource,
+            ResourceKind::XmlData => &self.xml_data_resource,
+            ResourceKind::BootMetadata => &self.boot_metadata_resource,
+            ResourceKind::Integrity => &self.integrity_resource,
+        }
+    }
+}
+
+#[cfg(feature = "parser")]
+impl WimParser {
+    /// 遍历文件头中的资源条目（文件头尚未读取时为空）
+    pub fn resources(&self) -> impl Iterator<Item = (ResourceKind, &FileResourceEntry)> {
+        self.header.iter().flat_map(|header| header.resources())
+    }
+
+    /// 确定资源条目所在的分卷及其在分卷文件内的位置
+    ///
+    /// 文件头引用的资源位于当前文件所在的分卷；数据流和元数据资源使用偏移表中记录的分卷号。
+    /// 提取、校验等上层功能都应通过此函数定位资源，以便支持多分卷 (`.swm`) 文件。
+    pub fn resolve_resource(
+        &mut self,
+        entry: &FileResourceEntry,
+    ) -> anyhow::Result<ResourceLocation> {
+        if entry.is_absent() {
+            return Err(anyhow::anyhow!("资源不存在，无法定位"));
+        }
+
+        let header = self.read_header()?;
+        let current_segment = header.segment_number;
+        let split = header.is_split();
+        let segment = if header.resources().any(|(_, resource)| resource == entry) {
+            current_segment
+        } else {
+            let part_number = self
+                .read_lookup_table()?
+                .iter()
+                .find(|lookup| lookup.resource == *entry)
+                .map(|lookup| lookup.part_number)
+                .ok_or_else(|| anyhow::anyhow!("偏移表中找不到该资源 (偏移: {})", entry.offset))?;
+            // SPANNED 标志不影响定位；单分卷文件中无效的分卷号 0 按当前文件处理
+            if part_number == 0 && !split {
+                current_segment
+            } else {
+                part_number
+            }
+        };
+
+        Ok(ResourceLocation {
+            segment,
+            offset: entry.offset,
+            size: entry.size,
+        })
+    }
+
+    /// 按 SHA-1 确定数据流所在的分卷及位置
+    pub fn resolve_stream(&mut self, hash: &[u8; 20]) -> anyhow::Result<ResourceLocation> {
+        let resource = self
+            .read_lookup_table()?
+            .iter()
+            .find(|entry| !entry.is_metadata() && entry.hash == *hash)
+            .map(|entry| entry.resource.clone())
+            .ok_or_else(|| anyhow::anyhow!("偏移表中找不到数据流"))?;
+        self.resolve_resource(&resource)
+    }
+
+    /// 确认资源位于当前文件所在的分卷
+    pub(crate) fn ensure_local_segment(
+        &mut self,
+        location: &ResourceLocation,
+    ) -> anyhow::Result<()> {
+        let current_segment = self.read_header()?.segment_number;
+        if location.segment != current_segment {
+            return Err(
+                anyhow::Error::new(Error::Unsupported("跨分卷读取")).context(format!(
+                    "资源位于分卷 {}，当前文件为分卷 {}，暂不支持跨分卷读取",
+                    location.segment, current_segment
+                )),
+            );
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,374 @@
+//! 底层资源读取：按分块表逐块读取（并解压）单个资源，供在原始资源之上构建自己的释放或分析逻辑
+//!
+//! 压缩资源（非固实）的布局为：分块表（除第一个分块外每个分块相对于表尾的起始偏移，
+//! 原始大小超过 4 GiB 时每项 8 字节，否则 4 字节）之后紧跟各分块的数据。
+//! 压缩后大小等于未压缩大小的分块按原样存储，其余分块按资源的压缩格式解压（XPRESS、LZX 和 LZMS）。
+//! 固实资源的布局见 `solid` 模块。
+
+use anyhow::{Context, Result};
+use std::fs::File;
+use std::io::{self, BufReader, Read, Seek, SeekFrom};
+use std::ops::Range;
+
+use crate::log::debug;
+use crate::{lzms, lzx, xpress, Compression, Error, FileResourceEntry, WimParser};
+
+/// 单个资源的顺序读取器（由 [`WimParser::open_resource`] 创建），读出的是资源的未压缩内容
+pub struct ResourceReader<'a> {
+    file: &'a mut BufReader<File>,
+    compression: Compression,
+    /// 读取器读出的字节数（固实资源中的数据流只读出其所在的一段）
+    size: u64,
+    /// 已读出的未压缩字节数
+    position: u64,
+    /// 分块大小（未压缩资源为 0）
+    chunk_size: u64,
+    /// 整个资源的未压缩大小，决定最后一个分块的大小
+    resource_size: u64,
+    /// 各分块压缩后的大小
+    chunk_sizes: Vec<u64>,
+    /// 各分块数据在文件中的绝对位置
+    chunk_starts: Vec<u64>,
+    /// 文件的当前读取位置，不连续时才重新定位
+    file_pos: u64,
+    /// 下一个要读取的分块
+    next_chunk: usize,
+    /// 当前分块的未压缩数据及读取位置
+    chunk: Vec<u8>,
+    chunk_pos: usize,
+    /// 读取第一个分块后跳过的字节数
+    skip: usize,
+    /// 固实资源的分块缓存：资源偏移和解析器中的缓存槽（读完时放回当前分块）
+    cache: Option<(u64, &'a mut Option<CachedChunk>)>,
+}
+
+/// 最近解压的一个固实资源分块
+///
+/// 固实资源的分块通常很大（ESD 为 64 MiB），其中的数据流按顺序读取时，
+/// 相邻的数据流往往位于同一分块，缓存后不必重复读取和解压。
+pub(crate) struct CachedChunk {
+    /// 固实资源的偏移
+    resource: u64,
+    index: usize,
+    data: Vec<u8>,
+}
+
+impl<'a> ResourceReader<'a> {
+    /// 未压缩资源的读取器，文件须已定位到资源开头
+    fn uncompressed(file: &'a mut BufReader<File>, compression: Compression, size: u64) -> Self {
+        Self {
+            file,
+            compression,
+            size,
+            position: 0,
+            chunk_size: 0,
+            resource_size: size,
+            chunk_sizes: Vec::new(),
+            chunk_starts: Vec::new(),
+            file_pos: 0,
+            next_chunk: 0,
+            chunk: Vec::new(),
+            chunk_pos: 0,
+            skip: 0,
+            cache: None,
+        }
+    }
+
+    /// 分块资源的读取器：`data_start` 为第一个分块在文件中的绝对位置，
+    /// 只读出未压缩数据中 `range` 的部分（从其所在的分块开始读取）
+    pub(crate) fn chunked(
+        file: &'a mut BufReader<File>,
+        compression: Compression,
+        chunk_size: u64,
+        resource_size: u64,
+        chunk_sizes: Vec<u64>,
+        data_start: u64,
+        range: Range<u64>,
+    ) -> Self {
+        let mut start = data_start;
+        let chunk_starts = chunk_sizes
+            .iter()
+            .map(|size| {
+                let chunk_start = start;
+                start += size;
+                chunk_start
+            })
+            .collect();
+        Self {
+            file,
+            compression,
+            size: range.end - range.start,
+            position: 0,
+            chunk_size,
+            resource_size,
+            chunk_sizes,
+            chunk_starts,
+            file_pos: u64::MAX,
+            next_chunk: (range.start / chunk_size) as usize,
+            chunk: Vec::new(),
+            chunk_pos: 0,
+            skip: (range.start % chunk_size) as usize,
+            cache: None,
+        }
+    }
+
+    /// 使用解析器中的分块缓存（`resource` 为固实资源的偏移）
+    pub(crate) fn with_cache(mut self, resource: u64, slot: &'a mut Option<CachedChunk>) -> Self {
+        self.cache = Some((resource, slot));
+        self
+    }
+
+    /// 读取器读出的字节数（资源或固实资源中数据流的未压缩大小）
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    /// 资源的压缩格式
+    pub fn compression(&self) -> Compression {
+        self.compression
+    }
+
+    /// 分块数量（未压缩资源为 0，固实资源中的数据流为整个固实资源的分块数）
+    pub fn chunk_count(&self) -> usize {
+        self.chunk_sizes.len()
+    }
+
+    /// 读完后把当前分块留在解析器的缓存中，供下一个读取器使用
+    fn release_chunk(&mut self) {
+        if let Some((resource, slot)) = &mut self.cache {
+            **slot = Some(CachedChunk {
+                resource: *resource,
+                index: self.next_chunk - 1,
+                data: std::mem::take(&mut self.chunk),
+            });
+        }
+    }
+
+    /// 读取并解压下一个分块到 `self.chunk`
+    fn load_next_chunk(&mut self) -> io::Result<()> {
+        let index = self.next_chunk;
+        if index >= self.chunk_sizes.len() {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        self.next_chunk += 1;
+        self.chunk_pos = std::mem::take(&mut self.skip);
+
+        if let Some((resource, slot)) = &mut self.cache {
+            let resource = *resource;
+            if let Some(cached) =
+                slot.take_if(|cached| cached.resource == resource && cached.index == index)
+            {
+                self.chunk = cached.data;
+                return Ok(());
+            }
+        }
+
+        let compressed_size = self.chunk_sizes[index] as usize;
+        let size = self
+            .chunk_size
+            .min(self.resource_size - index as u64 * self.chunk_size) as usize;
+        let start = self.chunk_starts[index];
+        if self.file_pos != start {
+            self.file.seek(SeekFrom::Start(start))?;
+        }
+
+        let mut data = vec![0u8; compressed_size];
+        self.file.read_exact(&mut data)?;
+        self.file_pos = start + compressed_size as u64;
+        self.chunk = if compressed_size == size {
+            data
+        } else {
+            decompress_chunk(self.compression, &data, size)?
+        };
+        Ok(())
+    }
+}
+
+impl Read for ResourceReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if buf.is_empty() || self.position >= self.size {
+            return Ok(0);
+        }
+        let remaining = self.size - self.position;
+        if self.chunk_size == 0 {
+            let len = buf.len().min(remaining as usize);
+            let read = self.file.read(&mut buf[..len])?;
+            if read == 0 {
+                return Err(io::ErrorKind::UnexpectedEof.into());
+            }
+            self.position += read as u64;
+            return Ok(read);
+        }
+
+        if self.chunk_pos >= self.chunk.len() {
+            self.load_next_chunk()?;
+        }
+        let available = &self.chunk[self.chunk_pos.min(self.chunk.len())..];
+        let len = buf
+            .len()
+            .min(available.len())
+            .min(usize::try_from(remaining).unwrap_or(usize::MAX));
+        if len == 0 {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        buf[..len].copy_from_slice(&available[..len]);
+        self.chunk_pos += len;
+        self.position += len as u64;
+        if self.position == self.size {
+            self.release_chunk();
+        }
+        Ok(len)
+    }
+}
+
+/// 解压单个分块（按原样存储的分块不经过此函数）
+fn decompress_chunk(compression: Compression, data: &[u8], size: usize) -> io::Result<Vec<u8>> {
+    match compression {
+        Compression::Xpress { .. } => xpress::decompress(data, size),
+        Compression::Lzx { chunk } => lzx::decompress(data, size, chunk as usize),
+        Compression::Lzms { .. } => lzms::decompress(data, size),
+        Compression::None | Compression::Unknown(_) => Err(Error::Unsupported("未知压缩格式")),
+    }
+    .map_err(io::Error::other)
+}
+
+impl WimParser {
+    /// 读取资源在文件中的原始字节（压缩资源为分块表和压缩后的分块，不解压）
+    pub(crate) fn read_raw_resource(&mut self, resource: &FileResourceEntry) -> Result<Vec<u8>> {
+        let location = self.resolve_resource(resource)?;
+        self.ensure_local_segment(&location)?;
+        self.options()
+            .resource_limits()
+            .check_memory(resource.size)?;
+        self.seek_to(resource.offset)?;
+        let mut buffer = vec![0u8; resource.size as usize];
+        self.file
+            .read_exact(&mut buffer)
+            .with_context(|| format!("读取资源数据失败，偏移: {}", resource.offset))?;
+        Ok(buffer)
+    }
+
+    /// 按 SHA-1 查找数据流的资源条目，可传给 [`open_resource`](Self::open_resource)
+    pub fn stream_resource(&mut self, hash: &[u8; 20]) -> Result<Option<FileResourceEntry>> {
+        Ok(self
+            .read_lookup_table()?
+            .iter()
+            .find(|entry| !entry.is_metadata() && entry.hash == *hash)
+            .map(|entry| entry.resource.clone()))
+    }
+
+    /// 打开资源的顺序读取器，透明地处理分块表和解压
+    ///
+    /// 资源须位于当前分卷。打开时只读取分块表，分块在读取到时才逐个读入和解压，内存占用约为一个分块。
+    /// 固实资源（ESD）中的数据流从其所在的分块开始读取，最近解压的一个分块保留在解析器中，
+    /// 按偏移表顺序读取相邻的数据流时不必重复解压。
+    pub fn open_resource(&mut self, resource: &FileResourceEntry) -> Result<ResourceReader<'_>> {
+        if resource.is_absent() {
+            return Err(anyhow::anyhow!(
+                "资源不存在 (标志: {}, 偏移: {})",
+                resource.resource_flags(),
+                resource.offset
+            ));
+        }
+        let location = self.resolve_resource(resource)?;
+        self.ensure_local_segment(&location)?;
+        if resource.is_solid() {
+            return self.open_solid_resource(resource);
+        }
+
+        let compression = self.resource_compression(resource)?;
+        if !compression.is_compressed() {
+            self.seek_to(resource.offset)?;
+            return Ok(ResourceReader::uncompressed(
+                &mut self.file,
+                compression,
+                resource.size,
+            ));
+        }
+
+        let chunk_size = chunk_size_of(compression)?;
+        let limits = self.options().resource_limits();
+        limits.check_memory(chunk_size)?;
+
+        let chunk_count = resource.original_size.div_ceil(chunk_size);
+        let entry_size: u64 = if resource.original_size > u64::from(u32::MAX) {
+            8
+        } else {
+            4
+        };
+        let table_size = chunk_count.saturating_sub(1).saturating_mul(entry_size);
+        if table_size > resource.size {
+            return Err(anyhow::Error::new(Error::Truncated {
+                expected: usize::try_from(table_size).unwrap_or(usize::MAX),
+                actual: usize::try_from(resource.size).unwrap_or(usize::MAX),
+            })
+            .context(format!("资源分块表被截断 (偏移: {})", resource.offset)));
+        }
+        limits.check_memory(table_size)?;
+
+        self.seek_to(resource.offset)?;
+        let mut table = vec![0u8; table_size as usize];
+        self.file
+            .read_exact(&mut table)
+            .with_context(|| format!("读取资源分块表失败，偏移: {}", resource.offset))?;
+
+        let data_size = resource.size - table_size;
+        let mut starts = Vec::with_capacity(chunk_count as usize + 1);
+        starts.push(0u64);
+        for entry in table.chunks_exact(entry_size as usize) {
+            starts.push(match entry_size {
+                8 => u64::from_le_bytes(entry.try_into().unwrap()),
+                _ => u64::from(u32::from_le_bytes(entry.try_into().unwrap())),
+            });
+        }
+        if chunk_count > 0 {
+            starts.push(data_size);
+        }
+        let chunk_sizes = starts
+            .windows(2)
+            .enumerate()
+            .map(|(index, pair)| {
+                let size = chunk_size.min(resource.original_size - index as u64 * chunk_size);
+                match pair[1].checked_sub(pair[0]) {
+                    Some(compressed) if compressed > 0 && compressed <= size => Ok(compressed),
+                    _ => Err(anyhow::anyhow!(
+                        "资源分块表损坏 (偏移: {}, 分块 {}: {} - {})",
+                        resource.offset,
+                        index,
+                        pair[0],
+                        pair[1]
+                    )),
+                }
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        debug!(
+            "打开资源 (偏移: {}, 格式: {}, {} 个分块)",
+            resource.offset,
+            compression,
+            chunk_sizes.len()
+        );
+        let data_start = self.base_offset() + resource.offset + table_size;
+        Ok(ResourceReader::chunked(
+            &mut self.file,
+            compression,
+            chunk_size,
+            resource.original_size,
+            chunk_sizes,
+            data_start,
+            0..resource.original_size,
+        ))
+    }
+}
+
+/// 压缩资源的分块大小，格式无法识别时返回 [`Error::Unsupported`]
+pub(crate) fn chunk_size_of(compression: Compression) -> Result<u64> {
+    compression
+        .chunk_size()
+        .filter(|&chunk| chunk > 0)
+        .map(u64::from)
+        .ok_or_else(|| {
+            anyhow::Error::new(Error::Unsupported("未知压缩格式"))
+                .context(format!("无法识别资源的压缩格式: {compression}"))
+        })
+}
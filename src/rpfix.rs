@@ -0,0 +1,109 @@
+//! 重解析点修正 (RP_FIX)：捕获时把指向捕获根目录内的绝对链接目标改写为相对捕获根目录的路径，
+//! 释放时再把它们重新指向释放目标之下
+//!
+//! 修正后的目标以 `\` 开头、不含盘符（例如 `C:\Users` 从 `C:\` 捕获后记为 `\Users`）。
+//! 目标在捕获根目录之外的链接保持原样，目录项设置 `WIM_RP_FLAG_NOT_FIXED`。
+
+use crate::metadata::IO_REPARSE_TAG_MOUNT_POINT;
+
+/// NT 路径前缀
+const NT_PATH_PREFIX: &str = r"\??\";
+
+/// 规范化 Windows 路径：分隔符统一为 `\`，去掉 `\??\` 前缀和结尾的 `\`
+#[cfg_attr(not(feature = "verify"), allow(dead_code))]
+fn normalize(path: &str) -> String {
+    let path = path.replace('/', "\\");
+    let path = path.strip_prefix(NT_PATH_PREFIX).unwrap_or(&path);
+    path.trim_end_matches('\\').to_string()
+}
+
+/// 是否为绝对路径（带盘符、UNC 或 NT 路径）
+pub(crate) fn is_absolute_target(target: &str) -> bool {
+    let target = target.replace('/', "\\");
+    let bytes = target.as_bytes();
+    target.starts_with('\\')
+        || (bytes.len() >= 2 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':')
+}
+
+/// 捕获时修正链接目标：目标位于 `capture_root` 之内时返回以 `\` 开头的镜像内路径，
+/// 否则返回 `None`（比较不区分大小写）
+#[cfg_attr(not(feature = "verify"), allow(dead_code))]
+pub(crate) fn fix_capture_target(target: &str, capture_root: &str) -> Option<String> {
+    if !is_absolute_target(target) {
+        return None;
+    }
+    let target = normalize(target);
+    let root = normalize(capture_root);
+    let prefix = target.get(..root.len())?;
+    if !prefix.eq_ignore_ascii_case(&root) {
+        return None;
+    }
+    match &target[root.len()..] {
+        "" => Some("\\".to_string()),
+        rest if rest.starts_with('\\') => Some(rest.to_string()),
+        _ => None,
+    }
+}
+
+/// 从链接所在目录指向镜像内路径的相对路径（`/` 分隔）
+pub(crate) fn relative_link_target(link_path: &str, image_path: &str) -> String {
+    let link_dirs: Vec<&str> = link_path
+        .split('/')
+        .filter(|part| !part.is_empty())
+        .collect();
+    let link_dirs = &link_dirs[..link_dirs.len().saturating_sub(1)];
+    let target: Vec<&str> = image_path
+        .split('/')
+        .filter(|part| !part.is_empty())
+        .collect();
+
+    let common = link_dirs
+        .iter()
+        .zip(&target)
+        .take_while(|(a, b)| a.eq_ignore_ascii_case(b))
+        .count();
+    let mut parts: Vec<&str> = vec![".."; link_dirs.len() - common];
+    parts.extend(&target[common..]);
+    if parts.is_empty() {
+        ".".to_string()
+    } else {
+        parts.join("/")
+    }
+}
+
+/// 编码符号链接或目录联接的重解析数据（不含 8 字节的重解析头）
+///
+/// 未修正的绝对目标的替代名称带 `\??\` 前缀；目录联接的名称以空字符结尾，
+/// 符号链接带 `SYMLINK_FLAG_RELATIVE` 标志字段。
+#[cfg_attr(not(feature = "verify"), allow(dead_code))]
+pub(crate) fn encode_link_reparse_data(tag: u32, target: &str, fixed: bool) -> Vec<u8> {
+    let print_name = target.replace('/', "\\");
+    let relative = !is_absolute_target(&print_name);
+    let substitute = if relative || fixed {
+        print_name.clone()
+    } else {
+        format!("{NT_PATH_PREFIX}{print_name}")
+    };
+    let encode =
+        |name: &str| -> Vec<u8> { name.encode_utf16().flat_map(u16::to_le_bytes).collect() };
+    let (substitute, print_name) = (encode(&substitute), encode(&print_name));
+
+    let junction = tag == IO_REPARSE_TAG_MOUNT_POINT;
+    let terminator: u16 = if junction { 2 } else { 0 };
+    let substitute_len = substitute.len() as u16;
+    let print_len = print_name.len() as u16;
+
+    let mut data = Vec::new();
+    data.extend_from_slice(&0u16.to_le_bytes());
+    data.extend_from_slice(&substitute_len.to_le_bytes());
+    data.extend_from_slice(&(substitute_len + terminator).to_le_bytes());
+    data.extend_from_slice(&print_len.to_le_bytes());
+    if !junction {
+        data.extend_from_slice(&u32::from(relative).to_le_bytes());
+    }
+    for name in [&substitute, &print_name] {
+        data.extend_from_slice(name);
+        data.resize(data.len() + usize::from(terminator), 0);
+    }
+    data
+}
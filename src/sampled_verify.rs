@@ -0,0 +1,171 @@
+//! 抽样校验：随机校验一部分数据流，作为大型目录的快速冒烟检查
+
+use anyhow::Result;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::fmt::{Align, Table, ToTable};
+use crate::log::info;
+use crate::{StreamStatus, StreamVerification, StreamVerifyOptions, WimParser};
+
+/// 报告中用于计算置信度的损坏比例
+const REPORTED_CORRUPT_FRACTION: f64 = 0.01;
+
+/// SplitMix64 伪随机数生成器（相同种子得到相同的抽样）
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// `0..bound` 范围内的随机数
+    fn below(&mut self, bound: usize) -> usize {
+        (self.next() % bound as u64) as usize
+    }
+}
+
+/// 抽样校验报告
+#[derive(Debug, Clone)]
+pub struct SampledVerification {
+    /// 抽样比例（百分比）
+    pub percent: f64,
+    /// 随机种子（传给 [`WimParser::verify_sampled_seeded`] 可复现同一抽样）
+    pub seed: u64,
+    /// 可校验的资源数量（未压缩、非固实、位于当前分卷）
+    pub population: usize,
+    /// 抽中的资源数量
+    pub sampled: usize,
+    /// 校验结果；未抽中的资源标记为 [`StreamStatus::Skipped`]
+    pub verification: StreamVerification,
+}
+
+impl SampledVerification {
+    /// 抽中的资源中没有不一致或读取失败
+    pub fn is_ok(&self) -> bool {
+        self.verification.is_ok()
+    }
+
+    /// 抽中的资源中校验失败的比例
+    pub fn failure_rate(&self) -> f64 {
+        let checked = self.checked();
+        if checked == 0 {
+            return 0.0;
+        }
+        self.verification.failures().count() as f64 / checked as f64
+    }
+
+    /// 可校验资源中有 `fraction`（0.0 到 1.0）损坏时，本次抽样至少发现一个损坏资源的概率
+    ///
+    /// 按无放回抽样（超几何分布）计算；抽样没有发现失败时，可作为
+    /// “损坏比例不超过 `fraction`” 的置信度。
+    pub fn detection_probability(&self, fraction: f64) -> f64 {
+        let population = self.population;
+        let corrupt = (fraction.clamp(0.0, 1.0) * population as f64).ceil() as usize;
+        if corrupt == 0 {
+            return 0.0;
+        }
+        let mut miss = 1.0;
+        for i in 0..self.checked() {
+            if population - i <= corrupt {
+                return 1.0;
+            }
+            miss *= (population - corrupt - i) as f64 / (population - i) as f64;
+        }
+        1.0 - miss
+    }
+
+    /// 实际完成校验的抽样数量（提前停止时可能少于 [`sampled`](Self::sampled)）
+    fn checked(&self) -> usize {
+        self.verification
+            .checks
+            .iter()
+            .filter(|check| {
+                matches!(check.status, StreamStatus::Valid) || check.status.is_failure()
+            })
+            .count()
+    }
+}
+
+impl ToTable for SampledVerification {
+    fn table(&self) -> Table {
+        let mut table = Table::new(["项目", "值"]).align(1, Align::Right);
+        table.push_row(["抽样比例".to_string(), format!("{}%", self.percent)]);
+        table.push_row(["随机种子".to_string(), self.seed.to_string()]);
+        table.push_row(["可校验资源".to_string(), self.population.to_string()]);
+        table.push_row(["已抽样".to_string(), self.sampled.to_string()]);
+        for row in self.verification.table().rows() {
+            table.push_row(row.clone());
+        }
+        table.push_row([
+            "检出 1% 损坏的置信度".to_string(),
+            format!(
+                "{:.1}%",
+                self.detection_probability(REPORTED_CORRUPT_FRACTION) * 100.0
+            ),
+        ]);
+        table
+    }
+}
+
+impl WimParser {
+    /// 随机校验 `percent`（0 到 100）比例的资源，随机种子取自当前时间
+    pub fn verify_sampled(&mut self, percent: f64) -> Result<SampledVerification> {
+        let seed = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_nanos() as u64);
+        self.verify_sampled_seeded(percent, seed)
+    }
+
+    /// 使用指定随机种子随机校验 `percent`（0 到 100）比例的资源
+    ///
+    /// 抽样数量向上取整（至少 1 个）；完整校验 5 GB 的文件很慢时，可先用少量抽样快速检查，
+    /// 报告中给出相应的统计置信度（见 [`SampledVerification::detection_probability`]）。
+    pub fn verify_sampled_seeded(
+        &mut self,
+        percent: f64,
+        seed: u64,
+    ) -> Result<SampledVerification> {
+        if !(percent > 0.0 && percent <= 100.0) {
+            return Err(anyhow::anyhow!("抽样比例必须在 0 到 100 之间: {}", percent));
+        }
+
+        let mut population = 0;
+        let mut sampled = 0;
+        let verification =
+            self.verify_selected_streams(&StreamVerifyOptions::new(), |candidates| {
+                population = candidates.len();
+                sampled = ((population as f64 * percent / 100.0).ceil() as usize)
+                    .clamp(population.min(1), population);
+
+                // 部分 Fisher-Yates 洗牌，取前 `sampled` 个
+                let mut rng = SplitMix64(seed);
+                let mut candidates = candidates.to_vec();
+                for i in 0..sampled {
+                    let j = i + rng.below(candidates.len() - i);
+                    candidates.swap(i, j);
+                }
+                candidates.truncate(sampled);
+                candidates
+            })?;
+
+        let report = SampledVerification {
+            percent,
+            seed,
+            population,
+            sampled,
+            verification,
+        };
+        info!(
+            "抽样校验完成 - 种子: {}, 抽样: {}/{}, 失败: {}",
+            seed,
+            sampled,
+            population,
+            report.verification.failures().count()
+        );
+        Ok(report)
+    }
+}
@@ -0,0 +1,511 @@
+//! 安全描述符（自相关格式，SECURITY_DESCRIPTOR_RELATIVE）的解析和 SDDL 表示
+//!
+//! 元数据资源开头的安全数据块保存镜像中所有文件的 NTFS 安全描述符，目录项通过 `security_id` 引用。
+//! 解析后可以查看所有者、主要组、DACL 和 SACL 中的每个 ACE，或转为与
+//! `ConvertSecurityDescriptorToStringSecurityDescriptor` 相同写法的 SDDL 字符串。
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::Error;
+
+/// 安全描述符控制标志：DACL 存在
+const SE_DACL_PRESENT: u16 = 0x0004;
+/// 安全描述符控制标志：SACL 存在
+const SE_SACL_PRESENT: u16 = 0x0010;
+/// DACL / SACL 的 SDDL 标志（`P`、`AR`、`AI`）对应的控制位：(DACL 位, SACL 位, 名称)
+const ACL_CONTROL_FLAGS: [(u16, u16, &str); 3] = [
+    (0x1000, 0x2000, "P"),
+    (0x0100, 0x0200, "AR"),
+    (0x0400, 0x0800, "AI"),
+];
+
+/// ACE 标志的 SDDL 名称，按 Windows 输出的顺序
+const ACE_FLAG_NAMES: [(u8, &str); 7] = [
+    (0x01, "OI"),
+    (0x02, "CI"),
+    (0x04, "NP"),
+    (0x08, "IO"),
+    (0x10, "ID"),
+    (0x40, "SA"),
+    (0x80, "FA"),
+];
+
+/// 与整个访问掩码完全相同时使用的文件权限别名
+const FILE_RIGHT_ALIASES: [(u32, &str); 4] = [
+    (0x001F_01FF, "FA"),
+    (0x0012_0089, "FR"),
+    (0x0012_0116, "FW"),
+    (0x0012_00A0, "FX"),
+];
+
+/// 可以逐位拼接的访问权限名称，按 Windows 输出的顺序
+const RIGHT_NAMES: [(u32, &str); 17] = [
+    (0x1000_0000, "GA"),
+    (0x8000_0000, "GR"),
+    (0x4000_0000, "GW"),
+    (0x2000_0000, "GX"),
+    (0x0002_0000, "RC"),
+    (0x0001_0000, "SD"),
+    (0x0004_0000, "WD"),
+    (0x0008_0000, "WO"),
+    (0x0000_0010, "RP"),
+    (0x0000_0020, "WP"),
+    (0x0000_0001, "CC"),
+    (0x0000_0002, "DC"),
+    (0x0000_0004, "LC"),
+    (0x0000_0008, "SW"),
+    (0x0000_0080, "LO"),
+    (0x0000_0040, "DT"),
+    (0x0000_0100, "CR"),
+];
+
+/// 强制完整性标签 ACE 的访问策略名称
+const LABEL_POLICY_NAMES: [(u32, &str); 3] = [(0x1, "NW"), (0x2, "NR"), (0x4, "NX")];
+
+/// 有 SDDL 别名的常见 SID：(标识颁发机构, 子颁发机构, 别名)
+const SID_ALIASES: [(u64, &[u32], &str); 44] = [
+    (1, &[0], "WD"),
+    (3, &[0], "CO"),
+    (3, &[1], "CG"),
+    (5, &[2], "NU"),
+    (5, &[4], "IU"),
+    (5, &[6], "SU"),
+    (5, &[7], "AN"),
+    (5, &[9], "ED"),
+    (5, &[10], "PS"),
+    (5, &[11], "AU"),
+    (5, &[12], "RC"),
+    (5, &[18], "SY"),
+    (5, &[19], "LS"),
+    (5, &[20], "NS"),
+    (5, &[32, 544], "BA"),
+    (5, &[32, 545], "BU"),
+    (5, &[32, 546], "BG"),
+    (5, &[32, 547], "PU"),
+    (5, &[32, 548], "AO"),
+    (5, &[32, 549], "SO"),
+    (5, &[32, 550], "PO"),
+    (5, &[32, 551], "BO"),
+    (5, &[32, 552], "RE"),
+    (5, &[32, 554], "RU"),
+    (5, &[32, 555], "RD"),
+    (5, &[32, 556], "NO"),
+    (5, &[32, 558], "MU"),
+    (5, &[32, 559], "LU"),
+    (5, &[32, 568], "IS"),
+    (5, &[32, 569], "CY"),
+    (5, &[32, 573], "ER"),
+    (5, &[32, 574], "CD"),
+    (5, &[32, 575], "RA"),
+    (5, &[32, 576], "ES"),
+    (5, &[32, 577], "MS"),
+    (5, &[32, 578], "HA"),
+    (5, &[32, 579], "AA"),
+    (5, &[32, 580], "RM"),
+    (15, &[2, 1], "AC"),
+    (16, &[4096], "LW"),
+    (16, &[8192], "ME"),
+    (16, &[8448], "MP"),
+    (16, &[12288], "HI"),
+    (16, &[16384], "SI"),
+];
+
+fn truncated(expected: usize, actual: usize) -> Error {
+    Error::Truncated { expected, actual }
+}
+
+fn read_u16(data: &[u8], offset: usize) -> Result<u16, Error> {
+    data.get(offset..offset + 2)
+        .map(|bytes| u16::from_le_bytes([bytes[0], bytes[1]]))
+        .ok_or(truncated(offset + 2, data.len()))
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Result<u32, Error> {
+    data.get(offset..offset + 4)
+        .map(|bytes| u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+        .ok_or(truncated(offset + 4, data.len()))
+}
+
+/// 安全标识符 (SID)
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Sid {
+    /// 修订号（始终为 1）
+    pub revision: u8,
+    /// 标识颁发机构（48 位）
+    pub authority: u64,
+    /// 子颁发机构
+    pub sub_authorities: Vec<u32>,
+}
+
+impl Sid {
+    /// 解析二进制 SID，返回 SID 及其占用的字节数
+    pub fn parse(data: &[u8]) -> Result<(Self, usize), Error> {
+        if data.len() < 8 {
+            return Err(truncated(8, data.len()));
+        }
+        let count = usize::from(data[1]);
+        let size = 8 + count * 4;
+        if data.len() < size {
+            return Err(truncated(size, data.len()));
+        }
+        let authority = data[2..8]
+            .iter()
+            .fold(0u64, |value, &byte| (value << 8) | u64::from(byte));
+        let sub_authorities = (0..count)
+            .map(|i| read_u32(data, 8 + i * 4))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok((
+            Self {
+                revision: data[0],
+                authority,
+                sub_authorities,
+            },
+            size,
+        ))
+    }
+
+    /// 常见 SID 的两字母 SDDL 别名（例如 `BA`、`SY`）
+    pub fn sddl_alias(&self) -> Option<&'static str> {
+        SID_ALIASES
+            .iter()
+            .find(|(authority, subs, _)| {
+                *authority == self.authority && *subs == self.sub_authorities.as_slice()
+            })
+            .map(|&(_, _, alias)| alias)
+    }
+
+    /// SDDL 写法：有别名时使用别名，否则为 `S-1-...` 形式
+    pub fn to_sddl(&self) -> String {
+        match self.sddl_alias() {
+            Some(alias) => String::from(alias),
+            None => format!("{self}"),
+        }
+    }
+}
+
+impl fmt::Display for Sid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "S-{}-", self.revision)?;
+        if self.authority >= 1 << 32 {
+            write!(f, "0x{:012X}", self.authority)?;
+        } else {
+            write!(f, "{}", self.authority)?;
+        }
+        for sub in &self.sub_authorities {
+            write!(f, "-{sub}")?;
+        }
+        Ok(())
+    }
+}
+
+/// 访问控制项 (ACE)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Ace {
+    /// ACE 类型（`ACCESS_ALLOWED_ACE_TYPE` 等）
+    pub ace_type: u8,
+    /// ACE 标志（继承、审核标志）
+    pub flags: u8,
+    /// 访问掩码
+    pub mask: u32,
+    /// 对象 ACE 的对象类型 GUID
+    pub object_type: Option<[u8; 16]>,
+    /// 对象 ACE 的继承对象类型 GUID
+    pub inherited_object_type: Option<[u8; 16]>,
+    /// 受托者 SID（无法识别的 ACE 类型为 `None`）
+    pub sid: Option<Sid>,
+}
+
+impl Ace {
+    /// 解析单个 ACE，返回 ACE 及其占用的字节数
+    fn parse(data: &[u8]) -> Result<(Self, usize), Error> {
+        if data.len() < 4 {
+            return Err(truncated(4, data.len()));
+        }
+        let (ace_type, flags) = (data[0], data[1]);
+        let size = usize::from(read_u16(data, 2)?);
+        if size < 4 || data.len() < size {
+            return Err(truncated(size.max(4), data.len()));
+        }
+        let body = &data[..size];
+        let mut ace = Self {
+            ace_type,
+            flags,
+            mask: 0,
+            object_type: None,
+            inherited_object_type: None,
+            sid: None,
+        };
+        if size < 8 {
+            return Ok((ace, size));
+        }
+        ace.mask = read_u32(body, 4)?;
+
+        let mut sid_offset = 8;
+        match ace_type {
+            // 访问允许/拒绝、审核、警报及其回调变体，强制完整性标签，资源属性，范围策略
+            0..=3 | 9 | 0x0A | 0x0D | 0x0E | 0x11..=0x13 => {}
+            // 对象 ACE：对象标志之后按标志跟随两个可选的 GUID
+            5..=8 | 0x0B | 0x0C | 0x0F | 0x10 => {
+                let object_flags = read_u32(body, 8)?;
+                sid_offset = 12;
+                let mut guid = |present: bool| -> Result<Option<[u8; 16]>, Error> {
+                    if !present {
+                        return Ok(None);
+                    }
+                    let bytes = body
+                        .get(sid_offset..sid_offset + 16)
+                        .ok_or(truncated(sid_offset + 16, size))?;
+                    sid_offset += 16;
+                    Ok(bytes.try_into().ok())
+                };
+                ace.object_type = guid(object_flags & 0x1 != 0)?;
+                ace.inherited_object_type = guid(object_flags & 0x2 != 0)?;
+            }
+            _ => return Ok((ace, size)),
+        }
+        ace.sid = Some(Sid::parse(&body[sid_offset.min(size)..])?.0);
+        Ok((ace, size))
+    }
+
+    /// ACE 类型的 SDDL 名称（无法识别的类型为其十六进制数值）
+    fn type_name(&self) -> String {
+        let name = match self.ace_type {
+            0 => "A",
+            1 => "D",
+            2 => "AU",
+            3 => "AL",
+            5 => "OA",
+            6 => "OD",
+            7 => "OU",
+            8 => "OL",
+            9 => "XA",
+            0x0A => "XD",
+            0x0B => "ZA",
+            0x0D => "XU",
+            0x11 => "ML",
+            0x12 => "RA",
+            0x13 => "SP",
+            other => return format!("0x{other:02x}"),
+        };
+        String::from(name)
+    }
+
+    /// 访问掩码的 SDDL 写法：别名、逐位拼接的权限名称或十六进制数值
+    fn rights(&self) -> String {
+        if self.mask == 0 {
+            return String::new();
+        }
+        let names: &[(u32, &str)] = if self.ace_type == 0x11 {
+            &LABEL_POLICY_NAMES
+        } else {
+            if let Some(&(_, alias)) = FILE_RIGHT_ALIASES
+                .iter()
+                .find(|&&(mask, _)| mask == self.mask)
+            {
+                return String::from(alias);
+            }
+            &RIGHT_NAMES
+        };
+        let known = names.iter().fold(0, |bits, &(bit, _)| bits | bit);
+        if self.mask & !known != 0 {
+            return format!("0x{:x}", self.mask);
+        }
+        names
+            .iter()
+            .filter(|&&(bit, _)| self.mask & bit != 0)
+            .map(|&(_, name)| name)
+            .collect()
+    }
+
+    /// SDDL 写法，例如 `(A;OICI;FA;;;SY)`
+    pub fn to_sddl(&self) -> String {
+        let flags: String = ACE_FLAG_NAMES
+            .iter()
+            .filter(|&&(bit, _)| self.flags & bit != 0)
+            .map(|&(_, name)| name)
+            .collect();
+        let guid = |guid: &Option<[u8; 16]>| guid.as_ref().map(format_guid).unwrap_or_default();
+        format!(
+            "({};{};{};{};{};{})",
+            self.type_name(),
+            flags,
+            self.rights(),
+            guid(&self.object_type),
+            guid(&self.inherited_object_type),
+            self.sid.as_ref().map(Sid::to_sddl).unwrap_or_default()
+        )
+    }
+}
+
+/// GUID 的字符串形式（前三段为 little-endian），不带花括号
+fn format_guid(guid: &[u8; 16]) -> String {
+    format!(
+        "{:08x}-{:04x}-{:04x}-{:02x}{:02x}-{:02x}{:02x}{:02x}{:02x}{:02x}{:02x}",
+        u32::from_le_bytes([guid[0], guid[1], guid[2], guid[3]]),
+        u16::from_le_bytes([guid[4], guid[5]]),
+        u16::from_le_bytes([guid[6], guid[7]]),
+        guid[8],
+        guid[9],
+        guid[10],
+        guid[11],
+        guid[12],
+        guid[13],
+        guid[14],
+        guid[15]
+    )
+}
+
+/// 访问控制列表 (ACL)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Acl {
+    /// 修订号（2 或带对象 ACE 时为 4）
+    pub revision: u8,
+    /// 访问控制项
+    pub aces: Vec<Ace>,
+}
+
+impl Acl {
+    /// 解析 ACL 头及其中的所有 ACE
+    pub fn parse(data: &[u8]) -> Result<Self, Error> {
+        if data.len() < 8 {
+            return Err(truncated(8, data.len()));
+        }
+        let size = usize::from(read_u16(data, 2)?);
+        let count = usize::from(read_u16(data, 4)?);
+        let data = data.get(..size.max(8)).ok_or(truncated(size, data.len()))?;
+        let mut offset = 8;
+        let mut aces = Vec::with_capacity(count.min(data.len() / 8));
+        for _ in 0..count {
+            let (ace, ace_size) = Ace::parse(&data[offset.min(data.len())..])?;
+            aces.push(ace);
+            offset += ace_size;
+        }
+        Ok(Self {
+            revision: data[0],
+            aces,
+        })
+    }
+}
+
+/// 解析后的安全描述符
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SecurityDescriptor {
+    /// 修订号（始终为 1）
+    pub revision: u8,
+    /// 控制标志（`SE_DACL_PRESENT`、`SE_DACL_PROTECTED` 等）
+    pub control: u16,
+    /// 所有者
+    pub owner: Option<Sid>,
+    /// 主要组
+    pub group: Option<Sid>,
+    /// 自主访问控制列表；设置了 `SE_DACL_PRESENT` 但没有 ACL 时为 `None`（允许所有访问）
+    pub dacl: Option<Acl>,
+    /// 系统访问控制列表（审核和完整性标签）
+    pub sacl: Option<Acl>,
+}
+
+impl SecurityDescriptor {
+    /// 解析自相关格式的安全描述符（安全数据块和 NTFS `$Secure` 中的格式）
+    ///
+    /// ```
+    /// use wim_parser::SecurityDescriptor;
+    ///
+    /// // 所有者 BA，DACL 存在但为空指针
+    /// let bytes = [
+    ///     1, 0, 0x04, 0x80, 20, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    ///     1, 2, 0, 0, 0, 0, 0, 5, 32, 0, 0, 0, 0x20, 0x02, 0, 0,
+    /// ];
+    /// let descriptor = SecurityDescriptor::parse(&bytes)?;
+    /// assert_eq!(descriptor.to_sddl(), "O:BAD:NO_ACCESS_CONTROL");
+    /// # Ok::<(), wim_parser::Error>(())
+    /// ```
+    pub fn parse(data: &[u8]) -> Result<Self, Error> {
+        if data.len() < 20 {
+            return Err(truncated(20, data.len()));
+        }
+        let control = read_u16(data, 2)?;
+        let at = |field: usize| -> Result<Option<&[u8]>, Error> {
+            let offset = read_u32(data, field)? as usize;
+            match offset {
+                0 => Ok(None),
+                offset => data
+                    .get(offset..)
+                    .filter(|rest| !rest.is_empty())
+                    .map(Some)
+                    .ok_or(truncated(offset + 1, data.len())),
+            }
+        };
+        let sid = |field| {
+            at(field)?
+                .map(|bytes| Sid::parse(bytes).map(|(sid, _)| sid))
+                .transpose()
+        };
+        let acl = |field, present| match present {
+            true => at(field)?.map(Acl::parse).transpose(),
+            false => Ok(None),
+        };
+        Ok(Self {
+            revision: data[0],
+            control,
+            owner: sid(4)?,
+            group: sid(8)?,
+            sacl: acl(12, control & SE_SACL_PRESENT != 0)?,
+            dacl: acl(16, control & SE_DACL_PRESENT != 0)?,
+        })
+    }
+
+    /// 是否设置了 `SE_DACL_PRESENT`
+    pub fn has_dacl(&self) -> bool {
+        self.control & SE_DACL_PRESENT != 0
+    }
+
+    /// 是否设置了 `SE_SACL_PRESENT`
+    pub fn has_sacl(&self) -> bool {
+        self.control & SE_SACL_PRESENT != 0
+    }
+
+    /// SDDL 字符串，例如 `O:BAG:SYD:PAI(A;OICI;FA;;;SY)(A;;0x1200a9;;;BU)`
+    pub fn to_sddl(&self) -> String {
+        let mut sddl = String::new();
+        if let Some(owner) = &self.owner {
+            sddl.push_str("O:");
+            sddl.push_str(&owner.to_sddl());
+        }
+        if let Some(group) = &self.group {
+            sddl.push_str("G:");
+            sddl.push_str(&group.to_sddl());
+        }
+        for (prefix, present, acl, is_sacl) in [
+            ("D:", self.has_dacl(), &self.dacl, false),
+            ("S:", self.has_sacl(), &self.sacl, true),
+        ] {
+            if !present {
+                continue;
+            }
+            sddl.push_str(prefix);
+            for &(dacl_bit, sacl_bit, name) in &ACL_CONTROL_FLAGS {
+                let bit = if is_sacl { sacl_bit } else { dacl_bit };
+                if self.control & bit != 0 {
+                    sddl.push_str(name);
+                }
+            }
+            match acl {
+                Some(acl) => acl
+                    .aces
+                    .iter()
+                    .for_each(|ace| sddl.push_str(&ace.to_sddl())),
+                None => sddl.push_str("NO_ACCESS_CONTROL"),
+            }
+        }
+        sddl
+    }
+}
+
+impl fmt::Display for SecurityDescriptor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_sddl())
+    }
+}
@@ -0,0 +1,327 @@
+use anyhow::{Context, Result};
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use crate::fmt::{format_bytes, format_guid, Align, Table, ToTable};
+use crate::log::{debug, info};
+use crate::{probe_header, WimParser};
+
+/// 分卷 (`.swm`) 文件信息
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SegmentInfo {
+    /// 文件头中的 GUID（同一分卷集的所有分卷相同）
+    pub guid: [u8; 16],
+    /// 分卷号（从 1 开始）
+    pub number: u16,
+    /// 文件头声明的分卷总数
+    pub total: u16,
+    /// 文件大小
+    pub size: u64,
+    /// 文件路径
+    pub path: PathBuf,
+}
+
+/// 分卷集检查发现的问题
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SegmentIssue {
+    /// 分卷的 GUID 与当前文件不同（不属于同一分卷集）
+    GuidMismatch {
+        /// 分卷文件路径
+        path: PathBuf,
+        /// 该分卷的 GUID
+        guid: [u8; 16],
+    },
+    /// 分卷声明的分卷总数与当前文件不同
+    TotalMismatch {
+        /// 分卷文件路径
+        path: PathBuf,
+        /// 该分卷声明的总数
+        total: u16,
+    },
+    /// 分卷号为 0 或超出分卷总数
+    OutOfRange {
+        /// 分卷文件路径
+        path: PathBuf,
+        /// 分卷号
+        number: u16,
+    },
+    /// 多个文件使用同一分卷号
+    Duplicate {
+        /// 分卷号
+        number: u16,
+        /// 重复的文件路径
+        path: PathBuf,
+    },
+    /// 缺少分卷
+    Missing {
+        /// 分卷号
+        number: u16,
+        /// 按命名惯例推测的文件路径
+        expected_path: Option<PathBuf>,
+    },
+}
+
+impl fmt::Display for SegmentIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SegmentIssue::GuidMismatch { path, guid } => write!(
+                f,
+                "{} 的 GUID {} 与当前文件不同",
+                path.display(),
+                format_guid(guid)
+            ),
+            SegmentIssue::TotalMismatch { path, total } => {
+                write!(f, "{} 声明的分卷总数为 {}", path.display(), total)
+            }
+            SegmentIssue::OutOfRange { path, number } => {
+                write!(f, "{} 的分卷号 {} 超出范围", path.display(), number)
+            }
+            SegmentIssue::Duplicate { number, path } => {
+                write!(f, "分卷 {} 重复: {}", number, path.display())
+            }
+            SegmentIssue::Missing {
+                number,
+                expected_path: Some(path),
+            } => write!(f, "缺少分卷 {} ({})", number, path.display()),
+            SegmentIssue::Missing {
+                number,
+                expected_path: None,
+            } => write!(f, "缺少分卷 {number}"),
+        }
+    }
+}
+
+/// 分卷集检查结果
+#[derive(Debug, Clone)]
+pub struct SegmentValidation {
+    /// 分卷集的 GUID（取自当前文件）
+    pub guid: [u8; 16],
+    /// 分卷总数（取自当前文件）
+    pub total: u16,
+    /// 属于该分卷集的分卷，按分卷号排列
+    pub segments: Vec<SegmentInfo>,
+    /// 发现的问题
+    pub issues: Vec<SegmentIssue>,
+}
+
+impl SegmentValidation {
+    /// 所有分卷都已找到且没有冲突
+    pub fn is_complete(&self) -> bool {
+        self.issues.is_empty()
+    }
+
+    /// 缺少的分卷号
+    pub fn missing(&self) -> Vec<u16> {
+        self.issues
+            .iter()
+            .filter_map(|issue| match issue {
+                SegmentIssue::Missing { number, .. } => Some(*number),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+impl ToTable for SegmentValidation {
+    fn table(&self) -> Table {
+        let mut table = Table::new(["分卷", "大小", "路径"]).align(1, Align::Right);
+        for number in 1..=self.total {
+            match self.segments.iter().find(|s| s.number == number) {
+                Some(segment) => table.push_row([
+                    format!("{}/{}", number, self.total),
+                    format_bytes(segment.size),
+                    segment.path.display().to_string(),
+                ]),
+                None => table.push_row([
+                    format!("{}/{}", number, self.total),
+                    "-".to_string(),
+                    "(缺失)".to_string(),
+                ]),
+            }
+        }
+        table
+    }
+}
+
+/// 按 wimlib/DISM 的命名惯例生成分卷路径：`install.swm`、`install2.swm`、`install3.swm`……
+///
+/// `first` 为第 1 个分卷的路径。
+pub fn segment_path(first: &Path, number: u16) -> PathBuf {
+    if number <= 1 {
+        return first.to_path_buf();
+    }
+    let stem = first.file_stem().unwrap_or_default().to_string_lossy();
+    let name = match first.extension() {
+        Some(extension) => format!("{}{}.{}", stem, number, extension.to_string_lossy()),
+        None => format!("{stem}{number}"),
+    };
+    first.with_file_name(name)
+}
+
+/// 根据任意分卷的路径和分卷号推测第 1 个分卷的路径
+fn first_segment_path(path: &Path, number: u16) -> PathBuf {
+    if number <= 1 {
+        return path.to_path_buf();
+    }
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let Some(base) = stem.strip_suffix(&number.to_string()) else {
+        return path.to_path_buf();
+    };
+    let name = match path.extension() {
+        Some(extension) => format!("{}.{}", base, extension.to_string_lossy()),
+        None => base.to_string(),
+    };
+    path.with_file_name(name)
+}
+
+/// 读取文件头，生成分卷信息
+fn read_segment_info(path: &Path) -> Result<SegmentInfo> {
+    let header =
+        probe_header(path).with_context(|| format!("无法读取分卷文件头: {}", path.display()))?;
+    let size = std::fs::metadata(path)
+        .with_context(|| format!("无法读取分卷文件信息: {}", path.display()))?
+        .len();
+    Ok(SegmentInfo {
+        guid: header.guid,
+        number: header.segment_number,
+        total: header.total_segments,
+        size,
+        path: path.to_path_buf(),
+    })
+}
+
+impl WimParser {
+    /// 当前文件作为分卷的信息
+    pub fn segment_info(&mut self) -> Result<SegmentInfo> {
+        let header = self.read_header()?;
+        let (guid, number, total) = (header.guid, header.segment_number, header.total_segments);
+        let size = self
+            .file
+            .get_ref()
+            .metadata()
+            .context("无法读取文件信息")?
+            .len()
+            .saturating_sub(self.base_offset());
+        Ok(SegmentInfo {
+            guid,
+            number,
+            total,
+            size,
+            path: self.path.clone().unwrap_or_default(),
+        })
+    }
+
+    /// 登记同一分卷集中的其他分卷文件（同一路径重复登记时更新其信息）
+    pub fn register_segment<P: AsRef<Path>>(&mut self, path: P) -> Result<SegmentInfo> {
+        let info = read_segment_info(path.as_ref())?;
+        debug!(
+            "登记分卷 {}/{}: {}",
+            info.number,
+            info.total,
+            info.path.display()
+        );
+        self.segments.retain(|segment| segment.path != info.path);
+        self.segments.push(info.clone());
+        Ok(info)
+    }
+
+    /// 按命名惯例在当前文件所在目录中查找并登记其余分卷，返回新登记的数量
+    pub fn discover_segments(&mut self) -> Result<usize> {
+        let current = self.segment_info()?;
+        if current.path.as_os_str().is_empty() {
+            return Ok(0);
+        }
+
+        let first = first_segment_path(&current.path, current.number);
+        let mut registered = 0;
+        for number in (1..=current.total).filter(|&number| number != current.number) {
+            let path = segment_path(&first, number);
+            if !path.is_file() || self.segments.iter().any(|s| s.path == path) {
+                continue;
+            }
+            self.register_segment(&path)?;
+            registered += 1;
+        }
+        Ok(registered)
+    }
+
+    /// 当前文件和已登记的分卷，按分卷号排列
+    pub fn segments(&mut self) -> Result<Vec<SegmentInfo>> {
+        let current = self.segment_info()?;
+        let mut segments: Vec<SegmentInfo> = self
+            .segments
+            .iter()
+            .filter(|segment| segment.path != current.path)
+            .cloned()
+            .collect();
+        segments.insert(0, current);
+        segments.sort_by_key(|segment| segment.number);
+        Ok(segments)
+    }
+
+    /// 检查分卷集：所有分卷的 GUID 一致，分卷号连续覆盖 `1..=total`
+    pub fn validate_segments(&mut self) -> Result<SegmentValidation> {
+        let current = self.segment_info()?;
+        let first = (!current.path.as_os_str().is_empty())
+            .then(|| first_segment_path(&current.path, current.number));
+
+        let mut validation = SegmentValidation {
+            guid: current.guid,
+            total: current.total,
+            segments: Vec::new(),
+            issues: Vec::new(),
+        };
+
+        for segment in self.segments()? {
+            let issue = if segment.guid != current.guid {
+                Some(SegmentIssue::GuidMismatch {
+                    path: segment.path.clone(),
+                    guid: segment.guid,
+                })
+            } else if segment.total != current.total {
+                Some(SegmentIssue::TotalMismatch {
+                    path: segment.path.clone(),
+                    total: segment.total,
+                })
+            } else if segment.number == 0 || segment.number > current.total {
+                Some(SegmentIssue::OutOfRange {
+                    path: segment.path.clone(),
+                    number: segment.number,
+                })
+            } else if validation
+                .segments
+                .iter()
+                .any(|s| s.number == segment.number)
+            {
+                Some(SegmentIssue::Duplicate {
+                    number: segment.number,
+                    path: segment.path.clone(),
+                })
+            } else {
+                None
+            };
+
+            match issue {
+                Some(issue) => validation.issues.push(issue),
+                None => validation.segments.push(segment),
+            }
+        }
+
+        for number in 1..=current.total {
+            if !validation.segments.iter().any(|s| s.number == number) {
+                validation.issues.push(SegmentIssue::Missing {
+                    number,
+                    expected_path: first.as_ref().map(|first| segment_path(first, number)),
+                });
+            }
+        }
+
+        info!(
+            "分卷集检查完成: {}/{} 个分卷, {} 个问题",
+            validation.segments.len(),
+            validation.total,
+            validation.issues.len()
+        );
+        Ok(validation)
+    }
+}
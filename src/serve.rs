@@ -0,0 +1,465 @@
+//! 只读 HTTP 服务：直接由 WIM 文件提供镜像中文件的浏览和下载，供网页界面查看归档镜像
+//!
+//! - `GET /<目录>/`：目录列表，默认为 HTML，`?format=json` 或 `Accept: application/json` 时为 JSON
+//!   （`{"path":..,"entries":[{"name":..,"directory":..,"size":..,"last_write_time":..}]}`）
+//! - `GET /<文件>`：文件的未命名数据流，支持单个 `Range: bytes=` 范围（206 / 416）
+//! - `HEAD` 与 `GET` 相同但不返回正文，其余方法返回 405
+//!
+//! 路径按 UTF-8 百分号解码，名称按 [`NameMatching`](crate::NameMatching) 的默认规则比较。
+//! 连接按顺序逐个处理，每个请求后关闭连接；读写超时（默认 30 秒，见 [`ImageServer::set_timeout`]）
+//! 防止不发送请求或不读取响应的客户端阻塞整个服务。
+
+use anyhow::{Context, Result};
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::time::Duration;
+
+use crate::error::json_string;
+use crate::log::{debug, info};
+use crate::{ImageMetadata, WimDirEntry, WimParser};
+
+/// 请求行和请求头的最大总长度
+const MAX_REQUEST_HEAD: u64 = 16 * 1024;
+
+/// 连接的默认读写超时
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// 由 [`WimParser::bind_image`] 创建的镜像 HTTP 服务
+pub struct ImageServer<'a> {
+    parser: &'a mut WimParser,
+    listener: TcpListener,
+    metadata: ImageMetadata,
+    /// 数据流 SHA-1 到未压缩大小的索引
+    sizes: HashMap<[u8; 20], u64>,
+    /// 单个连接的读写超时
+    timeout: Duration,
+}
+
+/// 解析后的请求
+struct Request {
+    method: String,
+    path: String,
+    query: String,
+    accept_json: bool,
+    range: Option<String>,
+}
+
+/// 单个字节范围
+enum ByteRange {
+    /// 整个文件
+    Full,
+    /// `start..end`（不含 `end`）
+    Partial(u64, u64),
+    /// 无法满足
+    Unsatisfiable,
+}
+
+impl ImageServer<'_> {
+    /// 实际监听的地址（绑定端口 0 时由系统分配）
+    pub fn local_addr(&self) -> Result<SocketAddr> {
+        self.listener.local_addr().context("获取监听地址失败")
+    }
+
+    /// 设置单个连接的读写超时（默认 30 秒）
+    ///
+    /// 超时的连接按出错处理并关闭，服务继续处理下一个连接。`Duration::ZERO` 表示不限时。
+    pub fn set_timeout(&mut self, timeout: Duration) {
+        self.timeout = timeout;
+    }
+
+    /// 持续处理连接，直到接受连接出错；单个连接的错误只记录日志，不中断服务
+    pub fn run(mut self) -> Result<()> {
+        info!(
+            "开始提供镜像 {} 的 HTTP 服务: {}",
+            self.metadata.index,
+            self.local_addr()?
+        );
+        loop {
+            let (stream, peer) = self.listener.accept().context("接受 HTTP 连接失败")?;
+            if let Err(err) = self.handle_connection(stream) {
+                debug!("处理来自 {} 的请求失败: {:#}", peer, err);
+            }
+        }
+    }
+
+    /// 接受并处理下一个连接
+    pub fn handle_next(&mut self) -> Result<()> {
+        let (stream, peer) = self.listener.accept().context("接受 HTTP 连接失败")?;
+        self.handle_connection(stream)
+            .with_context(|| format!("处理来自 {peer} 的请求失败"))
+    }
+
+    fn handle_connection(&mut self, stream: TcpStream) -> Result<()> {
+        let timeout = (!self.timeout.is_zero()).then_some(self.timeout);
+        stream
+            .set_read_timeout(timeout)
+            .and_then(|()| stream.set_write_timeout(timeout))
+            .context("设置连接超时失败")?;
+        let mut reader = BufReader::new(stream.try_clone().context("复制连接失败")?);
+        let mut writer = io::BufWriter::new(stream);
+        let request = match read_request(&mut reader)? {
+            Ok(request) => request,
+            Err(message) => return respond_text(&mut writer, 400, "Bad Request", message, false),
+        };
+        debug!("HTTP {} /{}", request.method, request.path);
+        let head_only = request.method == "HEAD";
+        if request.method != "GET" && !head_only {
+            return respond_text(
+                &mut writer,
+                405,
+                "Method Not Allowed",
+                "只支持 GET 和 HEAD".to_string(),
+                head_only,
+            );
+        }
+
+        let Some(entry) = self.metadata.root.find(&request.path) else {
+            let message = format!("镜像 {} 中找不到 /{}", self.metadata.index, request.path);
+            return respond_text(&mut writer, 404, "Not Found", message, head_only);
+        };
+        if entry.is_directory() {
+            let json =
+                request.accept_json || request.query.split('&').any(|pair| pair == "format=json");
+            let (content_type, body) = if json {
+                ("application/json", self.listing_json(&request.path, entry))
+            } else {
+                (
+                    "text/html; charset=utf-8",
+                    self.listing_html(&request.path, entry),
+                )
+            };
+            write_head(
+                &mut writer,
+                200,
+                "OK",
+                &[
+                    ("Content-Type", content_type.to_string()),
+                    ("Content-Length", body.len().to_string()),
+                ],
+            )?;
+            if !head_only {
+                writer.write_all(body.as_bytes())?;
+            }
+            return writer.flush().context("发送目录列表失败");
+        }
+
+        let hash = entry.hash;
+        let size = self.entry_size(entry);
+        let (status, reason, start, len) = match parse_range(request.range.as_deref(), size) {
+            ByteRange::Full => (200, "OK", 0, size),
+            ByteRange::Partial(start, end) => (206, "Partial Content", start, end - start),
+            ByteRange::Unsatisfiable => {
+                write_head(
+                    &mut writer,
+                    416,
+                    "Range Not Satisfiable",
+                    &[
+                        ("Content-Range", format!("bytes */{size}")),
+                        ("Content-Length", "0".to_string()),
+                    ],
+                )?;
+                return writer.flush().context("发送响应失败");
+            }
+        };
+
+        let mut reader = if hash == [0u8; 20] || head_only {
+            None
+        } else {
+            let resource = match self.parser.stream_resource(&hash)? {
+                Some(resource) => resource,
+                None => {
+                    let message = format!("/{} 的数据流不在偏移表中", request.path);
+                    return respond_text(&mut writer, 500, "Internal Server Error", message, false);
+                }
+            };
+            match self.parser.open_resource(&resource) {
+                Ok(reader) => Some(reader),
+                Err(err) => {
+                    let message = format!("读取 /{} 失败: {err:#}", request.path);
+                    return respond_text(&mut writer, 500, "Internal Server Error", message, false);
+                }
+            }
+        };
+
+        let mut headers = vec![
+            ("Content-Type", "application/octet-stream".to_string()),
+            ("Content-Length", len.to_string()),
+            ("Accept-Ranges", "bytes".to_string()),
+        ];
+        if status == 206 {
+            headers.push((
+                "Content-Range",
+                format!("bytes {}-{}/{}", start, start + len - 1, size),
+            ));
+        }
+        write_head(&mut writer, status, reason, &headers)?;
+        if let Some(reader) = &mut reader {
+            io::copy(&mut reader.by_ref().take(start), &mut io::sink())
+                .with_context(|| format!("读取 /{} 失败", request.path))?;
+            let copied = io::copy(&mut reader.by_ref().take(len), &mut writer)
+                .with_context(|| format!("发送 /{} 失败", request.path))?;
+            if copied != len {
+                return Err(anyhow::anyhow!(
+                    "/{} 的数据流比目录项记录的短 ({} < {})",
+                    request.path,
+                    copied,
+                    len
+                ));
+            }
+        }
+        writer.flush().context("发送文件内容失败")
+    }
+
+    /// 文件未命名数据流的未压缩大小（空文件或偏移表中没有的数据流为 0）
+    fn entry_size(&self, entry: &WimDirEntry) -> u64 {
+        self.sizes.get(&entry.hash).copied().unwrap_or(0)
+    }
+
+    fn listing_json(&self, path: &str, dir: &WimDirEntry) -> String {
+        let entries: Vec<String> = dir
+            .children
+            .iter()
+            .map(|child| {
+                format!(
+                    "{{\"name\":{},\"directory\":{},\"size\":{},\"last_write_time\":{}}}",
+                    json_string(&child.name),
+                    child.is_directory(),
+                    self.entry_size(child),
+                    json_string(&child.last_write_time.to_string())
+                )
+            })
+            .collect();
+        format!(
+            "{{\"path\":{},\"entries\":[{}]}}",
+            json_string(&format!("/{path}")),
+            entries.join(",")
+        )
+    }
+
+    fn listing_html(&self, path: &str, dir: &WimDirEntry) -> String {
+        let prefix: String = path
+            .split('/')
+            .filter(|part| !part.is_empty())
+            .map(|part| format!("/{}", percent_encode(part)))
+            .collect();
+        let title = html_escape(&format!("镜像 {} - /{}", self.metadata.index, path));
+        let mut html = format!(
+            "<!DOCTYPE html>\n<html><head><meta charset=\"utf-8\"><title>{title}</title></head><body>\n<h1>{title}</h1>\n<table>\n"
+        );
+        if !prefix.is_empty() {
+            let parent = prefix.rsplit_once('/').map_or("", |(parent, _)| parent);
+            let _ = writeln!(html, "<tr><td><a href=\"{parent}/\">..</a></td></tr>");
+        }
+        for child in &dir.children {
+            let href = format!("{}/{}", prefix, percent_encode(&child.name));
+            let name = html_escape(&child.name);
+            let _ = if child.is_directory() {
+                writeln!(
+                    html,
+                    "<tr><td><a href=\"{href}/\">{name}/</a></td><td></td><td>{}</td></tr>",
+                    child.last_write_time
+                )
+            } else {
+                writeln!(
+                    html,
+                    "<tr><td><a href=\"{href}\">{name}</a></td><td>{}</td><td>{}</td></tr>",
+                    self.entry_size(child),
+                    child.last_write_time
+                )
+            };
+        }
+        html.push_str("</table>\n</body></html>\n");
+        html
+    }
+}
+
+/// 读取请求行和请求头，格式错误时返回内层的 `Err`（说明），连接错误时返回外层的 `Err`
+fn read_request(reader: &mut impl BufRead) -> Result<std::result::Result<Request, String>> {
+    let mut limited = reader.take(MAX_REQUEST_HEAD);
+    let mut line = String::new();
+    if limited.read_line(&mut line).context("读取请求行失败")? == 0 {
+        return Ok(Err("请求为空".to_string()));
+    }
+    let mut parts = line.split_whitespace();
+    let (Some(method), Some(target), Some(_version)) = (parts.next(), parts.next(), parts.next())
+    else {
+        return Ok(Err(format!("请求行无效: {}", line.trim_end())));
+    };
+    let method = method.to_string();
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let Some(path) = percent_decode(path) else {
+        return Ok(Err(format!("路径编码无效: {path}")));
+    };
+    let query = query.to_string();
+
+    let mut accept_json = false;
+    let mut range = None;
+    loop {
+        let mut header = String::new();
+        if limited.read_line(&mut header).context("读取请求头失败")? == 0 {
+            return Ok(Err("请求头不完整或过长".to_string()));
+        }
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        let Some((name, value)) = header.split_once(':') else {
+            return Ok(Err(format!("请求头无效: {header}")));
+        };
+        let value = value.trim();
+        if name.eq_ignore_ascii_case("accept") {
+            accept_json = value.contains("application/json");
+        } else if name.eq_ignore_ascii_case("range") {
+            range = Some(value.to_string());
+        }
+    }
+
+    Ok(Ok(Request {
+        method,
+        path: path.trim_matches('/').to_string(),
+        query,
+        accept_json,
+        range,
+    }))
+}
+
+/// 解析 `Range` 请求头；不是单个 `bytes=` 范围时忽略，按整个文件返回
+fn parse_range(range: Option<&str>, size: u64) -> ByteRange {
+    let Some(spec) = range.and_then(|range| range.trim().strip_prefix("bytes=")) else {
+        return ByteRange::Full;
+    };
+    if spec.contains(',') {
+        return ByteRange::Full;
+    }
+    let Some((start, end)) = spec.trim().split_once('-') else {
+        return ByteRange::Full;
+    };
+    let (start, end) = match (start.trim(), end.trim()) {
+        ("", suffix) => match suffix.parse::<u64>() {
+            Ok(0) => return ByteRange::Unsatisfiable,
+            Ok(suffix) => (size.saturating_sub(suffix), size),
+            Err(_) => return ByteRange::Full,
+        },
+        (start, "") => match start.parse::<u64>() {
+            Ok(start) => (start, size),
+            Err(_) => return ByteRange::Full,
+        },
+        (start, end) => match (start.parse::<u64>(), end.parse::<u64>()) {
+            (Ok(start), Ok(end)) if start <= end => (start, end.saturating_add(1).min(size)),
+            _ => return ByteRange::Full,
+        },
+    };
+    if start >= size {
+        ByteRange::Unsatisfiable
+    } else {
+        ByteRange::Partial(start, end)
+    }
+}
+
+fn write_head(
+    writer: &mut impl Write,
+    status: u16,
+    reason: &str,
+    headers: &[(&str, String)],
+) -> Result<()> {
+    let mut head = format!("HTTP/1.1 {status} {reason}\r\nConnection: close\r\n");
+    for (name, value) in headers {
+        let _ = write!(head, "{name}: {value}\r\n");
+    }
+    head.push_str("\r\n");
+    writer.write_all(head.as_bytes()).context("发送响应头失败")
+}
+
+/// 纯文本响应（错误信息）
+fn respond_text(
+    writer: &mut impl Write,
+    status: u16,
+    reason: &str,
+    message: String,
+    head_only: bool,
+) -> Result<()> {
+    let body = message + "\n";
+    write_head(
+        writer,
+        status,
+        reason,
+        &[
+            ("Content-Type", "text/plain; charset=utf-8".to_string()),
+            ("Content-Length", body.len().to_string()),
+        ],
+    )?;
+    if !head_only {
+        writer.write_all(body.as_bytes())?;
+    }
+    writer.flush().context("发送响应失败")
+}
+
+/// 百分号解码（结果须为有效的 UTF-8）
+fn percent_decode(value: &str) -> Option<String> {
+    let bytes = value.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let hex = std::str::from_utf8(bytes.get(i + 1..i + 3)?).ok()?;
+            out.push(u8::from_str_radix(hex, 16).ok()?);
+            i += 3;
+        } else {
+            out.push(bytes[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8(out).ok()
+}
+
+/// 百分号编码路径中的一个名称（保留非保留字符）
+fn percent_encode(name: &str) -> String {
+    let mut out = String::with_capacity(name.len());
+    for byte in name.bytes() {
+        if byte.is_ascii_alphanumeric() || b"-._~".contains(&byte) {
+            out.push(byte as char);
+        } else {
+            let _ = write!(out, "%{byte:02X}");
+        }
+    }
+    out
+}
+
+fn html_escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+impl WimParser {
+    /// 在 `addr` 上绑定镜像的只读 HTTP 服务，由 [`ImageServer::run`] 或
+    /// [`ImageServer::handle_next`] 处理连接
+    ///
+    /// 绑定时解析镜像元数据并为偏移表建立大小索引，之后文件内容在请求时按需读取和解压。
+    pub fn bind_image<A: ToSocketAddrs>(&mut self, index: u32, addr: A) -> Result<ImageServer<'_>> {
+        let metadata = self.read_image_metadata(index)?;
+        let sizes = self
+            .read_lookup_table()?
+            .iter()
+            .filter(|entry| !entry.is_metadata())
+            .map(|entry| (entry.hash, entry.resource.original_size))
+            .collect();
+        let listener = TcpListener::bind(addr).context("绑定 HTTP 监听地址失败")?;
+        Ok(ImageServer {
+            parser: self,
+            listener,
+            metadata,
+            sizes,
+            timeout: DEFAULT_TIMEOUT,
+        })
+    }
+
+    /// 在 `addr` 上提供镜像的只读 HTTP 浏览和下载服务（阻塞，直到监听出错）
+    pub fn serve_image<A: ToSocketAddrs>(&mut self, index: u32, addr: A) -> Result<()> {
+        self.bind_image(index, addr)?.run()
+    }
+}
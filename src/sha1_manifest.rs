@@ -0,0 +1,332 @@
+//! `.sha1` 校验清单：`sha1sum` 格式（`<摘要>  <路径>`，以及 BSD 风格的 `SHA1 (<路径>) = <摘要>`）
+//!
+//! WIM 偏移表本身按 SHA-1 索引文件内容，因此导出清单和与清单比对都不需要读取文件数据。
+
+use anyhow::{Context, Result};
+use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
+
+use crate::fmt::{format_hash, Table, ToTable};
+use crate::log::info;
+use crate::WimParser;
+
+/// 空内容的 SHA-1（WIM 中空文件的摘要记为全零）
+const EMPTY_SHA1: [u8; 20] = [
+    0xda, 0x39, 0xa3, 0xee, 0x5e, 0x6b, 0x4b, 0x0d, 0x32, 0x55, 0xbf, 0xef, 0x95, 0x60, 0x18, 0x90,
+    0xaf, 0xd8, 0x07, 0x09,
+];
+
+/// 清单中的一项
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Sha1ManifestEntry {
+    /// 相对镜像根目录的路径（`/` 分隔）
+    pub path: String,
+    /// 文件内容的 SHA-1
+    pub hash: [u8; 20],
+}
+
+/// `.sha1` 校验清单
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Sha1Manifest {
+    entries: Vec<Sha1ManifestEntry>,
+}
+
+/// 解析 40 个十六进制字符的 SHA-1
+fn parse_sha1(hex: &str) -> Option<[u8; 20]> {
+    if hex.len() != 40 || !hex.is_ascii() {
+        return None;
+    }
+    let mut hash = [0u8; 20];
+    for (i, byte) in hash.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16).ok()?;
+    }
+    Some(hash)
+}
+
+/// 还原 `sha1sum` 对含 `\` 或换行的文件名所做的转义
+fn unescape_path(path: &str) -> String {
+    let mut out = String::with_capacity(path.len());
+    let mut chars = path.chars();
+    while let Some(c) = chars.next() {
+        match (c, chars.clone().next()) {
+            ('\\', Some('n')) => {
+                out.push('\n');
+                chars.next();
+            }
+            ('\\', Some('\\')) => {
+                out.push('\\');
+                chars.next();
+            }
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// 规范化清单路径：分隔符统一为 `/`，去掉开头的 `./` 和 `/`
+fn normalize_path(path: &str) -> String {
+    let path = path.replace('\\', "/");
+    let mut path = path.as_str();
+    loop {
+        if let Some(rest) = path.strip_prefix("./") {
+            path = rest;
+        } else if let Some(rest) = path.strip_prefix('/') {
+            path = rest;
+        } else {
+            return path.to_string();
+        }
+    }
+}
+
+/// 比较用的路径键（镜像中的路径不区分大小写）
+fn path_key(path: &str) -> String {
+    normalize_path(path).to_lowercase()
+}
+
+impl Sha1Manifest {
+    /// 创建空清单
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 添加一项
+    pub fn push(&mut self, path: &str, hash: [u8; 20]) {
+        self.entries.push(Sha1ManifestEntry {
+            path: normalize_path(path),
+            hash,
+        });
+    }
+
+    /// 所有项（按读取或添加顺序）
+    pub fn entries(&self) -> &[Sha1ManifestEntry] {
+        &self.entries
+    }
+
+    /// 项数
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// 是否为空
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// 解析清单文本
+    ///
+    /// 支持 `sha1sum` 的文本模式 (`<摘要>  <路径>`) 和二进制模式 (`<摘要> *<路径>`)、
+    /// 转义的文件名（以 `\` 开头的行），以及 BSD 风格的 `SHA1 (<路径>) = <摘要>`。
+    /// 空行和以 `#` 开头的行被忽略，开头的 UTF-8 BOM 会被去掉。
+    pub fn parse(text: &str) -> Result<Self> {
+        let mut manifest = Self::new();
+        let text = text.strip_prefix('\u{feff}').unwrap_or(text);
+        for (number, line) in text.lines().enumerate() {
+            let line = line.trim_end_matches('\r');
+            if line.trim().is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (hash, path) = Self::parse_line(line).ok_or_else(|| {
+                anyhow::anyhow!("第 {} 行不是有效的 SHA-1 清单项: {}", number + 1, line)
+            })?;
+            manifest.push(&path, hash);
+        }
+        Ok(manifest)
+    }
+
+    fn parse_line(line: &str) -> Option<([u8; 20], String)> {
+        if let Some(rest) = line.strip_prefix("SHA1 (") {
+            let (path, hex) = rest.rsplit_once(") = ")?;
+            return Some((parse_sha1(hex.trim())?, path.to_string()));
+        }
+        let (escaped, line) = match line.strip_prefix('\\') {
+            Some(rest) => (true, rest),
+            None => (false, line),
+        };
+        let hash = parse_sha1(line.get(..40)?)?;
+        let rest = line.get(40..)?;
+        let path = rest
+            .strip_prefix(" *")
+            .or_else(|| rest.strip_prefix("  "))
+            .or_else(|| rest.strip_prefix(' '))?;
+        if path.is_empty() {
+            return None;
+        }
+        let path = if escaped {
+            unescape_path(path)
+        } else {
+            path.to_string()
+        };
+        Some((hash, path))
+    }
+
+    /// 从文件读取清单
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("无法读取清单文件: {}", path.display()))?;
+        Self::parse(&text).with_context(|| format!("无法解析清单文件: {}", path.display()))
+    }
+
+    /// 以 `sha1sum` 格式写入文件
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let path = path.as_ref();
+        std::fs::write(path, self.to_string())
+            .with_context(|| format!("无法写入清单文件: {}", path.display()))
+    }
+}
+
+impl fmt::Display for Sha1Manifest {
+    /// `sha1sum` 文本模式格式，每项一行
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for entry in &self.entries {
+            if entry.path.contains(['\\', '\n']) {
+                let escaped = entry.path.replace('\\', "\\\\").replace('\n', "\\n");
+                writeln!(f, "\\{}  {}", format_hash(&entry.hash), escaped)?;
+            } else {
+                writeln!(f, "{}  {}", format_hash(&entry.hash), entry.path)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// 摘要不一致的文件
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Sha1Mismatch {
+    /// 清单中的路径
+    pub path: String,
+    /// 清单中的摘要
+    pub expected: [u8; 20],
+    /// 镜像中的摘要
+    pub actual: [u8; 20],
+}
+
+/// 清单与镜像内容的比对结果
+#[derive(Debug, Clone, Default)]
+pub struct Sha1Comparison {
+    /// 摘要一致的文件数量
+    pub matched: usize,
+    /// 摘要不一致的文件
+    pub mismatched: Vec<Sha1Mismatch>,
+    /// 清单中有、镜像中没有（或不是文件）的路径
+    pub missing: Vec<String>,
+    /// 镜像中有、清单中没有的文件
+    pub unlisted: Vec<String>,
+}
+
+impl Sha1Comparison {
+    /// 清单中的所有文件都存在且摘要一致（不考虑清单之外的文件）
+    pub fn is_ok(&self) -> bool {
+        self.mismatched.is_empty() && self.missing.is_empty()
+    }
+}
+
+impl ToTable for Sha1Comparison {
+    fn table(&self) -> Table {
+        let mut table = Table::new(["路径", "结果", "预期", "实际"]);
+        for mismatch in &self.mismatched {
+            table.push_row([
+                mismatch.path.clone(),
+                "不一致".to_string(),
+                format_hash(&mismatch.expected),
+                format_hash(&mismatch.actual),
+            ]);
+        }
+        for path in &self.missing {
+            table.push_row([
+                path.clone(),
+                "缺失".to_string(),
+                "-".to_string(),
+                "-".to_string(),
+            ]);
+        }
+        for path in &self.unlisted {
+            table.push_row([
+                path.clone(),
+                "未列出".to_string(),
+                "-".to_string(),
+                "-".to_string(),
+            ]);
+        }
+        table
+    }
+}
+
+impl WimParser {
+    /// 导出镜像中所有文件内容的 `.sha1` 清单（按路径排列，不含目录、重解析点和命名数据流）
+    ///
+    /// 摘要直接取自目录项，不读取文件数据；空文件使用空内容的 SHA-1。
+    ///
+    /// ```
+    /// # #[cfg(feature = "fixtures")] {
+    /// # let fixture = wim_parser::fixtures::MiniWim::create()?;
+    /// # let mut parser = wim_parser::WimParser::new(fixture.path())?;
+    /// let manifest = parser.sha1_manifest(1)?;
+    /// let comparison = parser.compare_sha1_manifest(1, &manifest)?;
+    /// assert!(comparison.is_ok());
+    /// # }
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    pub fn sha1_manifest(&mut self, index: u32) -> Result<Sha1Manifest> {
+        let root = self.read_metadata_root(index)?;
+        let mut manifest = Sha1Manifest::new();
+        root.walk_with_path(&mut |path, entry| {
+            if !entry.is_directory() && !entry.is_reparse_point() {
+                manifest.push(path, content_hash(&entry.hash));
+            }
+        });
+        manifest.entries.sort_by(|a, b| a.path.cmp(&b.path));
+        Ok(manifest)
+    }
+
+    /// 将 `.sha1` 清单与镜像内容比对（路径不区分大小写）
+    pub fn compare_sha1_manifest(
+        &mut self,
+        index: u32,
+        manifest: &Sha1Manifest,
+    ) -> Result<Sha1Comparison> {
+        let actual = self.sha1_manifest(index)?;
+        let mut remaining: HashMap<String, &Sha1ManifestEntry> = actual
+            .entries
+            .iter()
+            .map(|entry| (path_key(&entry.path), entry))
+            .collect();
+
+        let mut comparison = Sha1Comparison::default();
+        for expected in &manifest.entries {
+            match remaining.remove(&path_key(&expected.path)) {
+                Some(entry) if entry.hash == expected.hash => comparison.matched += 1,
+                Some(entry) => comparison.mismatched.push(Sha1Mismatch {
+                    path: expected.path.clone(),
+                    expected: expected.hash,
+                    actual: entry.hash,
+                }),
+                None => comparison.missing.push(expected.path.clone()),
+            }
+        }
+        comparison.unlisted = remaining
+            .into_values()
+            .map(|entry| entry.path.clone())
+            .collect();
+        comparison.unlisted.sort();
+
+        info!(
+            "清单比对完成: {} 项一致, {} 项不一致, {} 项缺失, {} 项未列出",
+            comparison.matched,
+            comparison.mismatched.len(),
+            comparison.missing.len(),
+            comparison.unlisted.len()
+        );
+        Ok(comparison)
+    }
+}
+
+/// 目录项中的摘要转换为文件内容的 SHA-1
+fn content_hash(hash: &[u8; 20]) -> [u8; 20] {
+    if *hash == [0u8; 20] {
+        EMPTY_SHA1
+    } else {
+        *hash
+    }
+}
@@ -0,0 +1,212 @@
+//! 固实资源 (ESD)：多个数据流拼接后整体分块压缩
+//!
+//! 偏移表中设置了 SOLID 标志、原始大小为 `0x100000000` 的条目是固实资源本身，偏移和大小
+//! 指向文件中的资源。资源开头是 16 字节的资源头（未压缩大小 8 字节、分块大小 4 字节、压缩格式 4 字节），
+//! 之后是每个分块压缩后大小的表（每项 4 字节，包括第一个分块），再之后是各分块的数据。
+//!
+//! 其余设置了 SOLID 标志的条目是其中的数据流：偏移是数据流在之前连续出现的一组固实资源的
+//! 未压缩数据（依次拼接）中的位置，大小和原始大小都是数据流的未压缩大小。
+
+use anyhow::{Context, Result};
+use std::collections::HashMap;
+use std::io::Read;
+use std::sync::Arc;
+
+use crate::log::debug;
+use crate::resource_reader::{chunk_size_of, ResourceReader};
+use crate::{Compression, Error, FileResourceEntry, WimParser};
+
+/// 固实资源头的大小
+const SOLID_HEADER_SIZE: u64 = 16;
+
+/// 固实资源头
+pub(crate) struct SolidHeader {
+    /// 未压缩大小
+    pub original_size: u64,
+    pub compression: Compression,
+}
+
+/// 数据流在固实资源中的位置
+#[derive(Debug, Clone)]
+pub(crate) struct SolidStream {
+    /// 所在的固实资源
+    pub resource: FileResourceEntry,
+    /// 在固实资源未压缩数据中的偏移
+    pub offset: u64,
+}
+
+/// 数据流条目（偏移, 原始大小）到其所在位置的索引
+pub(crate) type SolidIndex = HashMap<(u64, u64), SolidStream>;
+
+impl WimParser {
+    /// 读取固实资源头
+    pub(crate) fn read_solid_header(
+        &mut self,
+        resource: &FileResourceEntry,
+    ) -> Result<SolidHeader> {
+        let mut header = [0u8; SOLID_HEADER_SIZE as usize];
+        self.seek_to(resource.offset)?;
+        self.file
+            .read_exact(&mut header)
+            .with_context(|| format!("读取固实资源头失败 (偏移: {})", resource.offset))?;
+
+        let chunk = u32::from_le_bytes(header[8..12].try_into().unwrap());
+        let format = u32::from_le_bytes(header[12..16].try_into().unwrap());
+        Ok(SolidHeader {
+            original_size: u64::from_le_bytes(header[0..8].try_into().unwrap()),
+            compression: Compression::from_solid_format(format, chunk),
+        })
+    }
+
+    /// 固实资源中各数据流的位置（首次调用时按偏移表建立索引）
+    fn solid_index(&mut self) -> Result<Arc<SolidIndex>> {
+        if let Some(index) = &self.solid_index {
+            return Ok(index.clone());
+        }
+
+        let entries: Vec<FileResourceEntry> = self
+            .read_lookup_table()?
+            .iter()
+            .filter(|entry| entry.resource.is_solid())
+            .map(|entry| entry.resource.clone())
+            .collect();
+        let mut index = SolidIndex::new();
+        // 当前的一组固实资源及其未压缩大小；数据流条目之后再出现固实资源时开始新的一组
+        let mut group: Vec<(FileResourceEntry, u64)> = Vec::new();
+        let mut group_open = false;
+        for resource in entries {
+            if resource.is_solid_resource() {
+                if !group_open {
+                    group.clear();
+                    group_open = true;
+                }
+                let header = self.read_solid_header(&resource)?;
+                group.push((resource, header.original_size));
+                continue;
+            }
+
+            group_open = false;
+            let mut offset = resource.offset;
+            for (solid, size) in &group {
+                if offset < *size {
+                    if resource.original_size <= size - offset {
+                        index
+                            .entry((resource.offset, resource.original_size))
+                            .or_insert(SolidStream {
+                                resource: solid.clone(),
+                                offset,
+                            });
+                    }
+                    break;
+                }
+                offset -= size;
+            }
+        }
+
+        debug!("固实资源中共 {} 个数据流", index.len());
+        let index = Arc::new(index);
+        self.solid_index = Some(index.clone());
+        Ok(index)
+    }
+
+    /// 确定固实资源中的数据流所在的固实资源及偏移
+    pub(crate) fn locate_solid_stream(
+        &mut self,
+        stream: &FileResourceEntry,
+    ) -> Result<SolidStream> {
+        self.solid_index()?
+            .get(&(stream.offset, stream.original_size))
+            .cloned()
+            .ok_or_else(|| {
+                anyhow::Error::new(Error::CorruptData("数据流不在任何固实资源中")).context(format!(
+                    "找不到数据流所在的固实资源 (偏移: {}, 大小: {})",
+                    stream.offset, stream.original_size
+                ))
+            })
+    }
+
+    /// 打开固实资源（或其中一个数据流）的读取器
+    pub(crate) fn open_solid_resource(
+        &mut self,
+        resource: &FileResourceEntry,
+    ) -> Result<ResourceReader<'_>> {
+        let stream = if resource.is_solid_resource() {
+            SolidStream {
+                resource: resource.clone(),
+                offset: 0,
+            }
+        } else {
+            self.locate_solid_stream(resource)?
+        };
+        let solid = &stream.resource;
+        let header = self.read_solid_header(solid)?;
+        let size = if resource.is_solid_resource() {
+            header.original_size
+        } else {
+            resource.original_size
+        };
+
+        let compression = header.compression;
+        let chunk_size = chunk_size_of(compression)?;
+        let limits = self.options().resource_limits();
+        limits.check_memory(chunk_size)?;
+
+        let chunk_count = header.original_size.div_ceil(chunk_size);
+        let table_size = chunk_count.saturating_mul(4);
+        let data_size = solid
+            .size
+            .checked_sub(SOLID_HEADER_SIZE)
+            .and_then(|size| size.checked_sub(table_size))
+            .ok_or_else(|| {
+                anyhow::Error::new(Error::Truncated {
+                    expected: usize::try_from(SOLID_HEADER_SIZE.saturating_add(table_size))
+                        .unwrap_or(usize::MAX),
+                    actual: usize::try_from(solid.size).unwrap_or(usize::MAX),
+                })
+                .context(format!("固实资源分块表被截断 (偏移: {})", solid.offset))
+            })?;
+        limits.check_memory(table_size)?;
+
+        let mut table = vec![0u8; table_size as usize];
+        self.file
+            .read_exact(&mut table)
+            .with_context(|| format!("读取固实资源分块表失败，偏移: {}", solid.offset))?;
+        let chunk_sizes: Vec<u64> = table
+            .chunks_exact(4)
+            .map(|entry| u64::from(u32::from_le_bytes(entry.try_into().unwrap())))
+            .collect();
+        for (index, &compressed) in chunk_sizes.iter().enumerate() {
+            let size = chunk_size.min(header.original_size - index as u64 * chunk_size);
+            if compressed == 0 || compressed > size {
+                return Err(anyhow::anyhow!(
+                    "固实资源分块表损坏 (偏移: {}, 分块 {}: {} 字节)",
+                    solid.offset,
+                    index,
+                    compressed
+                ));
+            }
+        }
+        if chunk_sizes.iter().sum::<u64>() > data_size {
+            return Err(
+                anyhow::Error::new(Error::CorruptData("固实资源分块超出资源大小"))
+                    .context(format!("固实资源分块表损坏 (偏移: {})", solid.offset)),
+            );
+        }
+
+        debug!(
+            "打开固实资源中的数据 (资源偏移: {}, 格式: {}, 位置: {}, 大小: {})",
+            solid.offset, compression, stream.offset, size
+        );
+        let data_start = self.base_offset() + solid.offset + SOLID_HEADER_SIZE + table_size;
+        Ok(ResourceReader::chunked(
+            &mut self.file,
+            compression,
+            chunk_size,
+            header.original_size,
+            chunk_sizes,
+            data_start,
+            stream.offset..stream.offset + size,
+        )
+        .with_cache(solid.offset, &mut self.solid_chunk))
+    }
+}
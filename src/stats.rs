@@ -0,0 +1,154 @@
+use anyhow::Result;
+use std::collections::HashMap;
+
+use crate::fmt::{format_bytes, Align, Table, ToTable};
+use crate::log::{debug, info};
+use crate::metadata::DirEntry;
+use crate::WimParser;
+
+/// 镜像统计信息（对应 XML 中的 DIRCOUNT/FILECOUNT/TOTALBYTES）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ImageStats {
+    /// 目录数量（包含根目录）
+    pub dir_count: u32,
+    /// 文件数量
+    pub file_count: u32,
+    /// 所有数据流的未压缩总字节数（硬链接按目录项重复计算）
+    pub total_bytes: u64,
+}
+
+impl ImageStats {
+    /// 遍历目录树统计（`stream_sizes` 为数据流 SHA-1 到未压缩大小的映射）
+    pub(crate) fn from_tree(root: &DirEntry, stream_sizes: &HashMap<[u8; 20], u64>) -> Self {
+        let mut stats = ImageStats::default();
+        root.walk(&mut |entry| {
+            if entry.is_directory() {
+                stats.dir_count += 1;
+            } else {
+                stats.file_count += 1;
+            }
+
+            let hashes = std::iter::once(&entry.hash).chain(entry.streams.iter().map(|s| &s.hash));
+            for hash in hashes {
+                if let Some(size) = stream_sizes.get(hash) {
+                    stats.total_bytes += size;
+                }
+            }
+        });
+        stats
+    }
+}
+
+/// 镜像统计信息重新计算结果
+#[derive(Debug, Clone)]
+pub struct ImageRecount {
+    /// 镜像索引
+    pub index: u32,
+    /// XML 中记录的统计信息
+    pub recorded: ImageStats,
+    /// 遍历元数据资源得到的实际统计信息
+    pub actual: ImageStats,
+    /// 是否已用实际值修正了内存中的镜像信息
+    pub fixed: bool,
+}
+
+impl ImageRecount {
+    /// XML 记录与实际统计是否一致
+    pub fn is_consistent(&self) -> bool {
+        self.recorded == self.actual
+    }
+}
+
+impl std::fmt::Display for ImageStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "目录数: {}, 文件数: {}, 总字节数: {}",
+            self.dir_count, self.file_count, self.total_bytes
+        )
+    }
+}
+
+impl ToTable for ImageRecount {
+    fn table(&self) -> Table {
+        let mut table = Table::new(["统计项", "XML 记录", "实际", "状态"])
+            .align(1, Align::Right)
+            .align(2, Align::Right);
+
+        let status = |same: bool| if same { "一致" } else { "不一致" };
+        let (recorded, actual) = (self.recorded, self.actual);
+        table.push_row([
+            "目录数".to_string(),
+            recorded.dir_count.to_string(),
+            actual.dir_count.to_string(),
+            status(recorded.dir_count == actual.dir_count).to_string(),
+        ]);
+        table.push_row([
+            "文件数".to_string(),
+            recorded.file_count.to_string(),
+            actual.file_count.to_string(),
+            status(recorded.file_count == actual.file_count).to_string(),
+        ]);
+        table.push_row([
+            "总字节数".to_string(),
+            format_bytes(recorded.total_bytes),
+            format_bytes(actual.total_bytes),
+            status(recorded.total_bytes == actual.total_bytes).to_string(),
+        ]);
+        table
+    }
+}
+
+impl WimParser {
+    /// 遍历镜像元数据资源，重新计算 DIRCOUNT/FILECOUNT/TOTALBYTES 并与 XML 记录比对
+    ///
+    /// 经过编辑的 WIM 文件中 XML 统计信息可能与实际内容不一致。`fix` 为 `true` 时，
+    /// 用实际值修正内存中的 [`ImageInfo`](crate::ImageInfo)（不会写回文件）。
+    pub fn recount_image(&mut self, index: u32, fix: bool) -> Result<ImageRecount> {
+        if self.images.is_empty() {
+            self.parse_full()?;
+        }
+
+        let recorded = self
+            .get_image(index)
+            .map(|image| ImageStats {
+                dir_count: image.dir_count,
+                file_count: image.file_count,
+                total_bytes: image.total_bytes,
+            })
+            .ok_or_else(|| anyhow::anyhow!("XML 数据中没有镜像 {}", index))?;
+
+        let root = self.read_metadata_root(index)?;
+
+        // 数据流 SHA-1 -> 未压缩大小
+        let stream_sizes: HashMap<[u8; 20], u64> = self
+            .read_lookup_table()?
+            .iter()
+            .filter(|entry| !entry.is_metadata())
+            .map(|entry| (entry.hash, entry.resource.original_size))
+            .collect();
+
+        let actual = ImageStats::from_tree(&root, &stream_sizes);
+
+        debug!("镜像 {} 统计 - XML: {}, 实际: {}", index, recorded, actual);
+
+        let mut recount = ImageRecount {
+            index,
+            recorded,
+            actual,
+            fixed: false,
+        };
+
+        if fix && !recount.is_consistent() {
+            if let Some(image) = self.images.iter_mut().find(|img| img.index == index) {
+                image.dir_count = actual.dir_count;
+                image.file_count = actual.file_count;
+                image.total_bytes = actual.total_bytes;
+                recount.fixed = true;
+                info!("已修正镜像 {} 的统计信息: {}", index, actual);
+            }
+        }
+
+        Ok(recount)
+    }
+}
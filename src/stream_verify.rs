@@ -0,0 +1,447 @@
+use anyhow::Result;
+use sha1::{Digest as _, Sha1};
+use std::collections::HashSet;
+use std::fs::File;
+use std::io;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+
+use crate::fmt::{format_bytes, Align, Table, ToTable};
+use crate::log::{debug, info};
+use crate::lookup_table::LookupTableEntry;
+use crate::throttle::{self, Throttle};
+use crate::{FileResourceEntry, WimParser};
+
+/// 每次读取的块大小
+const READ_CHUNK_SIZE: usize = 1024 * 1024;
+
+/// 数据流校验选项
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StreamVerifyOptions {
+    threads: usize,
+    stop_on_first_failure: bool,
+    max_throughput: Option<u64>,
+}
+
+impl StreamVerifyOptions {
+    /// 默认选项：使用全部可用处理器，校验所有数据流
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 最大工作线程数（0 表示使用可用处理器数量）
+    pub fn threads(mut self, threads: usize) -> Self {
+        self.threads = threads;
+        self
+    }
+
+    /// 发现第一个失败的数据流后停止校验，其余数据流标记为已跳过
+    pub fn stop_on_first_failure(mut self, enabled: bool) -> Self {
+        self.stop_on_first_failure = enabled;
+        self
+    }
+
+    /// 限制所有工作线程合计的读取速率（字节/秒，令牌桶），以免后台校验占满其他服务的 I/O
+    pub fn max_throughput(mut self, bytes_per_sec: u64) -> Self {
+        self.max_throughput = Some(bytes_per_sec);
+        self
+    }
+
+    /// 实际使用的工作线程数上限
+    pub fn thread_limit(&self) -> usize {
+        match self.threads {
+            0 => thread::available_parallelism().map_or(1, |n| n.get()),
+            threads => threads,
+        }
+    }
+}
+
+/// 单个数据流的校验状态
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StreamStatus {
+    /// SHA-1 与偏移表一致
+    Valid,
+    /// SHA-1 与偏移表不一致
+    Mismatch {
+        /// 实际计算的 SHA-1
+        actual: [u8; 20],
+    },
+    /// 读取数据失败
+    ReadError(String),
+    /// 无法校验（[`verify_all_streams`](WimParser::verify_all_streams) 中的压缩资源、固实资源，或位于其他分卷）
+    Unverified(String),
+    /// 提前停止校验或未被抽样，未处理
+    Skipped,
+}
+
+impl StreamStatus {
+    /// 是否为校验失败（不一致或读取失败）
+    pub fn is_failure(&self) -> bool {
+        matches!(
+            self,
+            StreamStatus::Mismatch { .. } | StreamStatus::ReadError(_)
+        )
+    }
+}
+
+/// 单个数据流的校验结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StreamCheck {
+    /// 偏移表中记录的 SHA-1
+    pub hash: [u8; 20],
+    /// 存储字节数
+    pub size: u64,
+    /// 是否为镜像元数据资源
+    pub is_metadata: bool,
+    /// 校验状态
+    pub status: StreamStatus,
+}
+
+/// 数据流校验报告
+#[derive(Debug, Clone, Default)]
+pub struct StreamVerification {
+    /// 各数据流的结果，按偏移表顺序排列
+    pub checks: Vec<StreamCheck>,
+    /// 实际使用的工作线程数
+    pub threads: usize,
+    /// 是否因发现失败而提前停止
+    pub aborted: bool,
+}
+
+impl StreamVerification {
+    /// 没有不一致或读取失败的数据流
+    pub fn is_ok(&self) -> bool {
+        !self.checks.iter().any(|check| check.status.is_failure())
+    }
+
+    /// 校验失败的数据流
+    pub fn failures(&self) -> impl Iterator<Item = &StreamCheck> {
+        self.checks.iter().filter(|check| check.status.is_failure())
+    }
+
+    /// 校验通过的数据流数量
+    pub fn valid_count(&self) -> usize {
+        self.count(|status| matches!(status, StreamStatus::Valid))
+    }
+
+    /// 未校验的数据流数量
+    pub fn unverified_count(&self) -> usize {
+        self.count(|status| matches!(status, StreamStatus::Unverified(_)))
+    }
+
+    /// 因提前停止而跳过的数据流数量
+    pub fn skipped_count(&self) -> usize {
+        self.count(|status| matches!(status, StreamStatus::Skipped))
+    }
+
+    fn count(&self, predicate: impl Fn(&StreamStatus) -> bool) -> usize {
+        self.checks
+            .iter()
+            .filter(|check| predicate(&check.status))
+            .count()
+    }
+}
+
+impl ToTable for StreamVerification {
+    fn table(&self) -> Table {
+        let verified_bytes: u64 = self
+            .checks
+            .iter()
+            .filter(|check| check.status == StreamStatus::Valid)
+            .map(|check| check.size)
+            .sum();
+
+        let mut table = Table::new(["项目", "值"]).align(1, Align::Right);
+        table.push_row(["数据流".to_string(), self.checks.len().to_string()]);
+        table.push_row(["通过".to_string(), self.valid_count().to_string()]);
+        table.push_row(["失败".to_string(), self.failures().count().to_string()]);
+        table.push_row(["未校验".to_string(), self.unverified_count().to_string()]);
+        table.push_row(["已跳过".to_string(), self.skipped_count().to_string()]);
+        table.push_row(["已校验字节数".to_string(), format_bytes(verified_bytes)]);
+        table.push_row(["线程".to_string(), self.threads.to_string()]);
+        table
+    }
+}
+
+/// 从文件指定位置读取，不改变共享的文件位置
+#[cfg(unix)]
+fn read_exact_at(file: &File, buffer: &mut [u8], offset: u64) -> std::io::Result<()> {
+    std::os::unix::fs::FileExt::read_exact_at(file, buffer, offset)
+}
+
+/// 从文件指定位置读取，不依赖共享的文件位置
+#[cfg(windows)]
+fn read_exact_at(file: &File, mut buffer: &mut [u8], mut offset: u64) -> std::io::Result<()> {
+    use std::os::windows::fs::FileExt;
+    while !buffer.is_empty() {
+        match file.seek_read(buffer, offset)? {
+            0 => return Err(std::io::ErrorKind::UnexpectedEof.into()),
+            read => {
+                buffer = &mut buffer[read..];
+                offset += read as u64;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// 计算文件中一段未压缩数据的 SHA-1；`stop` 被设置时返回 `None`
+fn hash_range(
+    file: &File,
+    offset: u64,
+    size: u64,
+    chunk_size: usize,
+    stop: &AtomicBool,
+    throttle: Option<&Mutex<Throttle>>,
+) -> std::io::Result<Option<[u8; 20]>> {
+    let mut hasher = Sha1::new();
+    let mut buffer = vec![0u8; chunk_size.min(size as usize)];
+    let mut done = 0u64;
+    while done < size {
+        if stop.load(Ordering::Relaxed) {
+            return Ok(None);
+        }
+        let len = (size - done).min(buffer.len() as u64) as usize;
+        if let Some(throttle) = throttle {
+            throttle::consume_shared(throttle, len as u64);
+        }
+        read_exact_at(file, &mut buffer[..len], offset + done)?;
+        hasher.update(&buffer[..len]);
+        done += len as u64;
+    }
+    Ok(Some(hasher.finalize().into()))
+}
+
+/// 待校验的数据流（偏移表中的序号、位置）
+struct Job {
+    index: usize,
+    offset: u64,
+    size: u64,
+}
+
+impl WimParser {
+    /// 使用默认选项并行校验所有数据流
+    ///
+    /// ```
+    /// # #[cfg(feature = "fixtures")] {
+    /// # let fixture = wim_parser::fixtures::MiniWim::create()?;
+    /// # let mut parser = wim_parser::WimParser::new(fixture.path())?;
+    /// let report = parser.verify_all_streams()?;
+    /// assert!(report.is_ok());
+    /// assert_eq!(report.failures().count(), 0);
+    /// # }
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    pub fn verify_all_streams(&mut self) -> Result<StreamVerification> {
+        self.verify_all_streams_with(&StreamVerifyOptions::new())
+    }
+
+    /// 并行计算偏移表中每个资源的 SHA-1 并与记录的值比对
+    ///
+    /// 工作线程从共享队列中领取数据流（大的优先），线程数不超过
+    /// [`StreamVerifyOptions::threads`] 和 [`ResourceLimits`](crate::ResourceLimits)
+    /// 中的 `max_open_chunks`、`max_memory`。压缩资源、固实资源和其他分卷中的资源
+    /// 暂时无法校验，标记为 [`StreamStatus::Unverified`]。
+    pub fn verify_all_streams_with(
+        &mut self,
+        options: &StreamVerifyOptions,
+    ) -> Result<StreamVerification> {
+        self.verify_selected_streams(options, <[usize]>::to_vec)
+    }
+
+    /// 校验 `select` 从可校验资源（偏移表序号）中选出的部分，其余标记为 [`StreamStatus::Skipped`]
+    pub(crate) fn verify_selected_streams(
+        &mut self,
+        options: &StreamVerifyOptions,
+        select: impl FnOnce(&[usize]) -> Vec<usize>,
+    ) -> Result<StreamVerification> {
+        let current_segment = self.read_header()?.segment_number;
+        let entries = self.read_lookup_table()?.to_vec();
+
+        let mut checks = Vec::with_capacity(entries.len());
+        let mut jobs = Vec::new();
+        for (index, entry) in entries.iter().enumerate() {
+            let resource = &entry.resource;
+            let status = if resource.is_solid() {
+                StreamStatus::Unverified("固实资源".to_string())
+            } else if resource.is_compressed() {
+                let compression = self.resource_compression(resource)?;
+                StreamStatus::Unverified(format!("压缩资源 ({compression})"))
+            } else if entry.part_number != current_segment {
+                StreamStatus::Unverified(format!("位于分卷 {}", entry.part_number))
+            } else {
+                jobs.push(Job {
+                    index,
+                    offset: self.base_offset() + resource.offset,
+                    size: resource.size,
+                });
+                StreamStatus::Skipped
+            };
+            checks.push(StreamCheck {
+                hash: entry.hash,
+                size: resource.size,
+                is_metadata: entry.is_metadata(),
+                status,
+            });
+        }
+        let candidates: Vec<usize> = jobs.iter().map(|job| job.index).collect();
+        let selected: HashSet<usize> = select(&candidates).into_iter().collect();
+        jobs.retain(|job| selected.contains(&job.index));
+        jobs.sort_by_key(|job| std::cmp::Reverse(job.size));
+
+        // 每个工作线程持有一个读取块，线程数和块大小受资源限制约束
+        let (threads, chunk_size) = self
+            .options()
+            .resource_limits()
+            .chunk_budget(options.thread_limit().min(jobs.len()), READ_CHUNK_SIZE);
+        debug!(
+            "开始校验数据流 - 待校验: {}, 线程: {}, 读取块: {}",
+            jobs.len(),
+            threads,
+            format_bytes(chunk_size as u64)
+        );
+
+        let file = self.file.get_ref();
+        let next = AtomicUsize::new(0);
+        let stop = AtomicBool::new(false);
+        let throttle = options
+            .max_throughput
+            .map(|rate| Mutex::new(Throttle::new(rate)));
+        let results: Vec<(usize, StreamStatus)> = thread::scope(|scope| {
+            let workers: Vec<_> = (0..threads)
+                .map(|_| {
+                    scope.spawn(|| {
+                        let mut results = Vec::new();
+                        while !stop.load(Ordering::Relaxed) {
+                            let Some(job) = jobs.get(next.fetch_add(1, Ordering::Relaxed)) else {
+                                break;
+                            };
+                            let status = match hash_range(
+                                file,
+                                job.offset,
+                                job.size,
+                                chunk_size,
+                                &stop,
+                                throttle.as_ref(),
+                            ) {
+                                Ok(None) => continue,
+                                Ok(Some(actual)) if actual == entries[job.index].hash => {
+                                    StreamStatus::Valid
+                                }
+                                Ok(Some(actual)) => StreamStatus::Mismatch { actual },
+                                Err(err) => StreamStatus::ReadError(err.to_string()),
+                            };
+                            if options.stop_on_first_failure && status.is_failure() {
+                                stop.store(true, Ordering::Relaxed);
+                            }
+                            results.push((job.index, status));
+                        }
+                        results
+                    })
+                })
+                .collect();
+            workers
+                .into_iter()
+                .flat_map(|worker| worker.join().expect("校验线程异常退出"))
+                .collect()
+        });
+
+        for (index, status) in results {
+            checks[index].status = status;
+        }
+
+        let report = StreamVerification {
+            checks,
+            threads,
+            aborted: stop.load(Ordering::Relaxed),
+        };
+        info!(
+            "数据流校验完成: 通过 {}, 失败 {}, 未校验 {}, 跳过 {}",
+            report.valid_count(),
+            report.failures().count(),
+            report.unverified_count(),
+            report.skipped_count()
+        );
+        Ok(report)
+    }
+
+    /// 解压偏移表条目指向的资源，计算 SHA-1 并与条目中记录的值比对
+    ///
+    /// 与 [`verify_all_streams`](Self::verify_all_streams) 不同，压缩资源和固实资源（ESD）
+    /// 会被逐块解压后校验，相当于 wimlib 的 `--check`。数据损坏或无法解压时结果为
+    /// [`StreamStatus::ReadError`]；位于其他分卷的资源标记为 [`StreamStatus::Unverified`]。
+    ///
+    /// ```
+    /// # #[cfg(feature = "fixtures")] {
+    /// # let fixture = wim_parser::fixtures::MiniWim::create()?;
+    /// # let mut parser = wim_parser::WimParser::new(fixture.path())?;
+    /// use wim_parser::StreamStatus;
+    ///
+    /// for entry in parser.lookup_table_entries()? {
+    ///     assert_eq!(parser.verify_resource(&entry)?.status, StreamStatus::Valid);
+    /// }
+    /// # }
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    pub fn verify_resource(&mut self, entry: &LookupTableEntry) -> Result<StreamCheck> {
+        let current_segment = self.read_header()?.segment_number;
+        let status = if entry.part_number != current_segment {
+            StreamStatus::Unverified(format!("位于分卷 {}", entry.part_number))
+        } else {
+            match self.hash_resource(&entry.resource) {
+                Ok(actual) if actual == entry.hash => StreamStatus::Valid,
+                Ok(actual) => StreamStatus::Mismatch { actual },
+                Err(err) => StreamStatus::ReadError(format!("{err:#}")),
+            }
+        };
+        Ok(StreamCheck {
+            hash: entry.hash,
+            size: entry.resource.size,
+            is_metadata: entry.is_metadata(),
+            status,
+        })
+    }
+
+    /// 依次解压并校验偏移表中的所有资源（见 [`verify_resource`](Self::verify_resource)）
+    ///
+    /// ```
+    /// # #[cfg(feature = "fixtures")] {
+    /// # let fixture = wim_parser::fixtures::MiniWim::create()?;
+    /// # let mut parser = wim_parser::WimParser::new(fixture.path())?;
+    /// let report = parser.verify_all_resources()?;
+    /// assert!(report.is_ok());
+    /// assert_eq!(report.unverified_count(), 0);
+    /// # }
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    pub fn verify_all_resources(&mut self) -> Result<StreamVerification> {
+        let entries = self.read_lookup_table()?.to_vec();
+        debug!("开始解压校验所有资源 - 条目: {}", entries.len());
+        let checks = entries
+            .iter()
+            .map(|entry| self.verify_resource(entry))
+            .collect::<Result<Vec<_>>>()?;
+
+        let report = StreamVerification {
+            checks,
+            threads: 1,
+            aborted: false,
+        };
+        info!(
+            "资源校验完成: 通过 {}, 失败 {}, 未校验 {}",
+            report.valid_count(),
+            report.failures().count(),
+            report.unverified_count()
+        );
+        Ok(report)
+    }
+
+    /// 顺序读取（解压）资源并计算 SHA-1
+    fn hash_resource(&mut self, resource: &FileResourceEntry) -> Result<[u8; 20]> {
+        let mut reader = self.open_resource(resource)?;
+        let mut hasher = Sha1::new();
+        io::copy(&mut reader, &mut hasher)?;
+        Ok(hasher.finalize().into())
+    }
+}
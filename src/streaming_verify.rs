@@ -0,0 +1,189 @@
+//! 边下载边校验：按到达顺序接收文件字节，完整性表的每个分块一到齐就立即校验，
+//! 损坏的下载在出错的分块处即可中止，而不必等到下载完成
+//!
+//! 完整性表位于文件末尾，需要先单独取得（例如 HTTP 范围请求）：先取文件头，
+//! 用 [`StreamingVerifier::integrity_table_range`] 得到完整性表的字节范围，再取这一段。
+
+use anyhow::{Context, Result};
+use sha1::{Digest as _, Sha1};
+use std::ops::Range;
+
+use crate::fmt::format_hash;
+use crate::integrity::{integrity_region, parse_integrity_table, IntegrityTable};
+use crate::log::{debug, info};
+use crate::{Error, WimHeader};
+
+/// 校验失败的分块
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChunkFailure {
+    /// 分块序号（从 0 开始）
+    pub index: usize,
+    /// 分块在文件中的字节范围
+    pub range: Range<u64>,
+    /// 完整性表中的 SHA-1
+    pub expected: [u8; 20],
+    /// 实际计算的 SHA-1
+    pub actual: [u8; 20],
+}
+
+/// 渐进式完整性校验器
+#[derive(Debug, Clone)]
+pub struct StreamingVerifier {
+    region: Range<u64>,
+    table: IntegrityTable,
+    position: u64,
+    hasher: Sha1,
+    next_chunk: usize,
+    failure: Option<ChunkFailure>,
+}
+
+impl StreamingVerifier {
+    /// 完整性表资源在文件中的字节范围（文件没有完整性表时返回 `None`）
+    pub fn integrity_table_range(header: &WimHeader) -> Option<Range<u64>> {
+        let resource = &header.integrity_resource;
+        (!resource.is_absent()).then(|| resource.byte_range())
+    }
+
+    /// 由文件头和完整性表资源的原始字节创建校验器
+    ///
+    /// 完整性表的分块数必须与文件头声明的完整性区域（文件头之后到偏移表结尾）一致。
+    pub fn new(header: &WimHeader, integrity_table: &[u8]) -> Result<Self> {
+        let table = parse_integrity_table(integrity_table).context("无法解析完整性表")?;
+        let region = integrity_region(header);
+        let expected_chunks = (region.end - region.start).div_ceil(u64::from(table.chunk_size));
+        if table.hashes.len() as u64 != expected_chunks {
+            return Err(anyhow::anyhow!(
+                "完整性表有 {} 个分块，但完整性区域需要 {} 个",
+                table.hashes.len(),
+                expected_chunks
+            ));
+        }
+        debug!(
+            "渐进式校验: 区域 {}..{}，{} 个分块，分块大小 {}",
+            region.start,
+            region.end,
+            table.hashes.len(),
+            table.chunk_size
+        );
+        Ok(Self {
+            region,
+            table,
+            position: 0,
+            hasher: Sha1::new(),
+            next_chunk: 0,
+            failure: None,
+        })
+    }
+
+    /// 追加从文件开头起按顺序到达的下一段字节，返回本次新校验通过的分块数
+    ///
+    /// 某个分块校验失败时返回 [`Error::VerificationFailed`]，之后的调用都返回同样的错误；
+    /// 失败详情见 [`failure`](Self::failure)。完整性区域之外的字节（文件头、XML、完整性表）不参与校验。
+    pub fn update(&mut self, mut data: &[u8]) -> Result<usize> {
+        self.check_failed()?;
+        let mut verified = 0;
+        while !data.is_empty() && self.next_chunk < self.table.hashes.len() {
+            let chunk = self.table.chunk_range(&self.region, self.next_chunk);
+            if self.position < chunk.start {
+                let skip = (chunk.start - self.position).min(data.len() as u64) as usize;
+                self.position += skip as u64;
+                data = &data[skip..];
+                continue;
+            }
+
+            let len = (chunk.end - self.position).min(data.len() as u64) as usize;
+            self.hasher.update(&data[..len]);
+            self.position += len as u64;
+            data = &data[len..];
+            if self.position == chunk.end {
+                let actual: [u8; 20] = self.hasher.finalize_reset().into();
+                let expected = self.table.hashes[self.next_chunk];
+                if actual != expected {
+                    let failure = ChunkFailure {
+                        index: self.next_chunk,
+                        range: chunk,
+                        expected,
+                        actual,
+                    };
+                    let err = failure_error(&failure);
+                    self.failure = Some(failure);
+                    return Err(err);
+                }
+                self.next_chunk += 1;
+                verified += 1;
+            }
+        }
+        self.position += data.len() as u64;
+        Ok(verified)
+    }
+
+    /// 结束校验：所有分块都已校验通过时返回 `Ok`，数据不完整时返回 [`Error::Truncated`]
+    pub fn finish(&self) -> Result<()> {
+        self.check_failed()?;
+        if !self.is_complete() {
+            let truncated = Error::Truncated {
+                expected: usize::try_from(self.region.end).unwrap_or(usize::MAX),
+                actual: usize::try_from(self.position).unwrap_or(usize::MAX),
+            };
+            return Err(anyhow::Error::new(truncated).context(format!(
+                "下载不完整，只校验了 {}/{} 个分块",
+                self.next_chunk,
+                self.table.hashes.len()
+            )));
+        }
+        info!("渐进式校验通过: {} 个分块", self.table.hashes.len());
+        Ok(())
+    }
+
+    /// 已接收的字节数
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    /// 已校验通过的分块数
+    pub fn verified_chunks(&self) -> usize {
+        self.next_chunk
+    }
+
+    /// 分块总数
+    pub fn total_chunks(&self) -> usize {
+        self.table.hashes.len()
+    }
+
+    /// 已校验通过的比例（0.0 ~ 1.0，没有分块时为 1.0）
+    pub fn progress(&self) -> f64 {
+        match self.total_chunks() {
+            0 => 1.0,
+            total => self.next_chunk as f64 / total as f64,
+        }
+    }
+
+    /// 所有分块是否都已校验通过
+    pub fn is_complete(&self) -> bool {
+        self.failure.is_none() && self.next_chunk == self.table.hashes.len()
+    }
+
+    /// 校验失败的分块
+    pub fn failure(&self) -> Option<&ChunkFailure> {
+        self.failure.as_ref()
+    }
+
+    fn check_failed(&self) -> Result<()> {
+        match &self.failure {
+            None => Ok(()),
+            Some(failure) => Err(failure_error(failure)),
+        }
+    }
+}
+
+/// 分块校验失败对应的 [`Error::VerificationFailed`] 错误
+fn failure_error(failure: &ChunkFailure) -> anyhow::Error {
+    anyhow::Error::new(Error::VerificationFailed { failures: 1 }).context(format!(
+        "第 {} 个分块 ({}..{}) 校验失败: 预期 {}，实际 {}",
+        failure.index,
+        failure.range.start,
+        failure.range.end,
+        format_hash(&failure.expected),
+        format_hash(&failure.actual)
+    ))
+}
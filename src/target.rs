@@ -0,0 +1,774 @@
+//! 镜像释放的输出目标：释放逻辑只与 [`ApplyTarget`] 交互，本地目录、归档文件等输出各自实现
+
+use anyhow::{Context, Result};
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::apply::{
+    ApplyConflict, ApplyOptions, ConflictAction, UnsupportedEntry, UnsupportedFeature,
+};
+use crate::fmt::{format_bytes, Table, ToTable};
+use crate::glob::PathPattern;
+use crate::log::{debug, info};
+use crate::metadata::{
+    self, DirEntry, FILE_ATTRIBUTE_DIRECTORY, FILE_ATTRIBUTE_READONLY,
+    FILE_ATTRIBUTE_REPARSE_POINT, WIM_RP_FLAG_NOT_FIXED,
+};
+use crate::patch_stream;
+use crate::progress::ProgressReporter;
+use crate::rpfix;
+use crate::throttle::Throttle;
+use crate::{FileFlags, FileResourceEntry, ReparseInfo, WimParser, WimTimestamp};
+
+/// 限速时每次写入的字节数
+const THROTTLED_WRITE_SIZE: usize = 64 * 1024;
+
+/// 释放时传给输出目标的目录项元数据
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct EntryMetadata {
+    /// 文件属性
+    pub attributes: u32,
+    /// 创建时间
+    pub creation_time: WimTimestamp,
+    /// 最后访问时间
+    pub last_access_time: WimTimestamp,
+    /// 最后写入时间
+    pub last_write_time: WimTimestamp,
+}
+
+impl EntryMetadata {
+    /// 是否为目录
+    pub fn is_directory(&self) -> bool {
+        self.attributes & FILE_ATTRIBUTE_DIRECTORY != 0
+    }
+
+    /// 是否只读
+    pub fn is_readonly(&self) -> bool {
+        self.attributes & FILE_ATTRIBUTE_READONLY != 0
+    }
+
+    /// 是否为重解析点
+    pub fn is_reparse_point(&self) -> bool {
+        self.attributes & FILE_ATTRIBUTE_REPARSE_POINT != 0
+    }
+}
+
+impl From<&DirEntry> for EntryMetadata {
+    fn from(entry: &DirEntry) -> Self {
+        Self {
+            attributes: entry.attributes,
+            creation_time: entry.creation_time,
+            last_access_time: entry.last_access_time,
+            last_write_time: entry.last_write_time,
+        }
+    }
+}
+
+/// 镜像释放的输出目标
+///
+/// 路径均为镜像内以 `/` 分隔的相对路径（不含根目录），父目录总是先于其内容创建。
+/// 每个文件的内容写入完毕后对其调用 [`set_metadata`](Self::set_metadata)；
+/// 目录的元数据在全部内容写入之后按子项优先的顺序设置。
+pub trait ApplyTarget {
+    /// 创建目录
+    fn create_dir(&mut self, path: &str, metadata: &EntryMetadata) -> Result<()>;
+
+    /// 创建文件，返回写入其内容的 [`Write`]；`size` 为将写入的总字节数
+    fn create_file(
+        &mut self,
+        path: &str,
+        size: u64,
+        metadata: &EntryMetadata,
+    ) -> Result<Box<dyn Write + '_>>;
+
+    /// 设置文件或目录的时间和属性
+    fn set_metadata(&mut self, path: &str, metadata: &EntryMetadata) -> Result<()>;
+
+    /// 创建符号链接或目录联接，`target` 为链接目标（分隔符已转换为 `/`）
+    fn symlink(&mut self, path: &str, target: &str, metadata: &EntryMetadata) -> Result<()>;
+
+    /// 按原始重解析数据创建重解析点（文件或目录，由 `metadata` 决定），`data` 不含 8 字节的
+    /// 重解析头；返回 `false` 表示目标不支持，改为按符号链接或目录联接还原（默认）
+    fn reparse_point(
+        &mut self,
+        path: &str,
+        tag: u32,
+        data: &[u8],
+        metadata: &EntryMetadata,
+    ) -> Result<bool> {
+        let _ = (path, tag, data, metadata);
+        Ok(false)
+    }
+
+    /// 设置已创建的文件或目录的安全描述符（自相对格式），在 [`set_metadata`](Self::set_metadata)
+    /// 之前调用；返回 `false` 表示目标不支持 ACL（默认）
+    fn set_security_descriptor(&mut self, path: &str, descriptor: &[u8]) -> Result<bool> {
+        let _ = (path, descriptor);
+        Ok(false)
+    }
+
+    /// 为已创建的文件或目录创建命名数据流，返回写入其内容的 [`Write`]；
+    /// 返回 `None` 表示目标不支持（默认），该数据流记录在 [`ApplyReport::unsupported`] 中
+    fn create_named_stream(
+        &mut self,
+        path: &str,
+        name: &str,
+        size: u64,
+    ) -> Result<Option<Box<dyn Write + '_>>> {
+        let _ = (path, name, size);
+        Ok(None)
+    }
+
+    /// 创建指向已释放文件 `existing` 的硬链接，代替再次写入相同的内容；
+    /// 返回 `false` 表示目标不支持（默认），改为按普通文件写入
+    fn hard_link(&mut self, path: &str, existing: &str, metadata: &EntryMetadata) -> Result<bool> {
+        let _ = (path, existing, metadata);
+        Ok(false)
+    }
+
+    /// 经 RP_FIX 修正的链接目标在输出中的表示；`image_path` 为镜像内的路径（`/` 分隔，不含开头的 `/`）
+    ///
+    /// 默认转换为相对链接所在目录的路径，使链接指向释放出的内容而不是原系统上的位置。
+    fn fixed_link_target(&self, path: &str, image_path: &str) -> String {
+        rpfix::relative_link_target(path, image_path)
+    }
+
+    /// 所有内容写入之后调用一次（例如写出归档尾部）
+    fn finish(&mut self) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// 释放到本地目录
+///
+/// 已存在的文件会被覆盖，目录与已有目录合并；冲突检查见 [`WimParser::plan_apply_with`]。
+#[derive(Debug, Clone)]
+pub struct DirectoryTarget {
+    root: PathBuf,
+    /// 本次释放创建的符号链接（镜像内路径，忽略大小写）
+    links: HashSet<String>,
+}
+
+impl DirectoryTarget {
+    /// 释放到 `root` 目录（不存在时创建）
+    pub fn new<P: Into<PathBuf>>(root: P) -> Self {
+        Self {
+            root: root.into(),
+            links: HashSet::new(),
+        }
+    }
+
+    /// 目标根目录
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    fn local_path(&self, path: &str) -> PathBuf {
+        let mut local = self.root.clone();
+        local.extend(path.split('/'));
+        local
+    }
+
+    /// 要写入的本地路径，路径本身或父目录是本次释放创建的符号链接时报错（防止写到目标目录之外）
+    fn write_path(&self, path: &str) -> Result<PathBuf> {
+        let folded = path.to_lowercase();
+        if let Some(link) = std::iter::once(folded.as_str())
+            .chain(ancestors(&folded))
+            .find(|link| self.links.contains(*link))
+        {
+            return Err(anyhow::anyhow!(
+                "拒绝经由符号链接 {} 写入 {}",
+                self.local_path(link).display(),
+                path
+            ));
+        }
+        Ok(self.local_path(path))
+    }
+}
+
+impl ApplyTarget for DirectoryTarget {
+    fn create_dir(&mut self, path: &str, _metadata: &EntryMetadata) -> Result<()> {
+        let local = self.write_path(path)?;
+        fs::create_dir_all(&local).with_context(|| format!("无法创建目录: {}", local.display()))
+    }
+
+    fn create_file(
+        &mut self,
+        path: &str,
+        _size: u64,
+        _metadata: &EntryMetadata,
+    ) -> Result<Box<dyn Write + '_>> {
+        let local = self.write_path(path)?;
+        if let Some(parent) = local.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("无法创建目录: {}", parent.display()))?;
+        }
+        // 不跟随已存在的符号链接覆盖其指向的文件
+        if fs::symlink_metadata(&local).is_ok_and(|meta| meta.file_type().is_symlink()) {
+            return Err(anyhow::anyhow!("拒绝经由符号链接 {} 写入", local.display()));
+        }
+        let file =
+            File::create(&local).with_context(|| format!("无法创建文件: {}", local.display()))?;
+        Ok(Box::new(file))
+    }
+
+    fn set_metadata(&mut self, path: &str, metadata: &EntryMetadata) -> Result<()> {
+        // 符号链接的时间会作用到链接目标上，保持原样
+        if metadata.is_reparse_point() {
+            return Ok(());
+        }
+        let local = self.write_path(path)?;
+
+        let mut times = fs::FileTimes::new();
+        if let Some(time) = (!metadata.last_write_time.is_zero())
+            .then(|| metadata.last_write_time.to_system_time())
+            .flatten()
+        {
+            times = times.set_modified(time);
+        }
+        if let Some(time) = (!metadata.last_access_time.is_zero())
+            .then(|| metadata.last_access_time.to_system_time())
+            .flatten()
+        {
+            times = times.set_accessed(time);
+        }
+        #[cfg(windows)]
+        if let Some(time) = (!metadata.creation_time.is_zero())
+            .then(|| metadata.creation_time.to_system_time())
+            .flatten()
+        {
+            use std::os::windows::fs::FileTimesExt;
+            times = times.set_created(time);
+        }
+
+        #[cfg(windows)]
+        let file = {
+            use std::os::windows::fs::OpenOptionsExt;
+            // FILE_WRITE_ATTRIBUTES，目录也可以打开
+            File::options().access_mode(0x0100).open(&local)
+        };
+        #[cfg(not(windows))]
+        let file = File::open(&local);
+        file.and_then(|file| file.set_times(times))
+            .with_context(|| format!("无法设置时间: {}", local.display()))?;
+
+        if metadata.is_readonly() && !metadata.is_directory() {
+            let mut permissions = fs::metadata(&local)?.permissions();
+            permissions.set_readonly(true);
+            fs::set_permissions(&local, permissions)
+                .with_context(|| format!("无法设置只读属性: {}", local.display()))?;
+        }
+        Ok(())
+    }
+
+    /// 重新指向释放目录之下的绝对路径
+    fn fixed_link_target(&self, _path: &str, image_path: &str) -> String {
+        let local = if image_path.is_empty() {
+            self.root.clone()
+        } else {
+            self.local_path(image_path)
+        };
+        local.to_string_lossy().replace('\\', "/")
+    }
+
+    fn symlink(&mut self, path: &str, target: &str, metadata: &EntryMetadata) -> Result<()> {
+        let local = self.write_path(path)?;
+        #[cfg(unix)]
+        let result = {
+            let _ = metadata;
+            std::os::unix::fs::symlink(target, &local)
+        };
+        #[cfg(windows)]
+        let result = {
+            let target = target.replace('/', "\\");
+            if metadata.is_directory() {
+                std::os::windows::fs::symlink_dir(target, &local)
+            } else {
+                std::os::windows::fs::symlink_file(target, &local)
+            }
+        };
+        #[cfg(not(any(unix, windows)))]
+        let result: std::io::Result<()> = {
+            let _ = (target, metadata);
+            Err(std::io::ErrorKind::Unsupported.into())
+        };
+        result.with_context(|| format!("无法创建符号链接: {}", local.display()))?;
+        self.links.insert(path.to_lowercase());
+        Ok(())
+    }
+
+    /// 文件系统不支持硬链接时（如 FAT）改为写入内容
+    fn hard_link(&mut self, path: &str, existing: &str, _metadata: &EntryMetadata) -> Result<bool> {
+        let local = self.write_path(path)?;
+        match fs::hard_link(self.write_path(existing)?, &local) {
+            Ok(()) => Ok(true),
+            Err(err) => {
+                debug!("无法创建硬链接 {}: {}，改为写入内容", local.display(), err);
+                Ok(false)
+            }
+        }
+    }
+}
+
+/// 镜像释放结果
+#[derive(Debug, Clone)]
+pub struct ApplyReport {
+    /// 镜像索引
+    pub index: u32,
+    /// 创建的目录数量（不含根目录）
+    pub dir_count: u32,
+    /// 写入的文件数量
+    pub file_count: u32,
+    /// 创建的符号链接和目录联接数量
+    pub symlink_count: u32,
+    /// 创建的硬链接数量（同一硬链接组中首个文件之后的成员，不计入 `file_count`）
+    pub hard_link_count: u32,
+    /// 按原始重解析数据还原的重解析点数量
+    pub reparse_count: u32,
+    /// 设置了安全描述符的文件和目录数量
+    pub security_count: u32,
+    /// 写入的总字节数
+    pub total_bytes: u64,
+    /// 未能还原的特性（不支持的重解析点、命名数据流）
+    pub unsupported: Vec<UnsupportedEntry>,
+    /// 被过滤条件排除的文件和目录数量
+    pub filtered_count: u32,
+}
+
+impl ToTable for ApplyReport {
+    fn table(&self) -> Table {
+        let mut table = Table::new(["项目", "值"]);
+        table.push_row(["镜像".to_string(), self.index.to_string()]);
+        table.push_row(["目录数".to_string(), self.dir_count.to_string()]);
+        table.push_row(["文件数".to_string(), self.file_count.to_string()]);
+        table.push_row(["符号链接".to_string(), self.symlink_count.to_string()]);
+        table.push_row(["硬链接".to_string(), self.hard_link_count.to_string()]);
+        table.push_row(["重解析点".to_string(), self.reparse_count.to_string()]);
+        table.push_row(["ACL".to_string(), self.security_count.to_string()]);
+        table.push_row(["总大小".to_string(), format_bytes(self.total_bytes)]);
+        table.push_row(["未还原".to_string(), self.unsupported.len().to_string()]);
+        table.push_row(["已过滤".to_string(), self.filtered_count.to_string()]);
+        table
+    }
+}
+
+/// 目录项名称能否安全地作为路径组成部分（防止损坏或恶意的镜像写到目标之外）
+fn is_safe_name(name: &str) -> bool {
+    !name.is_empty() && name != "." && name != ".." && !name.contains(['/', '\\', '\0'])
+}
+
+/// 镜像内路径的各级父目录（由近及远，不含根目录）
+fn ancestors(path: &str) -> impl Iterator<Item = &str> {
+    std::iter::successors(path.rsplit_once('/').map(|(parent, _)| parent), |parent| {
+        parent.rsplit_once('/').map(|(parent, _)| parent)
+    })
+}
+
+/// 从符号链接或目录联接的重解析数据（不含 8 字节的重解析头）中解析链接目标，分隔符转换为 `/`
+fn reparse_link_target(tag: u32, data: &[u8]) -> Option<String> {
+    let target = ReparseInfo::parse(tag, data).ok()?.target()?;
+    Some(target.replace('\\', "/"))
+}
+
+impl WimParser {
+    /// 将镜像释放到任意输出目标
+    ///
+    /// 按 [`ApplyOptions`] 的过滤条件选择目录项，依次创建目录、写入文件内容、创建符号链接，
+    /// 最后设置目录的时间。不支持的重解析点和命名数据流会被跳过并记录在结果中。
+    ///
+    /// 目标实现了 [`ApplyTarget::reparse_point`]、[`ApplyTarget::set_security_descriptor`]
+    /// 或 [`ApplyTarget::create_named_stream`] 时，重解析点按原始数据还原，安全描述符和
+    /// 命名数据流一并写入。
+    ///
+    /// 文件头设置了 `RP_FIX` 时，捕获时修正过的绝对链接目标经
+    /// [`ApplyTarget::fixed_link_target`] 重新指向释放出的内容（可用
+    /// [`ApplyOptions::rp_fix`] 关闭）。
+    ///
+    /// 路径重复的目录项，或以符号链接、目录联接还原的目录项之下（或与之同名，忽略大小写）还有
+    /// 其他目录项时返回错误，防止构造的镜像经由链接写到目标之外。
+    ///
+    /// ```
+    /// # #[cfg(feature = "fixtures")] {
+    /// # let fixture = wim_parser::fixtures::MiniWim::create()?;
+    /// # let mut parser = wim_parser::WimParser::new(fixture.path())?;
+    /// use wim_parser::{ApplyOptions, DirectoryTarget};
+    ///
+    /// let out = tempfile::tempdir()?;
+    /// let mut target = DirectoryTarget::new(out.path());
+    /// let options = ApplyOptions::new().include_extensions(["exe", "dll"]);
+    /// let report = parser.apply_to(1, &mut target, &options)?;
+    /// assert_eq!(report.file_count, 4);
+    /// assert!(!out.path().join("readme.txt").exists());
+    /// # }
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    pub fn apply_to<T: ApplyTarget + ?Sized>(
+        &mut self,
+        index: u32,
+        target: &mut T,
+        options: &ApplyOptions,
+    ) -> Result<ApplyReport> {
+        let data = self.read_metadata_bytes(index)?;
+        let root = metadata::parse_metadata_resource(&data, &self.options().resource_limits())
+            .with_context(|| format!("解析镜像 {index} 的元数据资源失败"))?;
+        let descriptors = metadata::parse_security_descriptors(&data)
+            .with_context(|| format!("解析镜像 {index} 的安全数据块失败"))?;
+        let rp_fix =
+            options.rp_fix_enabled() && self.read_header()?.flags().contains(FileFlags::RP_FIX);
+
+        let resources: HashMap<[u8; 20], FileResourceEntry> = self
+            .read_lookup_table()?
+            .iter()
+            .filter(|entry| !entry.is_metadata())
+            .map(|entry| (entry.hash, entry.resource.clone()))
+            .collect();
+        let stream_sizes: HashMap<[u8; 20], u64> = resources
+            .iter()
+            .map(|(hash, resource)| (*hash, resource.original_size))
+            .collect();
+        let selection = options.select(&root, &stream_sizes);
+
+        let mut report = ApplyReport {
+            index,
+            dir_count: 0,
+            file_count: 0,
+            symlink_count: 0,
+            hard_link_count: 0,
+            reparse_count: 0,
+            security_count: 0,
+            total_bytes: 0,
+            unsupported: Vec::new(),
+            filtered_count: selection.filtered_count,
+        };
+        let mut dirs: Vec<(&str, EntryMetadata)> = Vec::new();
+        // 每个硬链接组（组 ID 和内容相同）中首个已释放文件的路径
+        let mut hard_links: HashMap<(u64, [u8; 20]), &str> = HashMap::new();
+        // 以符号链接或目录联接还原的目录项（忽略大小写），其本身和子孙不能再经由链接写到目标之外
+        let mut links: HashSet<String> = HashSet::new();
+        let mut seen: HashSet<&str> = HashSet::new();
+        let mut read_throttle = options.throughput_limit().map(Throttle::new);
+        let mut write_throttle = options.throughput_limit().map(Throttle::new);
+        let mut progress = options.progress_callback().map(|callback| {
+            let total = selection
+                .entries
+                .iter()
+                .filter(|(_, entry)| !entry.is_directory() && !entry.is_reparse_point())
+                .flat_map(|(_, entry)| {
+                    std::iter::once(&entry.hash).chain(
+                        entry
+                            .streams
+                            .iter()
+                            .filter(|s| !s.name.is_empty())
+                            .map(|s| &s.hash),
+                    )
+                })
+                .map(|hash| stream_sizes.get(hash).copied().unwrap_or(0))
+                .sum();
+            ProgressReporter::new(callback, total)
+        });
+
+        for (path, entry) in &selection.entries {
+            if !is_safe_name(&entry.name) {
+                return Err(anyhow::anyhow!(
+                    "镜像 {} 中有不安全的文件名: {:?}",
+                    index,
+                    path
+                ));
+            }
+            if !seen.insert(path) {
+                return Err(anyhow::anyhow!("镜像 {} 中有重复的目录项: {}", index, path));
+            }
+            let folded = path.to_lowercase();
+            if links.contains(&folded) {
+                return Err(anyhow::anyhow!(
+                    "镜像 {} 中的 {} 与符号链接同名，拒绝经由链接写入",
+                    index,
+                    path
+                ));
+            }
+            if let Some(link) = ancestors(&folded).find(|parent| links.contains(*parent)) {
+                return Err(anyhow::anyhow!(
+                    "镜像 {} 中的 {} 位于符号链接 {} 之下，拒绝经由链接写入",
+                    index,
+                    path,
+                    link
+                ));
+            }
+            let metadata = EntryMetadata::from(*entry);
+            let mut read = |hash: &[u8; 20]| -> Result<Vec<u8>> {
+                if *hash == [0u8; 20] {
+                    return Ok(Vec::new());
+                }
+                let resource = resources
+                    .get(hash)
+                    .ok_or_else(|| anyhow::anyhow!("偏移表中找不到 {} 的数据流", path))?;
+                if let Some(throttle) = &mut read_throttle {
+                    throttle.consume(resource.size);
+                }
+                self.read_stream_resource(resource)
+                    .with_context(|| format!("读取 {path} 失败"))
+            };
+            let descriptor = match usize::try_from(entry.security_id) {
+                Ok(id) => Some(descriptors.get(id).copied().ok_or_else(|| {
+                    anyhow::anyhow!("{} 的安全描述符索引无效: {}", path, entry.security_id)
+                })?),
+                Err(_) => None,
+            };
+
+            let mut reparse_restored = false;
+            if entry.is_reparse_point() {
+                let reparse_data = read(&entry.hash)?;
+                if target.reparse_point(path, entry.reparse_tag, &reparse_data, &metadata)? {
+                    report.reparse_count += 1;
+                    reparse_restored = true;
+                } else {
+                    let mut link = reparse_link_target(entry.reparse_tag, &reparse_data);
+                    if let Some(fixed) = link.as_deref().and_then(|link| link.strip_prefix('/')) {
+                        if rp_fix && entry.rp_flags & WIM_RP_FLAG_NOT_FIXED == 0 {
+                            link = Some(target.fixed_link_target(path, fixed));
+                        }
+                    }
+                    match link {
+                        Some(link) => {
+                            target.symlink(path, &link, &metadata)?;
+                            links.insert(folded);
+                            report.symlink_count += 1;
+                            continue;
+                        }
+                        None => report.unsupported.push(UnsupportedEntry {
+                            path: path.clone(),
+                            feature: UnsupportedFeature::ReparsePoint(entry.reparse_tag),
+                        }),
+                    }
+                    // 无法还原的目录重解析点按普通目录创建，以便释放其内容
+                    if !entry.is_directory() {
+                        continue;
+                    }
+                }
+            }
+
+            if entry.is_directory() {
+                if !reparse_restored {
+                    target.create_dir(path, &metadata)?;
+                }
+                dirs.push((path, metadata));
+                report.dir_count += 1;
+            } else if !reparse_restored {
+                let link_key = (entry.hard_link_group_id != 0)
+                    .then_some((entry.hard_link_group_id, entry.hash));
+                if let Some(existing) = link_key.and_then(|key| hard_links.get(&key)) {
+                    if target.hard_link(path, existing, &metadata)? {
+                        // 命名数据流、安全描述符和时间与首个文件共享
+                        if let Some(progress) = &mut progress {
+                            let size = std::iter::once(&entry.hash)
+                                .chain(
+                                    entry
+                                        .streams
+                                        .iter()
+                                        .filter(|s| !s.name.is_empty())
+                                        .map(|s| &s.hash),
+                                )
+                                .map(|hash| stream_sizes.get(hash).copied().unwrap_or(0))
+                                .sum();
+                            progress.advance(size);
+                        }
+                        report.hard_link_count += 1;
+                        continue;
+                    }
+                }
+                let data = read(&entry.hash)?;
+                patch_stream::ensure_plain_stream(&data, path)?;
+                let mut file = target.create_file(path, data.len() as u64, &metadata)?;
+                for piece in data.chunks(THROTTLED_WRITE_SIZE) {
+                    if let Some(throttle) = &mut write_throttle {
+                        throttle.consume(piece.len() as u64);
+                    }
+                    file.write_all(piece)
+                        .with_context(|| format!("写入 {path} 失败"))?;
+                    if let Some(progress) = &mut progress {
+                        progress.advance(piece.len() as u64);
+                    }
+                }
+                drop(file);
+                if let Some(key) = link_key {
+                    hard_links.entry(key).or_insert(path);
+                }
+                report.file_count += 1;
+                report.total_bytes += data.len() as u64;
+            }
+
+            for stream in entry.streams.iter().filter(|s| !s.name.is_empty()) {
+                let size = stream_sizes.get(&stream.hash).copied().unwrap_or(0);
+                match target.create_named_stream(path, &stream.name, size)? {
+                    Some(mut writer) => {
+                        let data = read(&stream.hash)?;
+                        if let Some(throttle) = &mut write_throttle {
+                            throttle.consume(data.len() as u64);
+                        }
+                        writer
+                            .write_all(&data)
+                            .with_context(|| format!("写入 {path}:{} 失败", stream.name))?;
+                        if let Some(progress) = &mut progress {
+                            progress.advance(data.len() as u64);
+                        }
+                        report.total_bytes += data.len() as u64;
+                    }
+                    None => report.unsupported.push(UnsupportedEntry {
+                        path: path.clone(),
+                        feature: UnsupportedFeature::NamedStream(stream.name.clone()),
+                    }),
+                }
+            }
+
+            if let Some(descriptor) = descriptor {
+                if target.set_security_descriptor(path, descriptor)? {
+                    report.security_count += 1;
+                }
+            }
+            if !entry.is_directory() {
+                target.set_metadata(path, &metadata)?;
+            }
+        }
+
+        for (path, metadata) in dirs.iter().rev() {
+            target.set_metadata(path, metadata)?;
+        }
+        target.finish()?;
+        if let Some(progress) = progress {
+            progress.finish();
+        }
+
+        debug!(
+            "镜像 {} 释放: {} 个符号链接, {} 个硬链接, {} 个未还原的特性",
+            index,
+            report.symlink_count,
+            report.hard_link_count,
+            report.unsupported.len()
+        );
+        info!(
+            "镜像 {} 释放完成 - 目录: {}, 文件: {}, 总大小: {}",
+            index,
+            report.dir_count,
+            report.file_count,
+            format_bytes(report.total_bytes)
+        );
+        Ok(report)
+    }
+
+    /// 将镜像释放到本地目录：还原目录结构、文件内容和时间
+    ///
+    /// 先按 [`plan_apply_with`](Self::plan_apply_with) 检查目标中已存在的内容，
+    /// 有处理动作为 [`ConflictAction::Fail`] 的冲突时不写入任何内容并返回错误；
+    /// 跳过的项保留已有内容（目录的内容一并跳过，计入 [`ApplyReport::filtered_count`]），
+    /// 覆盖的项先删除已有内容再写入。其余行为与 [`DirectoryTarget`] 上的
+    /// [`apply_to`](Self::apply_to) 相同。
+    ///
+    /// ```
+    /// # #[cfg(feature = "fixtures")] {
+    /// # let fixture = wim_parser::fixtures::MiniWim::create()?;
+    /// # let mut parser = wim_parser::WimParser::new(fixture.path())?;
+    /// use wim_parser::ApplyOptions;
+    ///
+    /// let target = tempfile::tempdir()?;
+    /// let report = parser.apply_image(2, target.path(), &ApplyOptions::new())?;
+    /// assert_eq!(report.file_count, 7);
+    /// assert!(target.path().join("Windows/System32/gpedit.msc").exists());
+    /// # }
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    pub fn apply_image<P: AsRef<Path>>(
+        &mut self,
+        index: u32,
+        target_dir: P,
+        options: &ApplyOptions,
+    ) -> Result<ApplyReport> {
+        let target_dir = target_dir.as_ref();
+        let plan = self.plan_apply_with(index, target_dir, options)?;
+        let fatal: Vec<&ApplyConflict> = plan
+            .conflicts
+            .iter()
+            .filter(|conflict| conflict.action == ConflictAction::Fail)
+            .collect();
+        if let Some(first) = fatal.first() {
+            return Err(anyhow::anyhow!(
+                "目标中已存在 {}（共 {} 个冲突），未写入任何内容",
+                first.target.display(),
+                fatal.len()
+            ));
+        }
+
+        let mut options = options.clone();
+        for conflict in &plan.conflicts {
+            match conflict.action {
+                ConflictAction::Skip => options.skip_paths.push(conflict.path.clone()),
+                ConflictAction::Overwrite => remove_existing(conflict)?,
+                ConflictAction::Fail => unreachable!(),
+            }
+        }
+        debug!(
+            "释放镜像 {} 到 {} - 跳过: {}, 覆盖: {}",
+            index,
+            target_dir.display(),
+            options.skip_paths.len(),
+            plan.conflicts.len() - options.skip_paths.len()
+        );
+
+        self.apply_to(index, &mut DirectoryTarget::new(target_dir), &options)
+    }
+
+    /// 只把匹配通配符模式的文件释放到本地目录（不区分大小写），保留它们在镜像中的目录结构
+    ///
+    /// 模式的写法见 [`PathPattern`]，例如 `Windows/System32/drivers/**/*.sys`；匹配的目录连同其全部内容一起释放。
+    /// 只创建包含所选内容的目录，冲突按 [`ApplyOptions`] 的默认策略报错。需要其他冲突策略或过滤条件时，
+    /// 可以把 [`ApplyOptions::include_patterns`] 与 [`apply_image`](Self::apply_image) 组合使用。
+    ///
+    /// ```
+    /// # #[cfg(feature = "fixtures")] {
+    /// # let fixture = wim_parser::fixtures::MiniWim::create()?;
+    /// # let mut parser = wim_parser::WimParser::new(fixture.path())?;
+    /// let target = tempfile::tempdir()?;
+    /// let report = parser.extract_matching(1, &["windows/**/*.DLL"], target.path())?;
+    /// assert_eq!(report.file_count, 2);
+    /// assert!(target.path().join("Windows/SysWOW64/kernel32.dll").exists());
+    /// assert!(!target.path().join("Users").exists());
+    /// # }
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    pub fn extract_matching<P: AsRef<Path>>(
+        &mut self,
+        index: u32,
+        patterns: &[&str],
+        target_dir: P,
+    ) -> Result<ApplyReport> {
+        let patterns = patterns
+            .iter()
+            .map(|pattern| PathPattern::new(pattern))
+            .collect::<Result<Vec<_>>>()?;
+        let options = ApplyOptions::new().include_patterns(patterns);
+        let report = self.apply_image(index, target_dir, &options)?;
+        if report.file_count == 0 {
+            info!("镜像 {} 中没有匹配的文件", index);
+        }
+        Ok(report)
+    }
+}
+
+/// 删除将被覆盖的已有内容（只读文件先去掉只读属性）
+fn remove_existing(conflict: &ApplyConflict) -> Result<()> {
+    let path = &conflict.target;
+    let result = if conflict.existing_is_dir {
+        fs::remove_dir_all(path)
+    } else {
+        let metadata = fs::symlink_metadata(path)?;
+        let mut permissions = metadata.permissions();
+        if permissions.readonly() && !metadata.file_type().is_symlink() {
+            #[allow(clippy::permissions_set_readonly_false)]
+            permissions.set_readonly(false);
+            fs::set_permissions(path, permissions)?;
+        }
+        fs::remove_file(path)
+    };
+    result.with_context(|| format!("无法删除已有内容: {}", path.display()))
+}
@@ -0,0 +1,264 @@
+//! 重写操作的临时文件：位置、大小配额和清理方式
+//!
+//! 默认在目标文件旁创建临时文件，完成后原子地重命名替换。源文件位于只读共享或空间不足的卷上时，
+//! 可以通过 [`TempPolicy::dir`] 把临时数据放到其他目录，并用 [`TempPolicy::max_bytes`] 限制占用。
+
+use anyhow::{Context, Result};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use crate::log::debug;
+use crate::Error;
+
+/// 临时文件策略（提交事务等需要重写整个文件的操作使用，见 [`WimParser::set_temp_policy`](crate::WimParser::set_temp_policy)）
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TempPolicy {
+    dir: Option<PathBuf>,
+    max_bytes: Option<u64>,
+    cleanup_on_drop: bool,
+    anonymous: bool,
+}
+
+impl Default for TempPolicy {
+    fn default() -> Self {
+        Self {
+            dir: None,
+            max_bytes: None,
+            cleanup_on_drop: true,
+            anonymous: true,
+        }
+    }
+}
+
+impl TempPolicy {
+    /// 默认策略：在目标文件旁创建、不限大小、出错时删除
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 在指定目录中创建临时文件
+    ///
+    /// 与目标文件不在同一文件系统（或使用匿名文件）时无法直接重命名，完成后先把内容复制到
+    /// 目标文件旁的临时文件，再重命名替换目标文件，因此目标所在的卷仍需容纳一份副本。
+    pub fn dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.dir = Some(dir.into());
+        self
+    }
+
+    /// 临时文件的最大字节数，写入超出时返回 [`Error::LimitExceeded`]（`max_temp_bytes`）
+    pub fn max_bytes(mut self, bytes: u64) -> Self {
+        self.max_bytes = Some(bytes);
+        self
+    }
+
+    /// 操作失败（临时文件未被采用）时是否删除临时文件，默认删除；保留便于排查问题
+    pub fn cleanup_on_drop(mut self, enabled: bool) -> Self {
+        self.cleanup_on_drop = enabled;
+        self
+    }
+
+    /// 使用 [`dir`](Self::dir) 时是否优先用 `O_TMPFILE` 创建匿名文件（仅 Linux，默认启用）
+    ///
+    /// 匿名文件没有目录项，进程异常退出时由内核回收，不会残留；不支持时退回普通的临时文件。
+    pub fn use_tmpfile(mut self, enabled: bool) -> Self {
+        self.anonymous = enabled;
+        self
+    }
+
+    /// 临时文件目录（`None` 表示目标文件所在目录）
+    pub fn temp_dir(&self) -> Option<&Path> {
+        self.dir.as_deref()
+    }
+
+    /// 临时文件的最大字节数（`None` 表示不限）
+    pub fn byte_limit(&self) -> Option<u64> {
+        self.max_bytes
+    }
+
+    /// 为最终写入 `target` 的内容创建临时文件
+    pub fn create_for(&self, target: &Path) -> Result<TempFile> {
+        let name = target
+            .file_name()
+            .ok_or_else(|| anyhow::anyhow!("无效的文件路径: {}", target.display()))?;
+        let dir = match &self.dir {
+            Some(dir) => dir.clone(),
+            None => target
+                .parent()
+                .filter(|parent| !parent.as_os_str().is_empty())
+                .unwrap_or(Path::new("."))
+                .to_path_buf(),
+        };
+
+        if self.dir.is_some() && self.anonymous {
+            if let Some(file) = open_anonymous(&dir) {
+                debug!("在 {} 中创建匿名临时文件 (O_TMPFILE)", dir.display());
+                return Ok(self.wrap(file, None));
+            }
+        }
+
+        let path = dir.join(format!(
+            ".{}.{}.tmp",
+            name.to_string_lossy(),
+            std::process::id()
+        ));
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(&path)
+            .with_context(|| format!("无法创建临时文件: {}", path.display()))?;
+        debug!("创建临时文件: {}", path.display());
+        Ok(self.wrap(file, Some(path)))
+    }
+
+    fn wrap(&self, file: File, path: Option<PathBuf>) -> TempFile {
+        TempFile {
+            file: Some(file),
+            path,
+            max_bytes: self.max_bytes,
+            position: 0,
+            cleanup_on_drop: self.cleanup_on_drop,
+        }
+    }
+}
+
+/// 用 `O_TMPFILE` 在目录中创建匿名文件，不支持时返回 `None`
+#[cfg(target_os = "linux")]
+fn open_anonymous(dir: &Path) -> Option<File> {
+    use std::os::unix::fs::OpenOptionsExt;
+
+    // `__O_TMPFILE | O_DIRECTORY`，两者的取值都因架构而异
+    OpenOptions::new()
+        .read(true)
+        .write(true)
+        .custom_flags(libc::O_TMPFILE)
+        .mode(0o600)
+        .open(dir)
+        .ok()
+}
+
+#[cfg(not(target_os = "linux"))]
+fn open_anonymous(_dir: &Path) -> Option<File> {
+    None
+}
+
+/// 按 [`TempPolicy`] 创建的临时文件（由 [`TempPolicy::create_for`] 创建）
+///
+/// 写入超出配额时返回错误；未通过 [`persist`](Self::persist) 采用时，按策略在释放时删除。
+#[derive(Debug)]
+pub struct TempFile {
+    /// 只在 [`persist`](Self::persist) 中取出
+    file: Option<File>,
+    /// 有名称的临时文件的路径（匿名文件为 `None`）
+    path: Option<PathBuf>,
+    max_bytes: Option<u64>,
+    position: u64,
+    cleanup_on_drop: bool,
+}
+
+impl TempFile {
+    /// 临时文件的路径（`O_TMPFILE` 创建的匿名文件为 `None`）
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    /// 底层文件
+    pub fn file(&self) -> &File {
+        self.file.as_ref().expect("临时文件已被取出")
+    }
+
+    fn file_mut(&mut self) -> &mut File {
+        self.file.as_mut().expect("临时文件已被取出")
+    }
+
+    /// 同步到磁盘
+    pub fn sync_all(&self) -> Result<()> {
+        self.file().sync_all().context("同步临时文件失败")
+    }
+
+    /// 用临时文件替换 `target`
+    ///
+    /// 有名称的临时文件先尝试重命名；匿名文件或跨文件系统时把内容复制到 `target` 旁的临时文件
+    /// 后再重命名。两种方式都是原子替换，复制中途出错或进程崩溃时 `target` 保持原样。
+    pub fn persist(mut self, target: &Path) -> Result<()> {
+        if let Some(path) = &self.path {
+            match fs::rename(path, target) {
+                Ok(()) => {
+                    self.path = None;
+                    return Ok(());
+                }
+                Err(err) if err.kind() == io::ErrorKind::CrossesDevices => {
+                    debug!("临时文件与 {} 不在同一文件系统，改为复制", target.display());
+                }
+                Err(err) => {
+                    return Err(err)
+                        .with_context(|| format!("无法用临时文件替换 {}", target.display()))
+                }
+            }
+        }
+
+        let mut file = self.file.take().expect("临时文件已被取出");
+        file.seek(SeekFrom::Start(0))?;
+        // 目标旁的有名称临时文件，复制完成后在同一目录内重命名
+        let staging = TempPolicy::new().create_for(target)?;
+        io::copy(&mut file, &mut staging.file())
+            .with_context(|| format!("无法把临时文件复制到 {} 旁", target.display()))?;
+        staging.sync_all()?;
+        staging.persist(target)?;
+        if let Some(path) = self.path.take() {
+            drop(file);
+            let _ = fs::remove_file(path);
+        }
+        Ok(())
+    }
+}
+
+impl Write for TempFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let end = self.position.saturating_add(buf.len() as u64);
+        if let Some(allowed) = self.max_bytes {
+            if end > allowed {
+                return Err(io::Error::other(Error::LimitExceeded {
+                    limit: "max_temp_bytes",
+                    requested: end,
+                    allowed,
+                }));
+            }
+        }
+        let written = self.file_mut().write(buf)?;
+        self.position += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file_mut().flush()
+    }
+}
+
+impl Read for TempFile {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.file_mut().read(buf)?;
+        self.position += read as u64;
+        Ok(read)
+    }
+}
+
+impl Seek for TempFile {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        self.position = self.file_mut().seek(pos)?;
+        Ok(self.position)
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        if let Some(path) = &self.path {
+            if self.cleanup_on_drop {
+                self.file = None;
+                let _ = fs::remove_file(path);
+            }
+        }
+    }
+}
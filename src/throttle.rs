@@ -0,0 +1,65 @@
+//! 令牌桶限速：限制释放和校验的读写速率，避免后台任务占满生产服务器的 I/O
+
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// 令牌桶
+///
+/// 令牌以 `rate` 字节/秒的速度补充，最多积累 0.1 秒的量（允许的突发）。取用超过现有令牌时
+/// 记为欠账，调用方等待欠账按速率还清的时间；单次取用再大也只等待一次，不需要拆分。
+#[derive(Debug)]
+pub(crate) struct Throttle {
+    rate: f64,
+    capacity: f64,
+    tokens: f64,
+    last: Instant,
+}
+
+impl Throttle {
+    /// 创建速率为 `bytes_per_sec` 的令牌桶（`0` 按 1 字节/秒处理）
+    pub fn new(bytes_per_sec: u64) -> Self {
+        let rate = bytes_per_sec.max(1) as f64;
+        let capacity = rate / 10.0;
+        Self {
+            rate,
+            capacity,
+            tokens: capacity,
+            last: Instant::now(),
+        }
+    }
+
+    /// 取用 `bytes` 个令牌，返回需要等待的时间
+    pub fn reserve(&mut self, bytes: u64) -> Duration {
+        let now = Instant::now();
+        let refill = now.duration_since(self.last).as_secs_f64() * self.rate;
+        self.tokens = (self.tokens + refill).min(self.capacity) - bytes as f64;
+        self.last = now;
+        if self.tokens < 0.0 {
+            Duration::from_secs_f64(-self.tokens / self.rate)
+        } else {
+            Duration::ZERO
+        }
+    }
+
+    /// 取用 `bytes` 个令牌，必要时阻塞当前线程
+    pub fn consume(&mut self, bytes: u64) {
+        sleep(self.reserve(bytes));
+    }
+}
+
+/// 从多个线程共享的令牌桶取用令牌；等待在锁外进行
+#[cfg_attr(not(feature = "verify"), allow(dead_code))]
+pub(crate) fn consume_shared(throttle: &Mutex<Throttle>, bytes: u64) {
+    let wait = throttle
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .reserve(bytes);
+    sleep(wait);
+}
+
+fn sleep(wait: Duration) {
+    if !wait.is_zero() {
+        thread::sleep(wait);
+    }
+}
@@ -0,0 +1,149 @@
+use core::fmt;
+
+/// FILETIME 纪元 (1601-01-01) 与 Unix 纪元 (1970-01-01) 之间的秒数
+const FILETIME_UNIX_EPOCH_SECS: i64 = 11_644_473_600;
+/// 每秒的 FILETIME 间隔数（100 纳秒）
+const TICKS_PER_SEC: u64 = 10_000_000;
+
+/// WIM 中使用的时间戳：1601-01-01 (UTC) 起的 100 纳秒间隔数 (FILETIME)
+///
+/// XML 数据中的 `<CREATIONTIME>` / `<LASTMODIFICATIONTIME>` 和目录项中的时间都使用此格式。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct WimTimestamp(u64);
+
+impl WimTimestamp {
+    /// 从原始 FILETIME 数值创建
+    pub const fn from_filetime(filetime: u64) -> Self {
+        Self(filetime)
+    }
+
+    /// 从高、低 32 位创建（XML 中的 `<HIGHPART>` / `<LOWPART>`）
+    pub const fn from_parts(high: u32, low: u32) -> Self {
+        Self(((high as u64) << 32) | low as u64)
+    }
+
+    /// 从 Unix 时间戳（秒 + 纳秒）创建，超出 FILETIME 范围时返回 `None`
+    pub fn from_unix(secs: i64, nanos: u32) -> Option<Self> {
+        if nanos >= 1_000_000_000 {
+            return None;
+        }
+        let secs = u64::try_from(secs.checked_add(FILETIME_UNIX_EPOCH_SECS)?).ok()?;
+        secs.checked_mul(TICKS_PER_SEC)?
+            .checked_add(u64::from(nanos / 100))
+            .map(Self)
+    }
+
+    /// 原始 FILETIME 数值
+    pub const fn filetime(&self) -> u64 {
+        self.0
+    }
+
+    /// 高 32 位
+    pub const fn high_part(&self) -> u32 {
+        (self.0 >> 32) as u32
+    }
+
+    /// 低 32 位
+    pub const fn low_part(&self) -> u32 {
+        self.0 as u32
+    }
+
+    /// 是否为零（未设置）
+    pub const fn is_zero(&self) -> bool {
+        self.0 == 0
+    }
+
+    /// Unix 时间戳（秒），早于 1970 年时为负数
+    pub const fn unix_seconds(&self) -> i64 {
+        (self.0 / TICKS_PER_SEC) as i64 - FILETIME_UNIX_EPOCH_SECS
+    }
+
+    /// 秒以下的纳秒部分
+    pub const fn subsec_nanos(&self) -> u32 {
+        (self.0 % TICKS_PER_SEC) as u32 * 100
+    }
+
+    /// 转换为 [`SystemTime`](std::time::SystemTime)，超出平台可表示范围时返回 `None`
+    #[cfg(feature = "std")]
+    pub fn to_system_time(&self) -> Option<std::time::SystemTime> {
+        use std::time::{Duration, UNIX_EPOCH};
+
+        let since_1601 = Duration::new(self.0 / TICKS_PER_SEC, self.subsec_nanos());
+        UNIX_EPOCH
+            .checked_sub(Duration::from_secs(FILETIME_UNIX_EPOCH_SECS as u64))?
+            .checked_add(since_1601)
+    }
+
+    /// 从 [`SystemTime`](std::time::SystemTime) 创建，早于 1601 年时返回 `None`
+    #[cfg(feature = "std")]
+    pub fn from_system_time(time: std::time::SystemTime) -> Option<Self> {
+        use std::time::UNIX_EPOCH;
+
+        match time.duration_since(UNIX_EPOCH) {
+            Ok(after) => {
+                Self::from_unix(i64::try_from(after.as_secs()).ok()?, after.subsec_nanos())
+            }
+            Err(err) => {
+                let before = err.duration();
+                let mut secs = -i64::try_from(before.as_secs()).ok()?;
+                let mut nanos = before.subsec_nanos();
+                if nanos > 0 {
+                    secs -= 1;
+                    nanos = 1_000_000_000 - nanos;
+                }
+                Self::from_unix(secs, nanos)
+            }
+        }
+    }
+
+    /// 转换为 `chrono::DateTime<Utc>`，超出 chrono 可表示范围时返回 `None`
+    #[cfg(feature = "chrono")]
+    pub fn to_chrono(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        chrono::DateTime::from_timestamp(self.unix_seconds(), self.subsec_nanos())
+    }
+
+    /// 从 `chrono::DateTime<Utc>` 创建，早于 1601 年时返回 `None`
+    #[cfg(feature = "chrono")]
+    pub fn from_chrono(time: chrono::DateTime<chrono::Utc>) -> Option<Self> {
+        Self::from_unix(time.timestamp(), time.timestamp_subsec_nanos())
+    }
+}
+
+impl From<u64> for WimTimestamp {
+    fn from(filetime: u64) -> Self {
+        Self(filetime)
+    }
+}
+
+/// 距 1970-01-01 的天数转换为公历年月日
+pub(crate) fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+impl fmt::Display for WimTimestamp {
+    /// 格式为 `YYYY-MM-DD HH:MM:SS UTC`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let secs = self.unix_seconds();
+        let (year, month, day) = civil_from_days(secs.div_euclid(86_400));
+        let time = secs.rem_euclid(86_400);
+        write!(
+            f,
+            "{:04}-{:02}-{:02} {:02}:{:02}:{:02} UTC",
+            year,
+            month,
+            day,
+            time / 3600,
+            time % 3600 / 60,
+            time % 60
+        )
+    }
+}
@@ -0,0 +1,834 @@
+//! 编辑事务：将多个镜像编辑（重命名、设置可引导镜像、删除镜像、复制文件）合并为一次原子重写
+
+use anyhow::{Context, Result};
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::{BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+#[cfg(feature = "verify")]
+use std::path::PathBuf;
+
+use crate::fmt::{format_bytes, Align, Table, ToTable};
+use crate::format::{self, WIM_HEADER_DISK_SIZE};
+use crate::lock;
+use crate::log::{debug, info};
+use crate::lookup_table::LookupTableEntry;
+use crate::metadata::DirEntry;
+use crate::xml_tree::escape_xml;
+use crate::{Error, FileResourceEntry, ImageStats, ResourceFlags, TempFile, WimHeader, WimParser};
+
+/// 事务中的一项编辑（镜像索引均指编辑前文件中的索引）
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ImageEdit {
+    /// 修改镜像的 `<NAME>`（存在 `<DISPLAYNAME>` 时一并修改）
+    Rename { index: u32, name: String },
+    /// 设置可引导镜像（0 表示不设置可引导镜像）
+    SetBootable(u32),
+    /// 删除镜像（之后的镜像索引依次前移）
+    Delete(u32),
+    /// 将镜像 `source` 中的文件或目录树复制到镜像 `target` 的相同路径，引用已有的数据流
+    /// （见 [`Transaction::copy_path`]）；`from` 为其他 WIM 时 `source` 是该文件中的镜像索引
+    #[cfg(feature = "verify")]
+    CopyPath {
+        source: u32,
+        path: String,
+        target: u32,
+        from: Option<PathBuf>,
+    },
+}
+
+/// 删除镜像后其余镜像索引的处理方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum IndexPolicy {
+    /// 被删除镜像之后的镜像依次前移，索引保持连续（默认）；新旧索引见 [`TransactionPlan::index_map`]
+    #[default]
+    Renumber,
+    /// 保留镜像的索引均不改变：只允许删除末尾的镜像，否则事务校验失败，
+    /// 避免按固定索引操作的脚本在删除后静默地作用到其他镜像
+    Preserve,
+}
+
+/// 提交事务所需的最小重写方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RewriteStrategy {
+    /// 没有编辑，不写入文件
+    Unchanged,
+    /// 只修改文件头（可引导镜像）
+    HeaderOnly,
+    /// 修改文件头并追加新的 XML 数据，其余资源原样保留（完整性表仍然有效）
+    XmlAndHeader,
+    /// 重建文件：按原始字节复制保留的资源，丢弃只被删除镜像引用的数据流，重写偏移表
+    Rebuild,
+}
+
+/// 事务的重写计划（提交前计算，不修改文件）
+#[derive(Debug, Clone)]
+pub struct TransactionPlan {
+    /// 按添加顺序排列的编辑
+    pub edits: Vec<ImageEdit>,
+    /// 重写方式
+    pub strategy: RewriteStrategy,
+    /// 提交后的镜像数量
+    pub image_count: u32,
+    /// 提交后的可引导镜像索引（0 表示无）
+    pub bootable_index: u32,
+    /// 丢弃的数据流数量
+    pub freed_streams: usize,
+    /// 丢弃的数据流在文件中占用的字节数
+    pub freed_bytes: u64,
+    /// 从原文件复制的字节数
+    pub copied_bytes: u64,
+    /// 从其他 WIM 复制而新增的数据流数量
+    pub added_streams: usize,
+    /// 新增的数据流在文件中占用的字节数
+    pub added_bytes: u64,
+    /// 重建后原完整性表失效并被移除
+    pub drops_integrity: bool,
+    /// 删除镜像后的索引处理方式
+    pub index_policy: IndexPolicy,
+    /// 编辑前索引（下标 + 1）对应的新索引，被删除的镜像为 `None`
+    new_indexes: Vec<Option<u32>>,
+}
+
+impl TransactionPlan {
+    /// 编辑前的镜像索引在提交后的新索引（被删除或超出范围时为 `None`）
+    pub fn new_index(&self, old_index: u32) -> Option<u32> {
+        let position = (old_index as usize).checked_sub(1)?;
+        self.new_indexes.get(position).copied().flatten()
+    }
+
+    /// 保留镜像的（编辑前索引, 提交后索引），按编辑前索引排序
+    pub fn index_map(&self) -> Vec<(u32, u32)> {
+        self.new_indexes
+            .iter()
+            .enumerate()
+            .filter_map(|(position, new)| new.map(|new| (position as u32 + 1, new)))
+            .collect()
+    }
+
+    /// 索引发生变化的保留镜像（编辑前索引, 提交后索引）
+    pub fn renumbered(&self) -> Vec<(u32, u32)> {
+        self.index_map()
+            .into_iter()
+            .filter(|(old, new)| old != new)
+            .collect()
+    }
+}
+
+impl ToTable for TransactionPlan {
+    fn table(&self) -> Table {
+        let strategy = match self.strategy {
+            RewriteStrategy::Unchanged => "无需写入",
+            RewriteStrategy::HeaderOnly => "仅文件头",
+            RewriteStrategy::XmlAndHeader => "文件头和 XML",
+            RewriteStrategy::Rebuild => "重建",
+        };
+        let mut table = Table::new(["项目", "值"]).align(1, Align::Right);
+        table.push_row(["编辑数".to_string(), self.edits.len().to_string()]);
+        table.push_row(["重写方式".to_string(), strategy.to_string()]);
+        table.push_row(["镜像数".to_string(), self.image_count.to_string()]);
+        let renumbered: Vec<String> = self
+            .renumbered()
+            .iter()
+            .map(|(old, new)| format!("{old}→{new}"))
+            .collect();
+        if !renumbered.is_empty() {
+            table.push_row(["重新编号".to_string(), renumbered.join(", ")]);
+        }
+        table.push_row(["可引导镜像".to_string(), self.bootable_index.to_string()]);
+        table.push_row(["丢弃的数据流".to_string(), self.freed_streams.to_string()]);
+        table.push_row(["释放的字节数".to_string(), format_bytes(self.freed_bytes)]);
+        table.push_row(["复制的字节数".to_string(), format_bytes(self.copied_bytes)]);
+        if self.added_streams > 0 {
+            table.push_row(["新增的数据流".to_string(), self.added_streams.to_string()]);
+            table.push_row(["新增的字节数".to_string(), format_bytes(self.added_bytes)]);
+        }
+        table.push_row([
+            "移除完整性表".to_string(),
+            if self.drops_integrity { "是" } else { "否" }.to_string(),
+        ]);
+        table
+    }
+}
+
+/// 经过校验、可以直接写入的事务内容
+struct Prepared {
+    plan: TransactionPlan,
+    /// 新文件头（资源位置在写入时更新）
+    header: WimHeader,
+    /// 新 XML 数据（只修改文件头时为 `None`）
+    xml: Option<String>,
+    /// 重建时保留的偏移表条目（元数据按新镜像顺序在前）及新的引用计数
+    entries: Vec<(LookupTableEntry, u32)>,
+    /// 不从原文件复制、直接写入的资源数据（按 `entries` 下标）：修改后的元数据和新增的数据流
+    data: HashMap<usize, Vec<u8>>,
+}
+
+/// 目录树被修改、需要写入新元数据资源的镜像
+#[cfg_attr(not(feature = "verify"), allow(dead_code))]
+pub(crate) struct RewrittenImage {
+    /// 修改后的目录树
+    pub root: DirEntry,
+    /// 新元数据资源的 SHA-1
+    pub hash: [u8; 20],
+    /// 新元数据资源（未压缩）
+    pub data: Vec<u8>,
+}
+
+/// 从其他 WIM 复制、重建时追加的数据流
+#[derive(Debug, Clone)]
+#[cfg_attr(not(feature = "verify"), allow(dead_code))]
+pub(crate) struct AddedStream {
+    /// 数据流的 SHA-1
+    pub hash: [u8; 20],
+    /// 资源的原始字节（压缩资源为分块表和压缩后的分块）
+    pub raw: Vec<u8>,
+    /// 未压缩大小
+    pub original_size: u64,
+    /// `raw` 是否为压缩资源
+    pub compressed: bool,
+}
+
+/// 编辑事务
+///
+/// 所有编辑在提交前统一校验；提交时先写入临时文件（默认在同目录下，见
+/// [`WimParser::set_temp_policy`]），成功后再替换原文件，失败时原文件保持不变。
+/// 提交后解析器重新打开替换后的文件。
+///
+/// ```
+/// # #[cfg(feature = "fixtures")] {
+/// # use wim_parser::WimParser;
+/// # let fixture = wim_parser::fixtures::MiniWim::create()?;
+/// let mut parser = WimParser::new(fixture.path())?;
+/// let plan = parser
+///     .transaction()
+///     .rename_image(2, "Windows 10 Pro N")
+///     .set_bootable(2)
+///     .delete_image(1)
+///     .commit()?;
+/// assert_eq!(plan.image_count, 1);
+/// # }
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+pub struct Transaction<'a> {
+    pub(crate) parser: &'a mut WimParser,
+    pub(crate) edits: Vec<ImageEdit>,
+    pub(crate) index_policy: IndexPolicy,
+    /// 从其他 WIM 读取的复制内容（按编辑下标）
+    #[cfg(feature = "verify")]
+    pub(crate) foreign: HashMap<usize, crate::copy_path::ForeignCopy>,
+}
+
+impl WimParser {
+    /// 开始一个编辑事务
+    pub fn transaction(&mut self) -> Transaction<'_> {
+        Transaction {
+            parser: self,
+            edits: Vec::new(),
+            index_policy: IndexPolicy::default(),
+            #[cfg(feature = "verify")]
+            foreign: HashMap::new(),
+        }
+    }
+}
+
+impl Transaction<'_> {
+    /// 添加编辑
+    pub fn edit(mut self, edit: ImageEdit) -> Self {
+        self.edits.push(edit);
+        self
+    }
+
+    /// 重命名镜像
+    pub fn rename_image(self, index: u32, name: impl Into<String>) -> Self {
+        self.edit(ImageEdit::Rename {
+            index,
+            name: name.into(),
+        })
+    }
+
+    /// 设置可引导镜像（0 表示取消）
+    pub fn set_bootable(self, index: u32) -> Self {
+        self.edit(ImageEdit::SetBootable(index))
+    }
+
+    /// 删除镜像
+    pub fn delete_image(self, index: u32) -> Self {
+        self.edit(ImageEdit::Delete(index))
+    }
+
+    /// 删除镜像后其余镜像索引的处理方式（见 [`IndexPolicy`]）
+    pub fn index_policy(mut self, policy: IndexPolicy) -> Self {
+        self.index_policy = policy;
+        self
+    }
+
+    /// 已添加的编辑
+    pub fn edits(&self) -> &[ImageEdit] {
+        &self.edits
+    }
+
+    /// 校验所有编辑并计算重写计划（不修改文件）
+    pub fn plan(&mut self) -> Result<TransactionPlan> {
+        Ok(self.prepare()?.plan)
+    }
+
+    /// 校验并提交所有编辑，返回执行的计划
+    pub fn commit(mut self) -> Result<TransactionPlan> {
+        let prepared = self.prepare()?;
+        if prepared.plan.strategy == RewriteStrategy::Unchanged {
+            return Ok(prepared.plan);
+        }
+
+        self.parser.ensure_standalone("提交编辑")?;
+        let path = self
+            .parser
+            .path
+            .clone()
+            .ok_or_else(|| anyhow::anyhow!("解析器没有关联的文件路径，无法提交编辑"))?;
+        // 持有原文件的排他锁直到替换完成
+        let _lock = lock::lock_existing(&path, self.parser.options().file_lock_policy())?;
+        let mut temp = self.parser.temp_policy.create_for(&path)?;
+
+        info!(
+            "提交 {} 项编辑到 {} (临时文件: {})",
+            prepared.plan.edits.len(),
+            path.display(),
+            temp.path()
+                .map_or_else(|| "匿名".to_string(), |temp| temp.display().to_string())
+        );
+
+        // 出错时临时文件按策略在释放时删除
+        self.write_to(&path, &mut temp, &prepared)?;
+        // 替换前确认没有其他进程在此期间修改过原文件
+        self.parser.check_header_unchanged()?;
+        temp.persist(&path)?;
+
+        self.parser.reopen()?;
+        Ok(prepared.plan)
+    }
+
+    /// 写入临时文件并同步到磁盘
+    fn write_to(&mut self, path: &Path, temp: &mut TempFile, prepared: &Prepared) -> Result<()> {
+        match prepared.plan.strategy {
+            RewriteStrategy::Rebuild => self.write_rebuild(temp, prepared)?,
+            _ => write_copy(path, temp, prepared)?,
+        }
+        temp.sync_all()
+    }
+
+    /// 按原始字节复制保留的资源，写入新的偏移表、XML 数据和文件头
+    fn write_rebuild(&mut self, temp: &mut TempFile, prepared: &Prepared) -> Result<()> {
+        let mut out = BufWriter::new(temp);
+        out.write_all(&[0u8; WIM_HEADER_DISK_SIZE])?;
+        let mut offset = WIM_HEADER_DISK_SIZE as u64;
+
+        // 按原偏移顺序复制，保持顺序读取；直接写入的资源放在最后
+        let mut order: Vec<usize> = (0..prepared.entries.len()).collect();
+        order.sort_by_key(|&i| {
+            (
+                prepared.data.contains_key(&i),
+                prepared.entries[i].0.resource.offset,
+            )
+        });
+        let mut new_offsets = vec![0u64; prepared.entries.len()];
+        for i in order {
+            if let Some(data) = prepared.data.get(&i) {
+                out.write_all(data)?;
+                new_offsets[i] = offset;
+                offset += data.len() as u64;
+                continue;
+            }
+            let resource = &prepared.entries[i].0.resource;
+            self.parser.seek_to(resource.offset)?;
+            let copied = std::io::copy(&mut (&mut self.parser.file).take(resource.size), &mut out)
+                .with_context(|| format!("复制资源失败，偏移: {}", resource.offset))?;
+            if copied != resource.size {
+                return Err(anyhow::anyhow!(
+                    "复制资源时文件提前结束，偏移: {}, 大小: {}",
+                    resource.offset,
+                    resource.size
+                ));
+            }
+            new_offsets[i] = offset;
+            offset += resource.size;
+        }
+
+        let mut header = prepared.header.clone();
+        let mut table = Vec::with_capacity(prepared.entries.len() * 50);
+        for (i, (entry, ref_count)) in prepared.entries.iter().enumerate() {
+            let mut entry = entry.clone();
+            entry.resource.offset = new_offsets[i];
+            entry.ref_count = *ref_count;
+            if entry.is_metadata() && i + 1 == header.bootable_image_index as usize {
+                header.boot_metadata_resource = entry.resource.clone();
+            }
+            table.extend_from_slice(&entry.to_bytes());
+        }
+
+        header.offset_table_resource = uncompressed_resource(offset, table.len(), true);
+        out.write_all(&table)?;
+        offset += table.len() as u64;
+
+        let xml = format::encode_xml_utf16(prepared.xml.as_deref().unwrap_or_default());
+        header.xml_data_resource = uncompressed_resource(offset, xml.len(), false);
+        out.write_all(&xml)?;
+
+        out.seek(SeekFrom::Start(0))?;
+        out.write_all(&header.to_bytes())?;
+        out.flush().context("写入临时文件失败")
+    }
+
+    /// 校验编辑并计算新文件头、XML 和偏移表
+    fn prepare(&mut self) -> Result<Prepared> {
+        let header = self.parser.read_header()?.clone();
+        if header.total_segments > 1 {
+            return Err(
+                anyhow::Error::new(Error::Unsupported("编辑分卷 WIM")).context(format!(
+                    "不支持编辑分卷 WIM (共 {} 个分卷)",
+                    header.total_segments
+                )),
+            );
+        }
+        if self.parser.images.is_empty() {
+            self.parser.parse_full()?;
+        }
+        let image_count = header.image_count;
+
+        let mut renames: HashMap<u32, String> = HashMap::new();
+        let mut deleted: HashSet<u32> = HashSet::new();
+        let mut bootable: Option<u32> = None;
+        #[cfg_attr(not(feature = "verify"), allow(unused_mut))]
+        let mut copy_targets: HashSet<u32> = HashSet::new();
+        for edit in &self.edits {
+            let index = match edit {
+                ImageEdit::Rename { index, .. } | ImageEdit::Delete(index) => *index,
+                ImageEdit::SetBootable(index) => *index,
+                #[cfg(feature = "verify")]
+                ImageEdit::CopyPath { target, .. } => *target,
+            };
+            let allow_zero = matches!(edit, ImageEdit::SetBootable(_));
+            if (index == 0 && !allow_zero) || index > image_count {
+                return Err(anyhow::anyhow!(
+                    "镜像索引 {} 超出范围 (1-{})",
+                    index,
+                    image_count
+                ));
+            }
+            match edit {
+                ImageEdit::Rename { index, name } => {
+                    if name.trim().is_empty() {
+                        return Err(anyhow::anyhow!("镜像 {} 的新名称为空", index));
+                    }
+                    if renames.insert(*index, name.clone()).is_some() {
+                        return Err(anyhow::anyhow!("镜像 {} 被重复重命名", index));
+                    }
+                }
+                ImageEdit::Delete(index) => {
+                    if !deleted.insert(*index) {
+                        return Err(anyhow::anyhow!("镜像 {} 被重复删除", index));
+                    }
+                }
+                ImageEdit::SetBootable(index) => {
+                    if bootable.replace(*index).is_some() {
+                        return Err(anyhow::anyhow!("可引导镜像被重复设置"));
+                    }
+                }
+                #[cfg(feature = "verify")]
+                ImageEdit::CopyPath {
+                    source,
+                    path,
+                    target,
+                    from,
+                } => {
+                    if from.is_none() && (*source == 0 || *source > image_count) {
+                        return Err(anyhow::anyhow!(
+                            "镜像索引 {} 超出范围 (1-{})",
+                            source,
+                            image_count
+                        ));
+                    }
+                    if from.is_none() && source == target {
+                        return Err(anyhow::anyhow!("复制的源镜像和目标镜像相同: {}", source));
+                    }
+                    if path.split(['/', '\\']).all(str::is_empty) {
+                        return Err(anyhow::anyhow!("不能复制根目录"));
+                    }
+                    copy_targets.insert(*target);
+                }
+            }
+        }
+
+        if !deleted.is_empty() && deleted.len() as u32 == image_count {
+            return Err(anyhow::anyhow!("不能删除所有镜像"));
+        }
+        if let Some(index) = renames.keys().find(|index| deleted.contains(index)) {
+            return Err(anyhow::anyhow!("镜像 {} 同时被重命名和删除", index));
+        }
+        if let Some(index) = copy_targets.iter().find(|index| deleted.contains(index)) {
+            return Err(anyhow::anyhow!(
+                "镜像 {} 同时是复制目标和被删除的镜像",
+                index
+            ));
+        }
+        let bootable_old = bootable.unwrap_or(header.bootable_image_index);
+        if deleted.contains(&bootable_old) {
+            return Err(anyhow::anyhow!(
+                "镜像 {} 是可引导镜像，删除前需设置新的可引导镜像（或设为 0）",
+                bootable_old
+            ));
+        }
+
+        // 编辑后的镜像名称不能重复
+        let mut names = HashSet::new();
+        for image in self.parser.images.iter() {
+            if deleted.contains(&image.index) {
+                continue;
+            }
+            let name = renames.get(&image.index).unwrap_or(&image.name);
+            if !names.insert(name.to_lowercase()) {
+                return Err(anyhow::anyhow!("编辑后存在重名镜像: {}", name));
+            }
+        }
+
+        if self.index_policy == IndexPolicy::Preserve {
+            let first_deleted = deleted.iter().min().copied().unwrap_or(image_count);
+            let kept_after: Vec<String> = (first_deleted + 1..=image_count)
+                .filter(|index| !deleted.contains(index))
+                .map(|index| index.to_string())
+                .collect();
+            if !kept_after.is_empty() {
+                return Err(anyhow::anyhow!(
+                    "删除镜像会改变镜像 {} 的索引（IndexPolicy::Preserve 只允许删除末尾的镜像）",
+                    kept_after.join(", ")
+                ));
+            }
+        }
+
+        let mut new_indexes = Vec::with_capacity(image_count as usize);
+        let mut next = 1;
+        for index in 1..=image_count {
+            if deleted.contains(&index) {
+                new_indexes.push(None);
+            } else {
+                new_indexes.push(Some(next));
+                next += 1;
+            }
+        }
+        let bootable_index = match bootable_old {
+            0 => 0,
+            index => new_indexes[index as usize - 1].unwrap_or(0),
+        };
+
+        let strategy = if !deleted.is_empty() || !copy_targets.is_empty() {
+            RewriteStrategy::Rebuild
+        } else if !renames.is_empty() {
+            RewriteStrategy::XmlAndHeader
+        } else if bootable.is_some_and(|index| index != header.bootable_image_index) {
+            RewriteStrategy::HeaderOnly
+        } else {
+            RewriteStrategy::Unchanged
+        };
+
+        let mut plan = TransactionPlan {
+            edits: self.edits.clone(),
+            strategy,
+            image_count: image_count - deleted.len() as u32,
+            bootable_index,
+            freed_streams: 0,
+            freed_bytes: 0,
+            copied_bytes: 0,
+            added_streams: 0,
+            added_bytes: 0,
+            drops_integrity: false,
+            index_policy: self.index_policy,
+            new_indexes,
+        };
+
+        #[cfg(feature = "verify")]
+        let (rewritten, added) = self.apply_copies()?;
+        #[cfg(not(feature = "verify"))]
+        let (rewritten, added): (HashMap<u32, RewrittenImage>, Vec<AddedStream>) =
+            (HashMap::new(), Vec::new());
+
+        let xml =
+            if strategy == RewriteStrategy::XmlAndHeader || strategy == RewriteStrategy::Rebuild {
+                let xml = format::decode_xml_utf16(&self.parser.read_xml_buffer()?)?;
+                let stats = self.rewritten_stats(&rewritten, &added)?;
+                Some(rewrite_xml(&xml, &renames, &stats, &plan.new_indexes)?)
+            } else {
+                None
+            };
+
+        let mut new_header = header.clone();
+        new_header.image_count = plan.image_count;
+        new_header.bootable_image_index = bootable_index;
+        if bootable_index != header.bootable_image_index || bootable.is_some() {
+            new_header.boot_metadata_resource = match bootable_old {
+                0 => empty_resource(),
+                index => self.parser.metadata_resource(index)?,
+            };
+        }
+
+        let mut entries = Vec::new();
+        let mut data = HashMap::new();
+        if strategy == RewriteStrategy::Rebuild {
+            entries = self.plan_rebuild(&deleted, rewritten, added, &mut plan, &mut data)?;
+            plan.drops_integrity = !header.integrity_resource.is_absent();
+            new_header.integrity_resource = empty_resource();
+        } else if strategy != RewriteStrategy::Unchanged {
+            plan.copied_bytes = self.parser.file.get_ref().metadata()?.len();
+        }
+
+        debug!(
+            "事务计划 - 编辑: {}, 镜像数: {}, 可引导镜像: {}, 丢弃数据流: {}",
+            plan.edits.len(),
+            plan.image_count,
+            plan.bootable_index,
+            plan.freed_streams
+        );
+
+        Ok(Prepared {
+            plan,
+            header: new_header,
+            xml,
+            entries,
+            data,
+        })
+    }
+
+    /// 修改了目录树的镜像重新统计的 DIRCOUNT/FILECOUNT/TOTALBYTES
+    fn rewritten_stats(
+        &mut self,
+        rewritten: &HashMap<u32, RewrittenImage>,
+        added: &[AddedStream],
+    ) -> Result<HashMap<u32, ImageStats>> {
+        if rewritten.is_empty() {
+            return Ok(HashMap::new());
+        }
+        let mut stream_sizes: HashMap<[u8; 20], u64> = self
+            .parser
+            .read_lookup_table()?
+            .iter()
+            .filter(|entry| !entry.is_metadata())
+            .map(|entry| (entry.hash, entry.resource.original_size))
+            .collect();
+        stream_sizes.extend(
+            added
+                .iter()
+                .map(|stream| (stream.hash, stream.original_size)),
+        );
+        Ok(rewritten
+            .iter()
+            .map(|(index, image)| (*index, ImageStats::from_tree(&image.root, &stream_sizes)))
+            .collect())
+    }
+
+    /// 按剩余镜像实际引用的次数确定保留的偏移表条目和新的引用计数
+    ///
+    /// 只丢弃被删除镜像引用、且剩余镜像不再引用的数据流；其他数据流（包括未被任何镜像引用的）原样保留。
+    /// 目录树被修改的镜像写入新的元数据资源（数据放入 `data`），其引用的数据流按实际引用次数重新计数。
+    fn plan_rebuild(
+        &mut self,
+        deleted: &HashSet<u32>,
+        mut rewritten: HashMap<u32, RewrittenImage>,
+        added: Vec<AddedStream>,
+        plan: &mut TransactionPlan,
+        data: &mut HashMap<usize, Vec<u8>>,
+    ) -> Result<Vec<(LookupTableEntry, u32)>> {
+        let mut deleted_refs = HashMap::new();
+        let mut kept_refs = HashMap::new();
+        // 被修改的镜像在修改前后引用的数据流
+        let mut changed = HashSet::new();
+        for index in 1..=plan.new_indexes.len() as u32 {
+            let root = self.parser.read_metadata_root(index)?;
+            if deleted.contains(&index) {
+                count_stream_refs(&root, &mut deleted_refs);
+                continue;
+            }
+            let Some(image) = rewritten.get(&index) else {
+                count_stream_refs(&root, &mut kept_refs);
+                continue;
+            };
+            let mut before = HashMap::new();
+            count_stream_refs(&root, &mut before);
+            count_stream_refs(&image.root, &mut kept_refs);
+            changed.extend(before.into_keys());
+        }
+        for image in rewritten.values() {
+            let mut after = HashMap::new();
+            count_stream_refs(&image.root, &mut after);
+            changed.extend(after.into_keys());
+        }
+
+        let lookup = self.parser.read_lookup_table()?;
+        if lookup.iter().any(|entry| entry.resource.is_solid()) {
+            return Err(anyhow::Error::new(Error::Unsupported("重建固实资源"))
+                .context("不支持重建包含固实资源的 WIM"));
+        }
+
+        let mut entries = Vec::with_capacity(lookup.len() + added.len());
+        let mut metadata_index = 0;
+        for entry in lookup.iter().filter(|entry| entry.is_metadata()) {
+            metadata_index += 1;
+            if deleted.contains(&metadata_index) {
+                continue;
+            }
+            let mut entry = entry.clone();
+            if let Some(image) = rewritten.remove(&metadata_index) {
+                entry.hash = image.hash;
+                entry.resource = uncompressed_resource(0, image.data.len(), true);
+                data.insert(entries.len(), image.data);
+            }
+            let ref_count = entry.ref_count;
+            entries.push((entry, ref_count));
+        }
+        for entry in lookup.iter().filter(|entry| !entry.is_metadata()) {
+            let kept = kept_refs.get(&entry.hash).copied().unwrap_or(0);
+            if !deleted_refs.contains_key(&entry.hash) && !changed.contains(&entry.hash) {
+                entries.push((entry.clone(), entry.ref_count));
+            } else if kept > 0 {
+                entries.push((entry.clone(), kept));
+            } else {
+                plan.freed_streams += 1;
+                plan.freed_bytes += entry.resource.size;
+            }
+        }
+        plan.copied_bytes = entries.iter().map(|(entry, _)| entry.resource.size).sum();
+
+        let existing: HashSet<[u8; 20]> = entries.iter().map(|(entry, _)| entry.hash).collect();
+        for stream in added {
+            let ref_count = kept_refs.get(&stream.hash).copied().unwrap_or(0);
+            if ref_count == 0 || existing.contains(&stream.hash) {
+                continue;
+            }
+            let mut resource = uncompressed_resource(0, stream.raw.len(), false);
+            resource.original_size = stream.original_size;
+            if stream.compressed {
+                resource.flags |= ResourceFlags::COMPRESSED;
+            }
+            plan.added_streams += 1;
+            plan.added_bytes += resource.size;
+            data.insert(entries.len(), stream.raw);
+            entries.push((
+                LookupTableEntry {
+                    resource,
+                    part_number: 1,
+                    ref_count,
+                    hash: stream.hash,
+                },
+                ref_count,
+            ));
+        }
+        Ok(entries)
+    }
+}
+
+/// 复制原文件，按需追加新的 XML 数据并更新文件头
+fn write_copy(path: &Path, temp: &mut TempFile, prepared: &Prepared) -> Result<()> {
+    let mut source =
+        File::open(path).with_context(|| format!("无法打开 WIM 文件: {}", path.display()))?;
+    std::io::copy(&mut source, temp).context("无法复制到临时文件")?;
+
+    let mut header = prepared.header.clone();
+    if let Some(xml) = &prepared.xml {
+        // 追加到文件末尾：完整性表只覆盖到偏移表结尾，原有资源保持不动
+        let xml = format::encode_xml_utf16(xml);
+        let offset = temp.seek(SeekFrom::End(0))?;
+        temp.write_all(&xml)?;
+        header.xml_data_resource = uncompressed_resource(offset, xml.len(), false);
+    }
+    temp.seek(SeekFrom::Start(0))?;
+    temp.write_all(&header.to_bytes())?;
+    Ok(())
+}
+
+/// 统计目录树中每个数据流的引用次数（累加到 `refs`）
+fn count_stream_refs(root: &DirEntry, refs: &mut HashMap<[u8; 20], u32>) {
+    root.walk(&mut |entry| {
+        let hashes = std::iter::once(&entry.hash).chain(entry.streams.iter().map(|s| &s.hash));
+        for hash in hashes.filter(|hash| **hash != [0u8; 20]) {
+            *refs.entry(*hash).or_insert(0) += 1;
+        }
+    });
+}
+
+/// 重写 XML：删除镜像节点、重新编号、替换名称和重新统计的计数
+fn rewrite_xml(
+    xml: &str,
+    renames: &HashMap<u32, String>,
+    stats: &HashMap<u32, ImageStats>,
+    new_indexes: &[Option<u32>],
+) -> Result<String> {
+    let mut out = String::with_capacity(xml.len());
+    let mut rest = xml;
+    for (position, new_index) in new_indexes.iter().enumerate() {
+        let index = position as u32 + 1;
+        let node = format::extract_image_xml(rest, index)
+            .ok_or_else(|| anyhow::anyhow!("XML 数据中没有镜像 {}", index))?;
+        let start = node.as_ptr() as usize - rest.as_ptr() as usize;
+        out.push_str(&rest[..start]);
+        rest = &rest[start + node.len()..];
+
+        let Some(new_index) = new_index else {
+            // 连同节点后的空白一起删除
+            rest = rest.trim_start();
+            continue;
+        };
+        let mut node = format::set_image_index(node, *new_index);
+        if let Some(name) = renames.get(&index) {
+            let name = escape_xml(name);
+            node = replace_tag(&node, "NAME", &name, true);
+            node = replace_tag(&node, "DISPLAYNAME", &name, false);
+        }
+        if let Some(stats) = stats.get(&index) {
+            node = replace_tag(&node, "DIRCOUNT", &stats.dir_count.to_string(), false);
+            node = replace_tag(&node, "FILECOUNT", &stats.file_count.to_string(), false);
+            node = replace_tag(&node, "TOTALBYTES", &stats.total_bytes.to_string(), false);
+        }
+        out.push_str(&node);
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+/// 替换 `<IMAGE>` 节点中的标签值；标签不存在且 `insert` 为 `true` 时插入到开始标签之后
+fn replace_tag(node: &str, tag: &str, value: &str, insert: bool) -> String {
+    let start_tag = format!("<{tag}>");
+    let end_tag = format!("</{tag}>");
+    if let Some(start) = node.find(&start_tag) {
+        if let Some(len) = node[start..].find(&end_tag) {
+            let end = start + len;
+            return format!("{}{start_tag}{value}{}", &node[..start], &node[end..]);
+        }
+    }
+    if !insert {
+        return node.to_string();
+    }
+    let open_end = node.find('>').map_or(node.len(), |end| end + 1);
+    format!(
+        "{}{start_tag}{value}{end_tag}{}",
+        &node[..open_end],
+        &node[open_end..]
+    )
+}
+
+/// 不存在的资源
+fn empty_resource() -> FileResourceEntry {
+    FileResourceEntry {
+        size: 0,
+        flags: 0,
+        offset: 0,
+        original_size: 0,
+    }
+}
+
+/// 未压缩资源的资源头
+fn uncompressed_resource(offset: u64, size: usize, metadata: bool) -> FileResourceEntry {
+    FileResourceEntry {
+        size: size as u64,
+        flags: if metadata { ResourceFlags::METADATA } else { 0 },
+        offset,
+        original_size: size as u64,
+    }
+}
@@ -0,0 +1,281 @@
+//! 摘要清单校验：将文件和镜像元数据的实际摘要与外部清单（ESD 目录、内部发布清单等）比对
+
+use anyhow::{Context, Result};
+use sha1::Sha1;
+use sha2::Sha256;
+use std::collections::HashMap;
+use std::fmt;
+use std::io::Read;
+
+use crate::fmt::{Table, ToTable};
+use crate::format::WIM_HEADER_DISK_SIZE;
+use crate::log::{debug, info};
+use crate::WimParser;
+
+/// 摘要算法及其值
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Digest {
+    /// SHA-1（WIM 偏移表中使用的算法）
+    Sha1([u8; 20]),
+    /// SHA-256
+    Sha256([u8; 32]),
+}
+
+impl Digest {
+    /// 从十六进制字符串解析 SHA-1 摘要
+    pub fn sha1_hex(hex: &str) -> Option<Self> {
+        parse_hex(hex).map(Digest::Sha1)
+    }
+
+    /// 从十六进制字符串解析 SHA-256 摘要
+    pub fn sha256_hex(hex: &str) -> Option<Self> {
+        parse_hex(hex).map(Digest::Sha256)
+    }
+
+    /// 算法名称
+    pub fn algorithm(&self) -> &'static str {
+        match self {
+            Digest::Sha1(_) => "SHA-1",
+            Digest::Sha256(_) => "SHA-256",
+        }
+    }
+
+    /// 摘要字节
+    pub fn as_bytes(&self) -> &[u8] {
+        match self {
+            Digest::Sha1(bytes) => bytes,
+            Digest::Sha256(bytes) => bytes,
+        }
+    }
+
+    /// 计算与 `self` 相同算法的摘要
+    fn compute_like(&self, reader: &mut impl Read) -> std::io::Result<Digest> {
+        match self {
+            Digest::Sha1(_) => hash_reader::<Sha1>(reader).map(|out| Digest::Sha1(out.into())),
+            Digest::Sha256(_) => {
+                hash_reader::<Sha256>(reader).map(|out| Digest::Sha256(out.into()))
+            }
+        }
+    }
+}
+
+impl fmt::Display for Digest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for byte in self.as_bytes() {
+            write!(f, "{byte:02x}")?;
+        }
+        Ok(())
+    }
+}
+
+fn parse_hex<const N: usize>(hex: &str) -> Option<[u8; N]> {
+    let hex = hex.trim();
+    if hex.len() != N * 2 || !hex.is_ascii() {
+        return None;
+    }
+    let mut out = [0u8; N];
+    for (i, byte) in out.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16).ok()?;
+    }
+    Some(out)
+}
+
+pub(crate) fn hash_reader<D: sha2::Digest>(
+    reader: &mut impl Read,
+) -> std::io::Result<sha2::digest::Output<D>> {
+    let mut hasher = D::new();
+    let mut buffer = vec![0u8; 1024 * 1024];
+    loop {
+        let read = reader.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+    Ok(hasher.finalize())
+}
+
+/// 预期摘要来源
+///
+/// 调用方可以从 Microsoft ESD 目录、内部发布清单或数据库实现此 trait。
+/// 返回 `None` 的项不参与校验。
+pub trait DigestManifest {
+    /// 整个文件的预期摘要
+    fn file_digest(&self) -> Option<Digest>;
+
+    /// 指定镜像（索引从 1 开始）元数据资源的预期摘要
+    fn image_metadata_digest(&self, index: u32) -> Option<Digest>;
+}
+
+/// 基于内存表的简单清单实现
+#[derive(Debug, Clone, Default)]
+pub struct Manifest {
+    file: Option<Digest>,
+    images: HashMap<u32, Digest>,
+}
+
+impl Manifest {
+    /// 创建空清单
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 设置整个文件的预期摘要
+    pub fn file(mut self, digest: Digest) -> Self {
+        self.file = Some(digest);
+        self
+    }
+
+    /// 设置指定镜像元数据资源的预期摘要
+    pub fn image_metadata(mut self, index: u32, digest: Digest) -> Self {
+        self.images.insert(index, digest);
+        self
+    }
+}
+
+impl DigestManifest for Manifest {
+    fn file_digest(&self) -> Option<Digest> {
+        self.file
+    }
+
+    fn image_metadata_digest(&self, index: u32) -> Option<Digest> {
+        self.images.get(&index).copied()
+    }
+}
+
+/// 校验对象
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VerifySubject {
+    /// 整个文件
+    File,
+    /// 镜像元数据资源
+    ImageMetadata(u32),
+}
+
+impl fmt::Display for VerifySubject {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VerifySubject::File => f.write_str("文件"),
+            VerifySubject::ImageMetadata(index) => write!(f, "镜像 {index} 元数据"),
+        }
+    }
+}
+
+/// 单项校验结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DigestCheck {
+    /// 校验对象
+    pub subject: VerifySubject,
+    /// 清单中的预期摘要
+    pub expected: Digest,
+    /// 实际计算的摘要
+    pub actual: Digest,
+}
+
+impl DigestCheck {
+    /// 摘要是否一致
+    pub fn is_match(&self) -> bool {
+        self.expected == self.actual
+    }
+}
+
+/// 清单校验报告
+#[derive(Debug, Clone, Default)]
+pub struct VerificationReport {
+    /// 各项校验结果，按文件、镜像索引顺序排列
+    pub checks: Vec<DigestCheck>,
+}
+
+impl VerificationReport {
+    /// 至少校验了一项且全部一致时视为通过
+    pub fn is_signed_off(&self) -> bool {
+        !self.checks.is_empty() && self.checks.iter().all(DigestCheck::is_match)
+    }
+
+    /// 不一致的校验项
+    pub fn mismatches(&self) -> impl Iterator<Item = &DigestCheck> {
+        self.checks.iter().filter(|check| !check.is_match())
+    }
+}
+
+impl ToTable for VerificationReport {
+    fn table(&self) -> Table {
+        let mut table = Table::new(["对象", "算法", "预期", "实际", "结果"]);
+        for check in &self.checks {
+            table.push_row([
+                check.subject.to_string(),
+                check.expected.algorithm().to_string(),
+                check.expected.to_string(),
+                check.actual.to_string(),
+                if check.is_match() {
+                    "通过"
+                } else {
+                    "不一致"
+                }
+                .to_string(),
+            ]);
+        }
+        table
+    }
+}
+
+impl WimParser {
+    /// 按清单校验文件和各镜像元数据资源的摘要
+    ///
+    /// 镜像元数据摘要按未压缩的元数据资源计算（SHA-1 与偏移表中记录的值相同）。
+    /// 清单只对文件头声明的镜像（`1..=image_count`）查询预期值。
+    ///
+    /// 嵌入其他文件中的 WIM（见 [`new_at`](Self::new_at)）的文件摘要只覆盖 WIM 自身：从起始偏移
+    /// 到文件头引用的资源的最远结束位置，不含容器中前后的其他数据。
+    pub fn verify_against(&mut self, manifest: &dyn DigestManifest) -> Result<VerificationReport> {
+        let header = self.read_header()?;
+        let image_count = header.image_count;
+        // 文件头引用的资源的最远结束位置（与 CarvedWim::extent 相同）
+        let extent = header
+            .resources()
+            .filter(|(_, resource)| !resource.is_absent())
+            .map(|(_, resource)| resource.byte_range().end)
+            .max()
+            .unwrap_or(WIM_HEADER_DISK_SIZE as u64);
+        let mut report = VerificationReport::default();
+
+        if let Some(expected) = manifest.file_digest() {
+            debug!("开始计算文件 {} 摘要", expected.algorithm());
+            self.seek_to(0)?;
+            let actual = if self.base_offset() == 0 {
+                expected.compute_like(&mut self.file)
+            } else {
+                expected.compute_like(&mut (&mut self.file).take(extent))
+            }
+            .context("计算文件摘要失败")?;
+            report.checks.push(DigestCheck {
+                subject: VerifySubject::File,
+                expected,
+                actual,
+            });
+        }
+
+        for index in 1..=image_count {
+            let Some(expected) = manifest.image_metadata_digest(index) else {
+                continue;
+            };
+            let resource = self.metadata_resource(index)?;
+            let data = self
+                .read_resource(&resource)
+                .with_context(|| format!("读取镜像 {index} 的元数据资源失败"))?;
+            let actual = expected.compute_like(&mut data.as_slice())?;
+            report.checks.push(DigestCheck {
+                subject: VerifySubject::ImageMetadata(index),
+                expected,
+                actual,
+            });
+        }
+
+        info!(
+            "清单校验完成: {} 项, {} 项不一致",
+            report.checks.len(),
+            report.mismatches().count()
+        );
+        Ok(report)
+    }
+}
@@ -0,0 +1,105 @@
+//! 由镜像名称和描述推断 Windows 版本的规则集
+//!
+//! 内置规则覆盖常见的客户端和服务器版本；调用方可以在运行时添加规则（例如新的服务器版本、
+//! LTSC 发行版或 OEM 定制名称），不必等待新版本发布。
+
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+/// 内置规则（按优先级从高到低）
+const BUILTIN_RULES: &[(&str, &str)] = &[
+    ("windows 11", "Windows 11"),
+    ("windows 10", "Windows 10"),
+    ("windows server 2022", "Windows Server 2022"),
+    ("windows server 2019", "Windows Server 2019"),
+    ("windows server", "Windows Server"),
+    ("windows", "Windows"),
+];
+
+/// 一条版本规则：名称或描述包含 `pattern`（不区分大小写）时，版本为 `version`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VersionRule {
+    /// 要查找的文本（已转换为小写）
+    pub pattern: String,
+    /// 匹配时的版本名称
+    pub version: String,
+}
+
+impl VersionRule {
+    /// 创建规则
+    pub fn new(pattern: &str, version: &str) -> Self {
+        Self {
+            pattern: pattern.to_lowercase(),
+            version: version.to_string(),
+        }
+    }
+
+    /// 是否匹配已转换为小写的名称和描述
+    fn matches(&self, text: &str) -> bool {
+        text.contains(self.pattern.as_str())
+    }
+}
+
+/// 版本推断规则集：按顺序检查，第一条匹配的规则决定版本
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VersionRules {
+    rules: Vec<VersionRule>,
+}
+
+impl Default for VersionRules {
+    /// 内置规则
+    fn default() -> Self {
+        Self::builtin()
+    }
+}
+
+impl VersionRules {
+    /// 内置规则（Windows 10/11、Windows Server 2019/2022 等）
+    pub fn builtin() -> Self {
+        Self {
+            rules: BUILTIN_RULES
+                .iter()
+                .map(|(pattern, version)| VersionRule::new(pattern, version))
+                .collect(),
+        }
+    }
+
+    /// 不含任何规则的空规则集
+    pub fn empty() -> Self {
+        Self { rules: Vec::new() }
+    }
+
+    /// 添加优先于现有规则的规则（用于比内置规则更具体的名称，例如 `windows server 2025`）
+    pub fn prepend(mut self, pattern: &str, version: &str) -> Self {
+        self.rules.insert(0, VersionRule::new(pattern, version));
+        self
+    }
+
+    /// 添加优先级最低的规则（只在现有规则都不匹配时生效）
+    pub fn push(mut self, pattern: &str, version: &str) -> Self {
+        self.rules.push(VersionRule::new(pattern, version));
+        self
+    }
+
+    /// 所有规则（按优先级从高到低）
+    pub fn rules(&self) -> &[VersionRule] {
+        &self.rules
+    }
+
+    /// 根据镜像名称和描述推断版本
+    pub fn detect(&self, name: &str, description: &str) -> Option<&str> {
+        let text = alloc::format!("{name} {description}").to_lowercase();
+        self.rules
+            .iter()
+            .find(|rule| rule.matches(&text))
+            .map(|rule| rule.version.as_str())
+    }
+}
+
+/// 用内置规则推断版本（不分配规则集）
+pub(crate) fn detect_builtin(text: &str) -> Option<&'static str> {
+    BUILTIN_RULES
+        .iter()
+        .find(|(pattern, _)| text.contains(pattern))
+        .map(|(_, version)| *version)
+}
@@ -0,0 +1,443 @@
+//! 内存中的虚拟 WIM：由内存文件组装镜像，再序列化为真实的未压缩 WIM
+
+use anyhow::Result;
+use sha1::{Digest as _, Sha1};
+use std::io::{BufWriter, Cursor, Seek, Write};
+use std::path::Path;
+
+use crate::lock::{self, LockPolicy};
+use crate::log::debug;
+use crate::metadata::{
+    self, DirEntry, FILE_ATTRIBUTE_DIRECTORY, FILE_ATTRIBUTE_REPARSE_POINT,
+    IO_REPARSE_TAG_MOUNT_POINT, IO_REPARSE_TAG_SYMLINK, WIM_RP_FLAG_NOT_FIXED,
+};
+use crate::rpfix;
+use crate::writer::WimWriter;
+use crate::xml_tree::escape_xml;
+use crate::{WimHeader, WimParser, WimTimestamp, WriteSettings};
+
+/// 普通文件属性 (FILE_ATTRIBUTE_NORMAL)
+const FILE_ATTRIBUTE_NORMAL: u32 = 0x0000_0080;
+
+/// 默认的捕获根目录
+const DEFAULT_CAPTURE_ROOT: &str = "C:\\";
+
+/// 数据流的 SHA-1 摘要和内容
+type Stream = ([u8; 20], Vec<u8>);
+
+/// 符号链接或目录联接（重解析数据在写入时按 RP_FIX 设置生成）
+#[derive(Debug, Clone)]
+struct VirtualLink {
+    path: String,
+    target: String,
+    tag: u32,
+}
+
+/// 虚拟 WIM 中的一个镜像
+#[derive(Debug, Clone)]
+pub struct VirtualImage {
+    name: String,
+    description: Option<String>,
+    extra_xml: String,
+    time: WimTimestamp,
+    root: DirEntry,
+    /// 文件内容（按添加顺序，写入时按 SHA-1 去重）
+    streams: Vec<Stream>,
+    links: Vec<VirtualLink>,
+}
+
+/// 内存中的虚拟 WIM 构造器
+///
+/// 用于在没有样本文件的情况下测试下游程序：在内存中添加目录和文件，
+/// 然后序列化为未压缩的 WIM（[`to_bytes`](Self::to_bytes) / [`save`](Self::save)），
+/// 或直接用 [`open`](Self::open) 写入文件并得到可用的 [`WimParser`]。
+///
+/// ```no_run
+/// # use wim_parser::VirtualWim;
+/// let mut wim = VirtualWim::new();
+/// wim.add_image("Windows 11 Pro")
+///     .add_file("/Windows/System32/a.dll", b"hello")?
+///     .add_dir("/Users")?;
+/// let mut parser = wim.open("test.wim")?;
+/// parser.parse_full()?;
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct VirtualWim {
+    images: Vec<VirtualImage>,
+    settings: WriteSettings,
+    capture_root: Option<String>,
+}
+
+/// 拆分路径（`/` 或 `\` 分隔，忽略空段）
+fn path_parts(path: &str) -> Vec<&str> {
+    path.split(['\\', '/'])
+        .filter(|part| !part.is_empty())
+        .collect()
+}
+
+/// `<HIGHPART>` / `<LOWPART>` 形式的时间节点
+fn xml_time(tag: &str, time: WimTimestamp) -> String {
+    format!(
+        "<{tag}><HIGHPART>0x{:08X}</HIGHPART><LOWPART>0x{:08X}</LOWPART></{tag}>",
+        time.high_part(),
+        time.low_part()
+    )
+}
+
+impl VirtualImage {
+    fn new(name: &str) -> Self {
+        let time = WimTimestamp::from_system_time(std::time::SystemTime::now()).unwrap_or_default();
+        Self {
+            name: name.to_string(),
+            description: None,
+            extra_xml: String::new(),
+            time,
+            root: new_entry("", FILE_ATTRIBUTE_DIRECTORY, time),
+            streams: Vec::new(),
+            links: Vec::new(),
+        }
+    }
+
+    /// 镜像名称
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// 设置 `<DESCRIPTION>`
+    pub fn set_description(&mut self, description: &str) -> &mut Self {
+        self.description = Some(description.to_string());
+        self
+    }
+
+    /// 追加到 `<IMAGE>` 节点内的原始 XML（如 `<WINDOWS>` 节）
+    pub fn extra_xml(&mut self, xml: &str) -> &mut Self {
+        self.extra_xml.push_str(xml);
+        self
+    }
+
+    /// 设置镜像和之后添加的目录项的时间（默认为创建镜像时的当前时间）
+    pub fn set_time(&mut self, time: WimTimestamp) -> &mut Self {
+        self.time = time;
+        self.root.creation_time = time;
+        self.root.last_access_time = time;
+        self.root.last_write_time = time;
+        self
+    }
+
+    /// 添加目录（自动创建上级目录）
+    pub fn add_dir(&mut self, path: &str) -> Result<&mut Self> {
+        let parts = path_parts(path);
+        self.ensure_dir(&parts, path)?;
+        Ok(self)
+    }
+
+    /// 添加文件（自动创建上级目录）；同名文件已存在时替换其内容
+    pub fn add_file(&mut self, path: &str, data: impl AsRef<[u8]>) -> Result<&mut Self> {
+        let parts = path_parts(path);
+        let (name, parents) = parts
+            .split_last()
+            .ok_or_else(|| anyhow::anyhow!("文件路径为空: {:?}", path))?;
+        let data = data.as_ref();
+        let hash = if data.is_empty() {
+            [0u8; 20]
+        } else {
+            Sha1::digest(data).into()
+        };
+
+        let time = self.time;
+        let dir = self.ensure_dir(parents, path)?;
+        let replaced = match dir
+            .children
+            .iter_mut()
+            .find(|child| child.name.eq_ignore_ascii_case(name))
+        {
+            Some(existing) if existing.is_directory() => {
+                return Err(anyhow::anyhow!("路径已存在同名目录: {}", path));
+            }
+            Some(existing) => Some(std::mem::replace(&mut existing.hash, hash)),
+            None => {
+                let mut entry = new_entry(name, FILE_ATTRIBUTE_NORMAL, time);
+                entry.hash = hash;
+                dir.children.push(entry);
+                None
+            }
+        };
+
+        if let Some(old) = replaced.filter(|old| *old != [0u8; 20]) {
+            if let Some(position) = self.streams.iter().position(|(h, _)| *h == old) {
+                self.streams.remove(position);
+            }
+        }
+        if !data.is_empty() {
+            self.streams.push((hash, data.to_vec()));
+        }
+        Ok(self)
+    }
+
+    /// 添加符号链接（自动创建上级目录）
+    ///
+    /// `target` 可以是相对路径，也可以是捕获时系统上的绝对路径（如 `C:\Users\Public`）；
+    /// 按 [`WriteSettings::rp_fix`] 写入时，位于捕获根目录之内的绝对目标改写为镜像内的路径。
+    pub fn add_symlink(&mut self, path: &str, target: &str) -> Result<&mut Self> {
+        self.add_link(path, target, IO_REPARSE_TAG_SYMLINK, 0)
+    }
+
+    /// 添加目录联接（自动创建上级目录），如 `Documents and Settings` → `C:\Users`
+    pub fn add_junction(&mut self, path: &str, target: &str) -> Result<&mut Self> {
+        self.add_link(
+            path,
+            target,
+            IO_REPARSE_TAG_MOUNT_POINT,
+            FILE_ATTRIBUTE_DIRECTORY,
+        )
+    }
+
+    fn add_link(
+        &mut self,
+        path: &str,
+        target: &str,
+        tag: u32,
+        attributes: u32,
+    ) -> Result<&mut Self> {
+        let parts = path_parts(path);
+        let (name, parents) = parts
+            .split_last()
+            .ok_or_else(|| anyhow::anyhow!("链接路径为空: {:?}", path))?;
+        if target.is_empty() {
+            return Err(anyhow::anyhow!("链接目标为空: {}", path));
+        }
+
+        let time = self.time;
+        let dir = self.ensure_dir(parents, path)?;
+        if dir
+            .children
+            .iter()
+            .any(|child| child.name.eq_ignore_ascii_case(name))
+        {
+            return Err(anyhow::anyhow!("路径已存在: {}", path));
+        }
+        let mut entry = new_entry(name, attributes | FILE_ATTRIBUTE_REPARSE_POINT, time);
+        entry.reparse_tag = tag;
+        dir.children.push(entry);
+
+        self.links.push(VirtualLink {
+            path: parts.join("/"),
+            target: target.to_string(),
+            tag,
+        });
+        Ok(self)
+    }
+
+    /// 生成链接的重解析数据，返回填好摘要的目录树和重解析数据流
+    fn resolve_links(&self, rp_fix: bool, capture_root: &str) -> (DirEntry, Vec<Stream>) {
+        let mut root = self.root.clone();
+        let mut streams = Vec::with_capacity(self.links.len());
+        for link in &self.links {
+            let fixed = rp_fix
+                .then(|| rpfix::fix_capture_target(&link.target, capture_root))
+                .flatten();
+            let not_fixed = rp_fix && fixed.is_none() && rpfix::is_absolute_target(&link.target);
+            let data = rpfix::encode_link_reparse_data(
+                link.tag,
+                fixed.as_deref().unwrap_or(&link.target),
+                fixed.is_some(),
+            );
+            let hash: [u8; 20] = Sha1::digest(&data).into();
+
+            if let Some(entry) = find_entry_mut(&mut root, &path_parts(&link.path)) {
+                entry.hash = hash;
+                entry.rp_flags = if not_fixed { WIM_RP_FLAG_NOT_FIXED } else { 0 };
+            }
+            streams.push((hash, data));
+        }
+        (root, streams)
+    }
+
+    /// 按路径逐级查找或创建目录
+    fn ensure_dir(&mut self, parts: &[&str], path: &str) -> Result<&mut DirEntry> {
+        let time = self.time;
+        let mut dir = &mut self.root;
+        for part in parts {
+            let position = match dir
+                .children
+                .iter()
+                .position(|child| child.name.eq_ignore_ascii_case(part))
+            {
+                Some(position)
+                    if !dir.children[position].is_directory()
+                        || dir.children[position].is_reparse_point() =>
+                {
+                    return Err(anyhow::anyhow!("路径中的 {} 已是文件: {}", part, path));
+                }
+                Some(position) => position,
+                None => {
+                    dir.children
+                        .push(new_entry(part, FILE_ATTRIBUTE_DIRECTORY, time));
+                    dir.children.len() - 1
+                }
+            };
+            dir = &mut dir.children[position];
+        }
+        Ok(dir)
+    }
+
+    /// 目录数（含根目录）、文件数和总字节数
+    fn stats(&self) -> (u32, u32, u64) {
+        let (mut dirs, mut files) = (0, 0);
+        self.root.walk(&mut |entry| {
+            if entry.is_directory() {
+                dirs += 1;
+            } else {
+                files += 1;
+            }
+        });
+        let bytes = self.streams.iter().map(|(_, data)| data.len() as u64).sum();
+        (dirs, files, bytes)
+    }
+
+    /// 生成 `<IMAGE>` 节点
+    fn image_xml(&self, index: usize) -> String {
+        let (dirs, files, bytes) = self.stats();
+        let name = escape_xml(&self.name);
+        let description = self.description.as_deref().map(escape_xml);
+        let mut xml = format!(
+            "<IMAGE INDEX=\"{index}\"><DIRCOUNT>{dirs}</DIRCOUNT><FILECOUNT>{files}</FILECOUNT>\
+             <TOTALBYTES>{bytes}</TOTALBYTES><HARDLINKBYTES>0</HARDLINKBYTES>{}{}{}\
+             <NAME>{name}</NAME><DISPLAYNAME>{name}</DISPLAYNAME>",
+            xml_time("CREATIONTIME", self.time),
+            xml_time("LASTMODIFICATIONTIME", self.time),
+            self.extra_xml,
+        );
+        if let Some(description) = description {
+            xml.push_str(&format!(
+                "<DESCRIPTION>{description}</DESCRIPTION>\
+                 <DISPLAYDESCRIPTION>{description}</DISPLAYDESCRIPTION>"
+            ));
+        }
+        xml.push_str("</IMAGE>");
+        xml
+    }
+}
+
+/// 按路径查找目录项（不区分大小写）
+fn find_entry_mut<'a>(dir: &'a mut DirEntry, parts: &[&str]) -> Option<&'a mut DirEntry> {
+    match parts.split_first() {
+        None => Some(dir),
+        Some((part, rest)) => {
+            let child = dir
+                .children
+                .iter_mut()
+                .find(|child| child.name.eq_ignore_ascii_case(part))?;
+            find_entry_mut(child, rest)
+        }
+    }
+}
+
+/// 创建没有安全描述符的目录项
+fn new_entry(name: &str, attributes: u32, time: WimTimestamp) -> DirEntry {
+    DirEntry {
+        attributes,
+        security_id: -1,
+        creation_time: time,
+        last_access_time: time,
+        last_write_time: time,
+        hash: [0u8; 20],
+        reparse_tag: 0,
+        rp_flags: 0,
+        hard_link_group_id: 0,
+        name: name.to_string(),
+        name_utf16: None,
+        short_name: String::new(),
+        streams: Vec::new(),
+        children: Vec::new(),
+    }
+}
+
+impl VirtualWim {
+    /// 创建空的虚拟 WIM
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 写入设置（可直接传入 [`Preset`](crate::Preset)），默认为未压缩、无完整性表
+    pub fn set_write_settings(&mut self, settings: impl Into<WriteSettings>) -> &mut Self {
+        self.settings = settings.into();
+        self
+    }
+
+    /// 捕获根目录（默认为 `C:\`）：启用 RP_FIX 时，指向其中的绝对链接目标改写为镜像内的路径
+    pub fn set_capture_root(&mut self, root: &str) -> &mut Self {
+        self.capture_root = Some(root.to_string());
+        self
+    }
+
+    /// 添加镜像（索引按添加顺序从 1 开始），返回该镜像以添加内容
+    pub fn add_image(&mut self, name: &str) -> &mut VirtualImage {
+        self.images.push(VirtualImage::new(name));
+        self.images.last_mut().unwrap()
+    }
+
+    /// 向最后添加的镜像添加文件（没有镜像时先创建名为 `Image 1` 的镜像）
+    pub fn add_file(&mut self, path: &str, data: impl AsRef<[u8]>) -> Result<&mut Self> {
+        if self.images.is_empty() {
+            self.add_image("Image 1");
+        }
+        self.images.last_mut().unwrap().add_file(path, data)?;
+        Ok(self)
+    }
+
+    /// 指定索引的镜像
+    pub fn image_mut(&mut self, index: u32) -> Option<&mut VirtualImage> {
+        let position = (index as usize).checked_sub(1)?;
+        self.images.get_mut(position)
+    }
+
+    /// 镜像数量
+    pub fn image_count(&self) -> u32 {
+        self.images.len() as u32
+    }
+
+    /// 写入 WIM（写入器的当前位置视为文件开始）
+    pub fn write_to<W: Write + Seek>(&self, writer: W) -> Result<WimHeader> {
+        self.write_into(writer).map(|(header, _)| header)
+    }
+
+    fn write_into<W: Write + Seek>(&self, writer: W) -> Result<(WimHeader, W)> {
+        let mut wim = WimWriter::new(writer, self.settings)?;
+        let mut images_xml = String::new();
+        let capture_root = self.capture_root.as_deref().unwrap_or(DEFAULT_CAPTURE_ROOT);
+        for (position, image) in self.images.iter().enumerate() {
+            let (root, link_streams) = image.resolve_links(self.settings.rp_fix, capture_root);
+            for (hash, data) in image.streams.iter().chain(&link_streams) {
+                wim.add_stream(*hash, data)?;
+            }
+            let metadata = metadata::encode_metadata_resource(&root);
+            wim.add_metadata(Sha1::digest(&metadata).into(), &metadata)?;
+            images_xml.push_str(&image.image_xml(position + 1));
+        }
+        let total_bytes: u64 = self.images.iter().map(|image| image.stats().2).sum();
+        let xml = format!("<WIM><TOTALBYTES>{total_bytes}</TOTALBYTES>{images_xml}</WIM>");
+
+        debug!("写入虚拟 WIM - 镜像数: {}", self.images.len());
+        wim.finish_into_inner(&xml)
+    }
+
+    /// 序列化为内存中的 WIM 文件内容
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        let (_, cursor) = self.write_into(Cursor::new(Vec::new()))?;
+        Ok(cursor.into_inner())
+    }
+
+    /// 写入 WIM 文件（写入期间持有排他锁，见 [`LockPolicy::FailFast`]）
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<WimHeader> {
+        let file = lock::create_locked(path.as_ref(), LockPolicy::FailFast)?;
+        self.write_to(BufWriter::new(file))
+    }
+
+    /// 写入 WIM 文件并用解析器打开
+    pub fn open<P: AsRef<Path>>(&self, path: P) -> Result<WimParser> {
+        self.save(path.as_ref())?;
+        WimParser::new(path)
+    }
+}
@@ -0,0 +1,111 @@
+use core::fmt;
+
+use crate::resource::ResourceKind;
+
+/// 解析过程中发现、但不影响继续解析的问题
+///
+/// 容错的调用方可以展示这些警告而不中断处理；严格模式
+/// (`ParseOptions::strict`) 下它们会被提升为错误。
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Warning {
+    /// 镜像的 `<ARCH>` 数值无法识别
+    UnknownArch {
+        /// 镜像索引
+        index: u32,
+        /// 原始 ARCH 数值
+        value: u32,
+    },
+    /// 文件头中的镜像数量与 XML 数据中的镜像数量不一致
+    ImageCountMismatch {
+        /// 文件头记录的镜像数量
+        header: u32,
+        /// XML 数据中的镜像数量
+        xml: usize,
+    },
+    /// `<IMAGE>` 节点缺少有效的 `INDEX` 属性（索引按 0 处理）
+    InvalidImageIndex {
+        /// 该节点在 XML 中的位置（从 1 开始）
+        position: usize,
+    },
+    /// XML 数据中有未闭合的 `<IMAGE>` 节点，已忽略
+    UnclosedImage {
+        /// 被忽略的节点数量
+        count: usize,
+    },
+    /// 文件头引用的资源设置了未定义的标志位
+    UnknownResourceFlags {
+        /// 资源种类
+        kind: ResourceKind,
+        /// 未定义的标志位
+        bits: u8,
+    },
+    /// 文件头设置了未定义的标志位（可能来自更新版本的工具）
+    UnknownHeaderFlags {
+        /// 未定义的标志位
+        bits: u32,
+    },
+    /// 文件头设置了 `WRITE_IN_PROGRESS`：上次写入可能没有完成
+    WriteInProgress,
+    /// 文件头的 `SPANNED` 标志与分卷总数不一致（读取时以分卷总数为准）
+    SpannedFlagMismatch {
+        /// 文件头记录的分卷总数
+        total_segments: u16,
+        /// 是否设置了 `SPANNED` 标志
+        flagged: bool,
+    },
+    /// 单分卷文件中有资源条目设置了 `SPANNED` 标志（读取时按当前文件中的资源处理）
+    SpannedResources {
+        /// 设置了 `SPANNED` 标志的资源条目数量（文件头和偏移表）
+        count: usize,
+    },
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Warning::UnknownArch { index, value } => {
+                write!(f, "镜像 {index} 的架构数值 {value} 无法识别")
+            }
+            Warning::ImageCountMismatch { header, xml } => {
+                write!(f, "文件头镜像数 ({header}) 与 XML 镜像数 ({xml}) 不一致")
+            }
+            Warning::InvalidImageIndex { position } => {
+                write!(f, "第 {position} 个 IMAGE 节点缺少有效的 INDEX 属性")
+            }
+            Warning::UnclosedImage { count } => {
+                write!(f, "XML 数据中有 {count} 个未闭合的 IMAGE 节点，已忽略")
+            }
+            Warning::UnknownResourceFlags { kind, bits } => {
+                write!(f, "资源 {kind} 设置了未定义的标志位 0x{bits:02X}")
+            }
+            Warning::UnknownHeaderFlags { bits } => {
+                write!(f, "文件头设置了未定义的标志位 0x{bits:08X}")
+            }
+            Warning::WriteInProgress => {
+                f.write_str("文件头设置了 WRITE_IN_PROGRESS 标志，文件可能未完整写入")
+            }
+            Warning::SpannedFlagMismatch {
+                total_segments,
+                flagged: true,
+            } => {
+                write!(
+                    f,
+                    "文件头设置了 SPANNED 标志，但分卷总数为 {total_segments}"
+                )
+            }
+            Warning::SpannedFlagMismatch {
+                total_segments,
+                flagged: false,
+            } => {
+                write!(
+                    f,
+                    "分卷总数为 {total_segments}，但文件头未设置 SPANNED 标志"
+                )
+            }
+            Warning::SpannedResources { count } => {
+                write!(f, "单分卷文件中有 {count} 个资源条目设置了 SPANNED 标志")
+            }
+        }
+    }
+}
@@ -0,0 +1,120 @@
+use anyhow::Result;
+
+use crate::log::debug;
+use crate::WimParser;
+
+/// winpeshl.ini：替换默认 shell 的启动配置
+pub(crate) const WINPESHL_INI: &str = r"\Windows\System32\winpeshl.ini";
+/// startnet.cmd：默认 shell 执行的启动脚本
+pub(crate) const STARTNET_CMD: &str = r"\Windows\System32\startnet.cmd";
+/// SYSTEM 注册表配置单元，其中记录暂存空间大小
+const SYSTEM_HIVE: &str = r"\Windows\System32\config\SYSTEM";
+/// 安装程序入口
+pub(crate) const SETUP_EXE: [&str; 2] = [r"\setup.exe", r"\sources\setup.exe"];
+
+/// 暂存空间大小对应的注册表值名称 (FBWF\WinPECacheThreshold，单位 MB)
+const SCRATCH_SPACE_VALUE: &[u8] = b"WinPECacheThreshold";
+
+/// Windows PE 镜像的专有信息
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WinPeInfo {
+    /// 镜像索引
+    pub index: u32,
+    /// 是否包含 winpeshl.ini
+    pub has_winpeshl_ini: bool,
+    /// 是否包含 startnet.cmd
+    pub has_startnet_cmd: bool,
+    /// 是否包含 Windows 安装程序 (setup.exe)
+    pub has_setup: bool,
+    /// 从 SYSTEM 配置单元中读取的暂存空间大小 (MB)，无法读取时为 `None`
+    pub scratch_space_mb: Option<u32>,
+}
+
+/// 在注册表配置单元中查找 `WinPECacheThreshold` 值（REG_DWORD，数据内联在 vk 单元中）
+///
+/// vk 单元布局：签名 `vk` (2) + 名称长度 (2) + 数据大小 (4) + 数据偏移/内联数据 (4) +
+/// 数据类型 (4) + 标志 (2) + 保留 (2) + 名称。
+fn find_scratch_space(hive: &[u8]) -> Option<u32> {
+    const NAME_OFFSET: usize = 0x14;
+    const REG_DWORD: u32 = 4;
+
+    hive.windows(SCRATCH_SPACE_VALUE.len())
+        .enumerate()
+        .filter(|(_, window)| *window == SCRATCH_SPACE_VALUE)
+        .filter_map(|(pos, _)| pos.checked_sub(NAME_OFFSET))
+        .find_map(|cell| {
+            let field = |offset: usize| -> Option<u32> {
+                Some(u32::from_le_bytes(
+                    hive.get(cell + offset..cell + offset + 4)?
+                        .try_into()
+                        .ok()?,
+                ))
+            };
+            let name_len = u16::from_le_bytes(hive.get(cell + 2..cell + 4)?.try_into().ok()?);
+            let inline = field(4)? & 0x8000_0000 != 0;
+            (hive.get(cell..cell + 2)? == b"vk"
+                && usize::from(name_len) == SCRATCH_SPACE_VALUE.len()
+                && inline
+                && field(12)? == REG_DWORD)
+                .then(|| field(8))
+                .flatten()
+        })
+}
+
+impl WimParser {
+    /// 获取 Windows PE 镜像的专有信息（镜像不是 WinPE 时返回 `None`）
+    ///
+    /// 暂存空间大小从 SYSTEM 配置单元中读取，配置单元位于压缩资源中时暂时无法获取。
+    pub fn winpe_info(&mut self, index: u32) -> Result<Option<WinPeInfo>> {
+        if self.images.is_empty() {
+            self.parse_full()?;
+        }
+
+        let is_pe = self
+            .get_image(index)
+            .ok_or_else(|| anyhow::anyhow!("XML 数据中没有镜像 {}", index))?
+            .is_windows_pe();
+        if !is_pe {
+            return Ok(None);
+        }
+
+        let root = self.read_metadata_root(index)?;
+        let has_file = |path: &str| {
+            root.find_path(path)
+                .is_some_and(|entry| !entry.is_directory())
+        };
+
+        let has_winpeshl_ini = has_file(WINPESHL_INI);
+        let has_startnet_cmd = has_file(STARTNET_CMD);
+        let has_setup = SETUP_EXE.iter().any(|path| has_file(path));
+
+        let hive_hash = root.find_path(SYSTEM_HIVE).map(|entry| entry.hash);
+        let scratch_space_mb = match hive_hash {
+            Some(hash) => match self.read_stream(&hash) {
+                Ok(hive) => find_scratch_space(&hive),
+                Err(e) => {
+                    debug!("无法读取镜像 {} 的 SYSTEM 配置单元: {}", index, e);
+                    None
+                }
+            },
+            None => None,
+        };
+
+        Ok(Some(WinPeInfo {
+            index,
+            has_winpeshl_ini,
+            has_startnet_cmd,
+            has_setup,
+            scratch_space_mb,
+        }))
+    }
+
+    /// 所有 Windows PE 镜像的索引
+    pub fn windows_pe_images(&self) -> Vec<u32> {
+        self.images
+            .iter()
+            .filter(|image| image.is_windows_pe())
+            .map(|image| image.index)
+            .collect()
+    }
+}
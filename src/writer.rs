@@ -0,0 +1,209 @@
+use anyhow::{Context, Result};
+use std::collections::hash_map::RandomState;
+use std::collections::HashMap;
+use std::fs::File;
+use std::hash::BuildHasher;
+use std::io::{BufWriter, Seek, SeekFrom, Write};
+use std::path::Path;
+
+use crate::format::{self, WIM_HEADER_DISK_SIZE, WIM_SIGNATURE};
+#[cfg(feature = "verify")]
+use crate::integrity::IntegrityBuilder;
+use crate::lock::{self, LockPolicy};
+use crate::lookup_table::LookupTableEntry;
+use crate::{FileResourceEntry, ResourceFlags, WimHeader, WriteSettings};
+
+/// WIM 格式版本 (1.13)
+const WIM_FORMAT_VERSION: u32 = 0x10d00;
+
+/// WIM 文件写入器
+///
+/// 数据流按 SHA-1 去重，重复添加只增加引用计数；元数据资源按添加顺序对应镜像索引。
+/// 文件头按 [`WriteSettings`] 声明压缩格式和分块大小；目前还没有压缩编码器，
+/// 除原样复制的压缩资源（见 [`add_raw_stream`](Self::add_raw_stream)）外，
+/// 资源均以未压缩形式存放（资源头不设压缩标志，与 DISM 存放不可压缩数据的方式相同）。
+pub(crate) struct WimWriter<W: Write + Seek = BufWriter<File>> {
+    out: W,
+    offset: u64,
+    settings: WriteSettings,
+    metadata: Vec<LookupTableEntry>,
+    streams: Vec<LookupTableEntry>,
+    stream_index: HashMap<[u8; 20], usize>,
+    /// 完整性表（文件头之后到偏移表结尾），偏移表写入后停止累计
+    #[cfg(feature = "verify")]
+    integrity: Option<IntegrityBuilder>,
+}
+
+impl WimWriter {
+    /// 创建输出文件、按 `lock_policy` 加排他锁并预留文件头空间
+    pub fn create(path: &Path, settings: WriteSettings, lock_policy: LockPolicy) -> Result<Self> {
+        let file = lock::create_locked(path, lock_policy)?;
+        Self::new(BufWriter::new(file), settings)
+    }
+}
+
+impl<W: Write + Seek> WimWriter<W> {
+    /// 写入到任意输出（从当前位置为 0 开始），预留文件头空间
+    pub fn new(mut out: W, settings: WriteSettings) -> Result<Self> {
+        #[cfg(not(feature = "verify"))]
+        if settings.integrity {
+            return Err(anyhow::anyhow!("写入完整性表需要启用 verify 特性"));
+        }
+        out.write_all(&[0u8; WIM_HEADER_DISK_SIZE])?;
+
+        Ok(Self {
+            out,
+            offset: WIM_HEADER_DISK_SIZE as u64,
+            settings,
+            metadata: Vec::new(),
+            streams: Vec::new(),
+            stream_index: HashMap::new(),
+            #[cfg(feature = "verify")]
+            integrity: settings
+                .integrity
+                .then(|| IntegrityBuilder::new(crate::INTEGRITY_CHUNK_SIZE)),
+        })
+    }
+
+    /// 写入一段未压缩资源，返回其资源头
+    fn write_resource(&mut self, data: &[u8], flags: u8) -> Result<FileResourceEntry> {
+        self.write_stored_resource(data, data.len() as u64, flags)
+    }
+
+    /// 原样写入资源数据（压缩资源为分块表和分块），返回其资源头
+    fn write_stored_resource(
+        &mut self,
+        data: &[u8],
+        original_size: u64,
+        flags: u8,
+    ) -> Result<FileResourceEntry> {
+        let entry = FileResourceEntry {
+            size: data.len() as u64,
+            flags,
+            offset: self.offset,
+            original_size,
+        };
+        self.out.write_all(data).context("写入资源数据失败")?;
+        #[cfg(feature = "verify")]
+        if let Some(integrity) = &mut self.integrity {
+            integrity.update(data);
+        }
+        self.offset += data.len() as u64;
+        Ok(entry)
+    }
+
+    /// 增加已写入数据流的引用计数
+    pub fn add_stream_ref(&mut self, hash: &[u8; 20]) -> bool {
+        match self.stream_index.get(hash) {
+            Some(&index) => {
+                self.streams[index].ref_count += 1;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// 添加数据流；已存在时只增加引用计数
+    pub fn add_stream(&mut self, hash: [u8; 20], data: &[u8]) -> Result<()> {
+        if self.add_stream_ref(&hash) {
+            return Ok(());
+        }
+        let resource = self.write_resource(data, 0)?;
+        self.push_stream(hash, resource);
+        Ok(())
+    }
+
+    /// 添加已按文件头的压缩格式和分块大小压缩的数据流，`raw` 为源资源的分块表和分块，原样写入；
+    /// 已存在时只增加引用计数
+    pub fn add_raw_stream(&mut self, hash: [u8; 20], raw: &[u8], original_size: u64) -> Result<()> {
+        if self.add_stream_ref(&hash) {
+            return Ok(());
+        }
+        let resource = self.write_stored_resource(raw, original_size, ResourceFlags::COMPRESSED)?;
+        self.push_stream(hash, resource);
+        Ok(())
+    }
+
+    fn push_stream(&mut self, hash: [u8; 20], resource: FileResourceEntry) {
+        self.stream_index.insert(hash, self.streams.len());
+        self.streams.push(LookupTableEntry {
+            resource,
+            part_number: 1,
+            ref_count: 1,
+            hash,
+        });
+    }
+
+    /// 添加镜像元数据资源（索引按添加顺序从 1 开始）
+    pub fn add_metadata(&mut self, hash: [u8; 20], data: &[u8]) -> Result<()> {
+        let resource = self.write_resource(data, ResourceFlags::METADATA)?;
+        self.metadata.push(LookupTableEntry {
+            resource,
+            part_number: 1,
+            ref_count: 1,
+            hash,
+        });
+        Ok(())
+    }
+
+    /// 写入偏移表、XML 数据和文件头，完成输出
+    pub fn finish(self, xml: &str) -> Result<WimHeader> {
+        self.finish_into_inner(xml).map(|(header, _)| header)
+    }
+
+    /// 完成输出并返回底层写入器
+    pub fn finish_into_inner(mut self, xml: &str) -> Result<(WimHeader, W)> {
+        let mut table = Vec::new();
+        for entry in self.metadata.iter().chain(&self.streams) {
+            table.extend_from_slice(&entry.to_bytes());
+        }
+        let offset_table_resource = self.write_resource(&table, ResourceFlags::METADATA)?;
+        #[cfg(feature = "verify")]
+        let integrity = self.integrity.take();
+        let xml_data_resource = self.write_resource(&format::encode_xml_utf16(xml), 0)?;
+
+        let empty = FileResourceEntry {
+            size: 0,
+            flags: 0,
+            offset: 0,
+            original_size: 0,
+        };
+        #[cfg(feature = "verify")]
+        let integrity_resource = match integrity {
+            Some(integrity) => self.write_resource(&integrity.finish(), 0)?,
+            None => empty.clone(),
+        };
+        #[cfg(not(feature = "verify"))]
+        let integrity_resource = empty.clone();
+        let header = WimHeader {
+            signature: WIM_SIGNATURE,
+            header_size: WIM_HEADER_DISK_SIZE as u32,
+            format_version: WIM_FORMAT_VERSION,
+            file_flags: self.settings.file_flags(),
+            compressed_size: self.settings.chunk_size(),
+            guid: new_guid(),
+            segment_number: 1,
+            total_segments: 1,
+            image_count: self.metadata.len() as u32,
+            offset_table_resource,
+            xml_data_resource,
+            boot_metadata_resource: empty,
+            bootable_image_index: 0,
+            integrity_resource,
+        };
+
+        self.out.seek(SeekFrom::Start(0))?;
+        self.out.write_all(&header.to_bytes())?;
+        self.out.flush().context("写入输出文件失败")?;
+        Ok((header, self.out))
+    }
+}
+
+/// 生成新的 WIM GUID
+fn new_guid() -> [u8; 16] {
+    let state = RandomState::new();
+    let mut guid = [0u8; 16];
+    guid[..8].copy_from_slice(&state.hash_one(0u8).to_le_bytes());
+    guid[8..].copy_from_slice(&state.hash_one(1u8).to_le_bytes());
+    guid
+}
@@ -0,0 +1,296 @@
+//! 轻量 XML 元素树：保留每个 `<IMAGE>` 节点的完整内容，按路径访问尚未建模的字段
+//!
+//! 只依赖 `core` 和 `alloc`。支持元素、属性、文本、CDATA 和常见实体，忽略注释、处理指令和 DOCTYPE 声明。
+//! 按名称查找时，不带前缀的名称也能匹配带命名空间前缀的元素和属性（`NAME` 匹配 `wim:NAME`）。
+
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+/// XML 元素
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct XmlElement {
+    /// 元素名
+    pub name: String,
+    /// 属性（按出现顺序）
+    pub attributes: Vec<(String, String)>,
+    /// 直接包含的文本（已解码实体、去除首尾空白）
+    pub text: String,
+    /// 子元素
+    pub children: Vec<XmlElement>,
+}
+
+impl XmlElement {
+    /// 解析第一个元素（前面的 XML 声明、注释和空白会被跳过），格式错误时返回 `None`
+    pub fn parse(xml: &str) -> Option<XmlElement> {
+        let mut parser = Parser { rest: xml };
+        parser.skip_misc();
+        parser.element(0)
+    }
+
+    /// 去掉命名空间前缀后的元素名
+    pub fn local_name(&self) -> &str {
+        local_name(&self.name)
+    }
+
+    /// 属性值（命名空间声明 `xmlns`、`xmlns:*` 也作为属性保留）
+    pub fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(key, _)| name_matches(key, name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// 第一个指定名称的子元素
+    pub fn child(&self, name: &str) -> Option<&XmlElement> {
+        self.children
+            .iter()
+            .find(|child| name_matches(&child.name, name))
+    }
+
+    /// 所有指定名称的子元素
+    pub fn children_named<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a XmlElement> {
+        self.children
+            .iter()
+            .filter(move |child| name_matches(&child.name, name))
+    }
+
+    /// 按文档顺序查找第一个指定名称的后代元素（不含自身）
+    pub fn descendant(&self, name: &str) -> Option<&XmlElement> {
+        self.children.iter().find_map(|child| {
+            if name_matches(&child.name, name) {
+                Some(child)
+            } else {
+                child.descendant(name)
+            }
+        })
+    }
+
+    /// 按文档顺序列出所有指定名称的后代元素（不含自身）
+    pub fn descendants_named<'a>(&'a self, name: &str) -> Vec<&'a XmlElement> {
+        let mut found = Vec::new();
+        self.collect_descendants(name, &mut found);
+        found
+    }
+
+    fn collect_descendants<'a>(&'a self, name: &str, found: &mut Vec<&'a XmlElement>) {
+        for child in &self.children {
+            if name_matches(&child.name, name) {
+                found.push(child);
+            }
+            child.collect_descendants(name, found);
+        }
+    }
+
+    /// 按路径查找后代元素，例如 `WINDOWS/VERSION/BUILD`
+    ///
+    /// 路径相对于当前元素，以 `/` 分隔，元素名区分大小写（不带前缀的名称匹配任意命名空间前缀）；`LANGUAGE[2]` 表示第二个同名子元素
+    /// （从 1 开始，与 XPath 相同）。
+    pub fn get(&self, path: &str) -> Option<&XmlElement> {
+        let mut element = self;
+        for segment in path.split('/').filter(|segment| !segment.is_empty()) {
+            let (name, position) = match segment.strip_suffix(']').and_then(|s| s.split_once('[')) {
+                Some((name, position)) => (name, position.parse::<usize>().ok()?.checked_sub(1)?),
+                None => (segment, 0),
+            };
+            element = element
+                .children
+                .iter()
+                .filter(|child| name_matches(&child.name, name))
+                .nth(position)?;
+        }
+        Some(element)
+    }
+
+    /// 按路径取文本值；最后一段为 `@名称` 时取属性值，例如 `WINDOWS/LANGUAGES/LANGUAGE[2]`、`@INDEX`
+    pub fn get_text(&self, path: &str) -> Option<&str> {
+        match path.rsplit_once('/') {
+            Some((parent, attribute)) if attribute.starts_with('@') => {
+                self.get(parent)?.attribute(&attribute[1..])
+            }
+            _ => match path.strip_prefix('@') {
+                Some(attribute) => self.attribute(attribute),
+                None => self.get(path).map(|element| element.text.as_str()),
+            },
+        }
+    }
+}
+
+/// 去掉命名空间前缀（`wim:IMAGE` → `IMAGE`）
+pub(crate) fn local_name(name: &str) -> &str {
+    name.rsplit_once(':').map_or(name, |(_, local)| local)
+}
+
+/// 名称是否匹配：完全相同，或查询不带前缀且与本地名相同
+fn name_matches(name: &str, query: &str) -> bool {
+    name == query
+        || (!query.contains(':') && !name.starts_with("xmlns:") && local_name(name) == query)
+}
+
+/// 元素嵌套深度上限，防止恶意输入导致栈溢出
+const MAX_DEPTH: usize = 64;
+
+struct Parser<'a> {
+    rest: &'a str,
+}
+
+impl<'a> Parser<'a> {
+    /// 跳过空白、XML 声明、处理指令、注释和 DOCTYPE
+    fn skip_misc(&mut self) {
+        loop {
+            self.rest = self.rest.trim_start();
+            let end = if self.rest.starts_with("<?") {
+                self.rest.find("?>").map(|end| end + 2)
+            } else if self.rest.starts_with("<!--") {
+                self.rest.find("-->").map(|end| end + 3)
+            } else if self.rest.starts_with("<!") && !self.rest.starts_with("<![CDATA[") {
+                self.rest.find('>').map(|end| end + 1)
+            } else {
+                return;
+            };
+            match end {
+                Some(end) => self.rest = &self.rest[end..],
+                None => {
+                    self.rest = "";
+                    return;
+                }
+            }
+        }
+    }
+
+    fn name(&mut self) -> Option<&'a str> {
+        let end = self
+            .rest
+            .find(|c: char| c.is_whitespace() || matches!(c, '>' | '/' | '='))
+            .unwrap_or(self.rest.len());
+        let (name, rest) = self.rest.split_at(end);
+        self.rest = rest;
+        (!name.is_empty()).then_some(name)
+    }
+
+    fn element(&mut self, depth: usize) -> Option<XmlElement> {
+        if depth >= MAX_DEPTH {
+            return None;
+        }
+        self.rest = self.rest.strip_prefix('<')?;
+        let mut element = XmlElement {
+            name: self.name()?.to_string(),
+            ..XmlElement::default()
+        };
+
+        // 属性
+        loop {
+            self.rest = self.rest.trim_start();
+            if let Some(rest) = self.rest.strip_prefix("/>") {
+                self.rest = rest;
+                return Some(element);
+            }
+            if let Some(rest) = self.rest.strip_prefix('>') {
+                self.rest = rest;
+                break;
+            }
+            let key = self.name()?.to_string();
+            self.rest = self.rest.trim_start().strip_prefix('=')?.trim_start();
+            let quote = self
+                .rest
+                .chars()
+                .next()
+                .filter(|c| matches!(c, '"' | '\''))?;
+            let end = self.rest[1..].find(quote)? + 1;
+            element
+                .attributes
+                .push((key, decode_entities(&self.rest[1..end])));
+            self.rest = &self.rest[end + 1..];
+        }
+
+        // 内容
+        let mut text = String::new();
+        loop {
+            let end = self.rest.find('<')?;
+            text.push_str(&decode_entities(&self.rest[..end]));
+            self.rest = &self.rest[end..];
+
+            if let Some(rest) = self.rest.strip_prefix("</") {
+                self.rest = rest;
+                if self.name()? != element.name {
+                    return None;
+                }
+                self.rest = self.rest.trim_start().strip_prefix('>')?;
+                element.text = text.trim().to_string();
+                return Some(element);
+            }
+            if let Some(rest) = self.rest.strip_prefix("<![CDATA[") {
+                let end = rest.find("]]>")?;
+                text.push_str(&rest[..end]);
+                self.rest = &rest[end + 3..];
+                continue;
+            }
+            if self.rest.starts_with("<?") || self.rest.starts_with("<!") {
+                self.skip_misc();
+                continue;
+            }
+            element.children.push(self.element(depth + 1)?);
+        }
+    }
+}
+
+/// 转义文本和属性值中的 `&<>"'`，丢弃 XML 1.0 不允许出现的控制字符（制表符、换行和回车除外）
+#[cfg(feature = "parser")]
+pub(crate) fn escape_xml(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            '\t' | '\n' | '\r' => out.push(c),
+            '\0'..='\x1F' | '\u{FFFE}' | '\u{FFFF}' => {}
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// 解码预定义实体和数字字符引用，无法识别的实体保持原样
+fn decode_entities(text: &str) -> String {
+    if !text.contains('&') {
+        return text.to_string();
+    }
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        out.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let decoded = rest.find(';').and_then(|end| {
+            let entity = &rest[1..end];
+            let c = match entity {
+                "amp" => '&',
+                "lt" => '<',
+                "gt" => '>',
+                "quot" => '"',
+                "apos" => '\'',
+                _ => {
+                    let code = match entity.strip_prefix("#x").or(entity.strip_prefix("#X")) {
+                        Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+                        None => entity.strip_prefix('#')?.parse().ok()?,
+                    };
+                    char::from_u32(code)?
+                }
+            };
+            Some((c, end + 1))
+        });
+        match decoded {
+            Some((c, len)) => {
+                out.push(c);
+                rest = &rest[len..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
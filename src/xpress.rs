@@ -0,0 +1,139 @@
+//! XPRESS（LZ77 + 哈夫曼）分块解压，用于 `/compress:fast` 创建的 WIM
+//!
+//! WIM 中每个分块独立压缩：开头 256 字节是 512 个符号的 4 位码长（低 4 位在前），
+//! 随后是以 16 位小端字为单位、从高位开始读取的比特流。解码器始终预读两个字，
+//! 匹配长度的扩展字节直接取自已预读的字之后，与 \[MS-XCA\] 2.2.4 的解压算法一致。
+
+use crate::huffman::HuffmanTable;
+use crate::Error;
+
+/// 符号数：256 个字面量加 256 个匹配头（偏移位数 × 16 + 长度头）
+const NUM_SYMBOLS: usize = 512;
+/// 码长表的字节数
+const TABLE_BYTES: usize = NUM_SYMBOLS / 2;
+/// 码字的最大长度
+const MAX_CODEWORD_LEN: u32 = 15;
+/// 最短匹配长度
+const MIN_MATCH_LEN: usize = 3;
+
+/// 比特流读取器：`bits` 中左对齐保存 `count` 个有效位，读取后保证至少 16 位有效
+struct BitReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+    bits: u32,
+    count: u32,
+}
+
+impl<'a> BitReader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        let mut reader = Self {
+            data,
+            pos: 0,
+            bits: 0,
+            count: 0,
+        };
+        reader.bits = (reader.next_word() << 16) | reader.next_word();
+        reader.count = 32;
+        reader
+    }
+
+    /// 下一个 16 位字（超出输入时按 0 补齐，由解压长度决定何时结束）
+    fn next_word(&mut self) -> u32 {
+        let word = match self.data.get(self.pos..self.pos + 2) {
+            Some(bytes) => u32::from(u16::from_le_bytes([bytes[0], bytes[1]])),
+            None => 0,
+        };
+        self.pos += 2;
+        word
+    }
+
+    /// 查看接下来的 `n` 位（`n` ≤ 16）
+    fn peek(&self, n: u32) -> u32 {
+        if n == 0 {
+            0
+        } else {
+            self.bits >> (32 - n)
+        }
+    }
+
+    /// 丢弃 `n` 位（`n` ≤ 15），有效位不足 16 时补充一个字
+    fn consume(&mut self, n: u32) {
+        self.bits <<= n;
+        self.count -= n;
+        if self.count < 16 {
+            self.bits |= self.next_word() << (16 - self.count);
+            self.count += 16;
+        }
+    }
+
+    /// 读取位于比特流字之间的一个字节
+    fn read_byte(&mut self) -> Result<u8, Error> {
+        let byte = *self.data.get(self.pos).ok_or(Error::Truncated {
+            expected: self.pos + 1,
+            actual: self.data.len(),
+        })?;
+        self.pos += 1;
+        Ok(byte)
+    }
+
+    /// 读取位于比特流字之间的 16 位小端整数
+    fn read_u16(&mut self) -> Result<u16, Error> {
+        Ok(u16::from_le_bytes([self.read_byte()?, self.read_byte()?]))
+    }
+}
+
+/// 解压一个 XPRESS 分块，`size` 为分块的未压缩大小
+pub(crate) fn decompress(input: &[u8], size: usize) -> Result<Vec<u8>, Error> {
+    let table = input.get(..TABLE_BYTES).ok_or(Error::Truncated {
+        expected: TABLE_BYTES,
+        actual: input.len(),
+    })?;
+    let lengths: Vec<u8> = table
+        .iter()
+        .flat_map(|&byte| [byte & 0x0F, byte >> 4])
+        .collect();
+    let table = HuffmanTable::new(&lengths, MAX_CODEWORD_LEN)
+        .ok_or(Error::CorruptData("XPRESS 哈夫曼码长无效"))?;
+
+    let mut bits = BitReader::new(&input[TABLE_BYTES..]);
+    let mut out = Vec::with_capacity(size);
+    while out.len() < size {
+        let (symbol, len) = table
+            .decode(bits.peek(table.max_len()))
+            .ok_or(Error::CorruptData("XPRESS 哈夫曼码字无效"))?;
+        bits.consume(len);
+        if symbol < 256 {
+            out.push(symbol as u8);
+            continue;
+        }
+
+        let header = usize::from(symbol - 256);
+        let offset_bits = (header >> 4) as u32;
+        let mut length = header & 0x0F;
+        if length == 0x0F {
+            length += usize::from(bits.read_byte()?);
+            if length == 0x0F + 0xFF {
+                length = usize::from(bits.read_u16()?);
+                if length < 0x0F {
+                    return Err(Error::CorruptData("XPRESS 匹配长度无效"));
+                }
+            }
+        }
+        length += MIN_MATCH_LEN;
+        let offset = (1usize << offset_bits) | bits.peek(offset_bits) as usize;
+        bits.consume(offset_bits);
+
+        if offset > out.len() {
+            return Err(Error::CorruptData("XPRESS 匹配偏移超出已解压的数据"));
+        }
+        if length > size - out.len() {
+            return Err(Error::CorruptData("XPRESS 匹配超出分块大小"));
+        }
+        let start = out.len() - offset;
+        for i in 0..length {
+            let byte = out[start + i];
+            out.push(byte);
+        }
+    }
+    Ok(out)
+}
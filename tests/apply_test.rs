@@ -0,0 +1,253 @@
+#![cfg(feature = "parser")]
+
+mod common;
+
+use common::{write_wim, ImageSpec};
+use wim_parser::{ApplyOptions, ConflictAction, ConflictPolicy, UnsupportedFeature, WimParser};
+
+/// 测试释放到空目录的预演
+#[test]
+fn test_plan_apply_empty_target() {
+    let wim = write_wim(&[ImageSpec::new("Image A")
+        .dir("/Windows/System32")
+        .file("/Windows/System32/a.dll", b"hello")
+        .file("/readme.txt", b"world!")]);
+    let target = tempfile::tempdir().unwrap();
+
+    let mut parser = WimParser::new(wim.path()).unwrap();
+    let plan = parser.plan_apply(1, target.path().join("new")).unwrap();
+
+    assert_eq!(plan.dir_count, 2);
+    assert_eq!(plan.file_count, 2);
+    assert_eq!(plan.total_bytes, 11);
+    assert!(plan.is_clean());
+    assert!(!target.path().join("new").exists());
+}
+
+/// 测试冲突、重解析点和 ACL 检测
+#[test]
+fn test_plan_apply_conflicts_and_unsupported() {
+    let wim = write_wim(&[ImageSpec::new("Image A")
+        .dir("/Windows")
+        .file("/Windows/a.txt", b"hello")
+        .file("/b.txt", b"world")
+        .reparse("/Windows/dedup.dat", 0x8000_0013)
+        .reparse("/link", 0xA000_000C)
+        .secured()]);
+    let target = tempfile::tempdir().unwrap();
+    std::fs::create_dir(target.path().join("Windows")).unwrap();
+    std::fs::write(target.path().join("Windows/a.txt"), b"old").unwrap();
+    std::fs::create_dir(target.path().join("b.txt")).unwrap();
+
+    let mut parser = WimParser::new(wim.path()).unwrap();
+    let plan = parser.plan_apply(1, target.path()).unwrap();
+
+    let conflicts: Vec<(&str, bool)> = plan
+        .conflicts
+        .iter()
+        .map(|c| (c.path.as_str(), c.existing_is_dir))
+        .collect();
+    assert_eq!(conflicts, [("Windows/a.txt", false), ("b.txt", true)]);
+
+    assert_eq!(plan.unsupported.len(), 1);
+    assert_eq!(plan.unsupported[0].path, "Windows/dedup.dat");
+    assert_eq!(
+        plan.unsupported[0].feature,
+        UnsupportedFeature::ReparsePoint(0x8000_0013)
+    );
+
+    let expected_acl = if cfg!(windows) { 0 } else { 5 };
+    assert_eq!(plan.acl_entries, expected_acl);
+    assert!(plan.missing_streams.is_empty());
+    assert!(!plan.is_clean());
+}
+
+/// 测试冲突策略和逐项回调
+#[test]
+fn test_conflict_policies() {
+    // 2100-01-01 的 FILETIME，比任何已存在文件都新
+    const FUTURE: u64 = (4_102_444_800 + 11_644_473_600) * 10_000_000;
+
+    let newer = write_wim(&[ImageSpec::new("Image A")
+        .file("/a.txt", b"hello")
+        .file("/b.txt", b"world")
+        .write_time(FUTURE)]);
+    let older = write_wim(&[ImageSpec::new("Image A")
+        .file("/a.txt", b"hello")
+        .file("/b.txt", b"world")]);
+
+    let target = tempfile::tempdir().unwrap();
+    std::fs::write(target.path().join("a.txt"), b"old").unwrap();
+    std::fs::write(target.path().join("b.txt"), b"old").unwrap();
+
+    let actions = |wim: &tempfile::NamedTempFile, options: &ApplyOptions| {
+        let mut parser = WimParser::new(wim.path()).unwrap();
+        let plan = parser.plan_apply_with(1, target.path(), options).unwrap();
+        plan.conflicts.iter().map(|c| c.action).collect::<Vec<_>>()
+    };
+
+    let mut parser = WimParser::new(newer.path()).unwrap();
+    assert!(parser
+        .plan_apply(1, target.path())
+        .unwrap()
+        .has_fatal_conflicts());
+
+    let skip = ApplyOptions::new().conflict_policy(ConflictPolicy::Skip);
+    assert_eq!(actions(&newer, &skip), [ConflictAction::Skip; 2]);
+
+    let overwrite = ApplyOptions::new().conflict_policy(ConflictPolicy::Overwrite);
+    assert_eq!(actions(&older, &overwrite), [ConflictAction::Overwrite; 2]);
+
+    let if_newer = ApplyOptions::new().conflict_policy(ConflictPolicy::OverwriteIfNewer);
+    assert_eq!(actions(&newer, &if_newer), [ConflictAction::Overwrite; 2]);
+    assert_eq!(actions(&older, &if_newer), [ConflictAction::Skip; 2]);
+
+    let custom = ApplyOptions::new()
+        .conflict_policy(ConflictPolicy::Skip)
+        .on_conflict(|conflict| (conflict.path == "b.txt").then_some(ConflictAction::Fail));
+    assert_eq!(
+        actions(&older, &custom),
+        [ConflictAction::Skip, ConflictAction::Fail]
+    );
+}
+
+/// 测试按属性、大小和扩展名过滤释放内容
+#[test]
+fn test_apply_filters() {
+    const FILE_ATTRIBUTE_HIDDEN: u32 = 0x2;
+    const FILE_ATTRIBUTE_SYSTEM: u32 = 0x4;
+
+    let wim = write_wim(&[ImageSpec::new("Image")
+        .file("/Windows/System32/drivers/disk.sys", b"driver")
+        .file("/Windows/System32/drivers/etc/hosts", b"127.0.0.1")
+        .file("/Windows/System32/config/SYSTEM", b"regf....")
+        .file("/Windows/System32/kernel32.DLL", b"dll")
+        .file("/Windows/big.cab", &[0u8; 64])
+        .file("/$Recycle.Bin/deleted.sys", b"old")
+        .file("/pagefile.sys", b"page")
+        .attributes(
+            "/$Recycle.Bin",
+            FILE_ATTRIBUTE_HIDDEN | FILE_ATTRIBUTE_SYSTEM,
+        )
+        .attributes("/pagefile.sys", FILE_ATTRIBUTE_HIDDEN)]);
+    let target = tempfile::tempdir().unwrap();
+    let mut parser = WimParser::new(wim.path()).unwrap();
+
+    let plan = parser.plan_apply(1, target.path()).unwrap();
+    assert_eq!(plan.file_count, 7);
+    assert_eq!(plan.filtered_count, 0);
+
+    // 只释放驱动和注册表配置单元，跳过隐藏和系统项
+    let options = ApplyOptions::new()
+        .skip_hidden(true)
+        .skip_system(true)
+        .include_extensions([".sys", ""]);
+    let plan = parser.plan_apply_with(1, target.path(), &options).unwrap();
+    assert_eq!(plan.file_count, 3, "disk.sys, hosts, SYSTEM");
+    assert_eq!(plan.total_bytes, 6 + 9 + 8);
+    // kernel32.DLL, big.cab, $Recycle.Bin 及其内容, pagefile.sys
+    assert_eq!(plan.filtered_count, 5);
+    assert_eq!(plan.filtered_bytes, 3 + 64 + 3 + 4);
+
+    let options = ApplyOptions::new()
+        .max_file_size(8)
+        .exclude_extensions(["DLL"]);
+    let plan = parser.plan_apply_with(1, target.path(), &options).unwrap();
+    assert_eq!(
+        plan.file_count, 4,
+        "disk.sys, SYSTEM, deleted.sys, pagefile.sys"
+    );
+    assert!(options.selects("a/b.txt", 0, false, 8));
+    assert!(!options.selects("a/b.txt", 0, false, 9));
+    assert!(!options.selects("a/b.dll", 0, false, 1));
+    assert!(ApplyOptions::new()
+        .min_file_size(4)
+        .selects("dir", 0x10, true, 0));
+}
+
+fn deployment_image() -> ImageSpec {
+    ImageSpec::new("Image A")
+        .dir("/Windows/System32")
+        .file("/Windows/System32/a.dll", b"hello")
+        .file("/Windows/win.ini", b"[fonts]")
+        .file("/readme.txt", b"world!")
+        // 2021-01-01 00:00:00 UTC
+        .write_time(132_539_328_000_000_000)
+}
+
+/// 测试将镜像释放到新目录：目录结构、文件内容和时间
+#[test]
+fn test_apply_image() {
+    let wim = write_wim(&[deployment_image()]);
+    let out = tempfile::tempdir().unwrap();
+    let target = out.path().join("deploy");
+    let mut parser = WimParser::new(wim.path()).unwrap();
+
+    let report = parser
+        .apply_image(1, &target, &ApplyOptions::new())
+        .unwrap();
+    assert_eq!(report.dir_count, 2);
+    assert_eq!(report.file_count, 3);
+    assert_eq!(report.total_bytes, 18);
+    assert_eq!(
+        std::fs::read(target.join("Windows/System32/a.dll")).unwrap(),
+        b"hello"
+    );
+    assert_eq!(std::fs::read(target.join("readme.txt")).unwrap(), b"world!");
+    let expected = std::time::UNIX_EPOCH + std::time::Duration::from_secs(1_609_459_200);
+    for path in ["Windows/win.ini", "Windows"] {
+        let modified = std::fs::metadata(target.join(path))
+            .unwrap()
+            .modified()
+            .unwrap();
+        assert_eq!(modified, expected);
+    }
+}
+
+/// 测试释放到已有内容的目录：默认报错且不写入，跳过保留已有内容，覆盖替换文件和类型不同的项
+#[test]
+fn test_apply_image_conflicts() {
+    let wim = write_wim(&[deployment_image()]);
+    let out = tempfile::tempdir().unwrap();
+    let target = out.path();
+    std::fs::create_dir_all(target.join("Windows/win.ini")).unwrap();
+    std::fs::write(target.join("Windows/win.ini/old.txt"), b"old").unwrap();
+    std::fs::write(target.join("readme.txt"), b"local").unwrap();
+    let mut permissions = std::fs::metadata(target.join("readme.txt"))
+        .unwrap()
+        .permissions();
+    permissions.set_readonly(true);
+    std::fs::set_permissions(target.join("readme.txt"), permissions).unwrap();
+    let mut parser = WimParser::new(wim.path()).unwrap();
+
+    let err = parser
+        .apply_image(1, target, &ApplyOptions::new())
+        .unwrap_err();
+    assert!(err.to_string().contains("共 2 个冲突"));
+    assert!(!target.join("Windows/System32").exists());
+
+    let skip = ApplyOptions::new().conflict_policy(ConflictPolicy::Skip);
+    let report = parser.apply_image(1, target, &skip).unwrap();
+    assert_eq!(report.filtered_count, 2);
+    assert_eq!(report.file_count, 1);
+    assert_eq!(std::fs::read(target.join("readme.txt")).unwrap(), b"local");
+    assert!(target.join("Windows/win.ini/old.txt").exists());
+    assert!(target.join("Windows/System32/a.dll").exists());
+
+    // 已释放的 a.dll 也成为冲突，回调对它保持跳过
+    let overwrite = ApplyOptions::new()
+        .conflict_policy(ConflictPolicy::Overwrite)
+        .on_conflict(|conflict| {
+            conflict
+                .path
+                .ends_with(".dll")
+                .then_some(ConflictAction::Skip)
+        });
+    let report = parser.apply_image(1, target, &overwrite).unwrap();
+    assert_eq!(report.file_count, 2);
+    assert_eq!(std::fs::read(target.join("readme.txt")).unwrap(), b"world!");
+    assert_eq!(
+        std::fs::read(target.join("Windows/win.ini")).unwrap(),
+        b"[fonts]"
+    );
+}
@@ -0,0 +1,94 @@
+#![cfg(feature = "parser")]
+
+mod common;
+
+use common::{write_wim, ImageSpec};
+use wim_parser::fmt::ToTable;
+use wim_parser::{AppxPackage, WimParser};
+
+const APPS: &str = "/Program Files/WindowsApps";
+const CALCULATOR: &str = "Microsoft.WindowsCalculator_10.1906.55.0_x64__8wekyb3d8bbwe";
+const CALCULATOR_OLD: &str = "Microsoft.WindowsCalculator_10.902.0.0_x64__8wekyb3d8bbwe";
+const CALCULATOR_SCALE: &str =
+    "Microsoft.WindowsCalculator_10.1906.55.0_neutral_split.scale-100_8wekyb3d8bbwe";
+const ZUNE_BUNDLE: &str = "Microsoft.ZuneMusic_2019.19071.19011.0_neutral_~_8wekyb3d8bbwe";
+
+fn utf16_with_bom(text: &str) -> Vec<u8> {
+    let mut data = vec![0xFF, 0xFE];
+    data.extend(text.encode_utf16().flat_map(|u| u.to_le_bytes()));
+    data
+}
+
+/// 测试列出预装应用及预配状态
+#[test]
+fn test_list_provisioned_appx() {
+    let provisioning = "<AppxProvisioning><Package><PackageFamilyName>\
+                        Microsoft.WindowsCalculator_8wekyb3d8bbwe\
+                        </PackageFamilyName></Package></AppxProvisioning>";
+    let specs = [
+        ImageSpec::new("Before")
+            .file(
+                &format!("{APPS}/{CALCULATOR}/AppxManifest.xml"),
+                b"<Package/>",
+            )
+            .file(
+                &format!("{APPS}/{CALCULATOR_OLD}/AppxManifest.xml"),
+                b"<Package/>",
+            )
+            .dir(&format!("{APPS}/{CALCULATOR_SCALE}"))
+            .dir(&format!("{APPS}/{ZUNE_BUNDLE}"))
+            .dir(&format!("{APPS}/Deleted"))
+            .dir(&format!("{APPS}/MutableBackup"))
+            .file(
+                "/ProgramData/Microsoft/Windows/AppxProvisioning.xml",
+                &utf16_with_bom(provisioning),
+            ),
+        ImageSpec::new("After").file(
+            &format!("{APPS}/{CALCULATOR}/AppxManifest.xml"),
+            b"<Package/>",
+        ),
+        ImageSpec::new("Empty"),
+    ];
+    let wim = write_wim(&specs);
+    let mut parser = WimParser::new(wim.path()).unwrap();
+
+    let before = parser.list_provisioned_appx(1).unwrap();
+    let names: Vec<&str> = before.iter().map(|p| p.full_name.as_str()).collect();
+    assert_eq!(
+        names,
+        [CALCULATOR_OLD, CALCULATOR, CALCULATOR_SCALE, ZUNE_BUNDLE]
+    );
+    assert!(before[0].has_manifest);
+    assert_eq!(before[0].provisioned, Some(true));
+    assert!(before[2].is_resource());
+    assert!(!before[2].has_manifest);
+    assert!(before[3].is_bundle());
+    assert_eq!(before[3].provisioned, Some(false));
+    assert_eq!(before.table().rows()[3][3], "捆绑包");
+
+    let after = parser.list_provisioned_appx(2).unwrap();
+    assert_eq!(after.len(), 1);
+    assert_eq!(after[0].provisioned, None);
+    let removed: Vec<&AppxPackage> = before.iter().filter(|p| !after.contains(p)).collect();
+    assert_eq!(removed.len(), 4);
+
+    assert!(parser.list_provisioned_appx(3).unwrap().is_empty());
+}
+
+/// 测试包完整名称解析
+#[test]
+fn test_package_full_name() {
+    let package = AppxPackage::from_full_name(CALCULATOR).unwrap();
+    assert_eq!(package.name, "Microsoft.WindowsCalculator");
+    assert_eq!(package.version, "10.1906.55.0");
+    assert_eq!(package.architecture, "x64");
+    assert_eq!(package.resource_id, "");
+    assert_eq!(
+        package.family_name(),
+        "Microsoft.WindowsCalculator_8wekyb3d8bbwe"
+    );
+    assert!(!package.is_bundle() && !package.is_resource());
+
+    assert!(AppxPackage::from_full_name("Deleted").is_none());
+    assert!(AppxPackage::from_full_name("A_1.0_x64__pub").is_none());
+}
@@ -0,0 +1,127 @@
+#![cfg(feature = "parser")]
+
+mod common;
+
+use common::{build_wim, write_bytes, write_wim, ImageSpec};
+use wim_parser::{Arch, BootIssue, WimParser};
+
+fn boot_wim_specs() -> Vec<ImageSpec> {
+    vec![
+        ImageSpec::new("Microsoft Windows PE (x64)"),
+        ImageSpec::new("Microsoft Windows Setup (x64)")
+            .file("/Windows/Boot/PXE/bootmgr.exe", b"bootmgr")
+            .file("/Windows/Boot/DVD/PCAT/BCD", b"bcd")
+            .file("/Windows/Boot/DVD/PCAT/boot.sdi", b"sdi"),
+    ]
+}
+
+/// 测试设置了可引导镜像的 boot.wim
+#[test]
+fn test_wimboot_info_bootable() {
+    let mut bytes = build_wim(&boot_wim_specs());
+    bytes[120..124].copy_from_slice(&2u32.to_le_bytes());
+    let wim = write_bytes(&bytes);
+
+    let mut parser = WimParser::new(wim.path()).unwrap();
+    let info = parser.wimboot_info().unwrap();
+
+    assert_eq!(info.bootable_index, 2);
+    assert_eq!(info.boot_image, Some(2));
+    assert!(!info.has_boot_metadata);
+    assert!(info.is_bootable());
+
+    let missing: Vec<&str> = info.missing_files().map(|file| file.path).collect();
+    assert_eq!(
+        missing,
+        [
+            r"\Windows\Boot\EFI\bootmgfw.efi",
+            r"\Windows\Boot\DVD\EFI\BCD"
+        ]
+    );
+}
+
+/// 测试未设置可引导镜像时检查最后一个镜像
+#[test]
+fn test_wimboot_info_not_bootable() {
+    let wim = write_wim(&boot_wim_specs());
+    let mut parser = WimParser::new(wim.path()).unwrap();
+    let info = parser.wimboot_info().unwrap();
+
+    assert_eq!(info.bootable_index, 0);
+    assert_eq!(info.boot_image, Some(2));
+    assert!(!info.is_bootable());
+    assert_eq!(info.missing_files().count(), 2);
+
+    let wim = write_wim(&[ImageSpec::new("Data")]);
+    let mut parser = WimParser::new(wim.path()).unwrap();
+    let info = parser.wimboot_info().unwrap();
+    assert_eq!(info.missing_files().count(), 5);
+}
+
+/// 构造只包含 PE 文件头的 EFI 文件
+fn fake_pe(machine: u16) -> Vec<u8> {
+    let mut image = vec![0u8; 0x90];
+    image[0..2].copy_from_slice(b"MZ");
+    image[0x3C..0x40].copy_from_slice(&0x80u32.to_le_bytes());
+    image[0x80..0x84].copy_from_slice(b"PE\0\0");
+    image[0x84..0x86].copy_from_slice(&machine.to_le_bytes());
+    image
+}
+
+fn setup_image(arch: u32, winload_machine: u16) -> ImageSpec {
+    ImageSpec::new("Microsoft Windows Setup")
+        .extra_xml(&format!("<WINDOWS><ARCH>{arch}</ARCH></WINDOWS>"))
+        .file(
+            "/Windows/System32/boot/winload.efi",
+            &fake_pe(winload_machine),
+        )
+        .file("/Windows/System32/startnet.cmd", b"wpeinit")
+}
+
+/// 测试 boot.wim 启动关键文件校验
+#[test]
+fn test_validate_boot_wim() {
+    let mut bytes = build_wim(&[ImageSpec::new("Windows PE"), setup_image(9, 0x8664)]);
+    bytes[120..124].copy_from_slice(&2u32.to_le_bytes());
+    let wim = write_bytes(&bytes);
+    let mut parser = WimParser::new(wim.path()).unwrap();
+    let validation = parser.validate_boot_wim().unwrap();
+    assert!(validation.is_valid(), "{:?}", validation.issues);
+    assert_eq!(validation.boot_image, Some(2));
+    assert!(validation.has_winload_efi);
+    assert!(validation.has_startnet_cmd);
+    assert!(!validation.has_winpeshl_ini);
+    assert_eq!(validation.loader_arch, Some(Arch::X64));
+
+    // 架构不一致且未设置可引导镜像
+    let wim = write_wim(&[setup_image(12, 0x8664)]);
+    let mut parser = WimParser::new(wim.path()).unwrap();
+    let validation = parser.validate_boot_wim().unwrap();
+    assert_eq!(
+        validation.issues,
+        [
+            BootIssue::NoBootableIndex,
+            BootIssue::ArchMismatch {
+                image: Arch::Arm64,
+                loader: Arch::X64
+            }
+        ]
+    );
+
+    // 缺少启动文件
+    let wim = write_wim(&[ImageSpec::new("Data")]);
+    let mut parser = WimParser::new(wim.path()).unwrap();
+    let validation = parser.validate_boot_wim().unwrap();
+    assert_eq!(
+        validation.issues,
+        [
+            BootIssue::NoBootableIndex,
+            BootIssue::MissingFile(r"\Windows\System32\boot\winload.efi"),
+            BootIssue::MissingShellLauncher
+        ]
+    );
+    assert_eq!(
+        validation.issues[2].to_string(),
+        "缺少 winpeshl.ini 和 startnet.cmd"
+    );
+}
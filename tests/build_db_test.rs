@@ -0,0 +1,90 @@
+#![cfg(feature = "build-db")]
+
+use wim_parser::{lookup_build, BuildDatabase, BuildDate, ImageInfo};
+
+fn date(year: u16, month: u8, day: u8) -> Option<BuildDate> {
+    Some(BuildDate { year, month, day })
+}
+
+/// 测试内置数据库查询
+#[test]
+fn test_lookup_builtin_build() {
+    let info = lookup_build(22631).unwrap();
+    assert_eq!(info.display_name(), "Windows 11 23H2");
+    assert_eq!(info.released, date(2023, 10, 31));
+    assert!(info.has_edition("professional"));
+    assert_eq!(info.end_of_support_for("Professional"), date(2025, 11, 11));
+    assert_eq!(info.end_of_support_for("Enterprise"), date(2026, 11, 10));
+    assert_eq!(info.released.unwrap().to_string(), "2023-10-31");
+    assert!(lookup_build(12345).is_none());
+
+    let database = BuildDatabase::builtin();
+    let ltsc = database
+        .lookup_edition(19044, Some(1288), "EnterpriseS")
+        .unwrap();
+    assert_eq!(ltsc.product, "Windows 10 Enterprise LTSC 2021");
+    assert_eq!(ltsc.end_of_support_for("EnterpriseS"), date(2027, 1, 12));
+
+    let mut image = ImageInfo::new_with_index(1);
+    image.build = Some(20348);
+    image.sp_build = Some(587);
+    image.installation_type = Some("Server Core".to_string());
+    assert_eq!(
+        database.lookup_image(&image).unwrap().product,
+        "Windows Server 2022"
+    );
+    image.build = Some(26100);
+    assert_eq!(
+        database.lookup_image(&image).unwrap().product,
+        "Windows Server 2025"
+    );
+    image.edition_id = Some("IoTEnterpriseS".to_string());
+    assert_eq!(
+        database.lookup_image(&image).unwrap().product,
+        "Windows 11 IoT Enterprise LTSC 2024"
+    );
+}
+
+/// 测试加载数据文件并合并到内置数据库
+#[test]
+fn test_load_and_merge_data_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("builds.txt");
+    std::fs::write(
+        &path,
+        "# 本地更新\n\
+         26100|0|Windows 11|24H2|2024-10-01|2026-10-13|2027-10-12|Professional\n\
+         26100|4000|Windows 11|24H2 (4000+)|2025-05-01|-|-|Professional\n\
+         28000|0|Windows 11|26H1|-|-|-|Core, Professional\n",
+    )
+    .unwrap();
+    let update = BuildDatabase::load(&path).unwrap();
+    assert_eq!(update.entries().len(), 3);
+
+    let mut database = BuildDatabase::builtin();
+    let count = database.entries().len();
+    database.merge(update);
+    assert_eq!(database.entries().len(), count + 2);
+
+    let info = database.lookup(28000, None).unwrap();
+    assert_eq!(info.release, "26H1");
+    assert_eq!(info.released, None);
+    assert_eq!(info.editions, ["Core", "Professional"]);
+    let replaced = database.lookup(26100, Some(3000)).unwrap();
+    assert_eq!(replaced.editions, ["Professional"]);
+    assert_eq!(
+        database.lookup(26100, Some(4460)).unwrap().release,
+        "24H2 (4000+)"
+    );
+    assert_eq!(
+        database
+            .lookup_edition(26100, Some(4460), "EnterpriseS")
+            .unwrap()
+            .product,
+        "Windows 11 Enterprise LTSC 2024"
+    );
+
+    let err = BuildDatabase::parse("22631|0|Windows 11|23H2|2023-13-01|-|-|Core").unwrap_err();
+    assert!(err.to_string().contains("第 1 行"));
+    assert!(BuildDatabase::load(dir.path().join("missing.txt")).is_err());
+}
@@ -0,0 +1,121 @@
+#![cfg(feature = "parser")]
+
+mod common;
+
+use common::{build_wim, write_bytes, write_wim, ImageSpec};
+use std::fs::{File, FileTimes};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use wim_parser::{ParseOptions, WimCatalogCache, WimParser};
+
+fn set_mtime(path: &std::path::Path, mtime: SystemTime) {
+    let file = File::options().write(true).open(path).unwrap();
+    file.set_times(FileTimes::new().set_modified(mtime))
+        .unwrap();
+}
+
+/// 测试多个解析器共享缓存
+#[test]
+fn test_cache_shared_between_parsers() {
+    let wim = write_wim(&[ImageSpec::new("Windows 11 Pro").file("a.txt", b"abc")]);
+    let cache = Arc::new(WimCatalogCache::new());
+
+    let mut first = WimParser::with_cache(wim.path(), cache.clone()).unwrap();
+    first.parse_full().unwrap();
+    assert_eq!(cache.stats().misses, 1);
+    assert_eq!(cache.len(), 1);
+
+    // 相同 GUID 和修改时间的副本直接命中缓存
+    let copy = tempfile::NamedTempFile::new().unwrap();
+    std::fs::copy(wim.path(), copy.path()).unwrap();
+    let mtime = std::fs::metadata(wim.path()).unwrap().modified().unwrap();
+    set_mtime(copy.path(), mtime);
+
+    let mut second = WimParser::with_cache(copy.path(), cache.clone()).unwrap();
+    second.parse_full().unwrap();
+    assert_eq!(cache.stats().hits, 1);
+    assert_eq!(second.get_images().len(), 1);
+    assert_eq!(second.get_images()[0].name, "Windows 11 Pro");
+    assert_eq!(first.cache_key(), second.cache_key());
+
+    // 偏移表同样在解析器之间共享
+    first.recount_image(1, false).unwrap();
+    let hits = cache.stats().hits;
+    second.recount_image(1, false).unwrap();
+    assert_eq!(cache.stats().hits, hits + 1);
+}
+
+/// 测试修改时间变化后不会命中旧条目，以及容量淘汰
+#[test]
+fn test_cache_invalidation_and_eviction() {
+    let wim = write_wim(&[ImageSpec::new("Image A")]);
+    let cache = Arc::new(WimCatalogCache::with_max_entries(1));
+
+    let mut parser = WimParser::with_cache(wim.path(), cache.clone()).unwrap();
+    parser.parse_full().unwrap();
+    let old_key = parser.cache_key().unwrap();
+
+    let mtime = std::fs::metadata(wim.path()).unwrap().modified().unwrap();
+    set_mtime(wim.path(), mtime + Duration::from_secs(60));
+
+    let mut parser = WimParser::with_cache(wim.path(), cache.clone()).unwrap();
+    parser.parse_full().unwrap();
+    assert_eq!(cache.stats().hits, 0);
+    assert_eq!(cache.stats().misses, 2);
+
+    assert_eq!(cache.len(), 1, "超出容量时应淘汰最早的条目");
+    assert!(!cache.contains(&old_key));
+    assert!(cache.contains(&parser.cache_key().unwrap()));
+
+    cache.clear();
+    assert!(cache.is_empty());
+}
+
+/// 测试拆分 WIM 的分卷（GUID 和修改时间相同）不会互相命中缓存
+#[test]
+fn test_cache_key_distinguishes_split_parts() {
+    let mut part1 = build_wim(&[ImageSpec::new("Image A").file("a.txt", b"first part")]);
+    // 分卷总数为 2
+    part1[42..44].copy_from_slice(&2u16.to_le_bytes());
+    let mut part2 = part1.clone();
+    part2[40..42].copy_from_slice(&2u16.to_le_bytes());
+    let (part1, part2) = (write_bytes(&part1), write_bytes(&part2));
+    let mtime = std::fs::metadata(part1.path()).unwrap().modified().unwrap();
+    set_mtime(part2.path(), mtime);
+
+    let cache = Arc::new(WimCatalogCache::new());
+    let mut first = WimParser::with_cache(part1.path(), cache.clone()).unwrap();
+    first.parse_full().unwrap();
+    let mut second = WimParser::with_cache(part2.path(), cache.clone()).unwrap();
+    second.parse_full().unwrap();
+
+    let (key1, key2) = (first.cache_key().unwrap(), second.cache_key().unwrap());
+    assert_eq!((key1.guid, key1.mtime), (key2.guid, key2.mtime));
+    assert_eq!((key1.part_number, key2.part_number), (1, 2));
+    assert_ne!(key1, key2);
+    assert_eq!(cache.stats().hits, 0);
+    assert_eq!(cache.len(), 2);
+}
+
+/// 测试缓存命中时重放解析警告，严格模式照常报错
+#[test]
+fn test_cache_replays_warnings() {
+    let wim =
+        write_wim(&[ImageSpec::new("Image A").extra_xml("<WINDOWS><ARCH>99</ARCH></WINDOWS>")]);
+    let cache = Arc::new(WimCatalogCache::new());
+
+    let mut first = WimParser::with_cache(wim.path(), cache.clone()).unwrap();
+    first.parse_full().unwrap();
+    assert_eq!(first.warnings().len(), 1);
+
+    let mut second = WimParser::with_cache(wim.path(), cache.clone()).unwrap();
+    second.parse_full().unwrap();
+    assert_eq!(cache.stats().hits, 1);
+    assert_eq!(second.warnings(), first.warnings());
+
+    let mut strict = WimParser::with_options(wim.path(), ParseOptions::new().strict(true)).unwrap();
+    strict.set_cache(cache.clone());
+    let err = strict.parse_full().unwrap_err();
+    assert_eq!(cache.stats().hits, 2);
+    assert!(err.to_string().contains("架构数值 99"), "{err}");
+}
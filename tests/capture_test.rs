@@ -0,0 +1,110 @@
+#![cfg(feature = "verify")]
+
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+
+use wim_parser::{capture, capture_incremental, WimParser};
+
+/// 读取捕获结果中的文件内容
+fn read_file(wim: &Path, path: &str) -> Vec<u8> {
+    let mut parser = WimParser::new(wim).unwrap();
+    let mut tree = parser.open_lazy_tree(1).unwrap();
+    let mut data = Vec::new();
+    tree.extract_file(&mut parser, path, &mut data).unwrap();
+    data
+}
+
+/// 设置文件内容和最后写入时间
+fn write_file(path: &Path, data: &[u8], mtime: SystemTime) {
+    fs::write(path, data).unwrap();
+    fs::File::options()
+        .write(true)
+        .open(path)
+        .unwrap()
+        .set_modified(mtime)
+        .unwrap();
+}
+
+fn source_tree() -> (tempfile::TempDir, SystemTime) {
+    let source = tempfile::tempdir().unwrap();
+    let mtime = SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+    fs::create_dir_all(source.path().join("Windows/System32")).unwrap();
+    write_file(
+        &source.path().join("Windows/System32/a.dll"),
+        b"alpha",
+        mtime,
+    );
+    write_file(
+        &source.path().join("Windows/System32/b.dll"),
+        b"bravo",
+        mtime,
+    );
+    write_file(&source.path().join("readme.txt"), b"golden image", mtime);
+    write_file(&source.path().join("empty.txt"), b"", mtime);
+    (source, mtime)
+}
+
+/// 测试完整捕获目录，文件内容和最后写入时间写入镜像
+#[test]
+fn test_capture_directory() {
+    let (source, mtime) = source_tree();
+    let out = tempfile::tempdir().unwrap();
+    let wim = out.path().join("golden.wim");
+
+    let report = capture(source.path(), &wim).unwrap();
+    assert_eq!(report.file_count, 4);
+    assert_eq!(report.dir_count, 2);
+    assert_eq!(report.hashed_files, 3);
+    assert_eq!(report.reused_files, 0);
+    assert_eq!(report.total_bytes, 22);
+
+    let mut parser = WimParser::new(&wim).unwrap();
+    parser.parse_full().unwrap();
+    let image = &parser.get_images()[0];
+    assert_eq!(image.file_count, 4);
+    assert_eq!(image.total_bytes, 22);
+    let mut tree = parser.open_lazy_tree(1).unwrap();
+    let entry = tree.find("/Windows/System32/a.dll").unwrap().unwrap();
+    assert_eq!(entry.last_write_time.to_system_time(), Some(mtime));
+    assert_eq!(read_file(&wim, "/readme.txt"), b"golden image");
+}
+
+/// 测试增量捕获只为大小或最后写入时间变化的文件计算摘要
+#[test]
+fn test_capture_incremental() {
+    let (source, mtime) = source_tree();
+    let out = tempfile::tempdir().unwrap();
+    let base = out.path().join("base.wim");
+    capture(source.path(), &base).unwrap();
+
+    let later = mtime + Duration::from_secs(60);
+    write_file(&source.path().join("readme.txt"), b"golden image v2", later);
+    write_file(&source.path().join("new.txt"), b"new file", later);
+    // 大小和时间不变的文件按清单沿用摘要，即使内容被替换
+    write_file(
+        &source.path().join("Windows/System32/b.dll"),
+        b"BRAVO",
+        mtime,
+    );
+
+    let next = out.path().join("next.wim");
+    let report = capture_incremental(source.path(), &base, &next).unwrap();
+    assert_eq!(
+        report.image_name,
+        source.path().file_name().unwrap().to_string_lossy()
+    );
+    assert_eq!(report.file_count, 5);
+    assert_eq!(report.hashed_files, 2);
+    assert_eq!(report.hashed_bytes, 23);
+    assert_eq!(report.reused_files, 2);
+    assert_eq!(read_file(&next, "/readme.txt"), b"golden image v2");
+    assert_eq!(read_file(&next, "/new.txt"), b"new file");
+    assert_eq!(read_file(&next, "/Windows/System32/a.dll"), b"alpha");
+
+    // 输出可以覆盖基准
+    let report = capture_incremental(source.path(), &next, &next).unwrap();
+    assert_eq!(report.hashed_files, 0);
+    assert_eq!(report.reused_files, 4);
+    assert_eq!(read_file(&next, "/new.txt"), b"new file");
+}
@@ -0,0 +1,77 @@
+#![cfg(feature = "parser")]
+
+mod common;
+
+use std::io::Cursor;
+
+use common::{build_wim, write_bytes, ImageSpec};
+use wim_parser::carve;
+
+/// 模拟原始磁盘：两个完整的 WIM、一个伪造的签名和一个被截断的 WIM
+fn disk() -> (Vec<u8>, Vec<u64>) {
+    let mut bytes = vec![0u8; 4096];
+    bytes[512..520].copy_from_slice(b"MSWIM\0\0\0");
+    let mut offsets = Vec::new();
+    for name in ["Windows RE", "Windows PE"] {
+        offsets.push(bytes.len() as u64);
+        bytes.extend(build_wim(&[
+            ImageSpec::new(name).file("/a.txt", name.as_bytes())
+        ]));
+        bytes.extend(vec![0u8; 1000]);
+    }
+    offsets.push(bytes.len() as u64);
+    let truncated = build_wim(&[ImageSpec::new("Lost")]);
+    bytes.extend_from_slice(&truncated[..truncated.len() - 100]);
+    (bytes, offsets)
+}
+
+/// 测试扫描找出所有通过校验的 WIM 并标出被截断的
+#[test]
+fn test_carve_finds_wims() {
+    let (bytes, offsets) = disk();
+    let carver = carve(Cursor::new(&bytes)).unwrap();
+    assert_eq!(carver.len(), bytes.len() as u64);
+    let carved: Vec<_> = carver.collect::<Result<_, _>>().unwrap();
+
+    let found: Vec<u64> = carved.iter().map(|carved| carved.offset).collect();
+    assert_eq!(found, offsets);
+    assert!(carved[0].complete);
+    assert!(carved[1].complete);
+    assert!(!carved[2].complete);
+    assert_eq!(carved[0].header.image_count, 1);
+    assert!(carved[0].extent <= offsets[1] - offsets[0]);
+
+    assert_eq!(carve(Cursor::new(&bytes[..4096])).unwrap().count(), 0);
+
+    // 签名跨越扫描块边界（1 MiB）
+    let mut bytes = vec![0u8; 1024 * 1024 - 3];
+    bytes.extend(build_wim(&[ImageSpec::new("Image A")]));
+    let carved: Vec<_> = carve(Cursor::new(&bytes))
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(carved.len(), 1);
+    assert_eq!(carved[0].offset, 1024 * 1024 - 3);
+}
+
+/// 测试以找到的偏移打开解析器
+#[test]
+fn test_carved_wim_open() {
+    let (bytes, _) = disk();
+    let file = write_bytes(&bytes);
+    let carved: Vec<_> = carve(std::fs::File::open(file.path()).unwrap())
+        .unwrap()
+        .filter_map(Result::ok)
+        .filter(|carved| carved.complete)
+        .collect();
+
+    let names: Vec<String> = carved
+        .iter()
+        .map(|carved| {
+            let mut parser = carved.open(file.path()).unwrap();
+            parser.parse_full().unwrap();
+            parser.get_images()[0].name.clone()
+        })
+        .collect();
+    assert_eq!(names, ["Windows RE", "Windows PE"]);
+}
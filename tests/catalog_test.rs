@@ -0,0 +1,98 @@
+#![cfg(feature = "verify")]
+
+use std::path::Path;
+
+use wim_parser::{Catalog, VirtualWim};
+
+/// 写入两个镜像共享同一文件内容的测试 WIM
+fn save_wim(path: &Path) {
+    let mut wim = VirtualWim::new();
+    wim.add_image("Windows 11 Pro")
+        .extra_xml("<WINDOWS><EDITIONID>Professional</EDITIONID></WINDOWS>")
+        .add_file("/Windows/notepad.exe", b"notepad")
+        .unwrap()
+        .add_file("/empty.txt", b"")
+        .unwrap();
+    wim.add_image("Windows 11 Home")
+        .add_file("/Windows/notepad.exe", b"notepad")
+        .unwrap();
+    wim.save(path).unwrap();
+}
+
+/// 测试目录收集 WIM 标识、镜像、数据流，以及按需收集文件列表
+#[test]
+fn test_catalog_collects_wims() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("install.wim");
+    save_wim(&path);
+
+    let mut catalog = Catalog::new();
+    let wim = catalog.add(&path).unwrap();
+    assert_eq!(wim.path, path);
+    assert_eq!(wim.file_size, std::fs::metadata(&path).unwrap().len());
+    assert_eq!((wim.segment_number, wim.total_segments), (1, 1));
+    assert_eq!(wim.images.len(), 2);
+    assert!(wim.streams.iter().any(|stream| stream.original_size == 7));
+    assert!(wim.files.is_empty());
+
+    let mut catalog = Catalog::new().with_files(true);
+    catalog.add(&path).unwrap();
+    let files = &catalog.wims()[0].files;
+    let notepad: Vec<_> = files
+        .iter()
+        .filter(|file| file.path == "Windows/notepad.exe")
+        .collect();
+    assert_eq!(notepad.len(), 2);
+    assert_eq!(notepad[0].hash, notepad[1].hash);
+    assert_eq!(notepad[0].size, 7);
+    let empty = files.iter().find(|file| file.path == "empty.txt").unwrap();
+    assert_eq!((empty.size, empty.hash), (0, None));
+    assert!(files
+        .iter()
+        .any(|file| file.path == "Windows" && file.hash.is_none()));
+}
+
+/// 测试导出 SQLite 后可以按摘要跨镜像查询文件，重复导出会覆盖旧文件
+#[cfg(feature = "sqlite")]
+#[test]
+fn test_catalog_export_sqlite() {
+    let dir = tempfile::tempdir().unwrap();
+    let first = dir.path().join("first.wim");
+    let second = dir.path().join("second.wim");
+    save_wim(&first);
+    save_wim(&second);
+
+    let mut catalog = Catalog::new().with_files(true);
+    catalog.add(&first).unwrap();
+    catalog.add(&second).unwrap();
+    let db = dir.path().join("catalog.db");
+    catalog.export_sqlite(&db).unwrap();
+    catalog.export_sqlite(&db).unwrap();
+
+    let connection = rusqlite::Connection::open(&db).unwrap();
+    let count = |sql: &str| -> i64 { connection.query_row(sql, [], |row| row.get(0)).unwrap() };
+    assert_eq!(count("SELECT COUNT(*) FROM wims"), 2);
+    assert_eq!(count("SELECT COUNT(*) FROM images"), 4);
+    assert_eq!(
+        count(
+            "SELECT COUNT(*) FROM files f JOIN streams s \
+             ON s.wim_id = f.wim_id AND s.hash = f.hash \
+             WHERE f.path = 'Windows/notepad.exe' AND s.original_size = 7"
+        ),
+        4
+    );
+
+    let edition: String = connection
+        .query_row(
+            "SELECT i.edition_id FROM images i JOIN wims w ON w.id = i.wim_id \
+             WHERE w.path LIKE '%first.wim' AND i.image_index = 1",
+            [],
+            |row| row.get(0),
+        )
+        .unwrap();
+    assert_eq!(edition, "Professional");
+    let guid: String = connection
+        .query_row("SELECT guid FROM wims LIMIT 1", [], |row| row.get(0))
+        .unwrap();
+    assert!(guid.starts_with('{') && guid.len() == 38);
+}
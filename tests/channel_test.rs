@@ -0,0 +1,78 @@
+#![cfg(feature = "parser")]
+
+mod common;
+
+use common::{write_wim, ImageSpec};
+use wim_parser::{format, ImageChannel, WimParser};
+
+fn image_xml(flags: &str, windows: &str) -> String {
+    format!("<IMAGE INDEX=\"1\"><FLAGS>{flags}</FLAGS><WINDOWS>{windows}</WINDOWS></IMAGE>")
+}
+
+/// 测试由 EDITIONID、安装类型和产品类型推断渠道
+#[test]
+fn test_image_channel_detection() {
+    let cases = [
+        (
+            "Professional",
+            "<INSTALLATIONTYPE>Client</INSTALLATIONTYPE><EDITIONID>Professional</EDITIONID>",
+            Some(ImageChannel::Client),
+        ),
+        (
+            "ServerDatacenterCore",
+            "<INSTALLATIONTYPE>Server Core</INSTALLATIONTYPE><PRODUCTTYPE>ServerNT</PRODUCTTYPE>\
+             <EDITIONID>ServerDatacenter</EDITIONID>",
+            Some(ImageChannel::Server),
+        ),
+        (
+            "ServerStandardEval",
+            "<INSTALLATIONTYPE>Server</INSTALLATIONTYPE><EDITIONID>ServerStandardEval</EDITIONID>",
+            Some(ImageChannel::Evaluation),
+        ),
+        (
+            "EnterpriseSEval",
+            "<INSTALLATIONTYPE>Client</INSTALLATIONTYPE>",
+            Some(ImageChannel::Evaluation),
+        ),
+        (
+            "IoTEnterpriseS",
+            "<INSTALLATIONTYPE>Client</INSTALLATIONTYPE><EDITIONID>IoTEnterpriseS</EDITIONID>",
+            Some(ImageChannel::IoT),
+        ),
+        (
+            "WindowsPE",
+            "<INSTALLATIONTYPE>WindowsPE</INSTALLATIONTYPE>",
+            None,
+        ),
+    ];
+    for (flags, windows, expected) in cases {
+        let image = format::parse_single_image_xml(&image_xml(flags, windows));
+        assert_eq!(image.flags.as_deref(), Some(flags));
+        assert_eq!(image.channel(), expected, "{flags}");
+    }
+
+    let image = format::parse_single_image_xml("<IMAGE INDEX=\"1\"><NAME>Data</NAME></IMAGE>");
+    assert_eq!(image.channel(), None);
+    assert_eq!(ImageChannel::IoT.to_string(), "IoT");
+}
+
+/// 测试解析后的镜像保留原样的 FLAGS 并提供渠道
+#[test]
+fn test_image_channel_from_wim() {
+    let wim = write_wim(&[
+        ImageSpec::new("Windows 11 Pro").extra_xml(
+            "<FLAGS>Professional</FLAGS><WINDOWS><EDITIONID>Professional</EDITIONID>\
+             <INSTALLATIONTYPE>Client</INSTALLATIONTYPE></WINDOWS>",
+        ),
+        ImageSpec::new("Windows Server 2022").extra_xml(
+            "<FLAGS>ServerStandard</FLAGS><WINDOWS><PRODUCTTYPE>ServerNT</PRODUCTTYPE></WINDOWS>",
+        ),
+    ]);
+    let mut parser = WimParser::new(wim.path()).unwrap();
+    parser.parse_full().unwrap();
+    let images = parser.get_images();
+    assert_eq!(images[0].flags.as_deref(), Some("Professional"));
+    assert_eq!(images[0].channel(), Some(ImageChannel::Client));
+    assert_eq!(images[1].flags.as_deref(), Some("ServerStandard"));
+    assert_eq!(images[1].channel(), Some(ImageChannel::Server));
+}
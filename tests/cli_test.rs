@@ -0,0 +1,304 @@
+#![cfg(feature = "verify")]
+
+mod common;
+
+use std::fs;
+use std::path::Path;
+use std::process::{Command, Output};
+
+use common::{build_wim, write_bytes, write_wim, ImageSpec};
+use wim_parser::{FileFlags, ResourceFlags};
+
+fn wim_parser(args: &[&str]) -> Output {
+    wim_parser_with_home(Path::new("/nonexistent"), args)
+}
+
+/// 以 `home` 作为主目录运行（默认配置文件为 `home/.config/wim-parser.toml`）
+fn wim_parser_with_home(home: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_wim-parser"))
+        .args(args)
+        .env("HOME", home)
+        .env_remove("XDG_CONFIG_HOME")
+        .output()
+        .unwrap()
+}
+
+/// 测试成功、参数错误和无效文件的退出码及 JSON 错误输出
+#[test]
+fn test_cli_exit_codes() {
+    let wim = write_wim(&[ImageSpec::new("Image A")]);
+    let path = wim.path().to_str().unwrap();
+    let output = wim_parser(&["info", path]);
+    assert_eq!(output.status.code(), Some(0));
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Image A"));
+
+    assert_eq!(wim_parser(&["list", path]).status.code(), Some(64));
+    assert_eq!(
+        wim_parser(&["--error-format", "xml", "info", path])
+            .status
+            .code(),
+        Some(64)
+    );
+
+    let mut bytes = build_wim(&[ImageSpec::new("Image A")]);
+    bytes[0] = b'X';
+    let invalid = write_bytes(&bytes);
+    let output = wim_parser(&[
+        "--error-format",
+        "json",
+        "header",
+        invalid.path().to_str().unwrap(),
+    ]);
+    assert_eq!(output.status.code(), Some(2));
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.starts_with('{') && stderr.trim_end().ends_with('}'));
+    assert!(stderr.contains("\"code\":131074"), "{stderr}");
+    assert!(stderr.contains("\"category\":\"format\""), "{stderr}");
+    assert!(stderr.contains("\"exit_code\":2"), "{stderr}");
+
+    let output = wim_parser(&["--error-format=json", "info", "/nonexistent/install.wim"]);
+    assert_eq!(output.status.code(), Some(5));
+}
+
+/// 测试校验失败和不支持的特性的退出码
+#[test]
+fn test_cli_verification_and_unsupported() {
+    let data = b"stream that will be corrupted";
+    let mut bytes = build_wim(&[ImageSpec::new("Image A").file("a.txt", data)]);
+    let offset = bytes
+        .windows(data.len())
+        .position(|window| window == data)
+        .unwrap();
+    bytes[offset] ^= 0xFF;
+    let corrupted = write_bytes(&bytes);
+    let output = wim_parser(&[
+        "--error-format",
+        "json",
+        "verify",
+        corrupted.path().to_str().unwrap(),
+    ]);
+    assert_eq!(output.status.code(), Some(3));
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("\"category\":\"verification\""), "{stderr}");
+
+    let mut bytes = build_wim(&[ImageSpec::new("Image A")]);
+    // 设置了压缩标志但没有压缩格式标志：无法识别的压缩格式
+    let flags = FileFlags::COMPRESSION;
+    bytes[16..20].copy_from_slice(&flags.to_le_bytes());
+    bytes[72 + 7] |= ResourceFlags::COMPRESSED;
+    // 原始大小大于压缩后大小，分块需要解压（而不是按原样存储）
+    bytes[72 + 16] += 1;
+    let compressed = write_bytes(&bytes);
+    let output = wim_parser(&["info", compressed.path().to_str().unwrap()]);
+    assert_eq!(output.status.code(), Some(4));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("未知压缩格式"));
+}
+
+/// 测试 info --recursive 盘点目录并输出单个 JSON 报告
+#[test]
+fn test_cli_recursive_inventory() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::create_dir(dir.path().join("x64")).unwrap();
+    std::fs::write(
+        dir.path().join("x64/install.wim"),
+        build_wim(&[ImageSpec::new("Windows 11 Pro")]),
+    )
+    .unwrap();
+    std::fs::write(dir.path().join("broken.esd"), b"MSWIM").unwrap();
+    let root = dir.path().to_str().unwrap();
+
+    let output = wim_parser(&["info", "--recursive", root, "--output", "json"]);
+    assert_eq!(output.status.code(), Some(0));
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(stdout.lines().count(), 1, "{stdout}");
+    assert!(stdout.contains("\"wim_count\":2"), "{stdout}");
+    assert!(stdout.contains("\"failures\":1"), "{stdout}");
+    assert!(stdout.contains("\"name\":\"Windows 11 Pro\""), "{stdout}");
+
+    let output = wim_parser(&["info", "-r", root]);
+    assert_eq!(output.status.code(), Some(0));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Windows 11 Pro"), "{stdout}");
+
+    let wim = dir.path().join("x64/install.wim");
+    let output = wim_parser(&["info", "--output=json", wim.to_str().unwrap()]);
+    assert_eq!(output.status.code(), Some(0));
+    assert!(String::from_utf8_lossy(&output.stdout).contains("\"image_count\":1"));
+
+    assert_eq!(
+        wim_parser(&["header", "--recursive", root]).status.code(),
+        Some(64)
+    );
+    assert_eq!(
+        wim_parser(&["info", "--output", "yaml", root])
+            .status
+            .code(),
+        Some(64)
+    );
+}
+
+/// 测试 flags 命令显示、设置和清除文件头标志
+#[test]
+fn test_cli_flags() {
+    let wim = write_wim(&[ImageSpec::new("Image A")]);
+    let path = wim.path().to_str().unwrap();
+    let output = wim_parser(&["flags", path]);
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), "NONE");
+
+    let output = wim_parser(&["flags", path, "--set", "READONLY,rp_fix"]);
+    assert_eq!(output.status.code(), Some(0));
+    assert!(String::from_utf8_lossy(&output.stdout).contains("NONE -> READONLY|RP_FIX"));
+    let output = wim_parser(&[
+        "flags",
+        path,
+        "--clear=RP_FIX",
+        "--clear",
+        "WRITE_IN_PROGRESS",
+    ]);
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(
+        wim_parser::probe_header(wim.path()).unwrap().file_flags,
+        FileFlags::READONLY
+    );
+
+    assert_eq!(
+        wim_parser(&["flags", path, "--set", "BOGUS"]).status.code(),
+        Some(64)
+    );
+    assert_eq!(
+        wim_parser(&["info", path, "--set", "READONLY"])
+            .status
+            .code(),
+        Some(64)
+    );
+    let output = wim_parser(&["flags", path, "--set", "SPANNED"]);
+    assert_eq!(output.status.code(), Some(4));
+    assert_eq!(
+        wim_parser::probe_header(wim.path()).unwrap().file_flags,
+        FileFlags::READONLY
+    );
+}
+
+/// 测试配置文件中的默认选项、命令行优先级和配置文件错误
+#[test]
+fn test_cli_config_file() {
+    let wim = write_wim(&[ImageSpec::new("Image A").file("a.txt", b"config")]);
+    let path = wim.path().to_str().unwrap();
+    let home = tempfile::tempdir().unwrap();
+    fs::create_dir(home.path().join(".config")).unwrap();
+    let config = home.path().join(".config/wim-parser.toml");
+    fs::write(
+        &config,
+        "# 默认选项\noutput = \"json\"\nthreads = 1 # 单线程\ncache_dir = '/tmp'\n",
+    )
+    .unwrap();
+
+    let output = wim_parser_with_home(home.path(), &["info", path]);
+    assert_eq!(output.status.code(), Some(0));
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(
+        stdout.starts_with('{') && stdout.contains("Image A"),
+        "{stdout}"
+    );
+    // 命令行选项优先，其他命令不受 output 影响
+    let output = wim_parser_with_home(home.path(), &["info", "--output", "text", path]);
+    assert!(!String::from_utf8(output.stdout).unwrap().starts_with('{'));
+    assert_eq!(
+        wim_parser_with_home(home.path(), &["header", path])
+            .status
+            .code(),
+        Some(0)
+    );
+    let output = wim_parser_with_home(home.path(), &["verify", path]);
+    assert_eq!(output.status.code(), Some(0));
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(
+        stdout
+            .lines()
+            .any(|line| line.contains("线程") && line.trim_end().ends_with('1')),
+        "{stdout}"
+    );
+
+    // 显式指定的配置文件和 XDG_CONFIG_HOME
+    let other = home.path().join("other.toml");
+    fs::write(&other, "error_format = \"json\"\n").unwrap();
+    let output = wim_parser_with_home(
+        home.path(),
+        &[
+            "--config",
+            other.to_str().unwrap(),
+            "info",
+            "/nonexistent.wim",
+        ],
+    );
+    assert_eq!(output.status.code(), Some(5));
+    assert!(output.stderr.starts_with(b"{"));
+    let output = wim_parser_with_home(
+        home.path(),
+        &["--config", "/nonexistent.toml", "info", path],
+    );
+    assert_eq!(output.status.code(), Some(64));
+
+    fs::write(&config, "threads = 2\ncolour = \"auto\"\n").unwrap();
+    let output = wim_parser_with_home(home.path(), &["info", path]);
+    assert_eq!(output.status.code(), Some(64));
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(
+        stderr.contains("第 2 行") && stderr.contains("colour"),
+        "{stderr}"
+    );
+    fs::write(&config, "threads = \"many\"\n").unwrap();
+    assert_eq!(
+        wim_parser_with_home(home.path(), &["info", path])
+            .status
+            .code(),
+        Some(64)
+    );
+}
+
+/// 测试各 shell 的补全脚本，以及 bash 脚本的补全结果
+#[test]
+fn test_cli_completions() {
+    for shell in ["bash", "zsh", "fish", "powershell"] {
+        let output = wim_parser(&["completions", shell]);
+        assert_eq!(output.status.code(), Some(0), "{shell}");
+        let script = String::from_utf8(output.stdout).unwrap();
+        for word in ["verify", "flags", "error-format", "threads", "RP_FIX"] {
+            assert!(script.contains(word), "{shell}: {word}");
+        }
+    }
+    assert_eq!(wim_parser(&["completions", "tcsh"]).status.code(), Some(64));
+    let output = wim_parser(&[]);
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("completions"));
+
+    let script = tempfile::NamedTempFile::new().unwrap();
+    fs::write(script.path(), wim_parser(&["completions", "bash"]).stdout).unwrap();
+    let complete = |words: &str| {
+        let output = Command::new("bash")
+            .arg("-c")
+            .arg(format!(
+                "source {}; COMP_WORDS=({words}); COMP_CWORD=$((${{#COMP_WORDS[@]}} - 1)); \
+                 _wim_parser; echo \"${{COMPREPLY[*]}}\"",
+                script.path().display()
+            ))
+            .output();
+        output.map(|output| String::from_utf8(output.stdout).unwrap().trim().to_string())
+    };
+    // 没有 bash 的环境只检查脚本内容
+    let Ok(commands) = complete("wim-parser ''") else {
+        return;
+    };
+    assert_eq!(commands, "info header verify flags completions");
+    assert_eq!(complete("wim-parser ve").unwrap(), "verify");
+    assert_eq!(
+        complete("wim-parser verify --").unwrap(),
+        "--error-format --config --threads"
+    );
+    assert_eq!(
+        complete("wim-parser flags x.wim --clear R").unwrap(),
+        "READONLY RP_FIX"
+    );
+}
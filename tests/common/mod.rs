@@ -0,0 +1,1195 @@
+//! 测试辅助：在内存中构造最小的未压缩 WIM 文件
+#![allow(dead_code)]
+
+use sha1::{Digest, Sha1};
+use std::io::Write;
+use tempfile::NamedTempFile;
+
+pub const FILE_ATTRIBUTE_DIRECTORY: u32 = 0x10;
+pub const FILE_ATTRIBUTE_NORMAL: u32 = 0x80;
+pub const FILE_ATTRIBUTE_REPARSE_POINT: u32 = 0x400;
+pub const IO_REPARSE_TAG_SYMLINK: u32 = 0xA000_000C;
+
+/// 文件头标志：XPRESS 压缩
+pub const XPRESS: u32 = 0x0002_0002;
+/// 文件头标志：LZX 压缩
+pub const LZX: u32 = 0x0004_0002;
+/// 文件头标志：LZMS 压缩
+pub const LZMS: u32 = 0x0008_0002;
+
+const HEADER_SIZE: usize = 208;
+const DENTRY_FIXED_SIZE: usize = 0x66;
+
+/// 测试用镜像描述
+#[derive(Debug, Clone, Default)]
+pub struct ImageSpec {
+    pub name: String,
+    /// `<NAME>` 中的名称（默认与显示名称相同）
+    pub english_name: Option<String>,
+    pub dirs: Vec<String>,
+    pub files: Vec<(String, Vec<u8>)>,
+    /// 覆盖 XML 中记录的 (DIRCOUNT, FILECOUNT, TOTALBYTES)
+    pub recorded_stats: Option<(u32, u32, u64)>,
+    /// 追加到 IMAGE 节点内的额外 XML
+    pub extra_xml: String,
+    /// 重解析点 (路径, 标记)
+    pub reparse_points: Vec<(String, u32)>,
+    /// 是否为所有目录项设置安全描述符
+    pub secured: bool,
+    /// 所有目录项的最后写入时间 (FILETIME)
+    pub write_time: u64,
+    /// 附加的文件属性 (路径, 属性位)
+    pub attributes: Vec<(String, u32)>,
+    /// 硬链接组 (路径, 组 ID)
+    pub hard_links: Vec<(String, u64)>,
+    /// 目录符号链接 (路径, 重解析数据)
+    pub dir_links: Vec<(String, Vec<u8>)>,
+    /// 8.3 短文件名 (路径, 短文件名)，路径为空时设置根目录
+    pub short_names: Vec<(String, String)>,
+}
+
+impl ImageSpec {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            ..Default::default()
+        }
+    }
+
+    pub fn english_name(mut self, name: &str) -> Self {
+        self.english_name = Some(name.to_string());
+        self
+    }
+
+    pub fn dir(mut self, path: &str) -> Self {
+        self.dirs.push(path.to_string());
+        self
+    }
+
+    pub fn file(mut self, path: &str, data: &[u8]) -> Self {
+        self.files.push((path.to_string(), data.to_vec()));
+        self
+    }
+
+    pub fn recorded_stats(mut self, dirs: u32, files: u32, bytes: u64) -> Self {
+        self.recorded_stats = Some((dirs, files, bytes));
+        self
+    }
+
+    pub fn extra_xml(mut self, xml: &str) -> Self {
+        self.extra_xml.push_str(xml);
+        self
+    }
+
+    pub fn reparse(mut self, path: &str, tag: u32) -> Self {
+        self.reparse_points.push((path.to_string(), tag));
+        self
+    }
+
+    /// 相对路径符号链接（重解析数据作为未命名数据流）
+    pub fn symlink(mut self, path: &str, target: &str) -> Self {
+        self.files.push((path.to_string(), symlink_data(target)));
+        self.reparse_points
+            .push((path.to_string(), IO_REPARSE_TAG_SYMLINK));
+        self
+    }
+
+    /// 指向 `target` 的目录符号链接，其下仍可添加子项（模拟构造的恶意镜像）
+    pub fn dir_symlink(mut self, path: &str, target: &str) -> Self {
+        self.dir_links
+            .push((path.to_string(), symlink_data(target)));
+        self
+    }
+
+    pub fn secured(mut self) -> Self {
+        self.secured = true;
+        self
+    }
+
+    pub fn write_time(mut self, filetime: u64) -> Self {
+        self.write_time = filetime;
+        self
+    }
+
+    pub fn attributes(mut self, path: &str, attributes: u32) -> Self {
+        self.attributes.push((path.to_string(), attributes));
+        self
+    }
+
+    pub fn hard_link(mut self, path: &str, group_id: u64) -> Self {
+        self.hard_links.push((path.to_string(), group_id));
+        self
+    }
+
+    pub fn short_name(mut self, path: &str, short_name: &str) -> Self {
+        self.short_names
+            .push((path.to_string(), short_name.to_string()));
+        self
+    }
+
+    fn tree(&self) -> Node {
+        let mut tree = Node::dir("");
+        for dir in &self.dirs {
+            tree.insert_dir(dir);
+        }
+        for (path, data) in &self.files {
+            tree.insert_file(path, data);
+        }
+        for (path, tag) in &self.reparse_points {
+            if !self.files.iter().any(|(file, _)| file == path) {
+                tree.insert_file(path, &[]);
+            }
+            tree.find_mut(path).reparse_tag = Some(*tag);
+        }
+        for (path, attributes) in &self.attributes {
+            tree.find_mut(path).extra_attributes |= attributes;
+        }
+        for (path, group_id) in &self.hard_links {
+            tree.find_mut(path).hard_link_group_id = *group_id;
+        }
+        for (path, short_name) in &self.short_names {
+            tree.find_mut(path).short_name = short_name.clone();
+        }
+        for (path, data) in &self.dir_links {
+            let node = tree.insert_dir(path);
+            node.reparse_tag = Some(IO_REPARSE_TAG_SYMLINK);
+            node.reparse_data = Some(data.clone());
+        }
+        tree
+    }
+
+    /// 实际统计值（目录数包含根目录）
+    pub fn actual_stats(&self) -> (u32, u32, u64) {
+        let tree = self.tree();
+        let (mut dirs, mut files, mut bytes) = (0, 0, 0);
+        tree.count(&mut dirs, &mut files, &mut bytes);
+        (dirs, files, bytes)
+    }
+}
+
+#[derive(Debug)]
+struct Node {
+    name: String,
+    data: Option<Vec<u8>>,
+    reparse_tag: Option<u32>,
+    /// 目录重解析点的重解析数据
+    reparse_data: Option<Vec<u8>>,
+    extra_attributes: u32,
+    hard_link_group_id: u64,
+    short_name: String,
+    children: Vec<Node>,
+}
+
+impl Node {
+    fn dir(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            data: None,
+            reparse_tag: None,
+            reparse_data: None,
+            extra_attributes: 0,
+            hard_link_group_id: 0,
+            short_name: String::new(),
+            children: Vec::new(),
+        }
+    }
+
+    fn is_dir(&self) -> bool {
+        self.data.is_none()
+    }
+
+    fn child_dir(&mut self, name: &str) -> &mut Node {
+        let pos = match self.children.iter().position(|c| c.name == name) {
+            Some(pos) => pos,
+            None => {
+                self.children.push(Node::dir(name));
+                self.children.len() - 1
+            }
+        };
+        &mut self.children[pos]
+    }
+
+    fn insert_dir(&mut self, path: &str) -> &mut Node {
+        let mut node = self;
+        for part in path.split('/').filter(|p| !p.is_empty()) {
+            node = node.child_dir(part);
+        }
+        node
+    }
+
+    fn insert_file(&mut self, path: &str, data: &[u8]) {
+        let (parent, name) = path.rsplit_once('/').unwrap_or(("", path));
+        self.insert_dir(parent).children.push(Node {
+            name: name.to_string(),
+            data: Some(data.to_vec()),
+            reparse_tag: None,
+            reparse_data: None,
+            extra_attributes: 0,
+            hard_link_group_id: 0,
+            short_name: String::new(),
+            children: Vec::new(),
+        });
+    }
+
+    fn find_mut(&mut self, path: &str) -> &mut Node {
+        let mut node = self;
+        for part in path.split('/').filter(|p| !p.is_empty()) {
+            node = node.children.iter_mut().find(|c| c.name == part).unwrap();
+        }
+        node
+    }
+
+    fn count(&self, dirs: &mut u32, files: &mut u32, bytes: &mut u64) {
+        match &self.data {
+            Some(data) => {
+                *files += 1;
+                *bytes += data.len() as u64;
+            }
+            None => *dirs += 1,
+        }
+        for child in &self.children {
+            child.count(dirs, files, bytes);
+        }
+    }
+}
+
+/// 数据的 SHA-1 摘要（与真实 WIM 中偏移表的哈希一致）
+pub fn sha1_hash(data: &[u8]) -> [u8; 20] {
+    Sha1::digest(data).into()
+}
+
+fn utf16le(s: &str) -> Vec<u8> {
+    s.encode_utf16().flat_map(|u| u.to_le_bytes()).collect()
+}
+
+/// 符号链接的重解析数据（相对路径标志按目标是否以 `/` 开头决定）
+fn symlink_data(target: &str) -> Vec<u8> {
+    let name = utf16le(target);
+    let mut data = Vec::new();
+    data.extend(0u16.to_le_bytes()); // SubstituteNameOffset
+    data.extend((name.len() as u16).to_le_bytes());
+    data.extend((name.len() as u16).to_le_bytes()); // PrintNameOffset
+    data.extend((name.len() as u16).to_le_bytes());
+    let relative = u32::from(!target.starts_with('/'));
+    data.extend(relative.to_le_bytes()); // SYMLINK_FLAG_RELATIVE
+    data.extend(&name);
+    data.extend(&name);
+    data
+}
+
+fn align8(n: usize) -> usize {
+    (n + 7) & !7
+}
+
+fn encode_dentry(node: &Node, security_id: i32, write_time: u64) -> Vec<u8> {
+    let name = utf16le(&node.name);
+    let short_name = utf16le(&node.short_name);
+    // 名称之后的空终止符在名称为空时省略
+    let name_len = if name.is_empty() { 0 } else { name.len() + 2 };
+    let short_name_len = if short_name.is_empty() {
+        0
+    } else {
+        short_name.len() + 2
+    };
+    let length = align8(DENTRY_FIXED_SIZE + name_len + short_name_len);
+
+    let mut buf = vec![0u8; length];
+    buf[0..8].copy_from_slice(&(length as u64).to_le_bytes());
+    let attributes = if node.is_dir() && node.reparse_tag.is_some() {
+        FILE_ATTRIBUTE_DIRECTORY | FILE_ATTRIBUTE_REPARSE_POINT
+    } else if node.is_dir() {
+        FILE_ATTRIBUTE_DIRECTORY
+    } else if node.reparse_tag.is_some() {
+        FILE_ATTRIBUTE_REPARSE_POINT
+    } else {
+        FILE_ATTRIBUTE_NORMAL
+    } | node.extra_attributes;
+    buf[0x08..0x0C].copy_from_slice(&attributes.to_le_bytes());
+    buf[0x0C..0x10].copy_from_slice(&security_id.to_le_bytes());
+    buf[0x38..0x40].copy_from_slice(&write_time.to_le_bytes());
+    if let Some(tag) = node.reparse_tag {
+        buf[0x58..0x5C].copy_from_slice(&tag.to_le_bytes());
+    } else {
+        buf[0x58..0x60].copy_from_slice(&node.hard_link_group_id.to_le_bytes());
+    }
+    if let Some(data) = node.data.as_ref().or(node.reparse_data.as_ref()) {
+        if !data.is_empty() {
+            buf[0x40..0x54].copy_from_slice(&sha1_hash(data));
+        }
+    }
+    buf[0x62..0x64].copy_from_slice(&(short_name.len() as u16).to_le_bytes());
+    buf[0x64..0x66].copy_from_slice(&(name.len() as u16).to_le_bytes());
+    buf[DENTRY_FIXED_SIZE..DENTRY_FIXED_SIZE + name.len()].copy_from_slice(&name);
+    let short_name_offset = DENTRY_FIXED_SIZE + name_len;
+    buf[short_name_offset..short_name_offset + short_name.len()].copy_from_slice(&short_name);
+    buf
+}
+
+/// 写入目录的子目录项列表，返回列表偏移
+fn write_children(buf: &mut Vec<u8>, node: &Node, security_id: i32, write_time: u64) -> u64 {
+    let list_offset = buf.len();
+    let mut positions = Vec::new();
+    for child in &node.children {
+        positions.push(buf.len());
+        buf.extend(encode_dentry(child, security_id, write_time));
+    }
+    buf.extend([0u8; 8]);
+
+    for (child, pos) in node.children.iter().zip(positions) {
+        if child.is_dir() {
+            let offset = write_children(buf, child, security_id, write_time);
+            buf[pos + 0x10..pos + 0x18].copy_from_slice(&offset.to_le_bytes());
+        }
+    }
+    list_offset as u64
+}
+
+fn build_metadata(spec: &ImageSpec) -> (Vec<u8>, Vec<Vec<u8>>) {
+    let tree = spec.tree();
+
+    let mut buf = Vec::new();
+    let security_id = if spec.secured {
+        // 安全数据块：1 个 20 字节的最小自相对安全描述符
+        let descriptor = [
+            1u8, 0, 0x04, 0x80, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        ];
+        buf.extend((16 + descriptor.len() as u32).to_le_bytes());
+        buf.extend(1u32.to_le_bytes());
+        buf.extend((descriptor.len() as u64).to_le_bytes());
+        buf.extend(descriptor);
+        buf.resize(align8(buf.len()), 0);
+        0
+    } else {
+        // 安全数据块：总长度 8，0 个描述符
+        buf.extend(8u32.to_le_bytes());
+        buf.extend(0u32.to_le_bytes());
+        -1
+    };
+
+    let root_pos = buf.len();
+    buf.extend(encode_dentry(&tree, security_id, spec.write_time));
+    buf.extend([0u8; 8]);
+    let offset = write_children(&mut buf, &tree, security_id, spec.write_time);
+    buf[root_pos + 0x10..root_pos + 0x18].copy_from_slice(&offset.to_le_bytes());
+
+    let streams = spec
+        .files
+        .iter()
+        .chain(&spec.dir_links)
+        .map(|(_, data)| data.clone())
+        .filter(|data| !data.is_empty())
+        .collect();
+    (buf, streams)
+}
+
+fn reshdr(size: u64, flags: u8, offset: u64, original_size: u64) -> Vec<u8> {
+    let mut buf = Vec::with_capacity(24);
+    buf.extend(&size.to_le_bytes()[..7]);
+    buf.push(flags);
+    buf.extend(offset.to_le_bytes());
+    buf.extend(original_size.to_le_bytes());
+    buf
+}
+
+fn lookup_entry(size: u64, flags: u8, offset: u64, ref_count: u32, hash: [u8; 20]) -> Vec<u8> {
+    let mut buf = reshdr(size, flags, offset, size);
+    buf.extend(1u16.to_le_bytes());
+    buf.extend(ref_count.to_le_bytes());
+    buf.extend(hash);
+    buf
+}
+
+fn build_xml(images: &[ImageSpec]) -> Vec<u8> {
+    let mut xml = String::from("<WIM>");
+    for (i, spec) in images.iter().enumerate() {
+        let (dirs, files, bytes) = spec.recorded_stats.unwrap_or_else(|| spec.actual_stats());
+        xml.push_str(&format!(
+            "<IMAGE INDEX=\"{}\"><DIRCOUNT>{dirs}</DIRCOUNT><FILECOUNT>{files}</FILECOUNT>\
+             <TOTALBYTES>{bytes}</TOTALBYTES>{}<DISPLAYNAME>{}</DISPLAYNAME>\
+             <DISPLAYDESCRIPTION>{}</DISPLAYDESCRIPTION><NAME>{}</NAME></IMAGE>",
+            i + 1,
+            spec.extra_xml,
+            spec.name,
+            spec.name,
+            spec.english_name.as_deref().unwrap_or(&spec.name)
+        ));
+    }
+    xml.push_str("</WIM>");
+
+    let mut buf = vec![0xFF, 0xFE];
+    buf.extend(utf16le(&xml));
+    buf
+}
+
+/// 构造完整的未压缩 WIM 文件内容
+pub fn build_wim(images: &[ImageSpec]) -> Vec<u8> {
+    let mut body: Vec<u8> = Vec::new();
+    let mut lookup: Vec<u8> = Vec::new();
+    let mut stream_entries: Vec<([u8; 20], u64, u64, u32)> = Vec::new();
+
+    for spec in images {
+        let (metadata, streams) = build_metadata(spec);
+
+        for data in streams {
+            let hash = sha1_hash(&data);
+            if let Some(entry) = stream_entries.iter_mut().find(|e| e.0 == hash) {
+                entry.3 += 1;
+                continue;
+            }
+            let offset = (HEADER_SIZE + body.len()) as u64;
+            body.extend(&data);
+            stream_entries.push((hash, offset, data.len() as u64, 1));
+        }
+
+        let offset = (HEADER_SIZE + body.len()) as u64;
+        body.extend(&metadata);
+        lookup.extend(lookup_entry(
+            metadata.len() as u64,
+            0x02,
+            offset,
+            1,
+            sha1_hash(&metadata),
+        ));
+    }
+
+    for (hash, offset, size, ref_count) in stream_entries {
+        lookup.extend(lookup_entry(size, 0, offset, ref_count, hash));
+    }
+
+    let lookup_offset = (HEADER_SIZE + body.len()) as u64;
+    body.extend(&lookup);
+
+    let xml = build_xml(images);
+    let xml_offset = (HEADER_SIZE + body.len()) as u64;
+    body.extend(&xml);
+
+    let mut header = vec![0u8; HEADER_SIZE];
+    header[0..8].copy_from_slice(b"MSWIM\0\0\0");
+    header[8..12].copy_from_slice(&(HEADER_SIZE as u32).to_le_bytes());
+    header[12..16].copy_from_slice(&0x10d00u32.to_le_bytes());
+    header[24..40].copy_from_slice(&[0x5A; 16]);
+    header[40..42].copy_from_slice(&1u16.to_le_bytes());
+    header[42..44].copy_from_slice(&1u16.to_le_bytes());
+    header[44..48].copy_from_slice(&(images.len() as u32).to_le_bytes());
+    header[48..72].copy_from_slice(&reshdr(
+        lookup.len() as u64,
+        0x02,
+        lookup_offset,
+        lookup.len() as u64,
+    ));
+    header[72..96].copy_from_slice(&reshdr(xml.len() as u64, 0, xml_offset, xml.len() as u64));
+
+    header.extend(body);
+    header
+}
+
+/// 将数据流改写为分块压缩资源（文件头改为 `flags` 和 `chunk` 分块大小）：`chunks` 为各分块（压缩后）的数据，
+/// 分块表和分块追加到文件末尾
+pub fn chunked_stream(
+    mut bytes: Vec<u8>,
+    flags: u32,
+    chunk: u32,
+    hash: [u8; 20],
+    original: u64,
+    chunks: &[&[u8]],
+) -> Vec<u8> {
+    bytes[16..20].copy_from_slice(&flags.to_le_bytes());
+    bytes[20..24].copy_from_slice(&chunk.to_le_bytes());
+
+    let mut resource = Vec::new();
+    let mut start = 0u32;
+    for chunk in &chunks[..chunks.len() - 1] {
+        start += chunk.len() as u32;
+        resource.extend(start.to_le_bytes());
+    }
+    for chunk in chunks {
+        resource.extend(*chunk);
+    }
+
+    // 偏移表在元数据之后，取最后一次出现的摘要
+    let entry = bytes
+        .windows(20)
+        .rposition(|window| window == hash)
+        .unwrap()
+        - 30;
+    let offset = bytes.len() as u64;
+    bytes[entry..entry + 8].copy_from_slice(&(resource.len() as u64).to_le_bytes());
+    bytes[entry + 7] = 0x04;
+    bytes[entry + 8..entry + 16].copy_from_slice(&offset.to_le_bytes());
+    bytes[entry + 16..entry + 24].copy_from_slice(&original.to_le_bytes());
+    bytes.extend(resource);
+    bytes
+}
+
+/// XPRESS 压缩一个分块：所有符号使用 9 位码长（码字即符号值），贪心匹配上一次出现的 3 字节序列
+pub fn xpress_compress(data: &[u8]) -> Vec<u8> {
+    struct Writer {
+        out: Vec<u8>,
+        bitbuf: u32,
+        bitcount: u32,
+        next_bits: usize,
+        next_bits2: usize,
+    }
+    impl Writer {
+        fn put(&mut self, at: usize, word: u16) {
+            self.out[at..at + 2].copy_from_slice(&word.to_le_bytes());
+        }
+        fn bits(&mut self, bits: u32, count: u32) {
+            self.bitbuf = (self.bitbuf << count) | bits;
+            self.bitcount += count;
+            if self.bitcount > 16 {
+                self.bitcount -= 16;
+                self.put(self.next_bits, (self.bitbuf >> self.bitcount) as u16);
+                self.next_bits = self.next_bits2;
+                self.next_bits2 = self.out.len();
+                self.out.extend([0, 0]);
+            }
+        }
+    }
+
+    let mut writer = Writer {
+        out: vec![0x99; 256],
+        bitbuf: 0,
+        bitcount: 0,
+        next_bits: 256,
+        next_bits2: 258,
+    };
+    writer.out.extend([0; 4]);
+    let mut last = std::collections::HashMap::new();
+    let mut pos = 0;
+    while pos < data.len() {
+        let key = data.get(pos..pos + 3);
+        let candidate = key.and_then(|key| last.insert(key, pos));
+        let length = candidate.map_or(0, |start: usize| {
+            (0..data.len() - pos)
+                .take_while(|&i| data[start + i] == data[pos + i])
+                .count()
+                .min(65535 + 3)
+        });
+        if length < 3 {
+            writer.bits(u32::from(data[pos]), 9);
+            pos += 1;
+            continue;
+        }
+        let offset = (pos - candidate.unwrap()) as u32;
+        let offset_bits = 31 - offset.leading_zeros();
+        let adjusted = length - 3;
+        writer.bits(256 + ((offset_bits << 4) | adjusted.min(15) as u32), 9);
+        if adjusted >= 15 + 255 {
+            writer.out.push(0xFF);
+            writer.out.extend((adjusted as u16).to_le_bytes());
+        } else if adjusted >= 15 {
+            writer.out.push((adjusted - 15) as u8);
+        }
+        writer.bits(offset ^ (1 << offset_bits), offset_bits);
+        for i in pos + 1..pos + length {
+            if let Some(key) = data.get(i..i + 3) {
+                last.insert(key, i);
+            }
+        }
+        pos += length;
+    }
+    let word = (writer.bitbuf << (16 - writer.bitcount)) as u16;
+    writer.put(writer.next_bits, word);
+    writer.out
+}
+
+/// LZX 压缩一个分块（不超过 32 KiB）：单个逐字块，主树符号使用 9 位码长、长度树符号使用 8 位码长
+/// （码字即符号值），贪心匹配上一次出现的 3 字节序列，偏移与最近偏移相同时使用重复偏移槽
+pub fn lzx_compress(data: &[u8]) -> Vec<u8> {
+    struct Writer {
+        out: Vec<u8>,
+        bitbuf: u32,
+        bitcount: u32,
+    }
+    impl Writer {
+        fn bits(&mut self, bits: u32, count: u32) {
+            for i in (0..count).rev() {
+                self.bitbuf = (self.bitbuf << 1) | ((bits >> i) & 1);
+                self.bitcount += 1;
+                if self.bitcount == 16 {
+                    self.out.extend((self.bitbuf as u16).to_le_bytes());
+                    self.bitbuf = 0;
+                    self.bitcount = 0;
+                }
+            }
+        }
+        /// 预树（20 个符号均为 5 位）及用它编码的一组码长（相对全零）
+        fn lens(&mut self, count: usize, len: u32) {
+            for _ in 0..20 {
+                self.bits(5, 4);
+            }
+            for _ in 0..count {
+                self.bits((17 - len) % 17, 5);
+            }
+        }
+    }
+    const NUM_SLOTS: usize = 30;
+    let extra_bits = |slot: usize| if slot < 4 { 0 } else { (slot as u32 - 2) / 2 };
+    let mut bases = vec![0usize];
+    for slot in 0..NUM_SLOTS {
+        bases.push(bases[slot] + (1 << extra_bits(slot)));
+    }
+
+    // E8 转换：CALL 指令的相对地址改为绝对地址
+    let mut data = data.to_vec();
+    let mut pos = 0;
+    while data.len() > 10 && pos < data.len() - 10 {
+        if data[pos] == 0xE8 {
+            let target = &mut data[pos + 1..pos + 5];
+            let rel = i32::from_le_bytes(target.try_into().unwrap());
+            let input_pos = pos as i32;
+            if rel >= -input_pos && rel < 12_000_000 {
+                let abs = if rel < 12_000_000 - input_pos {
+                    rel + input_pos
+                } else {
+                    rel - 12_000_000
+                };
+                target.copy_from_slice(&abs.to_le_bytes());
+            }
+            pos += 5;
+        } else {
+            pos += 1;
+        }
+    }
+
+    let mut writer = Writer {
+        out: Vec::new(),
+        bitbuf: 0,
+        bitcount: 0,
+    };
+    writer.bits(1, 3);
+    if data.len() == 32768 {
+        writer.bits(1, 1);
+    } else {
+        writer.bits(0, 1);
+        writer.bits(data.len() as u32, 16);
+    }
+    writer.lens(256, 9);
+    writer.lens(NUM_SLOTS * 8, 9);
+    writer.lens(249, 8);
+
+    let mut last = std::collections::HashMap::new();
+    let mut recent = 1;
+    let mut pos = 0;
+    while pos < data.len() {
+        let key = data.get(pos..pos + 3);
+        let candidate = key.and_then(|key| last.insert(key, pos));
+        let length = candidate.map_or(0, |start: usize| {
+            (0..data.len() - pos)
+                .take_while(|&i| data[start + i] == data[pos + i])
+                .count()
+                .min(257)
+        });
+        if length < 3 {
+            writer.bits(u32::from(data[pos]), 9);
+            pos += 1;
+            continue;
+        }
+        let offset = pos - candidate.unwrap();
+        let slot = if offset == recent {
+            0
+        } else {
+            (3..NUM_SLOTS)
+                .rev()
+                .find(|&slot| bases[slot] <= offset + 2)
+                .unwrap()
+        };
+        let len_header = (length - 2).min(7);
+        writer.bits((256 + slot * 8 + len_header) as u32, 9);
+        if len_header == 7 {
+            writer.bits((length - 9) as u32, 8);
+        }
+        if slot >= 3 {
+            writer.bits((offset + 2 - bases[slot]) as u32, extra_bits(slot));
+            recent = offset;
+        }
+        for i in pos + 1..pos + length {
+            if let Some(key) = data.get(i..i + 3) {
+                last.insert(key, i);
+            }
+        }
+        pos += length;
+    }
+    if writer.bitcount > 0 {
+        writer.bits(0, 16 - writer.bitcount);
+    }
+    writer.out
+}
+
+/// LZMS 压缩时 Huffman 码长的计算（与解压端的算法相同，结果必须逐位一致）
+fn lzms_code_lengths(freqs: &[u32]) -> Vec<u32> {
+    const MAX_LEN: usize = 15;
+    let n = freqs.len();
+    if n < 2 {
+        return vec![1; n];
+    }
+    let mut order: Vec<usize> = (0..n).collect();
+    order.sort_by_key(|&symbol| (freqs[symbol], symbol));
+    let mut node_freqs = vec![0u32; n - 1];
+    let mut parents = vec![0usize; n - 1];
+    let (mut leaf, mut node) = (0, 0);
+    for new in 0..n - 1 {
+        for _ in 0..2 {
+            if leaf < n && (node == new || freqs[order[leaf]] <= node_freqs[node]) {
+                node_freqs[new] += freqs[order[leaf]];
+                leaf += 1;
+            } else {
+                node_freqs[new] += node_freqs[node];
+                parents[node] = new;
+                node += 1;
+            }
+        }
+    }
+    let mut counts = [0u32; MAX_LEN + 1];
+    counts[1] = 2;
+    let mut depths = vec![0usize; n - 1];
+    for node in (0..n - 2).rev() {
+        depths[node] = depths[parents[node]] + 1;
+        let mut len = depths[node].min(MAX_LEN);
+        if len == MAX_LEN {
+            len -= 1;
+            while counts[len] == 0 {
+                len -= 1;
+            }
+        }
+        counts[len] -= 1;
+        counts[len + 1] += 2;
+    }
+    let mut lens = vec![0u32; n];
+    let mut sorted = order.into_iter();
+    for len in (1..=MAX_LEN).rev() {
+        for symbol in sorted.by_ref().take(counts[len] as usize) {
+            lens[symbol] = len as u32;
+        }
+    }
+    lens
+}
+
+/// LZMS 的偏移槽和长度槽：由基址之差的游程展开的基址（末尾附加上界）和附加位数
+fn lzms_slots(runs: &[u32], limit: u32) -> (Vec<u32>, Vec<u32>) {
+    let (mut bases, mut extra, mut base) = (Vec::new(), Vec::new(), 0u32);
+    for (order, &run) in runs.iter().enumerate() {
+        for _ in 0..run {
+            if !bases.is_empty() {
+                extra.push(order as u32);
+            }
+            base += 1 << order;
+            bases.push(base);
+        }
+    }
+    extra.push((limit - base).ilog2());
+    bases.push(limit);
+    (bases, extra)
+}
+
+/// LZMS 的 x86 地址转换（压缩方向）：可能的相对寻址指令后的 32 位地址加上指令位置
+fn lzms_x86_filter(data: &mut [u8]) {
+    if data.len() <= 17 {
+        return;
+    }
+    let mut last_usages = vec![-65536i32; 65536];
+    let mut last_x86 = -1024i32;
+    let tail = data.len() - 16;
+    let saved = data[tail];
+    data[tail] = 0xE8;
+    let mut p = 0;
+    while p < tail {
+        let (opcode_len, max_offset) = match (data[p], data[p + 1], data[p + 2]) {
+            (0x48, 0x8B, 0x05 | 0x0D) => (3, 1023),
+            (0x48 | 0x4C, 0x8D, modrm) if modrm & 7 == 5 => (3, 1023),
+            (0xE8, _, _) => (1, 511),
+            (0xE9, _, _) => {
+                p += 5;
+                continue;
+            }
+            (0xF0, 0x83, 0x05) => (3, 1023),
+            (0xFF, 0x15, _) => (2, 1023),
+            _ => {
+                p += 1;
+                continue;
+            }
+        };
+        let pos = p as i32;
+        p += opcode_len;
+        let operand = &mut data[p..p + 4];
+        let target = (pos as u16).wrapping_add(u16::from_le_bytes([operand[0], operand[1]]));
+        if pos - last_x86 <= max_offset {
+            let value = u32::from_le_bytes(operand.try_into().unwrap());
+            operand.copy_from_slice(&value.wrapping_add(pos as u32).to_le_bytes());
+        }
+        let end = pos + opcode_len as i32 + 3;
+        if end - last_usages[usize::from(target)] <= 65535 {
+            last_x86 = end;
+        }
+        last_usages[usize::from(target)] = end;
+        p += 4;
+    }
+    data[tail] = saved;
+}
+
+/// LZMS 压缩一个分块：与解压端同步维护自适应概率和定期重建的 Huffman 码，只输出字面量和 LZ 匹配
+/// （贪心匹配上一次出现的 3 字节序列，偏移在最近偏移队列中时使用重复匹配），压缩前做 x86 地址转换
+pub fn lzms_compress(data: &[u8]) -> Vec<u8> {
+    #[derive(Clone, Copy)]
+    struct Prob {
+        zeros: u32,
+        recent: u64,
+    }
+    struct Decision {
+        state: usize,
+        probs: Vec<Prob>,
+    }
+    impl Decision {
+        fn new(count: usize) -> Self {
+            let initial = Prob {
+                zeros: 48,
+                recent: 0x5555_5555,
+            };
+            Self {
+                state: 0,
+                probs: vec![initial; count],
+            }
+        }
+    }
+    /// 区间编码器：进位在输出前传递，第一个（哑）单元不输出
+    struct RangeEncoder {
+        low: u64,
+        range: u32,
+        cache: u16,
+        cache_size: u32,
+        skip: bool,
+        out: Vec<u16>,
+    }
+    impl RangeEncoder {
+        fn shift_low(&mut self) {
+            if (self.low as u32) < 0xFFFF_0000 || (self.low >> 32) != 0 {
+                let carry = (self.low >> 32) as u16;
+                loop {
+                    if self.skip {
+                        self.skip = false;
+                    } else {
+                        self.out.push(self.cache.wrapping_add(carry));
+                    }
+                    self.cache = 0xFFFF;
+                    self.cache_size -= 1;
+                    if self.cache_size == 0 {
+                        break;
+                    }
+                }
+                self.cache = (self.low >> 16) as u16;
+            }
+            self.cache_size += 1;
+            self.low = (self.low & 0xFFFF) << 16;
+        }
+        fn encode(&mut self, decision: &mut Decision, bit: bool) {
+            let bit = u32::from(bit);
+            let count = decision.probs.len();
+            let prob = &mut decision.probs[decision.state];
+            let bound = (self.range >> 6) * prob.zeros.clamp(1, 63);
+            if bit == 0 {
+                self.range = bound;
+            } else {
+                self.low += u64::from(bound);
+                self.range -= bound;
+            }
+            prob.zeros = prob.zeros + (prob.recent >> 63) as u32 - bit;
+            prob.recent = (prob.recent << 1) | u64::from(bit);
+            decision.state = ((decision.state << 1) | bit as usize) & (count - 1);
+            if self.range & 0xFFFF_0000 == 0 {
+                self.range <<= 16;
+                self.shift_low();
+            }
+        }
+    }
+    /// 反向比特流：按写入顺序收集 16 位字，最后整体倒序放在数据末尾
+    struct BitWriter {
+        bitbuf: u64,
+        count: u32,
+        words: Vec<u16>,
+    }
+    impl BitWriter {
+        fn put(&mut self, bits: u32, count: u32) {
+            self.bitbuf = (self.bitbuf << count) | u64::from(bits);
+            self.count += count;
+            while self.count >= 16 {
+                self.count -= 16;
+                self.words.push((self.bitbuf >> self.count) as u16);
+            }
+        }
+    }
+    struct Code {
+        freqs: Vec<u32>,
+        rebuild_freq: u32,
+        until_rebuild: u32,
+        codes: Vec<(u32, u32)>,
+    }
+    impl Code {
+        fn new(count: usize, rebuild_freq: u32) -> Self {
+            Self {
+                freqs: vec![1; count],
+                rebuild_freq,
+                until_rebuild: 1,
+                codes: Vec::new(),
+            }
+        }
+        fn encode(&mut self, writer: &mut BitWriter, symbol: usize) {
+            self.until_rebuild -= 1;
+            if self.until_rebuild == 0 {
+                let lens = lzms_code_lengths(&self.freqs);
+                self.codes = vec![(0, 0); lens.len()];
+                let mut code = 0;
+                for len in 1..=15 {
+                    for (symbol, _) in lens.iter().enumerate().filter(|(_, &l)| l == len) {
+                        self.codes[symbol] = (code, len);
+                        code += 1;
+                    }
+                    code <<= 1;
+                }
+                for freq in &mut self.freqs {
+                    *freq = (*freq >> 1) + 1;
+                }
+                self.until_rebuild = self.rebuild_freq;
+            }
+            let (code, len) = self.codes[symbol];
+            writer.put(code, len);
+            self.freqs[symbol] += 1;
+        }
+    }
+
+    let (offset_bases, offset_extra) = lzms_slots(
+        &[
+            9, 0, 9, 7, 10, 15, 15, 20, 20, 30, 33, 40, 42, 45, 60, 73, 80, 85, 95, 105, 6,
+        ],
+        0x7FFF_FFFF,
+    );
+    let (length_bases, length_extra) = lzms_slots(
+        &[27, 4, 6, 4, 5, 2, 1, 1, 1, 1, 1, 0, 0, 0, 0, 0, 1],
+        0x4001_08AB,
+    );
+    let slot = |bases: &[u32], value: u32| bases.iter().rposition(|&base| base <= value).unwrap();
+    let num_offset_slots = if data.len() < 2 {
+        0
+    } else {
+        1 + slot(
+            &offset_bases[..offset_bases.len() - 1],
+            data.len() as u32 - 1,
+        )
+    };
+
+    let mut data = data.to_vec();
+    lzms_x86_filter(&mut data);
+
+    let mut rc = RangeEncoder {
+        low: 0,
+        range: 0xFFFF_FFFF,
+        cache: 0,
+        cache_size: 1,
+        skip: true,
+        out: Vec::new(),
+    };
+    let mut bits = BitWriter {
+        bitbuf: 0,
+        count: 0,
+        words: Vec::new(),
+    };
+    let (mut main, mut matches, mut lz) = (Decision::new(16), Decision::new(32), Decision::new(64));
+    let mut lz_reps = [Decision::new(64), Decision::new(64)];
+    let mut literals = Code::new(256, 1024);
+    let mut offsets = Code::new(num_offset_slots, 1024);
+    let mut lengths = Code::new(54, 512);
+
+    let mut last = std::collections::HashMap::new();
+    let mut recent = [1usize, 2, 3, 4];
+    let mut previous_match = false;
+    let mut pos = 0;
+    while pos < data.len() {
+        let key = data.get(pos..pos + 3);
+        let candidate = key.and_then(|key| last.insert(key, pos));
+        let length = candidate.map_or(0, |start: usize| {
+            (0..data.len() - pos)
+                .take_while(|&i| data[start + i] == data[pos + i])
+                .count()
+                .min(1000)
+        });
+        if length < 3 {
+            rc.encode(&mut main, false);
+            literals.encode(&mut bits, usize::from(data[pos]));
+            previous_match = false;
+            pos += 1;
+            continue;
+        }
+
+        let offset = pos - candidate.unwrap();
+        rc.encode(&mut main, true);
+        rc.encode(&mut matches, false);
+        // 上一个条目是 LZ 匹配时，它的偏移尚未进入队列，最近偏移 i 位于 i + 1
+        let skip = usize::from(previous_match);
+        match (0..3).find(|&rep| recent[rep + skip] == offset) {
+            Some(rep) => {
+                rc.encode(&mut lz, true);
+                rc.encode(&mut lz_reps[0], rep > 0);
+                if rep > 0 {
+                    rc.encode(&mut lz_reps[1], rep > 1);
+                }
+                recent[rep + skip] = recent[rep];
+                recent.copy_within(0..rep, 1);
+            }
+            None => {
+                rc.encode(&mut lz, false);
+                let slot = slot(&offset_bases, offset as u32);
+                offsets.encode(&mut bits, slot);
+                bits.put(offset as u32 - offset_bases[slot], offset_extra[slot]);
+                recent.copy_within(0..3, 1);
+            }
+        }
+        recent[0] = offset;
+        previous_match = true;
+        let slot = slot(&length_bases, length as u32);
+        lengths.encode(&mut bits, slot);
+        bits.put(length as u32 - length_bases[slot], length_extra[slot]);
+
+        for i in pos + 1..pos + length {
+            if let Some(key) = data.get(i..i + 3) {
+                last.insert(key, i);
+            }
+        }
+        pos += length;
+    }
+
+    for _ in 0..4 {
+        rc.shift_low();
+    }
+    if bits.count > 0 {
+        bits.put(0, 16 - bits.count);
+    }
+    rc.out
+        .iter()
+        .chain(bits.words.iter().rev())
+        .flat_map(|word| word.to_le_bytes())
+        .collect()
+}
+
+/// 固实资源中的一组数据流
+pub struct SolidGroup<'a> {
+    /// 资源头中的压缩格式（1 = XPRESS，2 = LZX，3 = LZMS）
+    pub format: u32,
+    pub chunk: u32,
+    pub compress: fn(&[u8]) -> Vec<u8>,
+    pub streams: Vec<&'a [u8]>,
+}
+
+/// 将数据流改写为固实资源 (ESD) 中的数据流：每组数据流拼接后分块压缩为一个固实资源（不能变小的分块按原样存储），
+/// 固实资源追加到文件末尾；新的偏移表以这组固实资源的条目开头，数据流条目的偏移为在所有资源拼接后的数据中的位置
+pub fn solid_streams(mut bytes: Vec<u8>, flags: u32, groups: &[SolidGroup]) -> Vec<u8> {
+    bytes[12..16].copy_from_slice(&0xE00u32.to_le_bytes());
+    bytes[16..20].copy_from_slice(&flags.to_le_bytes());
+
+    let mut size = [0u8; 8];
+    size[..7].copy_from_slice(&bytes[48..55]);
+    let lookup_offset = u64::from_le_bytes(bytes[56..64].try_into().unwrap()) as usize;
+    let mut entries: Vec<Vec<u8>> = bytes
+        [lookup_offset..lookup_offset + u64::from_le_bytes(size) as usize]
+        .chunks(50)
+        .map(<[u8]>::to_vec)
+        .collect();
+
+    let mut solid_entries = Vec::new();
+    let mut position = 0u64;
+    for group in groups {
+        for stream in &group.streams {
+            let hash = sha1_hash(stream);
+            let entry = entries
+                .iter_mut()
+                .find(|entry| entry[30..50] == hash)
+                .unwrap();
+            let len = stream.len() as u64;
+            entry[0..24].copy_from_slice(&reshdr(len, 0x10, position, len));
+            position += len;
+        }
+
+        let data = group.streams.concat();
+        let chunks: Vec<Vec<u8>> = data
+            .chunks(group.chunk as usize)
+            .map(|chunk| {
+                let packed = (group.compress)(chunk);
+                if packed.len() < chunk.len() {
+                    packed
+                } else {
+                    chunk.to_vec()
+                }
+            })
+            .collect();
+        let mut resource = Vec::new();
+        resource.extend((data.len() as u64).to_le_bytes());
+        resource.extend(group.chunk.to_le_bytes());
+        resource.extend(group.format.to_le_bytes());
+        for chunk in &chunks {
+            resource.extend((chunk.len() as u32).to_le_bytes());
+        }
+        for chunk in &chunks {
+            resource.extend(chunk);
+        }
+
+        let mut entry = reshdr(
+            resource.len() as u64,
+            0x14,
+            bytes.len() as u64,
+            0x1_0000_0000,
+        );
+        entry.extend(1u16.to_le_bytes());
+        entry.extend(1u32.to_le_bytes());
+        entry.extend([0u8; 20]);
+        solid_entries.push(entry);
+        bytes.extend(resource);
+    }
+
+    let lookup: Vec<u8> = solid_entries.into_iter().chain(entries).flatten().collect();
+    let offset = bytes.len() as u64;
+    bytes[48..72].copy_from_slice(&reshdr(
+        lookup.len() as u64,
+        0x02,
+        offset,
+        lookup.len() as u64,
+    ));
+    bytes.extend(lookup);
+    bytes
+}
+
+/// 将构造的 WIM 写入临时文件
+pub fn write_wim(images: &[ImageSpec]) -> NamedTempFile {
+    write_bytes(&build_wim(images))
+}
+
+/// 在 WIM 数据末尾追加完整性表（覆盖文件头之后到偏移表结尾），并更新文件头
+pub fn add_integrity_table(bytes: &mut Vec<u8>, chunk_size: u32) {
+    let lookup_offset = u64::from_le_bytes(bytes[56..64].try_into().unwrap()) as usize;
+    let mut lookup_size = [0u8; 8];
+    lookup_size[..7].copy_from_slice(&bytes[48..55]);
+    let region_end = lookup_offset + u64::from_le_bytes(lookup_size) as usize;
+
+    let hashes: Vec<[u8; 20]> = bytes[HEADER_SIZE..region_end]
+        .chunks(chunk_size as usize)
+        .map(sha1_hash)
+        .collect();
+
+    let mut table = Vec::new();
+    table.extend((12 + hashes.len() as u32 * 20).to_le_bytes());
+    table.extend((hashes.len() as u32).to_le_bytes());
+    table.extend(chunk_size.to_le_bytes());
+    for hash in &hashes {
+        table.extend(hash);
+    }
+
+    let offset = bytes.len() as u64;
+    bytes[124..148].copy_from_slice(&reshdr(table.len() as u64, 0, offset, table.len() as u64));
+    bytes.extend(table);
+}
+
+/// 将（修改过的）WIM 数据写入临时文件
+pub fn write_bytes(bytes: &[u8]) -> NamedTempFile {
+    let mut file = NamedTempFile::new().unwrap();
+    file.write_all(bytes).unwrap();
+    file.flush().unwrap();
+    file
+}
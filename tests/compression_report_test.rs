@@ -0,0 +1,65 @@
+#![cfg(feature = "parser")]
+
+mod common;
+
+use common::{build_wim, sha1_hash, write_bytes, ImageSpec};
+use wim_parser::fmt::ToTable;
+use wim_parser::WimParser;
+
+const OFFSET_TABLE_RESHDR: usize = 48;
+
+/// 将偏移表中指定数据流的存储大小改为 `size`（模拟压缩后的大小）
+fn set_stored_size(bytes: &mut [u8], data: &[u8], size: u64) {
+    let table_offset = u64::from_le_bytes(
+        bytes[OFFSET_TABLE_RESHDR + 8..OFFSET_TABLE_RESHDR + 16]
+            .try_into()
+            .unwrap(),
+    ) as usize;
+    let hash = sha1_hash(data);
+    let entry = (table_offset..)
+        .step_by(50)
+        .find(|&entry| bytes[entry + 30..entry + 50] == hash)
+        .unwrap();
+    bytes[entry..entry + 7].copy_from_slice(&size.to_le_bytes()[..7]);
+}
+
+/// 测试按文件类型和镜像统计压缩率
+#[test]
+fn test_compression_report() {
+    let text = [b'a'; 100];
+    let cab = [b'c'; 50];
+    let mut bytes = build_wim(&[
+        ImageSpec::new("Image A")
+            .file("/readme.TXT", &text)
+            .file("/data.cab", &cab),
+        ImageSpec::new("Image B").file("/copy.txt", &text),
+    ]);
+    set_stored_size(&mut bytes, &text, 25);
+    set_stored_size(&mut bytes, &cab, 50);
+    let wim = write_bytes(&bytes);
+
+    let mut parser = WimParser::new(wim.path()).unwrap();
+    let report = parser.compression_report().unwrap();
+
+    assert_eq!(report.stream_count, 2);
+    assert_eq!(report.total.stored_bytes, 75);
+    assert_eq!(report.total.logical_bytes, 150);
+    assert_eq!(report.total.ratio(), Some(0.5));
+    assert_eq!(report.total.saved_bytes(), 75);
+    assert_eq!(report.solid_streams, 0);
+
+    let best = &report.best_types(1)[0];
+    assert_eq!(best.extension, "txt");
+    assert_eq!(best.streams, 1);
+    assert_eq!(best.ratio.ratio(), Some(0.25));
+    assert_eq!(report.worst_types(1)[0].extension, "cab");
+
+    assert_eq!(report.images.len(), 2);
+    assert_eq!(report.images[0].streams, 2);
+    assert_eq!(report.images[0].ratio.stored_bytes, 75);
+    assert_eq!(report.images[1].ratio.ratio(), Some(0.25));
+
+    let table = report.table();
+    assert_eq!(table.rows()[0][4], "50.0%");
+    assert_eq!(table.rows().len(), 2 + 2 + 2);
+}
@@ -0,0 +1,103 @@
+#![cfg(feature = "parser")]
+
+mod common;
+
+use common::{build_wim, write_wim, ImageSpec};
+use std::io::Write;
+use wim_parser::{
+    format, Compression, FileResourceEntry, ResourceFlags, WimParser, SOLID_RESOURCE_MAGIC,
+};
+
+/// 修改构造 WIM 的文件标志和分块大小字段
+fn with_flags(mut bytes: Vec<u8>, flags: u32, chunk: u32) -> Vec<u8> {
+    bytes[16..20].copy_from_slice(&flags.to_le_bytes());
+    bytes[20..24].copy_from_slice(&chunk.to_le_bytes());
+    bytes
+}
+
+/// 测试根据文件头标志识别压缩格式
+#[test]
+fn test_compression_from_header_flags() {
+    let bytes = build_wim(&[ImageSpec::new("Image A")]);
+    let cases = [
+        (0x0000_0000, 0, Compression::None),
+        (0x0002_0002, 0, Compression::Xpress { chunk: 32768 }),
+        (0x0004_0002, 32768, Compression::Lzx { chunk: 32768 }),
+        (0x0008_0002, 131072, Compression::Lzms { chunk: 131072 }),
+        (0x0020_0002, 65536, Compression::Xpress { chunk: 65536 }),
+        (0x0000_0002, 0, Compression::Unknown(0x0000_0002)),
+    ];
+
+    for (flags, chunk, expected) in cases {
+        let header = format::parse_header(&with_flags(bytes.clone(), flags, chunk)).unwrap();
+        assert_eq!(header.compression(), expected, "标志: 0x{flags:08X}");
+    }
+
+    let lzx = Compression::Lzx { chunk: 32768 };
+    assert!(lzx.is_compressed());
+    assert_eq!(lzx.chunk_size(), Some(32768));
+    assert_eq!(lzx.to_string(), "LZX (32 KiB 分块)");
+    assert_eq!(Compression::None.chunk_size(), None);
+}
+
+/// 测试按资源标志和固实资源头确定压缩格式
+#[test]
+fn test_resource_compression() {
+    let mut bytes = with_flags(build_wim(&[ImageSpec::new("Image A")]), 0x0004_0002, 0);
+
+    // 在文件末尾追加一个 LZMS 固实资源头
+    let solid_offset = bytes.len() as u64;
+    bytes.extend(1_000_000u64.to_le_bytes());
+    bytes.extend((1u32 << 26).to_le_bytes());
+    bytes.extend(3u32.to_le_bytes());
+
+    let mut file = tempfile::NamedTempFile::new().unwrap();
+    file.write_all(&bytes).unwrap();
+
+    let mut parser = WimParser::new(file.path()).unwrap();
+    parser.read_header().unwrap();
+    assert_eq!(parser.compression(), Compression::Lzx { chunk: 32768 });
+
+    let plain = FileResourceEntry {
+        size: 10,
+        flags: 0,
+        offset: 0,
+        original_size: 10,
+    };
+    assert_eq!(
+        parser.resource_compression(&plain).unwrap(),
+        Compression::None
+    );
+
+    let compressed = FileResourceEntry {
+        flags: ResourceFlags::COMPRESSED,
+        ..plain.clone()
+    };
+    assert_eq!(
+        parser.resource_compression(&compressed).unwrap(),
+        Compression::Lzx { chunk: 32768 }
+    );
+
+    let solid = FileResourceEntry {
+        size: 16,
+        flags: ResourceFlags::SOLID | ResourceFlags::COMPRESSED,
+        offset: solid_offset,
+        original_size: SOLID_RESOURCE_MAGIC,
+    };
+    assert_eq!(
+        parser.resource_compression(&solid).unwrap(),
+        Compression::Lzms { chunk: 1 << 26 }
+    );
+}
+
+/// 测试未压缩文件的兼容接口
+#[test]
+#[allow(deprecated)]
+fn test_uncompressed_wim() {
+    let wim = write_wim(&[ImageSpec::new("Image A")]);
+    let mut parser = WimParser::new(wim.path()).unwrap();
+    assert_eq!(parser.compression(), Compression::None);
+    parser.parse_full().unwrap();
+    assert_eq!(parser.compression(), Compression::None);
+    assert_eq!(parser.get_compression_type(), None);
+}
@@ -0,0 +1,166 @@
+#![cfg(feature = "verify")]
+
+mod common;
+
+use common::{sha1_hash, write_wim, ImageSpec};
+use wim_parser::{RewriteStrategy, WimParser};
+
+fn images() -> Vec<ImageSpec> {
+    vec![
+        ImageSpec::new("Home")
+            .dir("Windows")
+            .dir("Windows/Boot")
+            .dir("Windows/Boot/EFI")
+            .file("Windows/Boot/EFI/bootmgfw.efi", b"boot manager")
+            .file("Windows/Boot/BCD", b"new bcd")
+            .secured(),
+        ImageSpec::new("Pro")
+            .dir("Windows")
+            .dir("Windows/Boot")
+            .file("Windows/Boot/BCD", b"old bcd")
+            .file("Windows/Boot/pro.txt", b"pro only"),
+        ImageSpec::new("Enterprise").file("ent.txt", b"enterprise"),
+    ]
+}
+
+/// 测试镜像间复制目录：合并同名目录、替换同名文件、共享数据流并更新计数
+#[test]
+fn test_copy_path_between_images() {
+    let wim = write_wim(&images());
+    let mut parser = WimParser::new(wim.path()).unwrap();
+
+    let plan = parser
+        .transaction()
+        .copy_path(1, "/Windows/Boot", 2)
+        .copy_path(1, "\\windows\\boot\\EFI", 3)
+        .commit()
+        .unwrap();
+    assert_eq!(plan.strategy, RewriteStrategy::Rebuild);
+    assert_eq!(plan.image_count, 3);
+    assert_eq!(plan.added_streams, 0);
+    // "old bcd" 不再被引用
+    assert_eq!(plan.freed_streams, 1);
+    assert_eq!(plan.freed_bytes, b"old bcd".len() as u64);
+
+    let pro = parser.read_image_metadata(2).unwrap();
+    let boot = pro.root.find("Windows/Boot").unwrap();
+    assert_eq!(boot.children.len(), 3);
+    assert_eq!(
+        pro.root.find("Windows/Boot/BCD").unwrap().hash,
+        sha1_hash(b"new bcd")
+    );
+    assert!(pro.root.find("Windows/Boot/pro.txt").is_some());
+    let efi = pro.root.find("Windows/Boot/EFI/bootmgfw.efi").unwrap();
+    assert_eq!(efi.hash, sha1_hash(b"boot manager"));
+    // 安全描述符随目录项复制
+    assert_eq!(pro.security_descriptors.len(), 1);
+    assert_eq!(pro.security_descriptor(efi).unwrap().len(), 20);
+
+    // 缺少的上级目录按源镜像创建
+    let enterprise = parser.read_image_metadata(3).unwrap();
+    assert!(enterprise.root.find("Windows").unwrap().is_directory());
+    assert!(enterprise.root.find("Windows/EFI/bootmgfw.efi").is_none());
+    assert!(enterprise
+        .root
+        .find("Windows/Boot/EFI/bootmgfw.efi")
+        .is_some());
+
+    for index in 1..=3 {
+        assert!(parser.recount_image(index, false).unwrap().is_consistent());
+    }
+    assert!(parser.verify_all_streams().unwrap().is_ok());
+    // 引用计数包含复制后的引用：删除源镜像不会丢弃被复制的数据流
+    let delete = parser.plan_delete_image(1).unwrap();
+    assert!(delete.conflicts.is_empty());
+    assert!(delete.freed_streams.is_empty());
+}
+
+/// 测试从其他 WIM 复制：追加目标文件中没有的数据流
+#[test]
+fn test_copy_path_from_other_wim() {
+    let source = write_wim(&[ImageSpec::new("Source")
+        .dir("Tools")
+        .file("Tools/tool.exe", b"tool binary")
+        .file("Tools/shared.txt", b"enterprise")]);
+    let target = write_wim(&images());
+    let mut from = WimParser::new(source.path()).unwrap();
+    let mut parser = WimParser::new(target.path()).unwrap();
+
+    let plan = parser
+        .transaction()
+        .copy_path_from(&mut from, 1, "/Tools", 3)
+        .unwrap()
+        .commit()
+        .unwrap();
+    assert_eq!(plan.added_streams, 1);
+    assert_eq!(plan.added_bytes, b"tool binary".len() as u64);
+
+    let metadata = parser.read_image_metadata(3).unwrap();
+    assert_eq!(
+        metadata.root.find("Tools/tool.exe").unwrap().hash,
+        sha1_hash(b"tool binary")
+    );
+    assert!(parser.recount_image(3, false).unwrap().is_consistent());
+    assert!(parser.verify_all_streams().unwrap().is_ok());
+    let data = parser.read_stream(&sha1_hash(b"tool binary")).unwrap();
+    assert_eq!(data, b"tool binary");
+}
+
+/// 测试复制的错误情况
+#[test]
+fn test_copy_path_errors() {
+    let wim = write_wim(&images());
+    let mut parser = WimParser::new(wim.path()).unwrap();
+
+    let error = parser.copy_path(1, "/Windows/Missing", 2).unwrap_err();
+    assert!(format!("{error:#}").contains("找不到路径"));
+    assert!(parser.copy_path(1, "/", 2).is_err());
+    assert!(parser.copy_path(1, "/Windows", 1).is_err());
+    assert!(parser.copy_path(1, "/Windows", 4).is_err());
+    // 路径中间是文件
+    let error = parser
+        .copy_path(2, "/Windows/Boot/pro.txt/x", 1)
+        .unwrap_err();
+    assert!(format!("{error:#}").contains("不是目录"));
+    assert!(parser
+        .transaction()
+        .copy_path(1, "/Windows", 3)
+        .delete_image(3)
+        .plan()
+        .is_err());
+
+    parser.parse_full().unwrap();
+    assert_eq!(parser.get_image_count(), 3);
+    assert!(parser
+        .read_image_metadata(2)
+        .unwrap()
+        .root
+        .find("Windows/Boot/EFI")
+        .is_none());
+}
+
+/// 测试重写目录树时短文件名保持不变（根目录名称为空，短文件名前没有空终止符）
+#[test]
+fn test_copy_path_keeps_short_names() {
+    let wim = write_wim(&[
+        ImageSpec::new("Home").file("Program Files.txt", b"home"),
+        ImageSpec::new("Pro")
+            .file("Long File Name.txt", b"pro")
+            .short_name("", "ROOT")
+            .short_name("Long File Name.txt", "LONGFI~1.TXT"),
+    ]);
+    let mut parser = WimParser::new(wim.path()).unwrap();
+    let metadata = parser.read_image_metadata(2).unwrap();
+    assert_eq!(metadata.root.short_name, "ROOT");
+
+    parser
+        .transaction()
+        .copy_path(1, "/Program Files.txt", 2)
+        .commit()
+        .unwrap();
+    let metadata = parser.read_image_metadata(2).unwrap();
+    assert_eq!(metadata.root.short_name, "ROOT");
+    let file = metadata.root.find("Long File Name.txt").unwrap();
+    assert_eq!(file.short_name, "LONGFI~1.TXT");
+    assert!(metadata.root.find("Program Files.txt").is_some());
+}
@@ -0,0 +1,69 @@
+#![cfg(feature = "parser")]
+
+mod common;
+
+use common::{build_wim, sha1_hash, write_bytes, write_wim, ImageSpec};
+use wim_parser::fmt::ToTable;
+use wim_parser::{DeleteOptions, ResourceFlags, WimParser};
+
+const OFFSET_TABLE_RESHDR: usize = 48;
+
+fn specs() -> Vec<ImageSpec> {
+    vec![
+        ImageSpec::new("Image A")
+            .file("/shared.txt", b"shared")
+            .file("/only-a.txt", b"only in a"),
+        ImageSpec::new("Image B").file("/shared.txt", b"shared"),
+    ]
+}
+
+/// 测试引用计数正确时的删除检查
+#[test]
+fn test_plan_delete_image() {
+    let wim = write_wim(&specs());
+    let mut parser = WimParser::new(wim.path()).unwrap();
+
+    let plan = parser.plan_delete_image(1).unwrap();
+    assert!(plan.is_safe());
+    assert_eq!(plan.freed_streams, [sha1_hash(b"only in a")]);
+    assert_eq!(plan.freed_bytes, 9);
+    assert_eq!(plan.shared_streams, 1);
+    assert_eq!(plan.table().rows().len(), 5);
+
+    assert!(parser.plan_delete_image(3).is_err());
+}
+
+/// 测试引用计数偏小时拒绝删除，强制选项仍返回计划
+#[test]
+fn test_plan_delete_image_refcount_conflict() {
+    let mut bytes = build_wim(&specs());
+    let table_offset = u64::from_le_bytes(
+        bytes[OFFSET_TABLE_RESHDR + 8..OFFSET_TABLE_RESHDR + 16]
+            .try_into()
+            .unwrap(),
+    ) as usize;
+    let shared = sha1_hash(b"shared");
+    let entry = (table_offset..)
+        .step_by(50)
+        .find(|&entry| {
+            bytes[entry + 7] & ResourceFlags::METADATA == 0
+                && bytes[entry + 30..entry + 50] == shared
+        })
+        .unwrap();
+    bytes[entry + 26..entry + 30].copy_from_slice(&1u32.to_le_bytes());
+    let wim = write_bytes(&bytes);
+
+    let mut parser = WimParser::new(wim.path()).unwrap();
+    let message = parser.plan_delete_image(1).unwrap_err().to_string();
+    assert!(message.contains("仍被其他镜像引用"), "{message}");
+
+    let plan = parser
+        .plan_delete_image_with(1, &DeleteOptions::new().force(true))
+        .unwrap();
+    assert!(!plan.is_safe());
+    assert!(plan.forced);
+    assert_eq!(plan.conflicts.len(), 1);
+    assert_eq!(plan.conflicts[0].hash, shared);
+    assert_eq!(plan.conflicts[0].ref_count, 1);
+    assert_eq!(plan.conflicts[0].other_images, [2]);
+}
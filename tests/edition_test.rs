@@ -0,0 +1,186 @@
+#![cfg(feature = "parser")]
+
+mod common;
+
+use common::{write_wim, ImageSpec};
+use wim_parser::fmt::ToTable;
+use wim_parser::{edition_display_name, format, Edition, ImageChannel, SwitchKind, WimParser};
+
+fn windows_xml(arch: u32, edition_id: &str, build: u32) -> String {
+    format!(
+        "<WINDOWS><ARCH>{arch}</ARCH><EDITIONID>{edition_id}</EDITIONID>\
+         <VERSION><MAJOR>10</MAJOR><BUILD>{build}</BUILD></VERSION></WINDOWS>"
+    )
+}
+
+/// 测试按版本和架构分组
+#[test]
+fn test_edition_summary() {
+    let wim = write_wim(&[
+        ImageSpec::new("Windows 11 Home").extra_xml(&windows_xml(9, "Core", 22621)),
+        ImageSpec::new("Windows 11 Pro").extra_xml(&windows_xml(9, "Professional", 22621)),
+        ImageSpec::new("Windows 11 Pro").extra_xml(&windows_xml(12, "Professional", 22631)),
+        ImageSpec::new("Windows 11 Pro (legacy)")
+            .extra_xml(&windows_xml(9, "Professional", 22631))
+            .file("/a.txt", b"hello"),
+        ImageSpec::new("Windows 11 Education"),
+    ]);
+
+    let mut parser = WimParser::new(wim.path()).unwrap();
+    parser.parse_full().unwrap();
+    let groups = parser.edition_summary();
+
+    let editions: Vec<&str> = groups.iter().map(|g| g.edition.as_str()).collect();
+    assert_eq!(editions, ["Home", "Pro", "Pro", "Education"]);
+    let indexes: Vec<&[u32]> = groups.iter().map(|g| g.indexes.as_slice()).collect();
+    assert_eq!(indexes, [&[1][..], &[2, 4], &[3], &[5]]);
+    let arches: Vec<Option<&str>> = groups.iter().map(|g| g.architecture.as_deref()).collect();
+    assert_eq!(arches, [Some("x64"), Some("x64"), Some("ARM64"), None]);
+    let builds: Vec<Option<u32>> = groups.iter().map(|g| g.build).collect();
+    assert_eq!(builds, [Some(22621), Some(22631), Some(22631), None]);
+    assert_eq!(groups[1].edition_id.as_deref(), Some("Professional"));
+    assert_eq!(groups[1].total_bytes, 5);
+    assert_eq!(groups.table().rows()[1][1], "2, 4");
+
+    let info = parser.get_windows_info().unwrap();
+    assert_eq!(info.editions, ["Home", "Pro", "Education"]);
+}
+
+/// 测试根据镜像中的版本列出升级和降级路径
+#[test]
+fn test_upgrade_paths() {
+    let wim = write_wim(&[
+        ImageSpec::new("Windows 11 Home").extra_xml(&windows_xml(9, "Core", 22631)),
+        ImageSpec::new("Windows 11 Pro").extra_xml(&windows_xml(9, "Professional", 22631)),
+        ImageSpec::new("Windows 11 Pro").extra_xml(&windows_xml(12, "Professional", 22631)),
+        ImageSpec::new("Windows 11 Education").extra_xml(&windows_xml(9, "Education", 22631)),
+        ImageSpec::new("Custom").extra_xml(&windows_xml(9, "ContosoEdition", 22631)),
+    ]);
+    let mut parser = WimParser::new(wim.path()).unwrap();
+    parser.parse_full().unwrap();
+    let paths = parser.upgrade_paths();
+
+    let from_pro: Vec<(Edition, SwitchKind, bool)> = paths
+        .iter()
+        .filter(|path| path.from == Edition::Pro)
+        .map(|path| (path.to, path.kind, path.target_in_media))
+        .collect();
+    assert_eq!(
+        from_pro,
+        [
+            (Edition::ProEducation, SwitchKind::Upgrade, false),
+            (Edition::ProWorkstations, SwitchKind::Upgrade, false),
+            (Edition::Education, SwitchKind::Upgrade, true),
+            (Edition::Enterprise, SwitchKind::Upgrade, false),
+        ]
+    );
+    assert!(paths.iter().any(|path| path.from == Edition::Education
+        && path.to == Edition::Pro
+        && path.kind == SwitchKind::Downgrade));
+    assert!(!paths.iter().any(|path| path.to == Edition::Home));
+    assert_eq!(paths.table().rows()[0][0], "Home");
+
+    assert_eq!(
+        Edition::Enterprise.can_switch_to(Edition::Pro),
+        Some(SwitchKind::Downgrade)
+    );
+    assert_eq!(
+        Edition::HomeN.can_switch_to(Edition::ProN),
+        Some(SwitchKind::Upgrade)
+    );
+    assert_eq!(Edition::HomeN.can_switch_to(Edition::Pro), None);
+    assert_eq!(Edition::Pro.can_switch_to(Edition::Home), None);
+}
+
+/// 实际镜像 XML 中的 IoT、Team 和 SE 版本
+const REAL_WORLD_IMAGES: [(&str, &str, &str); 4] = [
+    (
+        "<IMAGE INDEX=\"1\"><WINDOWS><ARCH>9</ARCH><PRODUCTNAME>Microsoft® Windows® Operating System</PRODUCTNAME>\
+         <EDITIONID>IoTEnterpriseS</EDITIONID><INSTALLATIONTYPE>Client</INSTALLATIONTYPE>\
+         <PRODUCTTYPE>WinNT</PRODUCTTYPE><PRODUCTSUITE>Terminal Server</PRODUCTSUITE>\
+         <LANGUAGES><LANGUAGE>en-US</LANGUAGE><DEFAULT>en-US</DEFAULT></LANGUAGES>\
+         <VERSION><MAJOR>10</MAJOR><MINOR>0</MINOR><BUILD>26100</BUILD><SPBUILD>1742</SPBUILD></VERSION>\
+         <SYSTEMROOT>WINDOWS</SYSTEMROOT></WINDOWS><NAME>Windows 11 IoT Enterprise LTSC 2024</NAME>\
+         <DESCRIPTION>Windows 11 IoT Enterprise LTSC 2024</DESCRIPTION><FLAGS>IoTEnterpriseS</FLAGS>\
+         <DISPLAYNAME>Windows 11 IoT Enterprise LTSC 2024</DISPLAYNAME></IMAGE>",
+        "IoT Enterprise LTSC",
+        "IoTEnterpriseS",
+    ),
+    (
+        "<IMAGE INDEX=\"2\"><WINDOWS><ARCH>9</ARCH><EDITIONID>IoTEnterprise</EDITIONID>\
+         <INSTALLATIONTYPE>Client</INSTALLATIONTYPE><PRODUCTTYPE>WinNT</PRODUCTTYPE>\
+         <VERSION><MAJOR>10</MAJOR><MINOR>0</MINOR><BUILD>22621</BUILD></VERSION></WINDOWS>\
+         <NAME>Windows 11 IoT Enterprise</NAME><FLAGS>IoTEnterprise</FLAGS></IMAGE>",
+        "IoT Enterprise",
+        "IoTEnterprise",
+    ),
+    (
+        "<IMAGE INDEX=\"1\"><WINDOWS><ARCH>9</ARCH><EDITIONID>PPIPro</EDITIONID>\
+         <INSTALLATIONTYPE>Client</INSTALLATIONTYPE><PRODUCTTYPE>WinNT</PRODUCTTYPE>\
+         <VERSION><MAJOR>10</MAJOR><MINOR>0</MINOR><BUILD>19045</BUILD></VERSION></WINDOWS>\
+         <NAME>Windows 10 Team</NAME><FLAGS>PPIPro</FLAGS></IMAGE>",
+        "Team",
+        "PPIPro",
+    ),
+    (
+        "<IMAGE INDEX=\"1\"><WINDOWS><ARCH>9</ARCH><EDITIONID>CloudEdition</EDITIONID>\
+         <INSTALLATIONTYPE>Client</INSTALLATIONTYPE><PRODUCTTYPE>WinNT</PRODUCTTYPE>\
+         <VERSION><MAJOR>10</MAJOR><MINOR>0</MINOR><BUILD>22621</BUILD></VERSION></WINDOWS>\
+         <NAME>Windows 11 SE</NAME><FLAGS>CloudEdition</FLAGS></IMAGE>",
+        "SE",
+        "CloudEdition",
+    ),
+];
+
+/// 测试由实际 XML 识别 IoT Enterprise、Team 和 SE 版本
+#[test]
+fn test_iot_team_se_editions() {
+    for (xml, display, edition_id) in REAL_WORLD_IMAGES {
+        let image = format::parse_single_image_xml(xml);
+        assert_eq!(image.edition_id.as_deref(), Some(edition_id));
+        assert_eq!(edition_display_name(edition_id), display);
+        let edition = Edition::from_edition_id(edition_id).unwrap();
+        assert!(edition.matches(&image), "{edition_id}");
+        assert_eq!(edition.to_string(), display);
+    }
+    assert_eq!(
+        format::parse_single_image_xml(REAL_WORLD_IMAGES[0].0).channel(),
+        Some(ImageChannel::IoT)
+    );
+    assert_eq!(edition_display_name("EnterpriseS"), "Enterprise LTSC");
+    assert_eq!(
+        Edition::EnterpriseLtsc.can_switch_to(Edition::IoTEnterpriseLtsc),
+        Some(SwitchKind::Upgrade)
+    );
+}
+
+/// 测试没有 EDITIONID 时按名称识别新版本，不再归为通用名称
+#[test]
+fn test_edition_summary_from_names() {
+    let wim = write_wim(&[
+        ImageSpec::new("Windows 10 IoT Enterprise LTSC 2021"),
+        ImageSpec::new("Windows 11 IoT Enterprise"),
+        ImageSpec::new("Windows 11 Enterprise LTSC 2024"),
+        ImageSpec::new("Windows 10 Team"),
+        ImageSpec::new("Windows 11 SE"),
+        ImageSpec::new("Windows 11 Enterprise"),
+    ]);
+    let mut parser = WimParser::new(wim.path()).unwrap();
+    parser.parse_full().unwrap();
+    let editions: Vec<String> = parser
+        .edition_summary()
+        .into_iter()
+        .map(|group| group.edition)
+        .collect();
+    assert_eq!(
+        editions,
+        [
+            "IoT Enterprise LTSC",
+            "IoT Enterprise",
+            "Enterprise LTSC",
+            "Team",
+            "SE",
+            "Enterprise"
+        ]
+    );
+}
@@ -0,0 +1,92 @@
+#![cfg(feature = "parser")]
+
+mod common;
+
+use common::{build_wim, write_bytes, ImageSpec};
+use wim_parser::error::{codes, error_code};
+use wim_parser::{FileFlags, WimParser};
+
+/// 容器文件：前面是含有伪造签名的数据，WIM 之后还有其他数据
+fn container(wim: &[u8]) -> (Vec<u8>, u64) {
+    let mut bytes = vec![0xAAu8; 3000];
+    bytes[100..108].copy_from_slice(b"MSWIM\0\0\0");
+    let offset = bytes.len() as u64;
+    bytes.extend_from_slice(wim);
+    bytes.extend_from_slice(&[0x55u8; 512]);
+    (bytes, offset)
+}
+
+fn specs() -> Vec<ImageSpec> {
+    vec![
+        ImageSpec::new("Image A").file("/a.txt", b"hello"),
+        ImageSpec::new("Image B").file("/b.txt", b"world"),
+    ]
+}
+
+/// 测试按偏移读取嵌入的 WIM：XML、元数据、数据流和校验都相对于起始偏移
+#[test]
+fn test_new_at_reads_embedded_wim() {
+    let (bytes, offset) = container(&build_wim(&specs()));
+    let file = write_bytes(&bytes);
+
+    let mut parser = WimParser::new_at(file.path(), offset).unwrap();
+    assert_eq!(parser.base_offset(), offset);
+    parser.parse_full().unwrap();
+    let names: Vec<&str> = parser
+        .get_images()
+        .iter()
+        .map(|i| i.name.as_str())
+        .collect();
+    assert_eq!(names, ["Image A", "Image B"]);
+
+    let mut tree = parser.open_lazy_tree(2).unwrap();
+    let mut data = Vec::new();
+    tree.extract_file(&mut parser, "/b.txt", &mut data).unwrap();
+    assert_eq!(data, b"world");
+    #[cfg(feature = "verify")]
+    assert!(parser.verify_all_streams().unwrap().is_ok());
+
+    // 不指定偏移时无法识别
+    assert!(WimParser::new(file.path()).unwrap().parse_full().is_err());
+}
+
+/// 测试扫描签名查找嵌入的 WIM，跳过不能解析为文件头的签名
+#[test]
+fn test_find_embedded() {
+    let wim = build_wim(&specs());
+    let (bytes, offset) = container(&wim);
+    let file = write_bytes(&bytes);
+    assert_eq!(WimParser::find_embedded(file.path()).unwrap(), Some(offset));
+
+    let plain = write_bytes(&wim);
+    assert_eq!(WimParser::find_embedded(plain.path()).unwrap(), Some(0));
+
+    let junk = write_bytes(&bytes[..2000]);
+    assert_eq!(WimParser::find_embedded(junk.path()).unwrap(), None);
+}
+
+/// 测试原地修改文件头写入嵌入位置，重写整个文件的编辑被拒绝
+#[test]
+fn test_embedded_wim_writes() {
+    let (bytes, offset) = container(&build_wim(&specs()));
+    let file = write_bytes(&bytes);
+
+    let mut parser = WimParser::new_at(file.path(), offset).unwrap();
+    parser
+        .patch_header(|header| header.file_flags |= FileFlags::READONLY)
+        .unwrap();
+    let disk = std::fs::read(file.path()).unwrap();
+    assert_eq!(disk[..offset as usize], bytes[..offset as usize]);
+    let mut reopened = WimParser::new_at(file.path(), offset).unwrap();
+    assert_ne!(
+        reopened.read_header().unwrap().file_flags & FileFlags::READONLY,
+        0
+    );
+
+    let err = reopened
+        .transaction()
+        .rename_image(1, "Renamed")
+        .commit()
+        .unwrap_err();
+    assert_eq!(error_code(&err), codes::UNSUPPORTED);
+}
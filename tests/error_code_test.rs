@@ -0,0 +1,80 @@
+#![cfg(feature = "parser")]
+
+mod common;
+
+use common::{build_wim, write_wim, ImageSpec};
+use std::io::Write;
+use wim_parser::error::{codes, error_code, exit_code, exit_codes, io_error_code};
+use wim_parser::{format, Error, ErrorCategory, ErrorReport, WimParser};
+
+/// 测试类型化错误的稳定错误码
+#[test]
+fn test_error_codes_are_stable() {
+    let err = format::parse_header(&[0u8; 10]).unwrap_err();
+    assert_eq!(err.code(), 0x0002_0001);
+    assert_eq!(err.category(), ErrorCategory::Format);
+
+    let err = format::parse_header(&[0u8; 200]).unwrap_err();
+    assert_eq!(err.code(), codes::FORMAT_INVALID_SIGNATURE);
+
+    let err = format::decode_xml_utf16(&[0x00]).unwrap_err();
+    assert_eq!(err.code(), codes::XML_INVALID);
+    assert_eq!(
+        ErrorCategory::from_code(err.code()),
+        Some(ErrorCategory::Xml)
+    );
+    assert_eq!(ErrorCategory::from_code(codes::OK), None);
+}
+
+/// 测试解析器返回的 anyhow 错误可映射为错误码
+#[test]
+fn test_parser_error_codes() {
+    let err = WimParser::new("/nonexistent/install.wim").err().unwrap();
+    assert_eq!(error_code(&err), codes::IO_NOT_FOUND);
+
+    let mut bytes = build_wim(&[ImageSpec::new("Image A")]);
+    bytes[0] = b'X';
+    let mut file = tempfile::NamedTempFile::new().unwrap();
+    file.write_all(&bytes).unwrap();
+    let err = WimParser::new(file.path())
+        .unwrap()
+        .read_header()
+        .err()
+        .unwrap();
+    assert_eq!(error_code(&err), codes::FORMAT_INVALID_SIGNATURE);
+
+    let wim = write_wim(&[ImageSpec::new("Image A")]);
+    let mut parser = WimParser::new(wim.path()).unwrap();
+    parser.parse_full().unwrap();
+    let err = parser.recount_image(5, false).unwrap_err();
+    assert_eq!(error_code(&err), codes::OTHER);
+
+    let err = wim_parser::probe_header_from(&[0u8; 200][..]).unwrap_err();
+    assert_eq!(io_error_code(&err), codes::FORMAT_INVALID_SIGNATURE);
+}
+
+/// 测试错误码到退出码的映射和 JSON 错误报告
+#[test]
+fn test_exit_codes_and_error_report() {
+    assert_eq!(exit_code(codes::OK), exit_codes::OK);
+    assert_eq!(exit_code(codes::FORMAT_TRUNCATED), exit_codes::INVALID_FILE);
+    assert_eq!(exit_code(codes::XML_INVALID), exit_codes::INVALID_FILE);
+    assert_eq!(exit_code(codes::UNSUPPORTED), exit_codes::UNSUPPORTED);
+    assert_eq!(
+        exit_code(codes::VERIFICATION_FAILED),
+        exit_codes::VERIFICATION_FAILED
+    );
+    assert_eq!(exit_code(codes::IO_NOT_FOUND), exit_codes::IO);
+    assert_eq!(exit_code(codes::OTHER), exit_codes::FAILURE);
+    assert_eq!(exit_code(0x1234_0000), exit_codes::FAILURE);
+
+    let err = anyhow::Error::new(Error::Unsupported("压缩资源")).context("读取 \"a\\b\" 失败");
+    let report = ErrorReport::from_anyhow(&err);
+    assert_eq!(report.code, codes::UNSUPPORTED);
+    assert_eq!(report.category, ErrorCategory::Unsupported);
+    assert_eq!(report.exit_code, 4);
+    assert_eq!(
+        report.to_json(),
+        r#"{"code":327681,"code_hex":"0x00050001","category":"unsupported","exit_code":4,"message":"读取 \"a\\b\" 失败","causes":["不支持的特性: 压缩资源"]}"#
+    );
+}
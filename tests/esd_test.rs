@@ -0,0 +1,160 @@
+#![cfg(feature = "parser")]
+
+mod common;
+
+use common::{
+    build_wim, chunked_stream, lzms_compress, sha1_hash, solid_streams, write_bytes,
+    xpress_compress, ImageSpec, SolidGroup, LZMS,
+};
+use std::io::Read;
+use wim_parser::error::{codes, error_code};
+use wim_parser::{Compression, WimParser};
+
+const CHUNK: u32 = 32768;
+
+/// 文本、长重复段、x86 指令（CALL、RIP 相对加载、间接调用）和不可压缩的字节混合的测试数据
+fn sample_data() -> Vec<u8> {
+    let mut data = Vec::new();
+    for i in 0..600 {
+        data.extend(format!("line {i}: the quick brown fox jumps over the lazy dog\r\n").bytes());
+    }
+    for i in 0..300i32 {
+        data.push(0xE8);
+        data.extend((i * 37 - 4000).to_le_bytes());
+        data.extend([0x48, 0x8B, 0x05]);
+        data.extend((0x1000 - i * 7).to_le_bytes());
+        data.extend([0xFF, 0x15]);
+        data.extend(0x2000i32.to_le_bytes());
+    }
+    data.extend(std::iter::repeat_n(b'A', 5000));
+    let mut state = 0x1234_5678u32;
+    data.extend((0..8000).map(|_| {
+        state ^= state << 13;
+        state ^= state >> 17;
+        state ^= state << 5;
+        state as u8
+    }));
+    data.extend((0..20000u32).map(|i| (i % 251) as u8));
+    data
+}
+
+/// 测试读取 LZMS 压缩的普通（非固实）数据流
+#[test]
+fn test_read_lzms_stream() {
+    let data = sample_data();
+    let hash = sha1_hash(&data);
+    let compressed: Vec<Vec<u8>> = data.chunks(CHUNK as usize).map(lzms_compress).collect();
+    assert!(compressed
+        .iter()
+        .zip(data.chunks(CHUNK as usize))
+        .all(|(packed, chunk)| packed.len() < chunk.len()));
+    let chunks: Vec<&[u8]> = compressed.iter().map(Vec::as_slice).collect();
+    let bytes = build_wim(&[ImageSpec::new("Test").file("data.bin", &data)]);
+    let bytes = chunked_stream(bytes, LZMS, CHUNK, hash, data.len() as u64, &chunks);
+    let wim = write_bytes(&bytes);
+    let mut parser = WimParser::new(wim.path()).unwrap();
+
+    let resource = parser.stream_resource(&hash).unwrap().unwrap();
+    let mut reader = parser.open_resource(&resource).unwrap();
+    assert_eq!(reader.compression(), Compression::Lzms { chunk: CHUNK });
+    let mut read = Vec::new();
+    reader.read_to_end(&mut read).unwrap();
+    assert_eq!(read, data);
+}
+
+/// 测试固实资源：一组中的 LZMS 和 XPRESS 资源，数据流跨分块，乱序读取
+#[test]
+fn test_read_solid_resources() {
+    let large = sample_data();
+    let small = b"small file inside the first solid resource".to_vec();
+    let other = b"xpress solid resource ".repeat(400);
+    let bytes = build_wim(&[ImageSpec::new("Install")
+        .dir("Windows")
+        .file("Windows/large.bin", &large)
+        .file("Windows/small.txt", &small)
+        .file("other.txt", &other)]);
+    let bytes = solid_streams(
+        bytes,
+        LZMS,
+        &[
+            SolidGroup {
+                format: 3,
+                chunk: CHUNK,
+                compress: lzms_compress,
+                streams: vec![&small, &large],
+            },
+            SolidGroup {
+                format: 1,
+                chunk: 4096,
+                compress: xpress_compress,
+                streams: vec![&other],
+            },
+        ],
+    );
+    let wim = write_bytes(&bytes);
+    let mut parser = WimParser::new(wim.path()).unwrap();
+
+    for data in [&other, &large, &small, &large] {
+        assert_eq!(&parser.read_stream(&sha1_hash(data)).unwrap(), data);
+    }
+
+    let resource = parser.stream_resource(&sha1_hash(&large)).unwrap().unwrap();
+    assert!(resource.is_solid() && !resource.is_solid_resource());
+    assert_eq!(
+        parser.resource_compression(&resource).unwrap(),
+        Compression::Lzms { chunk: CHUNK }
+    );
+    let mut reader = parser.open_resource(&resource).unwrap();
+    assert_eq!(reader.size(), large.len() as u64);
+    assert_eq!(reader.chunk_count(), 3);
+    let mut head = vec![0u8; 100];
+    reader.read_exact(&mut head).unwrap();
+    assert_eq!(head, large[..100]);
+
+    let resource = parser.stream_resource(&sha1_hash(&other)).unwrap().unwrap();
+    assert_eq!(
+        parser.resource_compression(&resource).unwrap(),
+        Compression::Xpress { chunk: 4096 }
+    );
+
+    let metadata = parser.read_image_metadata(1).unwrap();
+    let entry = metadata.root.find("Windows/small.txt").unwrap();
+    assert_eq!(parser.read_stream(&entry.hash).unwrap(), small);
+}
+
+/// 测试损坏的 LZMS 分块和找不到所属固实资源的数据流
+#[test]
+fn test_corrupt_lzms_and_solid_streams() {
+    let data = b"abcabcabcabcabcabcabcabcabcabcabcabc".repeat(20);
+    let hash = sha1_hash(&data);
+    let bytes = build_wim(&[ImageSpec::new("Test").file("data.bin", &data)]);
+    let bytes = chunked_stream(bytes, LZMS, CHUNK, hash, data.len() as u64, &[&[1, 2, 3]]);
+    let wim = write_bytes(&bytes);
+    let mut parser = WimParser::new(wim.path()).unwrap();
+    let err = parser.read_stream(&hash).unwrap_err();
+    assert_eq!(error_code(&err), codes::FORMAT_CORRUPT_DATA);
+
+    // 数据流条目的偏移超出固实资源的未压缩大小
+    let bytes = build_wim(&[ImageSpec::new("Test").file("data.bin", &data)]);
+    let mut bytes = solid_streams(
+        bytes,
+        LZMS,
+        &[SolidGroup {
+            format: 3,
+            chunk: CHUNK,
+            compress: lzms_compress,
+            streams: vec![&data],
+        }],
+    );
+    let entry = bytes
+        .windows(20)
+        .rposition(|window| window == hash)
+        .unwrap()
+        - 30;
+    bytes[entry + 8..entry + 16].copy_from_slice(&100u64.to_le_bytes());
+    let wim = write_bytes(&bytes);
+    let mut parser = WimParser::new(wim.path()).unwrap();
+    let err = parser.read_stream(&hash).unwrap_err();
+    assert_eq!(error_code(&err), codes::FORMAT_CORRUPT_DATA);
+    assert!(format!("{err:#}").contains("固实资源"));
+}
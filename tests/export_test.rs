@@ -0,0 +1,129 @@
+#![cfg(feature = "parser")]
+
+mod common;
+
+use common::{build_wim, chunked_stream, sha1_hash, write_bytes, write_wim, ImageSpec, LZX};
+#[cfg(feature = "verify")]
+use wim_parser::error::{codes, error_code};
+use wim_parser::{export_edition, Edition, Preset, WimParser};
+
+fn edition_xml(edition_id: &str) -> String {
+    format!("<WINDOWS><ARCH>9</ARCH><EDITIONID>{edition_id}</EDITIONID></WINDOWS>")
+}
+
+/// 测试从多版本文件中导出单个版本
+#[test]
+fn test_export_edition() {
+    let esd = write_wim(&[
+        ImageSpec::new("Windows Setup Media"),
+        ImageSpec::new("Microsoft Windows PE (amd64)"),
+        ImageSpec::new("Microsoft Windows Setup (amd64)"),
+        ImageSpec::new("Windows 11 Home")
+            .extra_xml(&edition_xml("Core"))
+            .file("/Windows/shared.dll", b"shared"),
+        ImageSpec::new("Windows 11 Pro")
+            .extra_xml(&edition_xml("Professional"))
+            .dir("/Windows")
+            .file("/Windows/shared.dll", b"shared")
+            .file("/Windows/pro.dll", b"pro only")
+            .file("/Windows/copy.dll", b"pro only"),
+    ]);
+    let out = tempfile::NamedTempFile::new().unwrap();
+
+    let report = export_edition(esd.path(), Edition::Pro, out.path()).unwrap();
+    assert_eq!(report.source_index, 5);
+    assert_eq!(report.setup_indexes, [1, 2, 3]);
+    assert_eq!(report.stream_count, 2);
+    assert_eq!(report.stream_bytes, 14);
+    assert!(!report.compression.is_compressed());
+
+    let mut parser = WimParser::new(out.path()).unwrap();
+    parser.parse_full().unwrap();
+    let header = parser.get_header().unwrap();
+    assert_eq!(header.image_count, 1);
+    let images = parser.get_images();
+    assert_eq!(images.len(), 1);
+    assert_eq!(images[0].index, 1);
+    assert_eq!(images[0].name, "Windows 11 Pro");
+    assert_eq!(images[0].edition_id.as_deref(), Some("Professional"));
+
+    let recount = parser.recount_image(1, false).unwrap();
+    assert!(recount.is_consistent(), "{:?}", recount);
+    let target = tempfile::tempdir().unwrap();
+    let plan = parser.plan_apply(1, target.path()).unwrap();
+    assert_eq!(plan.file_count, 3);
+    assert!(plan.missing_streams.is_empty());
+
+    let err = export_edition(esd.path(), Edition::Enterprise, out.path()).unwrap_err();
+    assert!(err.to_string().contains("找不到版本"));
+}
+
+/// 测试版本与 EDITIONID 的对应关系
+#[test]
+fn test_edition_ids() {
+    assert_eq!(Edition::Home.edition_id(), "Core");
+    assert_eq!(Edition::from_edition_id("professional"), Some(Edition::Pro));
+    assert_eq!(Edition::from_edition_id("ServerStandard"), None);
+    assert_eq!(Edition::ProWorkstations.to_string(), "Pro for Workstations");
+}
+
+/// 构造 LZX 压缩（分块按原样存储）的单版本 WIM，返回文件和数据流内容
+fn lzx_source(corrupt: bool) -> (tempfile::NamedTempFile, Vec<u8>) {
+    let data: Vec<u8> = (0..70000u32).map(|i| (i % 251) as u8).collect();
+    let bytes = build_wim(&[ImageSpec::new("Windows 11 Pro")
+        .extra_xml(&edition_xml("Professional"))
+        .file("/Windows/big.dll", &data)]);
+    let mut stored = data.clone();
+    if corrupt {
+        stored[40000] ^= 0xFF;
+    }
+    let chunks: Vec<&[u8]> = stored.chunks(32768).collect();
+    let bytes = chunked_stream(bytes, LZX, 32768, sha1_hash(&data), 70000, &chunks);
+    (write_bytes(&bytes), data)
+}
+
+/// 测试压缩格式相同时原样复制压缩分块
+#[test]
+fn test_export_raw_chunk_copy() {
+    let (source, data) = lzx_source(false);
+    let hash = sha1_hash(&data);
+    let mut parser = WimParser::new(source.path()).unwrap();
+    let source_resource = parser.stream_resource(&hash).unwrap().unwrap();
+
+    let out = tempfile::NamedTempFile::new().unwrap();
+    let report = parser
+        .export_edition_with(Edition::Pro, out.path(), Preset::DismMax)
+        .unwrap();
+    assert_eq!(report.stream_count, 1);
+    assert_eq!(report.raw_stream_count, 1);
+    assert_eq!(report.stream_bytes, 70000);
+
+    let mut exported = WimParser::new(out.path()).unwrap();
+    let resource = exported.stream_resource(&hash).unwrap().unwrap();
+    assert!(resource.is_compressed());
+    assert_eq!(resource.size, source_resource.size);
+    assert_eq!(exported.read_stream(&hash).unwrap(), data);
+
+    // 压缩格式不同时解压后按未压缩形式写入
+    let report = parser
+        .export_edition_with(Edition::Pro, out.path(), Preset::DismFast)
+        .unwrap();
+    assert_eq!(report.raw_stream_count, 0);
+    let mut exported = WimParser::new(out.path()).unwrap();
+    let resource = exported.stream_resource(&hash).unwrap().unwrap();
+    assert!(!resource.is_compressed());
+    assert_eq!(exported.read_stream(&hash).unwrap(), data);
+}
+
+/// 测试原样复制前校验数据流的 SHA-1
+#[cfg(feature = "verify")]
+#[test]
+fn test_export_raw_copy_verifies_hash() {
+    let (source, _) = lzx_source(true);
+    let mut parser = WimParser::new(source.path()).unwrap();
+    let out = tempfile::NamedTempFile::new().unwrap();
+    let err = parser
+        .export_edition_with(Edition::Pro, out.path(), Preset::DismMax)
+        .unwrap_err();
+    assert_eq!(error_code(&err), codes::VERIFICATION_FAILED);
+}
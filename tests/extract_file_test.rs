@@ -0,0 +1,77 @@
+#![cfg(feature = "parser")]
+
+mod common;
+
+use common::{
+    build_wim, chunked_stream, sha1_hash, write_bytes, xpress_compress, ImageSpec, XPRESS,
+};
+use wim_parser::WimParser;
+
+/// 测试从压缩资源中释放单个文件
+#[test]
+fn test_extract_file() {
+    let kernel: Vec<u8> = (0..100_000u32).map(|i| (i % 253) as u8).collect();
+    let compressed: Vec<Vec<u8>> = kernel.chunks(32768).map(xpress_compress).collect();
+    let chunks: Vec<&[u8]> = compressed.iter().map(Vec::as_slice).collect();
+    let bytes = build_wim(&[
+        ImageSpec::new("Home").file("setup.exe", b"home setup"),
+        ImageSpec::new("Pro")
+            .dir("Windows")
+            .dir("Windows/System32")
+            .file("Windows/System32/ntoskrnl.exe", &kernel)
+            .file("setup.exe", b"pro setup")
+            .file("empty.txt", b""),
+    ]);
+    let bytes = chunked_stream(
+        bytes,
+        XPRESS,
+        32768,
+        sha1_hash(&kernel),
+        kernel.len() as u64,
+        &chunks,
+    );
+    let wim = write_bytes(&bytes);
+    let mut parser = WimParser::new(wim.path()).unwrap();
+
+    let mut out = Vec::new();
+    let size = parser
+        .extract_file(2, "/Windows/System32/ntoskrnl.exe", &mut out)
+        .unwrap();
+    assert_eq!(size, kernel.len() as u64);
+    assert_eq!(out, kernel);
+
+    let mut out = Vec::new();
+    parser
+        .extract_file(2, "\\WINDOWS\\system32\\NTOSKRNL.EXE", &mut out)
+        .unwrap();
+    assert_eq!(out, kernel);
+
+    let mut out = Vec::new();
+    assert_eq!(parser.extract_file(1, "setup.exe", &mut out).unwrap(), 10);
+    assert_eq!(out, b"home setup");
+
+    let mut out = Vec::new();
+    assert_eq!(parser.extract_file(2, "empty.txt", &mut out).unwrap(), 0);
+    assert!(out.is_empty());
+}
+
+/// 测试不存在的路径、目录和镜像索引
+#[test]
+fn test_extract_file_errors() {
+    let wim = write_bytes(&build_wim(&[ImageSpec::new("Test")
+        .dir("Windows")
+        .file("Windows/notepad.exe", b"notepad")]));
+    let mut parser = WimParser::new(wim.path()).unwrap();
+
+    let err = parser
+        .extract_file(1, "Windows/missing.dll", &mut Vec::new())
+        .unwrap_err();
+    assert!(err.to_string().contains("Windows/missing.dll"));
+    assert!(parser.extract_file(1, "Windows", &mut Vec::new()).is_err());
+    assert!(parser
+        .extract_file(1, "Windows/notepad.exe/child", &mut Vec::new())
+        .is_err());
+    assert!(parser
+        .extract_file(2, "Windows/notepad.exe", &mut Vec::new())
+        .is_err());
+}
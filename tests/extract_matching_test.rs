@@ -0,0 +1,98 @@
+#![cfg(feature = "parser")]
+
+mod common;
+
+use common::{write_wim, ImageSpec};
+use std::fs;
+use wim_parser::{ApplyOptions, PathPattern, WimParser};
+
+fn image() -> ImageSpec {
+    ImageSpec::new("Windows 11 Pro")
+        .dir("Windows")
+        .dir("Windows/System32")
+        .dir("Windows/System32/drivers")
+        .dir("Windows/System32/drivers/en-US")
+        .dir("Windows/INF")
+        .dir("Users")
+        .file("Windows/System32/drivers/NTFS.SYS", b"ntfs")
+        .file("Windows/System32/drivers/en-US/beep.sys.mui", b"mui")
+        .file("Windows/System32/drivers/en-US/usb.sys", b"usb")
+        .file("Windows/System32/drivers/etc.txt", b"etc")
+        .file("Windows/System32/kernel32.dll", b"kernel32")
+        .file("Windows/INF/net1.inf", b"inf1")
+        .file("Windows/INF/net2.pnf", b"pnf2")
+        .file("Users/a.sys", b"user")
+}
+
+/// 测试通配符模式的匹配规则
+#[test]
+fn test_path_pattern() {
+    let pattern = PathPattern::new("Windows/System32/drivers/**/*.sys").unwrap();
+    assert!(pattern.matches("Windows/System32/drivers/ntfs.sys"));
+    assert!(pattern.matches("/WINDOWS/system32/DRIVERS/en-US/usb.SYS"));
+    assert!(!pattern.matches("Windows/System32/drivers/en-US/beep.sys.mui"));
+    assert!(!pattern.matches("Windows/System32/kernel32.dll"));
+    assert!(!pattern.matches("Users/a.sys"));
+
+    let pattern = PathPattern::new(r"\Windows\INF\net?.[a-o]nf").unwrap();
+    assert!(pattern.matches("Windows/INF/net1.inf"));
+    assert!(!pattern.matches("Windows/INF/net2.pnf"));
+    assert!(!pattern.matches("Windows/INF/net10.inf"));
+    assert!(PathPattern::new("*.[!s]*").unwrap().matches("a.txt"));
+    assert!(!PathPattern::new("*.[!s]*").unwrap().matches("a.sys"));
+    assert!(PathPattern::new("**").unwrap().matches("Users/a.sys"));
+    assert!(PathPattern::new("Windows/INF")
+        .unwrap()
+        .matches_self_or_parent("Windows/INF/net1.inf"));
+
+    assert!(PathPattern::new("Windows/[abc").is_err());
+    assert!(PathPattern::new("/").is_err());
+}
+
+/// 测试只释放匹配的文件及其上级目录
+#[test]
+fn test_extract_matching() {
+    let wim = write_wim(&[image()]);
+    let out = tempfile::tempdir().unwrap();
+    let mut parser = WimParser::new(wim.path()).unwrap();
+
+    let report = parser
+        .extract_matching(
+            1,
+            &["windows/system32/drivers/**/*.sys", "Windows/INF"],
+            out.path(),
+        )
+        .unwrap();
+    assert_eq!(report.file_count, 4);
+    assert_eq!(report.dir_count, 5);
+    assert_eq!(
+        fs::read(out.path().join("Windows/System32/drivers/NTFS.SYS")).unwrap(),
+        b"ntfs"
+    );
+    assert!(out
+        .path()
+        .join("Windows/System32/drivers/en-US/usb.sys")
+        .exists());
+    assert!(out.path().join("Windows/INF/net2.pnf").exists());
+    assert!(!out.path().join("Windows/System32/kernel32.dll").exists());
+    assert!(!out.path().join("Users").exists());
+
+    // 已存在的内容按默认策略报错
+    assert!(parser
+        .extract_matching(1, &["Windows/INF/*"], out.path())
+        .is_err());
+    assert!(parser.extract_matching(1, &["[oops"], out.path()).is_err());
+
+    let empty = tempfile::tempdir().unwrap();
+    let report = parser
+        .extract_matching(1, &["**/*.exe"], empty.path())
+        .unwrap();
+    assert_eq!(report.file_count, 0);
+    assert_eq!(fs::read_dir(empty.path()).unwrap().count(), 0);
+
+    let options = ApplyOptions::new()
+        .include_patterns([PathPattern::new("**/*.sys").unwrap()])
+        .exclude_extensions(["SYS"]);
+    let plan = parser.plan_apply_with(1, empty.path(), &options).unwrap();
+    assert_eq!(plan.file_count, 0);
+}
@@ -0,0 +1,83 @@
+#![cfg(feature = "parser")]
+
+mod common;
+
+use common::{
+    build_wim, chunked_stream, sha1_hash, write_bytes, xpress_compress, ImageSpec, XPRESS,
+};
+use std::io::{self, Read};
+use wim_parser::{ParseOptions, ResourceLimits, WimParser};
+
+/// 由 3 个 XPRESS 分块组成的 100 000 字节文件
+fn compressed_wim(kernel: &[u8], options: ParseOptions) -> (tempfile::NamedTempFile, WimParser) {
+    let compressed: Vec<Vec<u8>> = kernel.chunks(32768).map(xpress_compress).collect();
+    let chunks: Vec<&[u8]> = compressed.iter().map(Vec::as_slice).collect();
+    let bytes = build_wim(&[ImageSpec::new("Pro")
+        .dir("Windows")
+        .dir("Windows/System32")
+        .file("Windows/System32/ntoskrnl.exe", kernel)
+        .file("empty.txt", b"")]);
+    let bytes = chunked_stream(
+        bytes,
+        XPRESS,
+        32768,
+        sha1_hash(kernel),
+        kernel.len() as u64,
+        &chunks,
+    );
+    let wim = write_bytes(&bytes);
+    let parser = WimParser::with_options(wim.path(), options).unwrap();
+    (wim, parser)
+}
+
+/// 测试以任意大小的缓冲区顺序读取压缩文件
+#[test]
+fn test_open_file_stream() {
+    let kernel: Vec<u8> = (0..100_000u32).map(|i| (i % 251) as u8).collect();
+    let (_wim, mut parser) = compressed_wim(&kernel, ParseOptions::new());
+
+    let mut stream = parser
+        .open_file_stream(1, r"\windows\SYSTEM32\ntoskrnl.exe")
+        .unwrap();
+    assert_eq!(stream.size(), kernel.len() as u64);
+    let mut out = Vec::new();
+    // 跨越补丁签名和分块边界的奇数大小读取
+    let mut buf = [0u8; 7001];
+    loop {
+        let read = stream.read(&mut buf).unwrap();
+        if read == 0 {
+            break;
+        }
+        out.extend_from_slice(&buf[..read]);
+    }
+    assert_eq!(stream.position(), kernel.len() as u64);
+    assert_eq!(out, kernel);
+
+    let mut empty = parser.open_file_stream(1, "empty.txt").unwrap();
+    assert_eq!(empty.size(), 0);
+    assert_eq!(io::copy(&mut empty, &mut io::sink()).unwrap(), 0);
+
+    assert!(parser.open_file_stream(1, "Windows").is_err());
+    assert!(parser.open_file_stream(1, "missing.txt").is_err());
+}
+
+/// 测试流式读取不受整个文件大小的内存限制
+#[test]
+fn test_file_stream_memory_limit() {
+    let kernel: Vec<u8> = (0..100_000u32).map(|i| (i * 7 % 256) as u8).collect();
+    let limits = ResourceLimits {
+        max_memory: Some(64 * 1024),
+        ..ResourceLimits::default()
+    };
+    let (_wim, mut parser) = compressed_wim(&kernel, ParseOptions::new().limits(limits));
+
+    // 一次读入整个文件超过内存限制
+    assert!(parser.read_stream(&sha1_hash(&kernel)).is_err());
+
+    let mut stream = parser
+        .open_file_stream(1, "Windows/System32/ntoskrnl.exe")
+        .unwrap();
+    let mut out = Vec::new();
+    stream.read_to_end(&mut out).unwrap();
+    assert_eq!(out, kernel);
+}
@@ -0,0 +1,58 @@
+#!/bin/sh
+# 用 wimlib-imagex 生成各压缩格式的参考文件（编码器与本库无关），供 tests/reference_test.rs 比对
+#
+# 用法: tests/fixtures/generate.sh（需要 wimlib-imagex 和 python3）
+#
+# 源目录树由下面的脚本确定性地生成，SHA1SUMS 记录每个文件内容的 SHA-1。
+# 在 Windows 上也可以对同一目录树运行 DISM /Capture-Image /Compress:fast|max|recovery 得到对应文件。
+set -e
+cd "$(dirname "$0")"
+src=$(mktemp -d)
+trap 'rm -rf "$src"' EXIT
+
+python3 - "$src" <<'EOF'
+import os
+import sys
+
+root = sys.argv[1]
+
+
+def write(path, data):
+    path = os.path.join(root, path)
+    os.makedirs(os.path.dirname(path), exist_ok=True)
+    with open(path, "wb") as f:
+        f.write(data)
+
+
+# 重复的文本，跨越多个 32 KiB 分块
+write("readme.txt", b"".join(b"WIM reference fixture line %04d\r\n" % i for i in range(2000)))
+
+# 线性同余生成的不可压缩字节（分块按原样存储）
+state, random = 1, bytearray()
+for _ in range(40000):
+    state = (state * 1103515245 + 12345) % (1 << 31)
+    random.append(state >> 16 & 0xFF)
+write("random.bin", bytes(random))
+
+# x86 指令：CALL rel32、RIP 相对加载和间接调用（触发 LZX/LZMS 的 x86 过滤）
+code = bytearray()
+for i in range(2500):
+    code += b"\xe8" + (i * 37 % 65536).to_bytes(4, "little")
+    code += b"\x48\x8b\x05" + (i * 91).to_bytes(4, "little")
+    code += b"\xff\x15" + (0x1000 + i).to_bytes(4, "little")
+    code += b"\x90\xc3"
+write("code.bin", bytes(code))
+
+# 长重复段
+write("zeros.bin", bytes(100000))
+write("empty.txt", b"")
+write("dir/nested.txt", b"nested file\r\n")
+EOF
+
+(cd "$src" && sha1sum readme.txt random.bin code.bin zeros.bin empty.txt dir/nested.txt) > SHA1SUMS
+
+rm -f xpress.wim lzx.wim lzms.wim solid.esd
+wimlib-imagex capture "$src" xpress.wim Reference --compress=XPRESS
+wimlib-imagex capture "$src" lzx.wim Reference --compress=LZX
+wimlib-imagex capture "$src" lzms.wim Reference --compress=LZMS --chunk-size=32768
+wimlib-imagex capture "$src" solid.esd Reference --solid
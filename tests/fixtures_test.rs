@@ -0,0 +1,42 @@
+#![cfg(feature = "fixtures")]
+
+use wim_parser::fixtures::{MiniWim, MINI_WIM};
+use wim_parser::{FileFlags, WimKind, WimParser};
+
+/// 测试内嵌的迷你 WIM：小于 10 KB、未压缩且内容与模块文档一致
+#[test]
+fn test_mini_wim_contents() {
+    assert!(MINI_WIM.len() < 10 * 1024);
+    let header = wim_parser::format::parse_header(MINI_WIM).unwrap();
+    assert!(!header.flags().contains(FileFlags::COMPRESSION));
+
+    let fixture = MiniWim::create().unwrap();
+    let mut parser = WimParser::new(fixture.path()).unwrap();
+    assert_eq!(parser.wim_kind().unwrap(), WimKind::Standard);
+    parser.parse_full().unwrap();
+    let editions: Vec<Option<&str>> = parser
+        .get_images()
+        .iter()
+        .map(|image| image.edition_id.as_deref())
+        .collect();
+    assert_eq!(editions, [Some("Core"), Some("Professional")]);
+    assert_eq!(parser.read_image_metadata(1).unwrap().counts(), (5, 6));
+    assert_eq!(parser.read_image_metadata(2).unwrap().counts(), (5, 7));
+    assert!(parser.verify_all_resources().unwrap().is_ok());
+}
+
+/// 测试副本互不影响，释放时删除
+#[test]
+fn test_mini_wim_copies() {
+    let first = MiniWim::create().unwrap();
+    let second = MiniWim::create().unwrap();
+    assert_ne!(first.path(), second.path());
+
+    let mut parser = WimParser::new(second.path()).unwrap();
+    parser.transaction().delete_image(1).commit().unwrap();
+    assert_eq!(std::fs::read(first.path()).unwrap(), MINI_WIM);
+
+    let path = first.path().to_path_buf();
+    drop(first);
+    assert!(!path.exists());
+}
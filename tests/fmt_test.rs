@@ -0,0 +1,64 @@
+#![cfg(feature = "parser")]
+
+mod common;
+
+use common::{write_wim, ImageSpec};
+use wim_parser::fmt::{display_width, format_bytes, Align, Table, ToTable};
+use wim_parser::WimParser;
+
+/// 测试表格按显示宽度对齐（中文字符占 2 列）
+#[test]
+fn test_table_alignment() {
+    let mut table = Table::new(["名称", "大小"]).align(1, Align::Right);
+    table.push_row(["Windows 11 专业版", "12"]);
+    table.push_row(["Home", "3456"]);
+    table.push_row(["仅一列"]);
+
+    let rendered = table.to_string();
+    let lines: Vec<&str> = rendered.lines().collect();
+    assert_eq!(lines.len(), 5);
+    assert_eq!(lines[0], format!("名称{}大小", " ".repeat(15)));
+    assert_eq!(lines[1], format!("{}  ----", "-".repeat(17)));
+    assert_eq!(lines[2], "Windows 11 专业版    12");
+    assert_eq!(lines[3], format!("Home{}3456", " ".repeat(15)));
+    assert_eq!(lines[4], format!("仅一列{}", " ".repeat(17)));
+
+    let widths: Vec<usize> = lines[..4].iter().map(|l| display_width(l)).collect();
+    assert!(widths.iter().all(|&w| w == widths[0]));
+
+    let colored = table.clone().color(true).to_string();
+    assert!(colored.starts_with("\x1b[1m名称"));
+    assert!(!rendered.contains('\x1b'));
+}
+
+/// 测试字节数格式化
+#[test]
+fn test_format_bytes() {
+    assert_eq!(format_bytes(0), "0 B");
+    assert_eq!(format_bytes(1023), "1023 B");
+    assert_eq!(format_bytes(1536), "1.50 KiB");
+    assert_eq!(format_bytes(5 * 1024 * 1024 * 1024 / 2), "2.50 GiB");
+}
+
+/// 测试镜像列表和统计比对报告的表格输出
+#[test]
+fn test_report_tables() {
+    let wim = write_wim(&[
+        ImageSpec::new("Windows 11 Home").file("/a.txt", b"hello"),
+        ImageSpec::new("Windows 11 Pro")
+            .file("/b.txt", b"world")
+            .recorded_stats(9, 9, 9),
+    ]);
+    let mut parser = WimParser::new(wim.path()).unwrap();
+    parser.parse_full().unwrap();
+
+    let images = parser.get_images().table();
+    assert_eq!(images.rows().len(), 2);
+    assert_eq!(images.headers()[0], "索引");
+    assert_eq!(images.rows()[1][0], "2");
+    assert_eq!(images.rows()[1][1], "Windows 11 Pro");
+
+    let recount = parser.recount_image(2, false).unwrap().table();
+    assert_eq!(recount.rows().len(), 3);
+    assert!(recount.rows().iter().all(|row| row[3] == "不一致"));
+}
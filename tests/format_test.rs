@@ -0,0 +1,120 @@
+mod common;
+
+use common::{build_wim, ImageSpec};
+use wim_parser::{format, Arch, Error, WimTimestamp};
+
+/// 测试纯解析函数解析文件头
+#[test]
+fn test_parse_header_from_bytes() {
+    let bytes = build_wim(&[ImageSpec::new("Image A"), ImageSpec::new("Image B")]);
+
+    let header = format::parse_header(&bytes).unwrap();
+    assert_eq!(header.signature, format::WIM_SIGNATURE);
+    assert_eq!(header.image_count, 2);
+    assert_eq!(header.total_segments, 1);
+    assert!(header.xml_data_resource.size > 0);
+
+    assert_eq!(
+        format::parse_header(&bytes[..100]).unwrap_err(),
+        Error::Truncated {
+            expected: format::WIM_HEADER_MIN_SIZE,
+            actual: 100
+        }
+    );
+
+    let mut corrupted = bytes.clone();
+    corrupted[0] = b'X';
+    assert_eq!(
+        format::parse_header(&corrupted).unwrap_err(),
+        Error::InvalidSignature
+    );
+}
+
+/// 测试 UTF-16 XML 解码与镜像提取
+#[test]
+fn test_decode_and_extract_images() {
+    let xml = r#"<WIM><IMAGE INDEX="1"><WINDOWS><ARCH>12</ARCH></WINDOWS><DISPLAYNAME>Windows 11 Pro</DISPLAYNAME></IMAGE><IMAGE INDEX="2"><DISPLAYNAME>Windows Server 2022 Standard</DISPLAYNAME></IMAGE></WIM>"#;
+    let mut buffer = vec![0xFF, 0xFE];
+    buffer.extend(xml.encode_utf16().flat_map(|u| u.to_le_bytes()));
+
+    let decoded = format::decode_xml_utf16(&buffer).unwrap();
+    assert_eq!(decoded, xml);
+
+    let images = format::parse_images_from_xml(&decoded);
+    assert_eq!(images.len(), 2);
+    assert_eq!(images[0].architecture.as_deref(), Some("ARM64"));
+    assert_eq!(images[1].index, 2);
+    assert_eq!(images[1].version.as_deref(), Some("Windows Server 2022"));
+
+    assert!(matches!(
+        format::decode_xml_utf16(&buffer[1..]),
+        Err(Error::InvalidXml(_))
+    ));
+    assert!(matches!(
+        format::decode_xml_utf16(&buffer[..buffer.len() - 1]),
+        Err(Error::InvalidXml(_))
+    ));
+}
+
+/// 测试 ARCH 原始数值的保留和已知架构的映射
+#[test]
+fn test_arch_raw_value_is_preserved() {
+    let xml = r#"<WIM><IMAGE INDEX="1"><WINDOWS><ARCH>6</ARCH></WINDOWS><DISPLAYNAME>Windows Server 2003 Enterprise</DISPLAYNAME></IMAGE><IMAGE INDEX="2"><WINDOWS><ARCH>42</ARCH></WINDOWS><DISPLAYNAME>Future</DISPLAYNAME></IMAGE><IMAGE INDEX="3"><DISPLAYNAME>Windows 10 Pro x64</DISPLAYNAME></IMAGE></WIM>"#;
+
+    let images = format::parse_images_from_xml(xml);
+    assert_eq!(images[0].arch_raw, Some(6));
+    assert_eq!(images[0].arch(), Some(Arch::Ia64));
+    assert_eq!(images[0].architecture.as_deref(), Some("IA64"));
+
+    assert_eq!(images[1].arch_raw, Some(42));
+    assert_eq!(images[1].arch(), Some(Arch::Unknown(42)));
+    assert_eq!(images[1].architecture, None);
+    assert_eq!(images[1].summary(), "[2] Future arch(42)");
+
+    // 没有 ARCH 标签时根据名称推断
+    assert_eq!(images[2].arch_raw, None);
+    assert_eq!(images[2].arch(), Some(Arch::X64));
+
+    let images = format::parse_images_from_xml_with(xml, false);
+    assert_eq!(images[0].arch_raw, None);
+    assert_eq!(images[0].arch(), None);
+
+    assert_eq!(Arch::from_raw(12), Arch::Arm64);
+    assert_eq!(Arch::Unknown(42).raw(), 42);
+    assert_eq!(Arch::from_name("AMD64"), Some(Arch::X64));
+    assert_eq!(Arch::X86.to_string(), "x86");
+}
+
+/// 测试解析 XML 中的创建和修改时间
+#[test]
+fn test_image_times() {
+    let xml = "<WIM><IMAGE INDEX=\"1\"><NAME>A</NAME>\
+               <CREATIONTIME><HIGHPART>0x01D5C036</HIGHPART><LOWPART>0x69050000</LOWPART></CREATIONTIME>\
+               <LASTMODIFICATIONTIME><HIGHPART>01D5C036</HIGHPART><LOWPART>0x6905ZZZZ</LOWPART></LASTMODIFICATIONTIME>\
+               </IMAGE></WIM>";
+    let images = format::parse_images_from_xml(xml);
+    let created = images[0].creation_time.unwrap();
+    assert_eq!(
+        created,
+        WimTimestamp::from_filetime(132_223_104_000_000_000)
+    );
+    assert_eq!(created.to_string(), "2020-01-01 00:00:00 UTC");
+    // 无法解析的时间视为缺失
+    assert_eq!(images[0].last_modification_time, None);
+}
+
+/// 测试由类型化字段生成的单行摘要
+#[test]
+fn test_image_summary() {
+    let xml = r#"<WIM><IMAGE INDEX="6"><TOTALBYTES>4939212390</TOTALBYTES><WINDOWS><ARCH>9</ARCH><VERSION><MAJOR>10</MAJOR><BUILD>22631</BUILD><SPBUILD>2861</SPBUILD></VERSION><LANGUAGES><LANGUAGE>en-US</LANGUAGE><DEFAULT>en-US</DEFAULT></LANGUAGES></WINDOWS><NAME>Windows 11 Pro</NAME><DISPLAYNAME>Windows 11 专业版</DISPLAYNAME></IMAGE><IMAGE INDEX="7"><DISPLAYNAME>Custom</DISPLAYNAME><TOTALBYTES>512</TOTALBYTES></IMAGE></WIM>"#;
+
+    let images = format::parse_images_from_xml(xml);
+    assert_eq!(images[0].sp_build, Some(2861));
+    assert_eq!(
+        images[0].summary(),
+        "[6] Windows 11 Pro x64 22631.2861 en-US 4.6GiB"
+    );
+    assert_eq!(images[0].to_string(), images[0].summary());
+    assert_eq!(images[1].summary(), "[7] Custom 512B");
+    assert_eq!(wim_parser::fmt::format_bytes_compact(1536), "1.5KiB");
+}
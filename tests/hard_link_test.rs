@@ -0,0 +1,110 @@
+#![cfg(feature = "parser")]
+
+mod common;
+
+use common::{sha1_hash, write_wim, ImageSpec};
+use std::io::Read;
+use wim_parser::{ApplyOptions, DirectoryTarget, TarTarget, WimParser};
+
+const DLL: &[u8] = b"MZ shared component";
+
+/// WinSxS 中的组件与 System32 中的文件互为硬链接
+fn winsxs_image() -> ImageSpec {
+    ImageSpec::new("WinSxS")
+        .dir("Windows/WinSxS/amd64_comctl")
+        .dir("Windows/System32")
+        .file("Windows/WinSxS/amd64_comctl/comctl32.dll", DLL)
+        .file("Windows/System32/comctl32.dll", DLL)
+        .file("Windows/System32/copy.dll", DLL)
+        .file("Windows/System32/a.txt", b"first")
+        .file("Windows/System32/b.txt", b"second")
+        .hard_link("Windows/WinSxS/amd64_comctl/comctl32.dll", 5)
+        .hard_link("Windows/System32/comctl32.dll", 5)
+        .hard_link("Windows/System32/a.txt", 9)
+        .hard_link("Windows/System32/b.txt", 9)
+}
+
+/// 测试按组 ID 和内容识别硬链接组
+#[test]
+fn test_hard_link_groups() {
+    let wim = write_wim(&[winsxs_image()]);
+    let mut parser = WimParser::new(wim.path()).unwrap();
+    let metadata = parser.read_image_metadata(1).unwrap();
+
+    let entry = metadata.root.find("Windows/System32/comctl32.dll").unwrap();
+    assert_eq!(entry.hard_link_group_id, 5);
+    let copy = metadata.root.find("Windows/System32/copy.dll").unwrap();
+    assert_eq!(copy.hard_link_group_id, 0);
+
+    // 组 9 中的两个文件内容不同，不是硬链接；内容相同但不在组中的文件也不是
+    let groups = metadata.hard_link_groups();
+    assert_eq!(groups.len(), 1);
+    assert_eq!(groups[0].id, 5);
+    assert_eq!(groups[0].hash, sha1_hash(DLL));
+    assert_eq!(
+        groups[0].paths,
+        [
+            "Windows/WinSxS/amd64_comctl/comctl32.dll",
+            "Windows/System32/comctl32.dll",
+        ]
+    );
+}
+
+/// 测试释放时重建硬链接：本地目录共享同一文件，tar 中写为硬链接条目
+#[test]
+fn test_apply_hard_links() {
+    let wim = write_wim(&[winsxs_image()]);
+    let mut parser = WimParser::new(wim.path()).unwrap();
+
+    let out = tempfile::tempdir().unwrap();
+    let report = parser
+        .apply_to(
+            1,
+            &mut DirectoryTarget::new(out.path()),
+            &ApplyOptions::new(),
+        )
+        .unwrap();
+    assert_eq!((report.file_count, report.hard_link_count), (4, 1));
+    assert_eq!(
+        report.total_bytes,
+        2 * DLL.len() as u64 + b"first".len() as u64 + b"second".len() as u64
+    );
+    let linked = out.path().join("Windows/System32/comctl32.dll");
+    assert_eq!(std::fs::read(&linked).unwrap(), DLL);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        let original = out.path().join("Windows/WinSxS/amd64_comctl/comctl32.dll");
+        let (original, linked) = (
+            std::fs::metadata(original).unwrap(),
+            std::fs::metadata(linked).unwrap(),
+        );
+        assert_eq!(original.ino(), linked.ino());
+        assert_eq!(linked.nlink(), 2);
+    }
+
+    let mut target = TarTarget::new(Vec::new());
+    let report = parser
+        .apply_to(1, &mut target, &ApplyOptions::new())
+        .unwrap();
+    assert_eq!(report.hard_link_count, 1);
+    let data = target.into_inner();
+    let mut archive = tar::Archive::new(data.as_slice());
+    let mut links = Vec::new();
+    for entry in archive.entries().unwrap() {
+        let mut entry = entry.unwrap();
+        if entry.header().entry_type() == tar::EntryType::Link {
+            let path = entry.path().unwrap().to_string_lossy().into_owned();
+            let link = entry.link_name().unwrap().unwrap();
+            links.push((path, link.to_string_lossy().into_owned()));
+        }
+        entry.read_to_end(&mut Vec::new()).unwrap();
+    }
+    assert_eq!(
+        links,
+        [(
+            "Windows/System32/comctl32.dll".to_string(),
+            "Windows/WinSxS/amd64_comctl/comctl32.dll".to_string()
+        )]
+    );
+}
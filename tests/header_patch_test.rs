@@ -0,0 +1,154 @@
+#![cfg(feature = "parser")]
+
+mod common;
+
+use std::io::{Seek, SeekFrom, Write};
+
+use common::{write_wim, ImageSpec};
+use wim_parser::error::{codes, error_code};
+use wim_parser::{FileFlags, HeaderFlags, WimParser};
+
+/// 在另一个“进程”中修改磁盘上的文件标志
+fn set_disk_flags(path: &std::path::Path, flags: u32) {
+    let mut file = std::fs::OpenOptions::new().write(true).open(path).unwrap();
+    file.seek(SeekFrom::Start(16)).unwrap();
+    file.write_all(&flags.to_le_bytes()).unwrap();
+}
+
+/// 测试原地写回文件头，以及磁盘上的文件头被修改后拒绝写入
+#[test]
+fn test_patch_header() {
+    let wim = write_wim(&[ImageSpec::new("Image A"), ImageSpec::new("Image B")]);
+    let mut parser = WimParser::new(wim.path()).unwrap();
+    parser.check_header_unchanged().unwrap();
+
+    let changes = parser
+        .patch_header(|header| header.file_flags |= FileFlags::READONLY)
+        .unwrap();
+    assert_eq!(changes.len(), 1);
+    assert_eq!(changes[0].name, "file_flags");
+    assert_eq!(changes[0].offset, 16);
+    assert!(changes[0].to_string().starts_with("file_flags (0x10):"));
+    assert!(parser.patch_header(|_| {}).unwrap().is_empty());
+    assert_eq!(
+        wim_parser::probe_header(wim.path()).unwrap().file_flags,
+        FileFlags::READONLY
+    );
+
+    // 例如 DISM 在操作期间设置了 WRITE_IN_PROGRESS
+    set_disk_flags(wim.path(), FileFlags::WRITE_IN_PROGRESS);
+    let err = parser.check_header_unchanged().unwrap_err();
+    assert_eq!(error_code(&err), codes::CONFLICT_HEADER_CHANGED);
+    let err = parser
+        .patch_header(|header| header.bootable_image_index = 2)
+        .unwrap_err();
+    assert_eq!(error_code(&err), codes::CONFLICT_HEADER_CHANGED);
+    assert!(err.to_string().contains("file_flags"), "{err}");
+    let header = wim_parser::probe_header(wim.path()).unwrap();
+    assert_eq!(header.file_flags, FileFlags::WRITE_IN_PROGRESS);
+    assert_eq!(header.bootable_image_index, 0);
+}
+
+/// 测试编辑事务在原文件被并发修改时不替换文件
+#[test]
+fn test_transaction_refuses_concurrent_change() {
+    let wim = write_wim(&[ImageSpec::new("Image A"), ImageSpec::new("Image B")]);
+    let mut parser = WimParser::new(wim.path()).unwrap();
+    parser.parse_full().unwrap();
+
+    set_disk_flags(wim.path(), FileFlags::WRITE_IN_PROGRESS);
+    let err = parser
+        .transaction()
+        .rename_image(1, "Renamed")
+        .commit()
+        .unwrap_err();
+    assert_eq!(error_code(&err), codes::CONFLICT_HEADER_CHANGED);
+
+    let mut parser = WimParser::new(wim.path()).unwrap();
+    parser.parse_full().unwrap();
+    assert_eq!(parser.get_images()[0].name, "Image A");
+    let prefix = format!(".{}.", wim.path().file_name().unwrap().to_string_lossy());
+    let leftovers = std::fs::read_dir(wim.path().parent().unwrap())
+        .unwrap()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_name().to_string_lossy().starts_with(&prefix))
+        .count();
+    assert_eq!(leftovers, 0);
+}
+
+/// 测试设置和清除文件头标志的校验
+#[test]
+fn test_edit_header_flags() {
+    assert_eq!(
+        HeaderFlags::from_name("readonly"),
+        Some(FileFlags::READONLY)
+    );
+    assert_eq!(
+        HeaderFlags::from_name("FLAG_RP_FIX"),
+        Some(FileFlags::RP_FIX)
+    );
+    assert_eq!(HeaderFlags::from_name("BOGUS"), None);
+
+    let wim = write_wim(&[ImageSpec::new("Image A")]);
+    let mut parser = WimParser::new(wim.path()).unwrap();
+    let edit = parser
+        .edit_header_flags(FileFlags::READONLY | FileFlags::RP_FIX, 0)
+        .unwrap();
+    assert!(edit.changed);
+    assert_eq!(edit.before, HeaderFlags(0));
+    assert_eq!(edit.after.to_string(), "READONLY|RP_FIX");
+    assert!(!edit.integrity_refreshed);
+    let edit = parser
+        .edit_header_flags(0, FileFlags::WRITE_IN_PROGRESS)
+        .unwrap();
+    assert!(!edit.changed);
+
+    let err = parser
+        .edit_header_flags(FileFlags::COMPRESSION | FileFlags::COMPRESS_LZX, 0)
+        .unwrap_err();
+    assert_eq!(error_code(&err), codes::UNSUPPORTED);
+    assert!(
+        err.to_string().contains("COMPRESSION|COMPRESS_LZX"),
+        "{err}"
+    );
+    assert!(parser
+        .edit_header_flags(FileFlags::READONLY, FileFlags::READONLY)
+        .is_err());
+    assert_eq!(
+        wim_parser::probe_header(wim.path()).unwrap().file_flags,
+        FileFlags::READONLY | FileFlags::RP_FIX
+    );
+}
+
+/// 测试清除 WRITE_IN_PROGRESS 时重新计算过期的完整性表
+#[cfg(feature = "verify")]
+#[test]
+fn test_clear_write_in_progress_refreshes_integrity() {
+    let mut bytes = common::build_wim(&[ImageSpec::new("Image A").file("a.txt", b"old data")]);
+    common::add_integrity_table(&mut bytes, 64);
+    // 写入中断：数据已经改变，完整性表还是旧的
+    let offset = bytes
+        .windows(8)
+        .position(|window| window == b"old data")
+        .unwrap();
+    bytes[offset..offset + 8].copy_from_slice(b"new data");
+    bytes[16..20].copy_from_slice(&FileFlags::WRITE_IN_PROGRESS.to_le_bytes());
+    let wim = common::write_bytes(&bytes);
+
+    let mut parser = WimParser::new(wim.path()).unwrap();
+    assert!(!parser.repair_plan().unwrap().is_empty());
+    let edit = parser
+        .edit_header_flags(0, FileFlags::WRITE_IN_PROGRESS)
+        .unwrap();
+    assert!(edit.changed && edit.integrity_refreshed);
+    assert_eq!(edit.after, HeaderFlags(0));
+    assert!(parser.repair_plan().unwrap().is_empty());
+    assert!(!parser.refresh_integrity_table().unwrap());
+
+    let mut reopened = WimParser::new(wim.path()).unwrap();
+    assert!(reopened.repair_plan().unwrap().is_empty());
+    assert_eq!(
+        std::fs::metadata(wim.path()).unwrap().len(),
+        bytes.len() as u64
+    );
+}
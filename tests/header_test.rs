@@ -0,0 +1,160 @@
+#![cfg(feature = "parser")]
+
+mod common;
+
+use common::{build_wim, write_bytes, write_wim, ImageSpec};
+use wim_parser::{ResHdrFlags, ResourceFlags, ResourceKind, WimParser, HEADER_FIELDS_SIZE};
+
+/// 测试文件头资源条目遍历
+#[test]
+fn test_header_resources() {
+    let wim = write_wim(&[ImageSpec::new("Image A").file("a.txt", b"hello")]);
+    let file_len = std::fs::metadata(wim.path()).unwrap().len();
+
+    let mut parser = WimParser::new(wim.path()).unwrap();
+    assert_eq!(parser.resources().count(), 0, "未读取文件头时应为空");
+    parser.read_header().unwrap();
+
+    let resources: Vec<_> = parser.resources().collect();
+    let kinds: Vec<_> = resources.iter().map(|(kind, _)| *kind).collect();
+    assert_eq!(kinds, ResourceKind::ALL.to_vec());
+
+    let (_, xml) = resources[1];
+    assert_eq!(xml.byte_range().end, file_len, "XML 数据位于文件末尾");
+    assert_eq!(xml.resource_flags(), ResHdrFlags(0));
+
+    let (_, offset_table) = resources[0];
+    assert!(offset_table
+        .resource_flags()
+        .contains(ResourceFlags::METADATA));
+    assert!(offset_table.byte_range().end <= xml.byte_range().start);
+
+    let (_, integrity) = resources[3];
+    assert!(integrity.byte_range().is_empty());
+}
+
+/// 测试资源标志的名称与未知位
+#[test]
+fn test_res_hdr_flags_display() {
+    assert_eq!(ResHdrFlags(0).to_string(), "NONE");
+    assert_eq!(ResHdrFlags(0x06).to_string(), "METADATA|COMPRESSED");
+    assert_eq!(ResHdrFlags(0x41).to_string(), "FREE|0x40");
+    assert_eq!(ResHdrFlags(0x41).unknown_bits(), 0x40);
+    assert_eq!(ResourceKind::Integrity.to_string(), "integrity");
+}
+
+/// 测试文件头字段布局与磁盘字节一致
+#[test]
+fn test_header_field_layout() {
+    let wim = write_wim(&[ImageSpec::new("Image A"), ImageSpec::new("Image B")]);
+    let on_disk = std::fs::read(wim.path()).unwrap();
+
+    let mut parser = WimParser::new(wim.path()).unwrap();
+    let header = parser.read_header().unwrap();
+
+    assert_eq!(&header.to_bytes()[..], &on_disk[..HEADER_FIELDS_SIZE]);
+
+    let layout = header.field_layout();
+    let mut expected_offset = 0;
+    for field in &layout {
+        assert_eq!(
+            field.offset, expected_offset,
+            "字段 {} 应紧接上一个字段",
+            field.name
+        );
+        assert_eq!(field.raw.len(), field.length);
+        assert_eq!(
+            field.raw,
+            on_disk[field.offset..field.offset + field.length]
+        );
+        expected_offset += field.length;
+    }
+    assert_eq!(expected_offset, HEADER_FIELDS_SIZE);
+
+    let image_count = layout.iter().find(|f| f.name == "image_count").unwrap();
+    assert_eq!(image_count.raw, 2u32.to_le_bytes());
+
+    // 磁盘上的布局包含保留区域中的实际字节
+    let mut bytes = build_wim(&[ImageSpec::new("Image A")]);
+    bytes[HEADER_FIELDS_SIZE + 2] = 0xAB;
+    let wim = write_bytes(&bytes);
+    let mut parser = WimParser::new(wim.path()).unwrap();
+    let on_disk = parser.header_layout().unwrap();
+    assert_eq!(on_disk.len(), layout.len() + 1);
+    let reserved = on_disk.last().unwrap();
+    assert_eq!(
+        (reserved.name, reserved.offset, reserved.length),
+        ("reserved", HEADER_FIELDS_SIZE, 60)
+    );
+    assert_eq!(reserved.raw, bytes[HEADER_FIELDS_SIZE..208]);
+    assert_eq!(reserved.raw[2], 0xAB);
+    let header = parser.read_header().unwrap();
+    assert_eq!(&on_disk[..layout.len()], &header.field_layout()[..]);
+}
+
+/// 测试仅读取文件头的轻量探测
+#[test]
+fn test_probe_header() {
+    let wim = write_wim(&[ImageSpec::new("Image A")]);
+
+    let header = wim_parser::probe_header(wim.path()).unwrap();
+    assert_eq!(header.image_count, 1);
+
+    let garbage = vec![0u8; 300];
+    let err = wim_parser::probe_header_from(&garbage[..]).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+
+    let err = wim_parser::probe_header_from(&garbage[..10]).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::UnexpectedEof);
+}
+
+/// 记录每次读取范围的读取器
+struct RecordingReader {
+    inner: std::io::Cursor<Vec<u8>>,
+    reads: Vec<std::ops::Range<u64>>,
+}
+
+impl std::io::Read for RecordingReader {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let start = self.inner.position();
+        let len = self.inner.read(buf)?;
+        self.reads.push(start..start + len as u64);
+        Ok(len)
+    }
+}
+
+impl std::io::Seek for RecordingReader {
+    fn seek(&mut self, pos: std::io::SeekFrom) -> std::io::Result<u64> {
+        self.inner.seek(pos)
+    }
+}
+
+/// 测试仅读取文件头和 XML 的深度探测
+#[test]
+fn test_probe_deep() {
+    let bytes = build_wim(&[
+        ImageSpec::new("Image A").file("a.txt", &[1u8; 4096]),
+        ImageSpec::new("Image B"),
+    ]);
+    let header = wim_parser::probe_header_from(&bytes[..]).unwrap();
+    let xml = header.xml_data_resource.byte_range();
+
+    let mut reader = RecordingReader {
+        inner: std::io::Cursor::new(bytes),
+        reads: Vec::new(),
+    };
+    let probe = wim_parser::probe_deep_from(&mut reader).unwrap();
+    let names: Vec<&str> = probe.images.iter().map(|i| i.name.as_str()).collect();
+    assert_eq!(names, ["Image A", "Image B"]);
+    assert_eq!(probe.header.image_count, 2);
+    assert_eq!(probe.xml_bytes, xml.end - xml.start);
+
+    // 两次读取：文件头和 XML 资源，不触及其他资源
+    assert_eq!(reader.reads.len(), 2);
+    assert_eq!(reader.reads[0].start, 0);
+    assert_eq!(reader.reads[1], xml);
+
+    let wim = write_wim(&[ImageSpec::new("Image A")]);
+    let probe = wim_parser::probe_deep(wim.path()).unwrap();
+    assert_eq!(probe.images.len(), 1);
+}
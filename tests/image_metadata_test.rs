@@ -0,0 +1,100 @@
+#![cfg(feature = "parser")]
+
+mod common;
+
+use common::{sha1_hash, write_wim, ImageSpec, FILE_ATTRIBUTE_DIRECTORY, IO_REPARSE_TAG_SYMLINK};
+use wim_parser::{WimParser, WimTimestamp};
+
+const WRITE_TIME: u64 = 133_000_000_000_000_000;
+
+/// 测试解析镜像的完整目录树
+#[test]
+fn test_read_image_metadata_tree() {
+    let wim = write_wim(&[
+        ImageSpec::new("Image A").file("a.txt", b"first image"),
+        ImageSpec::new("Image B")
+            .dir("Windows")
+            .dir("Windows/System32")
+            .file("Windows/System32/kernel32.dll", b"kernel32")
+            .file("Windows/notepad.exe", b"notepad")
+            .symlink("Windows/link", "notepad.exe")
+            .write_time(WRITE_TIME),
+    ]);
+    let mut parser = WimParser::new(wim.path()).unwrap();
+
+    let metadata = parser.read_image_metadata(2).unwrap();
+    assert_eq!(metadata.index, 2);
+    assert!(metadata.security_descriptors.is_empty());
+    assert_eq!(metadata.counts(), (2, 3));
+    assert!(metadata.root.is_directory());
+    assert_eq!(metadata.root.name, "");
+
+    let windows = metadata.root.find("WINDOWS").unwrap();
+    assert_eq!(
+        windows.attributes & FILE_ATTRIBUTE_DIRECTORY,
+        FILE_ATTRIBUTE_DIRECTORY
+    );
+    assert_eq!(
+        windows.last_write_time,
+        WimTimestamp::from_filetime(WRITE_TIME)
+    );
+    let kernel = metadata
+        .root
+        .find("Windows\\System32\\kernel32.dll")
+        .unwrap();
+    assert!(!kernel.is_directory());
+    assert_eq!(kernel.hash, sha1_hash(b"kernel32"));
+    assert_eq!(
+        &*kernel.raw_name(),
+        "kernel32.dll".encode_utf16().collect::<Vec<_>>()
+    );
+    assert_eq!(kernel.security_id, None);
+
+    let link = metadata.root.find("Windows/link").unwrap();
+    assert!(link.is_reparse_point());
+    assert_eq!(link.reparse_tag, IO_REPARSE_TAG_SYMLINK);
+    assert_eq!(link.hard_link_group_id, 0);
+
+    let mut paths = Vec::new();
+    metadata
+        .root
+        .walk(&mut |path, _| paths.push(path.to_string()));
+    assert_eq!(
+        paths,
+        [
+            "Windows",
+            "Windows/System32",
+            "Windows/System32/kernel32.dll",
+            "Windows/notepad.exe",
+            "Windows/link",
+        ]
+    );
+
+    let first = parser.read_image_metadata(1).unwrap();
+    assert_eq!(
+        first.root.find("a.txt").unwrap().hash,
+        sha1_hash(b"first image")
+    );
+    assert!(parser.read_image_metadata(3).is_err());
+}
+
+/// 测试安全数据块
+#[test]
+fn test_read_image_metadata_security() {
+    let wim = write_wim(&[ImageSpec::new("Secured")
+        .dir("Users")
+        .file("Users/a.txt", b"a")
+        .secured()]);
+    let mut parser = WimParser::new(wim.path()).unwrap();
+
+    let metadata = parser.read_image_metadata(1).unwrap();
+    assert_eq!(metadata.security_descriptors.len(), 1);
+    assert_eq!(metadata.security_descriptors[0].len(), 20);
+    let file = metadata.root.find("users/A.TXT").unwrap();
+    assert_eq!(file.security_id, Some(0));
+    assert_eq!(
+        metadata.security_descriptor(file),
+        Some(metadata.security_descriptors[0].as_slice())
+    );
+    assert!(file.streams.is_empty());
+}
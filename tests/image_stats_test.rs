@@ -0,0 +1,73 @@
+#![cfg(feature = "parser")]
+
+mod common;
+
+use common::{write_wim, ImageSpec};
+use wim_parser::WimParser;
+
+fn sample_image() -> ImageSpec {
+    ImageSpec::new("Windows 11 Pro")
+        .dir("Windows/System32")
+        .file("Windows/System32/kernel32.dll", b"kernel32 contents")
+        .file("Windows/win.ini", b"[fonts]")
+        .file("bootmgr", b"")
+}
+
+/// 测试统计信息与 XML 一致时的重新计算
+#[test]
+fn test_recount_consistent_image() {
+    let spec = sample_image();
+    let wim = write_wim(std::slice::from_ref(&spec));
+
+    let mut parser = WimParser::new(wim.path()).unwrap();
+    parser.parse_full().unwrap();
+
+    let recount = parser.recount_image(1, false).unwrap();
+    assert!(recount.is_consistent());
+    assert_eq!(recount.actual.dir_count, 3, "目录数应包含根目录");
+    assert_eq!(recount.actual.file_count, 3);
+    assert_eq!(recount.actual.total_bytes, 24);
+    assert!(!recount.fixed);
+}
+
+/// 测试不一致的统计信息可被检测并修正
+#[test]
+fn test_recount_detects_and_fixes_mismatch() {
+    let wim = write_wim(&[
+        ImageSpec::new("Image A").file("a.txt", b"aaaa"),
+        sample_image().recorded_stats(100, 200, 300),
+    ]);
+
+    let mut parser = WimParser::new(wim.path()).unwrap();
+    parser.parse_full().unwrap();
+
+    let recount = parser.recount_image(2, false).unwrap();
+    assert!(!recount.is_consistent());
+    assert_eq!(recount.recorded.file_count, 200);
+    assert_eq!(
+        parser.get_image(2).unwrap().file_count,
+        200,
+        "未要求修正时不应修改"
+    );
+
+    let recount = parser.recount_image(2, true).unwrap();
+    assert!(recount.fixed);
+    let image = parser.get_image(2).unwrap();
+    assert_eq!(image.dir_count, 3);
+    assert_eq!(image.file_count, 3);
+    assert_eq!(image.total_bytes, 24);
+
+    assert!(parser.recount_image(1, false).unwrap().is_consistent());
+}
+
+/// 测试不存在的镜像索引
+#[test]
+fn test_recount_missing_image() {
+    let wim = write_wim(&[sample_image()]);
+
+    let mut parser = WimParser::new(wim.path()).unwrap();
+    parser.parse_full().unwrap();
+
+    assert!(parser.recount_image(0, false).is_err());
+    assert!(parser.recount_image(2, false).is_err());
+}
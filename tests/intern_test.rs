@@ -0,0 +1,47 @@
+use wim_parser::SharedStr;
+
+/// 测试共享字符串的比较和转换
+#[test]
+fn test_shared_str() {
+    let a = SharedStr::from("Professional");
+    let b = SharedStr::from(String::from("Professional"));
+    assert_eq!(a, b);
+    assert_eq!(a, "Professional");
+    assert_eq!("Professional", a);
+    assert_eq!(a, String::from("Professional"));
+    assert_eq!(a.len(), 12);
+    assert!(a.starts_with("Pro"));
+    assert_eq!(format!("{a} {a:?}"), "Professional \"Professional\"");
+    assert_eq!(String::from(b), "Professional");
+
+    let other = SharedStr::from("Core");
+    assert!(!SharedStr::ptr_eq(&a, &other));
+    assert_ne!(a, other);
+}
+
+/// 测试字符串池去重；已满时不再加入新字符串，并定期清理已不再被引用的字符串
+#[cfg(feature = "std")]
+#[test]
+fn test_pool_is_bounded() {
+    let a = SharedStr::from("Enterprise");
+    assert!(SharedStr::ptr_eq(&a, &SharedStr::from("Enterprise")));
+    assert!(SharedStr::pool_size() > 0);
+
+    let held: Vec<SharedStr> = (0..5000)
+        .map(|i| SharedStr::from(format!("held-{i}")))
+        .collect();
+    assert!(SharedStr::pool_size() <= 4096);
+    assert_eq!(held[4999], "held-4999");
+    // 超出容量的字符串没有加入字符串池
+    let again = SharedStr::from("held-4999");
+    assert!(!SharedStr::ptr_eq(&held[4999], &again));
+    drop(held);
+
+    // 未命中达到清理间隔后，已无人引用的字符串被移出字符串池
+    for i in 0..1100 {
+        drop(SharedStr::from(format!("miss-{i}")));
+    }
+    assert!(SharedStr::pool_size() < 2048, "{}", SharedStr::pool_size());
+    let reused = SharedStr::from("reused");
+    assert!(SharedStr::ptr_eq(&reused, &SharedStr::from("reused")));
+}
@@ -0,0 +1,84 @@
+#![cfg(feature = "parser")]
+
+mod common;
+
+use std::fs;
+
+use common::{build_wim, ImageSpec};
+use wim_parser::error::codes;
+use wim_parser::inventory;
+
+/// 测试递归盘点目录：WIM/ESD 文件、ISO 中的 WIM 和解析失败的文件
+#[test]
+fn test_inventory_directory() {
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path();
+    fs::create_dir_all(root.join("win11/sources")).unwrap();
+    fs::write(
+        root.join("install.wim"),
+        build_wim(&[
+            ImageSpec::new("Windows 11 Home"),
+            ImageSpec::new("Windows 11 Pro"),
+        ]),
+    )
+    .unwrap();
+    fs::write(
+        root.join("win11/sources/boot.ESD"),
+        build_wim(&[ImageSpec::new("Microsoft Windows PE")]),
+    )
+    .unwrap();
+    let mut iso = vec![0u8; 0x8000];
+    iso.extend(build_wim(&[ImageSpec::new("Windows 10 Pro")]));
+    iso.resize(iso.len() + 0x800, 0);
+    fs::write(root.join("win10.iso"), iso).unwrap();
+    fs::write(root.join("broken.wim"), b"not a wim").unwrap();
+    fs::write(root.join("readme.txt"), b"ignored").unwrap();
+
+    let report = inventory(root).unwrap();
+    assert_eq!(report.files_scanned, 4);
+    assert_eq!(report.image_count(), 4);
+    let found: Vec<(String, Option<u64>, usize)> = report
+        .entries
+        .iter()
+        .map(|entry| {
+            let path = entry.path.strip_prefix(root).unwrap();
+            (path.display().to_string(), entry.offset, entry.images.len())
+        })
+        .collect();
+    assert_eq!(
+        found,
+        [
+            ("broken.wim".to_string(), None, 0),
+            ("install.wim".to_string(), None, 2),
+            ("win10.iso".to_string(), Some(0x8000), 1),
+            ("win11/sources/boot.ESD".to_string(), None, 1),
+        ]
+    );
+    let failures: Vec<_> = report.failures().collect();
+    assert_eq!(failures.len(), 1);
+    assert_eq!(
+        failures[0].error.as_ref().unwrap().0,
+        codes::IO_UNEXPECTED_EOF
+    );
+    assert_eq!(report.entries[2].images[0].name, "Windows 10 Pro");
+}
+
+/// 测试 JSON 报告的结构和转义
+#[test]
+fn test_inventory_json() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("a \"quoted\" name.wim");
+    fs::write(&path, build_wim(&[ImageSpec::new("Image \"A\"")])).unwrap();
+
+    let json = inventory(&path).unwrap().to_json();
+    assert!(json.starts_with('{') && json.ends_with('}'), "{json}");
+    assert!(json.contains("\"files_scanned\":1"), "{json}");
+    assert!(
+        json.contains("\"wim_count\":1,\"image_count\":1,\"failures\":0"),
+        "{json}"
+    );
+    assert!(json.contains("a \\\"quoted\\\" name.wim"), "{json}");
+    assert!(json.contains("\"name\":\"Image \\\"A\\\"\""), "{json}");
+    assert!(json.contains("\"offset\":null"), "{json}");
+    assert!(json.contains("\"error\":null"), "{json}");
+}
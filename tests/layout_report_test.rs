@@ -0,0 +1,132 @@
+#![cfg(feature = "parser")]
+
+mod common;
+
+use common::{
+    add_integrity_table, build_wim, sha1_hash, solid_streams, write_bytes, xpress_compress,
+    ImageSpec, SolidGroup, XPRESS,
+};
+use wim_parser::{RegionKind, WimParser};
+
+/// 测试区域覆盖整个文件，数据流附带所属镜像和路径
+#[test]
+fn test_layout_report_regions() {
+    let shared = b"shared between both images";
+    let mut bytes = build_wim(&[
+        ImageSpec::new("Image A")
+            .dir("Windows")
+            .file("Windows/a.txt", shared)
+            .file("only-a.bin", b"only in image a"),
+        ImageSpec::new("Image B").file("b.txt", shared),
+    ]);
+    add_integrity_table(&mut bytes, 4096);
+    let wim = write_bytes(&bytes);
+    let mut parser = WimParser::new(wim.path()).unwrap();
+    let report = parser.layout_report().unwrap();
+
+    assert_eq!(report.data_size, bytes.len() as u64);
+    assert_eq!(report.external_resources, 0);
+    assert_eq!(report.regions[0].kind, RegionKind::Header);
+    assert_eq!(report.regions[0].length, 208);
+    // 已定义的 26 个字段加保留区域
+    assert_eq!(report.regions[0].fields.len(), 27);
+    assert_eq!(report.regions[0].fields[26].name, "reserved");
+    // 区域连续且互不重叠
+    let mut end = 0;
+    for region in &report.regions {
+        assert_eq!(region.offset, end, "{region:?}");
+        end = region.end();
+    }
+    assert_eq!(end, bytes.len() as u64);
+
+    let kinds: Vec<RegionKind> = report.regions.iter().map(|region| region.kind).collect();
+    for kind in [
+        RegionKind::OffsetTable,
+        RegionKind::XmlData,
+        RegionKind::Integrity,
+        RegionKind::Metadata(1),
+        RegionKind::Metadata(2),
+    ] {
+        assert!(kinds.contains(&kind), "{kind:?}");
+    }
+
+    let hash = sha1_hash(shared);
+    let stream = report
+        .regions
+        .iter()
+        .find(|region| region.hash == Some(hash))
+        .unwrap();
+    assert_eq!(stream.kind, RegionKind::Stream);
+    let owners: Vec<(u32, &str)> = stream
+        .owners
+        .iter()
+        .map(|owner| (owner.image, owner.path.as_str()))
+        .collect();
+    assert_eq!(owners, [(1, "Windows/a.txt"), (2, "b.txt")]);
+    assert_eq!(stream.label, "镜像 1: Windows/a.txt (+1)");
+    let offset = bytes
+        .windows(shared.len())
+        .position(|w| w == shared)
+        .unwrap() as u64;
+    assert_eq!(report.region_at(offset + 3), Some(stream));
+    assert_eq!(report.unreferenced_bytes(), 0);
+}
+
+/// 测试固实资源、未引用的字节，以及 JSON、010 Editor 和 Kaitai 输出
+#[test]
+fn test_layout_report_exports() {
+    let data = b"solid stream content, solid stream content".repeat(8);
+    let bytes = build_wim(&[ImageSpec::new("ESD").file("Sources/data.bin", &data)]);
+    let bytes = solid_streams(
+        bytes,
+        XPRESS,
+        &[SolidGroup {
+            format: 1,
+            chunk: 4096,
+            compress: xpress_compress,
+            streams: vec![&data],
+        }],
+    );
+    let wim = write_bytes(&bytes);
+    let mut parser = WimParser::new(wim.path()).unwrap();
+    let report = parser.layout_report().unwrap();
+
+    let solid = report
+        .regions
+        .iter()
+        .find(|region| region.kind == RegionKind::SolidResource)
+        .unwrap();
+    assert_eq!(solid.label, "固实资源 (1 个数据流)");
+    assert_eq!(solid.original_size, data.len() as u64);
+    assert_eq!(solid.owners[0].path, "Sources/data.bin");
+    assert!(!report
+        .regions
+        .iter()
+        .any(|region| region.kind == RegionKind::Stream));
+    // 改写前的数据流和偏移表留在原处，已不再被引用
+    assert!(report.unreferenced_bytes() >= data.len() as u64);
+
+    let json = report.to_json();
+    assert!(json.starts_with("{\"base_offset\":0,"), "{json}");
+    assert!(json.contains("\"kind\":\"solid_resource\""), "{json}");
+    assert!(json.contains("\"path\":\"Sources/data.bin\""), "{json}");
+    assert!(json.contains("{\"name\":\"signature\",\"offset\":0,\"length\":8,"));
+
+    let template = report.to_010_template();
+    assert!(
+        template.contains("FSeek(0x0);\nWIM_HEADER header <"),
+        "{template}"
+    );
+    assert!(template.contains("comment=\"固实资源 (1 个数据流)\""));
+    assert_eq!(
+        template.matches("FSeek(").count(),
+        report.regions.len(),
+        "{template}"
+    );
+
+    let ksy = report.to_kaitai();
+    assert!(ksy.contains("meta:\n  id: wim_layout\n"));
+    assert!(ksy.contains("  header:\n    pos: 0x0\n    size: 208\n    type: wim_header\n"));
+    assert!(ksy.contains("    type: lookup_entry\n    repeat: expr\n"));
+    assert!(ksy.contains("doc: \"未引用的区域\""));
+}
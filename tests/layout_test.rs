@@ -0,0 +1,55 @@
+#![cfg(feature = "parser")]
+
+mod common;
+
+use common::{sha1_hash, write_wim, ImageSpec};
+use wim_parser::fmt::ToTable;
+use wim_parser::WimParser;
+
+/// 测试数据流布局计划：去重、按偏移排列、空文件
+#[test]
+fn test_plan_stream_layout() {
+    let wim = write_wim(&[
+        ImageSpec::new("Other").file("/other.bin", b"only in image 1"),
+        ImageSpec::new("Image")
+            .file("/b.txt", b"second")
+            .file("/a.txt", b"first")
+            .file("/copy/a.txt", b"first")
+            .file("/empty.txt", b"")
+            .symlink("/link", "a.txt"),
+    ]);
+    let mut parser = WimParser::new(wim.path()).unwrap();
+
+    let layout = parser.plan_stream_layout(2).unwrap();
+    assert_eq!(layout.streams.len(), 3);
+    assert!(layout
+        .streams
+        .windows(2)
+        .all(|pair| pair[0].offset < pair[1].offset));
+
+    let first = layout
+        .streams
+        .iter()
+        .find(|stream| stream.hash == sha1_hash(b"first"))
+        .unwrap();
+    let paths: Vec<&str> = first.uses.iter().map(|u| u.path.as_str()).collect();
+    assert_eq!(paths, ["a.txt", "copy/a.txt"]);
+    assert_eq!(first.size, 5);
+    assert!(!first.compressed);
+
+    let link = layout
+        .streams
+        .iter()
+        .find(|stream| stream.uses[0].path == "link")
+        .unwrap();
+    assert!(link.uses[0].is_reparse_data);
+
+    assert_eq!(layout.empty_files, ["empty.txt"]);
+    assert!(layout.missing.is_empty());
+    assert_eq!(layout.total_written_bytes(), layout.total_bytes() + 5);
+    assert_eq!(layout.table().rows().len(), 3);
+
+    // 按计划读取内容
+    let data = parser.read_stream(&first.hash).unwrap();
+    assert_eq!(data, b"first");
+}
@@ -0,0 +1,71 @@
+#![cfg(feature = "parser")]
+
+mod common;
+
+use common::{write_wim, ImageSpec};
+use wim_parser::{ParseOptions, ResourceLimits, WimParser};
+
+fn image() -> ImageSpec {
+    ImageSpec::new("Windows 11 Pro")
+        .dir("Windows")
+        .dir("Windows/System32")
+        .dir("Users")
+        .dir("Users/Public")
+        .file("Windows/System32/ntoskrnl.exe", b"kernel")
+        .file("Users/Public/a.txt", b"public")
+        .file("setup.exe", b"setup")
+}
+
+/// 测试只解析访问过的目录，并按路径读取文件
+#[test]
+fn test_lazy_tree_loads_visited_dirs() {
+    let wim = write_wim(&[image()]);
+    let mut parser = WimParser::new(wim.path()).unwrap();
+    let mut tree = parser.open_lazy_tree(1).unwrap();
+    assert!(tree.root().is_directory());
+    assert_eq!((tree.loaded_dirs(), tree.loaded_entries()), (0, 1));
+
+    let mut names: Vec<String> = tree
+        .list_dir("/windows\\SYSTEM32")
+        .unwrap()
+        .iter()
+        .map(|entry| entry.name.clone())
+        .collect();
+    names.sort();
+    assert_eq!(names, ["ntoskrnl.exe"]);
+    // 根目录、Windows、System32；Users 未被解析
+    assert_eq!(tree.loaded_dirs(), 3);
+
+    let mut out = Vec::new();
+    let size = tree
+        .extract_file(&mut parser, "Windows/System32/ntoskrnl.exe", &mut out)
+        .unwrap();
+    assert_eq!((size, out.as_slice()), (6, b"kernel".as_slice()));
+    assert_eq!(tree.loaded_dirs(), 3);
+
+    let entry = tree.find("setup.exe").unwrap().unwrap();
+    assert!(!entry.is_directory());
+    assert!(tree.find("Windows/missing.dll").unwrap().is_none());
+    assert!(tree.find("setup.exe/child").unwrap().is_none());
+    assert!(tree.list_dir("setup.exe").is_err());
+    assert!(tree
+        .extract_file(&mut parser, "Windows", &mut Vec::new())
+        .is_err());
+    assert_eq!(tree.loaded_dirs(), 3);
+}
+
+/// 测试按需加载同样受目录项数量限制
+#[test]
+fn test_lazy_tree_respects_dentry_limit() {
+    let wim = write_wim(&[image()]);
+    let limits = ResourceLimits {
+        max_dentries: Some(4),
+        ..ResourceLimits::default()
+    };
+    let mut parser =
+        WimParser::with_options(wim.path(), ParseOptions::new().limits(limits)).unwrap();
+    let mut tree = parser.open_lazy_tree(1).unwrap();
+    // 根目录 + 3 个子目录项
+    tree.list_dir("").unwrap();
+    assert!(tree.list_dir("Windows").is_err());
+}
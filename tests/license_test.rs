@@ -0,0 +1,112 @@
+#![cfg(feature = "parser")]
+
+mod common;
+
+use common::{write_wim, ImageSpec};
+use wim_parser::fmt::ToTable;
+use wim_parser::{ChannelSource, LicenseChannel, WimParser};
+
+const SKUS: &str = "/Windows/System32/spp/tokens/skus";
+
+/// 构造包含 DigitalProductId4 值的最小 SOFTWARE 配置单元
+fn software_hive(channel: &str) -> Vec<u8> {
+    let name = b"DigitalProductId4";
+    let mut hive = vec![0u8; 0x1000];
+
+    // vk 单元（前 4 字节为单元大小）
+    let mut vk = vec![0u8; 4];
+    vk.extend(b"vk");
+    vk.extend((name.len() as u16).to_le_bytes());
+    vk.extend(1272u32.to_le_bytes());
+    vk.extend(0x100u32.to_le_bytes());
+    vk.extend(3u32.to_le_bytes()); // REG_BINARY
+    vk.extend(1u16.to_le_bytes());
+    vk.extend(0u16.to_le_bytes());
+    vk.extend(name);
+    hive.extend(&vk);
+    hive.resize(0x1100, 0);
+
+    // 数据单元
+    let mut data = vec![0u8; 1272];
+    for (i, unit) in channel.encode_utf16().enumerate() {
+        data[0x3F8 + i * 2..0x3F8 + i * 2 + 2].copy_from_slice(&unit.to_le_bytes());
+    }
+    hive.extend((-(1276i32)).to_le_bytes());
+    hive.extend(data);
+    hive
+}
+
+/// 测试从注册表、版本 ID 和许可证令牌识别通道
+#[test]
+fn test_license_info() {
+    let specs = [
+        ImageSpec::new("Windows 10 Pro")
+            .file(
+                "/Windows/System32/config/SOFTWARE",
+                &software_hive("Volume:GVLK"),
+            )
+            .file(
+                &format!("{SKUS}/Professional/Professional-Retail-ul-oob-rtm.xrm-ms"),
+                b"<r/>",
+            )
+            .file(
+                &format!("{SKUS}/Professional/Professional-Volume-GVLK-ul-oob-rtm.xrm-ms"),
+                b"<v/>",
+            ),
+        ImageSpec::new("Windows Server 2022 Standard Evaluation")
+            .extra_xml("<WINDOWS><EDITIONID>ServerStandardEval</EDITIONID></WINDOWS>"),
+        ImageSpec::new("Windows 11 Home").file(
+            &format!("{SKUS}/Core/Core-OEM-DM-ul-oob-rtm.xrm-ms"),
+            b"<o/>",
+        ),
+        ImageSpec::new("Windows 11 Home").file("/Windows/System32/config/SOFTWARE", b"regf"),
+    ];
+    let wim = write_wim(&specs);
+    let mut parser = WimParser::new(wim.path()).unwrap();
+
+    let info = parser.license_info(1).unwrap();
+    assert_eq!(info.channel, Some(LicenseChannel::Volume));
+    assert_eq!(info.raw_channel.as_deref(), Some("Volume:GVLK"));
+    assert_eq!(info.source, Some(ChannelSource::DigitalProductId));
+    assert_eq!(
+        info.sku_channels,
+        [LicenseChannel::Retail, LicenseChannel::Volume]
+    );
+
+    let info = parser.license_info(2).unwrap();
+    assert_eq!(info.channel, Some(LicenseChannel::Evaluation));
+    assert_eq!(info.source, Some(ChannelSource::EditionId));
+
+    let info = parser.license_info(3).unwrap();
+    assert_eq!(info.channel, Some(LicenseChannel::Oem));
+    assert_eq!(info.source, Some(ChannelSource::SkuTokens));
+
+    let info = parser.license_info(4).unwrap();
+    assert_eq!(info.channel, None);
+    assert_eq!(info.raw_channel, None);
+
+    let infos: Vec<_> = (1..=4).map(|i| parser.license_info(i).unwrap()).collect();
+    let table = infos.as_slice().table();
+    assert_eq!(table.rows()[0][1], "Volume");
+    assert_eq!(table.rows()[3][1], "-");
+
+    assert!(parser.license_info(5).is_err());
+}
+
+/// 测试通道字符串识别
+#[test]
+fn test_channel_strings() {
+    assert_eq!(
+        LicenseChannel::from_channel_str("OEM:NONSLP"),
+        Some(LicenseChannel::Oem)
+    );
+    assert_eq!(
+        LicenseChannel::from_channel_str("Retail:TB:Eval"),
+        Some(LicenseChannel::Evaluation)
+    );
+    assert_eq!(
+        LicenseChannel::from_channel_str("Retail"),
+        Some(LicenseChannel::Retail)
+    );
+    assert_eq!(LicenseChannel::from_channel_str("???"), None);
+}
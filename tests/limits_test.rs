@@ -0,0 +1,153 @@
+#![cfg(feature = "parser")]
+
+mod common;
+
+use common::{write_wim, ImageSpec};
+use std::fs::{File, FileTimes};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use wim_parser::error::{codes, error_code};
+#[cfg(feature = "verify")]
+use wim_parser::StreamVerifyOptions;
+use wim_parser::{Error, ParseOptions, ResourceLimits, WimCatalogCache, WimParser};
+
+fn limited(path: &std::path::Path, limits: ResourceLimits) -> WimParser {
+    WimParser::with_options(path, ParseOptions::new().limits(limits)).unwrap()
+}
+
+fn exceeded(err: &anyhow::Error) -> &'static str {
+    assert_eq!(error_code(err), codes::LIMIT_EXCEEDED);
+    match err.chain().find_map(|cause| cause.downcast_ref::<Error>()) {
+        Some(Error::LimitExceeded { limit, .. }) => limit,
+        other => panic!("预期超出资源限制，实际: {other:?}"),
+    }
+}
+
+/// 测试 XML、内存和目录项数量限制
+#[test]
+fn test_parse_limits() {
+    let wim = write_wim(&[ImageSpec::new("Image")
+        .file("/a.txt", &[b'a'; 4096])
+        .file("/b.txt", b"b")
+        .file("/c/d.txt", b"d")]);
+
+    let mut parser = limited(
+        wim.path(),
+        ResourceLimits {
+            max_xml_bytes: Some(16),
+            ..ResourceLimits::default()
+        },
+    );
+    let err = parser.parse_full().unwrap_err();
+    assert_eq!(exceeded(&err), "max_xml_bytes");
+
+    let mut parser = limited(
+        wim.path(),
+        ResourceLimits {
+            max_memory: Some(1024),
+            ..ResourceLimits::default()
+        },
+    );
+    let hash = common::sha1_hash(&[b'a'; 4096]);
+    let err = parser.read_stream(&hash).unwrap_err();
+    assert_eq!(exceeded(&err), "max_memory");
+
+    // 根目录、3 个文件和 1 个子目录共 5 个目录项
+    let mut parser = limited(
+        wim.path(),
+        ResourceLimits {
+            max_dentries: Some(4),
+            ..ResourceLimits::default()
+        },
+    );
+    let err = parser.sha1_manifest(1).unwrap_err();
+    assert_eq!(exceeded(&err), "max_dentries");
+
+    let mut parser = limited(
+        wim.path(),
+        ResourceLimits {
+            max_dentries: Some(5),
+            ..ResourceLimits::with_memory_budget(256 * 1024 * 1024)
+        },
+    );
+    parser.parse_full().unwrap();
+    assert_eq!(parser.sha1_manifest(1).unwrap().len(), 3);
+    assert_eq!(parser.read_stream(&hash).unwrap().len(), 4096);
+}
+
+/// 测试读取块数量和内存限制约束并行校验
+#[test]
+#[cfg(feature = "verify")]
+fn test_verify_respects_chunk_limits() {
+    let wim = write_wim(&[ImageSpec::new("Image")
+        .file("/a.txt", b"aaa")
+        .file("/b.txt", b"bbb")
+        .file("/c.txt", b"ccc")]);
+
+    let mut parser = limited(
+        wim.path(),
+        ResourceLimits {
+            max_open_chunks: Some(1),
+            ..ResourceLimits::default()
+        },
+    );
+    let report = parser
+        .verify_all_streams_with(&StreamVerifyOptions::new().threads(4))
+        .unwrap();
+    assert_eq!(report.threads, 1);
+    assert!(report.is_ok());
+
+    // 内存预算小于一个读取块时缩小块大小，仍能完成校验
+    let mut parser = limited(
+        wim.path(),
+        ResourceLimits {
+            max_memory: Some(64 * 1024),
+            ..ResourceLimits::default()
+        },
+    );
+    let report = parser
+        .verify_all_streams_with(&StreamVerifyOptions::new().threads(4))
+        .unwrap();
+    assert_eq!(report.threads, 1);
+    assert!(report.is_ok());
+}
+
+/// 测试缓存内容超出内存限制时淘汰最早的条目
+#[test]
+fn test_cache_memory_limit() {
+    let first = write_wim(&[ImageSpec::new("Image").file("a.txt", b"abc")]);
+    let second = write_wim(&[ImageSpec::new("Image").file("a.txt", b"abc")]);
+    let mtime = SystemTime::now();
+    for (file, offset) in [(&first, 0), (&second, 60)] {
+        File::options()
+            .write(true)
+            .open(file.path())
+            .unwrap()
+            .set_times(FileTimes::new().set_modified(mtime + Duration::from_secs(offset)))
+            .unwrap();
+    }
+
+    let unlimited = Arc::new(WimCatalogCache::new());
+    WimParser::with_cache(first.path(), unlimited.clone())
+        .unwrap()
+        .parse_full()
+        .unwrap();
+    let entry_bytes = unlimited.stats().bytes;
+    assert!(entry_bytes > 0);
+
+    let cache = Arc::new(WimCatalogCache::with_limits(&ResourceLimits {
+        max_memory: Some(entry_bytes),
+        ..ResourceLimits::default()
+    }));
+    let mut parser = WimParser::with_cache(first.path(), cache.clone()).unwrap();
+    parser.parse_full().unwrap();
+    let first_key = parser.cache_key().unwrap();
+    assert!(cache.contains(&first_key));
+
+    let mut parser = WimParser::with_cache(second.path(), cache.clone()).unwrap();
+    parser.parse_full().unwrap();
+    assert_eq!(cache.len(), 1);
+    assert!(!cache.contains(&first_key));
+    assert!(cache.contains(&parser.cache_key().unwrap()));
+    assert!(cache.stats().bytes <= entry_bytes);
+}
@@ -0,0 +1,77 @@
+#![cfg(feature = "parser")]
+
+mod common;
+
+use common::{write_wim, ImageSpec};
+use wim_parser::error::{codes, error_code};
+use wim_parser::{ParseOptions, ResourceLimits, WimParser};
+
+fn image() -> ImageSpec {
+    ImageSpec::new("Windows 11 Pro")
+        .dir("Windows")
+        .dir("Windows/System32")
+        .dir("Users")
+        .file("Windows/System32/ntoskrnl.exe", b"kernel image")
+        .file("Users/a.txt", b"public")
+        .file("setup.exe", b"setup")
+        .file("empty.txt", b"")
+}
+
+/// 测试列出的路径与完整目录树一致，并带有大小和属性
+#[test]
+fn test_list_files() {
+    let wim = write_wim(&[image()]);
+    let mut parser = WimParser::new(wim.path()).unwrap();
+
+    let files: Vec<_> = parser
+        .list_files(1)
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    let mut expected = Vec::new();
+    let metadata = parser.read_image_metadata(1).unwrap();
+    metadata
+        .root
+        .walk(&mut |path, _| expected.push(path.to_string()));
+    let paths: Vec<&str> = files.iter().map(|(path, _)| path.as_str()).collect();
+    assert_eq!(paths, expected);
+    assert_eq!(files.len(), 7);
+
+    let find = |path: &str| &files.iter().find(|(p, _)| p == path).unwrap().1;
+    let kernel = find("Windows/System32/ntoskrnl.exe");
+    assert_eq!(kernel.size, 12);
+    assert!(!kernel.is_directory());
+    assert_eq!(find("empty.txt").size, 0);
+    assert!(find("Windows/System32").is_directory());
+    assert_eq!(find("Windows/System32").size, 0);
+    // 目录先于其内容
+    let position = |path: &str| paths.iter().position(|p| *p == path).unwrap();
+    assert!(position("Windows") < position("Windows/System32/ntoskrnl.exe"));
+
+    assert!(parser.list_files(2).is_err());
+}
+
+/// 测试目录在迭代到时才解析，超出目录项数量限制时返回错误后结束
+#[test]
+fn test_list_files_is_lazy() {
+    let wim = write_wim(&[image()]);
+    let mut parser = WimParser::new(wim.path()).unwrap();
+    let mut list = parser.list_files(1).unwrap();
+    // 根目录 + 4 个子目录项
+    assert_eq!(list.loaded_entries(), 5);
+    let first = list.find(|item| item.as_ref().unwrap().0 == "Windows/System32/ntoskrnl.exe");
+    assert!(first.is_some());
+    // Users 目录尚未解析
+    assert_eq!(list.loaded_entries(), 7);
+
+    let limits = ResourceLimits {
+        max_dentries: Some(6),
+        ..ResourceLimits::default()
+    };
+    let mut parser =
+        WimParser::with_options(wim.path(), ParseOptions::new().limits(limits)).unwrap();
+    let results: Vec<_> = parser.list_files(1).unwrap().collect();
+    let err = results.last().unwrap().as_ref().unwrap_err();
+    assert_eq!(error_code(err), codes::LIMIT_EXCEEDED);
+    assert!(results[..results.len() - 1].iter().all(Result::is_ok));
+}
@@ -0,0 +1,83 @@
+#![cfg(feature = "parser")]
+
+mod common;
+
+use common::{write_wim, ImageSpec};
+use wim_parser::{format, WimParser};
+
+fn languages_xml(languages: &[&str], default: &str) -> String {
+    let languages: String = languages
+        .iter()
+        .map(|language| format!("<LANGUAGE>{language}</LANGUAGE>"))
+        .collect();
+    format!(
+        "<WINDOWS><ARCH>9</ARCH><LANGUAGES>{languages}<DEFAULT>{default}</DEFAULT></LANGUAGES></WINDOWS>"
+    )
+}
+
+/// 测试按区域设置选择显示名称的回退规则
+#[test]
+fn test_display_name_for_locale() {
+    let wim = write_wim(&[
+        ImageSpec::new("Windows 11 专业版")
+            .english_name("Windows 11 Pro")
+            .extra_xml(&languages_xml(&["zh-CN"], "zh-CN")),
+        ImageSpec::new("Windows 11 Pro").extra_xml(&languages_xml(&["en-US", "de-DE"], "en-US")),
+        ImageSpec::new("Windows 11 家庭版").english_name("Windows 11 Home"),
+    ]);
+
+    let mut parser = WimParser::new(wim.path()).unwrap();
+    parser.parse_full().unwrap();
+    let images = parser.get_images();
+
+    assert_eq!(images[0].english_name.as_deref(), Some("Windows 11 Pro"));
+    assert_eq!(images[0].default_language.as_deref(), Some("zh-CN"));
+    assert_eq!(images[1].languages, ["en-US", "de-DE"]);
+
+    // 语言一致（包括只有主语言相同）时使用显示名称
+    assert_eq!(images[0].display_name_for("zh-CN"), "Windows 11 专业版");
+    assert_eq!(images[0].display_name_for("zh_cn"), "Windows 11 专业版");
+    assert_eq!(images[0].display_name_for("zh-TW"), "Windows 11 专业版");
+    // 英文及其他语言回退到 NAME
+    assert_eq!(images[0].display_name_for("en-US"), "Windows 11 Pro");
+    assert_eq!(images[0].display_name_for("fr-FR"), "Windows 11 Pro");
+    assert_eq!(images[1].display_name_for("ja-JP"), "Windows 11 Pro");
+    // 显示名称语言未知时只有英文区域设置使用 NAME
+    assert_eq!(images[2].display_name_for("zh-CN"), "Windows 11 家庭版");
+    assert_eq!(images[2].display_name_for("en-GB"), "Windows 11 Home");
+
+    // 字符串解析路径提取相同的字段
+    let xml = r#"<IMAGE INDEX="1"><WINDOWS><LANGUAGES><LANGUAGE>de-DE</LANGUAGE><LANGUAGE>en-US</LANGUAGE><DEFAULT>de-DE</DEFAULT></LANGUAGES></WINDOWS><DISPLAYNAME>Windows 10 Pro</DISPLAYNAME><NAME>Windows 10 Pro</NAME></IMAGE>"#;
+    let image = format::parse_single_image_xml(xml);
+    assert_eq!(image.languages, ["de-DE", "en-US"]);
+    assert_eq!(image.display_language(), Some("de-DE"));
+    assert_eq!(image.english_name.as_deref(), Some("Windows 10 Pro"));
+}
+
+/// 测试 WindowsInfo 按区域设置输出镜像名称
+#[test]
+fn test_windows_info_with_locale() {
+    let wim = write_wim(&[
+        ImageSpec::new("Windows 11 专业版")
+            .english_name("Windows 11 Pro")
+            .extra_xml(&languages_xml(&["zh-CN"], "zh-CN")),
+        ImageSpec::new("Windows 11 教育版")
+            .english_name("Windows 11 Education")
+            .extra_xml(&languages_xml(&["zh-CN"], "zh-CN")),
+    ]);
+
+    let mut parser = WimParser::new(wim.path()).unwrap();
+    parser.parse_full().unwrap();
+    let info = parser.get_windows_info().unwrap();
+
+    assert!(!info.to_string().contains("镜像:"));
+    assert!(info
+        .clone()
+        .with_locale("zh-CN")
+        .to_string()
+        .contains("镜像: Windows 11 专业版, Windows 11 教育版"));
+    assert!(info
+        .with_locale("en-US")
+        .to_string()
+        .contains("镜像: Windows 11 Pro, Windows 11 Education"));
+}
@@ -0,0 +1,83 @@
+#![cfg(feature = "parser")]
+
+mod common;
+
+use std::fs::File;
+use std::time::Duration;
+
+use common::{write_wim, ImageSpec};
+use wim_parser::error::{codes, error_code};
+use wim_parser::{export_edition, Edition, FileFlags, LockPolicy, ParseOptions, WimParser};
+
+/// 测试其他进程持有锁时编辑操作立即失败，关闭加锁后可以继续
+#[test]
+fn test_edit_operations_respect_lock() {
+    let wim = write_wim(&[ImageSpec::new("Image A"), ImageSpec::new("Image B")]);
+    let holder = File::open(wim.path()).unwrap();
+    holder.lock().unwrap();
+
+    let mut parser = WimParser::new(wim.path()).unwrap();
+    parser.parse_full().unwrap();
+    let err = parser
+        .patch_header(|header| header.bootable_image_index = 1)
+        .unwrap_err();
+    assert_eq!(error_code(&err), codes::CONFLICT_LOCKED);
+    let err = parser
+        .transaction()
+        .rename_image(1, "Renamed")
+        .commit()
+        .unwrap_err();
+    assert_eq!(error_code(&err), codes::CONFLICT_LOCKED);
+    assert_eq!(
+        wim_parser::error::exit_code(error_code(&err)),
+        wim_parser::error::exit_codes::CONFLICT
+    );
+
+    let esd = write_wim(&[ImageSpec::new("Windows 11 Pro")
+        .extra_xml("<WINDOWS><ARCH>9</ARCH><EDITIONID>Professional</EDITIONID></WINDOWS>")]);
+    let out = tempfile::NamedTempFile::new().unwrap();
+    let out_lock = File::open(out.path()).unwrap();
+    out_lock.lock().unwrap();
+    let err = export_edition(esd.path(), Edition::Pro, out.path()).unwrap_err();
+    assert_eq!(error_code(&err), codes::CONFLICT_LOCKED);
+    drop(out_lock);
+    export_edition(esd.path(), Edition::Pro, out.path()).unwrap();
+
+    parser.set_options(ParseOptions::new().lock_policy(LockPolicy::Disabled));
+    parser
+        .patch_header(|header| header.bootable_image_index = 1)
+        .unwrap();
+
+    drop(holder);
+    parser.set_options(ParseOptions::new());
+    parser
+        .patch_header(|header| header.bootable_image_index = 2)
+        .unwrap();
+    assert_eq!(
+        wim_parser::probe_header(wim.path())
+            .unwrap()
+            .bootable_image_index,
+        2
+    );
+}
+
+/// 测试等待策略在其他进程释放锁后继续编辑
+#[test]
+fn test_lock_policy_wait() {
+    let wim = write_wim(&[ImageSpec::new("Image A")]);
+    let holder = File::open(wim.path()).unwrap();
+    holder.lock().unwrap();
+    let release = std::thread::spawn(move || {
+        std::thread::sleep(Duration::from_millis(100));
+        drop(holder);
+    });
+
+    let options = ParseOptions::new().lock_policy(LockPolicy::Wait);
+    assert_eq!(options.file_lock_policy(), LockPolicy::Wait);
+    let mut parser = WimParser::with_options(wim.path(), options).unwrap();
+    let changes = parser
+        .patch_header(|header| header.file_flags |= FileFlags::READONLY)
+        .unwrap();
+    assert_eq!(changes.len(), 1);
+    release.join().unwrap();
+}
@@ -0,0 +1,120 @@
+#![cfg(feature = "parser")]
+
+mod common;
+
+use common::{build_wim, chunked_stream, lzx_compress, sha1_hash, write_bytes, ImageSpec, LZX};
+use std::io::Read;
+use wim_parser::error::{codes, error_code};
+use wim_parser::{Compression, WimParser};
+
+const CHUNK: u32 = 32768;
+
+/// 文本、长重复段、x86 CALL 指令和不可压缩的字节混合的测试数据
+fn sample_data() -> Vec<u8> {
+    let mut data = Vec::new();
+    for i in 0..600 {
+        data.extend(format!("line {i}: the quick brown fox jumps over the lazy dog\r\n").bytes());
+    }
+    for i in 0..300i32 {
+        // CALL rel32：正向、反向和超出范围的目标
+        data.push(0xE8);
+        data.extend((i * 37 - 4000).to_le_bytes());
+        data.extend([0x90, 0x90, 0x48, 0x8B]);
+    }
+    data.extend(std::iter::repeat_n(b'A', 5000));
+    let mut state = 0x1234_5678u32;
+    data.extend((0..8000).map(|_| {
+        state ^= state << 13;
+        state ^= state >> 17;
+        state ^= state << 5;
+        state as u8
+    }));
+    data.extend((0..20000u32).map(|i| (i % 251) as u8));
+    data
+}
+
+/// 将数据流改写为 LZX 分块资源
+fn lzx_wim(data: &[u8], chunks: &[&[u8]]) -> Vec<u8> {
+    let bytes = build_wim(&[ImageSpec::new("Test").file("data.bin", data)]);
+    chunked_stream(
+        bytes,
+        LZX,
+        CHUNK,
+        sha1_hash(data),
+        data.len() as u64,
+        chunks,
+    )
+}
+
+/// 测试读取 LZX 压缩的数据流（含完整的 32 KiB 分块和较小的末尾分块）
+#[test]
+fn test_read_lzx_stream() {
+    let data = sample_data();
+    let hash = sha1_hash(&data);
+    let compressed: Vec<Vec<u8>> = data.chunks(CHUNK as usize).map(lzx_compress).collect();
+    assert!(compressed
+        .iter()
+        .zip(data.chunks(CHUNK as usize))
+        .all(|(packed, chunk)| packed.len() < chunk.len()));
+    let chunks: Vec<&[u8]> = compressed.iter().map(Vec::as_slice).collect();
+    let wim = write_bytes(&lzx_wim(&data, &chunks));
+    let mut parser = WimParser::new(wim.path()).unwrap();
+
+    let resource = parser.stream_resource(&hash).unwrap().unwrap();
+    let mut reader = parser.open_resource(&resource).unwrap();
+    assert_eq!(reader.compression(), Compression::Lzx { chunk: CHUNK });
+    assert_eq!(reader.chunk_count(), 3);
+    let mut read = Vec::new();
+    reader.read_to_end(&mut read).unwrap();
+    assert_eq!(read, data);
+    assert_eq!(parser.read_stream(&hash).unwrap(), data);
+}
+
+/// 测试未压缩块：对齐后读取最近偏移和原始字节，奇数长度的块后有 1 字节填充，之后继续读取逐字块
+#[test]
+fn test_read_lzx_uncompressed_block() {
+    let mut block = b"uncompressed block ".repeat(52);
+    block.extend([0xE8, 0x20, 0, 0, 0, 0x90]);
+    block.extend(b"tail of the block");
+    assert_eq!(block.len() % 2, 1);
+    let verbatim = b"verbatim block follows ".repeat(200);
+    let data = [block.as_slice(), &verbatim].concat();
+    let hash = sha1_hash(&data);
+
+    let size = block.len() as u16;
+    let mut packed = Vec::new();
+    packed.extend(((3 << 13) | (size >> 4)).to_le_bytes());
+    packed.extend(((size & 0xF) << 12).to_le_bytes());
+    for _ in 0..3 {
+        packed.extend(1u32.to_le_bytes());
+    }
+    // 解压时撤销 E8 转换：绝对地址 0x20 + 位置还原为相对地址 0x20
+    let call = block.iter().position(|&b| b == 0xE8).unwrap();
+    block[call + 1..call + 5].copy_from_slice(&(0x20 + call as i32).to_le_bytes());
+    packed.extend(&block);
+    packed.push(0);
+    packed.extend(lzx_compress(&verbatim));
+    assert!(packed.len() < data.len());
+
+    let wim = write_bytes(&lzx_wim(&data, &[&packed]));
+    let mut parser = WimParser::new(wim.path()).unwrap();
+    assert_eq!(parser.read_stream(&hash).unwrap(), data);
+}
+
+/// 测试损坏的 LZX 分块：无效的块类型、超出分块的块大小
+#[test]
+fn test_corrupt_lzx_chunk() {
+    let data = b"abcabcabcabcabcabcabcabcabcabcabcabc".repeat(20);
+    let hash = sha1_hash(&data);
+
+    let wim = write_bytes(&lzx_wim(&data, &[&[0xFF; 100]]));
+    let mut parser = WimParser::new(wim.path()).unwrap();
+    let err = parser.read_stream(&hash).unwrap_err();
+    assert_eq!(error_code(&err), codes::FORMAT_CORRUPT_DATA);
+
+    // 逐字块，块大小 0x7FFF 大于分块的未压缩大小
+    let wim = write_bytes(&lzx_wim(&data, &[&[0xFF, 0x27, 0x00, 0xF0, 0, 0]]));
+    let mut parser = WimParser::new(wim.path()).unwrap();
+    let err = parser.read_stream(&hash).unwrap_err();
+    assert_eq!(error_code(&err), codes::FORMAT_CORRUPT_DATA);
+}
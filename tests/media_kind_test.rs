@@ -0,0 +1,51 @@
+#![cfg(feature = "parser")]
+
+mod common;
+
+use common::{write_wim, ImageSpec};
+use wim_parser::{Arch, MediaKind, WimParser};
+
+fn arch_image(name: &str, arch: u32) -> ImageSpec {
+    ImageSpec::new(name).extra_xml(&format!("<WINDOWS><ARCH>{arch}</ARCH></WINDOWS>"))
+}
+
+/// 测试同时含 ARM64 和 x64 镜像的介质返回完整架构集合并归为多架构
+#[test]
+fn test_multi_arch_media() {
+    let wim = write_wim(&[
+        arch_image("Windows 11 Pro", 12),
+        arch_image("Windows 11 Pro", 9),
+        arch_image("Windows 11 Home", 12),
+        ImageSpec::new("Unlabelled"),
+    ]);
+    let mut parser = WimParser::new(wim.path()).unwrap();
+    parser.parse_full().unwrap();
+
+    assert_eq!(parser.architectures(), [Arch::Arm64, Arch::X64]);
+    let kind = parser.media_kind();
+    assert_eq!(kind, MediaKind::MultiArch(vec![Arch::Arm64, Arch::X64]));
+    assert!(kind.is_multi_arch());
+    assert!(kind.contains(Arch::X64));
+    assert!(!kind.contains(Arch::X86));
+    assert_eq!(kind.to_string(), "多架构 (ARM64, x64)");
+}
+
+/// 测试单架构和没有架构信息的分类
+#[test]
+fn test_single_arch_and_unknown_media() {
+    let wim = write_wim(&[
+        arch_image("Windows 11 Pro", 12),
+        arch_image("Windows 11 Home", 12),
+    ]);
+    let mut parser = WimParser::new(wim.path()).unwrap();
+    parser.parse_full().unwrap();
+    assert_eq!(parser.media_kind(), MediaKind::SingleArch(Arch::Arm64));
+    assert_eq!(parser.media_kind().architectures(), [Arch::Arm64]);
+
+    assert_eq!(MediaKind::from_architectures(&[]), MediaKind::Unknown);
+    assert!(MediaKind::Unknown.architectures().is_empty());
+    assert_eq!(
+        MediaKind::from_architectures(&[Arch::X64, Arch::Unknown(42), Arch::X64]),
+        MediaKind::MultiArch(vec![Arch::X64, Arch::Unknown(42)])
+    );
+}
@@ -0,0 +1,85 @@
+#![cfg(feature = "parser")]
+
+mod common;
+
+use common::{build_wim, write_bytes, write_wim, ImageSpec};
+use wim_parser::{validate_media_set, Arch, MediaSetIssue};
+
+/// 带架构、内部版本号和语言的镜像 XML
+fn windows_xml(arch: u32, build: u32, language: &str) -> String {
+    format!(
+        "<WINDOWS><ARCH>{arch}</ARCH><VERSION><BUILD>{build}</BUILD></VERSION>\
+         <LANGUAGES><LANGUAGE>{language}</LANGUAGE><DEFAULT>{language}</DEFAULT></LANGUAGES></WINDOWS>"
+    )
+}
+
+fn boot_wim(arch: u32, build: u32, language: &str, with_setup: bool) -> Vec<u8> {
+    let xml = windows_xml(arch, build, language);
+    let mut setup = ImageSpec::new("Microsoft Windows Setup").extra_xml(&xml);
+    if with_setup {
+        setup = setup.file("/sources/setup.exe", b"MZ");
+    }
+    let mut bytes = build_wim(&[
+        ImageSpec::new("Microsoft Windows PE").extra_xml(&xml),
+        setup,
+    ]);
+    bytes[120..124].copy_from_slice(&2u32.to_le_bytes());
+    bytes
+}
+
+/// 测试同一套介质的 boot.wim 和 install.wim 通过检查
+#[test]
+fn test_validate_media_set_matching() {
+    let boot = write_bytes(&boot_wim(9, 22631, "zh-CN", true));
+    let install = write_wim(&[
+        ImageSpec::new("Windows 11 Home").extra_xml(&windows_xml(9, 22631, "zh-CN")),
+        ImageSpec::new("Windows 11 Pro").extra_xml(&windows_xml(9, 22631, "zh-CN")),
+    ]);
+
+    let validation = validate_media_set(boot.path(), install.path()).unwrap();
+    assert!(validation.is_valid(), "{:?}", validation.issues);
+    assert_eq!(validation.setup_image, Some(2));
+    assert!(validation.has_setup);
+    assert_eq!(validation.boot_architectures, [Arch::X64]);
+    assert_eq!(validation.boot_build, Some(22631));
+    assert_eq!(validation.install_builds, [22631]);
+    assert_eq!(validation.install_languages, ["zh-CN"]);
+}
+
+/// 测试混用介质时报告架构、内部版本、语言不一致和缺少 setup.exe
+#[test]
+fn test_validate_media_set_mixed() {
+    let boot = write_bytes(&boot_wim(9, 22621, "en-US", false));
+    let install =
+        write_wim(&[ImageSpec::new("Windows 11 Pro").extra_xml(&windows_xml(12, 26100, "zh-CN"))]);
+
+    let validation = validate_media_set(boot.path(), install.path()).unwrap();
+    assert!(!validation.has_setup);
+    assert_eq!(
+        validation.issues,
+        [
+            MediaSetIssue::MissingSetup { index: 2 },
+            MediaSetIssue::ArchMismatch {
+                boot: vec![Arch::X64],
+                install: vec![Arch::Arm64]
+            },
+            MediaSetIssue::BuildMismatch {
+                boot: 22621,
+                install: 26100
+            },
+            MediaSetIssue::NoCommonLanguage {
+                boot: vec!["en-US".to_string()],
+                install: vec!["zh-CN".to_string()]
+            },
+        ]
+    );
+    assert_eq!(
+        validation.issues[2].to_string(),
+        "install.wim 内部版本 26100 与 boot.wim 内部版本 22621 不一致"
+    );
+
+    // 没有镜像的 boot.wim
+    let empty = write_wim(&[]);
+    let validation = validate_media_set(empty.path(), install.path()).unwrap();
+    assert_eq!(validation.issues, [MediaSetIssue::NoSetupImage]);
+}
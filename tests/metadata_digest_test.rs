@@ -0,0 +1,75 @@
+#![cfg(feature = "verify")]
+
+mod common;
+
+use common::{write_wim, ImageSpec};
+use wim_parser::verify::Digest;
+use wim_parser::WimParser;
+
+fn golden_image() -> ImageSpec {
+    ImageSpec::new("Golden")
+        .dir("/Windows/System32")
+        .file("/Windows/System32/kernel32.dll", b"MZ kernel32")
+        .file("/Windows/System32/ntdll.dll", b"MZ ntdll")
+        .file("/readme.txt", b"hello")
+        .extra_xml("<DESCRIPTION>Golden image</DESCRIPTION>")
+}
+
+fn digest(image: ImageSpec) -> Digest {
+    let wim = write_wim(&[image]);
+    WimParser::new(wim.path())
+        .unwrap()
+        .metadata_digest(1)
+        .unwrap()
+}
+
+/// 测试重建容器（镜像索引、资源偏移和目录项顺序变化）后摘要不变
+#[test]
+fn test_metadata_digest_survives_rebuild() {
+    let original = digest(golden_image());
+    assert_eq!(original.algorithm(), "SHA-256");
+
+    // 同一镜像放在另一个 WIM 的第 2 个位置，数据流偏移和镜像索引都不同
+    let rebuilt = write_wim(&[
+        ImageSpec::new("Other").file("/other.txt", b"other"),
+        golden_image(),
+    ]);
+    let mut parser = WimParser::new(rebuilt.path()).unwrap();
+    assert_eq!(parser.metadata_digest(2).unwrap(), original);
+    assert_ne!(parser.metadata_digest(1).unwrap(), original);
+
+    // 目录项以不同顺序存储
+    let reordered = ImageSpec::new("Golden")
+        .file("/readme.txt", b"hello")
+        .dir("/Windows/System32")
+        .file("/Windows/System32/ntdll.dll", b"MZ ntdll")
+        .file("/Windows/System32/kernel32.dll", b"MZ kernel32")
+        .extra_xml("<DESCRIPTION>Golden image</DESCRIPTION>");
+    assert_eq!(digest(reordered), original);
+}
+
+/// 测试文件内容、时间、安全描述符或 XML 的任何修改都会改变摘要
+#[test]
+fn test_metadata_digest_detects_changes() {
+    let original = digest(golden_image());
+
+    let tampered = [
+        ImageSpec::new("Golden")
+            .dir("/Windows/System32")
+            .file("/Windows/System32/kernel32.dll", b"MZ kernel32")
+            .file("/Windows/System32/ntdll.dll", b"MZ patched")
+            .file("/readme.txt", b"hello")
+            .extra_xml("<DESCRIPTION>Golden image</DESCRIPTION>"),
+        golden_image().file("/Windows/System32/extra.dll", b""),
+        golden_image().write_time(0x01D9_0000_0000_0000),
+        golden_image().secured(),
+        golden_image().extra_xml("<FLAGS>Professional</FLAGS>"),
+    ];
+    for image in tampered {
+        assert_ne!(digest(image), original);
+    }
+
+    let wim = write_wim(&[golden_image()]);
+    let mut parser = WimParser::new(wim.path()).unwrap();
+    assert!(parser.metadata_digest(2).is_err());
+}
@@ -0,0 +1,106 @@
+#![cfg(feature = "parser")]
+
+mod common;
+
+use common::{build_wim, write_bytes, write_wim, ImageSpec};
+use wim_parser::{
+    decode_name, lossy_name, ApplyOptions, DirectoryTarget, NameMatching, ParseOptions, WimParser,
+};
+
+/// 将 WIM 中 UTF-16 名称 `from` 的最后一个码元替换为 `unit`
+fn patch_name(bytes: &mut [u8], from: &str, unit: u16) {
+    let pattern: Vec<u8> = from.encode_utf16().flat_map(u16::to_le_bytes).collect();
+    let pos = bytes
+        .windows(pattern.len())
+        .position(|window| window == pattern)
+        .expect("找不到名称");
+    let last = pos + pattern.len() - 2;
+    bytes[last..last + 2].copy_from_slice(&unit.to_le_bytes());
+}
+
+/// 测试名称中不成对的代理项：转义后保持唯一，可以按路径访问和释放
+#[test]
+fn test_invalid_utf16_names_stay_distinct() {
+    assert_eq!(decode_name(&[0x6E, 0xD800]), "n%uD800");
+    assert_eq!(lossy_name(&[0x6E, 0xD800]), "n\u{FFFD}");
+
+    let mut bytes = build_wim(&[ImageSpec::new("Test").file("n@", b"one").file("n#", b"two")]);
+    patch_name(&mut bytes, "n@", 0xD800);
+    patch_name(&mut bytes, "n#", 0xDC01);
+    let wim = write_bytes(&bytes);
+    let mut parser = WimParser::new(wim.path()).unwrap();
+
+    let mut tree = parser.open_lazy_tree(1).unwrap();
+    let entries = tree.list_dir("").unwrap().to_vec();
+    let names: Vec<&str> = entries.iter().map(|entry| entry.name.as_str()).collect();
+    assert_eq!(names, ["n%uD800", "n%uDC01"]);
+    assert!(entries.iter().all(|entry| !entry.has_valid_name()));
+    assert!(entries
+        .iter()
+        .all(|entry| entry.lossy_name() == "n\u{FFFD}"));
+    assert_eq!(&*entries[1].raw_name(), &[0x6E, 0xDC01]);
+
+    let mut data = Vec::new();
+    tree.extract_file(&mut parser, "n%uDC01", &mut data)
+        .unwrap();
+    assert_eq!(data, b"two");
+
+    let out = tempfile::tempdir().unwrap();
+    let mut target = DirectoryTarget::new(out.path());
+    let report = parser
+        .apply_to(1, &mut target, &ApplyOptions::new())
+        .unwrap();
+    assert_eq!(report.file_count, 2);
+    assert_eq!(std::fs::read(out.path().join("n%uD800")).unwrap(), b"one");
+    assert_eq!(std::fs::read(out.path().join("n%uDC01")).unwrap(), b"two");
+}
+
+fn compose_acute(name: &str) -> String {
+    name.replace("e\u{301}", "\u{E9}")
+}
+
+/// 测试名称比较规则
+#[test]
+fn test_name_matching_rules() {
+    let default = NameMatching::default();
+    assert!(default.matches("Äpfel.TXT", "äpfel.txt"));
+    assert!(!default.matches("readme.txt.", "readme.txt"));
+    assert!(!NameMatching::exact().matches("A.txt", "a.txt"));
+
+    let win32 = NameMatching::win32();
+    assert!(win32.matches("README.TXT", "readme.txt. "));
+    assert!(!win32.matches("..", "."));
+    assert_eq!(win32.key("straße"), "STRAßE");
+
+    let nfc = NameMatching::default().normalizer(compose_acute);
+    assert!(nfc.matches("Cafe\u{301}", "CAF\u{C9}"));
+    assert!(!default.matches("Cafe\u{301}", "CAF\u{C9}"));
+    assert_eq!(nfc, NameMatching::default().normalizer(compose_acute));
+    assert_ne!(nfc, default);
+}
+
+/// 测试按需加载目录树使用解析选项中的名称比较规则，完全相同的名称优先匹配
+#[test]
+fn test_lazy_tree_name_matching_option() {
+    let wim = write_wim(&[ImageSpec::new("Test")
+        .dir("Äpfel")
+        .file("Äpfel/notes.", b"dotted")
+        .file("Äpfel/NOTES", b"plain")
+        .file("Äpfel/readme.txt ", b"spaced")]);
+
+    let mut parser = WimParser::new(wim.path()).unwrap();
+    let mut tree = parser.open_lazy_tree(1).unwrap();
+    assert_eq!(tree.find("äpfel/notes.").unwrap().unwrap().name, "notes.");
+    assert!(tree.find("äpfel/readme.txt").unwrap().is_none());
+
+    let options = ParseOptions::new().name_matching(NameMatching::win32());
+    let mut parser = WimParser::with_options(wim.path(), options).unwrap();
+    let mut tree = parser.open_lazy_tree(1).unwrap();
+    assert_eq!(tree.find("ÄPFEL/notes").unwrap().unwrap().name, "notes.");
+    assert_eq!(tree.find("Äpfel/NOTES").unwrap().unwrap().name, "NOTES");
+    assert_eq!(tree.find("äpfel/notes.").unwrap().unwrap().name, "notes.");
+    let mut data = Vec::new();
+    tree.extract_file(&mut parser, "ÄPFEL/README.TXT", &mut data)
+        .unwrap();
+    assert_eq!(data, b"spaced");
+}
@@ -0,0 +1,76 @@
+#![cfg(feature = "parser")]
+
+mod common;
+
+use common::{write_wim, ImageSpec};
+use wim_parser::fmt::ToTable;
+use wim_parser::{ServicingPackage, WimParser};
+
+const PACKAGES: &str = "/Windows/servicing/Packages";
+const FOUNDATION: &str =
+    "Microsoft-Windows-Foundation-Package~31bf3856ad364e35~amd64~~10.0.19041.1.mum";
+const SECURITY_UPDATE: &str = "Package_for_KB5005565~31bf3856ad364e35~amd64~~19041.1237.1.8.mum";
+const ROLLUP: &str = "Package_for_RollupFix~31bf3856ad364e35~amd64~~19041.1237.1.8.mum";
+const LANGUAGE_PACK: &str =
+    "Microsoft-Windows-Client-LanguagePack-Package~31bf3856ad364e35~amd64~zh-CN~10.0.19041.1.mum";
+
+/// 测试列出已安装的服务包和更新
+#[test]
+fn test_list_packages() {
+    let rollup_manifest = br#"<?xml version="1.0" encoding="utf-8"?>
+<assembly xmlns="urn:schemas-microsoft-com:asm.v3" manifestVersion="1.0">
+  <assemblyIdentity name="Package_for_RollupFix" version="19041.1237.1.8" processorArchitecture="amd64" language="neutral" />
+  <package identifier="KB5005565" releaseType="Security Update" restart="required">
+  </package>
+</assembly>"#;
+    let specs = [
+        ImageSpec::new("Patched")
+            .file(&format!("{PACKAGES}/{FOUNDATION}"), b"<assembly/>")
+            .file(&format!("{PACKAGES}/{ROLLUP}"), rollup_manifest)
+            .file(&format!("{PACKAGES}/{SECURITY_UPDATE}"), b"<assembly/>")
+            .file(&format!("{PACKAGES}/{LANGUAGE_PACK}"), b"<assembly/>")
+            .file(
+                &format!(
+                    "{PACKAGES}/Package_for_RollupFix~31bf3856ad364e35~amd64~~19041.1237.1.8.cat"
+                ),
+                b"catalog",
+            )
+            .dir(&format!("{PACKAGES}/Staged")),
+        ImageSpec::new("Empty"),
+    ];
+    let wim = write_wim(&specs);
+    let mut parser = WimParser::new(wim.path()).unwrap();
+
+    let packages = parser.list_packages(1).unwrap();
+    let names: Vec<&str> = packages.iter().map(|p| p.file_name.as_str()).collect();
+    assert_eq!(names, [LANGUAGE_PACK, FOUNDATION, SECURITY_UPDATE, ROLLUP]);
+
+    assert_eq!(packages[0].language, "zh-CN");
+    assert!(!packages[1].is_update());
+
+    assert_eq!(packages[2].kb.as_deref(), Some("KB5005565"));
+    assert_eq!(packages[2].release_type, None);
+
+    assert_eq!(packages[3].name, "Package_for_RollupFix");
+    assert_eq!(packages[3].kb.as_deref(), Some("KB5005565"));
+    assert_eq!(packages[3].release_type.as_deref(), Some("Security Update"));
+    assert!(packages[3].is_update());
+    assert_eq!(packages.table().rows()[3][3], "KB5005565");
+
+    assert!(parser.list_packages(2).unwrap().is_empty());
+}
+
+/// 测试 `.mum` 文件名解析
+#[test]
+fn test_package_file_name() {
+    let package = ServicingPackage::from_file_name(SECURITY_UPDATE).unwrap();
+    assert_eq!(package.name, "Package_for_KB5005565");
+    assert_eq!(package.public_key_token, "31bf3856ad364e35");
+    assert_eq!(package.architecture, "amd64");
+    assert_eq!(package.language, "");
+    assert_eq!(package.version, "19041.1237.1.8");
+    assert_eq!(package.kb.as_deref(), Some("KB5005565"));
+
+    assert!(ServicingPackage::from_file_name("Package_for_KB1~abc~amd64~~1.0.cat").is_none());
+    assert!(ServicingPackage::from_file_name("update.mum").is_none());
+}
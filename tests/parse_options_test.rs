@@ -0,0 +1,75 @@
+#![cfg(feature = "parser")]
+
+mod common;
+
+use common::{write_wim, ImageSpec};
+use std::sync::Arc;
+use wim_parser::{ParseOptions, WimCatalogCache, WimParser};
+
+fn sample_wim() -> tempfile::NamedTempFile {
+    write_wim(&[
+        ImageSpec::new("Windows 11 Home"),
+        ImageSpec::new("Windows 11 Pro").extra_xml("<WINDOWS><ARCH>12</ARCH></WINDOWS>"),
+    ])
+}
+
+/// 测试只读取文件头
+#[test]
+fn test_header_only() {
+    let wim = sample_wim();
+    let mut parser = WimParser::with_options(wim.path(), ParseOptions::header_only()).unwrap();
+    parser.parse_full().unwrap();
+
+    assert_eq!(parser.get_image_count(), 2);
+    assert!(parser.get_images().is_empty());
+    assert!(!parser.has_windows_metadata());
+}
+
+/// 测试关闭 Windows 元数据后按需补全
+#[test]
+fn test_lazy_windows_metadata() {
+    let wim = sample_wim();
+    let options = ParseOptions::new().parse_windows_metadata(false);
+    let mut parser = WimParser::with_options(wim.path(), options).unwrap();
+    parser.parse_full().unwrap();
+
+    let images = parser.get_images();
+    assert_eq!(images.len(), 2);
+    assert_eq!(images[1].name, "Windows 11 Pro");
+    assert!(images.iter().all(|image| image.version.is_none()));
+    assert!(images.iter().all(|image| image.architecture.is_none()));
+    assert!(!parser.has_windows_metadata());
+    assert!(parser.get_windows_info().is_none());
+
+    parser.load_windows_metadata().unwrap();
+    assert!(parser.has_windows_metadata());
+    let pro = parser.get_image(2).unwrap();
+    assert_eq!(pro.version.as_deref(), Some("Windows 11"));
+    assert_eq!(pro.architecture.as_deref(), Some("ARM64"));
+    assert!(parser.get_windows_info().is_some());
+}
+
+/// 测试不完整的解析结果不会写入共享缓存
+#[test]
+fn test_partial_parse_not_cached() {
+    let wim = sample_wim();
+    let cache = Arc::new(WimCatalogCache::new());
+
+    let mut partial = WimParser::with_cache(wim.path(), cache.clone()).unwrap();
+    partial.set_options(ParseOptions::new().parse_windows_metadata(false));
+    partial.parse_full().unwrap();
+    assert!(cache.is_empty());
+
+    let mut full = WimParser::with_cache(wim.path(), cache.clone()).unwrap();
+    full.parse_full().unwrap();
+    assert_eq!(cache.len(), 1);
+
+    let mut cached = WimParser::with_cache(wim.path(), cache.clone()).unwrap();
+    cached.set_options(ParseOptions::new().parse_windows_metadata(false));
+    cached.parse_full().unwrap();
+    assert!(cached.has_windows_metadata());
+    assert_eq!(
+        cached.get_image(1).unwrap().version.as_deref(),
+        Some("Windows 11")
+    );
+}
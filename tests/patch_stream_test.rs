@@ -0,0 +1,111 @@
+#![cfg(feature = "parser")]
+
+mod common;
+
+use common::{write_wim, ImageSpec};
+use wim_parser::error::{codes, error_code};
+use wim_parser::{ApplyOptions, DirectoryTarget, PatchStreamKind, WimParser};
+
+/// WinSxS 正向增量：4 字节 CRC32 后接 MSDelta 签名
+const FORWARD_DELTA: &[u8] = b"\x12\x34\x56\x78PA30\x00\x01\x02\x03";
+/// 压缩的组件清单
+const MANIFEST: &[u8] = b"DCM\x01PA30\x10\x20\x30";
+
+fn servicing_image() -> ImageSpec {
+    ImageSpec::new("Windows 11 Pro")
+        .dir("/Windows/WinSxS/Manifests")
+        .dir("/Windows/WinSxS/amd64_foo/f")
+        .file("/Windows/WinSxS/Manifests/amd64_foo.manifest", MANIFEST)
+        .file("/Windows/WinSxS/amd64_foo/f/foo.dll", FORWARD_DELTA)
+        .file("/Windows/WinSxS/amd64_foo/f/copy.dll", FORWARD_DELTA)
+        .file("/Windows/notepad.exe", b"MZ\x90\x00\x03\x00\x00\x00PA30")
+}
+
+/// 测试按签名识别补丁种类
+#[test]
+fn test_detect_patch_kind() {
+    assert_eq!(
+        PatchStreamKind::detect(FORWARD_DELTA),
+        Some(PatchStreamKind::MsDelta)
+    );
+    assert_eq!(
+        PatchStreamKind::detect(b"PA31\x00\x00"),
+        Some(PatchStreamKind::MsDelta)
+    );
+    assert_eq!(
+        PatchStreamKind::detect(b"PA19\x00\x00"),
+        Some(PatchStreamKind::MsPatch)
+    );
+    assert_eq!(
+        PatchStreamKind::detect(MANIFEST),
+        Some(PatchStreamKind::CompressedManifest)
+    );
+    assert_eq!(PatchStreamKind::detect(b"MZ\x90\x00\x03\x00\x00\x00"), None);
+    assert_eq!(PatchStreamKind::detect(b"PA3"), None);
+}
+
+/// 测试列出镜像中的补丁数据流
+#[test]
+fn test_patch_streams_per_image() {
+    let wim = write_wim(&[
+        servicing_image(),
+        ImageSpec::new("Windows PE").file("/setup.exe", b"MZ\x90\x00"),
+    ]);
+    let mut parser = WimParser::new(wim.path()).unwrap();
+
+    let patches = parser.patch_streams(1).unwrap();
+    let found: Vec<(&str, PatchStreamKind)> = patches
+        .iter()
+        .map(|patch| (patch.path.as_str(), patch.kind))
+        .collect();
+    assert_eq!(
+        found,
+        [
+            (
+                "Windows/WinSxS/Manifests/amd64_foo.manifest",
+                PatchStreamKind::CompressedManifest
+            ),
+            (
+                "Windows/WinSxS/amd64_foo/f/foo.dll",
+                PatchStreamKind::MsDelta
+            ),
+            (
+                "Windows/WinSxS/amd64_foo/f/copy.dll",
+                PatchStreamKind::MsDelta
+            ),
+        ]
+    );
+    assert_eq!(patches[1].size, FORWARD_DELTA.len() as u64);
+    assert!(patches[1].stream.is_empty());
+    assert!(parser.has_patch_streams(1).unwrap());
+    assert!(!parser.has_patch_streams(2).unwrap());
+}
+
+/// 测试释放补丁数据流时报告不支持的数据流类型，而不是写出补丁字节
+#[test]
+fn test_extract_patch_stream_fails() {
+    let wim = write_wim(&[servicing_image()]);
+    let mut parser = WimParser::new(wim.path()).unwrap();
+
+    let mut tree = parser.open_lazy_tree(1).unwrap();
+    let mut out = Vec::new();
+    let err = tree
+        .extract_file(&mut parser, "Windows/WinSxS/amd64_foo/f/foo.dll", &mut out)
+        .unwrap_err();
+    assert_eq!(error_code(&err), codes::UNSUPPORTED_STREAM_TYPE);
+    assert!(err.to_string().contains("MSDelta"), "{err}");
+    assert!(out.is_empty());
+    tree.extract_file(&mut parser, "Windows/notepad.exe", &mut out)
+        .unwrap();
+
+    let dir = tempfile::tempdir().unwrap();
+    let mut target = DirectoryTarget::new(dir.path());
+    let err = parser
+        .apply_to(1, &mut target, &ApplyOptions::new())
+        .unwrap_err();
+    assert_eq!(error_code(&err), codes::UNSUPPORTED_STREAM_TYPE);
+    assert!(!dir
+        .path()
+        .join("Windows/WinSxS/Manifests/amd64_foo.manifest")
+        .exists());
+}
@@ -0,0 +1,117 @@
+#![cfg(feature = "parser")]
+
+mod common;
+
+use common::{build_wim, write_bytes, ImageSpec};
+use wim_parser::fmt::ToTable;
+use wim_parser::{Arch, PeMachine, WimParser};
+
+const AMD64: u16 = 0x8664;
+const ARM64: u16 = 0xAA64;
+
+/// 构造 PE32+ 文件：一个节、加载配置目录位于节的开头，`chpe` 为 CHPE 元数据指针
+fn pe64(machine: u16, chpe: u64, section_offset: usize) -> Vec<u8> {
+    let mut image = vec![0u8; section_offset + 0x200];
+    image[0..2].copy_from_slice(b"MZ");
+    image[0x3C..0x40].copy_from_slice(&0x80u32.to_le_bytes());
+    image[0x80..0x84].copy_from_slice(b"PE\0\0");
+    image[0x84..0x86].copy_from_slice(&machine.to_le_bytes());
+    image[0x86..0x88].copy_from_slice(&1u16.to_le_bytes()); // NumberOfSections
+    image[0x94..0x96].copy_from_slice(&240u16.to_le_bytes()); // SizeOfOptionalHeader
+
+    let optional = 0x98;
+    image[optional..optional + 2].copy_from_slice(&0x020Bu16.to_le_bytes());
+    image[optional + 108..optional + 112].copy_from_slice(&16u32.to_le_bytes());
+    let directory = optional + 112 + 10 * 8;
+    image[directory..directory + 4].copy_from_slice(&0x1000u32.to_le_bytes());
+    image[directory + 4..directory + 8].copy_from_slice(&0x140u32.to_le_bytes());
+
+    let section = optional + 240;
+    image[section..section + 8].copy_from_slice(b".rdata\0\0");
+    image[section + 8..section + 12].copy_from_slice(&0x200u32.to_le_bytes());
+    image[section + 12..section + 16].copy_from_slice(&0x1000u32.to_le_bytes());
+    image[section + 16..section + 20].copy_from_slice(&0x200u32.to_le_bytes());
+    image[section + 20..section + 24].copy_from_slice(&(section_offset as u32).to_le_bytes());
+
+    image[section_offset..section_offset + 4].copy_from_slice(&0x140u32.to_le_bytes());
+    image[section_offset + 0xC8..section_offset + 0xD0].copy_from_slice(&chpe.to_le_bytes());
+    image
+}
+
+fn arm64_image() -> ImageSpec {
+    ImageSpec::new("Windows 11 Pro")
+        .extra_xml("<WINDOWS><ARCH>12</ARCH></WINDOWS>")
+        .file("Windows/System32/kernel32.dll", &pe64(ARM64, 0x1800, 0x400))
+        .file("Windows/System32/notepad.exe", &pe64(ARM64, 0, 0x400))
+        .file(
+            "Windows/System32/xtajit64.dll",
+            &pe64(AMD64, 0x2000, 0x3000),
+        )
+        .file("Program Files/App/app.exe", &pe64(AMD64, 0, 0x400))
+        .file("Program Files/App/APP.DLL", &pe64(ARM64, 0x1800, 0x5000))
+        .file("Program Files/App/readme.txt", b"MZ not a binary")
+        .file("Program Files/App/fake.dll", b"not a PE file")
+}
+
+/// 测试 ARM64 镜像中 ARM64X、ARM64EC 和模拟运行的 x64 文件的识别
+#[test]
+fn test_pe_inventory_hybrid() {
+    let wim = write_bytes(&build_wim(&[arm64_image()]));
+    let mut parser = WimParser::new(wim.path()).unwrap();
+    let inventory = parser.pe_inventory(1).unwrap();
+
+    assert_eq!(inventory.image_arch, Some(Arch::Arm64));
+    let machines: Vec<(&str, PeMachine)> = inventory
+        .binaries
+        .iter()
+        .map(|binary| (binary.path.as_str(), binary.machine))
+        .collect();
+    assert_eq!(
+        machines,
+        [
+            ("Program Files/App/APP.DLL", PeMachine::Arm64X),
+            ("Program Files/App/app.exe", PeMachine::X64),
+            ("Windows/System32/kernel32.dll", PeMachine::Arm64X),
+            ("Windows/System32/notepad.exe", PeMachine::Arm64),
+            ("Windows/System32/xtajit64.dll", PeMachine::Arm64Ec),
+        ]
+    );
+
+    let hybrid: Vec<&str> = inventory.hybrid().map(|b| b.path.as_str()).collect();
+    assert_eq!(hybrid.len(), 3);
+    assert!(hybrid.contains(&"Windows/System32/xtajit64.dll"));
+    let foreign: Vec<&str> = inventory.foreign().map(|b| b.path.as_str()).collect();
+    assert_eq!(foreign, ["Program Files/App/app.exe"]);
+    assert_eq!(inventory.count(PeMachine::Arm64X), 2);
+    assert_eq!(PeMachine::Arm64Ec.arch(), Some(Arch::Arm64));
+}
+
+/// 测试 x64 镜像中没有混合二进制，以及机器类型汇总表
+#[test]
+fn test_pe_inventory_summary() {
+    let mut legacy = pe64(0x014C, 0, 0x400);
+    legacy[0x98..0x9A].copy_from_slice(&0x010Bu16.to_le_bytes());
+    let image = ImageSpec::new("Windows 10 Pro")
+        .extra_xml("<WINDOWS><ARCH>9</ARCH></WINDOWS>")
+        .file("Windows/System32/cmd.exe", &pe64(AMD64, 0, 0x400))
+        .file("Windows/SysWOW64/cmd.exe", &legacy)
+        .file("Windows/System32/drivers/beep.sys", &pe64(0x1234, 0, 0x400));
+    let wim = write_bytes(&build_wim(&[image]));
+    let mut parser = WimParser::new(wim.path()).unwrap();
+    let inventory = parser.pe_inventory(1).unwrap();
+
+    assert_eq!(inventory.binaries.len(), 3);
+    assert_eq!(inventory.hybrid().count(), 0);
+    assert_eq!(
+        inventory.machine_counts(),
+        [
+            (PeMachine::X86, 1),
+            (PeMachine::X64, 1),
+            (PeMachine::Unknown(0x1234), 1)
+        ]
+    );
+    let table = inventory.table();
+    assert_eq!(table.rows()[2], ["0x1234", "1"]);
+
+    assert!(parser.pe_inventory(2).is_err());
+}
@@ -0,0 +1,89 @@
+#![cfg(feature = "verify")]
+
+use wim_parser::{
+    Compression, Preset, RepairSource, VirtualWim, WimParser, WriteSettings, INTEGRITY_CHUNK_SIZE,
+};
+
+fn sample() -> VirtualWim {
+    let mut wim = VirtualWim::new();
+    wim.add_image("Windows 11 Pro")
+        .add_file("/Windows/a.dll", b"hello")
+        .unwrap();
+    wim
+}
+
+/// 测试预设对应的压缩格式、分块大小和文件头标志
+#[test]
+fn test_preset_settings() {
+    let max = Preset::DismMax.settings();
+    assert_eq!(max.compression, Compression::Lzx { chunk: 32768 });
+    assert_eq!(max.file_flags(), 0x0004_0082);
+    assert!(max.rp_fix);
+    assert_eq!(max.chunk_size(), 32768);
+    assert!(max.solid.is_none());
+    assert!(!max.integrity);
+
+    let fast = Preset::DismFast.settings();
+    assert_eq!(fast.compression, Compression::Xpress { chunk: 32768 });
+    assert_eq!(fast.file_flags(), 0x0002_0082);
+
+    let esd = Preset::Esd.settings();
+    assert_eq!(esd.compression, Compression::Lzms { chunk: 128 * 1024 });
+    assert_eq!(
+        esd.solid,
+        Some(Compression::Lzms {
+            chunk: 64 * 1024 * 1024
+        })
+    );
+    assert_eq!(esd.file_flags(), 0x0008_0082);
+
+    assert_eq!(WriteSettings::default(), Preset::DismNone.settings());
+    assert_eq!(WriteSettings::default().file_flags(), 0x80);
+    assert_eq!(WriteSettings::default().rp_fix(false).file_flags(), 0);
+    assert_eq!(Preset::Esd.to_string(), "/Compress:recovery");
+}
+
+/// 测试按预设写入的文件头和数据流可以读回
+#[test]
+fn test_write_with_preset() {
+    let dir = tempfile::tempdir().unwrap();
+    for preset in Preset::ALL {
+        let path = dir.path().join(format!("{preset:?}.wim"));
+        let mut wim = sample();
+        wim.set_write_settings(preset);
+        let mut parser = wim.open(&path).unwrap();
+
+        parser.parse_full().unwrap();
+        let header = parser.get_header().unwrap();
+        assert_eq!(header.compression(), preset.settings().compression);
+        assert_eq!(header.integrity_resource.size, 0);
+        assert_eq!(parser.sha1_manifest(1).unwrap().len(), 1);
+        assert!(parser.verify_all_streams().unwrap().is_ok());
+    }
+}
+
+/// 测试写入完整性表 (`/CheckIntegrity`)
+#[test]
+fn test_write_integrity_table() {
+    let mut wim = sample();
+    wim.set_write_settings(Preset::DismMax.settings().integrity(true));
+    let mut bytes = wim.to_bytes().unwrap();
+
+    let file = tempfile::NamedTempFile::new().unwrap();
+    std::fs::write(file.path(), &bytes).unwrap();
+    let mut parser = WimParser::new(file.path()).unwrap();
+    let plan = parser.repair_plan().unwrap();
+    assert_eq!(
+        plan.source,
+        RepairSource::IntegrityTable {
+            chunk_size: INTEGRITY_CHUNK_SIZE
+        }
+    );
+    assert!(plan.is_empty());
+
+    let position = bytes.windows(5).position(|w| w == b"hello").unwrap();
+    bytes[position] ^= 0xFF;
+    std::fs::write(file.path(), &bytes).unwrap();
+    let mut parser = WimParser::new(file.path()).unwrap();
+    assert!(!parser.repair_plan().unwrap().is_empty());
+}
@@ -0,0 +1,75 @@
+#![cfg(feature = "parser")]
+
+mod common;
+
+use common::{write_wim, ImageSpec};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use wim_parser::{ApplyOptions, DirectoryTarget, ProgressUpdate, RateEstimator, WimParser};
+
+fn ms(millis: u64) -> Duration {
+    Duration::from_millis(millis)
+}
+
+/// 测试采样窗口合并、EWMA 平滑和剩余时间
+#[test]
+fn test_rate_estimator() {
+    let mut estimator = RateEstimator::new(10_000)
+        .smoothing(0.5)
+        .min_interval(ms(100));
+    let start = estimator.snapshot_at(Duration::ZERO);
+    assert_eq!(start.eta, None);
+    assert_eq!(start.bytes_per_sec, 0.0);
+
+    // 窗口内的更新只记录字节数
+    assert!(estimator.record_at(50, ms(10)).is_none());
+    let first = estimator.record_at(1_000, ms(1_000)).unwrap();
+    assert_eq!(first.bytes_per_sec, 1_000.0);
+    assert_eq!(first.eta, Some(Duration::from_secs(9)));
+    assert_eq!(first.fraction(), 0.1);
+
+    // 3000 字节/秒的窗口与之前的 1000 字节/秒各占一半
+    let second = estimator.record_at(4_000, ms(2_000)).unwrap();
+    assert_eq!(second.bytes_per_sec, 2_000.0);
+    assert_eq!(second.eta, Some(Duration::from_secs(3)));
+
+    // 停顿时吞吐率逐渐下降，而不是立刻归零
+    let stalled = estimator.record_at(4_000, ms(3_000)).unwrap();
+    assert_eq!(stalled.bytes_per_sec, 1_000.0);
+    assert_eq!(stalled.eta, Some(Duration::from_secs(6)));
+
+    let done = estimator.record_at(10_000, ms(4_000)).unwrap();
+    assert!(done.is_complete());
+    assert_eq!(done.eta, Some(Duration::ZERO));
+    assert_eq!(RateEstimator::new(0).snapshot().fraction(), 1.0);
+}
+
+/// 测试释放镜像时回报进度
+#[test]
+fn test_apply_progress() {
+    let wim = write_wim(&[ImageSpec::new("Windows 11 Pro")
+        .dir("Windows")
+        .file("Windows/big.bin", &vec![7u8; 300_000])
+        .file("readme.txt", b"hello")
+        .file("empty.txt", b"")]);
+    let out = tempfile::tempdir().unwrap();
+    let mut parser = WimParser::new(wim.path()).unwrap();
+
+    let updates: Arc<Mutex<Vec<ProgressUpdate>>> = Arc::default();
+    let recorded = Arc::clone(&updates);
+    let options = ApplyOptions::new()
+        .progress(move |update: &ProgressUpdate| recorded.lock().unwrap().push(*update));
+    let report = parser
+        .apply_to(1, &mut DirectoryTarget::new(out.path()), &options)
+        .unwrap();
+
+    let updates = updates.lock().unwrap();
+    assert!(updates.len() >= 2);
+    assert_eq!(updates[0].done, 0);
+    assert_eq!(updates[0].total, 300_005);
+    let last = updates.last().unwrap();
+    assert_eq!(last.done, report.total_bytes);
+    assert!(last.is_complete());
+    assert_eq!(last.eta, Some(Duration::ZERO));
+    assert!(updates.windows(2).all(|pair| pair[0].done <= pair[1].done));
+}
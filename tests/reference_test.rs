@@ -0,0 +1,55 @@
+#![cfg(feature = "parser")]
+
+mod common;
+
+use common::sha1_hash;
+use std::path::Path;
+use wim_parser::{Compression, WimParser};
+
+/// 解压 tests/fixtures 中的参考文件，按 SHA1SUMS 比对镜像 1 中每个文件的内容，返回压缩格式
+fn check_fixture(name: &str) -> Compression {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+    let path = dir.join(name);
+    assert!(
+        path.exists(),
+        "缺少 {}，请用 tests/fixtures/generate.sh 生成",
+        path.display()
+    );
+    let sums = std::fs::read_to_string(dir.join("SHA1SUMS")).unwrap();
+
+    let mut parser = WimParser::new(&path).unwrap();
+    parser.parse_full().unwrap();
+    for line in sums.lines() {
+        let (expected, file) = line.split_once("  ").unwrap();
+        let mut data = Vec::new();
+        parser.extract_file(1, file, &mut data).unwrap();
+        let actual: String = sha1_hash(&data)
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect();
+        assert_eq!(actual, expected, "{name}: {file}");
+    }
+    parser.compression()
+}
+
+/// 测试解压 wimlib-imagex 以 XPRESS、LZX 和 LZMS 分块压缩的参考文件
+#[test]
+#[ignore = "参考文件需在装有 wimlib-imagex 的环境中用 tests/fixtures/generate.sh 生成"]
+fn test_reference_chunked() {
+    assert!(matches!(
+        check_fixture("xpress.wim"),
+        Compression::Xpress { .. }
+    ));
+    assert!(matches!(check_fixture("lzx.wim"), Compression::Lzx { .. }));
+    assert_eq!(
+        check_fixture("lzms.wim"),
+        Compression::Lzms { chunk: 32768 }
+    );
+}
+
+/// 测试解压 wimlib-imagex 生成的固实 ESD
+#[test]
+#[ignore = "参考文件需在装有 wimlib-imagex 的环境中用 tests/fixtures/generate.sh 生成"]
+fn test_reference_solid() {
+    check_fixture("solid.esd");
+}
@@ -0,0 +1,63 @@
+#![cfg(feature = "verify")]
+
+mod common;
+
+use common::{add_integrity_table, build_wim, write_bytes, ImageSpec};
+use wim_parser::fmt::ToTable;
+use wim_parser::{RepairSource, WimParser};
+
+fn specs() -> Vec<ImageSpec> {
+    vec![ImageSpec::new("Image A")
+        .file("/a.bin", &[0xAA; 300])
+        .file("/b.bin", &[0xBB; 300])]
+}
+
+/// 测试基于完整性表的修复计划
+#[test]
+fn test_repair_plan_with_integrity_table() {
+    let mut bytes = build_wim(&specs());
+    add_integrity_table(&mut bytes, 128);
+
+    let wim = write_bytes(&bytes);
+    let mut parser = WimParser::new(wim.path()).unwrap();
+    let plan = parser.repair_plan().unwrap();
+    assert_eq!(
+        plan.source,
+        RepairSource::IntegrityTable { chunk_size: 128 }
+    );
+    assert!(plan.is_empty());
+
+    // 破坏第一个数据流中的两个相邻分块
+    bytes[208 + 100] ^= 0xFF;
+    bytes[208 + 200] ^= 0xFF;
+    let wim = write_bytes(&bytes);
+    let mut parser = WimParser::new(wim.path()).unwrap();
+    let plan = parser.repair_plan().unwrap();
+
+    assert_eq!(plan.ranges.len(), 1);
+    assert_eq!(plan.ranges[0].range, 208..208 + 256);
+    assert_eq!(plan.total_bytes(), 256);
+    assert_eq!(plan.ranges[0].resources.len(), 1);
+    assert_eq!(plan.table().rows().len(), 1);
+}
+
+/// 测试没有完整性表时按偏移表校验未压缩资源
+#[test]
+fn test_repair_plan_from_lookup_table() {
+    let mut bytes = build_wim(&specs());
+
+    let wim = write_bytes(&bytes);
+    let mut parser = WimParser::new(wim.path()).unwrap();
+    let plan = parser.repair_plan().unwrap();
+    assert_eq!(plan.source, RepairSource::LookupTable);
+    assert!(plan.is_empty());
+    assert_eq!(plan.unverified, 0);
+
+    // 第二个数据流紧跟在第一个之后
+    bytes[208 + 300 + 5] ^= 0xFF;
+    let wim = write_bytes(&bytes);
+    let mut parser = WimParser::new(wim.path()).unwrap();
+    let plan = parser.repair_plan().unwrap();
+    assert_eq!(plan.ranges.len(), 1);
+    assert_eq!(plan.ranges[0].range, 508..808);
+}
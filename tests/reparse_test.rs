@@ -0,0 +1,110 @@
+#![cfg(feature = "parser")]
+
+mod common;
+
+use common::{write_wim, ImageSpec};
+use wim_parser::error::{codes, error_code};
+use wim_parser::{Error, ReparseInfo, WimParser};
+
+const IO_REPARSE_TAG_MOUNT_POINT: u32 = 0xA000_0003;
+const IO_REPARSE_TAG_WOF: u32 = 0x8000_0017;
+
+fn utf16le(text: &str) -> Vec<u8> {
+    text.encode_utf16().flat_map(u16::to_le_bytes).collect()
+}
+
+/// 目录联接的重解析数据：名称以空字符结尾，没有标志字段
+fn junction_data(substitute: &str, print: &str) -> Vec<u8> {
+    let (substitute, print) = (utf16le(substitute), utf16le(print));
+    let mut data = Vec::new();
+    data.extend(0u16.to_le_bytes());
+    data.extend((substitute.len() as u16).to_le_bytes());
+    data.extend((substitute.len() as u16 + 2).to_le_bytes());
+    data.extend((print.len() as u16).to_le_bytes());
+    data.extend(&substitute);
+    data.extend([0, 0]);
+    data.extend(&print);
+    data.extend([0, 0]);
+    data
+}
+
+/// 测试解析符号链接、目录联接和其他重解析数据
+#[test]
+fn test_parse_reparse_data() {
+    let info = ReparseInfo::parse(
+        IO_REPARSE_TAG_MOUNT_POINT,
+        &junction_data(r"\??\C:\Users\Public", ""),
+    )
+    .unwrap();
+    assert_eq!(
+        info,
+        ReparseInfo::Junction {
+            substitute_name: r"\??\C:\Users\Public".to_string(),
+            print_name: String::new(),
+        }
+    );
+    // 显示名称为空时使用去掉 \??\ 前缀的替代名称
+    assert_eq!(info.target().as_deref(), Some(r"C:\Users\Public"));
+    assert!(info.is_link() && !info.is_relative());
+    assert_eq!(info.tag(), IO_REPARSE_TAG_MOUNT_POINT);
+    assert_eq!(info.to_string(), r"目录联接 -> C:\Users\Public");
+
+    let info = ReparseInfo::parse(IO_REPARSE_TAG_WOF, &[1, 0, 0, 0, 2, 0, 0, 0]).unwrap();
+    assert_eq!(info.tag(), IO_REPARSE_TAG_WOF);
+    assert_eq!(info.target(), None);
+    assert_eq!(info.to_string(), "重解析点 0x80000017 (8 字节)");
+
+    let mut data = junction_data(r"\??\D:\", "D:\\");
+    data.truncate(data.len() - 4);
+    assert!(matches!(
+        ReparseInfo::parse(IO_REPARSE_TAG_MOUNT_POINT, &data),
+        Err(Error::Truncated { .. })
+    ));
+    assert!(matches!(
+        ReparseInfo::parse(0xA000_000C, &[0; 8]),
+        Err(Error::Truncated {
+            expected: 12,
+            actual: 8
+        })
+    ));
+}
+
+/// 测试从镜像读取重解析点，以及按文件读取重解析点时报错
+#[test]
+fn test_reparse_info_in_image() {
+    let wim = write_wim(&[ImageSpec::new("Links")
+        .dir("Users")
+        .file(
+            "Users/All Users",
+            &junction_data(r"\??\C:\ProgramData", r"C:\ProgramData"),
+        )
+        .reparse("Users/All Users", IO_REPARSE_TAG_MOUNT_POINT)
+        .symlink("Users/link.txt", r"..\readme.txt")
+        .file("readme.txt", b"plain file")]);
+    let mut parser = WimParser::new(wim.path()).unwrap();
+
+    let info = parser.reparse_info(1, "users/LINK.TXT").unwrap().unwrap();
+    assert!(matches!(info, ReparseInfo::Symlink { relative: true, .. }));
+    assert_eq!(info.target().as_deref(), Some(r"..\readme.txt"));
+    let info = parser.reparse_info(1, "Users/All Users").unwrap().unwrap();
+    assert_eq!(info.target().as_deref(), Some(r"C:\ProgramData"));
+    assert_eq!(parser.reparse_info(1, "readme.txt").unwrap(), None);
+    assert!(parser.reparse_info(1, "missing").is_err());
+
+    let mut tree = parser.open_lazy_tree(1).unwrap();
+    let info = tree.reparse_info(&mut parser, "Users/link.txt").unwrap();
+    assert_eq!(info.unwrap().tag(), 0xA000_000C);
+
+    // 重解析数据不会被当作文件内容写出
+    let mut out = Vec::new();
+    let err = parser
+        .extract_file(1, "Users/link.txt", &mut out)
+        .unwrap_err();
+    assert_eq!(error_code(&err), codes::UNSUPPORTED_STREAM_TYPE);
+    assert!(
+        err.to_string().contains(r"符号链接 -> ..\readme.txt"),
+        "{err}"
+    );
+    assert!(out.is_empty());
+    assert_eq!(parser.extract_file(1, "readme.txt", &mut out).unwrap(), 10);
+}
@@ -0,0 +1,55 @@
+#![cfg(feature = "parser")]
+
+mod common;
+
+use common::{build_wim, sha1_hash, write_bytes, ImageSpec};
+use wim_parser::{ResourceFlags, ResourceLocation, WimParser};
+
+const OFFSET_TABLE_RESHDR: usize = 48;
+
+/// 测试文件头资源和数据流的定位
+#[test]
+fn test_resolve_resource() {
+    let mut bytes = build_wim(&[ImageSpec::new("Image A").file("/a.txt", b"hello")]);
+    let table_offset = u64::from_le_bytes(
+        bytes[OFFSET_TABLE_RESHDR + 8..OFFSET_TABLE_RESHDR + 16]
+            .try_into()
+            .unwrap(),
+    ) as usize;
+    let stream_entry = (table_offset..)
+        .step_by(50)
+        .find(|&entry| bytes[entry + 7] & ResourceFlags::METADATA == 0)
+        .unwrap();
+    let stream_offset = u64::from_le_bytes(
+        bytes[stream_entry + 8..stream_entry + 16]
+            .try_into()
+            .unwrap(),
+    );
+    let wim = write_bytes(&bytes);
+
+    let mut parser = WimParser::new(wim.path()).unwrap();
+    let xml = parser.read_header().unwrap().xml_data_resource.clone();
+    let location = parser.resolve_resource(&xml).unwrap();
+    assert_eq!(
+        location,
+        ResourceLocation {
+            segment: 1,
+            offset: xml.offset,
+            size: xml.size,
+        }
+    );
+    assert_eq!(location.byte_range(), xml.byte_range());
+
+    let hash = sha1_hash(b"hello");
+    let location = parser.resolve_stream(&hash).unwrap();
+    assert_eq!(location.segment, 1);
+    assert_eq!(location.offset, stream_offset);
+    assert_eq!(location.size, 5);
+    assert!(parser.resolve_stream(&[0xAB; 20]).is_err());
+
+    // 偏移表记录的分卷号决定数据流所在的分卷
+    bytes[stream_entry + 24..stream_entry + 26].copy_from_slice(&2u16.to_le_bytes());
+    let wim = write_bytes(&bytes);
+    let mut parser = WimParser::new(wim.path()).unwrap();
+    assert_eq!(parser.resolve_stream(&hash).unwrap().segment, 2);
+}
@@ -0,0 +1,107 @@
+#![cfg(feature = "parser")]
+
+mod common;
+
+use common::{build_wim, chunked_stream, sha1_hash, write_bytes, write_wim, ImageSpec, XPRESS};
+use std::io::Read;
+use wim_parser::error::{codes, error_code};
+use wim_parser::{Compression, WimParser};
+
+const CHUNK: u32 = 32768;
+fn sample_data() -> Vec<u8> {
+    (0..70000u32).map(|i| (i % 251) as u8).collect()
+}
+
+/// 测试读取按原样存储分块的压缩资源
+#[test]
+fn test_open_chunked_resource() {
+    let data = sample_data();
+    let hash = sha1_hash(&data);
+    let bytes = build_wim(&[ImageSpec::new("Test").file("big.bin", &data)]);
+    let chunks: Vec<&[u8]> = data.chunks(CHUNK as usize).collect();
+    let wim = write_bytes(&chunked_stream(
+        bytes,
+        XPRESS,
+        CHUNK,
+        hash,
+        data.len() as u64,
+        &chunks,
+    ));
+    let mut parser = WimParser::new(wim.path()).unwrap();
+
+    let resource = parser.stream_resource(&hash).unwrap().unwrap();
+    assert!(resource.is_compressed());
+    let mut reader = parser.open_resource(&resource).unwrap();
+    assert_eq!(reader.compression(), Compression::Xpress { chunk: CHUNK });
+    assert_eq!(reader.size(), data.len() as u64);
+    assert_eq!(reader.chunk_count(), 3);
+    let mut read = Vec::new();
+    reader.read_to_end(&mut read).unwrap();
+    assert_eq!(read, data);
+
+    assert_eq!(parser.read_stream(&hash).unwrap(), data);
+}
+
+/// 测试读取未压缩资源
+#[test]
+fn test_open_plain_resource() {
+    let wim = write_wim(&[ImageSpec::new("Test").file("a.txt", b"hello world")]);
+    let mut parser = WimParser::new(wim.path()).unwrap();
+    let resource = parser
+        .stream_resource(&sha1_hash(b"hello world"))
+        .unwrap()
+        .unwrap();
+    assert!(parser.stream_resource(&[0xAB; 20]).unwrap().is_none());
+
+    let mut reader = parser.open_resource(&resource).unwrap();
+    assert_eq!(reader.compression(), Compression::None);
+    assert_eq!(reader.chunk_count(), 0);
+    let mut read = String::new();
+    reader.read_to_string(&mut read).unwrap();
+    assert_eq!(read, "hello world");
+}
+
+/// 测试损坏的压缩分块和分块表
+#[test]
+fn test_open_resource_errors() {
+    let data = sample_data();
+    let hash = sha1_hash(&data);
+    let bytes = build_wim(&[ImageSpec::new("Test").file("big.bin", &data)]);
+
+    // 第二个分块的码长表超额分配（512 个符号的码长都是 1）
+    let (first, rest) = data.split_at(CHUNK as usize);
+    let corrupt = [0x11u8; 300];
+    let chunks: [&[u8]; 3] = [first, &corrupt, &rest[CHUNK as usize..]];
+    let wim = write_bytes(&chunked_stream(
+        bytes.clone(),
+        XPRESS,
+        CHUNK,
+        hash,
+        data.len() as u64,
+        &chunks,
+    ));
+    let mut parser = WimParser::new(wim.path()).unwrap();
+    let resource = parser.stream_resource(&hash).unwrap().unwrap();
+    let mut reader = parser.open_resource(&resource).unwrap();
+    let mut head = vec![0u8; CHUNK as usize];
+    reader.read_exact(&mut head).unwrap();
+    assert_eq!(head, first);
+    let err = anyhow::Error::new(reader.read_to_end(&mut Vec::new()).unwrap_err());
+    assert_eq!(error_code(&err), codes::FORMAT_CORRUPT_DATA);
+    let err = parser.read_stream(&hash).unwrap_err();
+    assert_eq!(error_code(&err), codes::FORMAT_CORRUPT_DATA);
+
+    // 第二个分块比未压缩的分块还大
+    let chunks: [&[u8]; 3] = [&data[..100], &data[100..40000], &data[40000..]];
+    let wim = write_bytes(&chunked_stream(
+        bytes,
+        XPRESS,
+        CHUNK,
+        hash,
+        data.len() as u64,
+        &chunks,
+    ));
+    let mut parser = WimParser::new(wim.path()).unwrap();
+    let resource = parser.stream_resource(&hash).unwrap().unwrap();
+    assert!(parser.open_resource(&resource).is_err());
+}
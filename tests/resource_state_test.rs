@@ -0,0 +1,110 @@
+#![cfg(feature = "parser")]
+
+mod common;
+
+use common::{build_wim, write_bytes, ImageSpec};
+use wim_parser::{
+    Compression, FileFlags, ResHdrFlags, ResourceFlags, ResourceKind, ResourceState, WimParser,
+};
+
+/// 文件头中各资源条目的偏移
+const OFFSET_TABLE_RESHDR: usize = 48;
+const XML_RESHDR: usize = 72;
+const BOOT_METADATA_RESHDR: usize = 96;
+
+/// 测试 FREE 标志和全零条目在文件头中视为不存在
+#[test]
+fn test_absent_header_resources() {
+    let mut bytes = build_wim(&[ImageSpec::new("Image A")]);
+    // 引导元数据：大小非零但设置了 FREE 标志，偏移为 0
+    bytes[BOOT_METADATA_RESHDR] = 0x10;
+    bytes[BOOT_METADATA_RESHDR + 7] = ResourceFlags::FREE;
+    let wim = write_bytes(&bytes);
+
+    let mut parser = WimParser::new(wim.path()).unwrap();
+    let header = parser.read_header().unwrap().clone();
+    assert_eq!(header.boot_metadata_resource.state(), ResourceState::Absent);
+    assert!(header
+        .present_resource(ResourceKind::BootMetadata)
+        .is_none());
+    // 完整性数据为全零条目
+    assert!(header.integrity_resource.is_absent());
+    assert_eq!(header.xml_data_resource.state(), ResourceState::Present);
+    assert!(header.present_resource(ResourceKind::XmlData).is_some());
+    assert!(!parser.wimboot_info().unwrap().has_boot_metadata);
+
+    // FREE 的 XML 资源不会从偏移 0 读取
+    let mut bytes = build_wim(&[ImageSpec::new("Image A")]);
+    bytes[XML_RESHDR + 7] |= ResourceFlags::FREE;
+    let wim = write_bytes(&bytes);
+    let mut parser = WimParser::new(wim.path()).unwrap();
+    let message = parser.parse_full().unwrap_err().to_string();
+    assert!(message.contains("没有 XML 数据资源"), "{message}");
+}
+
+/// 测试偏移表中 FREE 的条目被忽略
+#[test]
+fn test_free_lookup_entry_is_skipped() {
+    let mut bytes = build_wim(&[ImageSpec::new("Image A").file("/a.txt", b"hello")]);
+    let table_offset = u64::from_le_bytes(
+        bytes[OFFSET_TABLE_RESHDR + 8..OFFSET_TABLE_RESHDR + 16]
+            .try_into()
+            .unwrap(),
+    ) as usize;
+    let table_size = u64::from_le_bytes(
+        bytes[OFFSET_TABLE_RESHDR..OFFSET_TABLE_RESHDR + 8]
+            .try_into()
+            .unwrap(),
+    ) as usize
+        & 0x00FF_FFFF_FFFF_FFFF;
+
+    let stream_entry = (table_offset..table_offset + table_size)
+        .step_by(50)
+        .find(|&entry| bytes[entry + 7] & ResourceFlags::METADATA == 0)
+        .unwrap();
+    bytes[stream_entry + 7] |= ResourceFlags::FREE;
+    let wim = write_bytes(&bytes);
+
+    let mut parser = WimParser::new(wim.path()).unwrap();
+    parser.parse_full().unwrap();
+    let target = tempfile::tempdir().unwrap();
+    let plan = parser.plan_apply(1, target.path()).unwrap();
+    assert_eq!(plan.missing_streams, ["a.txt"]);
+}
+
+/// 测试资源头标志的类型化判断
+#[test]
+fn test_reshdr_flag_helpers() {
+    let flags = ResHdrFlags(ResourceFlags::METADATA | ResourceFlags::COMPRESSED);
+    assert!(flags.is_metadata());
+    assert!(flags.is_compressed());
+    assert!(!flags.is_spanned());
+    assert!(!flags.is_solid());
+    assert!(!flags.is_free());
+    assert!(ResHdrFlags(ResourceFlags::SPANNED).is_spanned());
+    assert!(ResHdrFlags(ResourceFlags::SOLID).is_solid());
+
+    let wim = write_bytes(&build_wim(&[ImageSpec::new("Image A")]));
+    let mut parser = WimParser::new(wim.path()).unwrap();
+    let header = parser.read_header().unwrap().clone();
+    assert!(header.offset_table_resource.is_metadata());
+    assert!(!header.xml_data_resource.is_compressed());
+    assert!(!header.xml_data_resource.is_spanned());
+}
+
+/// 测试文件头声明压缩但资源未设置压缩标志时，资源按未压缩数据直接读取
+#[test]
+fn test_uncompressed_resources_in_compressed_wim() {
+    let mut bytes = build_wim(&[ImageSpec::new("Image A").file("/a.txt", b"hello")]);
+    let flags = FileFlags::COMPRESSION | FileFlags::COMPRESS_LZX;
+    bytes[16..20].copy_from_slice(&flags.to_le_bytes());
+    let wim = write_bytes(&bytes);
+
+    let mut parser = WimParser::new(wim.path()).unwrap();
+    parser.parse_full().unwrap();
+    assert!(matches!(parser.compression(), Compression::Lzx { .. }));
+    let mut tree = parser.open_lazy_tree(1).unwrap();
+    let mut data = Vec::new();
+    tree.extract_file(&mut parser, "/a.txt", &mut data).unwrap();
+    assert_eq!(data, b"hello");
+}
@@ -0,0 +1,107 @@
+#![cfg(feature = "verify")]
+
+use std::io::Read;
+
+use wim_parser::{
+    ApplyOptions, DirectoryTarget, FileFlags, TarTarget, VirtualWim, WimParser, WriteSettings,
+};
+
+/// 从 `C:\` 捕获的镜像：目录联接、捕获根目录内外的绝对符号链接和相对符号链接
+fn captured_wim(settings: WriteSettings) -> (tempfile::TempDir, WimParser) {
+    let mut wim = VirtualWim::new();
+    wim.set_write_settings(settings).set_capture_root(r"C:\");
+    wim.add_image("Windows 11 Pro")
+        .add_file("/Users/Public/a.txt", b"public")
+        .unwrap()
+        .add_junction("/Documents and Settings", r"C:\Users")
+        .unwrap()
+        .add_symlink("/Users/Public/link.txt", r"C:\Users\Public\a.txt")
+        .unwrap()
+        .add_symlink("/data", r"D:\data")
+        .unwrap()
+        .add_symlink("/Users/Public/rel.txt", "a.txt")
+        .unwrap();
+
+    let dir = tempfile::tempdir().unwrap();
+    let parser = wim.open(dir.path().join("rpfix.wim")).unwrap();
+    (dir, parser)
+}
+
+/// 测试 RP_FIX 标志的写入，以及释放到 tar 时修正后的目标转换为相对路径
+#[test]
+fn test_rp_fix_header_and_tar_targets() {
+    let (_dir, mut parser) = captured_wim(WriteSettings::default());
+    let flags = parser.read_header().unwrap().flags();
+    assert!(flags.contains(FileFlags::RP_FIX));
+
+    let mut target = TarTarget::new(Vec::new());
+    parser
+        .apply_to(1, &mut target, &ApplyOptions::new())
+        .unwrap();
+    let data = target.into_inner();
+    let mut archive = tar::Archive::new(data.as_slice());
+    let mut links = Vec::new();
+    for entry in archive.entries().unwrap() {
+        let mut entry = entry.unwrap();
+        if let Some(link) = entry.link_name().unwrap() {
+            let path = entry.path().unwrap().to_string_lossy().into_owned();
+            links.push((path, link.to_string_lossy().into_owned()));
+        }
+        entry.read_to_end(&mut Vec::new()).unwrap();
+    }
+    links.sort();
+    assert_eq!(
+        links,
+        [
+            ("Documents and Settings".to_string(), "Users".to_string()),
+            ("Users/Public/link.txt".to_string(), "a.txt".to_string()),
+            ("Users/Public/rel.txt".to_string(), "a.txt".to_string()),
+            ("data".to_string(), "D:/data".to_string()),
+        ]
+    );
+
+    let (_dir, mut parser) = captured_wim(WriteSettings::default().rp_fix(false));
+    let flags = parser.read_header().unwrap().flags();
+    assert!(!flags.contains(FileFlags::RP_FIX));
+}
+
+/// 测试释放到本地目录时修正后的链接指向目标目录之下，关闭 RP_FIX 时保持镜像内路径
+#[cfg(unix)]
+#[test]
+fn test_rp_fix_directory_target() {
+    let (_dir, mut parser) = captured_wim(WriteSettings::default());
+    let out = tempfile::tempdir().unwrap();
+    let mut target = DirectoryTarget::new(out.path());
+    parser
+        .apply_to(1, &mut target, &ApplyOptions::new())
+        .unwrap();
+
+    let root = out.path().to_string_lossy().replace('\\', "/");
+    let read_link = |path: &str| {
+        std::fs::read_link(out.path().join(path))
+            .unwrap()
+            .to_string_lossy()
+            .into_owned()
+    };
+    assert_eq!(read_link("Documents and Settings"), format!("{root}/Users"));
+    assert_eq!(
+        read_link("Users/Public/link.txt"),
+        format!("{root}/Users/Public/a.txt")
+    );
+    assert_eq!(read_link("data"), "D:/data");
+    assert_eq!(read_link("Users/Public/rel.txt"), "a.txt");
+    assert_eq!(
+        std::fs::read(out.path().join("Users/Public/link.txt")).unwrap(),
+        b"public"
+    );
+
+    let out = tempfile::tempdir().unwrap();
+    let mut target = DirectoryTarget::new(out.path());
+    parser
+        .apply_to(1, &mut target, &ApplyOptions::new().rp_fix(false))
+        .unwrap();
+    assert_eq!(
+        std::fs::read_link(out.path().join("Documents and Settings")).unwrap(),
+        std::path::Path::new("/Users")
+    );
+}
@@ -0,0 +1,67 @@
+#![cfg(feature = "verify")]
+
+mod common;
+
+use common::{build_wim, write_bytes, write_wim, ImageSpec};
+use wim_parser::fmt::ToTable;
+use wim_parser::{StreamStatus, WimParser};
+
+fn spec() -> ImageSpec {
+    (0..9).fold(ImageSpec::new("Image"), |spec, i| {
+        spec.file(&format!("/file{i}.bin"), &[i as u8 + 1; 512])
+    })
+}
+
+fn valid_hashes(report: &wim_parser::SampledVerification) -> Vec<[u8; 20]> {
+    report
+        .verification
+        .checks
+        .iter()
+        .filter(|check| check.status == StreamStatus::Valid)
+        .map(|check| check.hash)
+        .collect()
+}
+
+/// 测试相同种子得到相同的抽样
+#[test]
+fn test_verify_sampled_seeded() {
+    let wim = write_wim(&[spec()]);
+    let mut parser = WimParser::new(wim.path()).unwrap();
+
+    // 9 个数据流 + 1 个元数据资源，30% 向上取整为 3 个
+    let first = parser.verify_sampled_seeded(30.0, 42).unwrap();
+    assert_eq!(first.population, 10);
+    assert_eq!(first.sampled, 3);
+    assert_eq!(first.verification.valid_count(), 3);
+    assert_eq!(first.verification.skipped_count(), 7);
+    assert!(first.is_ok());
+    assert_eq!(first.failure_rate(), 0.0);
+    let confidence = first.detection_probability(0.5);
+    assert!(confidence > 0.0 && confidence < 1.0);
+
+    let second = parser.verify_sampled_seeded(30.0, 42).unwrap();
+    assert_eq!(valid_hashes(&first), valid_hashes(&second));
+
+    let full = parser.verify_sampled(100.0).unwrap();
+    assert_eq!(full.sampled, 10);
+    assert_eq!(full.detection_probability(0.01), 1.0);
+    assert!(full.table().to_string().contains("随机种子"));
+
+    assert!(parser.verify_sampled(0.0).is_err());
+    assert!(parser.verify_sampled(150.0).is_err());
+}
+
+/// 测试抽样发现损坏的数据流
+#[test]
+fn test_verify_sampled_detects_corruption() {
+    let mut bytes = build_wim(&[spec()]);
+    let position = bytes.windows(512).position(|w| w == [3u8; 512]).unwrap();
+    bytes[position] ^= 0xFF;
+    let wim = write_bytes(&bytes);
+    let mut parser = WimParser::new(wim.path()).unwrap();
+
+    let report = parser.verify_sampled_seeded(100.0, 7).unwrap();
+    assert!(!report.is_ok());
+    assert_eq!(report.verification.failures().count(), 1);
+    assert!((report.failure_rate() - 0.1).abs() < 1e-9);
+}
@@ -0,0 +1,135 @@
+#![cfg(feature = "parser")]
+
+mod common;
+
+use common::{write_wim, ImageSpec};
+use wim_parser::{Error, SecurityDescriptor, Sid, WimParser};
+
+fn sid(authority: u8, subs: &[u32]) -> Vec<u8> {
+    let mut bytes = vec![1, subs.len() as u8, 0, 0, 0, 0, 0, authority];
+    bytes.extend(subs.iter().flat_map(|sub| sub.to_le_bytes()));
+    bytes
+}
+
+fn ace(ace_type: u8, flags: u8, mask: u32, sid: &[u8]) -> Vec<u8> {
+    let size = (8 + sid.len()) as u16;
+    let mut bytes = vec![ace_type, flags];
+    bytes.extend(size.to_le_bytes());
+    bytes.extend(mask.to_le_bytes());
+    bytes.extend(sid);
+    bytes
+}
+
+fn acl(aces: &[Vec<u8>]) -> Vec<u8> {
+    let size = (8 + aces.iter().map(Vec::len).sum::<usize>()) as u16;
+    let mut bytes = vec![2, 0];
+    bytes.extend(size.to_le_bytes());
+    bytes.extend((aces.len() as u16).to_le_bytes());
+    bytes.extend([0, 0]);
+    aces.iter().for_each(|ace| bytes.extend(ace));
+    bytes
+}
+
+/// 自相关安全描述符：各部分依次排列在 20 字节的头部之后
+fn descriptor(control: u16, owner: &[u8], group: &[u8], sacl: &[u8], dacl: &[u8]) -> Vec<u8> {
+    let mut bytes = vec![1, 0];
+    bytes.extend(control.to_le_bytes());
+    let mut body: Vec<u8> = Vec::new();
+    for part in [owner, group, sacl, dacl] {
+        let offset = if part.is_empty() {
+            0
+        } else {
+            20 + body.len() as u32
+        };
+        bytes.extend(offset.to_le_bytes());
+        body.extend(part);
+    }
+    bytes.extend(body);
+    bytes
+}
+
+/// 测试解析典型的 NTFS 安全描述符并转为 SDDL
+#[test]
+fn test_security_descriptor_sddl() {
+    let trusted_installer = [
+        80, 956008885, 3418522649, 1831038044, 1853292631, 2271478464,
+    ];
+    let dacl = acl(&[
+        ace(0, 0x03, 0x001F_01FF, &sid(5, &[18])),
+        ace(0, 0x0B, 0x1000_0000, &sid(3, &[0])),
+        ace(0, 0x00, 0x0012_00A9, &sid(5, &[32, 545])),
+        ace(0, 0x13, 0x001F_01FF, &sid(5, &trusted_installer)),
+        ace(1, 0x00, 0x000C_0000, &sid(5, &[7])),
+    ]);
+    let sacl = acl(&[ace(0x11, 0x00, 0x1, &sid(16, &[12288]))]);
+    let bytes = descriptor(
+        0x8000 | 0x1000 | 0x0400 | 0x0010 | 0x0004,
+        &sid(5, &[32, 544]),
+        &sid(5, &[18]),
+        &sacl,
+        &dacl,
+    );
+
+    let descriptor = SecurityDescriptor::parse(&bytes).unwrap();
+    assert!(descriptor.has_dacl() && descriptor.has_sacl());
+    let owner = descriptor.owner.as_ref().unwrap();
+    assert_eq!(owner.to_string(), "S-1-5-32-544");
+    assert_eq!(owner.sddl_alias(), Some("BA"));
+    let dacl = descriptor.dacl.as_ref().unwrap();
+    assert_eq!(dacl.aces.len(), 5);
+    assert_eq!(dacl.aces[2].mask, 0x0012_00A9);
+    assert_eq!(
+        descriptor.to_sddl(),
+        "O:BAG:SYD:PAI(A;OICI;FA;;;SY)(A;OICIIO;GA;;;CO)(A;;0x1200a9;;;BU)\
+         (A;OICIID;FA;;;S-1-5-80-956008885-3418522649-1831038044-1853292631-2271478464)\
+         (D;;WDWO;;;AN)S:(ML;;NW;;;HI)"
+    );
+
+    let (sid, size) = Sid::parse(&sid(5, &[21, 1, 2, 3, 1001])).unwrap();
+    assert_eq!(size, 28);
+    assert_eq!(sid.to_sddl(), "S-1-5-21-1-2-3-1001");
+
+    assert!(matches!(
+        SecurityDescriptor::parse(&bytes[..30]),
+        Err(Error::Truncated { .. })
+    ));
+    assert!(SecurityDescriptor::parse(&[1, 0, 4]).is_err());
+}
+
+/// 测试从元数据资源读取目录项的安全描述符
+#[test]
+fn test_image_security_descriptors() {
+    let wim = write_wim(&[
+        ImageSpec::new("Secured")
+            .dir("Windows")
+            .file("Windows/notepad.exe", b"notepad")
+            .secured(),
+        ImageSpec::new("Plain").file("readme.txt", b"readme"),
+    ]);
+    let mut parser = WimParser::new(wim.path()).unwrap();
+
+    let metadata = parser.read_image_metadata(1).unwrap();
+    assert_eq!(metadata.security_descriptors.len(), 1);
+    let notepad = metadata.root.find("Windows/notepad.exe").unwrap();
+    assert_eq!(notepad.security_id, Some(0));
+    let descriptor = metadata
+        .parsed_security_descriptor(notepad)
+        .unwrap()
+        .unwrap();
+    assert_eq!(descriptor.owner, None);
+    assert_eq!(descriptor.dacl, None);
+    assert_eq!(descriptor.to_string(), "D:NO_ACCESS_CONTROL");
+
+    let files: Vec<_> = parser.list_files(1).unwrap().map(Result::unwrap).collect();
+    assert!(files.iter().all(|(_, file)| file.security_id == Some(0)));
+
+    let metadata = parser.read_image_metadata(2).unwrap();
+    let readme = metadata.root.find("readme.txt").unwrap();
+    assert_eq!(readme.security_id, None);
+    assert!(metadata
+        .parsed_security_descriptor(readme)
+        .unwrap()
+        .is_none());
+    let files: Vec<_> = parser.list_files(2).unwrap().map(Result::unwrap).collect();
+    assert_eq!(files[0].1.security_id, None);
+}
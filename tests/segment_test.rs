@@ -0,0 +1,107 @@
+#![cfg(feature = "parser")]
+
+mod common;
+
+use std::path::Path;
+
+use common::{build_wim, ImageSpec};
+use wim_parser::fmt::{format_guid, ToTable};
+use wim_parser::{segment_path, SegmentIssue, WimParser};
+
+/// 写入一个分卷文件
+fn write_segment(path: &Path, number: u16, total: u16, guid: u8) {
+    let mut bytes = build_wim(&[ImageSpec::new("Image A")]);
+    bytes[24..40].copy_from_slice(&[guid; 16]);
+    bytes[40..42].copy_from_slice(&number.to_le_bytes());
+    bytes[42..44].copy_from_slice(&total.to_le_bytes());
+    std::fs::write(path, bytes).unwrap();
+}
+
+/// 测试分卷命名惯例
+#[test]
+fn test_segment_path() {
+    let first = Path::new("/media/sources/install.swm");
+    assert_eq!(segment_path(first, 1), first);
+    assert_eq!(
+        segment_path(first, 3),
+        Path::new("/media/sources/install3.swm")
+    );
+    assert_eq!(
+        format_guid(&[
+            0xE0, 0x04, 0x25, 0x3F, 0x89, 0x4F, 0xD3, 0x11, 0x9A, 0x0C, 0x03, 0x05, 0xE8, 0x2C,
+            0x33, 0x01
+        ]),
+        "{3F2504E0-4F89-11D3-9A0C-0305E82C3301}"
+    );
+}
+
+/// 测试查找和检查完整的分卷集
+#[test]
+fn test_discover_complete_set() {
+    let dir = tempfile::tempdir().unwrap();
+    let first = dir.path().join("install.swm");
+    for number in 1..=3 {
+        write_segment(&segment_path(&first, number), number, 3, 0x11);
+    }
+
+    // 从第 2 个分卷开始也能找到其余分卷
+    let mut parser = WimParser::new(segment_path(&first, 2)).unwrap();
+    assert_eq!(parser.discover_segments().unwrap(), 2);
+    assert_eq!(parser.discover_segments().unwrap(), 0);
+
+    let segments = parser.segments().unwrap();
+    let numbers: Vec<u16> = segments.iter().map(|s| s.number).collect();
+    assert_eq!(numbers, [1, 2, 3]);
+    assert_eq!(segments[0].path, first);
+    assert!(segments.iter().all(|s| s.total == 3 && s.size > 0));
+
+    let validation = parser.validate_segments().unwrap();
+    assert!(validation.is_complete());
+    assert_eq!(validation.segments.len(), 3);
+    assert_eq!(validation.table().rows().len(), 3);
+}
+
+/// 测试缺失、GUID 不一致和重复的分卷
+#[test]
+fn test_validate_incomplete_set() {
+    let dir = tempfile::tempdir().unwrap();
+    let first = dir.path().join("install.swm");
+    write_segment(&first, 1, 3, 0x11);
+    let other = dir.path().join("other.swm");
+    write_segment(&other, 2, 3, 0x22);
+    let copy = dir.path().join("copy.swm");
+    write_segment(&copy, 1, 3, 0x11);
+
+    let mut parser = WimParser::new(&first).unwrap();
+    assert_eq!(parser.discover_segments().unwrap(), 0);
+    parser.register_segment(&other).unwrap();
+    parser.register_segment(&copy).unwrap();
+    // 重复登记同一路径只保留一份
+    parser.register_segment(&copy).unwrap();
+    assert!(parser
+        .register_segment(dir.path().join("absent.swm"))
+        .is_err());
+
+    let validation = parser.validate_segments().unwrap();
+    assert!(!validation.is_complete());
+    assert_eq!(validation.missing(), [2, 3]);
+    assert!(validation.issues.contains(&SegmentIssue::GuidMismatch {
+        path: other.clone(),
+        guid: [0x22; 16],
+    }));
+    assert!(validation.issues.contains(&SegmentIssue::Duplicate {
+        number: 1,
+        path: copy.clone(),
+    }));
+
+    let missing = validation
+        .issues
+        .iter()
+        .find(|issue| matches!(issue, SegmentIssue::Missing { number: 2, .. }))
+        .unwrap();
+    assert_eq!(
+        missing.to_string(),
+        format!("缺少分卷 2 ({})", dir.path().join("install2.swm").display())
+    );
+    assert!(validation.table().to_string().contains("(缺失)"));
+}
@@ -0,0 +1,187 @@
+#![cfg(feature = "serve")]
+
+mod common;
+
+use common::{
+    build_wim, chunked_stream, sha1_hash, write_bytes, xpress_compress, ImageSpec, XPRESS,
+};
+use std::io::{Read, Write};
+use std::net::TcpStream;
+use std::thread;
+use std::time::{Duration, Instant};
+use wim_parser::WimParser;
+
+/// 在镜像服务上依次发送原始请求，返回各响应（头部, 正文）
+fn exchange(parser: &mut WimParser, index: u32, requests: &[&str]) -> Vec<(String, Vec<u8>)> {
+    let mut server = parser.bind_image(index, "127.0.0.1:0").unwrap();
+    let addr = server.local_addr().unwrap();
+    let requests: Vec<String> = requests.iter().map(|r| r.to_string()).collect();
+    let count = requests.len();
+    let client = thread::spawn(move || {
+        requests
+            .iter()
+            .map(|request| {
+                let mut stream = TcpStream::connect(addr).unwrap();
+                stream.write_all(request.as_bytes()).unwrap();
+                let mut response = Vec::new();
+                stream.read_to_end(&mut response).unwrap();
+                let split = response.windows(4).position(|w| w == b"\r\n\r\n").unwrap();
+                let head = String::from_utf8(response[..split].to_vec()).unwrap();
+                (head, response[split + 4..].to_vec())
+            })
+            .collect()
+    });
+    for _ in 0..count {
+        server.handle_next().unwrap();
+    }
+    client.join().unwrap()
+}
+
+fn get(path: &str, headers: &str) -> String {
+    format!("GET {path} HTTP/1.1\r\nHost: localhost\r\n{headers}\r\n")
+}
+
+/// 测试目录列表（HTML 和 JSON）和文件下载
+#[test]
+fn test_serve_listing_and_download() {
+    let readme = b"archived image served over http".to_vec();
+    let bytes = build_wim(&[ImageSpec::new("Test")
+        .dir("Windows")
+        .dir("Windows/System32")
+        .file("Windows/System32/a b.txt", &readme)
+        .file("readme.txt", &readme)
+        .file("empty.txt", b"")]);
+    let wim = write_bytes(&bytes);
+    let mut parser = WimParser::new(wim.path()).unwrap();
+
+    let responses = exchange(
+        &mut parser,
+        1,
+        &[
+            &get("/", ""),
+            &get("/Windows/System32?format=json", ""),
+            &get("/windows/", "Accept: application/json\r\n"),
+            &get("/Windows/System32/a%20b.txt", ""),
+            &get("/empty.txt", ""),
+            "HEAD /readme.txt HTTP/1.1\r\n\r\n",
+        ],
+    );
+
+    let (head, body) = &responses[0];
+    assert!(head.starts_with("HTTP/1.1 200 OK"));
+    assert!(head.contains("Content-Type: text/html"));
+    let html = String::from_utf8_lossy(body);
+    assert!(html.contains("<a href=\"/Windows/\">Windows/</a>"));
+    assert!(html.contains("<a href=\"/readme.txt\">readme.txt</a>"));
+
+    let (head, body) = &responses[1];
+    assert!(head.contains("Content-Type: application/json"));
+    let json = String::from_utf8_lossy(body);
+    assert!(json.starts_with("{\"path\":\"/Windows/System32\",\"entries\":[{\"name\":\"a b.txt\""));
+    assert!(json.contains(&format!("\"directory\":false,\"size\":{}", readme.len())));
+
+    let json = String::from_utf8_lossy(&responses[2].1);
+    assert!(json.contains("{\"name\":\"System32\",\"directory\":true,\"size\":0"));
+
+    let (head, body) = &responses[3];
+    assert!(head.starts_with("HTTP/1.1 200 OK"));
+    assert!(head.contains("Accept-Ranges: bytes"));
+    assert_eq!(body, &readme);
+
+    assert!(responses[4].0.contains("Content-Length: 0"));
+    assert!(responses[4].1.is_empty());
+
+    let (head, body) = &responses[5];
+    assert!(head.contains(&format!("Content-Length: {}", readme.len())));
+    assert!(body.is_empty());
+}
+
+/// 测试压缩数据流的范围请求
+#[test]
+fn test_serve_ranges() {
+    let data: Vec<u8> = (0..100_000u32).map(|i| (i % 251) as u8).collect();
+    let hash = sha1_hash(&data);
+    let compressed: Vec<Vec<u8>> = data.chunks(32768).map(xpress_compress).collect();
+    let chunks: Vec<&[u8]> = compressed.iter().map(Vec::as_slice).collect();
+    let bytes = build_wim(&[ImageSpec::new("Test").file("data.bin", &data)]);
+    let bytes = chunked_stream(bytes, XPRESS, 32768, hash, data.len() as u64, &chunks);
+    let wim = write_bytes(&bytes);
+    let mut parser = WimParser::new(wim.path()).unwrap();
+
+    let responses = exchange(
+        &mut parser,
+        1,
+        &[
+            &get("/data.bin", "Range: bytes=40000-70009\r\n"),
+            &get("/data.bin", "Range: bytes=-10\r\n"),
+            &get("/data.bin", "Range: bytes=99990-\r\n"),
+            &get("/data.bin", "Range: bytes=100000-\r\n"),
+            &get("/data.bin", "Range: bytes=0-1,5-6\r\n"),
+        ],
+    );
+
+    let (head, body) = &responses[0];
+    assert!(head.starts_with("HTTP/1.1 206 Partial Content"));
+    assert!(head.contains("Content-Range: bytes 40000-70009/100000"));
+    assert_eq!(body, &data[40000..70010]);
+
+    assert!(responses[1]
+        .0
+        .contains("Content-Range: bytes 99990-99999/100000"));
+    assert_eq!(responses[1].1, data[99990..]);
+    assert_eq!(responses[2].1, data[99990..]);
+
+    let (head, body) = &responses[3];
+    assert!(head.starts_with("HTTP/1.1 416"));
+    assert!(head.contains("Content-Range: bytes */100000"));
+    assert!(body.is_empty());
+
+    assert!(responses[4].0.starts_with("HTTP/1.1 200 OK"));
+    assert_eq!(responses[4].1, data);
+}
+
+/// 测试不存在的路径、不支持的方法和无效的请求
+#[test]
+fn test_serve_errors() {
+    let bytes = build_wim(&[ImageSpec::new("Test").file("readme.txt", b"hello")]);
+    let wim = write_bytes(&bytes);
+    let mut parser = WimParser::new(wim.path()).unwrap();
+
+    let responses = exchange(
+        &mut parser,
+        1,
+        &[
+            &get("/missing.txt", ""),
+            "POST /readme.txt HTTP/1.1\r\nContent-Length: 0\r\n\r\n",
+            "garbage\r\n\r\n",
+            &get("/%FF", ""),
+        ],
+    );
+    assert!(responses[0].0.starts_with("HTTP/1.1 404 Not Found"));
+    assert!(String::from_utf8_lossy(&responses[0].1).contains("missing.txt"));
+    assert!(responses[1].0.starts_with("HTTP/1.1 405"));
+    assert!(responses[2].0.starts_with("HTTP/1.1 400"));
+    assert!(responses[3].0.starts_with("HTTP/1.1 400"));
+
+    // 不发送请求的客户端超时后被断开，不影响后续连接
+    let mut server = parser.bind_image(1, "127.0.0.1:0").unwrap();
+    server.set_timeout(Duration::from_millis(200));
+    let addr = server.local_addr().unwrap();
+    let idle = TcpStream::connect(addr).unwrap();
+    let start = Instant::now();
+    assert!(server.handle_next().is_err());
+    assert!(start.elapsed() < Duration::from_secs(10));
+    drop(idle);
+    let client = thread::spawn(move || {
+        let mut stream = TcpStream::connect(addr).unwrap();
+        stream.write_all(get("/readme.txt", "").as_bytes()).unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        response
+    });
+    server.handle_next().unwrap();
+    assert!(client.join().unwrap().starts_with("HTTP/1.1 200 OK"));
+    drop(server);
+
+    assert!(parser.bind_image(2, "127.0.0.1:0").is_err());
+}
@@ -0,0 +1,105 @@
+#![cfg(feature = "parser")]
+
+mod common;
+
+use common::{sha1_hash, write_wim, ImageSpec};
+use wim_parser::fmt::ToTable;
+use wim_parser::{Sha1Manifest, WimParser};
+
+const EMPTY_SHA1: &str = "da39a3ee5e6b4b0d3255bfef95601890afd80709";
+
+fn hex(data: &[u8]) -> String {
+    sha1_hash(data).iter().map(|b| format!("{b:02x}")).collect()
+}
+
+/// 测试从镜像导出 `.sha1` 清单并重新解析
+#[test]
+fn test_export_sha1_manifest() {
+    let wim = write_wim(&[ImageSpec::new("Image")
+        .file("/Windows/a.dll", b"hello")
+        .file("/empty.txt", b"")
+        .symlink("/link", "empty.txt")]);
+    let mut parser = WimParser::new(wim.path()).unwrap();
+
+    let manifest = parser.sha1_manifest(1).unwrap();
+    let text = manifest.to_string();
+    assert_eq!(
+        text,
+        format!(
+            "{}  Windows/a.dll\n{EMPTY_SHA1}  empty.txt\n",
+            hex(b"hello")
+        )
+    );
+    assert_eq!(Sha1Manifest::parse(&text).unwrap(), manifest);
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("image.sha1");
+    manifest.save(&path).unwrap();
+    assert_eq!(Sha1Manifest::load(&path).unwrap(), manifest);
+}
+
+/// 测试解析不同工具生成的清单格式
+#[test]
+fn test_parse_sha1_manifest_formats() {
+    let hash = hex(b"hello");
+    let text = format!(
+        "\u{feff}# generated\r\n\
+         {hash}  ./Windows/a.dll\r\n\
+         {hash} *Windows\\b.dll\n\
+         \\{hash}  dir\\\\name\n\
+         SHA1 (Windows/c.dll) = {}\n\n",
+        hash.to_uppercase()
+    );
+    let manifest = Sha1Manifest::parse(&text).unwrap();
+    let paths: Vec<&str> = manifest.entries().iter().map(|e| e.path.as_str()).collect();
+    assert_eq!(
+        paths,
+        [
+            "Windows/a.dll",
+            "Windows/b.dll",
+            "dir/name",
+            "Windows/c.dll"
+        ]
+    );
+    assert!(manifest
+        .entries()
+        .iter()
+        .all(|entry| entry.hash == sha1_hash(b"hello")));
+
+    let error = Sha1Manifest::parse("not a manifest line").unwrap_err();
+    assert!(error.to_string().contains("第 1 行"));
+}
+
+/// 测试清单与镜像内容比对
+#[test]
+fn test_compare_sha1_manifest() {
+    let wim = write_wim(&[ImageSpec::new("Image")
+        .file("/Windows/a.dll", b"hello")
+        .file("/Windows/b.dll", b"changed")
+        .file("/new.txt", b"new")
+        .file("/empty.txt", b"")]);
+    let mut parser = WimParser::new(wim.path()).unwrap();
+
+    let text = format!(
+        "{}  windows/A.DLL\n{}  Windows/b.dll\n{}  gone.txt\n{EMPTY_SHA1}  empty.txt\n",
+        hex(b"hello"),
+        hex(b"original"),
+        hex(b"gone"),
+    );
+    let manifest = Sha1Manifest::parse(&text).unwrap();
+    let comparison = parser.compare_sha1_manifest(1, &manifest).unwrap();
+
+    assert_eq!(comparison.matched, 2);
+    assert_eq!(comparison.mismatched.len(), 1);
+    assert_eq!(comparison.mismatched[0].path, "Windows/b.dll");
+    assert_eq!(comparison.mismatched[0].actual, sha1_hash(b"changed"));
+    assert_eq!(comparison.missing, ["gone.txt"]);
+    assert_eq!(comparison.unlisted, ["new.txt"]);
+    assert!(!comparison.is_ok());
+    assert_eq!(comparison.table().rows().len(), 3);
+
+    let exported = parser.sha1_manifest(1).unwrap();
+    let comparison = parser.compare_sha1_manifest(1, &exported).unwrap();
+    assert!(comparison.is_ok());
+    assert!(comparison.unlisted.is_empty());
+}
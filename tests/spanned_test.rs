@@ -0,0 +1,96 @@
+#![cfg(feature = "parser")]
+
+mod common;
+
+use common::{build_wim, sha1_hash, write_bytes, ImageSpec};
+use wim_parser::{FileFlags, ParseOptions, Warning, WimParser};
+
+/// 将数据流的偏移表条目标记为 SPANNED，分卷号改为 `part_number`
+fn mark_spanned(bytes: &mut [u8], hash: [u8; 20], part_number: u16) {
+    let entry = bytes
+        .windows(20)
+        .rposition(|window| window == hash)
+        .unwrap()
+        - 30;
+    bytes[entry + 7] |= 0x08;
+    bytes[entry + 24..entry + 26].copy_from_slice(&part_number.to_le_bytes());
+}
+
+fn set_header(bytes: &mut [u8], flags: u32, total_segments: u16) {
+    bytes[16..20].copy_from_slice(&flags.to_le_bytes());
+    bytes[42..44].copy_from_slice(&total_segments.to_le_bytes());
+}
+
+/// 测试单分卷文件误设 SPANNED 标志时报告警告，并仍能读取数据流
+#[test]
+fn test_mis_flagged_single_segment() {
+    let mut bytes = build_wim(&[ImageSpec::new("Test")
+        .dir("Windows")
+        .file("Windows/notepad.exe", b"notepad")
+        .file("readme.txt", b"readme")]);
+    set_header(&mut bytes, FileFlags::SPANNED, 1);
+    mark_spanned(&mut bytes, sha1_hash(b"notepad"), 0);
+    mark_spanned(&mut bytes, sha1_hash(b"readme"), 1);
+    let wim = write_bytes(&bytes);
+
+    let mut parser = WimParser::new(wim.path()).unwrap();
+    parser.parse_full().unwrap();
+    let header = parser.get_header().unwrap();
+    assert!(!header.is_split());
+    assert!(header.spanned_flag_mismatch());
+
+    let mut out = Vec::new();
+    parser
+        .extract_file(1, "Windows/notepad.exe", &mut out)
+        .unwrap();
+    assert_eq!(out, b"notepad");
+    let location = parser.resolve_stream(&sha1_hash(b"notepad")).unwrap();
+    assert_eq!(location.segment, 1);
+    assert_eq!(
+        parser.warnings(),
+        [
+            Warning::SpannedFlagMismatch {
+                total_segments: 1,
+                flagged: true,
+            },
+            Warning::SpannedResources { count: 2 },
+        ]
+    );
+    assert_eq!(
+        parser.warnings()[1].to_string(),
+        "单分卷文件中有 2 个资源条目设置了 SPANNED 标志"
+    );
+
+    let mut parser = WimParser::with_options(wim.path(), ParseOptions::new().strict(true)).unwrap();
+    let err = parser.read_header().unwrap_err();
+    assert!(err.to_string().contains("SPANNED"));
+}
+
+/// 测试多分卷文件缺少 SPANNED 标志时报告警告，分卷号仍按偏移表处理
+#[test]
+fn test_split_without_spanned_flag() {
+    let mut bytes = build_wim(&[ImageSpec::new("Test").file("readme.txt", b"readme")]);
+    set_header(&mut bytes, 0, 2);
+    mark_spanned(&mut bytes, sha1_hash(b"readme"), 2);
+    let wim = write_bytes(&bytes);
+
+    let mut parser = WimParser::new(wim.path()).unwrap();
+    let header = parser.read_header().unwrap();
+    assert!(header.is_split());
+    assert!(header.spanned_flag_mismatch());
+    assert_eq!(
+        parser.warnings(),
+        [Warning::SpannedFlagMismatch {
+            total_segments: 2,
+            flagged: false,
+        }]
+    );
+
+    // 多分卷文件中的 SPANNED 资源是正常的，位于其他分卷的数据流不能从当前文件读取
+    let location = parser.resolve_stream(&sha1_hash(b"readme")).unwrap();
+    assert_eq!(location.segment, 2);
+    assert_eq!(parser.warnings().len(), 1);
+    assert!(parser
+        .extract_file(1, "readme.txt", &mut Vec::new())
+        .is_err());
+}
@@ -0,0 +1,137 @@
+#![cfg(feature = "verify")]
+
+mod common;
+
+use common::{
+    build_wim, chunked_stream, sha1_hash, write_bytes, write_wim, xpress_compress, ImageSpec,
+    XPRESS,
+};
+use wim_parser::fmt::ToTable;
+use wim_parser::{StreamStatus, StreamVerifyOptions, WimParser};
+
+/// 把 `data.bin` 改写为 XPRESS 压缩资源，分块由 `content` 压缩得到
+fn compressed_wim(data: &[u8], content: &[u8]) -> Vec<u8> {
+    let compressed: Vec<Vec<u8>> = content.chunks(32768).map(xpress_compress).collect();
+    let chunks: Vec<&[u8]> = compressed.iter().map(Vec::as_slice).collect();
+    let bytes = build_wim(&[ImageSpec::new("Test")
+        .file("data.bin", data)
+        .file("a.txt", b"hello")]);
+    chunked_stream(
+        bytes,
+        XPRESS,
+        32768,
+        sha1_hash(data),
+        data.len() as u64,
+        &chunks,
+    )
+}
+
+fn specs() -> Vec<ImageSpec> {
+    vec![
+        ImageSpec::new("Image A")
+            .file("/big.bin", &[0xAB; 4096])
+            .file("/a.txt", b"hello"),
+        ImageSpec::new("Image B")
+            .file("/a.txt", b"hello")
+            .file("/b.txt", b"world"),
+    ]
+}
+
+/// 测试并行校验所有数据流
+#[test]
+fn test_verify_all_streams() {
+    let wim = write_wim(&specs());
+    let mut parser = WimParser::new(wim.path()).unwrap();
+
+    let report = parser
+        .verify_all_streams_with(&StreamVerifyOptions::new().threads(2))
+        .unwrap();
+    // 2 个元数据资源 + 3 个数据流
+    assert_eq!(report.checks.len(), 5);
+    assert_eq!(report.checks.iter().filter(|c| c.is_metadata).count(), 2);
+    assert!(report.is_ok());
+    assert!(!report.aborted);
+    assert_eq!(report.threads, 2);
+    assert_eq!(report.valid_count(), 5);
+    assert!(report.table().to_string().contains("通过"));
+
+    // 校验后解析器仍可正常使用
+    parser.parse_full().unwrap();
+    assert_eq!(parser.get_images().len(), 2);
+}
+
+/// 测试损坏的数据流和提前停止
+#[test]
+fn test_verify_all_streams_failures() {
+    let mut bytes = build_wim(&specs());
+    let position = bytes.windows(4096).position(|w| w == [0xAB; 4096]).unwrap();
+    bytes[position + 100] ^= 0xFF;
+    let wim = write_bytes(&bytes);
+    let mut parser = WimParser::new(wim.path()).unwrap();
+
+    let report = parser.verify_all_streams().unwrap();
+    assert!(!report.is_ok());
+    let failures: Vec<_> = report.failures().collect();
+    assert_eq!(failures.len(), 1);
+    assert_eq!(failures[0].hash, sha1_hash(&[0xAB; 4096]));
+    assert!(matches!(failures[0].status, StreamStatus::Mismatch { .. }));
+    assert_eq!(report.valid_count(), 4);
+
+    // 单线程时最大的数据流最先校验，失败后其余数据流被跳过
+    let options = StreamVerifyOptions::new()
+        .threads(1)
+        .stop_on_first_failure(true);
+    let report = parser.verify_all_streams_with(&options).unwrap();
+    assert!(report.aborted);
+    assert_eq!(report.failures().count(), 1);
+    assert_eq!(report.skipped_count(), 4);
+}
+
+/// 测试解压校验压缩资源（verify_all_streams 无法校验）
+#[test]
+fn test_verify_all_resources() {
+    let data: Vec<u8> = (0..80_000u32).map(|i| (i % 251) as u8).collect();
+    let wim = write_bytes(&compressed_wim(&data, &data));
+    let mut parser = WimParser::new(wim.path()).unwrap();
+
+    let streams = parser.verify_all_streams().unwrap();
+    assert_eq!(streams.unverified_count(), 1);
+
+    let report = parser.verify_all_resources().unwrap();
+    assert!(report.is_ok());
+    assert_eq!(report.checks.len(), 3);
+    assert_eq!(report.valid_count(), 3);
+    assert_eq!(report.unverified_count(), 0);
+
+    let entries = parser.lookup_table_entries().unwrap();
+    let entry = entries
+        .iter()
+        .find(|entry| entry.hash == sha1_hash(&data))
+        .unwrap();
+    assert!(entry.resource.is_compressed());
+    let check = parser.verify_resource(entry).unwrap();
+    assert_eq!(check.status, StreamStatus::Valid);
+    assert_eq!(check.size, entry.resource.size);
+}
+
+/// 测试解压后内容与偏移表中的 SHA-1 不一致
+#[test]
+fn test_verify_resource_mismatch() {
+    let data: Vec<u8> = (0..80_000u32).map(|i| (i % 251) as u8).collect();
+    let mut other = data.clone();
+    other[70_000] ^= 0xFF;
+    let wim = write_bytes(&compressed_wim(&data, &other));
+    let mut parser = WimParser::new(wim.path()).unwrap();
+
+    let report = parser.verify_all_resources().unwrap();
+    let failures: Vec<_> = report.failures().collect();
+    assert_eq!(failures.len(), 1);
+    assert_eq!(failures[0].hash, sha1_hash(&data));
+    assert_eq!(
+        failures[0].status,
+        StreamStatus::Mismatch {
+            actual: sha1_hash(&other)
+        }
+    );
+    assert_eq!(report.valid_count(), 2);
+}
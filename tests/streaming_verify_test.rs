@@ -0,0 +1,103 @@
+#![cfg(feature = "verify")]
+
+use sha1::{Digest, Sha1};
+use wim_parser::format::{parse_header, WIM_HEADER_DISK_SIZE};
+use wim_parser::{Error, StreamingVerifier, VirtualWim, WriteSettings};
+
+/// 带完整性表的测试 WIM
+fn wim_bytes() -> Vec<u8> {
+    let mut wim = VirtualWim::new();
+    wim.set_write_settings(WriteSettings::default().integrity(true));
+    wim.add_image("Windows 11 Pro")
+        .add_file("/Windows/notepad.exe", vec![0x5a; 4096])
+        .unwrap();
+    wim.to_bytes().unwrap()
+}
+
+/// 按指定分块大小为完整性区域计算完整性表
+fn integrity_table(data: &[u8], region_end: usize, chunk_size: usize) -> Vec<u8> {
+    let hashes: Vec<[u8; 20]> = data[WIM_HEADER_DISK_SIZE..region_end]
+        .chunks(chunk_size)
+        .map(|chunk| Sha1::digest(chunk).into())
+        .collect();
+    let mut table = Vec::new();
+    table.extend_from_slice(&(12 + hashes.len() as u32 * 20).to_le_bytes());
+    table.extend_from_slice(&(hashes.len() as u32).to_le_bytes());
+    table.extend_from_slice(&(chunk_size as u32).to_le_bytes());
+    for hash in &hashes {
+        table.extend_from_slice(hash);
+    }
+    table
+}
+
+/// 测试按任意大小分段送入完整文件时校验通过，数据不完整时 finish 报告截断
+#[test]
+fn test_streaming_verifier_accepts_download() {
+    let data = wim_bytes();
+    let header = parse_header(&data).unwrap();
+    let range = StreamingVerifier::integrity_table_range(&header).unwrap();
+    let table = &data[range.start as usize..range.end as usize];
+
+    let mut verifier = StreamingVerifier::new(&header, table).unwrap();
+    let mut verified = 0;
+    for piece in data.chunks(333) {
+        verified += verifier.update(piece).unwrap();
+    }
+    assert_eq!(verified, verifier.total_chunks());
+    assert!(verifier.is_complete());
+    assert_eq!(verifier.progress(), 1.0);
+    assert_eq!(verifier.position(), data.len() as u64);
+    verifier.finish().unwrap();
+
+    let mut verifier = StreamingVerifier::new(&header, table).unwrap();
+    verifier
+        .update(&data[..WIM_HEADER_DISK_SIZE + 100])
+        .unwrap();
+    let err = verifier.finish().unwrap_err();
+    assert!(matches!(
+        err.downcast_ref::<Error>(),
+        Some(Error::Truncated { .. })
+    ));
+}
+
+/// 测试损坏的数据在所在分块到齐时立即报告，之后的数据不再处理
+#[test]
+fn test_streaming_verifier_fails_early() {
+    let mut data = wim_bytes();
+    let header = parse_header(&data).unwrap();
+    let region_end = header.offset_table_resource.byte_range().end as usize;
+    let table = integrity_table(&data, region_end, 256);
+    let mut verifier = StreamingVerifier::new(&header, &table).unwrap();
+    assert!(verifier.total_chunks() > 4);
+
+    let corrupt = WIM_HEADER_DISK_SIZE + 256 + 10;
+    data[corrupt] ^= 0xff;
+    let mut failed_at = None;
+    for (offset, piece) in data.chunks(16).enumerate() {
+        if let Err(err) = verifier.update(piece) {
+            assert!(matches!(
+                err.downcast_ref::<Error>(),
+                Some(Error::VerificationFailed { failures: 1 })
+            ));
+            failed_at = Some((offset + 1) * 16);
+            break;
+        }
+    }
+
+    let failure = verifier.failure().unwrap().clone();
+    assert_eq!(failure.index, 1);
+    assert_eq!(
+        failure.range,
+        (WIM_HEADER_DISK_SIZE as u64 + 256)..(WIM_HEADER_DISK_SIZE as u64 + 512)
+    );
+    assert_eq!(failed_at, Some(WIM_HEADER_DISK_SIZE + 512));
+    assert_eq!(verifier.verified_chunks(), 1);
+    assert!(verifier.update(&[0]).is_err());
+    assert!(verifier.finish().is_err());
+
+    // 被截断或分块数与完整性区域不符的表被拒绝
+    let truncated = &table[..table.len() - 20];
+    assert!(StreamingVerifier::new(&header, truncated).is_err());
+    let short = integrity_table(&data, region_end - 300, 256);
+    assert!(StreamingVerifier::new(&header, &short).is_err());
+}
@@ -0,0 +1,395 @@
+#![cfg(feature = "parser")]
+
+mod common;
+
+use anyhow::Result;
+use common::{write_wim, ImageSpec};
+use std::io::{Read, Write};
+use wim_parser::{
+    ApplyOptions, ApplyTarget, DirectoryTarget, EntryMetadata, TarTarget, UnsupportedFeature,
+    WimParser, ZipTarget,
+};
+
+/// 2021-01-01 00:00:00 UTC
+const WRITE_TIME: u64 = 132_539_328_000_000_000;
+const WRITE_TIME_UNIX: i64 = 1_609_459_200;
+
+fn sample_image() -> ImageSpec {
+    ImageSpec::new("Image A")
+        .dir("/Windows/System32")
+        .file("/Windows/System32/a.dll", b"hello")
+        .file("/readme.txt", b"world!")
+        .symlink("/Windows/link.txt", r"..\readme.txt")
+        .reparse("/Windows/dedup.dat", 0x8000_0013)
+        .write_time(WRITE_TIME)
+}
+
+/// 记录调用顺序的内存目标；`raw` 为 `true` 时按原始数据还原重解析点并接受安全描述符
+#[derive(Default)]
+struct RecordingTarget {
+    calls: Vec<String>,
+    files: Vec<(String, Vec<u8>)>,
+    raw: bool,
+}
+
+impl ApplyTarget for RecordingTarget {
+    fn create_dir(&mut self, path: &str, _metadata: &EntryMetadata) -> Result<()> {
+        self.calls.push(format!("dir {path}"));
+        Ok(())
+    }
+
+    fn create_file(
+        &mut self,
+        path: &str,
+        _size: u64,
+        _metadata: &EntryMetadata,
+    ) -> Result<Box<dyn Write + '_>> {
+        self.calls.push(format!("file {path}"));
+        self.files.push((path.to_string(), Vec::new()));
+        Ok(Box::new(&mut self.files.last_mut().unwrap().1))
+    }
+
+    fn set_metadata(&mut self, path: &str, _metadata: &EntryMetadata) -> Result<()> {
+        self.calls.push(format!("meta {path}"));
+        Ok(())
+    }
+
+    fn symlink(&mut self, path: &str, target: &str, _metadata: &EntryMetadata) -> Result<()> {
+        self.calls.push(format!("link {path} -> {target}"));
+        Ok(())
+    }
+
+    fn reparse_point(
+        &mut self,
+        path: &str,
+        tag: u32,
+        data: &[u8],
+        metadata: &EntryMetadata,
+    ) -> Result<bool> {
+        if self.raw {
+            self.calls.push(format!(
+                "reparse {path} 0x{tag:08X} {} dir={}",
+                data.len(),
+                metadata.is_directory()
+            ));
+        }
+        Ok(self.raw)
+    }
+
+    fn set_security_descriptor(&mut self, path: &str, descriptor: &[u8]) -> Result<bool> {
+        if self.raw {
+            self.calls.push(format!("acl {path} {}", descriptor.len()));
+        }
+        Ok(self.raw)
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.calls.push("finish".to_string());
+        Ok(())
+    }
+}
+
+/// 测试释放逻辑对输出目标的调用顺序
+#[test]
+fn test_apply_to_custom_target() {
+    let wim = write_wim(&[sample_image()]);
+    let mut parser = WimParser::new(wim.path()).unwrap();
+
+    let mut target = RecordingTarget::default();
+    let report = parser
+        .apply_to(1, &mut target, &ApplyOptions::new())
+        .unwrap();
+
+    assert_eq!(
+        target.calls,
+        [
+            "dir Windows",
+            "dir Windows/System32",
+            "file Windows/System32/a.dll",
+            "meta Windows/System32/a.dll",
+            "link Windows/link.txt -> ../readme.txt",
+            "file readme.txt",
+            "meta readme.txt",
+            "meta Windows/System32",
+            "meta Windows",
+            "finish",
+        ]
+    );
+    assert_eq!(target.files[0].1, b"hello");
+    assert_eq!(
+        (report.dir_count, report.file_count, report.symlink_count),
+        (2, 2, 1)
+    );
+    assert_eq!(report.total_bytes, 11);
+    assert_eq!(report.unsupported.len(), 1);
+    assert_eq!(
+        report.unsupported[0].feature,
+        UnsupportedFeature::ReparsePoint(0x8000_0013)
+    );
+}
+
+/// 测试支持原始重解析数据和 ACL 的目标（如 NTFS 卷）收到所有重解析点和安全描述符
+#[test]
+fn test_apply_to_raw_target() {
+    let wim = write_wim(&[sample_image().secured()]);
+    let mut parser = WimParser::new(wim.path()).unwrap();
+
+    let mut target = RecordingTarget {
+        raw: true,
+        ..RecordingTarget::default()
+    };
+    let report = parser
+        .apply_to(1, &mut target, &ApplyOptions::new())
+        .unwrap();
+
+    let calls: Vec<&str> = target.calls.iter().map(String::as_str).collect();
+    assert_eq!(
+        calls[..3],
+        ["dir Windows", "acl Windows 20", "dir Windows/System32"]
+    );
+    assert!(calls.contains(&"reparse Windows/dedup.dat 0x80000013 0 dir=false"));
+    let link = calls
+        .iter()
+        .position(|call| call.starts_with("reparse Windows/link.txt 0xA000000C"))
+        .unwrap();
+    assert_eq!(
+        calls[link + 1..link + 3],
+        ["acl Windows/link.txt 20", "meta Windows/link.txt"]
+    );
+    assert!(!calls.iter().any(|call| call.starts_with("link ")));
+
+    assert_eq!((report.reparse_count, report.symlink_count), (2, 0));
+    assert_eq!(report.security_count, 6);
+    assert!(report.unsupported.is_empty());
+}
+
+/// 测试释放到本地目录
+#[test]
+fn test_apply_to_directory() {
+    let wim = write_wim(&[sample_image()]);
+    let out = tempfile::tempdir().unwrap();
+    let mut parser = WimParser::new(wim.path()).unwrap();
+
+    let options = ApplyOptions::new().exclude_extensions(["txt"]);
+    let mut target = DirectoryTarget::new(out.path());
+    let report = parser.apply_to(1, &mut target, &options).unwrap();
+    assert_eq!(report.filtered_count, 2);
+
+    let dll = out.path().join("Windows/System32/a.dll");
+    assert_eq!(std::fs::read(&dll).unwrap(), b"hello");
+    assert!(!out.path().join("readme.txt").exists());
+
+    let modified = std::fs::metadata(&dll).unwrap().modified().unwrap();
+    let expected = std::time::UNIX_EPOCH + std::time::Duration::from_secs(WRITE_TIME_UNIX as u64);
+    assert_eq!(modified, expected);
+    let dir_modified = std::fs::metadata(out.path().join("Windows"))
+        .unwrap()
+        .modified()
+        .unwrap();
+    assert_eq!(dir_modified, expected);
+
+    #[cfg(unix)]
+    {
+        let out = tempfile::tempdir().unwrap();
+        let mut target = DirectoryTarget::new(out.path());
+        parser
+            .apply_to(1, &mut target, &ApplyOptions::new())
+            .unwrap();
+        let link = out.path().join("Windows/link.txt");
+        assert_eq!(
+            std::fs::read_link(&link).unwrap(),
+            std::path::Path::new("../readme.txt")
+        );
+        assert_eq!(std::fs::read(&link).unwrap(), b"world!");
+    }
+}
+
+/// 测试输出为 tar 归档（含长路径）
+#[test]
+fn test_apply_to_tar() {
+    let long_dir = format!("/{}", "d".repeat(120));
+    let wim = write_wim(&[sample_image().file(&format!("{long_dir}/long.bin"), &[7u8; 1000])]);
+    let mut parser = WimParser::new(wim.path()).unwrap();
+
+    let mut target = TarTarget::new(Vec::new());
+    parser
+        .apply_to(1, &mut target, &ApplyOptions::new())
+        .unwrap();
+    let data = target.into_inner();
+    assert_eq!(data.len() % 512, 0);
+
+    let mut archive = tar::Archive::new(data.as_slice());
+    let mut entries = Vec::new();
+    for entry in archive.entries().unwrap() {
+        let mut entry = entry.unwrap();
+        let path = entry.path().unwrap().to_string_lossy().into_owned();
+        let kind = entry.header().entry_type();
+        let mtime = entry.header().mtime().unwrap();
+        let link = entry
+            .link_name()
+            .unwrap()
+            .map(|link| link.to_string_lossy().into_owned());
+        let mut content = Vec::new();
+        entry.read_to_end(&mut content).unwrap();
+        entries.push((path, kind, mtime, link, content));
+    }
+
+    let paths: Vec<&str> = entries.iter().map(|e| e.0.as_str()).collect();
+    let long_path = format!("{}/long.bin", &long_dir[1..]);
+    assert_eq!(
+        paths,
+        [
+            "Windows/",
+            "Windows/System32/",
+            "Windows/System32/a.dll",
+            "Windows/link.txt",
+            "readme.txt",
+            &format!("{}/", &long_dir[1..]),
+            &long_path,
+        ]
+    );
+    assert!(entries[0].1.is_dir());
+    assert_eq!(entries[2].4, b"hello");
+    assert_eq!(entries[2].2, WRITE_TIME_UNIX as u64);
+    assert!(entries[3].1.is_symlink());
+    assert_eq!(entries[3].3.as_deref(), Some("../readme.txt"));
+    assert_eq!(entries[6].4, [7u8; 1000]);
+}
+
+/// 测试输出为 zip 归档
+#[test]
+fn test_apply_to_zip() {
+    let wim = write_wim(&[sample_image()]);
+    let mut parser = WimParser::new(wim.path()).unwrap();
+
+    let mut target = ZipTarget::new(std::io::Cursor::new(Vec::new()));
+    parser
+        .apply_to(1, &mut target, &ApplyOptions::new())
+        .unwrap();
+    let data = target.into_inner().into_inner();
+
+    let mut archive = zip::ZipArchive::new(std::io::Cursor::new(data)).unwrap();
+    let names: Vec<String> = archive.file_names().map(str::to_string).collect();
+    assert_eq!(names.len(), 5);
+
+    let mut dll = archive.by_name("Windows/System32/a.dll").unwrap();
+    let mut content = Vec::new();
+    dll.read_to_end(&mut content).unwrap();
+    assert_eq!(content, b"hello");
+    let modified = dll.last_modified().unwrap();
+    assert_eq!(
+        (modified.year(), modified.month(), modified.day()),
+        (2021, 1, 1)
+    );
+    drop(dll);
+
+    assert!(archive.by_name("Windows/").unwrap().is_dir());
+    let link = archive.by_name("Windows/link.txt").unwrap();
+    assert_eq!(link.unix_mode().map(|mode| mode & 0o170000), Some(0o120000));
+}
+
+/// 测试将镜像导出为 zip（保留时间戳）
+#[test]
+fn test_export_image_as_zip() {
+    let wim = write_wim(&[sample_image(), ImageSpec::new("Empty")]);
+    let mut parser = WimParser::new(wim.path()).unwrap();
+
+    let mut data = Vec::new();
+    let report = parser.export_image_as_zip(1, &mut data).unwrap();
+    assert_eq!(report.file_count, 2);
+
+    let mut archive = zip::ZipArchive::new(std::io::Cursor::new(data)).unwrap();
+    let readme = archive.by_name("readme.txt").unwrap();
+    let mut unix_time = None;
+    let mut filetime = None;
+    for field in readme.extra_data_fields() {
+        match field {
+            zip::ExtraField::ExtendedTimestamp(ts) => unix_time = ts.mod_time(),
+            zip::ExtraField::Ntfs(ntfs) => filetime = Some(ntfs.mtime()),
+        }
+    }
+    assert_eq!(unix_time, Some(WRITE_TIME_UNIX as u32));
+    assert_eq!(filetime, Some(WRITE_TIME));
+    drop(readme);
+
+    let mut empty = Vec::new();
+    parser.export_image_as_zip(2, &mut empty).unwrap();
+    assert_eq!(
+        zip::ZipArchive::new(std::io::Cursor::new(empty))
+            .unwrap()
+            .len(),
+        0
+    );
+}
+
+/// 测试目录符号链接之下的子项不会经由链接写到释放目录之外
+#[cfg(unix)]
+#[test]
+fn test_apply_rejects_writes_through_symlinks() {
+    let outside = tempfile::tempdir().unwrap();
+    let link_target = outside.path().to_string_lossy().into_owned();
+    let wim = write_wim(&[ImageSpec::new("Crafted")
+        .dir_symlink("/escape", &link_target)
+        .file("/escape/payload.txt", b"pwned")]);
+    let mut parser = WimParser::new(wim.path()).unwrap();
+
+    let out = tempfile::tempdir().unwrap();
+    let err = parser
+        .apply_to(
+            1,
+            &mut DirectoryTarget::new(out.path()),
+            &ApplyOptions::new(),
+        )
+        .unwrap_err();
+    assert!(
+        err.to_string().contains("位于符号链接 escape 之下"),
+        "{err}"
+    );
+    assert!(!outside.path().join("payload.txt").exists());
+
+    // 输出目标本身也拒绝经由本次创建的符号链接写入
+    let mut target = DirectoryTarget::new(out.path().join("direct"));
+    let metadata = EntryMetadata::default();
+    target.create_dir("dir", &metadata).unwrap();
+    target.symlink("dir/link", &link_target, &metadata).unwrap();
+    assert!(target.create_file("dir/link/a.txt", 1, &metadata).is_err());
+    assert!(target.create_dir("dir/link/sub", &metadata).is_err());
+    assert!(target.create_file("dir/b.txt", 1, &metadata).is_ok());
+    assert_eq!(std::fs::read_dir(outside.path()).unwrap().count(), 0);
+}
+
+/// 测试与符号链接同名的文件不会跟随链接覆盖释放目录之外的文件
+#[cfg(unix)]
+#[test]
+fn test_apply_rejects_file_over_symlink() {
+    let outside = tempfile::tempdir().unwrap();
+    let victim = outside.path().join("victim.txt");
+    std::fs::write(&victim, b"original").unwrap();
+    let wim = write_wim(&[ImageSpec::new("x")
+        .symlink("/evil", &victim.to_string_lossy())
+        .file("/evil", b"PWNED")]);
+    let mut parser = WimParser::new(wim.path()).unwrap();
+
+    let out = tempfile::tempdir().unwrap();
+    let err = parser
+        .apply_to(
+            1,
+            &mut DirectoryTarget::new(out.path()),
+            &ApplyOptions::new(),
+        )
+        .unwrap_err();
+    assert!(err.to_string().contains("重复的目录项"), "{err}");
+    assert_eq!(std::fs::read(&victim).unwrap(), b"original");
+
+    // 输出目标本身不跟随符号链接写入
+    let mut target = DirectoryTarget::new(out.path().join("direct"));
+    let metadata = EntryMetadata::default();
+    target.create_dir("dir", &metadata).unwrap();
+    target
+        .symlink("dir/Evil", &victim.to_string_lossy(), &metadata)
+        .unwrap();
+    assert!(target.create_file("dir/evil", 1, &metadata).is_err());
+    std::os::unix::fs::symlink(&victim, out.path().join("direct/dir/other")).unwrap();
+    assert!(target.create_file("dir/other", 1, &metadata).is_err());
+    assert_eq!(std::fs::read(&victim).unwrap(), b"original");
+}
@@ -0,0 +1,145 @@
+#![cfg(feature = "parser")]
+
+mod common;
+
+use common::{build_wim, ImageSpec};
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+use wim_parser::error::{codes, error_code};
+use wim_parser::{RewriteStrategy, TempPolicy, WimParser};
+
+fn images() -> Vec<ImageSpec> {
+    vec![
+        ImageSpec::new("Home").file("/home.txt", &[b'h'; 4096]),
+        ImageSpec::new("Pro").file("/pro.txt", b"pro only"),
+    ]
+}
+
+fn entries(dir: &Path) -> Vec<String> {
+    let mut names: Vec<String> = fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+        .collect();
+    names.sort();
+    names
+}
+
+/// 测试临时文件放在其他目录（匿名文件和有名称的文件）时提交事务
+#[test]
+fn test_commit_with_temp_dir() {
+    let source = tempfile::tempdir().unwrap();
+    let scratch = tempfile::tempdir().unwrap();
+    let path = source.path().join("install.wim");
+    fs::write(&path, build_wim(&images())).unwrap();
+    let mut parser = WimParser::new(&path).unwrap();
+    assert_eq!(parser.temp_policy(), &TempPolicy::default());
+
+    parser.set_temp_policy(TempPolicy::new().dir(scratch.path()));
+    let plan = parser.transaction().delete_image(1).commit().unwrap();
+    assert_eq!(plan.strategy, RewriteStrategy::Rebuild);
+    assert_eq!(entries(source.path()), ["install.wim"]);
+    assert!(entries(scratch.path()).is_empty());
+
+    parser.set_temp_policy(TempPolicy::new().dir(scratch.path()).use_tmpfile(false));
+    parser
+        .transaction()
+        .rename_image(1, "Pro N")
+        .commit()
+        .unwrap();
+    assert_eq!(entries(source.path()), ["install.wim"]);
+    assert!(entries(scratch.path()).is_empty());
+
+    parser.parse_full().unwrap();
+    assert_eq!(parser.get_image_count(), 1);
+    assert_eq!(parser.get_images()[0].name, "Pro N");
+    assert_eq!(parser.temp_policy().temp_dir(), Some(scratch.path()));
+}
+
+/// 测试超出临时文件配额：提交失败、原文件不变，按策略删除或保留临时文件
+#[test]
+fn test_commit_exceeding_temp_quota() {
+    let source = tempfile::tempdir().unwrap();
+    let scratch = tempfile::tempdir().unwrap();
+    let path = source.path().join("install.wim");
+    let bytes = build_wim(&images());
+    fs::write(&path, &bytes).unwrap();
+    let mut parser = WimParser::new(&path).unwrap();
+
+    parser.set_temp_policy(TempPolicy::new().max_bytes(256));
+    let err = parser.transaction().delete_image(1).commit().unwrap_err();
+    assert_eq!(error_code(&err), codes::LIMIT_EXCEEDED);
+    assert!(format!("{err:#}").contains("max_temp_bytes"));
+    assert_eq!(fs::read(&path).unwrap(), bytes);
+    assert_eq!(entries(source.path()), ["install.wim"]);
+
+    parser.set_temp_policy(
+        TempPolicy::new()
+            .dir(scratch.path())
+            .use_tmpfile(false)
+            .cleanup_on_drop(false)
+            .max_bytes(256),
+    );
+    let err = parser
+        .transaction()
+        .rename_image(2, "X")
+        .commit()
+        .unwrap_err();
+    assert_eq!(error_code(&err), codes::LIMIT_EXCEEDED);
+    assert_eq!(fs::read(&path).unwrap(), bytes);
+    let left = entries(scratch.path());
+    assert_eq!(left.len(), 1);
+    assert!(left[0].starts_with(".install.wim.") && left[0].ends_with(".tmp"));
+
+    parser.set_temp_policy(TempPolicy::new().max_bytes(bytes.len() as u64 * 2));
+    parser.transaction().delete_image(1).commit().unwrap();
+    parser.parse_full().unwrap();
+    assert_eq!(parser.get_image_count(), 1);
+}
+
+#[cfg(unix)]
+fn inode(metadata: &fs::Metadata) -> u64 {
+    std::os::unix::fs::MetadataExt::ino(metadata)
+}
+
+/// 测试直接使用临时文件：写入、丢弃和替换目标文件
+#[test]
+fn test_temp_file_persist() {
+    let source = tempfile::tempdir().unwrap();
+    let scratch = tempfile::tempdir().unwrap();
+    let target = source.path().join("out.bin");
+
+    let policy = TempPolicy::new().dir(scratch.path()).use_tmpfile(false);
+    let mut temp = policy.create_for(&target).unwrap();
+    temp.write_all(b"discarded").unwrap();
+    let temp_path = temp.path().unwrap().to_path_buf();
+    assert!(temp_path.starts_with(scratch.path()));
+    drop(temp);
+    assert!(!temp_path.exists());
+    assert!(!target.exists());
+
+    // Linux 上默认以 O_TMPFILE 创建匿名文件
+    #[cfg(target_os = "linux")]
+    {
+        let temp = TempPolicy::new().dir(scratch.path()).create_for(&target);
+        assert!(temp.unwrap().path().is_none());
+    }
+
+    for policy in [
+        policy,
+        TempPolicy::new().dir(scratch.path()),
+        TempPolicy::new(),
+    ] {
+        let mut temp = policy.create_for(&target).unwrap();
+        temp.write_all(b"converted output").unwrap();
+        #[cfg(unix)]
+        let before = fs::metadata(&target).ok().map(|m| inode(&m));
+        temp.persist(&target).unwrap();
+        assert_eq!(fs::read(&target).unwrap(), b"converted output");
+        // 匿名文件也经由重命名替换，而不是截断后原地覆盖目标文件
+        #[cfg(unix)]
+        assert_ne!(before, Some(inode(&fs::metadata(&target).unwrap())));
+        assert!(entries(scratch.path()).is_empty());
+        assert_eq!(entries(source.path()), ["out.bin"]);
+    }
+}
@@ -0,0 +1,58 @@
+#![cfg(feature = "verify")]
+
+use std::time::{Duration, Instant};
+
+use wim_parser::{ApplyOptions, StreamVerifyOptions, TarTarget, VirtualWim, WimParser};
+
+/// 含一个 200 KB 文件的测试 WIM
+fn open_wim(dir: &tempfile::TempDir) -> WimParser {
+    let mut wim = VirtualWim::new();
+    wim.add_image("Windows 11 Pro")
+        .add_file("/big.bin", vec![0xa5; 200_000])
+        .unwrap();
+    wim.open(dir.path().join("throttle.wim")).unwrap()
+}
+
+/// 测试限速释放：读写各受速率限制，内容不受影响
+#[test]
+fn test_apply_max_throughput() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut parser = open_wim(&dir);
+
+    let options = ApplyOptions::new().max_throughput(1_000_000);
+    assert_eq!(options.throughput_limit(), Some(1_000_000));
+    assert_eq!(ApplyOptions::new().throughput_limit(), None);
+
+    let start = Instant::now();
+    let mut target = TarTarget::new(Vec::new());
+    let report = parser.apply_to(1, &mut target, &options).unwrap();
+    // 读取和写入各约 0.1 秒的欠账（突发额度为 0.1 秒）
+    assert!(start.elapsed() >= Duration::from_millis(150));
+    assert_eq!(report.total_bytes, 200_000);
+
+    let data = target.into_inner();
+    let mut archive = tar::Archive::new(data.as_slice());
+    let mut entry = archive.entries().unwrap().next().unwrap().unwrap();
+    let mut content = Vec::new();
+    std::io::Read::read_to_end(&mut entry, &mut content).unwrap();
+    assert_eq!(content, vec![0xa5; 200_000]);
+}
+
+/// 测试限速校验：所有线程合计的读取速率受限，结果不变
+#[test]
+fn test_verify_max_throughput() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut parser = open_wim(&dir);
+
+    let start = Instant::now();
+    let verification = parser
+        .verify_all_streams_with(
+            &StreamVerifyOptions::new()
+                .threads(4)
+                .max_throughput(1_000_000),
+        )
+        .unwrap();
+    assert!(start.elapsed() >= Duration::from_millis(80));
+    assert!(verification.is_ok());
+    assert!(verification.valid_count() >= 2);
+}
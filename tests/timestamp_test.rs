@@ -0,0 +1,71 @@
+#[cfg(feature = "std")]
+use std::time::{Duration, UNIX_EPOCH};
+
+use wim_parser::WimTimestamp;
+
+/// 2020-01-01 00:00:00 UTC
+const NEW_YEAR_2020: u64 = 132_223_104_000_000_000;
+
+/// 测试 FILETIME 与 Unix 时间的换算
+#[test]
+fn test_unix_conversions() {
+    let time = WimTimestamp::from_filetime(NEW_YEAR_2020 + 1234);
+    assert_eq!(time.unix_seconds(), 1_577_836_800);
+    assert_eq!(time.subsec_nanos(), 123_400);
+    assert_eq!(WimTimestamp::from_unix(1_577_836_800, 123_400), Some(time));
+    assert_eq!(
+        WimTimestamp::from_parts(0x01D5_C036, 0x6905_0000).filetime(),
+        NEW_YEAR_2020
+    );
+    assert_eq!(time.high_part(), 0x01D5_C036);
+
+    // FILETIME 纪元
+    let epoch = WimTimestamp::default();
+    assert!(epoch.is_zero());
+    assert_eq!(epoch.unix_seconds(), -11_644_473_600);
+    assert_eq!(WimTimestamp::from_unix(-11_644_473_600, 0), Some(epoch));
+    assert_eq!(WimTimestamp::from_unix(-11_644_473_601, 0), None);
+}
+
+/// 测试与 SystemTime 的换算
+#[cfg(feature = "std")]
+#[test]
+fn test_system_time() {
+    let time = WimTimestamp::from_filetime(NEW_YEAR_2020 + 5);
+    let system = time.to_system_time().unwrap();
+    assert_eq!(system, UNIX_EPOCH + Duration::new(1_577_836_800, 500));
+    assert_eq!(WimTimestamp::from_system_time(system), Some(time));
+
+    // 早于 Unix 纪元
+    let before = UNIX_EPOCH - Duration::new(10, 300);
+    let time = WimTimestamp::from_system_time(before).unwrap();
+    assert_eq!(time.unix_seconds(), -11);
+    assert_eq!(time.subsec_nanos(), 999_999_700);
+    assert_eq!(time.to_system_time(), Some(before));
+}
+
+/// 测试显示格式
+#[test]
+fn test_display() {
+    assert_eq!(
+        WimTimestamp::from_filetime(NEW_YEAR_2020).to_string(),
+        "2020-01-01 00:00:00 UTC"
+    );
+    let time = WimTimestamp::from_unix(951_827_696, 0).unwrap();
+    assert_eq!(time.to_string(), "2000-02-29 12:34:56 UTC");
+    assert_eq!(
+        WimTimestamp::default().to_string(),
+        "1601-01-01 00:00:00 UTC"
+    );
+}
+
+/// 测试与 chrono 的换算
+#[cfg(feature = "chrono")]
+#[test]
+fn test_chrono() {
+    let time = WimTimestamp::from_filetime(NEW_YEAR_2020 + 7);
+    let chrono = time.to_chrono().unwrap();
+    assert_eq!(chrono.timestamp(), 1_577_836_800);
+    assert_eq!(chrono.timestamp_subsec_nanos(), 700);
+    assert_eq!(WimTimestamp::from_chrono(chrono), Some(time));
+}
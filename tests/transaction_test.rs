@@ -0,0 +1,240 @@
+#![cfg(feature = "parser")]
+
+mod common;
+
+use common::{add_integrity_table, build_wim, write_bytes, write_wim, ImageSpec};
+use wim_parser::fmt::ToTable;
+use wim_parser::{IndexPolicy, RewriteStrategy, WimParser};
+
+fn images() -> Vec<ImageSpec> {
+    vec![
+        ImageSpec::new("Home")
+            .file("/shared.txt", b"shared")
+            .file("/home.txt", b"home only"),
+        ImageSpec::new("Pro")
+            .file("/shared.txt", b"shared")
+            .file("/pro.txt", b"pro only"),
+        ImageSpec::new("Enterprise").file("/ent.txt", b"enterprise"),
+    ]
+}
+
+/// 测试重命名和设置可引导镜像：只追加 XML、保留完整性表
+#[test]
+fn test_transaction_rename_and_bootable() {
+    let mut bytes = build_wim(&images());
+    add_integrity_table(&mut bytes, 64);
+    let wim = write_bytes(&bytes);
+    let mut parser = WimParser::new(wim.path()).unwrap();
+
+    let plan = parser
+        .transaction()
+        .rename_image(2, "Pro for Workstations")
+        .set_bootable(3)
+        .commit()
+        .unwrap();
+    assert_eq!(plan.strategy, RewriteStrategy::XmlAndHeader);
+    assert!(!plan.drops_integrity);
+
+    parser.parse_full().unwrap();
+    let names: Vec<&str> = parser
+        .get_images()
+        .iter()
+        .map(|i| i.name.as_str())
+        .collect();
+    assert_eq!(names, ["Home", "Pro for Workstations", "Enterprise"]);
+    let header = parser.read_header().unwrap().clone();
+    assert_eq!(header.bootable_image_index, 3);
+    assert!(!header.integrity_resource.is_absent());
+    #[cfg(feature = "verify")]
+    assert!(parser.repair_plan().unwrap().ranges.is_empty());
+
+    let plan = parser.transaction().set_bootable(0).plan().unwrap();
+    assert_eq!(plan.strategy, RewriteStrategy::HeaderOnly);
+    let plan = parser.transaction().set_bootable(3).plan().unwrap();
+    assert_eq!(plan.strategy, RewriteStrategy::Unchanged);
+}
+
+/// 测试重命名时转义 XML 特殊字符，丢弃 XML 中不允许的控制字符
+#[test]
+fn test_transaction_rename_escapes_xml() {
+    let wim = write_wim(&images());
+    let mut parser = WimParser::new(wim.path()).unwrap();
+
+    parser
+        .transaction()
+        .rename_image(1, "Home \"N\" <K&N> 'x'\u{1}")
+        .commit()
+        .unwrap();
+    parser.parse_full().unwrap();
+    assert_eq!(parser.get_images()[0].name, "Home \"N\" <K&N> 'x'");
+    assert_eq!(parser.get_images()[1].name, "Pro");
+}
+
+/// 测试删除镜像：重新编号、丢弃独占数据流、保留共享数据流
+#[test]
+fn test_transaction_delete_image() {
+    let wim = write_wim(&images());
+    let original_len = std::fs::metadata(wim.path()).unwrap().len();
+    let mut parser = WimParser::new(wim.path()).unwrap();
+
+    let plan = parser
+        .transaction()
+        .delete_image(1)
+        .rename_image(3, "Enterprise N")
+        .commit()
+        .unwrap();
+    assert_eq!(plan.strategy, RewriteStrategy::Rebuild);
+    assert_eq!(plan.image_count, 2);
+    assert_eq!(plan.freed_streams, 1);
+    assert_eq!(plan.freed_bytes, b"home only".len() as u64);
+    assert_eq!(plan.new_index(1), None);
+    assert_eq!(plan.new_index(3), Some(2));
+    assert!(std::fs::metadata(wim.path()).unwrap().len() < original_len);
+
+    parser.parse_full().unwrap();
+    let images: Vec<(u32, &str)> = parser
+        .get_images()
+        .iter()
+        .map(|i| (i.index, i.name.as_str()))
+        .collect();
+    assert_eq!(images, [(1, "Pro"), (2, "Enterprise N")]);
+
+    let manifest = parser.sha1_manifest(1).unwrap();
+    let comparison = parser.compare_sha1_manifest(1, &manifest).unwrap();
+    assert!(comparison.is_ok());
+    let out = tempfile::tempdir().unwrap();
+    let mut target = wim_parser::DirectoryTarget::new(out.path());
+    parser
+        .apply_to(1, &mut target, &wim_parser::ApplyOptions::new())
+        .unwrap();
+    assert_eq!(
+        std::fs::read(out.path().join("shared.txt")).unwrap(),
+        b"shared"
+    );
+    assert_eq!(
+        std::fs::read(out.path().join("pro.txt")).unwrap(),
+        b"pro only"
+    );
+
+    let plan = parser.plan_delete_image(1).unwrap();
+    assert!(plan.is_safe());
+}
+
+/// 测试提交前校验：任何一项无效时不修改文件
+#[test]
+fn test_transaction_validation() {
+    let wim = write_wim(&images());
+    let original = std::fs::read(wim.path()).unwrap();
+    let mut parser = WimParser::new(wim.path()).unwrap();
+
+    let cases = [
+        parser
+            .transaction()
+            .rename_image(1, "Renamed")
+            .delete_image(4)
+            .commit(),
+        parser
+            .transaction()
+            .delete_image(2)
+            .rename_image(2, "Pro N")
+            .commit(),
+        parser.transaction().rename_image(1, "Pro").commit(),
+        parser
+            .transaction()
+            .delete_image(1)
+            .delete_image(1)
+            .commit(),
+        parser
+            .transaction()
+            .delete_image(1)
+            .delete_image(2)
+            .delete_image(3)
+            .commit(),
+        parser
+            .transaction()
+            .set_bootable(2)
+            .delete_image(2)
+            .commit(),
+    ];
+    for result in cases {
+        assert!(result.is_err());
+    }
+    assert_eq!(std::fs::read(wim.path()).unwrap(), original);
+
+    let dir = wim.path().parent().unwrap();
+    let name = wim
+        .path()
+        .file_name()
+        .unwrap()
+        .to_string_lossy()
+        .into_owned();
+    let leftovers = std::fs::read_dir(dir)
+        .unwrap()
+        .filter_map(Result::ok)
+        .filter(|entry| {
+            entry
+                .file_name()
+                .to_string_lossy()
+                .contains(&format!(".{name}."))
+        })
+        .count();
+    assert_eq!(leftovers, 0);
+}
+
+/// 测试删除后的重新编号：新旧索引对应关系、XML 索引和可引导镜像一致
+#[test]
+fn test_transaction_renumber_mapping() {
+    let mut specs = images();
+    specs.push(ImageSpec::new("Education").file("/edu.txt", b"education"));
+    let wim = write_wim(&specs);
+    let mut parser = WimParser::new(wim.path()).unwrap();
+    parser.transaction().set_bootable(4).commit().unwrap();
+
+    let plan = parser.transaction().delete_image(2).plan().unwrap();
+    assert_eq!(plan.index_policy, IndexPolicy::Renumber);
+    assert_eq!(plan.index_map(), [(1, 1), (3, 2), (4, 3)]);
+    assert_eq!(plan.renumbered(), [(3, 2), (4, 3)]);
+    assert_eq!(plan.bootable_index, 3);
+    assert!(plan.table().rows().iter().any(|row| row[1] == "3→2, 4→3"));
+
+    parser.transaction().delete_image(2).commit().unwrap();
+    parser.parse_full().unwrap();
+    let images: Vec<(u32, &str)> = parser
+        .get_images()
+        .iter()
+        .map(|i| (i.index, i.name.as_str()))
+        .collect();
+    assert_eq!(images, [(1, "Home"), (2, "Enterprise"), (3, "Education")]);
+    let header = parser.read_header().unwrap().clone();
+    assert_eq!(header.bootable_image_index, 3);
+    assert!(!header.boot_metadata_resource.is_absent());
+}
+
+/// 测试保持索引不变：只允许删除末尾的镜像
+#[test]
+fn test_transaction_preserve_indexes() {
+    let wim = write_wim(&images());
+    let original = std::fs::read(wim.path()).unwrap();
+    let mut parser = WimParser::new(wim.path()).unwrap();
+
+    let err = parser
+        .transaction()
+        .index_policy(IndexPolicy::Preserve)
+        .delete_image(1)
+        .commit()
+        .unwrap_err();
+    assert!(err.to_string().contains("镜像 2, 3 的索引"));
+    assert_eq!(std::fs::read(wim.path()).unwrap(), original);
+
+    let plan = parser
+        .transaction()
+        .index_policy(IndexPolicy::Preserve)
+        .delete_image(3)
+        .commit()
+        .unwrap();
+    assert_eq!(plan.index_map(), [(1, 1), (2, 2)]);
+    assert!(plan.renumbered().is_empty());
+    parser.parse_full().unwrap();
+    assert_eq!(parser.get_image_count(), 2);
+    assert_eq!(parser.get_images()[1].name, "Pro");
+}
@@ -0,0 +1,78 @@
+#![cfg(feature = "verify")]
+
+mod common;
+
+use common::{build_wim, write_bytes, write_wim, ImageSpec};
+use sha2::{Digest as _, Sha256};
+use wim_parser::verify::{Digest, Manifest, VerifySubject};
+use wim_parser::WimParser;
+
+/// 测试整个文件和镜像元数据的清单校验
+#[test]
+fn test_verify_against_manifest() {
+    let specs = [
+        ImageSpec::new("Image A").file("/a.txt", b"hello"),
+        ImageSpec::new("Image B").file("/b.txt", b"world"),
+    ];
+    let bytes = build_wim(&specs);
+    let wim = write_wim(&specs);
+    let file_digest = Digest::Sha256(Sha256::digest(&bytes).into());
+
+    let mut parser = WimParser::new(wim.path()).unwrap();
+
+    // 先用错误的摘要得到实际值
+    let wrong = Digest::Sha1([0; 20]);
+    let manifest = Manifest::new().file(file_digest).image_metadata(2, wrong);
+    let report = parser.verify_against(&manifest).unwrap();
+    assert_eq!(report.checks.len(), 2);
+    assert_eq!(report.checks[0].subject, VerifySubject::File);
+    assert!(report.checks[0].is_match());
+    assert_eq!(report.checks[1].subject, VerifySubject::ImageMetadata(2));
+    assert!(!report.is_signed_off());
+    assert_eq!(report.mismatches().count(), 1);
+
+    let metadata_digest = report.checks[1].actual;
+    let manifest = Manifest::new()
+        .file(file_digest)
+        .image_metadata(2, metadata_digest);
+    let report = parser.verify_against(&manifest).unwrap();
+    assert!(report.is_signed_off());
+
+    // 校验后解析器仍可正常使用
+    parser.parse_full().unwrap();
+    assert_eq!(parser.get_images().len(), 2);
+}
+
+/// 测试嵌入容器文件中的 WIM 的文件摘要不含容器前后的数据
+#[test]
+fn test_verify_embedded_file_digest() {
+    let wim = build_wim(&[ImageSpec::new("Image A").file("/a.txt", b"hello")]);
+    let mut container = vec![0xAAu8; 1000];
+    container.extend_from_slice(&wim);
+    container.extend_from_slice(&[0x55u8; 512]);
+    let file = write_bytes(&container);
+
+    let mut parser = WimParser::new_at(file.path(), 1000).unwrap();
+    let digest = Digest::Sha256(Sha256::digest(&wim).into());
+    let report = parser
+        .verify_against(&Manifest::new().file(digest))
+        .unwrap();
+    assert!(report.is_signed_off(), "{:?}", report.checks);
+}
+
+/// 测试空清单和十六进制摘要解析
+#[test]
+fn test_empty_manifest_and_hex() {
+    let wim = write_wim(&[ImageSpec::new("Image A")]);
+    let mut parser = WimParser::new(wim.path()).unwrap();
+    let report = parser.verify_against(&Manifest::new()).unwrap();
+    assert!(report.checks.is_empty());
+    assert!(!report.is_signed_off());
+
+    let hex = "da39a3ee5e6b4b0d3255bfef95601890afd80709";
+    let digest = Digest::sha1_hex(hex).unwrap();
+    assert_eq!(digest.to_string(), hex);
+    assert_eq!(digest.algorithm(), "SHA-1");
+    assert!(Digest::sha256_hex(hex).is_none());
+    assert!(Digest::sha1_hex("zz39a3ee5e6b4b0d3255bfef95601890afd80709").is_none());
+}
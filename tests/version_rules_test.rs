@@ -0,0 +1,72 @@
+#![cfg(feature = "parser")]
+
+mod common;
+
+use std::sync::Arc;
+
+use common::{write_wim, ImageSpec};
+use wim_parser::{VersionRules, WimParser};
+
+/// 测试内置规则以及运行时添加的规则的优先级
+#[test]
+fn test_version_rules_detect() {
+    let rules = VersionRules::builtin();
+    assert_eq!(rules.detect("Windows 11 Pro", ""), Some("Windows 11"));
+    assert_eq!(
+        rules.detect("Windows Server 2025 Datacenter", ""),
+        Some("Windows Server")
+    );
+    assert_eq!(rules.detect("Contoso OS", ""), None);
+
+    let rules = VersionRules::builtin()
+        .prepend("Windows Server 2025", "Windows Server 2025")
+        .prepend("LTSC 2024", "Windows 11 LTSC 2024")
+        .push("contoso", "Contoso OS");
+    assert_eq!(
+        rules.detect("Windows Server 2025 Datacenter", ""),
+        Some("Windows Server 2025")
+    );
+    assert_eq!(
+        rules.detect("Windows 11 IoT Enterprise LTSC 2024", ""),
+        Some("Windows 11 LTSC 2024")
+    );
+    assert_eq!(rules.detect("Contoso OS", "OEM build"), Some("Contoso OS"));
+    assert_eq!(rules.detect("Windows 10 Pro", ""), Some("Windows 10"));
+    assert_eq!(rules.rules()[0].pattern, "ltsc 2024");
+
+    assert!(VersionRules::empty().detect("Windows 11 Pro", "").is_none());
+}
+
+/// 测试解析器使用自定义规则推断版本，解析后设置的规则立即生效
+#[test]
+fn test_parser_version_rules() {
+    let wim = write_wim(&[
+        ImageSpec::new("Windows Server 2025 Standard"),
+        ImageSpec::new("Windows 11 Pro"),
+    ]);
+    let rules = Arc::new(VersionRules::builtin().prepend("server 2025", "Windows Server 2025"));
+
+    let mut parser = WimParser::new(wim.path()).unwrap();
+    parser.set_version_rules(rules.clone());
+    parser.parse_full().unwrap();
+    assert_eq!(
+        parser.get_images()[0].version.as_deref(),
+        Some("Windows Server 2025")
+    );
+    assert_eq!(
+        parser.get_images()[1].version.as_deref(),
+        Some("Windows 11")
+    );
+
+    let mut parser = WimParser::new(wim.path()).unwrap();
+    parser.parse_full().unwrap();
+    assert_eq!(
+        parser.get_images()[0].version.as_deref(),
+        Some("Windows Server")
+    );
+    parser.set_version_rules(rules);
+    assert_eq!(
+        parser.get_images()[0].version.as_deref(),
+        Some("Windows Server 2025")
+    );
+}
@@ -0,0 +1,97 @@
+#![cfg(feature = "verify")]
+
+mod common;
+
+use common::sha1_hash;
+use wim_parser::{ApplyOptions, DirectoryTarget, VirtualWim, WimParser, WimTimestamp};
+
+/// 2021-01-01 00:00:00 UTC
+const WRITE_TIME: u64 = 132_539_328_000_000_000;
+
+/// 测试由虚拟 WIM 生成的文件可被解析器完整读取
+#[test]
+fn test_virtual_wim_round_trip() {
+    let mut wim = VirtualWim::new();
+    wim.add_image("Windows 11 Pro")
+        .set_time(WimTimestamp::from_filetime(WRITE_TIME))
+        .set_description("Pro & more")
+        .extra_xml("<WINDOWS><ARCH>9</ARCH></WINDOWS>")
+        .add_file("/Windows/System32/a.dll", b"hello")
+        .unwrap()
+        .add_file(r"Windows\notepad.exe", b"notepad")
+        .unwrap()
+        .add_file("/empty.txt", b"")
+        .unwrap()
+        .add_dir("/Users/Public")
+        .unwrap();
+    wim.add_image("Windows 11 Home")
+        .add_file("/Windows/System32/a.dll", b"hello")
+        .unwrap();
+    assert_eq!(wim.image_count(), 2);
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("virtual.wim");
+    let mut parser = wim.open(&path).unwrap();
+    parser.parse_full().unwrap();
+    // 除文件头中随机生成的 GUID 外，写入文件与内存序列化结果相同
+    let (file, bytes) = (std::fs::read(&path).unwrap(), wim.to_bytes().unwrap());
+    assert!(file[..24] == bytes[..24] && file[40..] == bytes[40..]);
+
+    let images = parser.get_images();
+    assert_eq!(images.len(), 2);
+    assert_eq!(images[0].name, "Windows 11 Pro");
+    assert_eq!(images[0].arch_raw, Some(9));
+    assert_eq!(
+        images[0].creation_time,
+        Some(WimTimestamp::from_filetime(WRITE_TIME))
+    );
+    assert_eq!(images[1].name, "Windows 11 Home");
+
+    let recount = parser.recount_image(1, false).unwrap();
+    assert!(recount.is_consistent());
+    assert_eq!(recount.actual.dir_count, 5);
+    assert_eq!(recount.actual.file_count, 3);
+    assert_eq!(recount.actual.total_bytes, 12);
+
+    // 共享的数据流只存储一次
+    assert_eq!(parser.read_stream(&sha1_hash(b"hello")).unwrap(), b"hello");
+    let layout = parser.plan_stream_layout(2).unwrap();
+    assert_eq!(layout.streams.len(), 1);
+
+    let out = tempfile::tempdir().unwrap();
+    let mut target = DirectoryTarget::new(out.path());
+    let report = parser
+        .apply_to(1, &mut target, &ApplyOptions::new())
+        .unwrap();
+    assert_eq!(report.file_count, 3);
+    assert_eq!(
+        std::fs::read(out.path().join("Windows/notepad.exe")).unwrap(),
+        b"notepad"
+    );
+    assert!(out.path().join("Users/Public").is_dir());
+}
+
+/// 测试虚拟镜像的路径冲突和文件替换
+#[test]
+fn test_virtual_wim_paths() {
+    let mut wim = VirtualWim::new();
+    wim.add_file("/a/b.txt", b"first").unwrap();
+    wim.add_file("/A/B.TXT", b"second").unwrap();
+    assert!(wim.add_file("/a/b.txt/c", b"x").is_err());
+    assert!(wim.add_file("/a", b"x").is_err());
+    assert!(wim.add_file("/", b"x").is_err());
+    assert!(wim.image_mut(1).unwrap().add_dir("/a/b.txt").is_err());
+    assert_eq!(wim.image_mut(1).unwrap().name(), "Image 1");
+
+    let bytes = wim.to_bytes().unwrap();
+    let header = wim_parser::format::parse_header(&bytes).unwrap();
+    assert_eq!(header.image_count, 1);
+
+    let dir = tempfile::tempdir().unwrap();
+    let mut parser: WimParser = wim.open(dir.path().join("paths.wim")).unwrap();
+    let manifest = parser.sha1_manifest(1).unwrap();
+    assert_eq!(manifest.entries().len(), 1);
+    assert_eq!(manifest.entries()[0].path, "a/b.txt");
+    assert_eq!(manifest.entries()[0].hash, sha1_hash(b"second"));
+    assert!(parser.read_stream(&sha1_hash(b"first")).is_err());
+}
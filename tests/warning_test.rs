@@ -0,0 +1,132 @@
+#![cfg(feature = "parser")]
+
+mod common;
+
+use common::{build_wim, write_bytes, write_wim, ImageSpec};
+use wim_parser::{FileFlags, HeaderFlags, ParseOptions, ResourceKind, Warning, WimParser};
+
+fn utf16le(s: &str) -> Vec<u8> {
+    s.encode_utf16().flat_map(|u| u.to_le_bytes()).collect()
+}
+
+/// 将 UTF-16 XML 中的第一处 `from` 替换为等长的 `to`
+fn patch_xml(bytes: &mut [u8], from: &str, to: &str) {
+    let (from, to) = (utf16le(from), utf16le(to));
+    assert_eq!(from.len(), to.len());
+    let position = bytes.windows(from.len()).position(|w| w == from).unwrap();
+    bytes[position..position + to.len()].copy_from_slice(&to);
+}
+
+/// 测试正常文件没有警告
+#[test]
+fn test_no_warnings() {
+    let wim = write_wim(&[ImageSpec::new("Image A"), ImageSpec::new("Image B")]);
+    let mut parser = WimParser::new(wim.path()).unwrap();
+    parser.parse_full().unwrap();
+    assert!(parser.warnings().is_empty());
+}
+
+/// 测试未知架构、未闭合节点和镜像数量不一致的警告
+#[test]
+fn test_xml_warnings() {
+    let specs = [
+        ImageSpec::new("Image A").extra_xml("<WINDOWS><ARCH>99</ARCH></WINDOWS>"),
+        ImageSpec::new("Image B"),
+    ];
+    let mut bytes = build_wim(&specs);
+    patch_xml(&mut bytes, "</IMAGE></WIM>", "</IMAGX></WIM>");
+    let wim = write_bytes(&bytes);
+
+    let mut parser = WimParser::new(wim.path()).unwrap();
+    parser.parse_full().unwrap();
+    assert_eq!(parser.get_images().len(), 1);
+    assert_eq!(
+        parser.warnings(),
+        [
+            Warning::UnclosedImage { count: 1 },
+            Warning::UnknownArch {
+                index: 1,
+                value: 99
+            },
+            Warning::ImageCountMismatch { header: 2, xml: 1 },
+        ]
+    );
+
+    // 重复解析不会重复记录
+    parser.read_xml_data().unwrap();
+    assert_eq!(parser.warnings().len(), 3);
+    assert_eq!(
+        parser.warnings()[2].to_string(),
+        "文件头镜像数 (2) 与 XML 镜像数 (1) 不一致"
+    );
+}
+
+/// 测试无效的 INDEX 属性和资源标志位警告
+#[test]
+fn test_index_and_flag_warnings() {
+    let mut bytes = build_wim(&[ImageSpec::new("Image A")]);
+    patch_xml(&mut bytes, "INDEX=\"1\"", "INDEX=\"x\"");
+    bytes[72 + 7] |= 0x40;
+    let wim = write_bytes(&bytes);
+
+    let mut parser = WimParser::new(wim.path()).unwrap();
+    parser.parse_full().unwrap();
+    assert_eq!(parser.get_images()[0].index, 0);
+    assert_eq!(
+        parser.warnings(),
+        [
+            Warning::UnknownResourceFlags {
+                kind: ResourceKind::XmlData,
+                bits: 0x40
+            },
+            Warning::InvalidImageIndex { position: 1 },
+        ]
+    );
+}
+
+/// 测试文件头中未定义的标志位和 WRITE_IN_PROGRESS 警告
+#[test]
+fn test_header_flag_warnings() {
+    let mut bytes = build_wim(&[ImageSpec::new("Image A")]);
+    let flags = FileFlags::RP_FIX | FileFlags::WRITE_IN_PROGRESS | 0x0100_0000;
+    bytes[16..20].copy_from_slice(&flags.to_le_bytes());
+    let wim = write_bytes(&bytes);
+
+    let mut parser = WimParser::new(wim.path()).unwrap();
+    parser.parse_full().unwrap();
+    let header = parser.get_header().unwrap();
+    assert_eq!(header.file_flags, flags);
+    assert_eq!(header.flags().unknown_bits(), 0x0100_0000);
+    assert!(header.flags().contains(FileFlags::RP_FIX));
+    assert_eq!(
+        header.flags().to_string(),
+        "WRITE_IN_PROGRESS|RP_FIX|0x01000000"
+    );
+    assert_eq!(
+        parser.warnings(),
+        [
+            Warning::UnknownHeaderFlags { bits: 0x0100_0000 },
+            Warning::WriteInProgress,
+        ]
+    );
+    assert_eq!(HeaderFlags(FileFlags::RP_FIX).unknown_bits(), 0);
+    assert_eq!(HeaderFlags(0).to_string(), "NONE");
+}
+
+/// 测试严格模式将警告提升为错误
+#[test]
+fn test_strict_mode() {
+    let wim =
+        write_wim(&[ImageSpec::new("Image A").extra_xml("<WINDOWS><ARCH>99</ARCH></WINDOWS>")]);
+
+    let mut parser = WimParser::with_options(wim.path(), ParseOptions::new().strict(true)).unwrap();
+    let err = parser.parse_full().unwrap_err();
+    assert!(err.to_string().contains("严格模式"));
+    assert!(err.to_string().contains("架构数值 99"));
+
+    // 严格模式下正常文件可以解析
+    let wim = write_wim(&[ImageSpec::new("Image A")]);
+    let mut parser = WimParser::with_options(wim.path(), ParseOptions::new().strict(true)).unwrap();
+    parser.parse_full().unwrap();
+    assert!(parser.warnings().is_empty());
+}
@@ -0,0 +1,83 @@
+#![cfg(feature = "parser")]
+
+mod common;
+
+use common::{build_wim, sha1_hash, write_bytes, ImageSpec};
+use wim_parser::{FileFlags, WimKind, WimParser};
+
+/// 改写文件头标志，`drop_xml` 时去掉 XML 数据资源
+fn patch(mut bytes: Vec<u8>, flags: u32, drop_xml: bool) -> Vec<u8> {
+    bytes[16..20].copy_from_slice(&flags.to_le_bytes());
+    if drop_xml {
+        bytes[72..96].fill(0);
+    }
+    bytes
+}
+
+/// 测试按文件头标志和镜像数量区分 WIM 种类
+#[test]
+fn test_wim_kind_detection() {
+    let cases = [
+        (build_wim(&[ImageSpec::new("Pro")]), WimKind::Standard),
+        (build_wim(&[]), WimKind::Empty),
+        (
+            patch(build_wim(&[]), FileFlags::RESOURCE_ONLY, false),
+            WimKind::ResourceOnly,
+        ),
+        (
+            patch(
+                build_wim(&[ImageSpec::new("Pro")]),
+                FileFlags::METADATA_ONLY,
+                false,
+            ),
+            WimKind::MetadataOnly,
+        ),
+    ];
+    for (bytes, kind) in cases {
+        let wim = write_bytes(&bytes);
+        let mut parser = WimParser::new(wim.path()).unwrap();
+        assert_eq!(parser.wim_kind().unwrap(), kind);
+        assert_eq!(parser.read_header().unwrap().kind(), kind);
+    }
+    assert_eq!(WimKind::ResourceOnly.to_string(), "resource-only");
+    assert!(!WimKind::Empty.has_images());
+    assert!(WimKind::MetadataOnly.has_images());
+}
+
+/// 测试没有 XML 数据资源的仅资源 WIM 和空 WIM：元数据接口返回空结果
+#[test]
+fn test_resource_only_without_xml() {
+    let data = build_wim(&[ImageSpec::new("Data").file("a.txt", b"payload")]);
+    // 去掉镜像计数，模拟服务操作使用的纯数据 WIM
+    let mut bytes = patch(data, FileFlags::RESOURCE_ONLY, true);
+    bytes[44..48].fill(0);
+    for bytes in [bytes.clone(), patch(build_wim(&[]), 0, true)] {
+        let wim = write_bytes(&bytes);
+        let mut parser = WimParser::new(wim.path()).unwrap();
+        parser.parse_full().unwrap();
+        assert!(parser.get_images().is_empty());
+        assert!(parser.get_windows_info().is_none());
+        parser.load_windows_metadata().unwrap();
+        assert!(parser.warnings().is_empty());
+    }
+
+    // 数据流仍可按 SHA-1 读取
+    let wim = write_bytes(&bytes);
+    let mut parser = WimParser::new(wim.path()).unwrap();
+    assert_eq!(parser.wim_kind().unwrap(), WimKind::ResourceOnly);
+    assert_eq!(
+        parser.read_stream(&sha1_hash(b"payload")).unwrap(),
+        b"payload"
+    );
+}
+
+/// 测试有镜像的 WIM 缺少 XML 数据资源仍然是错误
+#[test]
+fn test_standard_without_xml_is_error() {
+    let bytes = patch(build_wim(&[ImageSpec::new("Pro")]), 0, true);
+    let wim = write_bytes(&bytes);
+    let mut parser = WimParser::new(wim.path()).unwrap();
+    assert_eq!(parser.wim_kind().unwrap(), WimKind::Standard);
+    let err = parser.parse_full().unwrap_err();
+    assert!(err.to_string().contains("XML"));
+}
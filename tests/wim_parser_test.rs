@@ -1,3 +1,5 @@
+#![cfg(feature = "parser")]
+
 use std::fs::File;
 use wim_parser::WimParser;
 
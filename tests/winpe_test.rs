@@ -0,0 +1,80 @@
+#![cfg(feature = "parser")]
+
+mod common;
+
+use common::{write_wim, ImageSpec};
+use wim_parser::{format, WimParser};
+
+/// 构造只包含 WinPECacheThreshold 值的最小配置单元片段
+fn fake_system_hive(scratch_mb: u32) -> Vec<u8> {
+    let name = b"WinPECacheThreshold";
+    let mut hive = b"regf".to_vec();
+    hive.resize(64, 0);
+    hive.extend(b"vk");
+    hive.extend((name.len() as u16).to_le_bytes());
+    hive.extend(0x8000_0004u32.to_le_bytes());
+    hive.extend(scratch_mb.to_le_bytes());
+    hive.extend(4u32.to_le_bytes());
+    hive.extend(1u16.to_le_bytes());
+    hive.extend(0u16.to_le_bytes());
+    hive.extend(name);
+    hive
+}
+
+/// 测试通过 FLAGS 和安装类型识别 WinPE
+#[test]
+fn test_is_windows_pe() {
+    let image = format::parse_single_image_xml(
+        "<IMAGE INDEX=\"1\"><NAME>Microsoft Windows PE (x64)</NAME>\
+         <WINDOWS><PRODUCTTYPE>WinNT</PRODUCTTYPE>\
+         <INSTALLATIONTYPE>WindowsPE</INSTALLATIONTYPE></WINDOWS></IMAGE>",
+    );
+    assert!(image.is_windows_pe());
+    assert_eq!(image.product_type.as_deref(), Some("WinNT"));
+
+    let image = format::parse_single_image_xml(
+        "<IMAGE INDEX=\"2\"><FLAGS>WindowsPE</FLAGS><NAME>Setup</NAME></IMAGE>",
+    );
+    assert!(image.is_windows_pe());
+    assert_eq!(image.flags.as_deref(), Some("WindowsPE"));
+
+    let image = format::parse_single_image_xml(
+        "<IMAGE INDEX=\"3\"><FLAGS>Professional</FLAGS>\
+         <WINDOWS><INSTALLATIONTYPE>Client</INSTALLATIONTYPE></WINDOWS></IMAGE>",
+    );
+    assert!(!image.is_windows_pe());
+}
+
+/// 测试 WinPE 专有信息
+#[test]
+fn test_winpe_info() {
+    let wim = write_wim(&[
+        ImageSpec::new("Microsoft Windows PE (x64)")
+            .extra_xml("<FLAGS>WindowsPE</FLAGS>")
+            .file("/Windows/System32/startnet.cmd", b"wpeinit")
+            .file("/Windows/System32/config/SYSTEM", &fake_system_hive(512)),
+        ImageSpec::new("Microsoft Windows Setup (x64)")
+            .extra_xml("<FLAGS>WindowsPE</FLAGS>")
+            .file("/Windows/System32/winpeshl.ini", b"[LaunchApps]")
+            .file("/sources/setup.exe", b"MZ"),
+        ImageSpec::new("Windows 11 Pro").extra_xml("<FLAGS>Professional</FLAGS>"),
+    ]);
+
+    let mut parser = WimParser::new(wim.path()).unwrap();
+    parser.parse_full().unwrap();
+    assert_eq!(parser.windows_pe_images(), [1, 2]);
+
+    let pe = parser.winpe_info(1).unwrap().unwrap();
+    assert!(pe.has_startnet_cmd);
+    assert!(!pe.has_winpeshl_ini);
+    assert!(!pe.has_setup);
+    assert_eq!(pe.scratch_space_mb, Some(512));
+
+    let setup = parser.winpe_info(2).unwrap().unwrap();
+    assert!(setup.has_winpeshl_ini);
+    assert!(setup.has_setup);
+    assert_eq!(setup.scratch_space_mb, None);
+
+    assert!(parser.winpe_info(3).unwrap().is_none());
+    assert!(parser.winpe_info(4).is_err());
+}
@@ -0,0 +1,56 @@
+#![cfg(feature = "parser")]
+
+mod common;
+
+use common::{write_wim, ImageSpec};
+use wim_parser::{WimParser, XmlElement};
+
+/// 测试按路径读取镜像 XML 中未建模的字段
+#[test]
+fn test_get_xml_field() {
+    let windows = "<WINDOWS><ARCH>9</ARCH><VERSION><MAJOR>10</MAJOR><BUILD>26100</BUILD>\
+                   <SPBUILD>1742</SPBUILD><SPLEVEL>0</SPLEVEL></VERSION>\
+                   <LANGUAGES><LANGUAGE>en-US</LANGUAGE><LANGUAGE>zh-CN</LANGUAGE></LANGUAGES>\
+                   <SYSTEMROOT>WINDOWS</SYSTEMROOT><HAL>acpiapic</HAL></WINDOWS>\
+                   <WIMBOOT>0</WIMBOOT>";
+    let wim = write_wim(&[ImageSpec::new("Windows 11 Pro").extra_xml(windows)]);
+    let mut parser = WimParser::new(wim.path()).unwrap();
+    parser.parse_full().unwrap();
+    let image = &parser.get_images()[0];
+
+    assert_eq!(image.get_xml_field("WINDOWS/VERSION/BUILD"), Some("26100"));
+    assert_eq!(image.get_xml_field("WINDOWS/VERSION/SPLEVEL"), Some("0"));
+    assert_eq!(image.get_xml_field("WINDOWS/SYSTEMROOT"), Some("WINDOWS"));
+    assert_eq!(image.get_xml_field("WIMBOOT"), Some("0"));
+    assert_eq!(
+        image.get_xml_field("WINDOWS/LANGUAGES/LANGUAGE[2]"),
+        Some("zh-CN")
+    );
+    assert_eq!(image.get_xml_field("@INDEX"), Some("1"));
+    assert_eq!(image.get_xml_field("WINDOWS/EDITIONID"), None);
+    assert_eq!(image.get_xml_field("WINDOWS/LANGUAGES/LANGUAGE[3]"), None);
+    assert_eq!(image.get_xml_field("WINDOWS/LANGUAGES/LANGUAGE[0]"), None);
+}
+
+/// 测试轻量元素树的实体、属性、CDATA 和格式错误处理
+#[test]
+fn test_xml_element_parse() {
+    let xml = r#"<?xml version="1.0"?><!-- comment --><IMAGE INDEX='3' NAME="a &amp; b">
+        <DESCRIPTION>Pro &lt;N&gt; &#x4E2D;&#25991;</DESCRIPTION>
+        <EMPTY/>
+        <RAW><![CDATA[<not a tag>]]></RAW>
+    </IMAGE>"#;
+    let element = XmlElement::parse(xml).unwrap();
+    assert_eq!(element.name, "IMAGE");
+    assert_eq!(element.attribute("INDEX"), Some("3"));
+    assert_eq!(element.get_text("@NAME"), Some("a & b"));
+    assert_eq!(element.get_text("DESCRIPTION"), Some("Pro <N> 中文"));
+    assert_eq!(element.get_text("EMPTY"), Some(""));
+    assert_eq!(element.get_text("RAW"), Some("<not a tag>"));
+    assert_eq!(element.children.len(), 3);
+    assert_eq!(element.child("RAW").unwrap().name, "RAW");
+
+    assert_eq!(XmlElement::parse("<A><B></A>"), None);
+    assert_eq!(XmlElement::parse("<A>unterminated"), None);
+    assert_eq!(XmlElement::parse(&"<A>".repeat(100)), None);
+}
@@ -0,0 +1,123 @@
+#![cfg(feature = "parser")]
+
+mod common;
+
+use common::{build_wim, write_bytes, ImageSpec};
+use wim_parser::{format, Arch, WimParser, WimTimestamp};
+
+/// OEM 恢复介质的写法：命名空间前缀、属性在 INDEX 之前、单引号、空字段用自闭合标签
+const OEM_XML: &str = r#"<?xml version="1.0" encoding="utf-16"?>
+<wim:WIM xmlns:wim="http://schemas.oem.example/recovery/wim">
+  <!-- <IMAGE INDEX="9"> 被注释掉的旧镜像 -->
+  <wim:TOTALBYTES>4096</wim:TOTALBYTES>
+  <wim:IMAGE wim:NAME="Factory" wim:INDEX="1">
+    <wim:DIRCOUNT>12</wim:DIRCOUNT>
+    <wim:FILECOUNT>34</wim:FILECOUNT>
+    <wim:TOTALBYTES>56789</wim:TOTALBYTES>
+    <wim:CREATIONTIME><wim:HIGHPART>0x01D9F1A2</wim:HIGHPART><wim:LOWPART>0x3B2C1000</wim:LOWPART></wim:CREATIONTIME>
+    <wim:WINDOWS>
+      <wim:ARCH>9</wim:ARCH>
+      <wim:EDITIONID>Core</wim:EDITIONID>
+      <wim:LANGUAGES><wim:LANGUAGE>en-US</wim:LANGUAGE><wim:LANGUAGE>de-DE</wim:LANGUAGE><wim:DEFAULT>en-US</wim:DEFAULT></wim:LANGUAGES>
+      <wim:VERSION><wim:BUILD>22631</wim:BUILD><wim:SPBUILD>2861</wim:SPBUILD></wim:VERSION>
+    </wim:WINDOWS>
+    <wim:NAME>Windows 11 Home</wim:NAME>
+    <wim:DISPLAYNAME>Windows 11 Home</wim:DISPLAYNAME>
+    <wim:DISPLAYDESCRIPTION/>
+    <wim:FLAGS/>
+  </wim:IMAGE>
+  <IMAGE NAME='Recovery &amp; Tools' OEM:VENDOR="Contoso" INDEX = '2' >
+    <WINDOWS><ARCH>12</ARCH><PRODUCTTYPE/></WINDOWS>
+    <NAME>WinRE</NAME>
+    <DISPLAYNAME>Windows Recovery Environment</DISPLAYNAME>
+  </IMAGE>
+  <IMAGE INDEX="3" NAME="Placeholder"/>
+</wim:WIM>"#;
+
+/// 把 WIM 的 XML 资源替换为 `xml`（XML 资源位于文件末尾）
+fn with_xml(mut bytes: Vec<u8>, xml: &str) -> Vec<u8> {
+    let offset = u64::from_le_bytes(bytes[80..88].try_into().unwrap());
+    bytes.truncate(offset as usize);
+    let mut resource = vec![0xFF, 0xFE];
+    resource.extend(xml.encode_utf16().flat_map(u16::to_le_bytes));
+    let size = resource.len() as u64;
+    bytes.extend(resource);
+    bytes[72..79].copy_from_slice(&size.to_le_bytes()[..7]);
+    bytes[88..96].copy_from_slice(&size.to_le_bytes());
+    bytes
+}
+
+/// 测试带命名空间前缀、任意属性顺序和自闭合标签的镜像 XML
+#[test]
+fn test_namespaced_image_xml() {
+    let images = format::parse_images_from_xml(OEM_XML);
+    let indexes: Vec<u32> = images.iter().map(|image| image.index).collect();
+    assert_eq!(indexes, [1, 2, 3]);
+
+    let home = &images[0];
+    assert_eq!(home.name, "Windows 11 Home");
+    assert_eq!(home.description, "Unknown");
+    assert_eq!(home.flags, None);
+    assert_eq!((home.dir_count, home.file_count), (12, 34));
+    assert_eq!(home.total_bytes, 56789);
+    assert_eq!(home.arch(), Some(Arch::X64));
+    assert_eq!(home.edition_id.as_deref(), Some("Core"));
+    assert_eq!((home.build, home.sp_build), (Some(22631), Some(2861)));
+    assert_eq!(home.languages, ["en-US", "de-DE"]);
+    assert_eq!(home.default_language.as_deref(), Some("en-US"));
+    assert_eq!(
+        home.creation_time,
+        Some(WimTimestamp::from_parts(0x01D9_F1A2, 0x3B2C_1000))
+    );
+    assert_eq!(home.get_xml_field("WINDOWS/VERSION/BUILD"), Some("22631"));
+    assert_eq!(home.get_xml_field("@NAME"), Some("Factory"));
+
+    let recovery = &images[1];
+    assert_eq!(recovery.name, "Windows Recovery Environment");
+    assert_eq!(recovery.english_name.as_deref(), Some("WinRE"));
+    assert_eq!(recovery.arch(), Some(Arch::Arm64));
+    assert_eq!(recovery.product_type, None);
+    assert_eq!(recovery.get_xml_field("@NAME"), Some("Recovery & Tools"));
+    assert_eq!(recovery.get_xml_field("@VENDOR"), Some("Contoso"));
+
+    assert_eq!(images[2].name, "Image 3");
+    assert!(images[2].xml.is_some());
+
+    let node = format::extract_image_xml(OEM_XML, 2).unwrap();
+    assert!(node.starts_with("<IMAGE NAME='Recovery") && node.ends_with("</IMAGE>"));
+    assert_eq!(
+        format::extract_image_xml(OEM_XML, 3),
+        Some(r#"<IMAGE INDEX="3" NAME="Placeholder"/>"#)
+    );
+    assert!(format::extract_image_xml(OEM_XML, 9).is_none());
+}
+
+/// 测试从文件读取 OEM 写法的 XML，以及删除镜像后重新编号
+#[test]
+fn test_namespaced_xml_in_wim() {
+    let specs = [
+        ImageSpec::new("Home"),
+        ImageSpec::new("WinRE"),
+        ImageSpec::new("Placeholder"),
+    ];
+    let wim = write_bytes(&with_xml(build_wim(&specs), OEM_XML));
+    let mut parser = WimParser::new(wim.path()).unwrap();
+    parser.parse_full().unwrap();
+    assert!(parser.warnings().is_empty(), "{:?}", parser.warnings());
+    assert_eq!(parser.get_images().len(), 3);
+    assert_eq!(
+        parser.get_image(2).unwrap().english_name.as_deref(),
+        Some("WinRE")
+    );
+
+    parser.transaction().delete_image(1).commit().unwrap();
+    parser.parse_full().unwrap();
+    let images = parser.get_images();
+    assert_eq!(images.len(), 2);
+    assert_eq!(images[0].index, 1);
+    assert_eq!(images[0].english_name.as_deref(), Some("WinRE"));
+    // 单引号和其余属性保持不变
+    assert_eq!(images[0].get_xml_field("@VENDOR"), Some("Contoso"));
+    assert_eq!(images[1].index, 2);
+    assert_eq!(images[1].get_xml_field("@NAME"), Some("Placeholder"));
+}
@@ -0,0 +1,41 @@
+#![cfg(feature = "parser")]
+
+mod common;
+
+use common::{build_wim, write_bytes, ImageSpec};
+use wim_parser::{FileFlags, ResourceFlags, WimParser};
+
+/// 文件头中 XML 数据资源条目的偏移
+const XML_RESHDR: usize = 72;
+
+/// 测试压缩的 XML 资源先按压缩格式处理，而不是直接做 BOM 检查
+#[test]
+fn test_compressed_xml_resource() {
+    let mut bytes = build_wim(&[ImageSpec::new("Image A")]);
+    let flags = FileFlags::COMPRESSION | FileFlags::COMPRESS_LZX;
+    bytes[16..20].copy_from_slice(&flags.to_le_bytes());
+    bytes[XML_RESHDR + 7] |= ResourceFlags::COMPRESSED;
+    // 原始大小大于压缩后大小，分块需要解压（而不是按原样存储）
+    bytes[XML_RESHDR + 16] += 1;
+    let wim = write_bytes(&bytes);
+
+    let mut parser = WimParser::new(wim.path()).unwrap();
+    let message = format!("{:#}", parser.parse_full().unwrap_err());
+    assert!(message.contains("压缩资源"), "{message}");
+    assert!(message.contains("LZX"), "{message}");
+    assert!(!message.contains("BOM"), "{message}");
+}
+
+/// 测试 XML 数据长度与资源原始大小不一致时报错
+#[test]
+fn test_xml_length_mismatch() {
+    let mut bytes = build_wim(&[ImageSpec::new("Image A")]);
+    let range = XML_RESHDR + 16..XML_RESHDR + 24;
+    let original_size = u64::from_le_bytes(bytes[range.clone()].try_into().unwrap());
+    bytes[range].copy_from_slice(&(original_size + 2).to_le_bytes());
+    let wim = write_bytes(&bytes);
+
+    let mut parser = WimParser::new(wim.path()).unwrap();
+    let message = parser.parse_full().unwrap_err().to_string();
+    assert!(message.contains("原始大小"), "{message}");
+}
@@ -0,0 +1,141 @@
+#![cfg(feature = "parser")]
+
+mod common;
+
+use common::{
+    build_wim, chunked_stream, sha1_hash, write_bytes, xpress_compress, ImageSpec, XPRESS,
+};
+use std::io::Read;
+use wim_parser::error::{codes, error_code};
+use wim_parser::{ApplyOptions, Compression, DirectoryTarget, WimParser};
+
+const CHUNK: u32 = 32768;
+
+/// 伪随机（不可压缩）字节
+fn noise(len: usize, mut state: u32) -> impl Iterator<Item = u8> {
+    (0..len).map(move |_| {
+        state ^= state << 13;
+        state ^= state >> 17;
+        state ^= state << 5;
+        state as u8
+    })
+}
+
+/// 文本、长重复段和不可压缩的字节混合的测试数据
+fn sample_data() -> Vec<u8> {
+    let mut data = Vec::new();
+    for i in 0..400 {
+        data.extend(format!("line {i}: the quick brown fox jumps over the lazy dog\r\n").bytes());
+    }
+    data.extend(std::iter::repeat_n(b'A', 5000));
+    data.extend(noise(20000, 0x1234_5678));
+    data.extend((0..30000u32).map(|i| (i % 251) as u8));
+    data
+}
+
+/// 压缩数据流并改写为分块资源；不可压缩的分块按原样存储
+fn xpress_wim(data: &[u8]) -> (Vec<u8>, Vec<Vec<u8>>) {
+    let compressed: Vec<Vec<u8>> = data
+        .chunks(CHUNK as usize)
+        .map(|chunk| {
+            let packed = xpress_compress(chunk);
+            if packed.len() < chunk.len() {
+                packed
+            } else {
+                chunk.to_vec()
+            }
+        })
+        .collect();
+    let chunks: Vec<&[u8]> = compressed.iter().map(Vec::as_slice).collect();
+    let bytes = build_wim(&[ImageSpec::new("Test").file("data.bin", data)]);
+    let bytes = chunked_stream(
+        bytes,
+        XPRESS,
+        CHUNK,
+        sha1_hash(data),
+        data.len() as u64,
+        &chunks,
+    );
+    (bytes, compressed)
+}
+
+/// 测试读取 XPRESS 压缩的数据流
+#[test]
+fn test_read_xpress_stream() {
+    let data = sample_data();
+    let hash = sha1_hash(&data);
+    let (bytes, compressed) = xpress_wim(&data);
+    assert!(compressed
+        .iter()
+        .zip(data.chunks(CHUNK as usize))
+        .all(|(packed, chunk)| packed.len() < chunk.len()));
+    let wim = write_bytes(&bytes);
+    let mut parser = WimParser::new(wim.path()).unwrap();
+
+    let resource = parser.stream_resource(&hash).unwrap().unwrap();
+    assert!(resource.size < resource.original_size);
+    let mut reader = parser.open_resource(&resource).unwrap();
+    assert_eq!(reader.compression(), Compression::Xpress { chunk: CHUNK });
+    assert_eq!(reader.chunk_count(), 3);
+    let mut read = Vec::new();
+    reader.read_to_end(&mut read).unwrap();
+    assert_eq!(read, data);
+
+    assert_eq!(parser.read_stream(&hash).unwrap(), data);
+    let dir = tempfile::tempdir().unwrap();
+    let mut target = DirectoryTarget::new(dir.path());
+    parser
+        .apply_to(1, &mut target, &ApplyOptions::new())
+        .unwrap();
+    assert_eq!(std::fs::read(dir.path().join("data.bin")).unwrap(), data);
+}
+
+/// 测试压缩分块和按原样存储的分块混合
+#[test]
+fn test_read_mixed_xpress_chunks() {
+    let mut data = sample_data();
+    data.truncate(CHUNK as usize * 2);
+    data.extend(noise(10000, 0x9E37_79B9));
+    let hash = sha1_hash(&data);
+    let (bytes, compressed) = xpress_wim(&data);
+    assert_eq!(compressed[2].len(), 10000);
+    let wim = write_bytes(&bytes);
+    let mut parser = WimParser::new(wim.path()).unwrap();
+    assert_eq!(parser.read_stream(&hash).unwrap(), data);
+}
+
+/// 测试损坏的 XPRESS 分块：匹配偏移超出已解压的数据、压缩数据被截断
+#[test]
+fn test_corrupt_xpress_chunk() {
+    let data = b"abcabcabcabcabcabcabcabcabcabcabcabc".repeat(20);
+    let hash = sha1_hash(&data);
+    let bytes = build_wim(&[ImageSpec::new("Test").file("data.bin", &data)]);
+
+    // 第一个符号就是偏移为 1 的匹配
+    let mut packed = vec![0x99u8; 256];
+    packed.extend([0x00, 0x80, 0, 0]);
+    let wim = write_bytes(&chunked_stream(
+        bytes.clone(),
+        XPRESS,
+        CHUNK,
+        hash,
+        data.len() as u64,
+        &[&packed],
+    ));
+    let mut parser = WimParser::new(wim.path()).unwrap();
+    let err = parser.read_stream(&hash).unwrap_err();
+    assert_eq!(error_code(&err), codes::FORMAT_CORRUPT_DATA);
+
+    let packed = xpress_compress(&data);
+    let wim = write_bytes(&chunked_stream(
+        bytes,
+        XPRESS,
+        CHUNK,
+        hash,
+        data.len() as u64,
+        &[&packed[..200]],
+    ));
+    let mut parser = WimParser::new(wim.path()).unwrap();
+    let err = parser.read_stream(&hash).unwrap_err();
+    assert_eq!(error_code(&err), codes::FORMAT_TRUNCATED);
+}